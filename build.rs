@@ -0,0 +1,9 @@
+fn main() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available");
+    // SAFETY: build scripts are single-threaded; prost-build reads PROTOC below.
+    unsafe { std::env::set_var("PROTOC", protoc) };
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/galynx.proto"], &["proto"])
+        .expect("failed to compile proto/galynx.proto");
+}
@@ -0,0 +1,507 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    channels::ChannelService,
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    notifications,
+    storage::{ChannelAccessRequestRecordStore, Storage},
+};
+
+const NOTE_MAX_CHARS: usize = 500;
+
+#[derive(Clone)]
+pub struct AccessRequestService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessRequestStatus {
+    Pending,
+    Approved,
+    Denied,
+}
+
+impl AccessRequestStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "approved" => Self::Approved,
+            "denied" => Self::Denied,
+            _ => Self::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessDecision {
+    Approve,
+    Deny,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RequestAccessRequest {
+    /// Optional message for the channel admins, up to 500 characters.
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AccessDecisionRequest {
+    pub decision: AccessDecision,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccessRequestResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    pub note: Option<String>,
+    pub status: AccessRequestStatus,
+    pub created_at: i64,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccessRequestListResponse {
+    /// Pending requests oldest first, then decided ones most recent first.
+    pub items: Vec<AccessRequestResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/channels/:id/request_access", post(request_access))
+        .route(
+            "/api/v1/channels/:id/access_requests",
+            get(list_access_requests),
+        )
+        .route(
+            "/api/v1/channels/:id/access_requests/:request_id/decision",
+            post(decide_access_request),
+        )
+}
+
+impl AccessRequestService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn request(
+        &self,
+        context: &AuthContext,
+        channel_id: Uuid,
+        payload: RequestAccessRequest,
+    ) -> ApiResult<AccessRequestResponse> {
+        let note = payload
+            .note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        let mut errors = FieldErrors::default();
+        if note
+            .as_ref()
+            .is_some_and(|note| note.chars().count() > NOTE_MAX_CHARS)
+        {
+            errors.add(
+                "note",
+                format!("must be at most {NOTE_MAX_CHARS} characters"),
+            );
+        }
+        errors.into_result()?;
+
+        let channel = self
+            .storage
+            .get_channel(&channel_id)
+            .await
+            .filter(|channel| channel.workspace_id == context.workspace_id)
+            .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
+        if !channel.is_private {
+            return Err(ApiError::BadRequest(
+                "channel is public, join it directly".to_string(),
+            ));
+        }
+        if context.role != WorkspaceRole::Member
+            || self
+                .storage
+                .is_channel_member(channel_id, context.user_id)
+                .await
+        {
+            return Err(ApiError::Conflict(
+                "you already have access to this channel".to_string(),
+            ));
+        }
+        if self
+            .storage
+            .list_channel_access_requests(channel_id)
+            .await
+            .iter()
+            .any(|request| {
+                request.user_id == context.user_id
+                    && request.status == AccessRequestStatus::Pending.as_str()
+            })
+        {
+            return Err(ApiError::Conflict(
+                "you already requested access to this channel".to_string(),
+            ));
+        }
+
+        let record = ChannelAccessRequestRecordStore {
+            id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            workspace_id: context.workspace_id,
+            channel_id,
+            user_id: context.user_id,
+            note,
+            status: AccessRequestStatus::Pending.as_str().to_string(),
+            created_at: Utc::now().timestamp_millis(),
+            decided_by: None,
+            decided_at: None,
+        };
+        self.storage
+            .put_channel_access_request(record.clone())
+            .await;
+        Ok(AccessRequestResponse::from(&record))
+    }
+
+    pub async fn list(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        channel_id: Uuid,
+    ) -> ApiResult<AccessRequestListResponse> {
+        self.manageable_channel(channels, context, channel_id)
+            .await?;
+        let mut records = self.storage.list_channel_access_requests(channel_id).await;
+        records.sort_by_key(|record| {
+            let pending = record.status == AccessRequestStatus::Pending.as_str();
+            (
+                !pending,
+                if pending {
+                    record.created_at
+                } else {
+                    -record.decided_at.unwrap_or_default()
+                },
+            )
+        });
+        Ok(AccessRequestListResponse {
+            items: records.iter().map(AccessRequestResponse::from).collect(),
+        })
+    }
+
+    /// Approving adds the requester to the channel; both outcomes close the
+    /// request.
+    pub async fn decide(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        channel_id: Uuid,
+        request_id: Uuid,
+        decision: AccessDecision,
+    ) -> ApiResult<AccessRequestResponse> {
+        self.manageable_channel(channels, context, channel_id)
+            .await?;
+        let mut record = self
+            .storage
+            .list_channel_access_requests(channel_id)
+            .await
+            .into_iter()
+            .find(|request| request.id == request_id)
+            .ok_or_else(|| ApiError::NotFound("access request not found".to_string()))?;
+        if record.status != AccessRequestStatus::Pending.as_str() {
+            return Err(ApiError::Conflict(
+                "access request was already decided".to_string(),
+            ));
+        }
+
+        let status = match decision {
+            AccessDecision::Approve => {
+                channels
+                    .add_channel_member(context.workspace_id, channel_id, record.user_id)
+                    .await?;
+                AccessRequestStatus::Approved
+            }
+            AccessDecision::Deny => AccessRequestStatus::Denied,
+        };
+        record.status = status.as_str().to_string();
+        record.decided_by = Some(context.user_id);
+        record.decided_at = Some(Utc::now().timestamp_millis());
+        self.storage
+            .put_channel_access_request(record.clone())
+            .await;
+        Ok(AccessRequestResponse::from(&record))
+    }
+
+    async fn manageable_channel(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        channel_id: Uuid,
+    ) -> ApiResult<()> {
+        self.storage
+            .get_channel(&channel_id)
+            .await
+            .filter(|channel| channel.workspace_id == context.workspace_id)
+            .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
+        channels.ensure_channel_manager(context, channel_id).await
+    }
+}
+
+impl From<&ChannelAccessRequestRecordStore> for AccessRequestResponse {
+    fn from(record: &ChannelAccessRequestRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            channel_id: record.channel_id,
+            user_id: record.user_id,
+            note: record.note.clone(),
+            status: AccessRequestStatus::parse(&record.status),
+            created_at: record.created_at,
+            decided_by: record.decided_by,
+            decided_at: record.decided_at,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/request_access",
+    operation_id = "request_channel_access",
+    tag = "channels",
+    request_body = RequestAccessRequest,
+    responses(
+        (status = 201, description = "Access requested", body = AccessRequestResponse),
+        (status = 400, description = "Channel is public", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "Already a member or already requested", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn request_access(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<RequestAccessRequest>,
+) -> ApiResult<(StatusCode, Json<AccessRequestResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let request = state
+        .access_requests
+        .request(&context, channel_id, payload)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CHANNEL_ACCESS_REQUESTED",
+            "channel",
+            Some(channel_id.to_string()),
+            json!({ "request_id": request.id, "note": request.note }),
+        )
+        .await;
+    notifications::notify_access_request(&state, &context, &request).await;
+    Ok((StatusCode::CREATED, Json(request)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/channels/{id}/access_requests",
+    operation_id = "list_channel_access_requests",
+    tag = "channels",
+    responses(
+        (status = 200, description = "Access requests of the channel", body = AccessRequestListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_access_requests(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+) -> ApiResult<Json<AccessRequestListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(
+        state
+            .access_requests
+            .list(&state.channels, &context, channel_id)
+            .await?,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/access_requests/{request_id}/decision",
+    operation_id = "decide_channel_access_request",
+    tag = "channels",
+    request_body = AccessDecisionRequest,
+    responses(
+        (status = 200, description = "Request approved or denied", body = AccessRequestResponse),
+        (status = 400, description = "Requester left the workspace", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel or request not found", body = ErrorResponse),
+        (status = 409, description = "Request already decided", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn decide_access_request(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((channel_id, request_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<AccessDecisionRequest>,
+) -> ApiResult<Json<AccessRequestResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let request = state
+        .access_requests
+        .decide(
+            &state.channels,
+            &context,
+            channel_id,
+            request_id,
+            payload.decision,
+        )
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            match payload.decision {
+                AccessDecision::Approve => "CHANNEL_ACCESS_APPROVED",
+                AccessDecision::Deny => "CHANNEL_ACCESS_DENIED",
+            },
+            "channel",
+            Some(channel_id.to_string()),
+            json!({ "request_id": request.id, "member_user_id": request.user_id }),
+        )
+        .await;
+    notifications::notify_access_decision(&state, &context, &request).await;
+    Ok(Json(request))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{channels::CreateChannelRequest, storage::PersistenceBackend};
+
+    #[tokio::test]
+    async fn access_requests_wait_for_a_channel_manager() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let (workspace_id, owner_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let channels = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let requests = AccessRequestService::new(storage.clone());
+        let owner = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let member = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        storage
+            .put_membership_role(workspace_id, member.user_id, "member")
+            .await;
+        let channel = |is_private| CreateChannelRequest {
+            name: format!("room-{is_private}"),
+            is_private,
+            e2ee: false,
+        };
+        let public = channels
+            .create_channel(workspace_id, owner_id, channel(false))
+            .await
+            .expect("public channel");
+        let private = channels
+            .create_channel(workspace_id, owner_id, channel(true))
+            .await
+            .expect("private channel");
+
+        assert!(matches!(
+            requests
+                .request(&member, public.id, RequestAccessRequest::default())
+                .await,
+            Err(ApiError::BadRequest(_))
+        ));
+        let request = requests
+            .request(
+                &member,
+                private.id,
+                RequestAccessRequest {
+                    note: Some(" working on the launch ".to_string()),
+                },
+            )
+            .await
+            .expect("requested");
+        assert_eq!(request.note.as_deref(), Some("working on the launch"));
+        assert!(matches!(
+            requests
+                .request(&member, private.id, RequestAccessRequest::default())
+                .await,
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(matches!(
+            requests.list(&channels, &member, private.id).await,
+            Err(ApiError::Forbidden(_))
+        ));
+
+        let approved = requests
+            .decide(
+                &channels,
+                &owner,
+                private.id,
+                request.id,
+                AccessDecision::Approve,
+            )
+            .await
+            .expect("approved");
+        assert_eq!(approved.status, AccessRequestStatus::Approved);
+        assert!(storage.is_channel_member(private.id, member.user_id).await);
+        assert!(matches!(
+            requests
+                .decide(
+                    &channels,
+                    &owner,
+                    private.id,
+                    request.id,
+                    AccessDecision::Deny
+                )
+                .await,
+            Err(ApiError::Conflict(_))
+        ));
+    }
+}
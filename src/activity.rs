@@ -0,0 +1,41 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    routing::get,
+};
+
+use crate::{
+    app::AppState,
+    channels::{ActivityListResponse, ActivityQuery},
+    errors::{ApiResult, ErrorResponse},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/v1/me/activity", get(list_activity))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/activity",
+    operation_id = "list_activity",
+    tag = "activity",
+    params(ActivityQuery),
+    responses(
+        (status = 200, description = "Mentions of and replies to the caller, newest first", body = ActivityListResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_activity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ActivityQuery>,
+) -> ApiResult<Json<ActivityListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let page = state.channels.list_activity(&context, &query).await?;
+    Ok(Json(page))
+}
@@ -0,0 +1,987 @@
+use std::sync::{
+    RwLock,
+    atomic::{AtomicBool, Ordering},
+};
+
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{Query, State},
+    http::{HeaderMap, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    analytics::{AnalyticsQuery, DEFAULT_WINDOW_DAYS, MAX_WINDOW_DAYS, WorkspaceAnalyticsResponse},
+    anonymize::{AnonymizeReport, AnonymizeRequest, anonymize_workspace},
+    app::{self, AppState},
+    attachments::AttachmentGcResponse,
+    auth::{AuthContext, WorkspaceRole},
+    config::{Config, ConfigError, RateLimitSettings},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse},
+    legal_holds::LegalHolds,
+    operator::ensure_operator,
+    rate_limit::RateLimitInspectionResponse,
+    storage::{CollectionStats, Storage},
+    usage::WorkspaceUsageResponse,
+    versioning::ApiVersion,
+};
+
+const DEFAULT_MAINTENANCE_MESSAGE: &str = "the server is under maintenance, try again later";
+const MAX_RETENTION_DAYS: u32 = 3650;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LogLevelResponse {
+    pub directives: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateLogLevelRequest {
+    pub directives: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RuntimeConfigResponse {
+    pub log_filter: Option<String>,
+    pub max_attachment_size_bytes: u64,
+    pub rate_limits: RateLimitSettings,
+    pub maintenance: MaintenanceResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MaintenanceResponse {
+    pub enabled: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMaintenanceRequest {
+    pub enabled: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkspaceStatsResponse {
+    pub workspace_id: Uuid,
+    pub members: usize,
+    pub channels: usize,
+    pub messages: usize,
+    pub deleted_messages: usize,
+    pub attachments: usize,
+    pub attachment_bytes: u64,
+    pub pending_uploads: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CollectionStatsResponse {
+    pub name: String,
+    pub documents: u64,
+    /// Uncompressed data size; `null` on the memory backend.
+    pub size_bytes: Option<u64>,
+    pub storage_bytes: Option<u64>,
+    pub index_bytes: Option<u64>,
+    pub indexes: Vec<String>,
+    pub missing_indexes: Vec<String>,
+    /// Expired documents still stored; `null` when nothing in the collection
+    /// expires.
+    pub pending_purge: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StorageStatsResponse {
+    pub backend: String,
+    pub documents: u64,
+    /// `null` on the memory backend.
+    pub size_bytes: Option<u64>,
+    pub missing_indexes: usize,
+    pub pending_purge: u64,
+    pub collections: Vec<CollectionStatsResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RetentionPurgeRequest {
+    pub older_than_days: u32,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RetentionPurgeResponse {
+    pub dry_run: bool,
+    pub cutoff: i64,
+    pub purged_messages: usize,
+    /// Expired messages kept because a legal hold covers them.
+    pub held_messages: usize,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RateLimitQuery {
+    /// Full key (`user=<id>`), one component of it, or a bare user id, IP or email.
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct AttachmentGcRequest {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Instance-wide switch that makes every non-exempt request answer `503` and
+/// closes open WebSockets when it is turned on.
+#[derive(Debug)]
+pub struct MaintenanceMode {
+    enabled: AtomicBool,
+    message: RwLock<Option<String>>,
+    changes: watch::Sender<bool>,
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self::new(false, None)
+    }
+}
+
+impl MaintenanceMode {
+    pub fn new(enabled: bool, message: Option<String>) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            message: RwLock::new(message),
+            changes: watch::Sender::new(enabled),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn status(&self) -> MaintenanceResponse {
+        MaintenanceResponse {
+            enabled: self.is_enabled(),
+            message: self.message.read().ok().and_then(|message| message.clone()),
+        }
+    }
+
+    /// Message shown to clients: the configured one or a generic default.
+    pub fn message(&self) -> String {
+        self.status()
+            .message
+            .unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.to_string())
+    }
+
+    pub fn set(&self, enabled: bool, message: Option<String>) {
+        if let Ok(mut current) = self.message.write() {
+            *current = message;
+        }
+        self.enabled.store(enabled, Ordering::Relaxed);
+        self.changes.send_replace(enabled);
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.changes.subscribe()
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/admin/log-level",
+            get(get_log_level).put(update_log_level),
+        )
+        .route("/api/v1/admin/config/reload", post(reload_config))
+        .route("/api/v1/admin/rate_limits", get(inspect_rate_limits))
+        .route(
+            "/api/v1/admin/maintenance",
+            get(get_maintenance).put(update_maintenance),
+        )
+        .route("/api/v1/admin/storage/stats", get(storage_stats))
+        .route("/api/v1/admin/workspace/stats", get(workspace_stats))
+        .route(
+            "/api/v1/admin/workspace/analytics",
+            get(workspace_analytics),
+        )
+        .route("/api/v1/admin/workspace/usage", get(workspace_usage))
+        .route("/api/v1/admin/retention/purge", post(retention_purge))
+        .route("/api/v1/admin/attachments/gc", post(attachments_gc))
+        .route("/api/v1/admin/anonymize", post(anonymize))
+}
+
+/// Rejects requests with `503 MAINTENANCE_MODE` while maintenance is on. Health,
+/// readiness, the OpenAPI document and the operator routes that can switch it
+/// off stay reachable; login and token refresh are frozen like any other write.
+pub async fn maintenance_middleware(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if state.maintenance.is_enabled() && !maintenance_exempt(request.uri().path()) {
+        return ApiError::Coded(ErrorCode::MaintenanceMode, state.maintenance.message())
+            .into_response();
+    }
+    next.run(request).await
+}
+
+fn maintenance_exempt(path: &str) -> bool {
+    let Some(rest) = ApiVersion::from_path(path).map(|version| &path[version.prefix().len()..])
+    else {
+        return false;
+    };
+    matches!(
+        rest,
+        "/health" | "/ready" | "/openapi.json" | "/admin/maintenance" | "/admin/config/reload"
+    )
+}
+
+pub async fn workspace_stats_for(storage: &Storage, workspace_id: Uuid) -> WorkspaceStatsResponse {
+    let messages = storage.list_messages(workspace_id).await;
+    let deleted_messages = messages
+        .iter()
+        .filter(|message| message.deleted_at.is_some())
+        .count();
+    let attachments = storage.list_attachments(workspace_id).await;
+    WorkspaceStatsResponse {
+        workspace_id,
+        members: storage.list_workspace_memberships(workspace_id).await.len(),
+        channels: storage.list_channels(workspace_id).await.len(),
+        messages: messages.len() - deleted_messages,
+        deleted_messages,
+        attachments: attachments.len(),
+        attachment_bytes: attachments
+            .iter()
+            .map(|attachment| attachment.size_bytes)
+            .sum(),
+        pending_uploads: storage.list_pending_uploads(workspace_id).await.len(),
+    }
+}
+
+/// Hard-deletes every message of the workspace created before `cutoff`, including
+/// thread replies, soft-deleted messages and DMs, except those under legal hold.
+/// Attachments are left for the GC. Returns `(purged, held)` counts.
+pub async fn purge_messages_before(
+    storage: &Storage,
+    workspace_id: Uuid,
+    cutoff: i64,
+    dry_run: bool,
+) -> (usize, usize) {
+    let holds = LegalHolds::load(storage, workspace_id).await;
+    let (held, expired): (Vec<_>, Vec<_>) = storage
+        .list_messages(workspace_id)
+        .await
+        .into_iter()
+        .filter(|message| message.created_at < cutoff)
+        .partition(|message| holds.covers_message(message));
+    let expired = expired
+        .into_iter()
+        .map(|message| message.id)
+        .collect::<Vec<_>>();
+    let (held_dms, expired_dms): (Vec<_>, Vec<_>) = storage
+        .list_workspace_dm_messages(workspace_id)
+        .await
+        .into_iter()
+        .filter(|message| message.created_at < cutoff)
+        .partition(|message| holds.covers_dm_message(message));
+    let expired_dms = expired_dms
+        .into_iter()
+        .map(|message| message.id)
+        .collect::<Vec<_>>();
+    if !dry_run {
+        storage.remove_messages(&expired).await;
+        storage.remove_dm_messages(&expired_dms).await;
+    }
+    (
+        expired.len() + expired_dms.len(),
+        held.len() + held_dms.len(),
+    )
+}
+
+/// Re-reads the configuration and applies the hot-reloadable subset (rate limits,
+/// log filter, attachment size limit and maintenance mode). Any other change
+/// requires a restart.
+pub async fn reload_runtime_config(state: &AppState) -> Result<RuntimeConfigResponse, ConfigError> {
+    let config = Config::load_with(state.config.overrides.clone(), app::CONFIG_CHECKS)?;
+    state.rate_limit.reconfigure(&config.rate_limits).await;
+    state
+        .attachments
+        .set_max_size_bytes(config.max_attachment_size_bytes);
+    if let Some(filter) = config
+        .log_filter
+        .as_deref()
+        .and_then(|directives| EnvFilter::try_new(directives).ok())
+        && let Err(error) = state.log_filter.reload(filter)
+    {
+        tracing::warn!("failed to reload log filter: {}", error);
+    }
+    // The configured mode wins over a toggle made through the API; setting it only
+    // when it differs avoids closing the sockets again on every reload.
+    let maintenance = MaintenanceResponse {
+        enabled: config.maintenance_mode,
+        message: config.maintenance_message,
+    };
+    if state.maintenance.status() != maintenance {
+        state
+            .maintenance
+            .set(maintenance.enabled, maintenance.message.clone());
+        tracing::warn!("maintenance mode set to {} by reload", maintenance.enabled);
+    }
+    Ok(RuntimeConfigResponse {
+        log_filter: config.log_filter,
+        max_attachment_size_bytes: config.max_attachment_size_bytes,
+        rate_limits: config.rate_limits,
+        maintenance,
+    })
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to maintain this workspace".to_string(),
+        )),
+    }
+}
+
+fn ensure_workspace_owner(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner => Ok(()),
+        WorkspaceRole::Admin | WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "only the workspace owner can anonymize data".to_string(),
+        )),
+    }
+}
+
+fn parse_directives(directives: &str) -> ApiResult<EnvFilter> {
+    let directives = directives.trim();
+    if directives.is_empty() {
+        return Err(ApiError::BadRequest("directives are required".to_string()));
+    }
+    EnvFilter::try_new(directives)
+        .map_err(|error| ApiError::BadRequest(format!("invalid log directives: {error}")))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/log-level",
+    operation_id = "get_log_level",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Current log filter directives", body = LogLevelResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_log_level(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<LogLevelResponse>> {
+    ensure_operator(&state, &headers)?;
+    let directives = state
+        .log_filter
+        .with_current(ToString::to_string)
+        .map_err(|error| ApiError::Internal(format!("failed to read log filter: {error}")))?;
+    Ok(Json(LogLevelResponse { directives }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/log-level",
+    operation_id = "update_log_level",
+    tag = "admin",
+    request_body = UpdateLogLevelRequest,
+    responses(
+        (status = 200, description = "Log filter updated", body = LogLevelResponse),
+        (status = 400, description = "Invalid directives", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_log_level(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateLogLevelRequest>,
+) -> ApiResult<Json<LogLevelResponse>> {
+    ensure_operator(&state, &headers)?;
+    let filter = parse_directives(&payload.directives)?;
+    let directives = filter.to_string();
+    let previous = state
+        .log_filter
+        .with_current(ToString::to_string)
+        .unwrap_or_default();
+    state
+        .log_filter
+        .reload(filter)
+        .map_err(|error| ApiError::Internal(format!("failed to update log filter: {error}")))?;
+    tracing::warn!(
+        actor = "operator",
+        "log filter changed from {:?} to {:?}",
+        previous,
+        directives
+    );
+    Ok(Json(LogLevelResponse { directives }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/config/reload",
+    operation_id = "reload_config",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Hot-reloadable configuration applied", body = RuntimeConfigResponse),
+        (status = 400, description = "Invalid configuration, nothing was applied", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn reload_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<RuntimeConfigResponse>> {
+    ensure_operator(&state, &headers)?;
+    let applied = reload_runtime_config(&state)
+        .await
+        .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+    tracing::info!(
+        actor = "operator",
+        "runtime configuration reloaded: {:?}",
+        applied
+    );
+    Ok(Json(applied))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/rate_limits",
+    operation_id = "inspect_rate_limits",
+    tag = "admin",
+    params(RateLimitQuery),
+    responses(
+        (status = 200, description = "Bucket state and recent rejections for the key on this instance", body = RateLimitInspectionResponse),
+        (status = 400, description = "Missing key", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn inspect_rate_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<RateLimitQuery>,
+) -> ApiResult<Json<RateLimitInspectionResponse>> {
+    ensure_operator(&state, &headers)?;
+    let key = query
+        .key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| ApiError::BadRequest("key is required".to_string()))?;
+    Ok(Json(state.rate_limit.inspect(key).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/maintenance",
+    operation_id = "get_maintenance",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Current maintenance mode", body = MaintenanceResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_maintenance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<MaintenanceResponse>> {
+    ensure_operator(&state, &headers)?;
+    Ok(Json(state.maintenance.status()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/maintenance",
+    operation_id = "update_maintenance",
+    tag = "admin",
+    request_body = UpdateMaintenanceRequest,
+    responses(
+        (status = 200, description = "Maintenance mode updated", body = MaintenanceResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_maintenance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateMaintenanceRequest>,
+) -> ApiResult<Json<MaintenanceResponse>> {
+    ensure_operator(&state, &headers)?;
+    let message = payload
+        .message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty());
+    state.maintenance.set(payload.enabled, message);
+    let status = state.maintenance.status();
+    tracing::warn!(
+        actor = "operator",
+        message = ?status.message,
+        "maintenance mode set to {}",
+        status.enabled
+    );
+    Ok(Json(status))
+}
+
+impl From<CollectionStats> for CollectionStatsResponse {
+    fn from(stats: CollectionStats) -> Self {
+        Self {
+            name: stats.name,
+            documents: stats.documents,
+            size_bytes: stats.size_bytes,
+            storage_bytes: stats.storage_bytes,
+            index_bytes: stats.index_bytes,
+            indexes: stats.indexes,
+            missing_indexes: stats.missing_indexes,
+            pending_purge: stats.pending_purge,
+        }
+    }
+}
+
+fn storage_stats_response(backend: &str, stats: Vec<CollectionStats>) -> StorageStatsResponse {
+    let collections = stats
+        .into_iter()
+        .map(CollectionStatsResponse::from)
+        .collect::<Vec<_>>();
+    StorageStatsResponse {
+        backend: backend.to_string(),
+        documents: collections.iter().map(|stats| stats.documents).sum(),
+        size_bytes: collections.iter().map(|stats| stats.size_bytes).sum(),
+        missing_indexes: collections
+            .iter()
+            .map(|stats| stats.missing_indexes.len())
+            .sum(),
+        pending_purge: collections
+            .iter()
+            .filter_map(|stats| stats.pending_purge)
+            .sum(),
+        collections,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/storage/stats",
+    operation_id = "storage_stats",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Per-collection counts, sizes, index health and purge backlog of the instance", body = StorageStatsResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Operator API is disabled", body = ErrorResponse),
+        (status = 500, description = "The database did not answer", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn storage_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<StorageStatsResponse>> {
+    ensure_operator(&state, &headers)?;
+    let stats =
+        state.storage.collection_stats().await.map_err(|error| {
+            ApiError::Internal(format!("failed to read storage stats: {error}"))
+        })?;
+    Ok(Json(storage_stats_response(
+        state.storage.backend().as_str(),
+        stats,
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/workspace/stats",
+    operation_id = "workspace_stats",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Counters for the current workspace", body = WorkspaceStatsResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn workspace_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<WorkspaceStatsResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_workspace_admin(&context)?;
+    Ok(Json(
+        workspace_stats_for(&state.storage, context.workspace_id).await,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/workspace/analytics",
+    operation_id = "workspace_analytics",
+    tag = "admin",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Daily activity of the current workspace over a rolling window", body = WorkspaceAnalyticsResponse),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn workspace_analytics(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AnalyticsQuery>,
+) -> ApiResult<Json<WorkspaceAnalyticsResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_workspace_admin(&context)?;
+    let days = query.days.unwrap_or(DEFAULT_WINDOW_DAYS);
+    if !(1..=MAX_WINDOW_DAYS).contains(&days) {
+        return Err(ApiError::BadRequest(format!(
+            "days must be between 1 and {MAX_WINDOW_DAYS}"
+        )));
+    }
+    Ok(Json(
+        crate::analytics::workspace_analytics(&state, context.workspace_id, days).await,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/workspace/usage",
+    operation_id = "workspace_usage",
+    tag = "admin",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "API usage of the current workspace per user over a rolling window", body = WorkspaceUsageResponse),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn workspace_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AnalyticsQuery>,
+) -> ApiResult<Json<WorkspaceUsageResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_workspace_admin(&context)?;
+    let days = crate::usage::window_days(&query)?;
+    Ok(Json(
+        crate::usage::workspace_usage(&state, context.workspace_id, days).await,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/retention/purge",
+    operation_id = "retention_purge",
+    tag = "admin",
+    request_body = RetentionPurgeRequest,
+    responses(
+        (status = 200, description = "Messages older than the cutoff were purged", body = RetentionPurgeResponse),
+        (status = 400, description = "Invalid retention window", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn retention_purge(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RetentionPurgeRequest>,
+) -> ApiResult<Json<RetentionPurgeResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_workspace_admin(&context)?;
+    if !(1..=MAX_RETENTION_DAYS).contains(&payload.older_than_days) {
+        return Err(ApiError::BadRequest(format!(
+            "older_than_days must be between 1 and {MAX_RETENTION_DAYS}"
+        )));
+    }
+    let cutoff = Utc::now().timestamp_millis() - i64::from(payload.older_than_days) * DAY_MS;
+    let (purged_messages, held_messages) = purge_messages_before(
+        &state.storage,
+        context.workspace_id,
+        cutoff,
+        payload.dry_run,
+    )
+    .await;
+    let response = RetentionPurgeResponse {
+        dry_run: payload.dry_run,
+        cutoff,
+        purged_messages,
+        held_messages,
+    };
+    if !payload.dry_run {
+        state
+            .audit
+            .write(
+                context.workspace_id,
+                Some(context.user_id),
+                "RETENTION_PURGED",
+                "workspace",
+                Some(context.workspace_id.to_string()),
+                json!({
+                    "cutoff": cutoff,
+                    "purged_messages": purged_messages,
+                    "held_messages": held_messages,
+                }),
+            )
+            .await;
+    }
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/attachments/gc",
+    operation_id = "attachments_gc",
+    tag = "admin",
+    request_body = AttachmentGcRequest,
+    responses(
+        (status = 200, description = "Expired uploads and orphaned attachments removed", body = AttachmentGcResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn attachments_gc(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Option<Json<AttachmentGcRequest>>,
+) -> ApiResult<Json<AttachmentGcResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_workspace_admin(&context)?;
+    let Json(payload) = payload.unwrap_or_default();
+    let report = state
+        .attachments
+        .collect_garbage(context.workspace_id, payload.dry_run)
+        .await;
+    if !payload.dry_run {
+        state
+            .audit
+            .write(
+                context.workspace_id,
+                Some(context.user_id),
+                "ATTACHMENTS_GC",
+                "workspace",
+                Some(context.workspace_id.to_string()),
+                serde_json::to_value(&report).unwrap_or_else(|_| json!({})),
+            )
+            .await;
+    }
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/anonymize",
+    operation_id = "anonymize",
+    tag = "admin",
+    request_body = AnonymizeRequest,
+    responses(
+        (status = 200, description = "PII scrubbed from the workspace or member", body = AnonymizeReport),
+        (status = 400, description = "Cannot anonymize yourself", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User is not a member of the workspace", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn anonymize(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<AnonymizeRequest>,
+) -> ApiResult<Json<AnonymizeReport>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_workspace_owner(&context)?;
+    if let Some(user_id) = payload.user_id {
+        if user_id == context.user_id {
+            return Err(ApiError::BadRequest(
+                "you cannot anonymize yourself".to_string(),
+            ));
+        }
+        if state
+            .storage
+            .get_membership_role(context.workspace_id, user_id)
+            .await
+            .is_none()
+        {
+            return Err(ApiError::NotFound("user not found".to_string()));
+        }
+    }
+    let report = anonymize_workspace(
+        &state.storage,
+        context.workspace_id,
+        payload.user_id,
+        context.user_id,
+        payload.dry_run,
+    )
+    .await?;
+    if !payload.dry_run {
+        state
+            .audit
+            .write(
+                context.workspace_id,
+                Some(context.user_id),
+                "DATA_ANONYMIZED",
+                if payload.user_id.is_some() {
+                    "user"
+                } else {
+                    "workspace"
+                },
+                Some(payload.user_id.unwrap_or(context.workspace_id).to_string()),
+                serde_json::to_value(&report).unwrap_or_else(|_| json!({})),
+            )
+            .await;
+    }
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        attachments::AttachmentService,
+        storage::{AttachmentRecordStore, MessageRecordStore, PersistenceBackend},
+    };
+
+    #[test]
+    fn parse_directives_rejects_invalid_filters() {
+        assert!(parse_directives("galynx_api=debug,tower_http=info").is_ok());
+        assert!(matches!(
+            parse_directives("   "),
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            parse_directives("galynx_api=notalevel"),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn maintenance_keeps_operator_routes_reachable() {
+        assert!(maintenance_exempt("/api/v1/health"));
+        assert!(maintenance_exempt("/api/v1/admin/maintenance"));
+        assert!(maintenance_exempt("/api/v1/admin/config/reload"));
+        assert!(!maintenance_exempt("/api/v1/auth/login"));
+        assert!(!maintenance_exempt("/api/v1/auth/refresh"));
+        assert!(!maintenance_exempt("/api/v1/admin/retention/purge"));
+        assert!(!maintenance_exempt("/api/v1/channels"));
+        assert!(!maintenance_exempt("/api/v1/healthz"));
+    }
+
+    #[tokio::test]
+    async fn maintenance_mode_notifies_open_connections() {
+        let maintenance = MaintenanceMode::new(false, None);
+        let mut changes = maintenance.subscribe();
+        assert_eq!(maintenance.message(), DEFAULT_MAINTENANCE_MESSAGE);
+
+        maintenance.set(true, Some("db migration".to_string()));
+        changes.changed().await.unwrap();
+        assert!(*changes.borrow_and_update());
+        assert_eq!(maintenance.message(), "db migration");
+    }
+
+    #[tokio::test]
+    async fn purge_then_gc_removes_old_messages_and_their_attachments() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage"),
+        );
+        let workspace_id = Uuid::new_v4();
+        let channel_id = Uuid::new_v4();
+        let sender_id = Uuid::new_v4();
+        let message = |created_at| MessageRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id,
+            channel_id,
+            sender_id,
+            body_md: "hello".to_string(),
+            thread_root_id: None,
+            origin_workspace_id: None,
+            seq: 0,
+            created_at,
+            edited_at: None,
+            deleted_at: None,
+            crosspost_group_id: None,
+        };
+        let old = message(1_000);
+        let recent = message(10_000);
+        storage
+            .insert_message(old.clone())
+            .await
+            .expect("message stored");
+        storage
+            .insert_message(recent.clone())
+            .await
+            .expect("message stored");
+        storage
+            .put_attachment(AttachmentRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id,
+                channel_id,
+                message_id: Some(old.id),
+                uploader_id: sender_id,
+                filename: "report.pdf".to_string(),
+                content_type: "application/pdf".to_string(),
+                size_bytes: 42,
+                bucket: "bucket".to_string(),
+                key: "key".to_string(),
+                thumbnail_key: None,
+                region: "us-east-1".to_string(),
+                created_at: 1_000,
+            })
+            .await;
+
+        assert_eq!(
+            purge_messages_before(&storage, workspace_id, 5_000, true).await,
+            (1, 0)
+        );
+        assert_eq!(
+            workspace_stats_for(&storage, workspace_id).await.messages,
+            2
+        );
+        assert_eq!(
+            purge_messages_before(&storage, workspace_id, 5_000, false).await,
+            (1, 0)
+        );
+        let stats = workspace_stats_for(&storage, workspace_id).await;
+        assert_eq!((stats.messages, stats.attachments), (1, 1));
+
+        let attachments = AttachmentService::new_without_object_storage(storage.clone());
+        let report = attachments.collect_garbage(workspace_id, false).await;
+        assert_eq!(report.orphaned_attachments, 1);
+        assert_eq!(report.reclaimed_bytes, 42);
+        assert_eq!(
+            workspace_stats_for(&storage, workspace_id)
+                .await
+                .attachments,
+            0
+        );
+    }
+}
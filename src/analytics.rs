@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState, auth::AuthContext, channels::MessageResponse, storage::DailyActivityRecordStore,
+};
+
+pub const DEFAULT_WINDOW_DAYS: u32 = 30;
+pub const MAX_WINDOW_DAYS: u32 = 90;
+const TOP_CHANNELS_LIMIT: usize = 10;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AnalyticsQuery {
+    /// Rolling window ending today (UTC), 1 to 90 days; defaults to 30.
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailyAnalyticsResponse {
+    pub day: String,
+    pub active_users: usize,
+    pub messages: i64,
+    pub peak_ws_connections: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TopChannelResponse {
+    pub channel_id: Uuid,
+    /// `null` when the channel has since been deleted.
+    pub name: Option<String>,
+    pub messages: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkspaceAnalyticsResponse {
+    pub workspace_id: Uuid,
+    pub window_days: u32,
+    pub from_day: String,
+    pub to_day: String,
+    /// Distinct users active at any point in the window.
+    pub active_users: usize,
+    pub messages: i64,
+    pub peak_ws_connections: i64,
+    /// Realtime connections open on this instance right now.
+    pub current_ws_connections: usize,
+    /// One entry per day, oldest first, including days without activity.
+    pub days: Vec<DailyAnalyticsResponse>,
+    pub top_channels: Vec<TopChannelResponse>,
+}
+
+fn day_key(at: DateTime<Utc>) -> String {
+    at.date_naive().format("%Y-%m-%d").to_string()
+}
+
+/// Counts a new message (channel message or thread reply) towards today's totals.
+pub(crate) async fn record_message(state: &AppState, message: &MessageResponse) {
+    state
+        .storage
+        .record_daily_message(
+            message.workspace_id,
+            &day_key(Utc::now()),
+            message.sender_id,
+            message.channel_id,
+        )
+        .await;
+}
+
+/// Marks the user active and folds the current realtime concurrency into today's peak.
+pub(crate) async fn record_connection(state: &AppState, context: &AuthContext) {
+    let connections = state.realtime.connection_count(context.workspace_id).await;
+    state
+        .storage
+        .record_daily_connection(
+            context.workspace_id,
+            &day_key(Utc::now()),
+            context.user_id,
+            connections as i64,
+        )
+        .await;
+}
+
+pub(crate) async fn workspace_analytics(
+    state: &AppState,
+    workspace_id: Uuid,
+    window_days: u32,
+) -> WorkspaceAnalyticsResponse {
+    let today = Utc::now().date_naive();
+    let from = today - Duration::days(i64::from(window_days) - 1);
+    let from_day = from.format("%Y-%m-%d").to_string();
+    let activity = state
+        .storage
+        .list_daily_activity(workspace_id, &from_day)
+        .await;
+    let channel_names = state
+        .storage
+        .list_channels(workspace_id)
+        .await
+        .into_iter()
+        .map(|channel| (channel.id, channel.name))
+        .collect::<HashMap<_, _>>();
+    let mut report = summarize(workspace_id, from, today, activity, &channel_names);
+    report.current_ws_connections = state.realtime.connection_count(workspace_id).await;
+    report
+}
+
+fn summarize(
+    workspace_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+    activity: Vec<DailyActivityRecordStore>,
+    channel_names: &HashMap<Uuid, String>,
+) -> WorkspaceAnalyticsResponse {
+    let mut by_day = activity
+        .into_iter()
+        .map(|item| (item.day.clone(), item))
+        .collect::<HashMap<_, _>>();
+    let mut active_users = HashSet::new();
+    let mut channel_messages = HashMap::<Uuid, i64>::new();
+    let mut days = Vec::new();
+    for day in from.iter_days().take_while(|day| *day <= to) {
+        let key = day.format("%Y-%m-%d").to_string();
+        let item = by_day.remove(&key).unwrap_or_default();
+        for (channel_id, count) in &item.channel_messages {
+            *channel_messages.entry(*channel_id).or_default() += count;
+        }
+        days.push(DailyAnalyticsResponse {
+            day: key,
+            active_users: item.active_users.len(),
+            messages: item.messages,
+            peak_ws_connections: item.peak_ws_connections,
+        });
+        active_users.extend(item.active_users);
+    }
+    let mut top_channels = channel_messages
+        .into_iter()
+        .map(|(channel_id, messages)| TopChannelResponse {
+            channel_id,
+            name: channel_names.get(&channel_id).cloned(),
+            messages,
+        })
+        .collect::<Vec<_>>();
+    top_channels.sort_by(|a, b| {
+        b.messages
+            .cmp(&a.messages)
+            .then_with(|| a.channel_id.cmp(&b.channel_id))
+    });
+    top_channels.truncate(TOP_CHANNELS_LIMIT);
+
+    WorkspaceAnalyticsResponse {
+        workspace_id,
+        window_days: days.len() as u32,
+        from_day: from.format("%Y-%m-%d").to_string(),
+        to_day: to.format("%Y-%m-%d").to_string(),
+        active_users: active_users.len(),
+        messages: days.iter().map(|day| day.messages).sum(),
+        peak_ws_connections: days
+            .iter()
+            .map(|day| day.peak_ws_connections)
+            .max()
+            .unwrap_or_default(),
+        current_ws_connections: 0,
+        days,
+        top_channels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_fills_gaps_and_ranks_channels() {
+        let workspace_id = Uuid::new_v4();
+        let (general, random) = (Uuid::new_v4(), Uuid::new_v4());
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let day =
+            |day: &str, users: &[Uuid], channels: &[(Uuid, i64)], peak| DailyActivityRecordStore {
+                workspace_id,
+                day: day.to_string(),
+                messages: channels.iter().map(|(_, count)| count).sum(),
+                active_users: users.iter().copied().collect(),
+                channel_messages: channels.iter().copied().collect(),
+                peak_ws_connections: peak,
+            };
+        let activity = vec![
+            day("2026-03-01", &[alice], &[(general, 3)], 2),
+            day("2026-03-03", &[alice, bob], &[(general, 1), (random, 5)], 4),
+        ];
+        let names = HashMap::from([(general, "general".to_string())]);
+
+        let report = summarize(
+            workspace_id,
+            NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 3).unwrap(),
+            activity,
+            &names,
+        );
+
+        assert_eq!(report.window_days, 3);
+        assert_eq!(
+            report
+                .days
+                .iter()
+                .map(|day| (day.day.as_str(), day.active_users, day.messages))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2026-03-01", 1, 3),
+                ("2026-03-02", 0, 0),
+                ("2026-03-03", 2, 6)
+            ]
+        );
+        assert_eq!(report.active_users, 2);
+        assert_eq!(report.messages, 9);
+        assert_eq!(report.peak_ws_connections, 4);
+        assert_eq!(report.top_channels[0].channel_id, random);
+        assert_eq!(report.top_channels[0].name, None);
+        assert_eq!(report.top_channels[1].name.as_deref(), Some("general"));
+    }
+}
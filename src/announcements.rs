@@ -0,0 +1,446 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    notifications::{NewNotification, NotificationKind},
+    realtime,
+    storage::{AnnouncementRecordStore, Storage},
+};
+
+const TITLE_MAX_CHARS: usize = 200;
+const BODY_MAX_CHARS: usize = 4000;
+
+#[derive(Clone)]
+pub struct AnnouncementService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AnnouncementSeverity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "warning" => Self::Warning,
+            "critical" => Self::Critical,
+            _ => Self::Info,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateAnnouncementRequest {
+    pub title: String,
+    #[serde(default)]
+    pub body_md: String,
+    #[serde(default)]
+    pub severity: AnnouncementSeverity,
+    /// Show as a banner until dismissed or expired; defaults to `true`.
+    #[serde(default = "default_pinned")]
+    pub pinned: bool,
+    /// Unix millis after which the banner is no longer returned.
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnnouncementResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub author_id: Uuid,
+    pub title: String,
+    pub body_md: String,
+    pub severity: AnnouncementSeverity,
+    pub pinned: bool,
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnnouncementListResponse {
+    pub items: Vec<AnnouncementResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/announcements", get(list_announcements))
+        .route("/api/v1/admin/announcements", post(create_announcement))
+        .route(
+            "/api/v1/admin/announcements/:id",
+            delete(delete_announcement),
+        )
+}
+
+impl AnnouncementService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn create(
+        &self,
+        context: &AuthContext,
+        payload: CreateAnnouncementRequest,
+    ) -> ApiResult<AnnouncementResponse> {
+        ensure_workspace_admin(context)?;
+        let now = Utc::now().timestamp_millis();
+        let title = payload.title.trim().to_string();
+        let body_md = payload.body_md.trim().to_string();
+        let mut errors = FieldErrors::default();
+        if title.is_empty() || title.chars().count() > TITLE_MAX_CHARS {
+            errors.add(
+                "title",
+                format!("must be between 1 and {TITLE_MAX_CHARS} characters"),
+            );
+        }
+        if body_md.chars().count() > BODY_MAX_CHARS {
+            errors.add(
+                "body_md",
+                format!("must be at most {BODY_MAX_CHARS} characters"),
+            );
+        }
+        if payload
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now)
+        {
+            errors.add("expires_at", "must be in the future");
+        }
+        errors.into_result()?;
+
+        let record = AnnouncementRecordStore {
+            id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            workspace_id: context.workspace_id,
+            author_id: context.user_id,
+            title,
+            body_md,
+            severity: payload.severity.as_str().to_string(),
+            pinned: payload.pinned,
+            expires_at: payload.expires_at,
+            created_at: now,
+        };
+        self.storage.put_announcement(record.clone()).await?;
+        Ok(AnnouncementResponse::from(&record))
+    }
+
+    /// Announcements that have not expired, newest first.
+    pub async fn list_active(&self, workspace_id: Uuid) -> AnnouncementListResponse {
+        let now = Utc::now().timestamp_millis();
+        let mut records = self.storage.list_announcements(workspace_id).await;
+        records.retain(|record| record.expires_at.is_none_or(|expires_at| expires_at > now));
+        records.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
+        });
+        AnnouncementListResponse {
+            items: records.iter().map(AnnouncementResponse::from).collect(),
+        }
+    }
+
+    pub async fn remove(&self, context: &AuthContext, announcement_id: Uuid) -> ApiResult<()> {
+        ensure_workspace_admin(context)?;
+        if !self
+            .storage
+            .remove_announcement(context.workspace_id, announcement_id)
+            .await
+        {
+            return Err(ApiError::NotFound("announcement not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Members other than the author, who each get an inbox entry.
+    async fn recipients(&self, announcement: &AnnouncementResponse) -> Vec<Uuid> {
+        self.storage
+            .list_workspace_memberships(announcement.workspace_id)
+            .await
+            .into_iter()
+            .map(|(user_id, _)| user_id)
+            .filter(|user_id| *user_id != announcement.author_id)
+            .collect()
+    }
+}
+
+impl From<&AnnouncementRecordStore> for AnnouncementResponse {
+    fn from(record: &AnnouncementRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            author_id: record.author_id,
+            title: record.title.clone(),
+            body_md: record.body_md.clone(),
+            severity: AnnouncementSeverity::parse(&record.severity),
+            pinned: record.pinned,
+            expires_at: record.expires_at,
+            created_at: record.created_at,
+        }
+    }
+}
+
+fn default_pinned() -> bool {
+    true
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to post announcements".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/announcements",
+    operation_id = "list_announcements",
+    tag = "announcements",
+    responses(
+        (status = 200, description = "Active workspace announcements, newest first", body = AnnouncementListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_announcements(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<AnnouncementListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(
+        state.announcements.list_active(context.workspace_id).await,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/announcements",
+    operation_id = "create_announcement",
+    tag = "announcements",
+    request_body = CreateAnnouncementRequest,
+    responses(
+        (status = 201, description = "Announcement posted to every member", body = AnnouncementResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_announcement(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateAnnouncementRequest>,
+) -> ApiResult<(StatusCode, Json<AnnouncementResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let announcement = state.announcements.create(&context, payload).await?;
+    let recipients = state.announcements.recipients(&announcement).await;
+    for user_id in &recipients {
+        state
+            .notifications
+            .create(NewNotification {
+                workspace_id: context.workspace_id,
+                user_id: *user_id,
+                kind: NotificationKind::AdminAnnouncement,
+                actor_id: Some(context.user_id),
+                channel_id: None,
+                message_id: None,
+                body: announcement.title.clone(),
+            })
+            .await;
+    }
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "ANNOUNCEMENT_CREATED",
+            "announcement",
+            Some(announcement.id.to_string()),
+            json!({
+                "title": announcement.title,
+                "severity": announcement.severity,
+                "pinned": announcement.pinned,
+                "recipients": recipients.len(),
+            }),
+        )
+        .await;
+    state
+        .realtime
+        .emit(
+            context.workspace_id,
+            realtime::make_event(
+                "ANNOUNCEMENT",
+                context.workspace_id,
+                None,
+                None,
+                serde_json::to_value(&announcement).unwrap_or_default(),
+            ),
+        )
+        .await;
+    Ok((StatusCode::CREATED, Json(announcement)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/announcements/{id}",
+    operation_id = "delete_announcement",
+    tag = "announcements",
+    responses(
+        (status = 204, description = "Announcement removed"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Announcement not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn delete_announcement(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(announcement_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state
+        .announcements
+        .remove(&context, announcement_id)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "ANNOUNCEMENT_DELETED",
+            "announcement",
+            Some(announcement_id.to_string()),
+            json!({}),
+        )
+        .await;
+    state
+        .realtime
+        .emit(
+            context.workspace_id,
+            realtime::make_event(
+                "ANNOUNCEMENT_REMOVED",
+                context.workspace_id,
+                None,
+                None,
+                json!({ "id": announcement_id }),
+            ),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PersistenceBackend;
+
+    #[tokio::test]
+    async fn only_admins_post_and_expired_announcements_are_hidden() {
+        let workspace_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let announcements = AnnouncementService::new(storage.clone());
+        let admin = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Admin,
+        };
+        let member = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let request = |title: &str| CreateAnnouncementRequest {
+            title: title.to_string(),
+            body_md: String::new(),
+            severity: AnnouncementSeverity::Warning,
+            pinned: true,
+            expires_at: None,
+        };
+
+        assert!(matches!(
+            announcements.create(&member, request("nope")).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        let posted = announcements
+            .create(&admin, request("Maintenance tonight"))
+            .await
+            .expect("admin should post");
+        storage
+            .put_announcement(AnnouncementRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id,
+                author_id: admin.user_id,
+                title: "Expired".to_string(),
+                body_md: String::new(),
+                severity: "info".to_string(),
+                pinned: true,
+                expires_at: Some(posted.created_at - 1),
+                created_at: posted.created_at - 10,
+            })
+            .await
+            .expect("announcement stored");
+        storage
+            .put_membership_role(workspace_id, admin.user_id, "admin")
+            .await;
+        storage
+            .put_membership_role(workspace_id, member.user_id, "member")
+            .await;
+
+        let active = announcements.list_active(workspace_id).await.items;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].title, "Maintenance tonight");
+        assert_eq!(active[0].severity, AnnouncementSeverity::Warning);
+        assert_eq!(
+            announcements.recipients(&posted).await,
+            vec![member.user_id]
+        );
+
+        announcements
+            .remove(&admin, posted.id)
+            .await
+            .expect("admin should remove");
+        assert!(
+            announcements
+                .list_active(workspace_id)
+                .await
+                .items
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{auth::hash_password, errors::ApiResult, legal_holds::LegalHolds, storage::Storage};
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+const IP_PATTERN: &str = r"\b(?:(?:\d{1,3}\.){3}\d{1,3}|(?:[0-9A-Fa-f]{1,4}:){7}[0-9A-Fa-f]{1,4}|(?:[0-9A-Fa-f]{1,4}:){1,6}:(?:[0-9A-Fa-f]{1,4}:?){0,6})";
+const MIN_NAME_LEN: usize = 3;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AnonymizeRequest {
+    /// Limit the job to one member; omit it to anonymize the whole workspace.
+    pub user_id: Option<Uuid>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct AnonymizeReport {
+    pub dry_run: bool,
+    pub users_anonymized: usize,
+    /// Members that also belong to other workspaces or are under legal hold
+    /// keep their account; only their unheld content here is scrubbed.
+    pub users_skipped: usize,
+    /// Channel and direct messages.
+    pub messages_scrubbed: usize,
+    /// Messages (channel or direct) left untouched because a legal hold covers them.
+    pub messages_held: usize,
+    pub notifications_scrubbed: usize,
+    pub audit_entries_scrubbed: usize,
+}
+
+/// Replaces PII in free text: email addresses, IP addresses and the known
+/// names/emails of the users being anonymized.
+struct Scrubber {
+    email: Regex,
+    ip: Regex,
+    identities: Vec<(Regex, String)>,
+}
+
+impl Scrubber {
+    fn new() -> Self {
+        Self {
+            email: Regex::new(EMAIL_PATTERN).expect("valid email pattern"),
+            ip: Regex::new(IP_PATTERN).expect("valid ip pattern"),
+            identities: Vec::new(),
+        }
+    }
+
+    fn add_identity(&mut self, value: &str, replacement: String) {
+        let value = value.trim();
+        if value.chars().count() < MIN_NAME_LEN {
+            return;
+        }
+        if let Ok(pattern) = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(value))) {
+            self.identities.push((pattern, replacement));
+        }
+    }
+
+    fn scrub_identities(&self, text: &str) -> String {
+        self.identities
+            .iter()
+            .fold(text.to_string(), |text, (pattern, replacement)| {
+                pattern
+                    .replace_all(&text, regex::NoExpand(replacement))
+                    .into_owned()
+            })
+    }
+
+    fn scrub_all(&self, text: &str) -> String {
+        let text = self.scrub_identities(text);
+        let text = self.email.replace_all(&text, "[email]");
+        self.ip.replace_all(&text, "[ip]").into_owned()
+    }
+
+    fn scrub_value(&self, value: &Value, full: bool) -> Value {
+        match value {
+            Value::String(text) if full => Value::String(self.scrub_all(text)),
+            Value::String(text) => Value::String(self.scrub_identities(text)),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.scrub_value(item, full))
+                    .collect(),
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, item)| (key.clone(), self.scrub_value(item, full)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+fn pseudonym(user_id: Uuid) -> String {
+    format!("anon-{}", &user_id.simple().to_string()[..12])
+}
+
+/// Scrubs PII from a workspace, or from one of its members when `user_id` is
+/// set, keeping ids, senders, threads and timestamps intact so analytics still
+/// add up. Accounts that only belong to this workspace get a pseudonymous name
+/// and email, a random password and their sessions revoked. `exclude` is never
+/// anonymized (the operator running the job), and nothing under legal hold is
+/// rewritten.
+pub async fn anonymize_workspace(
+    storage: &Storage,
+    workspace_id: Uuid,
+    user_id: Option<Uuid>,
+    exclude: Uuid,
+    dry_run: bool,
+) -> ApiResult<AnonymizeReport> {
+    let mut report = AnonymizeReport {
+        dry_run,
+        ..AnonymizeReport::default()
+    };
+    let subjects: Vec<Uuid> = match user_id {
+        Some(user_id) => vec![user_id],
+        None => storage
+            .list_workspace_memberships(workspace_id)
+            .await
+            .into_iter()
+            .map(|(user_id, _)| user_id)
+            .filter(|user_id| *user_id != exclude)
+            .collect(),
+    };
+
+    let holds = LegalHolds::load(storage, workspace_id).await;
+    let mut scrubber = Scrubber::new();
+    for subject in &subjects {
+        let Some(mut user) = storage.get_auth_user_by_id(*subject).await else {
+            continue;
+        };
+        let alias = pseudonym(user.id);
+        scrubber.add_identity(&user.email, "[email]".to_string());
+        scrubber.add_identity(&user.name, alias.clone());
+
+        if holds.covers_user(user.id) || storage.list_user_memberships(user.id).await.len() > 1 {
+            report.users_skipped += 1;
+            continue;
+        }
+        report.users_anonymized += 1;
+        if dry_run {
+            continue;
+        }
+        user.email = format!("{alias}@anonymized.invalid");
+        user.name = alias;
+        user.password_hash = hash_password(&Uuid::new_v4().to_string())?;
+        storage.put_auth_user(user.clone()).await;
+        storage
+            .revoke_refresh_sessions(user.id, None, Utc::now().timestamp())
+            .await;
+    }
+
+    let in_scope = |actor: Option<Uuid>| user_id.is_none() || actor == user_id;
+    let subject_ids: HashSet<String> = subjects.iter().map(ToString::to_string).collect();
+
+    for mut message in storage.list_messages(workspace_id).await {
+        let body_md = if in_scope(Some(message.sender_id)) {
+            scrubber.scrub_all(&message.body_md)
+        } else {
+            scrubber.scrub_identities(&message.body_md)
+        };
+        if body_md == message.body_md {
+            continue;
+        }
+        if holds.covers_message(&message) {
+            report.messages_held += 1;
+            continue;
+        }
+        report.messages_scrubbed += 1;
+        if !dry_run {
+            message.body_md = body_md;
+            storage.update_message(message).await?;
+        }
+    }
+
+    for mut message in storage.list_workspace_dm_messages(workspace_id).await {
+        let body_md = if in_scope(Some(message.sender_id)) {
+            scrubber.scrub_all(&message.body_md)
+        } else {
+            scrubber.scrub_identities(&message.body_md)
+        };
+        if body_md == message.body_md {
+            continue;
+        }
+        if holds.covers_dm_message(&message) {
+            report.messages_held += 1;
+            continue;
+        }
+        report.messages_scrubbed += 1;
+        if !dry_run {
+            message.body_md = body_md;
+            storage.update_dm_message(message).await?;
+        }
+    }
+
+    for mut notification in storage.list_workspace_notifications(workspace_id).await {
+        let body = if in_scope(notification.actor_id) || in_scope(Some(notification.user_id)) {
+            scrubber.scrub_all(&notification.body)
+        } else {
+            scrubber.scrub_identities(&notification.body)
+        };
+        if body == notification.body
+            || notification
+                .actor_id
+                .is_some_and(|actor_id| holds.covers_user(actor_id))
+        {
+            continue;
+        }
+        report.notifications_scrubbed += 1;
+        if !dry_run {
+            notification.body = body;
+            storage.put_notification(notification).await?;
+        }
+    }
+
+    for entry in storage.list_audit_entries(workspace_id).await {
+        let full = in_scope(entry.actor_id)
+            || entry
+                .target_id
+                .as_ref()
+                .is_some_and(|target| subject_ids.contains(target));
+        let metadata = scrubber.scrub_value(&entry.metadata, full);
+        if metadata == entry.metadata {
+            continue;
+        }
+        report.audit_entries_scrubbed += 1;
+        if !dry_run {
+            storage.update_audit_metadata(entry.id, metadata).await;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::storage::{
+        AuditEntryRecord, AuthUserRecordStore, DmMessageRecordStore, MessageRecordStore,
+        NotificationRecordStore, PersistenceBackend,
+    };
+
+    #[test]
+    fn scrubber_replaces_emails_ips_and_known_names() {
+        let mut scrubber = Scrubber::new();
+        scrubber.add_identity("Ana Torres", "anon-1".to_string());
+        scrubber.add_identity("Al", "anon-2".to_string());
+        assert_eq!(
+            scrubber.scrub_all(
+                "ana torres (ana@x.io) from 10.0.0.12 and fe80::1:2 said hi to Al at 12:30:45"
+            ),
+            "anon-1 ([email]) from [ip] and [ip] said hi to Al at 12:30:45"
+        );
+        assert_eq!(
+            scrubber.scrub_value(
+                &json!({ "email": "bob@x.io", "who": ["Ana Torres"] }),
+                false
+            ),
+            json!({ "email": "bob@x.io", "who": ["anon-1"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn user_scope_keeps_structure_and_other_members_content() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage");
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        for (id, email, name, role) in [
+            (owner_id, "owner@x.io", "Owner", "owner"),
+            (user_id, "ana@x.io", "Ana Torres", "member"),
+        ] {
+            storage
+                .put_auth_user(AuthUserRecordStore {
+                    id,
+                    email: email.to_string(),
+                    name: name.to_string(),
+                    password_hash: String::new(),
+                    email_verified_at: None,
+                })
+                .await;
+            storage.put_membership_role(workspace_id, id, role).await;
+        }
+        let message = |sender_id, body_md: &str| MessageRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id,
+            channel_id: Uuid::new_v4(),
+            sender_id,
+            body_md: body_md.to_string(),
+            thread_root_id: None,
+            origin_workspace_id: None,
+            seq: 0,
+            created_at: 1_000,
+            edited_at: None,
+            deleted_at: None,
+            crosspost_group_id: None,
+        };
+        let own = message(user_id, "reach me at ana@x.io");
+        let other = message(owner_id, "thanks Ana Torres, ping ops@x.io");
+        storage
+            .insert_message(own.clone())
+            .await
+            .expect("message stored");
+        storage
+            .insert_message(other.clone())
+            .await
+            .expect("message stored");
+        storage
+            .append_audit_entry(AuditEntryRecord {
+                id: Uuid::new_v4(),
+                workspace_id,
+                actor_id: Some(owner_id),
+                action: "USER_CREATED".to_string(),
+                target_type: "user".to_string(),
+                target_id: Some(user_id.to_string()),
+                metadata: json!({ "email": "ana@x.io", "ip": "10.0.0.1" }),
+                created_at: 1_000,
+            })
+            .await;
+
+        let dm = DmMessageRecordStore {
+            id: Uuid::new_v4(),
+            conversation_id: Uuid::new_v4(),
+            workspace_id,
+            sender_id: user_id,
+            body_md: "my ip is 10.0.0.7".to_string(),
+            created_at: 1_000,
+        };
+        storage
+            .write_dm_message_with_events(dm.clone(), Vec::new())
+            .await
+            .expect("dm stored");
+
+        let notification = NotificationRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id,
+            user_id: owner_id,
+            kind: "mention".to_string(),
+            actor_id: Some(user_id),
+            channel_id: None,
+            message_id: Some(own.id),
+            body: "reach me at ana@x.io".to_string(),
+            created_at: 1_000,
+            read_at: None,
+        };
+        storage
+            .put_notification(notification)
+            .await
+            .expect("notification stored");
+
+        let report = anonymize_workspace(&storage, workspace_id, Some(user_id), owner_id, false)
+            .await
+            .expect("anonymize");
+        assert_eq!(
+            (
+                report.users_anonymized,
+                report.messages_scrubbed,
+                report.notifications_scrubbed,
+                report.audit_entries_scrubbed
+            ),
+            (1, 3, 1, 1)
+        );
+
+        let user = storage.get_auth_user_by_id(user_id).await.expect("user");
+        assert_eq!(user.name, pseudonym(user_id));
+        assert!(storage.get_auth_user_by_email("ana@x.io").await.is_none());
+        let own = storage.get_message(&own.id).await.expect("own message");
+        assert_eq!(own.body_md, "reach me at [email]");
+        assert_eq!(own.sender_id, user_id);
+        let other = storage.get_message(&other.id).await.expect("other message");
+        assert_eq!(
+            other.body_md,
+            format!("thanks {}, ping ops@x.io", pseudonym(user_id))
+        );
+        let dms = storage.list_workspace_dm_messages(workspace_id).await;
+        assert_eq!(dms[0].body_md, "my ip is [ip]");
+        let notifications = storage.list_notifications(workspace_id, owner_id).await;
+        assert_eq!(notifications[0].body, "reach me at [email]");
+        let audit = storage.list_audit_entries(workspace_id).await;
+        assert_eq!(
+            audit[0].metadata,
+            json!({ "email": "[email]", "ip": "[ip]" })
+        );
+    }
+}
@@ -1,13 +1,36 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, future::Future, sync::Arc, time::Duration};
 
-use axum::{Json, Router, extract::State, middleware, routing::get};
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::State,
+    http::{HeaderName, HeaderValue, Method, Request, StatusCode, header},
+    middleware,
+    response::Html,
+    routing::get,
+};
 use serde::Serialize;
-use tower_http::trace::TraceLayer;
-use utoipa::{OpenApi, ToSchema};
+use tokio::time::timeout;
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    trace::TraceLayer,
+};
+use utoipa::{
+    Modify, OpenApi, ToSchema,
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme},
+};
 
 use crate::{
-    attachments, audit, auth, channels, config::Config, observability, rate_limit, reactions,
-    realtime, storage, threads, users, workspaces,
+    access_requests, activity, admin, announcements, apps, attachments, audit, auth, calls,
+    channel_links, channel_templates, channels,
+    config::{Config, ConfigCheck, ConfigError},
+    dms, e2ee, email, email_verification, encryption, errors, event_subscriptions, graphql, groups,
+    ip_allowlists, keywords, legal_holds, moderation, moderation_cases, mutes, notifications,
+    observability, operator, password_reset, presence, rate_limit, reactions, realtime,
+    recurring_posts, reminders, search, session_limits, sessions, storage, sync, threads, usage,
+    users,
+    versioning::ApiVersion,
+    workspaces,
 };
 
 #[derive(Clone)]
@@ -16,137 +39,525 @@ pub struct AppState {
     pub storage: Arc<storage::Storage>,
     pub auth: Arc<auth::AuthService>,
     pub channels: Arc<channels::ChannelService>,
+    pub channel_links: Arc<channel_links::ChannelLinkService>,
+    pub channel_templates: Arc<channel_templates::ChannelTemplates>,
+    pub groups: Arc<groups::GroupService>,
+    pub calls: Arc<calls::CallService>,
     pub attachments: Arc<attachments::AttachmentService>,
     pub audit: Arc<audit::AuditService>,
     pub rate_limit: Arc<rate_limit::RateLimitService>,
     pub reactions: Arc<reactions::ReactionService>,
+    pub moderation: Arc<moderation::ModerationPipeline>,
+    pub notifications: Arc<notifications::NotificationService>,
+    pub email: Arc<email::EmailService>,
+    pub keywords: Arc<keywords::KeywordService>,
+    pub announcements: Arc<announcements::AnnouncementService>,
+    pub legal_holds: Arc<legal_holds::LegalHoldService>,
+    pub mutes: Arc<mutes::MuteService>,
+    pub moderation_cases: Arc<moderation_cases::ModerationCaseService>,
+    pub access_requests: Arc<access_requests::AccessRequestService>,
+    pub recurring_posts: Arc<recurring_posts::RecurringPostService>,
+    pub search: Arc<search::SearchService>,
+    pub dms: Arc<dms::DmService>,
+    pub webhook_subscriptions: Arc<event_subscriptions::WebhookSubscriptions>,
     pub realtime: Arc<realtime::RealtimeHub>,
     pub users: Arc<users::UserService>,
     pub workspaces: Arc<workspaces::WorkspaceService>,
     pub metrics: Arc<observability::AppMetrics>,
+    pub log_filter: observability::LogFilterHandle,
+    pub maintenance: Arc<admin::MaintenanceMode>,
+    pub graphql: Arc<graphql::GraphqlSchema>,
 }
 
-pub async fn build_state(config: Config) -> AppState {
-    let storage = Arc::new(
-        storage::Storage::new(config.persistence_backend, config.mongo_uri.as_deref())
-            .await
-            .expect("failed to initialize storage"),
-    );
+/// Startup checks for settings parsed by these modules, run with
+/// `Config::validate` so every problem shows up in one report.
+pub const CONFIG_CHECKS: &[ConfigCheck] = &[
+    channel_templates::ChannelTemplates::check_config,
+    moderation::ModerationPipeline::check_config,
+    email::EmailService::check_config,
+];
+
+/// Fails only on settings that `CONFIG_CHECKS` already rejects at load time.
+pub async fn build_state(
+    config: Config,
+    log_filter: observability::LogFilterHandle,
+) -> Result<AppState, ConfigError> {
+    let invalid = |problem: String| ConfigError::Invalid(vec![problem]);
+    let storage = if config.migrate_on_startup {
+        storage::Storage::new_without_indexes(
+            config.persistence_backend,
+            config.mongo_uri.as_deref(),
+        )
+        .await
+    } else {
+        storage::Storage::new(config.persistence_backend, config.mongo_uri.as_deref()).await
+    };
+    let mut storage = storage.expect("failed to initialize storage");
+    if let Some(key) = config.message_encryption_key.as_deref() {
+        storage = storage.with_message_encryption(
+            encryption::MessageEncryption::from_base64(key).map_err(invalid)?,
+        );
+    }
+    let storage = Arc::new(storage);
     let auth_service = auth::AuthService::new(
         storage.clone(),
         &config.bootstrap_workspace_name,
         &config.bootstrap_email,
         &config.bootstrap_password,
     );
+    let channel_templates =
+        Arc::new(channel_templates::ChannelTemplates::from_config(&config).map_err(invalid)?);
     let channels_service = channels::ChannelService::new(
         storage.clone(),
         auth_service.bootstrap_workspace_id(),
         auth_service.bootstrap_user_id(),
-    );
+    )
+    .with_templates(channel_templates.clone());
+    let channel_links_service = channel_links::ChannelLinkService::new(storage.clone());
     let audit_service = audit::AuditService::new(storage.clone());
     let attachments_service = attachments::AttachmentService::new(storage.clone(), &config).await;
-    let rate_limit_service = rate_limit::RateLimitService::new();
+    let rate_limit_service =
+        rate_limit::RateLimitService::new(&config.trusted_proxies, &config.rate_limits);
     let reactions_service = reactions::ReactionService::new(storage.clone());
-    let realtime_hub = realtime::RealtimeHub::new(config.redis_url.as_deref());
+    let moderation_pipeline =
+        moderation::ModerationPipeline::from_config(&config).map_err(invalid)?;
+    let notifications_service = notifications::NotificationService::new(storage.clone());
+    let keywords_service = keywords::KeywordService::new(storage.clone());
+    let groups_service = groups::GroupService::new(storage.clone());
+    let calls_service = calls::CallService::new(storage.clone());
+    let announcements_service = announcements::AnnouncementService::new(storage.clone());
+    let legal_holds_service = legal_holds::LegalHoldService::new(storage.clone());
+    let mutes_service = mutes::MuteService::new(storage.clone());
+    let moderation_cases_service = moderation_cases::ModerationCaseService::new(storage.clone());
+    let access_requests_service = access_requests::AccessRequestService::new(storage.clone());
+    let recurring_posts_service = recurring_posts::RecurringPostService::new(storage.clone());
+    let search_service = search::SearchService::new(storage.clone());
+    let dms_service = dms::DmService::new(storage.clone());
+    let webhook_subscriptions = event_subscriptions::WebhookSubscriptions::new(storage.clone());
+    let metrics = Arc::new(observability::AppMetrics::default());
+    let realtime_hub = realtime::RealtimeHub::new(
+        config.redis_url.as_deref(),
+        Duration::from_secs(config.presence_away_secs),
+        metrics.clone(),
+    );
+    let email_service =
+        email::EmailService::from_config(&config, metrics.clone()).map_err(invalid)?;
+    let maintenance =
+        admin::MaintenanceMode::new(config.maintenance_mode, config.maintenance_message.clone());
     let users_service = users::UserService::new(storage.clone());
-    let workspaces_service = workspaces::WorkspaceService::new(storage.clone());
-    let metrics = observability::AppMetrics::default();
-    AppState {
+    let workspaces_service =
+        workspaces::WorkspaceService::new(storage.clone(), channel_templates.clone());
+    Ok(AppState {
         config: Arc::new(config),
         storage,
         auth: Arc::new(auth_service),
         channels: Arc::new(channels_service),
+        channel_links: Arc::new(channel_links_service),
+        channel_templates,
+        groups: Arc::new(groups_service),
+        calls: Arc::new(calls_service),
         attachments: Arc::new(attachments_service),
         audit: Arc::new(audit_service),
         rate_limit: Arc::new(rate_limit_service),
         reactions: Arc::new(reactions_service),
+        moderation: Arc::new(moderation_pipeline),
+        notifications: Arc::new(notifications_service),
+        email: Arc::new(email_service),
+        keywords: Arc::new(keywords_service),
+        announcements: Arc::new(announcements_service),
+        legal_holds: Arc::new(legal_holds_service),
+        mutes: Arc::new(mutes_service),
+        moderation_cases: Arc::new(moderation_cases_service),
+        access_requests: Arc::new(access_requests_service),
+        recurring_posts: Arc::new(recurring_posts_service),
+        search: Arc::new(search_service),
+        dms: Arc::new(dms_service),
+        webhook_subscriptions: Arc::new(webhook_subscriptions),
         realtime: Arc::new(realtime_hub),
         users: Arc::new(users_service),
         workspaces: Arc::new(workspaces_service),
-        metrics: Arc::new(metrics),
-    }
+        metrics,
+        log_filter,
+        maintenance: Arc::new(maintenance),
+        graphql: Arc::new(graphql::schema()),
+    })
 }
 
 pub fn router(state: AppState) -> Router {
-    let metrics_enabled = state.config.metrics_enabled;
+    let internal_listener = state.config.internal_port.is_some();
+    let metrics_enabled = state.config.metrics_enabled && !internal_listener;
     let metrics_state = state.clone();
+    let usage_state = state.clone();
+    let cors = cors_layer(&state.config);
     let mut router = Router::new()
         .route("/api/v1/health", get(health))
         .route("/api/v1/ready", get(ready))
         .route("/api/v1/openapi.json", get(openapi_spec))
         .merge(auth::router())
+        .merge(password_reset::router())
+        .merge(email_verification::router())
+        .merge(usage::router())
+        .merge(sessions::router())
+        .merge(presence::router())
         .merge(channels::router())
+        .merge(search::router())
+        .merge(dms::router())
         .merge(attachments::router())
         .merge(threads::router())
+        .merge(activity::router())
+        .merge(sync::router())
+        .merge(notifications::router())
+        .merge(keywords::router())
+        .merge(groups::router())
+        .merge(calls::router())
+        .merge(e2ee::router())
+        .merge(ip_allowlists::router())
+        .merge(session_limits::router())
+        .merge(event_subscriptions::router())
+        .merge(apps::router())
+        .merge(announcements::router())
+        .merge(legal_holds::router())
+        .merge(mutes::router())
+        .merge(moderation_cases::router())
+        .merge(access_requests::router())
+        .merge(channel_links::router())
+        .merge(reactions::router())
+        .merge(reminders::router())
+        .merge(recurring_posts::router())
         .merge(audit::router())
         .merge(realtime::router())
         .merge(users::router())
-        .merge(workspaces::router());
+        .merge(workspaces::router())
+        .merge(v2_routes());
 
+    if !internal_listener {
+        router = router.merge(admin::router());
+        if state.config.operator_token.is_some() {
+            router = router.merge(operator::router());
+        }
+    }
     if metrics_enabled {
         router = router.route("/api/v1/metrics", get(observability::metrics_handler));
     }
+    if state.config.graphql_enabled {
+        router = router.merge(graphql::router());
+    }
+    if state.config.api_docs_enabled {
+        router = router.route("/api/v1/docs", get(api_docs_page));
+    }
 
-    router
+    let maintenance_state = state.clone();
+    let router = router
         .with_state(state)
+        .layer(middleware::from_fn_with_state(
+            maintenance_state,
+            admin::maintenance_middleware,
+        ))
+        .layer(middleware::from_fn(errors::problem_details_middleware))
+        .layer(middleware::from_fn_with_state(
+            usage_state,
+            usage::usage_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             metrics_state,
             observability::metrics_middleware,
         ))
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(middleware::from_fn(observability::request_id_middleware));
+
+    match cors {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
 }
 
+/// Routes served on `INTERNAL_PORT` when it is configured: probes, metrics and
+/// instance administration, kept off the public listener.
+pub fn internal_router(state: AppState) -> Router {
+    let mut router = Router::new()
+        .route("/api/v1/health", get(health))
+        .route("/api/v1/ready", get(ready))
+        .merge(admin::router());
+
+    if state.config.operator_token.is_some() {
+        router = router.merge(operator::router());
+    }
+    if state.config.metrics_enabled {
+        router = router.route("/api/v1/metrics", get(observability::metrics_handler));
+    }
+
+    router
+        .with_state(state)
+        .layer(middleware::from_fn(errors::problem_details_middleware))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(middleware::from_fn(observability::request_id_middleware))
+}
+
+fn request_span(request: &Request<Body>) -> tracing::Span {
+    let request_id = request
+        .headers()
+        .get(observability::REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    tracing::debug_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        request_id = %request_id,
+        user_id = tracing::field::Empty,
+        workspace_id = tracing::field::Empty,
+    )
+}
+
+fn cors_layer(config: &Config) -> Option<CorsLayer> {
+    if !config.cors_enabled {
+        return None;
+    }
+    let origins = if config
+        .cors_allowed_origins
+        .iter()
+        .any(|origin| origin == "*")
+    {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config
+                .cors_allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    let methods = config
+        .cors_allowed_methods
+        .iter()
+        .filter_map(|method| Method::from_bytes(method.as_bytes()).ok())
+        .collect::<Vec<_>>();
+    let headers = config
+        .cors_allowed_headers
+        .iter()
+        .filter_map(|name| HeaderName::from_bytes(name.as_bytes()).ok())
+        .collect::<Vec<_>>();
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(config.cors_allow_credentials)
+            .expose_headers([
+                HeaderName::from_static(observability::REQUEST_ID_HEADER),
+                header::ETAG,
+            ])
+            .max_age(Duration::from_secs(config.cors_max_age_secs)),
+    )
+}
+
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Serialize, ToSchema)]
 struct HealthResponse {
     status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_version: Option<ApiVersion>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ReadinessResponse {
+    status: &'static str,
+    checks: BTreeMap<String, DependencyCheck>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct DependencyCheck {
+    status: &'static str,
+    error: Option<String>,
+}
+
+impl DependencyCheck {
+    fn is_down(&self) -> bool {
+        self.status == "down"
+    }
 }
 
 #[utoipa::path(
     get,
     path = "/api/v1/health",
+    operation_id = "health",
+    tag = "system",
+    security(()),
     responses(
         (status = 200, description = "Service health", body = HealthResponse)
     )
 )]
-async fn health() -> Json<HealthResponse> {
-    Json(HealthResponse { status: "ok" })
+async fn health(version: ApiVersion) -> Json<HealthResponse> {
+    let api_version = (version != ApiVersion::V1).then_some(version);
+    Json(HealthResponse {
+        status: "ok",
+        api_version,
+    })
+}
+
+fn v2_routes() -> Router<AppState> {
+    Router::new().route("/api/v2/health", get(health))
 }
 
 #[utoipa::path(
     get,
     path = "/api/v1/ready",
+    operation_id = "ready",
+    tag = "system",
+    security(()),
     responses(
-        (status = 200, description = "Service readiness", body = HealthResponse)
+        (status = 200, description = "Service readiness", body = ReadinessResponse),
+        (status = 503, description = "One or more dependencies are down", body = ReadinessResponse)
     )
 )]
-async fn ready(State(_state): State<AppState>) -> Json<HealthResponse> {
-    Json(HealthResponse { status: "ready" })
+async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let (mongo, redis, s3) = tokio::join!(
+        check_dependency(state.storage.ping()),
+        check_dependency(state.realtime.ping_redis()),
+        check_dependency(state.attachments.ping_object_storage()),
+    );
+    let checks = BTreeMap::from([
+        ("mongo".to_string(), mongo),
+        ("redis".to_string(), redis),
+        ("s3".to_string(), s3),
+    ]);
+
+    if checks.values().any(DependencyCheck::is_down) {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse {
+                status: "not_ready",
+                checks,
+            }),
+        )
+    } else {
+        (
+            StatusCode::OK,
+            Json(ReadinessResponse {
+                status: "ready",
+                checks,
+            }),
+        )
+    }
 }
 
+async fn check_dependency(
+    probe: impl Future<Output = Option<Result<(), String>>>,
+) -> DependencyCheck {
+    match timeout(READINESS_CHECK_TIMEOUT, probe).await {
+        Ok(None) => DependencyCheck {
+            status: "disabled",
+            error: None,
+        },
+        Ok(Some(Ok(()))) => DependencyCheck {
+            status: "up",
+            error: None,
+        },
+        Ok(Some(Err(error))) => DependencyCheck {
+            status: "down",
+            error: Some(error),
+        },
+        Err(_) => DependencyCheck {
+            status: "down",
+            error: Some(format!(
+                "timed out after {}ms",
+                READINESS_CHECK_TIMEOUT.as_millis()
+            )),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/openapi.json",
+    operation_id = "openapi_spec",
+    tag = "system",
+    security(()),
+    responses(
+        (status = 200, description = "This OpenAPI document", body = Object)
+    )
+)]
 async fn openapi_spec() -> Json<utoipa::openapi::OpenApi> {
     Json(ApiDoc::openapi())
 }
 
+const API_DOCS_HTML: &str = r##"<!doctype html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Galynx API docs</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.ui = SwaggerUIBundle({
+      url: "/api/v1/openapi.json",
+      dom_id: "#swagger-ui",
+      persistAuthorization: true,
+    });
+  </script>
+</body>
+</html>
+"##;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/docs",
+    operation_id = "api_docs_page",
+    tag = "system",
+    security(()),
+    responses(
+        (status = 200, description = "Swagger UI for this document", content_type = "text/html", body = String)
+    )
+)]
+async fn api_docs_page() -> Html<&'static str> {
+    Html(API_DOCS_HTML)
+}
+
 #[derive(OpenApi)]
 #[openapi(
     paths(
         health,
         ready,
+        openapi_spec,
+        api_docs_page,
         crate::observability::metrics_handler,
+        crate::graphql::graphql,
         crate::auth::login,
         crate::auth::refresh,
         crate::auth::logout,
         crate::auth::me,
+        crate::usage::my_usage,
+        crate::sessions::list_sessions,
+        crate::sessions::revoke_session,
+        crate::password_reset::forgot_password,
+        crate::password_reset::reset_password,
+        crate::email_verification::request_email_verification,
+        crate::email_verification::verify_email,
         crate::channels::list_channels,
         crate::channels::create_channel,
+        crate::channels::update_channel,
         crate::channels::delete_channel,
         crate::channels::list_channel_members,
         crate::channels::add_channel_member,
+        crate::channels::update_channel_member,
         crate::channels::remove_channel_member,
+        crate::channels::update_read_marker,
         crate::channels::list_messages,
         crate::channels::create_message,
+        crate::channels::crosspost_message,
         crate::channels::update_message,
         crate::channels::delete_message,
+        crate::search::search_messages,
+        crate::dms::open_dm,
+        crate::dms::list_dms,
+        crate::dms::list_dm_messages,
+        crate::dms::create_dm_message,
         crate::threads::get_thread,
         crate::threads::list_replies,
         crate::threads::create_reply,
@@ -157,28 +568,216 @@ async fn openapi_spec() -> Json<utoipa::openapi::OpenApi> {
         crate::realtime::ws_upgrade,
         crate::users::list_users,
         crate::users::create_user,
+        crate::users::deactivate_user,
+        crate::activity::list_activity,
+        crate::sync::sync,
+        crate::notifications::list_notifications,
+        crate::notifications::mark_notifications_read,
+        crate::notifications::clear_notifications,
+        crate::keywords::list_keywords,
+        crate::keywords::create_keyword,
+        crate::keywords::delete_keyword,
+        crate::groups::list_groups,
+        crate::groups::create_group,
+        crate::groups::update_group,
+        crate::groups::delete_group,
+        crate::groups::add_channel_group,
+        crate::calls::get_call,
+        crate::calls::start_call,
+        crate::calls::join_call,
+        crate::calls::leave_call,
+        crate::calls::end_call,
+        crate::calls::signal_call,
+        crate::e2ee::update_public_key,
+        crate::e2ee::get_channel_keys,
+        crate::e2ee::distribute_channel_keys,
+        crate::ip_allowlists::get_ip_allowlist,
+        crate::ip_allowlists::update_ip_allowlist,
+        crate::session_limits::get_session_limits,
+        crate::presence::get_presence,
+        crate::session_limits::update_session_limits,
+        crate::event_subscriptions::list_event_subscriptions,
+        crate::event_subscriptions::create_event_subscription,
+        crate::event_subscriptions::delete_event_subscription,
+        crate::apps::list_apps,
+        crate::apps::install_app,
+        crate::apps::get_app,
+        crate::apps::uninstall_app,
+        crate::reminders::create_reminder,
+        crate::reminders::list_reminders,
+        crate::reminders::cancel_reminder,
+        crate::recurring_posts::list_recurring_posts,
+        crate::recurring_posts::create_recurring_post,
+        crate::recurring_posts::update_recurring_post,
+        crate::recurring_posts::pause_recurring_post,
+        crate::recurring_posts::resume_recurring_post,
+        crate::recurring_posts::delete_recurring_post,
+        crate::announcements::list_announcements,
+        crate::announcements::create_announcement,
+        crate::announcements::delete_announcement,
+        crate::legal_holds::list_legal_holds,
+        crate::legal_holds::create_legal_hold,
+        crate::legal_holds::release_legal_hold,
+        crate::mutes::list_mutes,
+        crate::mutes::mute_user,
+        crate::mutes::unmute_user,
+        crate::moderation_cases::report_message,
+        crate::moderation_cases::moderation_queue,
+        crate::moderation_cases::resolve_case,
+        crate::access_requests::request_access,
+        crate::access_requests::list_access_requests,
+        crate::access_requests::decide_access_request,
+        crate::channel_links::list_channel_links,
+        crate::channel_links::create_channel_link,
+        crate::channel_links::approve_channel_link,
+        crate::channel_links::delete_channel_link,
+        crate::reactions::list_reactions,
+        crate::reactions::add_reaction,
+        crate::reactions::remove_reaction,
         crate::workspaces::list_workspaces,
         crate::workspaces::create_workspace,
         crate::workspaces::list_workspace_members,
-        crate::workspaces::onboard_workspace_member
+        crate::workspaces::onboard_workspace_member,
+        crate::workspaces::get_message_limits,
+        crate::workspaces::update_message_limits,
+        crate::admin::get_log_level,
+        crate::admin::update_log_level,
+        crate::admin::reload_config,
+        crate::admin::inspect_rate_limits,
+        crate::admin::get_maintenance,
+        crate::admin::update_maintenance,
+        crate::admin::storage_stats,
+        crate::admin::workspace_stats,
+        crate::admin::workspace_analytics,
+        crate::admin::workspace_usage,
+        crate::admin::retention_purge,
+        crate::admin::attachments_gc,
+        crate::admin::anonymize,
+        crate::operator::list_workspaces,
+        crate::operator::get_workspace,
+        crate::operator::update_suspension
     ),
     components(
         schemas(
             HealthResponse,
+            ApiVersion,
+            ReadinessResponse,
+            DependencyCheck,
             crate::auth::LoginRequest,
             crate::auth::RefreshRequest,
             crate::auth::LogoutRequest,
             crate::auth::AuthTokensResponse,
             crate::auth::MeResponse,
             crate::auth::WorkspaceRole,
+            crate::password_reset::ForgotPasswordRequest,
+            crate::password_reset::ResetPasswordRequest,
+            crate::email_verification::VerifyEmailRequest,
             crate::channels::CreateChannelRequest,
+            crate::channels::UpdateChannelRequest,
             crate::channels::ChannelResponse,
             crate::channels::ChannelMemberResponse,
             crate::channels::AddChannelMemberRequest,
+            crate::channels::ChannelMemberRole,
+            crate::channels::UpdateChannelMemberRequest,
             crate::channels::CreateMessageRequest,
             crate::channels::UpdateMessageRequest,
+            crate::channels::CrosspostMessageRequest,
+            crate::channels::CrosspostMessageResponse,
             crate::channels::MessageResponse,
+            crate::channels::MessageReactionSummary,
+            crate::channels::ActivityKind,
+            crate::channels::ActivityItemResponse,
+            crate::channels::ActivityListResponse,
+            crate::channels::UpdateReadMarkerRequest,
+            crate::channels::ReadMarkerResponse,
+            crate::channels::MembershipChangeKind,
+            crate::channels::MembershipChangeResponse,
+            crate::channels::SyncResponse,
+            crate::notifications::NotificationKind,
+            crate::notifications::NotificationResponse,
+            crate::keywords::CreateKeywordRequest,
+            crate::keywords::KeywordSubscriptionResponse,
+            crate::keywords::KeywordListResponse,
+            crate::groups::CreateGroupRequest,
+            crate::groups::UpdateGroupRequest,
+            crate::groups::GroupResponse,
+            crate::groups::GroupListResponse,
+            crate::groups::AddChannelGroupRequest,
+            crate::groups::ChannelGroupAddedResponse,
+            crate::calls::StartCallRequest,
+            crate::calls::CallSignalRequest,
+            crate::calls::CallResponse,
+            crate::e2ee::UpdatePublicKeyRequest,
+            crate::e2ee::PublicKeyResponse,
+            crate::e2ee::E2eeMemberResponse,
+            crate::e2ee::WrappedKeyResponse,
+            crate::e2ee::ChannelKeysResponse,
+            crate::e2ee::WrappedKeyInput,
+            crate::e2ee::DistributeChannelKeysRequest,
+            crate::ip_allowlists::UpdateIpAllowlistRequest,
+            crate::ip_allowlists::IpAllowlistResponse,
+            crate::session_limits::SessionLimits,
+            crate::presence::PresenceStatus,
+            crate::presence::PresenceResponse,
+            crate::presence::PresenceListResponse,
+            crate::session_limits::UpdateSessionLimitsRequest,
+            crate::event_subscriptions::EventDelivery,
+            crate::event_subscriptions::CreateEventSubscriptionRequest,
+            crate::event_subscriptions::EventSubscriptionResponse,
+            crate::event_subscriptions::EventSubscriptionListResponse,
+            crate::apps::AppManifest,
+            crate::apps::SlashCommand,
+            crate::apps::BotUserManifest,
+            crate::apps::AppResponse,
+            crate::apps::AppListResponse,
+            crate::apps::BotCredentials,
+            crate::apps::InstallAppResponse,
+            crate::reminders::CreateReminderRequest,
+            crate::reminders::ReminderResponse,
+            crate::reminders::ReminderListResponse,
+            crate::recurring_posts::CreateRecurringPostRequest,
+            crate::recurring_posts::UpdateRecurringPostRequest,
+            crate::recurring_posts::RecurringPostResponse,
+            crate::recurring_posts::RecurringPostListResponse,
+            crate::announcements::AnnouncementSeverity,
+            crate::announcements::CreateAnnouncementRequest,
+            crate::announcements::AnnouncementResponse,
+            crate::announcements::AnnouncementListResponse,
+            crate::legal_holds::LegalHoldScope,
+            crate::legal_holds::CreateLegalHoldRequest,
+            crate::legal_holds::LegalHoldResponse,
+            crate::legal_holds::LegalHoldListResponse,
+            crate::mutes::MuteUserRequest,
+            crate::mutes::MuteResponse,
+            crate::mutes::MuteListResponse,
+            crate::moderation_cases::ReportCategory,
+            crate::moderation_cases::CaseStatus,
+            crate::moderation_cases::ReportMessageRequest,
+            crate::moderation_cases::ModerationCaseResponse,
+            crate::moderation_cases::CaseAction,
+            crate::moderation_cases::ModerationQueueItem,
+            crate::moderation_cases::ModerationQueueResponse,
+            crate::moderation_cases::ResolveCaseRequest,
+            crate::access_requests::AccessRequestStatus,
+            crate::access_requests::AccessDecision,
+            crate::access_requests::RequestAccessRequest,
+            crate::access_requests::AccessDecisionRequest,
+            crate::access_requests::AccessRequestResponse,
+            crate::access_requests::AccessRequestListResponse,
+            crate::channel_links::ChannelLinkStatus,
+            crate::channel_links::CreateChannelLinkRequest,
+            crate::channel_links::ChannelLinkResponse,
+            crate::channel_links::ChannelLinkListResponse,
+            crate::notifications::NotificationListResponse,
+            crate::notifications::MarkNotificationsReadRequest,
+            crate::notifications::NotificationsUpdatedResponse,
+            crate::notifications::NotificationsClearedResponse,
             crate::channels::MessageListResponse,
+            crate::dms::OpenDmRequest,
+            crate::dms::DmConversationResponse,
+            crate::dms::DmConversationListResponse,
+            crate::dms::DmMessageResponse,
+            crate::dms::DmMessageListResponse,
             crate::channels::ThreadSummaryResponse,
             crate::attachments::PresignRequest,
             crate::attachments::PresignResponse,
@@ -188,6 +787,8 @@ async fn openapi_spec() -> Json<utoipa::openapi::OpenApi> {
             crate::audit::AuditLogResponse,
             crate::audit::AuditListResponse,
             crate::reactions::ReactionUpdateResponse,
+            crate::reactions::ReactionSummaryResponse,
+            crate::reactions::AddReactionRequest,
             crate::realtime::WsEventEnvelope,
             crate::users::CreateUserRequest,
             crate::users::UserResponse,
@@ -195,15 +796,177 @@ async fn openapi_spec() -> Json<utoipa::openapi::OpenApi> {
             crate::workspaces::CreateWorkspaceRequest,
             crate::workspaces::WorkspaceMemberResponse,
             crate::workspaces::OnboardWorkspaceMemberRequest,
-            crate::errors::ErrorResponse
+            crate::channels::MessageLimits,
+            crate::channels::UpdateMessageLimitsRequest,
+            crate::admin::LogLevelResponse,
+            crate::admin::UpdateLogLevelRequest,
+            crate::admin::RuntimeConfigResponse,
+            crate::rate_limit::RateLimitInspectionResponse,
+            crate::rate_limit::RateLimitBucketResponse,
+            crate::rate_limit::RateLimitRejectionResponse,
+            crate::admin::MaintenanceResponse,
+            crate::admin::UpdateMaintenanceRequest,
+            crate::admin::StorageStatsResponse,
+            crate::admin::CollectionStatsResponse,
+            crate::admin::WorkspaceStatsResponse,
+            crate::analytics::WorkspaceAnalyticsResponse,
+            crate::analytics::DailyAnalyticsResponse,
+            crate::usage::MyUsageResponse,
+            crate::usage::DailyUsageResponse,
+            crate::usage::WorkspaceUsageResponse,
+            crate::usage::UserUsageResponse,
+            crate::sessions::SessionResponse,
+            crate::sessions::SessionListResponse,
+            crate::analytics::TopChannelResponse,
+            crate::admin::RetentionPurgeRequest,
+            crate::admin::RetentionPurgeResponse,
+            crate::admin::AttachmentGcRequest,
+            crate::operator::OperatorWorkspaceResponse,
+            crate::operator::UsageTotalsResponse,
+            crate::operator::OperatorWorkspaceListResponse,
+            crate::operator::OperatorMemberResponse,
+            crate::operator::OperatorChannelResponse,
+            crate::operator::OperatorWorkspaceDetailResponse,
+            crate::operator::UpdateSuspensionRequest,
+            crate::anonymize::AnonymizeRequest,
+            crate::anonymize::AnonymizeReport,
+            crate::attachments::AttachmentGcResponse,
+            crate::config::RateLimitSettings,
+            crate::errors::ErrorResponse,
+            crate::errors::ErrorCode,
+            crate::errors::ProblemDetails
         )
     ),
     tags(
         (name = "system", description = "System and health endpoints"),
         (name = "auth", description = "Authentication and identity"),
-        (name = "channels", description = "Channels and messages"),
+        (name = "workspaces", description = "Workspaces, members and workspace policies"),
+        (name = "users", description = "Workspace users"),
+        (name = "channels", description = "Channels and channel membership"),
+        (name = "messages", description = "Channel messages"),
+        (name = "dms", description = "Direct message conversations"),
+        (name = "threads", description = "Message threads"),
+        (name = "reactions", description = "Message reactions"),
         (name = "attachments", description = "File attachments"),
-        (name = "audit", description = "Audit log")
-    )
+        (name = "activity", description = "Mentions and replies feed"),
+        (name = "sync", description = "Offline sync"),
+        (name = "notifications", description = "Notification inbox"),
+        (name = "keywords", description = "Keyword alerts"),
+        (name = "groups", description = "User groups"),
+        (name = "reminders", description = "Message reminders"),
+        (name = "recurring_posts", description = "Messages posted on a schedule"),
+        (name = "calls", description = "Call signaling"),
+        (name = "e2ee", description = "End-to-end encrypted channels"),
+        (name = "channel_links", description = "Channels shared between workspaces"),
+        (name = "announcements", description = "Workspace announcements"),
+        (name = "legal_holds", description = "Legal holds"),
+        (name = "moderation", description = "Member mutes, reports and moderation cases"),
+        (name = "event_subscriptions", description = "Event subscriptions for bots and integrations"),
+        (name = "apps", description = "Installed third-party apps"),
+        (name = "realtime", description = "WebSocket realtime"),
+        (name = "graphql", description = "Read-only GraphQL"),
+        (name = "audit", description = "Audit log"),
+        (name = "admin", description = "Workspace and instance administration"),
+        (name = "operator", description = "Multi-tenant operator API")
+    ),
+    modifiers(&SecuritySchemes)
 )]
 struct ApiDoc;
+
+/// `bearer_auth` (user access token) applies to every operation unless it
+/// declares its own `security`.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        for (name, description) in [
+            ("operator_token", "OPERATOR_TOKEN of the instance"),
+            ("metrics_token", "METRICS_TOKEN, when configured"),
+        ] {
+            components.add_security_scheme(
+                name,
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .description(Some(description))
+                        .build(),
+                ),
+            );
+        }
+        openapi.security = Some(vec![SecurityRequirement::new(
+            "bearer_auth",
+            Vec::<String>::new(),
+        )]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn check_dependency_reports_disabled_up_and_down() {
+        assert_eq!(check_dependency(async { None }).await.status, "disabled");
+        assert_eq!(check_dependency(async { Some(Ok(())) }).await.status, "up");
+
+        let failed = check_dependency(async { Some(Err("boom".to_string())) }).await;
+        assert!(failed.is_down());
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn openapi_operations_have_unique_ids_and_declared_tags() {
+        let doc = ApiDoc::openapi();
+        let declared = doc
+            .tags
+            .iter()
+            .flatten()
+            .map(|tag| tag.name.clone())
+            .collect::<std::collections::HashSet<_>>();
+        let schemes = doc
+            .components
+            .as_ref()
+            .map(|components| components.security_schemes.clone())
+            .unwrap_or_default();
+        let mut ids = std::collections::HashSet::new();
+        for (path, item) in &doc.paths.paths {
+            for operation in [&item.get, &item.post, &item.put, &item.patch, &item.delete]
+                .into_iter()
+                .flatten()
+            {
+                let id = operation.operation_id.clone().unwrap_or_default();
+                assert!(
+                    ids.insert(id.clone()),
+                    "duplicate operationId {id} at {path}"
+                );
+                let tags = operation.tags.clone().unwrap_or_default();
+                assert_eq!(tags.len(), 1, "{id} should have one tag");
+                assert!(
+                    declared.contains(&tags[0]),
+                    "{id} uses undeclared tag {}",
+                    tags[0]
+                );
+                for requirement in operation.security.iter().flatten() {
+                    let value = serde_json::to_value(requirement).unwrap_or_default();
+                    for name in value.as_object().into_iter().flat_map(|map| map.keys()) {
+                        assert!(
+                            schemes.contains_key(name),
+                            "{id} uses unknown scheme {name}"
+                        );
+                    }
+                }
+            }
+        }
+        assert!(ids.contains("list_workspaces") && ids.contains("operator_list_workspaces"));
+    }
+}
@@ -0,0 +1,608 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+};
+use chrono::Utc;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    event_subscriptions,
+    storage::{AppInstallationRecordStore, SlashCommandRecordStore},
+    users::CreateUserRequest,
+};
+
+const KNOWN_SCOPES: &[&str] = &[
+    "channels:read",
+    "channels:write",
+    "messages:read",
+    "messages:write",
+    "reactions:write",
+    "files:write",
+    "users:read",
+    "commands",
+];
+const MAX_APP_NAME_LEN: usize = 80;
+const MAX_DESCRIPTION_LEN: usize = 500;
+const MAX_SLASH_COMMANDS: usize = 25;
+const MAX_COMMAND_LEN: usize = 32;
+const MAX_COMMAND_DESCRIPTION_LEN: usize = 200;
+const BOT_EMAIL_DOMAIN: &str = "apps.galynx.local";
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AppManifest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Permissions the app declares. They are recorded for review only: the
+    /// bot user acts with the permissions of a regular member.
+    pub scopes: Vec<String>,
+    /// `https` endpoint that receives the events listed in `events`.
+    pub webhook_url: Option<String>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub slash_commands: Vec<SlashCommand>,
+    pub bot_user: BotUserManifest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SlashCommand {
+    /// Leading `/` followed by lowercase letters, digits, `-` or `_`.
+    pub command: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct BotUserManifest {
+    /// Display name of the bot user; defaults to the app name.
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AppResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub scopes: Vec<String>,
+    pub webhook_url: Option<String>,
+    pub event_subscription_id: Option<Uuid>,
+    pub slash_commands: Vec<SlashCommand>,
+    pub bot_user_id: Uuid,
+    pub installed_by: Uuid,
+    pub installed_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AppListResponse {
+    pub items: Vec<AppResponse>,
+}
+
+/// Credentials for the bot user; only returned by the install call.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BotCredentials {
+    pub user_id: Uuid,
+    pub email: String,
+    pub password: String,
+    /// Signing secret of the app's webhook, when it has one.
+    pub webhook_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InstallAppResponse {
+    pub app: AppResponse,
+    pub credentials: BotCredentials,
+}
+
+impl From<&AppInstallationRecordStore> for AppResponse {
+    fn from(record: &AppInstallationRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            name: record.name.clone(),
+            description: record.description.clone(),
+            scopes: record.scopes.clone(),
+            webhook_url: record.webhook_url.clone(),
+            event_subscription_id: record.event_subscription_id,
+            slash_commands: record
+                .slash_commands
+                .iter()
+                .map(|command| SlashCommand {
+                    command: command.command.clone(),
+                    description: command.description.clone(),
+                })
+                .collect(),
+            bot_user_id: record.bot_user_id,
+            installed_by: record.installed_by,
+            installed_at: record.installed_at,
+        }
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/apps", get(list_apps).post(install_app))
+        .route("/api/v1/apps/:id", get(get_app).delete(uninstall_app))
+}
+
+fn ensure_app_admin(context: &AuthContext) -> ApiResult<()> {
+    if !matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin) {
+        return Err(ApiError::Forbidden(
+            "you do not have permission to manage apps".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn is_valid_command(value: &str) -> bool {
+    value.strip_prefix('/').is_some_and(|name| {
+        !name.is_empty()
+            && name.len() <= MAX_COMMAND_LEN
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-' || ch == '_')
+    })
+}
+
+/// Manifest fields after trimming, deduplication and defaults.
+struct ValidatedManifest {
+    name: String,
+    description: Option<String>,
+    bot_name: String,
+    scopes: Vec<String>,
+    webhook: Option<(String, Vec<String>)>,
+    slash_commands: Vec<SlashCommandRecordStore>,
+}
+
+fn validate_manifest(manifest: &AppManifest) -> ApiResult<ValidatedManifest> {
+    let mut errors = FieldErrors::default();
+    let name = manifest.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_APP_NAME_LEN {
+        errors.add(
+            "name",
+            format!("must have between 1 and {MAX_APP_NAME_LEN} characters"),
+        );
+    }
+    let description = manifest
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToString::to_string);
+    if description
+        .as_ref()
+        .is_some_and(|value| value.chars().count() > MAX_DESCRIPTION_LEN)
+    {
+        errors.add(
+            "description",
+            format!("must have at most {MAX_DESCRIPTION_LEN} characters"),
+        );
+    }
+    let bot_name = manifest
+        .bot_user
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(&name)
+        .to_string();
+    if bot_name.chars().count() > MAX_APP_NAME_LEN {
+        errors.add(
+            "bot_user.name",
+            format!("must have at most {MAX_APP_NAME_LEN} characters"),
+        );
+    }
+
+    let mut scopes: Vec<String> = Vec::with_capacity(manifest.scopes.len());
+    if manifest.scopes.is_empty() {
+        errors.add("scopes", "must request at least one scope");
+    }
+    for (index, scope) in manifest.scopes.iter().enumerate() {
+        let scope = scope.trim();
+        if !KNOWN_SCOPES.contains(&scope) {
+            errors.add(
+                &format!("scopes[{index}]"),
+                format!("must be one of {}", KNOWN_SCOPES.join(", ")),
+            );
+        } else if !scopes.iter().any(|existing| existing == scope) {
+            scopes.push(scope.to_string());
+        }
+    }
+
+    let webhook = match manifest.webhook_url.as_deref().map(str::trim) {
+        Some(url) => {
+            if !event_subscriptions::is_valid_webhook_url(url) {
+                errors.add("webhook_url", "must be an https URL to a public host");
+            }
+            let events =
+                event_subscriptions::parse_event_types(&manifest.events, "events", &mut errors);
+            if manifest.events.is_empty() {
+                errors.add("events", "must list the events sent to webhook_url");
+            }
+            Some((url.to_string(), events))
+        }
+        None => {
+            if !manifest.events.is_empty() {
+                errors.add("events", "requires webhook_url");
+            }
+            None
+        }
+    };
+
+    if manifest.slash_commands.len() > MAX_SLASH_COMMANDS {
+        errors.add(
+            "slash_commands",
+            format!("must contain at most {MAX_SLASH_COMMANDS} entries"),
+        );
+    }
+    if !manifest.slash_commands.is_empty() && !scopes.iter().any(|scope| scope == "commands") {
+        errors.add("scopes", "must include commands to register slash commands");
+    }
+    let mut slash_commands: Vec<SlashCommandRecordStore> = Vec::new();
+    for (index, command) in manifest.slash_commands.iter().enumerate() {
+        let name = command.command.trim();
+        let description = command.description.trim();
+        if !is_valid_command(name) {
+            errors.add(
+                &format!("slash_commands[{index}].command"),
+                "must look like /deploy",
+            );
+        } else if slash_commands
+            .iter()
+            .any(|existing| existing.command == name)
+        {
+            errors.add(
+                &format!("slash_commands[{index}].command"),
+                "is declared more than once",
+            );
+        }
+        if description.chars().count() > MAX_COMMAND_DESCRIPTION_LEN {
+            errors.add(
+                &format!("slash_commands[{index}].description"),
+                format!("must have at most {MAX_COMMAND_DESCRIPTION_LEN} characters"),
+            );
+        }
+        slash_commands.push(SlashCommandRecordStore {
+            command: name.to_string(),
+            description: description.to_string(),
+        });
+    }
+    errors.into_result()?;
+
+    Ok(ValidatedManifest {
+        name,
+        description,
+        bot_name,
+        scopes,
+        webhook,
+        slash_commands,
+    })
+}
+
+fn generate_bot_password() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/apps",
+    operation_id = "list_apps",
+    tag = "apps",
+    responses(
+        (status = 200, description = "Apps installed in the workspace", body = AppListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_apps(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<AppListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mut items = state
+        .storage
+        .list_app_installations(context.workspace_id)
+        .await
+        .iter()
+        .map(AppResponse::from)
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    Ok(Json(AppListResponse { items }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/apps/{id}",
+    operation_id = "get_app",
+    tag = "apps",
+    responses(
+        (status = 200, description = "Installed app", body = AppResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "App not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_app(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(app_id): Path<Uuid>,
+) -> ApiResult<Json<AppResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state
+        .storage
+        .list_app_installations(context.workspace_id)
+        .await
+        .iter()
+        .find(|installation| installation.id == app_id)
+        .map(|installation| Json(AppResponse::from(installation)))
+        .ok_or_else(|| ApiError::NotFound("app not found".to_string()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/apps",
+    operation_id = "install_app",
+    tag = "apps",
+    request_body = AppManifest,
+    responses(
+        (status = 201, description = "App installed and bot user provisioned", body = InstallAppResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Slash command already registered", body = ErrorResponse),
+        (status = 422, description = "Invalid manifest", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn install_app(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(manifest): Json<AppManifest>,
+) -> ApiResult<(StatusCode, Json<InstallAppResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_app_admin(&context)?;
+    let manifest = validate_manifest(&manifest)?;
+    let installed = state
+        .storage
+        .list_app_installations(context.workspace_id)
+        .await;
+    if let Some(taken) = manifest.slash_commands.iter().find(|command| {
+        installed.iter().any(|installation| {
+            installation
+                .slash_commands
+                .iter()
+                .any(|existing| existing.command == command.command)
+        })
+    }) {
+        return Err(ApiError::Conflict(format!(
+            "slash command {} is already registered by another app",
+            taken.command
+        )));
+    }
+
+    let installation_id = Uuid::new_v4();
+    let password = generate_bot_password();
+    let bot = state
+        .users
+        .create_user(
+            context.workspace_id,
+            CreateUserRequest {
+                email: format!("app-{}@{BOT_EMAIL_DOMAIN}", installation_id.simple()),
+                name: manifest.bot_name,
+                password: password.clone(),
+                role: WorkspaceRole::Member,
+            },
+        )
+        .await?;
+    let subscription = manifest.webhook.map(|(url, events)| {
+        event_subscriptions::webhook_subscription(context.workspace_id, bot.id, events, url)
+    });
+    if let Some(subscription) = &subscription {
+        state
+            .storage
+            .put_event_subscription(subscription.clone())
+            .await;
+        state
+            .webhook_subscriptions
+            .invalidate(context.workspace_id)
+            .await;
+    }
+
+    let record = AppInstallationRecordStore {
+        id: installation_id,
+        workspace_id: context.workspace_id,
+        name: manifest.name,
+        description: manifest.description,
+        scopes: manifest.scopes,
+        webhook_url: subscription
+            .as_ref()
+            .and_then(|subscription| subscription.webhook_url.clone()),
+        event_subscription_id: subscription.as_ref().map(|subscription| subscription.id),
+        slash_commands: manifest.slash_commands,
+        bot_user_id: bot.id,
+        installed_by: context.user_id,
+        installed_at: Utc::now().timestamp_millis(),
+    };
+    state.storage.put_app_installation(record.clone()).await;
+    let app = AppResponse::from(&record);
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "APP_INSTALLED",
+            "app",
+            Some(record.id.to_string()),
+            json!({
+                "name": app.name,
+                "requested_scopes": app.scopes,
+                "webhook_url": app.webhook_url,
+                "events": subscription.as_ref().map(|subscription| &subscription.event_types),
+                "slash_commands": app.slash_commands,
+                "bot_user_id": bot.id,
+            }),
+        )
+        .await;
+    Ok((
+        StatusCode::CREATED,
+        Json(InstallAppResponse {
+            app,
+            credentials: BotCredentials {
+                user_id: bot.id,
+                email: bot.email,
+                password,
+                webhook_secret: subscription.and_then(|subscription| subscription.webhook_secret),
+            },
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/apps/{id}",
+    operation_id = "uninstall_app",
+    tag = "apps",
+    responses(
+        (status = 204, description = "App uninstalled and bot user removed"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "App not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn uninstall_app(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(app_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_app_admin(&context)?;
+    let installation = state
+        .storage
+        .list_app_installations(context.workspace_id)
+        .await
+        .into_iter()
+        .find(|installation| installation.id == app_id)
+        .ok_or_else(|| ApiError::NotFound("app not found".to_string()))?;
+
+    if let Some(subscription_id) = installation.event_subscription_id {
+        state
+            .storage
+            .remove_event_subscription(
+                context.workspace_id,
+                installation.bot_user_id,
+                subscription_id,
+            )
+            .await;
+        state
+            .webhook_subscriptions
+            .invalidate(context.workspace_id)
+            .await;
+    }
+    match state
+        .users
+        .deactivate_user(
+            context.workspace_id,
+            context.user_id,
+            installation.bot_user_id,
+        )
+        .await
+    {
+        Ok(_) | Err(ApiError::NotFound(_)) => {}
+        Err(error) => return Err(error),
+    }
+    state
+        .storage
+        .remove_app_installation(context.workspace_id, app_id)
+        .await;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "APP_UNINSTALLED",
+            "app",
+            Some(app_id.to_string()),
+            json!({ "name": installation.name, "bot_user_id": installation.bot_user_id }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> AppManifest {
+        AppManifest {
+            name: " Deploy Bot ".to_string(),
+            description: None,
+            scopes: vec!["messages:write".to_string(), "commands".to_string()],
+            webhook_url: Some("https://bot.example.com/events".to_string()),
+            events: vec!["MESSAGE_CREATED".to_string()],
+            slash_commands: vec![SlashCommand {
+                command: "/deploy".to_string(),
+                description: "Ship it".to_string(),
+            }],
+            bot_user: BotUserManifest { name: None },
+        }
+    }
+
+    #[test]
+    fn manifests_are_normalized_and_validated() {
+        let valid = validate_manifest(&manifest()).expect("manifest should be valid");
+        assert_eq!(valid.name, "Deploy Bot");
+        assert_eq!(valid.bot_name, "Deploy Bot");
+        assert_eq!(valid.slash_commands[0].command, "/deploy");
+        assert!(valid.webhook.is_some());
+
+        for invalid in [
+            AppManifest {
+                scopes: vec!["admin:everything".to_string()],
+                ..manifest()
+            },
+            AppManifest {
+                scopes: vec!["messages:write".to_string()],
+                ..manifest()
+            },
+            AppManifest {
+                webhook_url: Some("http://bot.example.com".to_string()),
+                ..manifest()
+            },
+            AppManifest {
+                webhook_url: None,
+                ..manifest()
+            },
+            AppManifest {
+                slash_commands: vec![SlashCommand {
+                    command: "deploy".to_string(),
+                    description: String::new(),
+                }],
+                ..manifest()
+            },
+        ] {
+            assert!(matches!(
+                validate_manifest(&invalid),
+                Err(ApiError::Validation(_))
+            ));
+        }
+    }
+}
@@ -1,4 +1,11 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 use aws_config::{BehaviorVersion, Region, meta::region::RegionProviderChain};
 use aws_credential_types::Credentials;
@@ -8,7 +15,8 @@ use aws_sdk_s3::{
 use axum::{
     Json, Router,
     extract::{Path, State},
-    http::HeaderMap,
+    http::{HeaderMap, StatusCode},
+    response::Response,
     routing::{get, post},
 };
 use chrono::Utc;
@@ -21,11 +29,12 @@ use crate::{
     app::AppState,
     auth::AuthContext,
     config::Config,
-    errors::{ApiError, ApiResult, ErrorResponse},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    idempotency,
+    legal_holds::LegalHolds,
     storage::{AttachmentRecordStore, PendingUploadRecord, Storage},
 };
 
-const MAX_ATTACHMENT_SIZE_BYTES: u64 = 100 * 1024 * 1024;
 const PRESIGN_TTL_SECONDS: i64 = 900;
 const DOWNLOAD_TTL_SECONDS: i64 = 600;
 
@@ -33,21 +42,26 @@ const DOWNLOAD_TTL_SECONDS: i64 = 600;
 pub struct AttachmentService {
     storage: Arc<Storage>,
     object_storage: Option<Arc<S3ObjectStorage>>,
+    max_size_bytes: Arc<AtomicU64>,
 }
 
 #[derive(Clone)]
 struct S3ObjectStorage {
+    api_client: S3Client,
     presign_client: S3Client,
     bucket: String,
     region: String,
 }
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PresignRequest {
     pub channel_id: Uuid,
     pub filename: String,
     pub content_type: String,
     pub size_bytes: u64,
+    /// Also presign a slot for a client-generated thumbnail; `image/*` only.
+    #[serde(default)]
+    pub thumbnail: bool,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -56,10 +70,14 @@ pub struct PresignResponse {
     pub upload_url: String,
     pub bucket: String,
     pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_upload_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_key: Option<String>,
     pub expires_at: i64,
 }
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CommitRequest {
     pub upload_id: Uuid,
     pub message_id: Option<Uuid>,
@@ -79,13 +97,27 @@ pub struct AttachmentResponse {
     pub storage_bucket: String,
     pub storage_key: String,
     pub storage_region: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_key: Option<String>,
     pub created_at: i64,
 }
 
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct AttachmentGcResponse {
+    pub dry_run: bool,
+    pub expired_uploads: usize,
+    pub orphaned_attachments: usize,
+    pub reclaimed_bytes: u64,
+    /// Orphaned attachments kept because a legal hold covers them.
+    pub held_attachments: usize,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct AttachmentGetResponse {
     pub attachment: AttachmentResponse,
     pub download_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
     pub expires_at: i64,
 }
 
@@ -102,14 +134,29 @@ impl AttachmentService {
         Self {
             storage,
             object_storage,
+            max_size_bytes: Arc::new(AtomicU64::new(config.max_attachment_size_bytes)),
         }
     }
 
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_max_size_bytes(&self, max_size_bytes: u64) {
+        self.max_size_bytes.store(max_size_bytes, Ordering::Relaxed);
+    }
+
+    pub async fn ping_object_storage(&self) -> Option<Result<(), String>> {
+        let object_storage = self.object_storage.as_ref()?;
+        Some(object_storage.ping().await)
+    }
+
     #[cfg(test)]
     pub fn new_without_object_storage(storage: Arc<Storage>) -> Self {
         Self {
             storage,
             object_storage: None,
+            max_size_bytes: Arc::new(AtomicU64::new(100 * 1024 * 1024)),
         }
     }
 
@@ -120,18 +167,25 @@ impl AttachmentService {
     ) -> ApiResult<PresignResponse> {
         let filename = payload.filename.trim().to_string();
         let content_type = payload.content_type.trim().to_string();
+        let max_size_bytes = self.max_size_bytes();
+        let mut errors = FieldErrors::default();
         if filename.is_empty() {
-            return Err(ApiError::BadRequest("filename is required".to_string()));
+            errors.add("filename", "must not be empty");
         }
         if content_type.is_empty() {
-            return Err(ApiError::BadRequest("content_type is required".to_string()));
+            errors.add("content_type", "must not be empty");
+        }
+        if payload.thumbnail && !content_type.starts_with("image/") {
+            errors.add("thumbnail", "is only supported for image/* uploads");
         }
         if payload.size_bytes == 0 {
-            return Err(ApiError::BadRequest("size_bytes must be > 0".to_string()));
+            errors.add("size_bytes", "must be greater than 0");
         }
-        if payload.size_bytes > MAX_ATTACHMENT_SIZE_BYTES {
-            return Err(ApiError::BadRequest(
-                "file size exceeds 100MB limit".to_string(),
+        errors.into_result()?;
+        if payload.size_bytes > max_size_bytes {
+            return Err(ApiError::Coded(
+                ErrorCode::AttachmentTooLarge,
+                format!("file size exceeds {max_size_bytes} byte limit"),
             ));
         }
 
@@ -145,17 +199,31 @@ impl AttachmentService {
             sanitize_filename(&filename)
         );
 
-        let (bucket, upload_url) = if let Some(object_storage) = &self.object_storage {
-            let url = object_storage
-                .presign_upload_url(&key, &content_type, payload.size_bytes)
-                .await?;
-            (object_storage.bucket.clone(), url)
-        } else {
-            (
-                "galynx-attachments".to_string(),
-                format!("https://storage.galynx.local/upload/{upload_id}"),
-            )
-        };
+        let thumbnail_key = payload.thumbnail.then(|| format!("{key}.thumb"));
+
+        let (bucket, upload_url, thumbnail_upload_url) =
+            if let Some(object_storage) = &self.object_storage {
+                let url = object_storage
+                    .presign_upload_url(&key, &content_type, payload.size_bytes)
+                    .await?;
+                let thumbnail_url = match &thumbnail_key {
+                    Some(thumbnail_key) => Some(
+                        object_storage
+                            .presign_upload_url(thumbnail_key, &content_type, 0)
+                            .await?,
+                    ),
+                    None => None,
+                };
+                (object_storage.bucket.clone(), url, thumbnail_url)
+            } else {
+                (
+                    "galynx-attachments".to_string(),
+                    format!("https://storage.galynx.local/upload/{upload_id}"),
+                    thumbnail_key
+                        .as_ref()
+                        .map(|_| format!("https://storage.galynx.local/upload/{upload_id}/thumb")),
+                )
+            };
 
         let pending = PendingUploadRecord {
             workspace_id: context.workspace_id,
@@ -165,6 +233,7 @@ impl AttachmentService {
             content_type,
             size_bytes: payload.size_bytes,
             storage_key: key.clone(),
+            thumbnail_key: thumbnail_key.clone(),
             expires_at: now + PRESIGN_TTL_SECONDS,
             created_at: now,
         };
@@ -175,6 +244,8 @@ impl AttachmentService {
             upload_url,
             bucket,
             key,
+            thumbnail_upload_url,
+            thumbnail_key,
             expires_at: now + PRESIGN_TTL_SECONDS,
         })
     }
@@ -196,12 +267,13 @@ impl AttachmentService {
             return Err(ApiError::NotFound("upload_id not found".to_string()));
         }
         if pending.uploader_id != context.user_id {
-            return Err(ApiError::Unauthorized(
+            return Err(ApiError::Forbidden(
                 "cannot commit upload from another user".to_string(),
             ));
         }
         if pending.expires_at < now {
-            return Err(ApiError::BadRequest(
+            return Err(ApiError::Coded(
+                ErrorCode::UploadExpired,
                 "presigned upload has expired".to_string(),
             ));
         }
@@ -223,6 +295,7 @@ impl AttachmentService {
             size_bytes: pending.size_bytes,
             bucket,
             key: pending.storage_key,
+            thumbnail_key: pending.thumbnail_key,
             region,
             created_at: pending.created_at,
         };
@@ -246,26 +319,112 @@ impl AttachmentService {
         }
 
         let expires_at = Utc::now().timestamp() + DOWNLOAD_TTL_SECONDS;
-        let download_url = if let Some(object_storage) = &self.object_storage {
-            object_storage
-                .presign_download_url(&attachment.key)
-                .await
-                .map_err(|error| {
-                    ApiError::Internal(format!("failed to presign download url: {error}"))
-                })?
-        } else {
-            format!(
-                "https://storage.galynx.local/download/{}/{}?exp={}",
-                attachment.bucket, attachment.id, expires_at
-            )
+        let download_url = self
+            .download_url(&attachment, &attachment.key, expires_at)
+            .await?;
+        let thumbnail_url = match &attachment.thumbnail_key {
+            Some(thumbnail_key) => Some(
+                self.download_url(&attachment, thumbnail_key, expires_at)
+                    .await?,
+            ),
+            None => None,
         };
 
         Ok(AttachmentGetResponse {
             attachment: AttachmentResponse::from(&attachment),
             download_url,
+            thumbnail_url,
             expires_at,
         })
     }
+
+    async fn download_url(
+        &self,
+        attachment: &AttachmentRecordStore,
+        key: &str,
+        expires_at: i64,
+    ) -> ApiResult<String> {
+        let Some(object_storage) = &self.object_storage else {
+            let suffix = if key == attachment.key { "" } else { "/thumb" };
+            return Ok(format!(
+                "https://storage.galynx.local/download/{}/{}{suffix}?exp={}",
+                attachment.bucket, attachment.id, expires_at
+            ));
+        };
+        object_storage
+            .presign_download_url(key)
+            .await
+            .map_err(|error| ApiError::Internal(format!("failed to presign download url: {error}")))
+    }
+
+    /// Drops uploads whose presign window has passed without a commit, and
+    /// attachments whose message no longer exists or was deleted, unless a
+    /// legal hold covers their channel or uploader.
+    pub async fn collect_garbage(&self, workspace_id: Uuid, dry_run: bool) -> AttachmentGcResponse {
+        let now = Utc::now().timestamp();
+        let mut report = AttachmentGcResponse {
+            dry_run,
+            ..AttachmentGcResponse::default()
+        };
+
+        for (upload_id, pending) in self.storage.list_pending_uploads(workspace_id).await {
+            if pending.expires_at >= now {
+                continue;
+            }
+            report.expired_uploads += 1;
+            if !dry_run {
+                self.storage.take_pending_upload(&upload_id).await;
+                self.delete_object(&pending.storage_key).await;
+                if let Some(thumbnail_key) = &pending.thumbnail_key {
+                    self.delete_object(thumbnail_key).await;
+                }
+            }
+        }
+
+        let holds = LegalHolds::load(&self.storage, workspace_id).await;
+        let live_messages = self
+            .storage
+            .list_messages(workspace_id)
+            .await
+            .into_iter()
+            .filter(|message| message.deleted_at.is_none())
+            .map(|message| message.id)
+            .collect::<HashSet<_>>();
+        for attachment in self.storage.list_attachments(workspace_id).await {
+            let Some(message_id) = attachment.message_id else {
+                continue;
+            };
+            if live_messages.contains(&message_id) {
+                continue;
+            }
+            if holds.covers_channel(attachment.channel_id)
+                || holds.covers_user(attachment.uploader_id)
+            {
+                report.held_attachments += 1;
+                continue;
+            }
+            report.orphaned_attachments += 1;
+            report.reclaimed_bytes += attachment.size_bytes;
+            if !dry_run {
+                self.storage.remove_attachment(&attachment.id).await;
+                self.delete_object(&attachment.key).await;
+                if let Some(thumbnail_key) = &attachment.thumbnail_key {
+                    self.delete_object(thumbnail_key).await;
+                }
+            }
+        }
+
+        report
+    }
+
+    async fn delete_object(&self, key: &str) {
+        let Some(object_storage) = &self.object_storage else {
+            return;
+        };
+        if let Err(error) = object_storage.delete_object(key).await {
+            tracing::warn!("failed to delete object {}: {}", key, error);
+        }
+    }
 }
 
 impl S3ObjectStorage {
@@ -292,6 +451,11 @@ impl S3ObjectStorage {
         }
 
         let shared_config = loader.load().await;
+        let api_client = build_s3_client(
+            &shared_config,
+            config.s3_endpoint.as_deref(),
+            config.s3_force_path_style,
+        );
         let presign_client = build_s3_client(
             &shared_config,
             config
@@ -302,12 +466,34 @@ impl S3ObjectStorage {
         );
 
         Some(Self {
+            api_client,
             presign_client,
             bucket,
             region: config.s3_region.clone(),
         })
     }
 
+    async fn ping(&self) -> Result<(), String> {
+        self.api_client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|error| format!("s3 head_bucket failed: {error}"))
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), String> {
+        self.api_client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|error| format!("s3 delete_object failed: {error}"))
+    }
+
     async fn presign_upload_url(
         &self,
         key: &str,
@@ -378,6 +564,7 @@ impl From<&AttachmentRecordStore> for AttachmentResponse {
             storage_bucket: record.bucket.clone(),
             storage_key: record.key.clone(),
             storage_region: record.region.clone(),
+            thumbnail_key: record.thumbnail_key.clone(),
             created_at: record.created_at,
         }
     }
@@ -399,22 +586,35 @@ fn sanitize_filename(value: &str) -> String {
 #[utoipa::path(
     post,
     path = "/api/v1/attachments/presign",
+    operation_id = "presign",
+    tag = "attachments",
     request_body = PresignRequest,
     responses(
         (status = 200, description = "Generated presigned upload URL", body = PresignResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 400, description = "Validation error", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key in use", body = ErrorResponse),
+        (status = 422, description = "Invalid fields or Idempotency-Key reused", body = ErrorResponse)
     )
 )]
 pub(crate) async fn presign(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<PresignRequest>,
-) -> ApiResult<Json<PresignResponse>> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
+    let mut idempotency = idempotency::scope(&headers, &context, "presign_attachment", &payload)?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
+    state
+        .rate_limit
+        .check_attachment_presign(context.user_id)
+        .await?;
     state
         .channels
         .ensure_channel_access(&context, payload.channel_id)
@@ -431,29 +631,49 @@ pub(crate) async fn presign(
             json!({ "key": response.key, "expires_at": response.expires_at }),
         )
         .await;
-    Ok(Json(response))
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::OK, &response)
+        .await)
 }
 
 #[utoipa::path(
     post,
     path = "/api/v1/attachments/commit",
+    operation_id = "commit",
+    tag = "attachments",
     request_body = CommitRequest,
     responses(
         (status = 200, description = "Committed uploaded attachment", body = AttachmentResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 404, description = "Upload not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Upload not found", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key in use", body = ErrorResponse),
+        (status = 422, description = "Idempotency-Key reused", body = ErrorResponse)
     )
 )]
 pub(crate) async fn commit(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<CommitRequest>,
-) -> ApiResult<Json<AttachmentResponse>> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
+    let mut idempotency = idempotency::scope(&headers, &context, "commit_attachment", &payload)?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
+    if let Some(message_id) = payload.message_id {
+        state
+            .channels
+            .ensure_attachment_capacity(context.workspace_id, message_id)
+            .await?;
+    }
     let response = state.attachments.commit(&context, payload).await?;
+    state
+        .metrics
+        .record_attachment_committed(context.workspace_id, response.size_bytes);
     state
         .audit
         .write(
@@ -465,15 +685,20 @@ pub(crate) async fn commit(
             json!({ "channel_id": response.channel_id, "message_id": response.message_id }),
         )
         .await;
-    Ok(Json(response))
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::OK, &response)
+        .await)
 }
 
 #[utoipa::path(
     get,
     path = "/api/v1/attachments/{id}",
+    operation_id = "get_attachment",
+    tag = "attachments",
     responses(
         (status = 200, description = "Attachment metadata + download URL", body = AttachmentGetResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Attachment not found", body = ErrorResponse)
     )
 )]
@@ -519,6 +744,7 @@ mod tests {
                     filename: "design doc.pdf".to_string(),
                     content_type: "application/pdf".to_string(),
                     size_bytes: 1024,
+                    thumbnail: false,
                 },
             )
             .await
@@ -534,5 +760,72 @@ mod tests {
             .await
             .expect("commit should succeed");
         assert_eq!(commit.filename, "design doc.pdf");
+
+        let too_large = service
+            .presign(
+                &context,
+                PresignRequest {
+                    channel_id: Uuid::new_v4(),
+                    filename: "backup.tar".to_string(),
+                    content_type: "application/x-tar".to_string(),
+                    size_bytes: service.max_size_bytes() + 1,
+                    thumbnail: false,
+                },
+            )
+            .await;
+        assert!(matches!(
+            too_large,
+            Err(ApiError::Coded(ErrorCode::AttachmentTooLarge, _))
+        ));
+    }
+
+    #[tokio::test]
+    async fn image_thumbnail_key_is_kept_through_commit() {
+        let service = AttachmentService::new_without_object_storage(Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        ));
+        let context = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            role: WorkspaceRole::Member,
+        };
+        let request = |content_type: &str| PresignRequest {
+            channel_id: Uuid::new_v4(),
+            filename: "photo.png".to_string(),
+            content_type: content_type.to_string(),
+            size_bytes: 2048,
+            thumbnail: true,
+        };
+
+        assert!(matches!(
+            service.presign(&context, request("application/pdf")).await,
+            Err(ApiError::Validation(_))
+        ));
+        let presign = service
+            .presign(&context, request("image/png"))
+            .await
+            .expect("presign should succeed");
+        assert!(presign.thumbnail_upload_url.is_some());
+        let thumbnail_key = presign.thumbnail_key.clone().expect("thumbnail key");
+        assert_eq!(thumbnail_key, format!("{}.thumb", presign.key));
+
+        let commit = service
+            .commit(
+                &context,
+                CommitRequest {
+                    upload_id: presign.upload_id,
+                    message_id: None,
+                },
+            )
+            .await
+            .expect("commit should succeed");
+        assert_eq!(commit.thumbnail_key, Some(thumbnail_key));
+        let fetched = service
+            .get(context.workspace_id, commit.id)
+            .await
+            .expect("attachment should exist");
+        assert!(fetched.thumbnail_url.is_some());
     }
 }
@@ -15,7 +15,8 @@ use uuid::Uuid;
 use crate::{
     app::AppState,
     auth::{AuthContext, WorkspaceRole},
-    errors::{ApiError, ApiResult, ErrorResponse},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse},
+    observability,
     storage::{AuditEntryRecord, Storage},
 };
 
@@ -64,8 +65,16 @@ impl AuditService {
         action: &str,
         target_type: &str,
         target_id: Option<String>,
-        metadata: Value,
+        mut metadata: Value,
     ) {
+        if let (Some(request_id), Some(object)) = (
+            observability::current_request_id(),
+            metadata.as_object_mut(),
+        ) {
+            object
+                .entry("request_id")
+                .or_insert_with(|| Value::String(request_id));
+        }
         let entry = AuditEntryRecord {
             id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
             workspace_id,
@@ -80,6 +89,22 @@ impl AuditService {
         self.storage.append_audit_entry(entry).await;
     }
 
+    /// Writes the same entry to every workspace `user_id` belongs to, for
+    /// account events that happen outside a workspace session.
+    pub async fn write_for_user(&self, user_id: Uuid, action: &str, metadata: Value) {
+        for (workspace_id, _) in self.storage.list_user_memberships(user_id).await {
+            self.write(
+                workspace_id,
+                Some(user_id),
+                action,
+                "user",
+                Some(user_id.to_string()),
+                metadata.clone(),
+            )
+            .await;
+        }
+    }
+
     pub async fn list(
         &self,
         workspace_id: Uuid,
@@ -91,7 +116,9 @@ impl AuditService {
             .as_deref()
             .map(parse_cursor)
             .transpose()
-            .map_err(|error| ApiError::BadRequest(format!("invalid cursor: {error}")))?;
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
 
         let entries = self.storage.list_audit_entries(workspace_id).await;
         let mut filtered = entries
@@ -159,7 +186,7 @@ impl From<&AuditEntryRecord> for AuditLogResponse {
 fn ensure_audit_access(context: &AuthContext) -> ApiResult<()> {
     match context.role {
         WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
-        WorkspaceRole::Member => Err(ApiError::Unauthorized(
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
             "you do not have permission to read audit logs".to_string(),
         )),
     }
@@ -168,10 +195,13 @@ fn ensure_audit_access(context: &AuthContext) -> ApiResult<()> {
 #[utoipa::path(
     get,
     path = "/api/v1/audit",
+    operation_id = "list_audit",
+    tag = "audit",
     params(AuditQuery),
     responses(
         (status = 200, description = "Audit logs", body = AuditListResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
     )
 )]
 pub(crate) async fn list_audit(
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use argon2::{
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
@@ -6,8 +6,9 @@ use argon2::{
 };
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{ConnectInfo, State},
     http::{HeaderMap, StatusCode, header},
+    response::Response,
     routing::{get, post},
 };
 use base64::Engine;
@@ -23,8 +24,9 @@ use uuid::Uuid;
 
 use crate::{
     app::AppState,
-    errors::{ApiError, ApiResult, ErrorResponse},
-    rate_limit::client_ip_from_headers,
+    conditional,
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse},
+    ip_allowlists, observability, session_limits, sessions,
     storage::{AuthUserRecordStore, RefreshSessionRecordStore, Storage, WorkspaceRecordStore},
 };
 
@@ -48,7 +50,7 @@ pub enum WorkspaceRole {
 }
 
 impl WorkspaceRole {
-    fn from_storage_role(value: &str) -> Result<Self, &'static str> {
+    pub(crate) fn from_storage_role(value: &str) -> Result<Self, &'static str> {
         match value.trim().to_ascii_lowercase().as_str() {
             "owner" => Ok(Self::Owner),
             "admin" => Ok(Self::Admin),
@@ -99,6 +101,7 @@ pub struct MeResponse {
     pub id: Uuid,
     pub email: String,
     pub name: String,
+    pub email_verified: bool,
     pub workspace_id: Uuid,
     pub role: WorkspaceRole,
 }
@@ -167,7 +170,7 @@ impl AuthService {
         }
 
         let mut memberships = self.storage.list_user_memberships(user_id).await;
-        memberships.sort_by(|a, b| a.0.cmp(&b.0));
+        memberships.sort_by_key(|a| a.0);
         let (workspace_id, role) = memberships.into_iter().next()?;
         Some((workspace_id, WorkspaceRole::from_storage_role(&role).ok()?))
     }
@@ -187,14 +190,24 @@ impl AuthService {
             .storage
             .get_auth_user_by_email(&email)
             .await
-            .ok_or_else(|| ApiError::Unauthorized("invalid credentials".to_string()))?;
+            .ok_or_else(|| {
+                ApiError::Coded(
+                    ErrorCode::InvalidCredentials,
+                    "invalid credentials".to_string(),
+                )
+            })?;
 
         let parsed_hash = PasswordHash::new(&user.password_hash)
             .map_err(|_| ApiError::Internal("invalid stored password hash".to_string()))?;
 
         Argon2::default()
             .verify_password(password.as_bytes(), &parsed_hash)
-            .map_err(|_| ApiError::Unauthorized("invalid credentials".to_string()))?;
+            .map_err(|_| {
+                ApiError::Coded(
+                    ErrorCode::InvalidCredentials,
+                    "invalid credentials".to_string(),
+                )
+            })?;
 
         self.issue_tokens(
             user,
@@ -223,6 +236,7 @@ impl AuthService {
             .ok_or_else(|| {
                 ApiError::Unauthorized("user has no workspace membership".to_string())
             })?;
+        self.ensure_workspace_active(workspace_id).await?;
 
         let claims = AccessClaims {
             sub: user.id.to_string(),
@@ -244,11 +258,16 @@ impl AuthService {
         let refresh_token = generate_refresh_token();
         let refresh_hash = token_hash(&refresh_token);
         let session = RefreshSessionRecordStore {
+            session_id: Uuid::new_v4(),
             user_id: user.id,
             workspace_id,
+            created_at: now.timestamp(),
             expires_at: refresh_exp.timestamp(),
             revoked_at: None,
             replaced_by_hash: None,
+            ip: None,
+            user_agent: None,
+            new_device: false,
         };
 
         self.storage
@@ -278,10 +297,18 @@ impl AuthService {
             .storage
             .get_refresh_session(&incoming_hash)
             .await
-            .ok_or_else(|| ApiError::Unauthorized("invalid refresh token".to_string()))?;
+            .ok_or_else(|| {
+                ApiError::Coded(
+                    ErrorCode::RefreshTokenInvalid,
+                    "invalid refresh token".to_string(),
+                )
+            })?;
 
         if snapshot.expires_at <= now {
-            return Err(ApiError::Unauthorized("refresh token expired".to_string()));
+            return Err(ApiError::Coded(
+                ErrorCode::RefreshTokenExpired,
+                "refresh token expired".to_string(),
+            ));
         }
 
         if snapshot.revoked_at.is_some() {
@@ -293,7 +320,8 @@ impl AuthService {
                     })
                     .await;
             }
-            return Err(ApiError::Unauthorized(
+            return Err(ApiError::Coded(
+                ErrorCode::RefreshTokenReused,
                 "refresh token reuse detected".to_string(),
             ));
         }
@@ -303,7 +331,12 @@ impl AuthService {
                 session.revoked_at = Some(now);
             })
             .await
-            .ok_or_else(|| ApiError::Unauthorized("invalid refresh token".to_string()))?;
+            .ok_or_else(|| {
+                ApiError::Coded(
+                    ErrorCode::RefreshTokenInvalid,
+                    "invalid refresh token".to_string(),
+                )
+            })?;
         let refresh_token = generate_refresh_token();
         let refresh_hash = token_hash(&refresh_token);
         self.storage
@@ -314,11 +347,16 @@ impl AuthService {
 
         let refresh_exp = Utc::now() + Duration::days(refresh_ttl_days);
         let rotated = RefreshSessionRecordStore {
+            session_id: snapshot.session_id,
             user_id: snapshot.user_id,
             workspace_id: snapshot.workspace_id,
+            created_at: snapshot.created_at,
             expires_at: refresh_exp.timestamp(),
             revoked_at: None,
             replaced_by_hash: None,
+            ip: snapshot.ip.clone(),
+            user_agent: snapshot.user_agent.clone(),
+            new_device: snapshot.new_device,
         };
         self.storage
             .put_refresh_session(refresh_hash, rotated)
@@ -337,6 +375,7 @@ impl AuthService {
             .ok_or_else(|| {
                 ApiError::Unauthorized("user has no workspace membership".to_string())
             })?;
+        self.ensure_workspace_active(workspace_id).await?;
         let claims = AccessClaims {
             sub: user.id.to_string(),
             email: user.email,
@@ -371,7 +410,12 @@ impl AuthService {
                 session.revoked_at = Some(now);
             })
             .await
-            .ok_or_else(|| ApiError::Unauthorized("invalid refresh token".to_string()))?;
+            .ok_or_else(|| {
+                ApiError::Coded(
+                    ErrorCode::RefreshTokenInvalid,
+                    "invalid refresh token".to_string(),
+                )
+            })?;
         Ok(())
     }
 
@@ -387,6 +431,7 @@ impl AuthService {
             id: user.id,
             email: user.email.clone(),
             name: user.name.clone(),
+            email_verified: user.email_verified_at.is_some(),
             workspace_id: context.workspace_id,
             role: context.role.clone(),
         })
@@ -422,25 +467,61 @@ impl AuthService {
             &DecodingKey::from_secret(jwt_secret.as_bytes()),
             &Validation::default(),
         )
-        .map_err(|_| ApiError::Unauthorized("invalid access token".to_string()))?;
+        .map_err(|_| {
+            ApiError::Coded(ErrorCode::InvalidToken, "invalid access token".to_string())
+        })?;
 
         if token_data.claims.token_type != "access" {
-            return Err(ApiError::Unauthorized("invalid token type".to_string()));
+            return Err(ApiError::Coded(
+                ErrorCode::InvalidToken,
+                "invalid token type".to_string(),
+            ));
         }
 
-        let user_id = Uuid::parse_str(&token_data.claims.sub)
-            .map_err(|_| ApiError::Unauthorized("invalid access token subject".to_string()))?;
-        let workspace_id = Uuid::parse_str(&token_data.claims.workspace_id)
-            .map_err(|_| ApiError::Unauthorized("invalid workspace id in token".to_string()))?;
+        let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| {
+            ApiError::Coded(
+                ErrorCode::InvalidToken,
+                "invalid access token subject".to_string(),
+            )
+        })?;
+        let workspace_id = Uuid::parse_str(&token_data.claims.workspace_id).map_err(|_| {
+            ApiError::Coded(
+                ErrorCode::InvalidToken,
+                "invalid workspace id in token".to_string(),
+            )
+        })?;
+
+        let context = self.member_context(workspace_id, user_id).await?;
+
+        let span = tracing::Span::current();
+        span.record("user_id", tracing::field::display(user_id));
+        span.record("workspace_id", tracing::field::display(workspace_id));
+        observability::record_request_workspace(workspace_id);
+
+        Ok(context)
+    }
 
+    /// Context of a workspace member acting without a token, for background
+    /// jobs that run on a user's behalf. Fails like an access token would once
+    /// the membership is gone or the workspace is suspended.
+    pub async fn member_context(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> ApiResult<AuthContext> {
         let role = self
             .storage
             .get_membership_role(workspace_id, user_id)
             .await
-            .ok_or_else(|| ApiError::Unauthorized("membership no longer valid".to_string()))?;
+            .ok_or_else(|| {
+                ApiError::Coded(
+                    ErrorCode::MembershipRevoked,
+                    "membership no longer valid".to_string(),
+                )
+            })?;
         let role = WorkspaceRole::from_storage_role(&role)
             .map_err(|_| ApiError::Unauthorized("invalid membership role".to_string()))?;
-
+        self.ensure_workspace_active(workspace_id).await?;
         Ok(AuthContext {
             user_id,
             workspace_id,
@@ -448,6 +529,17 @@ impl AuthService {
         })
     }
 
+    /// Suspended workspaces reject new tokens and every request made with existing ones.
+    async fn ensure_workspace_active(&self, workspace_id: Uuid) -> ApiResult<()> {
+        match self.storage.get_workspace(workspace_id).await {
+            Some(workspace) if workspace.suspended_at.is_some() => Err(ApiError::Coded(
+                ErrorCode::WorkspaceSuspended,
+                "workspace is suspended".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
     async fn ensure_bootstrap_seed(&self) {
         if let Some(existing) = self
             .storage
@@ -466,6 +558,8 @@ impl AuthService {
                         name: self.bootstrap_workspace_name.clone(),
                         created_by: existing.id,
                         created_at: Utc::now().timestamp_millis(),
+                        suspended_at: None,
+                        suspended_reason: None,
                     })
                     .await;
                 self.storage
@@ -480,6 +574,7 @@ impl AuthService {
             email: self.bootstrap_email.clone(),
             name: self.bootstrap_name.clone(),
             password_hash: self.bootstrap_password_hash.clone(),
+            email_verified_at: None,
         };
         self.storage.put_auth_user(user).await;
         self.storage
@@ -488,6 +583,8 @@ impl AuthService {
                 name: self.bootstrap_workspace_name.clone(),
                 created_by: self.bootstrap_user_id,
                 created_at: Utc::now().timestamp_millis(),
+                suspended_at: None,
+                suspended_reason: None,
             })
             .await;
         self.storage
@@ -496,7 +593,7 @@ impl AuthService {
     }
 }
 
-fn hash_password(password: &str) -> ApiResult<String> {
+pub(crate) fn hash_password(password: &str) -> ApiResult<String> {
     let salt = SaltString::generate(&mut OsRng);
     Argon2::default()
         .hash_password(password.as_bytes(), &salt)
@@ -504,7 +601,7 @@ fn hash_password(password: &str) -> ApiResult<String> {
         .map(|hash| hash.to_string())
 }
 
-fn token_hash(token: &str) -> String {
+pub(crate) fn token_hash(token: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     format!("{:x}", hasher.finalize())
@@ -516,16 +613,45 @@ fn generate_refresh_token() -> String {
     BASE64_STANDARD.encode(bytes)
 }
 
+/// `(user_id, workspace_id)` claimed by a bearer access token, checked only by
+/// signature and expiry. Meant for accounting, never for authorization.
+pub(crate) fn access_token_identity(headers: &HeaderMap, jwt_secret: &str) -> Option<(Uuid, Uuid)> {
+    let access_token = bearer_from_headers(headers).ok()?;
+    let claims = decode::<AccessClaims>(
+        &access_token,
+        &DecodingKey::from_secret(jwt_secret.as_bytes()),
+        &Validation::default(),
+    )
+    .ok()?
+    .claims;
+    if claims.token_type != "access" {
+        return None;
+    }
+    Some((
+        Uuid::parse_str(&claims.sub).ok()?,
+        Uuid::parse_str(&claims.workspace_id).ok()?,
+    ))
+}
+
 fn bearer_from_headers(headers: &HeaderMap) -> ApiResult<String> {
-    let value = headers
-        .get(header::AUTHORIZATION)
-        .ok_or_else(|| ApiError::Unauthorized("missing authorization header".to_string()))?;
-    let value = value
-        .to_str()
-        .map_err(|_| ApiError::Unauthorized("invalid authorization header".to_string()))?;
+    let value = headers.get(header::AUTHORIZATION).ok_or_else(|| {
+        ApiError::Coded(
+            ErrorCode::MissingToken,
+            "missing authorization header".to_string(),
+        )
+    })?;
+    let value = value.to_str().map_err(|_| {
+        ApiError::Coded(
+            ErrorCode::InvalidToken,
+            "invalid authorization header".to_string(),
+        )
+    })?;
 
     let Some(token) = value.strip_prefix("Bearer ") else {
-        return Err(ApiError::Unauthorized("expected bearer token".to_string()));
+        return Err(ApiError::Coded(
+            ErrorCode::InvalidToken,
+            "expected bearer token".to_string(),
+        ));
     };
 
     Ok(token.trim().to_string())
@@ -534,14 +660,18 @@ fn bearer_from_headers(headers: &HeaderMap) -> ApiResult<String> {
 #[utoipa::path(
     post,
     path = "/api/v1/auth/login",
+    operation_id = "login",
+    tag = "auth",
+    security(()),
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login successful", body = AuthTokensResponse),
-        (status = 401, description = "Invalid credentials", body = ErrorResponse)
+        (status = 401, description = "Invalid credentials, or address not in the workspace IP allowlist", body = ErrorResponse)
     )
 )]
 pub(crate) async fn login(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> ApiResult<Json<AuthTokensResponse>> {
@@ -550,7 +680,9 @@ pub(crate) async fn login(
             "email and password are required".to_string(),
         ));
     }
-    let client_ip = client_ip_from_headers(&headers);
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
     state
         .rate_limit
         .check_auth(&client_ip, Some(&payload.email))
@@ -566,11 +698,30 @@ pub(crate) async fn login(
             state.config.access_ttl_minutes,
             state.config.refresh_ttl_days,
         )
-        .await?;
+        .await
+        .inspect_err(|error| {
+            if error.status_code() == StatusCode::UNAUTHORIZED {
+                state.metrics.record_auth_failure("login");
+            }
+        })?;
     let context = state
         .auth
         .context_from_access_token(&response.access_token, &state.config.jwt_secret)
         .await?;
+    // A blocked address gets the same answer as a wrong password, so it cannot
+    // tell valid credentials apart; the audit log keeps the real reason.
+    if ip_allowlists::enforce(&state, &context, &client_ip, "login")
+        .await
+        .is_err()
+    {
+        let _ = state.auth.logout(&response.refresh_token).await;
+        state.metrics.record_auth_failure("login");
+        return Err(ApiError::Coded(
+            ErrorCode::InvalidCredentials,
+            "invalid credentials".to_string(),
+        ));
+    }
+    session_limits::enforce_refresh_sessions(&state, &context).await;
     state
         .audit
         .write(
@@ -582,6 +733,14 @@ pub(crate) async fn login(
             json!({ "email": payload.email.trim().to_ascii_lowercase() }),
         )
         .await;
+    sessions::track_login(
+        &state,
+        &context,
+        &response.refresh_token,
+        &client_ip,
+        &headers,
+    )
+    .await;
 
     Ok(Json(response))
 }
@@ -589,18 +748,25 @@ pub(crate) async fn login(
 #[utoipa::path(
     post,
     path = "/api/v1/auth/refresh",
+    operation_id = "refresh",
+    tag = "auth",
+    security(()),
     request_body = RefreshRequest,
     responses(
         (status = 200, description = "Refresh successful", body = AuthTokensResponse),
-        (status = 401, description = "Invalid refresh token", body = ErrorResponse)
+        (status = 401, description = "Invalid refresh token", body = ErrorResponse),
+        (status = 403, description = "Address not in the workspace IP allowlist", body = ErrorResponse)
     )
 )]
 pub(crate) async fn refresh(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
     Json(payload): Json<RefreshRequest>,
 ) -> ApiResult<Json<AuthTokensResponse>> {
-    let client_ip = client_ip_from_headers(&headers);
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
     state.rate_limit.check_auth(&client_ip, None).await?;
 
     let response = state
@@ -611,11 +777,20 @@ pub(crate) async fn refresh(
             state.config.access_ttl_minutes,
             state.config.refresh_ttl_days,
         )
-        .await?;
+        .await
+        .inspect_err(|error| {
+            if error.status_code() == StatusCode::UNAUTHORIZED {
+                state.metrics.record_auth_failure("refresh");
+            }
+        })?;
     let context = state
         .auth
         .context_from_access_token(&response.access_token, &state.config.jwt_secret)
         .await?;
+    if let Err(error) = ip_allowlists::enforce(&state, &context, &client_ip, "refresh").await {
+        let _ = state.auth.logout(&response.refresh_token).await;
+        return Err(error);
+    }
     state
         .audit
         .write(
@@ -634,6 +809,8 @@ pub(crate) async fn refresh(
 #[utoipa::path(
     post,
     path = "/api/v1/auth/logout",
+    operation_id = "logout",
+    tag = "auth",
     request_body = LogoutRequest,
     responses(
         (status = 204, description = "Logout successful"),
@@ -642,6 +819,7 @@ pub(crate) async fn refresh(
 )]
 pub(crate) async fn logout(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
     Json(payload): Json<LogoutRequest>,
 ) -> ApiResult<StatusCode> {
@@ -649,7 +827,9 @@ pub(crate) async fn logout(
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
-    let client_ip = client_ip_from_headers(&headers);
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
     state.rate_limit.check_auth(&client_ip, None).await?;
 
     state.auth.logout(&payload.refresh_token).await?;
@@ -670,21 +850,21 @@ pub(crate) async fn logout(
 #[utoipa::path(
     get,
     path = "/api/v1/me",
+    operation_id = "me",
+    tag = "auth",
     responses(
         (status = 200, description = "Current user", body = MeResponse),
+        (status = 304, description = "Not modified (If-None-Match matched the ETag)"),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     )
 )]
-pub(crate) async fn me(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-) -> ApiResult<Json<MeResponse>> {
+pub(crate) async fn me(State(state): State<AppState>, headers: HeaderMap) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
     let me = state.auth.me_from_context(&context).await?;
-    Ok(Json(me))
+    conditional::json_with_etag(&headers, &me)
 }
 
 #[cfg(test)]
@@ -719,7 +899,7 @@ mod tests {
             .await
             .expect_err("reusing token should fail");
 
-        assert!(matches!(reused, ApiError::Unauthorized(_)));
+        assert_eq!(reused.code(), ErrorCode::RefreshTokenReused);
         assert!(!second.refresh_token.is_empty());
     }
 }
@@ -1,18 +1,22 @@
 #![allow(dead_code)]
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use argon2::{
     Argon2, PasswordHasher,
     password_hash::{SaltString, rand_core::OsRng},
 };
 use chrono::Utc;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use serde::Serialize;
 use uuid::Uuid;
 
 #[path = "../config.rs"]
 mod config;
+#[path = "../encryption.rs"]
+mod encryption;
+#[path = "bootstrap/import.rs"]
+mod import;
 #[path = "../storage.rs"]
 mod storage;
 
@@ -23,12 +27,95 @@ mod storage;
     about = "Operational bootstrap for galynx-api"
 )]
 struct BootstrapCli {
+    #[command(subcommand)]
+    command: Option<BootstrapCommand>,
     #[arg(long)]
     workspace_name: Option<String>,
     #[arg(long)]
     owner_email: Option<String>,
     #[arg(long)]
     owner_password: Option<String>,
+    /// Populate the workspace with sample users, channels, threads and reactions.
+    #[arg(long)]
+    seed_demo: bool,
+    #[arg(long, default_value = "Demo123!")]
+    demo_password: String,
+    /// Print what would be created or modified without writing anything.
+    #[arg(long, global = true)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum BootstrapCommand {
+    /// Create Mongo indexes and apply pending schema migrations, then exit.
+    Migrate,
+    /// Rotate the owner's password and revoke all of their refresh sessions.
+    ResetOwnerPassword {
+        /// New password; read from GALYNX_NEW_OWNER_PASSWORD to keep it out of shell history.
+        #[arg(long, env = "GALYNX_NEW_OWNER_PASSWORD", hide_env_values = true)]
+        new_password: String,
+    },
+    /// Import channels and message history from another chat platform into the owner's workspace.
+    Import {
+        #[arg(long, value_enum)]
+        format: import::ImportFormat,
+        /// Export file, or a directory of channel exports for Discord.
+        #[arg(long)]
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ResetOwnerPasswordResult {
+    completed_at: i64,
+    backend: String,
+    owner_user_id: String,
+    owner_email: String,
+    revoked_sessions: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct MigrateResult {
+    completed_at: i64,
+    backend: String,
+    #[serde(flatten)]
+    report: storage::MigrationReport,
+}
+
+#[derive(Debug, Serialize)]
+struct DryRunPlan {
+    dry_run: bool,
+    backend: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<PlannedChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace: Option<PlannedChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner_membership: Option<PlannedChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_channel: Option<PlannedChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    demo: Option<PlannedChange>,
+    #[serde(flatten)]
+    schema: storage::SchemaPlan,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedChange {
+    action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    detail: String,
+}
+
+impl PlannedChange {
+    fn new(action: &'static str, id: Option<Uuid>, detail: impl Into<String>) -> Self {
+        Self {
+            action,
+            id: id.map(|id| id.to_string()),
+            detail: detail.into(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -43,13 +130,94 @@ struct BootstrapResult {
     created_workspace: bool,
     created_owner: bool,
     created_default_channel: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    demo: Option<DemoSeedResult>,
 }
 
+#[derive(Debug, Default, Serialize)]
+struct DemoSeedResult {
+    skipped: bool,
+    users_created: usize,
+    channels_created: usize,
+    messages_created: usize,
+    reactions_created: usize,
+}
+
+const DEMO_USERS: &[(&str, &str)] = &[
+    ("ana@demo.galynx.local", "Ana Torres"),
+    ("bruno@demo.galynx.local", "Bruno Diaz"),
+    ("carla@demo.galynx.local", "Carla Mendez"),
+];
+
+const DEMO_CHANNELS: &[(&str, bool)] =
+    &[("random", false), ("engineering", false), ("leads", true)];
+
+// (channel, sender index, body, replies as (sender index, body), reactions as (emoji, user indexes))
+type DemoThread = (
+    &'static str,
+    usize,
+    &'static str,
+    &'static [(usize, &'static str)],
+    &'static [(&'static str, &'static [usize])],
+);
+
+const DEMO_THREADS: &[DemoThread] = &[
+    (
+        "general",
+        0,
+        "Welcome to **Galynx**! This workspace was seeded with demo data.",
+        &[(1, "Hi everyone :wave:"), (2, "Glad to be here.")],
+        &[("👋", &[1, 2, 3])],
+    ),
+    (
+        "general",
+        1,
+        "Reminder: weekly sync is on Thursday at 10:00.",
+        &[],
+        &[("👍", &[0, 2])],
+    ),
+    (
+        "random",
+        2,
+        "Anyone up for lunch tacos today? :taco:",
+        &[(3, "Count me in"), (1, "Same, 13:00?")],
+        &[("🌮", &[1, 3])],
+    ),
+    (
+        "engineering",
+        1,
+        "Deploy of `v0.2.0` is scheduled for tonight. Please review the release notes.",
+        &[
+            (3, "Migrations were tested against staging."),
+            (0, "Thanks, go ahead."),
+        ],
+        &[("🚀", &[0, 2, 3])],
+    ),
+    (
+        "engineering",
+        3,
+        "Opened a PR that adds cursor pagination to the audit log.",
+        &[],
+        &[("👀", &[1])],
+    ),
+    (
+        "leads",
+        0,
+        "Q3 planning doc is ready for comments.",
+        &[(1, "Will review tomorrow morning.")],
+        &[],
+    ),
+];
+
 #[tokio::main]
 async fn main() {
     let cli = BootstrapCli::parse();
 
-    let mut cfg = config::Config::from_env();
+    let mut cfg = config::Config::load_without_jwt_secret(config::ConfigOverrides::default())
+        .unwrap_or_else(|error| {
+            eprintln!("galynx-bootstrap: {error}");
+            std::process::exit(1);
+        });
     if let Some(workspace_name) = cli.workspace_name {
         cfg.bootstrap_workspace_name = workspace_name;
     }
@@ -60,11 +228,142 @@ async fn main() {
         cfg.bootstrap_password = owner_password;
     }
 
-    let storage = Arc::new(
-        storage::Storage::new(cfg.persistence_backend, cfg.mongo_uri.as_deref())
+    let storage = if cli.dry_run {
+        storage::Storage::new_without_indexes(cfg.persistence_backend, cfg.mongo_uri.as_deref())
             .await
-            .expect("failed to initialize storage"),
-    );
+    } else {
+        storage::Storage::new(cfg.persistence_backend, cfg.mongo_uri.as_deref()).await
+    };
+    let mut storage = storage.expect("failed to initialize storage");
+    if let Some(key) = cfg.message_encryption_key.as_deref() {
+        storage = storage.with_message_encryption(
+            encryption::MessageEncryption::from_base64(key)
+                .expect("invalid MESSAGE_ENCRYPTION_KEY"),
+        );
+    }
+    let storage = Arc::new(storage);
+
+    let backend = match cfg.persistence_backend {
+        storage::PersistenceBackend::Memory => "memory",
+        storage::PersistenceBackend::Mongo => "mongo",
+    };
+
+    if let Some(BootstrapCommand::Import { format, path }) = &cli.command {
+        let importer = format.importer();
+        let batch = importer.parse(path).unwrap_or_else(|error| {
+            eprintln!(
+                "galynx-bootstrap: failed to parse {} export: {error}",
+                importer.source()
+            );
+            std::process::exit(1);
+        });
+        let email = cfg.bootstrap_email.trim().to_ascii_lowercase();
+        let Some(owner) = storage.get_auth_user_by_email(&email).await else {
+            eprintln!("galynx-bootstrap: owner {email} not found; run bootstrap first");
+            std::process::exit(1);
+        };
+        let Some((workspace_id, _)) = storage.find_primary_membership(owner.id).await else {
+            eprintln!("galynx-bootstrap: owner {email} has no workspace; run bootstrap first");
+            std::process::exit(1);
+        };
+        let result = import::apply_import(
+            &storage,
+            workspace_id,
+            owner.id,
+            importer.source(),
+            batch,
+            cli.dry_run,
+        )
+        .await;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).expect("failed to serialize result")
+        );
+        return;
+    }
+
+    if cli.dry_run {
+        let schema = storage.schema_plan().await.unwrap_or_else(|error| {
+            eprintln!("galynx-bootstrap: failed to inspect schema: {error}");
+            std::process::exit(1);
+        });
+        let mut plan = DryRunPlan {
+            dry_run: true,
+            backend: backend.to_string(),
+            owner: None,
+            workspace: None,
+            owner_membership: None,
+            default_channel: None,
+            demo: None,
+            schema,
+        };
+        match &cli.command {
+            None => plan_bootstrap(&storage, &cfg, cli.seed_demo, &mut plan).await,
+            Some(BootstrapCommand::ResetOwnerPassword { .. }) => {
+                let email = cfg.bootstrap_email.trim().to_ascii_lowercase();
+                plan.owner = Some(match storage.get_auth_user_by_email(&email).await {
+                    Some(owner) => PlannedChange::new(
+                        "update",
+                        Some(owner.id),
+                        "rotate password hash and revoke refresh sessions",
+                    ),
+                    None => PlannedChange::new("missing", None, email),
+                });
+            }
+            Some(BootstrapCommand::Migrate | BootstrapCommand::Import { .. }) => {}
+        }
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&plan).expect("failed to serialize plan")
+        );
+        return;
+    }
+
+    if let Some(BootstrapCommand::ResetOwnerPassword { new_password }) = &cli.command {
+        if new_password.len() < 8 {
+            eprintln!("galynx-bootstrap: new password must be at least 8 characters");
+            std::process::exit(1);
+        }
+        let email = cfg.bootstrap_email.trim().to_ascii_lowercase();
+        let Some(mut owner) = storage.get_auth_user_by_email(&email).await else {
+            eprintln!("galynx-bootstrap: owner {email} not found; run bootstrap first");
+            std::process::exit(1);
+        };
+        owner.password_hash = hash_password(new_password).expect("failed to hash owner password");
+        storage.put_auth_user(owner.clone()).await;
+        let revoked_sessions = storage
+            .revoke_refresh_sessions(owner.id, None, Utc::now().timestamp())
+            .await;
+        let result = ResetOwnerPasswordResult {
+            completed_at: Utc::now().timestamp_millis(),
+            backend: backend.to_string(),
+            owner_user_id: owner.id.to_string(),
+            owner_email: owner.email,
+            revoked_sessions,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).expect("failed to serialize result")
+        );
+        return;
+    }
+
+    if let Some(BootstrapCommand::Migrate) = cli.command {
+        let report = storage.migrate().await.unwrap_or_else(|error| {
+            eprintln!("galynx-bootstrap: migration failed: {error}");
+            std::process::exit(1);
+        });
+        let result = MigrateResult {
+            completed_at: Utc::now().timestamp_millis(),
+            backend: backend.to_string(),
+            report,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).expect("failed to serialize result")
+        );
+        return;
+    }
 
     let email = cfg.bootstrap_email.trim().to_ascii_lowercase();
     let owner = if let Some(existing) = storage.get_auth_user_by_email(&email).await {
@@ -76,6 +375,7 @@ async fn main() {
             name: "Owner".to_string(),
             password_hash: hash_password(&cfg.bootstrap_password)
                 .expect("failed to hash owner password"),
+            email_verified_at: None,
         };
         storage.put_auth_user(user.clone()).await;
         (user, true)
@@ -90,6 +390,8 @@ async fn main() {
                     name: workspace_name.clone(),
                     created_by: owner.0.id,
                     created_at: Utc::now().timestamp_millis(),
+                    suspended_at: None,
+                    suspended_reason: None,
                 },
             );
             storage.put_workspace(existing.clone()).await;
@@ -100,6 +402,8 @@ async fn main() {
                 name: workspace_name.clone(),
                 created_by: owner.0.id,
                 created_at: Utc::now().timestamp_millis(),
+                suspended_at: None,
+                suspended_reason: None,
             };
             storage.put_workspace(workspace.clone()).await;
             (workspace, true)
@@ -130,6 +434,11 @@ async fn main() {
                     workspace_id: workspace.0.id,
                     name: "general".to_string(),
                     is_private: false,
+                    topic: None,
+                    icon_emoji: None,
+                    color: None,
+                    e2ee: false,
+                    key_epoch: 0,
                     created_by: owner.0.id,
                     created_at: Utc::now().timestamp_millis(),
                 })
@@ -137,9 +446,19 @@ async fn main() {
             (channel_id, true)
         };
 
-    let backend = match cfg.persistence_backend {
-        storage::PersistenceBackend::Memory => "memory",
-        storage::PersistenceBackend::Mongo => "mongo",
+    let demo = if cli.seed_demo {
+        Some(
+            seed_demo(
+                &storage,
+                workspace.0.id,
+                owner.0.id,
+                default_channel_id,
+                &cli.demo_password,
+            )
+            .await,
+        )
+    } else {
+        None
     };
 
     let result = BootstrapResult {
@@ -153,6 +472,7 @@ async fn main() {
         created_workspace: workspace.1,
         created_owner: owner.1,
         created_default_channel,
+        demo,
     };
 
     println!(
@@ -161,6 +481,256 @@ async fn main() {
     );
 }
 
+async fn plan_bootstrap(
+    storage: &storage::Storage,
+    cfg: &config::Config,
+    seed_demo: bool,
+    plan: &mut DryRunPlan,
+) {
+    let email = cfg.bootstrap_email.trim().to_ascii_lowercase();
+    let workspace_name = cfg.bootstrap_workspace_name.trim();
+    let Some(owner) = storage.get_auth_user_by_email(&email).await else {
+        plan.owner = Some(PlannedChange::new("create", None, email));
+        plan.workspace = Some(PlannedChange::new("create", None, workspace_name));
+        plan.owner_membership = Some(PlannedChange::new("create", None, "owner"));
+        plan.default_channel = Some(PlannedChange::new("create", None, "general"));
+        plan.demo = seed_demo.then(|| plan_demo_seed(0, false));
+        return;
+    };
+    plan.owner = Some(PlannedChange::new("none", Some(owner.id), email));
+
+    let Some((workspace_id, role)) = storage.find_primary_membership(owner.id).await else {
+        plan.workspace = Some(PlannedChange::new("create", None, workspace_name));
+        plan.owner_membership = Some(PlannedChange::new("create", None, "owner"));
+        plan.default_channel = Some(PlannedChange::new("create", None, "general"));
+        plan.demo = seed_demo.then(|| plan_demo_seed(0, false));
+        return;
+    };
+    plan.workspace = Some(match storage.get_workspace(workspace_id).await {
+        Some(workspace) => PlannedChange::new("none", Some(workspace_id), workspace.name),
+        None => PlannedChange::new("create", Some(workspace_id), workspace_name),
+    });
+    plan.owner_membership = Some(if role == "owner" {
+        PlannedChange::new("none", None, role)
+    } else {
+        PlannedChange::new("update", None, format!("{role} -> owner"))
+    });
+
+    let general = storage
+        .list_channels(workspace_id)
+        .await
+        .into_iter()
+        .filter(|channel| channel.name.eq_ignore_ascii_case("general"))
+        .min_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+    plan.default_channel = Some(match general {
+        Some(channel) => PlannedChange::new("none", Some(channel.id), channel.name),
+        None => PlannedChange::new("create", None, "general"),
+    });
+
+    if seed_demo {
+        let mut existing_users = 0;
+        for (email, _) in DEMO_USERS {
+            if storage.get_auth_user_by_email(email).await.is_some() {
+                existing_users += 1;
+            }
+        }
+        let seeded = storage
+            .channel_name_exists(workspace_id, DEMO_CHANNELS[0].0)
+            .await;
+        plan.demo = Some(plan_demo_seed(existing_users, seeded));
+    }
+}
+
+fn plan_demo_seed(existing_users: usize, seeded: bool) -> PlannedChange {
+    let users = DEMO_USERS.len() - existing_users;
+    if seeded {
+        return PlannedChange::new(
+            if users > 0 { "create" } else { "none" },
+            None,
+            format!("{users} users; channels and messages already seeded"),
+        );
+    }
+    let messages: usize = DEMO_THREADS
+        .iter()
+        .map(|(_, _, _, replies, _)| 1 + replies.len())
+        .sum();
+    PlannedChange::new(
+        "create",
+        None,
+        format!(
+            "{users} users, {} channels, {messages} messages",
+            DEMO_CHANNELS.len()
+        ),
+    )
+}
+
+async fn seed_demo(
+    storage: &storage::Storage,
+    workspace_id: Uuid,
+    owner_id: Uuid,
+    default_channel_id: Uuid,
+    demo_password: &str,
+) -> DemoSeedResult {
+    let mut result = DemoSeedResult::default();
+
+    let mut user_ids = vec![owner_id];
+    for (email, name) in DEMO_USERS {
+        let user_id = match storage.get_auth_user_by_email(email).await {
+            Some(existing) => existing.id,
+            None => {
+                let user = storage::AuthUserRecordStore {
+                    id: Uuid::new_v4(),
+                    email: email.to_string(),
+                    name: name.to_string(),
+                    password_hash: hash_password(demo_password)
+                        .expect("failed to hash demo password"),
+                    email_verified_at: None,
+                };
+                storage.put_auth_user(user.clone()).await;
+                result.users_created += 1;
+                user.id
+            }
+        };
+        if storage
+            .get_membership_role(workspace_id, user_id)
+            .await
+            .is_none()
+        {
+            storage
+                .put_membership_role(workspace_id, user_id, "member")
+                .await;
+        }
+        user_ids.push(user_id);
+    }
+
+    if storage
+        .channel_name_exists(workspace_id, DEMO_CHANNELS[0].0)
+        .await
+    {
+        result.skipped = true;
+        return result;
+    }
+
+    let mut channel_ids = vec![("general", default_channel_id)];
+    for (name, is_private) in DEMO_CHANNELS {
+        let channel_id = Uuid::new_v4();
+        storage
+            .insert_channel(storage::ChannelRecordStore {
+                id: channel_id,
+                workspace_id,
+                name: name.to_string(),
+                is_private: *is_private,
+                topic: None,
+                icon_emoji: None,
+                color: None,
+                e2ee: false,
+                key_epoch: 0,
+                created_by: owner_id,
+                created_at: Utc::now().timestamp_millis(),
+            })
+            .await;
+        let members = if *is_private {
+            &user_ids[..2]
+        } else {
+            &user_ids[..]
+        };
+        for member_id in members {
+            storage.add_channel_member(channel_id, *member_id).await;
+        }
+        channel_ids.push((name, channel_id));
+        result.channels_created += 1;
+    }
+
+    let mut created_at = Utc::now().timestamp_millis() - 60 * 60 * 1000;
+    for (channel, sender, body, replies, reactions) in DEMO_THREADS {
+        let Some((_, channel_id)) = channel_ids.iter().find(|(name, _)| name == channel) else {
+            continue;
+        };
+        let root_id = Uuid::new_v4();
+        storage
+            .insert_message(storage::MessageRecordStore {
+                seq: storage.next_channel_seq(*channel_id).await,
+                ..demo_message(
+                    workspace_id,
+                    *channel_id,
+                    user_ids[*sender],
+                    body,
+                    None,
+                    created_at,
+                    root_id,
+                )
+            })
+            .await
+            .unwrap_or_else(|error| {
+                eprintln!("galynx-bootstrap: {error}");
+                std::process::exit(1);
+            });
+        result.messages_created += 1;
+
+        for (reply_sender, reply_body) in *replies {
+            created_at += 60 * 1000;
+            storage
+                .insert_message(storage::MessageRecordStore {
+                    seq: storage.next_channel_seq(*channel_id).await,
+                    ..demo_message(
+                        workspace_id,
+                        *channel_id,
+                        user_ids[*reply_sender],
+                        reply_body,
+                        Some(root_id),
+                        created_at,
+                        Uuid::new_v4(),
+                    )
+                })
+                .await
+                .unwrap_or_else(|error| {
+                    eprintln!("galynx-bootstrap: {error}");
+                    std::process::exit(1);
+                });
+            result.messages_created += 1;
+        }
+
+        for (emoji, users) in *reactions {
+            for user in *users {
+                storage.add_reaction(root_id, emoji, user_ids[*user]).await;
+                result.reactions_created += 1;
+            }
+        }
+        created_at += 5 * 60 * 1000;
+    }
+
+    result
+}
+
+fn demo_message(
+    workspace_id: Uuid,
+    channel_id: Uuid,
+    sender_id: Uuid,
+    body_md: &str,
+    thread_root_id: Option<Uuid>,
+    created_at: i64,
+    id: Uuid,
+) -> storage::MessageRecordStore {
+    storage::MessageRecordStore {
+        id,
+        workspace_id,
+        channel_id,
+        sender_id,
+        body_md: body_md.to_string(),
+        thread_root_id,
+        origin_workspace_id: None,
+        seq: 0,
+        created_at,
+        edited_at: None,
+        deleted_at: None,
+        crosspost_group_id: None,
+    }
+}
+
 fn hash_password(password: &str) -> Result<String, String> {
     let salt = SaltString::generate(&mut OsRng);
     Argon2::default()
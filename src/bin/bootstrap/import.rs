@@ -0,0 +1,445 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::DateTime;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::storage;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// Mattermost bulk export (JSONL produced by `mmctl export`).
+    Mattermost,
+    /// DiscordChatExporter JSON: one file per channel, or a directory of them.
+    Discord,
+}
+
+impl ImportFormat {
+    pub fn importer(self) -> Box<dyn Importer> {
+        match self {
+            Self::Mattermost => Box::new(MattermostImporter),
+            Self::Discord => Box::new(DiscordImporter),
+        }
+    }
+}
+
+/// Source-agnostic view of an export, produced by each `Importer` and applied
+/// to storage by `apply_import`.
+#[derive(Debug, Default)]
+pub struct ImportBatch {
+    pub users: HashMap<String, ImportedUser>,
+    pub channels: Vec<ImportedChannel>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedUser {
+    pub display_name: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ImportedChannel {
+    pub name: String,
+    pub is_private: bool,
+    pub messages: Vec<ImportedMessage>,
+}
+
+#[derive(Debug)]
+pub struct ImportedMessage {
+    pub source_id: Option<String>,
+    pub author: String,
+    pub body_md: String,
+    pub created_at: i64,
+    pub reply_to: Option<String>,
+}
+
+pub trait Importer {
+    fn source(&self) -> &'static str;
+    fn parse(&self, path: &Path) -> Result<ImportBatch, String>;
+}
+
+pub struct MattermostImporter;
+
+#[derive(Deserialize)]
+struct MattermostLine {
+    #[serde(rename = "type")]
+    kind: String,
+    user: Option<MattermostUser>,
+    channel: Option<MattermostChannel>,
+    post: Option<MattermostPost>,
+}
+
+#[derive(Deserialize)]
+struct MattermostUser {
+    username: String,
+    email: Option<String>,
+    #[serde(default)]
+    first_name: String,
+    #[serde(default)]
+    last_name: String,
+}
+
+#[derive(Deserialize)]
+struct MattermostChannel {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct MattermostPost {
+    channel: String,
+    user: String,
+    #[serde(default)]
+    message: String,
+    create_at: i64,
+    #[serde(default)]
+    replies: Vec<MattermostReply>,
+}
+
+#[derive(Deserialize)]
+struct MattermostReply {
+    user: String,
+    #[serde(default)]
+    message: String,
+    create_at: i64,
+}
+
+impl Importer for MattermostImporter {
+    fn source(&self) -> &'static str {
+        "mattermost"
+    }
+
+    fn parse(&self, path: &Path) -> Result<ImportBatch, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+        let mut batch = ImportBatch::default();
+        let mut channel_index = HashMap::new();
+
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line: MattermostLine = serde_json::from_str(line)
+                .map_err(|error| format!("line {}: {error}", number + 1))?;
+            match line.kind.as_str() {
+                "user" => {
+                    let Some(user) = line.user else { continue };
+                    let full_name = format!("{} {}", user.first_name, user.last_name);
+                    let display_name = match full_name.trim() {
+                        "" => user.username.clone(),
+                        name => name.to_string(),
+                    };
+                    batch.users.insert(
+                        user.username,
+                        ImportedUser {
+                            display_name,
+                            email: user.email,
+                        },
+                    );
+                }
+                "channel" => {
+                    let Some(channel) = line.channel else {
+                        continue;
+                    };
+                    channel_index.insert(channel.name.clone(), batch.channels.len());
+                    batch.channels.push(ImportedChannel {
+                        name: channel.name,
+                        is_private: channel.kind == "P",
+                        messages: Vec::new(),
+                    });
+                }
+                "post" => {
+                    let Some(post) = line.post else { continue };
+                    let index = *channel_index
+                        .entry(post.channel.clone())
+                        .or_insert_with(|| {
+                            batch.channels.push(ImportedChannel {
+                                name: post.channel.clone(),
+                                is_private: false,
+                                messages: Vec::new(),
+                            });
+                            batch.channels.len() - 1
+                        });
+                    let root_key = format!("line-{}", number + 1);
+                    let messages = &mut batch.channels[index].messages;
+                    messages.push(ImportedMessage {
+                        source_id: Some(root_key.clone()),
+                        author: post.user,
+                        body_md: post.message,
+                        created_at: post.create_at,
+                        reply_to: None,
+                    });
+                    for reply in post.replies {
+                        messages.push(ImportedMessage {
+                            source_id: None,
+                            author: reply.user,
+                            body_md: reply.message,
+                            created_at: reply.create_at,
+                            reply_to: Some(root_key.clone()),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(batch)
+    }
+}
+
+pub struct DiscordImporter;
+
+#[derive(Deserialize)]
+struct DiscordExport {
+    channel: DiscordChannel,
+    #[serde(default)]
+    messages: Vec<DiscordMessage>,
+}
+
+#[derive(Deserialize)]
+struct DiscordChannel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct DiscordMessage {
+    id: String,
+    timestamp: String,
+    #[serde(default)]
+    content: String,
+    author: DiscordAuthor,
+    reference: Option<DiscordReference>,
+}
+
+#[derive(Deserialize)]
+struct DiscordAuthor {
+    id: String,
+    name: String,
+    nickname: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscordReference {
+    message_id: Option<String>,
+}
+
+impl Importer for DiscordImporter {
+    fn source(&self) -> &'static str {
+        "discord"
+    }
+
+    fn parse(&self, path: &Path) -> Result<ImportBatch, String> {
+        let files: Vec<PathBuf> = if path.is_dir() {
+            let mut files = fs::read_dir(path)
+                .map_err(|error| format!("failed to read {}: {error}", path.display()))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut batch = ImportBatch::default();
+        for file in files {
+            let content = fs::read_to_string(&file)
+                .map_err(|error| format!("failed to read {}: {error}", file.display()))?;
+            let export: DiscordExport = serde_json::from_str(&content)
+                .map_err(|error| format!("{}: {error}", file.display()))?;
+
+            let mut messages = Vec::with_capacity(export.messages.len());
+            for message in export.messages {
+                let created_at = DateTime::parse_from_rfc3339(&message.timestamp)
+                    .map_err(|error| {
+                        format!("{}: message {}: {error}", file.display(), message.id)
+                    })?
+                    .timestamp_millis();
+                batch
+                    .users
+                    .entry(message.author.id.clone())
+                    .or_insert_with(|| ImportedUser {
+                        display_name: message
+                            .author
+                            .nickname
+                            .clone()
+                            .unwrap_or(message.author.name.clone()),
+                        email: None,
+                    });
+                messages.push(ImportedMessage {
+                    source_id: Some(message.id),
+                    author: message.author.id,
+                    body_md: message.content,
+                    created_at,
+                    reply_to: message.reference.and_then(|reference| reference.message_id),
+                });
+            }
+            batch.channels.push(ImportedChannel {
+                name: export.channel.name,
+                is_private: false,
+                messages,
+            });
+        }
+
+        Ok(batch)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportResult {
+    pub source: &'static str,
+    pub dry_run: bool,
+    pub channels_created: usize,
+    pub channels_skipped: Vec<String>,
+    pub messages_created: usize,
+    pub users_matched: usize,
+    pub users_unmatched: usize,
+}
+
+/// Writes a parsed batch into `workspace_id`. Channels that already exist by
+/// name are skipped so re-running an import does not duplicate history.
+/// Authors are matched to workspace members by email; anyone else is posted as
+/// the importing owner with their original name prefixed to the message.
+pub async fn apply_import(
+    storage: &storage::Storage,
+    workspace_id: Uuid,
+    owner_id: Uuid,
+    source: &'static str,
+    batch: ImportBatch,
+    dry_run: bool,
+) -> ImportResult {
+    let mut result = ImportResult {
+        source,
+        dry_run,
+        ..ImportResult::default()
+    };
+
+    let mut authors: HashMap<String, Option<Uuid>> = HashMap::new();
+    for (key, user) in &batch.users {
+        let mut matched = None;
+        if let Some(email) = &user.email {
+            let email = email.trim().to_ascii_lowercase();
+            if let Some(existing) = storage.get_auth_user_by_email(&email).await
+                && storage
+                    .get_membership_role(workspace_id, existing.id)
+                    .await
+                    .is_some()
+            {
+                matched = Some(existing.id);
+            }
+        }
+        if matched.is_some() {
+            result.users_matched += 1;
+        } else {
+            result.users_unmatched += 1;
+        }
+        authors.insert(key.clone(), matched);
+    }
+
+    for channel in batch.channels {
+        let name = channel.name.trim().to_ascii_lowercase();
+        if name.is_empty() || storage.channel_name_exists(workspace_id, &name).await {
+            result.channels_skipped.push(name);
+            continue;
+        }
+        result.channels_created += 1;
+        let messages: Vec<_> = channel
+            .messages
+            .into_iter()
+            .filter(|message| !message.body_md.trim().is_empty())
+            .collect();
+        result.messages_created += messages.len();
+        if dry_run {
+            continue;
+        }
+
+        let channel_id = Uuid::new_v4();
+        storage
+            .insert_channel(storage::ChannelRecordStore {
+                id: channel_id,
+                workspace_id,
+                name,
+                is_private: channel.is_private,
+                topic: None,
+                icon_emoji: None,
+                color: None,
+                e2ee: false,
+                key_epoch: 0,
+                created_by: owner_id,
+                created_at: messages
+                    .iter()
+                    .map(|message| message.created_at)
+                    .min()
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+            })
+            .await;
+        if channel.is_private {
+            storage.add_channel_member(channel_id, owner_id).await;
+            for member_id in authors.values().flatten() {
+                storage.add_channel_member(channel_id, *member_id).await;
+            }
+        }
+
+        let mut imported_ids: HashMap<String, Uuid> = HashMap::new();
+        let mut thread_roots: HashMap<Uuid, Uuid> = HashMap::new();
+        for message in messages {
+            let id = Uuid::new_v7(uuid::Timestamp::from_unix(
+                uuid::NoContext,
+                message.created_at.div_euclid(1000) as u64,
+                (message.created_at.rem_euclid(1000) * 1_000_000) as u32,
+            ));
+            let thread_root_id = message
+                .reply_to
+                .as_ref()
+                .and_then(|parent| imported_ids.get(parent))
+                .map(|parent| thread_roots.get(parent).copied().unwrap_or(*parent));
+            if let Some(root_id) = thread_root_id {
+                thread_roots.insert(id, root_id);
+            }
+            if let Some(source_id) = message.source_id {
+                imported_ids.insert(source_id, id);
+            }
+
+            let (sender_id, body_md) = match authors.get(&message.author).copied().flatten() {
+                Some(user_id) => (user_id, message.body_md),
+                None => {
+                    let display_name = batch
+                        .users
+                        .get(&message.author)
+                        .map(|user| user.display_name.as_str())
+                        .unwrap_or(&message.author);
+                    (owner_id, format!("**{display_name}**: {}", message.body_md))
+                }
+            };
+            storage
+                .insert_message(storage::MessageRecordStore {
+                    id,
+                    workspace_id,
+                    channel_id,
+                    sender_id,
+                    body_md,
+                    thread_root_id,
+                    origin_workspace_id: None,
+                    seq: storage.next_channel_seq(channel_id).await,
+                    created_at: message.created_at,
+                    edited_at: None,
+                    deleted_at: None,
+                    crosspost_group_id: None,
+                })
+                .await
+                .unwrap_or_else(|error| {
+                    eprintln!("galynx-bootstrap: {error}");
+                    std::process::exit(1);
+                });
+        }
+    }
+
+    result
+}
@@ -1,21 +1,68 @@
 use std::{
+    collections::BTreeMap,
     env, fs,
+    io::{IsTerminal, Write},
     path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
 use chrono::Utc;
-use clap::{Args, Parser, Subcommand};
-use reqwest::{Client, Method, StatusCode};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::{Body, Client, Method, StatusCode, header};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Message as WsMessage, client::IntoClientRequest, http::HeaderValue},
+};
 
 const DEFAULT_BASE_URL: &str = "http://localhost:3000";
+const DEFAULT_PROFILE: &str = "default";
+const KEYRING_SERVICE: &str = "galynx";
+const DOCTOR_TIMEOUT_SECONDS: u64 = 5;
+const WARN_CLOCK_SKEW_SECONDS: i64 = 5;
+const MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+const USER_COLUMNS: &[&str] = &["id", "email", "name", "role"];
+const WORKSPACE_COLUMNS: &[&str] = &["id", "name", "role", "created_at"];
+const REACTION_COLUMNS: &[&str] = &["emoji", "count", "user_ids"];
+const CHANNEL_COLUMNS: &[&str] = &["id", "name", "is_private", "created_at"];
+const MESSAGE_COLUMNS: &[&str] = &["id", "sender_id", "created_at", "body_md"];
+const CHANNEL_MEMBER_COLUMNS: &[&str] = &["user_id", "role"];
+const WORKSPACE_MEMBER_COLUMNS: &[&str] = &["user_id", "email", "name", "role"];
+const AUDIT_COLUMNS: &[&str] = &[
+    "created_at",
+    "action",
+    "target_type",
+    "target_id",
+    "actor_id",
+];
+const MAX_CELL_WIDTH: usize = 60;
+const SNIPPET_CONTEXT: usize = 40;
+const MAX_PAGE_LIMIT: usize = 100;
+const MAX_ALL_ITEMS: usize = 10_000;
 
 #[derive(Parser, Debug)]
 #[command(name = "galynx", version, about = "CLI for galynx-api")]
 struct Cli {
     #[arg(long, global = true)]
     base_url: Option<String>,
+    /// Output format; defaults to `table` on a terminal and `json` otherwise.
+    #[arg(long, global = true, value_enum)]
+    output: Option<OutputFormat>,
+    /// Stored session to use; each profile keeps its own base URL and tokens.
+    #[arg(long, global = true, env = "GALYNX_PROFILE")]
+    profile: Option<String>,
+    /// Where tokens are kept; use `file` on headless machines without a keyring.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        env = "GALYNX_CREDENTIAL_STORE",
+        default_value = "keyring"
+    )]
+    credential_store: CredentialStore,
     #[command(subcommand)]
     command: Command,
 }
@@ -46,6 +93,10 @@ enum Command {
         #[command(subcommand)]
         command: AttachmentCommands,
     },
+    Reactions {
+        #[command(subcommand)]
+        command: ReactionCommands,
+    },
     Audit {
         #[command(subcommand)]
         command: AuditCommands,
@@ -54,6 +105,35 @@ enum Command {
         #[command(subcommand)]
         command: UserCommands,
     },
+    Ws {
+        #[command(subcommand)]
+        command: WsCommands,
+    },
+    /// Check connectivity, session and clock against the configured server.
+    Doctor,
+    /// Minimal interactive chat on one channel over the realtime socket.
+    Chat(ChatArgs),
+    /// Server maintenance; requires an owner or admin session, except instance
+    /// maintenance mode, which takes the operator token.
+    Admin {
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+    /// Print a shell completion script to stdout.
+    Completions { shell: clap_complete::Shell },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum CredentialStore {
+    Keyring,
+    File,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    Table,
+    Plain,
 }
 
 #[derive(Subcommand, Debug)]
@@ -62,6 +142,8 @@ enum AuthCommands {
     Me,
     Refresh,
     Logout,
+    /// List stored profiles.
+    Profiles,
 }
 
 #[derive(Args, Debug)]
@@ -111,9 +193,16 @@ enum ChannelCommands {
     List,
     Create(CreateChannelArgs),
     Delete(DeleteChannelArgs),
+    /// Manage the channel roster; `members <channel>` alone lists it.
     Members(ChannelMembersArgs),
+    #[command(hide = true)]
     MemberAdd(ChannelMemberAddArgs),
+    #[command(hide = true)]
     MemberRemove(ChannelMemberRemoveArgs),
+    /// Add yourself to a channel.
+    Join(ChannelRefArgs),
+    /// Remove yourself from a channel.
+    Leave(ChannelRefArgs),
 }
 
 #[derive(Args, Debug)]
@@ -130,8 +219,34 @@ struct DeleteChannelArgs {
 }
 
 #[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 struct ChannelMembersArgs {
-    channel_id: String,
+    #[command(subcommand)]
+    command: Option<ChannelMemberCommands>,
+    /// Channel id or name.
+    channel: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ChannelMemberCommands {
+    List(ChannelRefArgs),
+    Add(ChannelMemberArgs),
+    Remove(ChannelMemberArgs),
+}
+
+#[derive(Args, Debug)]
+struct ChannelRefArgs {
+    /// Channel id or name.
+    channel: String,
+}
+
+#[derive(Args, Debug)]
+struct ChannelMemberArgs {
+    /// Channel id or name.
+    channel: String,
+    /// User id or email.
+    #[arg(long)]
+    user: String,
 }
 
 #[derive(Args, Debug)]
@@ -154,16 +269,26 @@ enum MessageCommands {
     Send(SendMessageArgs),
     Edit(EditMessageArgs),
     Delete(DeleteMessageArgs),
+    Search(SearchMessagesArgs),
 }
 
 #[derive(Args, Debug)]
 struct ListMessagesArgs {
     #[arg(long)]
     channel: String,
+    #[command(flatten)]
+    page: PageArgs,
+}
+
+#[derive(Args, Debug)]
+struct PageArgs {
     #[arg(long)]
     cursor: Option<String>,
     #[arg(long)]
     limit: Option<usize>,
+    /// Follow `next_cursor` until the last page, stopping after about 10000 items.
+    #[arg(long)]
+    all: bool,
 }
 
 #[derive(Args, Debug)]
@@ -186,6 +311,19 @@ struct DeleteMessageArgs {
     message_id: String,
 }
 
+#[derive(Args, Debug)]
+struct SearchMessagesArgs {
+    #[arg(long)]
+    query: String,
+    /// Channel id or name.
+    #[arg(long)]
+    channel: Option<String>,
+    #[arg(long)]
+    cursor: Option<String>,
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
 #[derive(Subcommand, Debug)]
 enum ThreadCommands {
     Get(ThreadGetArgs),
@@ -201,10 +339,8 @@ struct ThreadGetArgs {
 #[derive(Args, Debug)]
 struct ThreadRepliesArgs {
     root_id: String,
-    #[arg(long)]
-    cursor: Option<String>,
-    #[arg(long)]
-    limit: Option<usize>,
+    #[command(flatten)]
+    page: PageArgs,
 }
 
 #[derive(Args, Debug)]
@@ -219,6 +355,8 @@ enum AttachmentCommands {
     Presign(AttachmentPresignArgs),
     Commit(AttachmentCommitArgs),
     Get(AttachmentGetArgs),
+    /// Presign, upload the file bytes and commit in one step.
+    Upload(AttachmentUploadArgs),
 }
 
 #[derive(Args, Debug)]
@@ -248,44 +386,197 @@ struct AttachmentGetArgs {
     attachment_id: String,
 }
 
+#[derive(Args, Debug)]
+struct AttachmentUploadArgs {
+    #[arg(long)]
+    channel: String,
+    #[arg(long)]
+    file: PathBuf,
+    #[arg(long = "content-type")]
+    content_type: Option<String>,
+    #[arg(long = "message-id")]
+    message_id: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ReactionCommands {
+    Add(ReactionArgs),
+    Remove(ReactionArgs),
+    List(ReactionListArgs),
+}
+
+#[derive(Args, Debug)]
+struct ReactionArgs {
+    message_id: String,
+    #[arg(long)]
+    emoji: String,
+}
+
+#[derive(Args, Debug)]
+struct ReactionListArgs {
+    message_id: String,
+}
+
 #[derive(Subcommand, Debug)]
 enum AuditCommands {
     List(AuditListArgs),
+    /// Write every entry in a time range, oldest first.
+    Export(AuditExportArgs),
+    /// Poll for new entries and print them as they appear.
+    Tail(AuditTailArgs),
 }
 
 #[derive(Args, Debug)]
-struct AuditListArgs {
+struct AuditExportArgs {
+    /// RFC 3339 time, `YYYY-MM-DD`, epoch millis or a relative age such as `24h` or `7d`.
     #[arg(long)]
-    cursor: Option<String>,
+    since: Option<String>,
     #[arg(long)]
-    limit: Option<usize>,
+    until: Option<String>,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+    format: ExportFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Jsonl,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct AuditTailArgs {
+    /// Seconds between polls.
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+}
+
+#[derive(Args, Debug)]
+struct AuditListArgs {
+    #[command(flatten)]
+    page: PageArgs,
 }
 
 #[derive(Subcommand, Debug)]
 enum UserCommands {
     List,
     Create(CreateUserArgs),
+    /// Remove a user from the current workspace.
+    Deactivate(DeactivateUserArgs),
 }
 
 #[derive(Args, Debug)]
 struct CreateUserArgs {
+    #[arg(long, required_unless_present = "json")]
+    email: Option<String>,
+    #[arg(long, required_unless_present = "json")]
+    name: Option<String>,
+    #[arg(long, required_unless_present = "json")]
+    password: Option<String>,
+    #[arg(long, required_unless_present = "json")]
+    role: Option<String>,
+    /// JSON file (or `-` for stdin) with one user object or an array of them.
+    #[arg(long, conflicts_with_all = ["email", "name", "password", "role"])]
+    json: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct DeactivateUserArgs {
+    user_id: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum WsCommands {
+    /// Stream realtime events until interrupted.
+    Tail(WsTailArgs),
+}
+
+#[derive(Args, Debug)]
+struct WsTailArgs {
     #[arg(long)]
-    email: String,
+    channel: Option<String>,
+    #[arg(long = "event-type")]
+    event_type: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct ChatArgs {
+    /// Channel id or name.
     #[arg(long)]
-    name: String,
+    channel: String,
+    /// Recent messages to show before going live.
+    #[arg(long, default_value_t = 20)]
+    history: usize,
+}
+
+#[derive(Subcommand, Debug)]
+enum AdminCommands {
+    /// Show member, channel, message and attachment counters for the workspace.
+    Stats,
+    /// Delete messages older than the retention window.
+    Purge(AdminPurgeArgs),
+    /// Remove expired uploads and attachments of deleted messages.
+    Gc(AdminGcArgs),
+    /// Show or toggle instance maintenance mode with the operator token.
+    Maintenance {
+        /// Instance `OPERATOR_TOKEN`; not stored in the session.
+        #[arg(long, env = "GALYNX_OPERATOR_TOKEN", hide_env_values = true)]
+        operator_token: Option<String>,
+        #[command(subcommand)]
+        command: MaintenanceCommands,
+    },
+}
+
+#[derive(Args, Debug)]
+struct AdminPurgeArgs {
     #[arg(long)]
-    password: String,
+    older_than_days: u32,
     #[arg(long)]
-    role: String,
+    dry_run: bool,
+    /// Skip the confirmation prompt.
+    #[arg(long)]
+    yes: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Args, Debug)]
+struct AdminGcArgs {
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum MaintenanceCommands {
+    Status,
+    On {
+        #[arg(long)]
+        message: Option<String>,
+    },
+    Off,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct StoredSession {
     base_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     access_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     refresh_token: String,
     access_expires_at: i64,
     refresh_expires_at: i64,
+    /// Tokens live in the OS keyring instead of this file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    keyring: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyringTokens {
+    access_token: String,
+    refresh_token: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CredentialsFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, StoredSession>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -296,10 +587,22 @@ struct AuthTokensResponse {
     refresh_expires_at: i64,
 }
 
+#[derive(Debug, Deserialize)]
+struct PresignUploadResponse {
+    upload_id: String,
+    upload_url: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiErrorResponse {
     error: String,
+    #[serde(default)]
+    code: Option<String>,
     message: String,
+    #[serde(default)]
+    fields: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    request_id: Option<String>,
 }
 
 #[derive(Debug)]
@@ -317,6 +620,9 @@ impl std::error::Error for CliError {}
 
 type CliResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+static CREDENTIAL_STORE: OnceLock<CredentialStore> = OnceLock::new();
+
 #[tokio::main]
 async fn main() {
     if let Err(error) = run().await {
@@ -327,17 +633,45 @@ async fn main() {
 
 async fn run() -> CliResult<()> {
     let cli = Cli::parse();
+    let profile = cli
+        .profile
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_PROFILE);
+    let _ = ACTIVE_PROFILE.set(profile.to_string());
+    let _ = CREDENTIAL_STORE.set(cli.credential_store);
     let client = Client::new();
+    let output = cli.output.unwrap_or_else(|| {
+        if std::io::stdout().is_terminal() {
+            OutputFormat::Table
+        } else {
+            OutputFormat::Json
+        }
+    });
 
     match cli.command {
         Command::Auth { command } => run_auth(command, cli.base_url, &client).await,
-        Command::Workspaces { command } => run_workspaces(command, cli.base_url, &client).await,
-        Command::Channels { command } => run_channels(command, cli.base_url, &client).await,
-        Command::Messages { command } => run_messages(command, cli.base_url, &client).await,
-        Command::Threads { command } => run_threads(command, cli.base_url, &client).await,
+        Command::Workspaces { command } => {
+            run_workspaces(command, cli.base_url, &client, output).await
+        }
+        Command::Channels { command } => run_channels(command, cli.base_url, &client, output).await,
+        Command::Messages { command } => run_messages(command, cli.base_url, &client, output).await,
+        Command::Threads { command } => run_threads(command, cli.base_url, &client, output).await,
         Command::Attachments { command } => run_attachments(command, cli.base_url, &client).await,
-        Command::Audit { command } => run_audit(command, cli.base_url, &client).await,
-        Command::Users { command } => run_users(command, cli.base_url, &client).await,
+        Command::Reactions { command } => {
+            run_reactions(command, cli.base_url, &client, output).await
+        }
+        Command::Audit { command } => run_audit(command, cli.base_url, &client, output).await,
+        Command::Users { command } => run_users(command, cli.base_url, &client, output).await,
+        Command::Ws { command } => run_ws(command, cli.base_url, &client).await,
+        Command::Doctor => run_doctor(cli.base_url, &client).await,
+        Command::Chat(args) => run_chat(args, cli.base_url, &client).await,
+        Command::Admin { command } => run_admin(command, cli.base_url, &client, output).await,
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "galynx", &mut std::io::stdout());
+            Ok(())
+        }
     }
 }
 
@@ -377,6 +711,7 @@ async fn run_auth(
                 refresh_token: tokens.refresh_token,
                 access_expires_at: tokens.access_expires_at,
                 refresh_expires_at: tokens.refresh_expires_at,
+                keyring: false,
             })?;
             println!("login ok");
             Ok(())
@@ -421,6 +756,16 @@ async fn run_auth(
             println!("logout ok");
             Ok(())
         }
+        AuthCommands::Profiles => {
+            let credentials = read_credentials()?;
+            let active = active_profile();
+            for (name, session) in &credentials.profiles {
+                let marker = if name == active { "*" } else { " " };
+                let store = if session.keyring { "keyring" } else { "file" };
+                println!("{marker} {name}\t{}\t{store}", session.base_url);
+            }
+            Ok(())
+        }
     }
 }
 
@@ -428,10 +773,16 @@ async fn run_workspaces(
     command: WorkspaceCommands,
     base_url_flag: Option<String>,
     client: &Client,
+    output: OutputFormat,
 ) -> CliResult<()> {
     let mut session = load_session()?;
     session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
 
+    let columns = match &command {
+        WorkspaceCommands::List => Some(WORKSPACE_COLUMNS),
+        WorkspaceCommands::Members(_) => Some(WORKSPACE_MEMBER_COLUMNS),
+        _ => None,
+    };
     let response = match command {
         WorkspaceCommands::List => {
             send_authed_json(client, Method::GET, &mut session, "/workspaces", None, None).await?
@@ -453,6 +804,11 @@ async fn run_workspaces(
         }
         WorkspaceCommands::Onboard(args) => {
             let role = normalize_user_role(&args.role)?;
+            if role == "owner" {
+                return Err(Box::new(cli_error(
+                    "invalid role: owners cannot be onboarded, use admin|member".to_string(),
+                )));
+            }
             let path = format!("/workspaces/{}/members", args.workspace_id);
             send_authed_json(
                 client,
@@ -472,17 +828,26 @@ async fn run_workspaces(
     };
 
     save_session(&session)?;
-    print_or_ok(response).await
+    print_output(response, output, columns).await
 }
 
 async fn run_channels(
     command: ChannelCommands,
     base_url_flag: Option<String>,
     client: &Client,
+    output: OutputFormat,
 ) -> CliResult<()> {
     let mut session = load_session()?;
     session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
 
+    let columns = match &command {
+        ChannelCommands::List => Some(CHANNEL_COLUMNS),
+        ChannelCommands::Members(ChannelMembersArgs {
+            command: None | Some(ChannelMemberCommands::List(_)),
+            ..
+        }) => Some(CHANNEL_MEMBER_COLUMNS),
+        _ => None,
+    };
     let response = match command {
         ChannelCommands::List => {
             send_authed_json(client, Method::GET, &mut session, "/channels", None, None).await?
@@ -505,9 +870,58 @@ async fn run_channels(
             let path = format!("/channels/{}", args.channel_id);
             send_authed_json(client, Method::DELETE, &mut session, &path, None, None).await?
         }
-        ChannelCommands::Members(args) => {
-            let path = format!("/channels/{}/members", args.channel_id);
-            send_authed_json(client, Method::GET, &mut session, &path, None, None).await?
+        ChannelCommands::Members(args) => match (args.command, args.channel) {
+            (Some(ChannelMemberCommands::List(ChannelRefArgs { channel })), _)
+            | (None, Some(channel)) => {
+                let channel_id = resolve_channel_id(client, &mut session, &channel).await?;
+                let path = format!("/channels/{channel_id}/members");
+                send_authed_json(client, Method::GET, &mut session, &path, None, None).await?
+            }
+            (Some(ChannelMemberCommands::Add(args)), _) => {
+                let channel_id = resolve_channel_id(client, &mut session, &args.channel).await?;
+                let user_id = resolve_user_id(client, &mut session, &args.user).await?;
+                let path = format!("/channels/{channel_id}/members");
+                send_authed_json(
+                    client,
+                    Method::POST,
+                    &mut session,
+                    &path,
+                    Some(json!({ "user_id": user_id })),
+                    None,
+                )
+                .await?
+            }
+            (Some(ChannelMemberCommands::Remove(args)), _) => {
+                let channel_id = resolve_channel_id(client, &mut session, &args.channel).await?;
+                let user_id = resolve_user_id(client, &mut session, &args.user).await?;
+                let path = format!("/channels/{channel_id}/members/{user_id}");
+                send_authed_json(client, Method::DELETE, &mut session, &path, None, None).await?
+            }
+            (None, None) => {
+                return Err(Box::new(cli_error(
+                    "missing channel; use `galynx channels members list <channel>`".to_string(),
+                )));
+            }
+        },
+        ChannelCommands::Join(args) => {
+            let channel_id = resolve_channel_id(client, &mut session, &args.channel).await?;
+            let user_id = current_user_id(client, &mut session).await?;
+            let path = format!("/channels/{channel_id}/members");
+            send_authed_json(
+                client,
+                Method::POST,
+                &mut session,
+                &path,
+                Some(json!({ "user_id": user_id })),
+                None,
+            )
+            .await?
+        }
+        ChannelCommands::Leave(args) => {
+            let channel_id = resolve_channel_id(client, &mut session, &args.channel).await?;
+            let user_id = current_user_id(client, &mut session).await?;
+            let path = format!("/channels/{channel_id}/members/{user_id}");
+            send_authed_json(client, Method::DELETE, &mut session, &path, None, None).await?
         }
         ChannelCommands::MemberAdd(args) => {
             let path = format!("/channels/{}/members", args.channel_id);
@@ -528,13 +942,14 @@ async fn run_channels(
     };
 
     save_session(&session)?;
-    print_or_ok(response).await
+    print_output(response, output, columns).await
 }
 
 async fn run_messages(
     command: MessageCommands,
     base_url_flag: Option<String>,
     client: &Client,
+    output: OutputFormat,
 ) -> CliResult<()> {
     let mut session = load_session()?;
     session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
@@ -542,8 +957,15 @@ async fn run_messages(
     let response = match command {
         MessageCommands::List(args) => {
             let path = format!("/channels/{}/messages", args.channel);
-            let query = cursor_limit_query(args.cursor, args.limit);
-            send_authed_json(client, Method::GET, &mut session, &path, None, Some(query)).await?
+            return print_pages(
+                client,
+                &mut session,
+                &path,
+                args.page,
+                output,
+                MESSAGE_COLUMNS,
+            )
+            .await;
         }
         MessageCommands::Send(args) => {
             let path = format!("/channels/{}/messages", args.channel);
@@ -573,6 +995,11 @@ async fn run_messages(
             let path = format!("/messages/{}", args.message_id);
             send_authed_json(client, Method::DELETE, &mut session, &path, None, None).await?
         }
+        MessageCommands::Search(args) => {
+            let result = search_messages(client, &mut session, args, output).await;
+            save_session(&session)?;
+            return result;
+        }
     };
 
     save_session(&session)?;
@@ -583,6 +1010,7 @@ async fn run_threads(
     command: ThreadCommands,
     base_url_flag: Option<String>,
     client: &Client,
+    output: OutputFormat,
 ) -> CliResult<()> {
     let mut session = load_session()?;
     session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
@@ -594,8 +1022,15 @@ async fn run_threads(
         }
         ThreadCommands::Replies(args) => {
             let path = format!("/threads/{}/replies", args.root_id);
-            let query = cursor_limit_query(args.cursor, args.limit);
-            send_authed_json(client, Method::GET, &mut session, &path, None, Some(query)).await?
+            return print_pages(
+                client,
+                &mut session,
+                &path,
+                args.page,
+                output,
+                MESSAGE_COLUMNS,
+            )
+            .await;
         }
         ThreadCommands::Reply(args) => {
             let path = format!("/threads/{}/replies", args.root_id);
@@ -659,6 +1094,44 @@ async fn run_attachments(
             let path = format!("/attachments/{}", args.attachment_id);
             send_authed_json(client, Method::GET, &mut session, &path, None, None).await?
         }
+        AttachmentCommands::Upload(args) => {
+            let (filename, content_type, size_bytes) =
+                resolve_attachment_presign_fields(&AttachmentPresignArgs {
+                    channel: args.channel.clone(),
+                    file: Some(args.file.clone()),
+                    filename: None,
+                    content_type: args.content_type.clone(),
+                    size_bytes: None,
+                })?;
+            let response = send_authed_json(
+                client,
+                Method::POST,
+                &mut session,
+                "/attachments/presign",
+                Some(json!({
+                    "channel_id": args.channel,
+                    "filename": filename,
+                    "content_type": content_type,
+                    "size_bytes": size_bytes,
+                })),
+                None,
+            )
+            .await?;
+            let presigned: PresignUploadResponse = parse_json(response).await?;
+            upload_file(client, &presigned.upload_url, &args.file, &content_type).await?;
+            send_authed_json(
+                client,
+                Method::POST,
+                &mut session,
+                "/attachments/commit",
+                Some(json!({
+                    "upload_id": presigned.upload_id,
+                    "message_id": args.message_id,
+                })),
+                None,
+            )
+            .await?
+        }
     };
 
     save_session(&session)?;
@@ -719,70 +1192,933 @@ fn resolve_attachment_presign_fields(
     Ok((filename, content_type, size_bytes))
 }
 
-async fn run_audit(
-    command: AuditCommands,
+async fn upload_file(
+    client: &Client,
+    upload_url: &str,
+    file_path: &Path,
+    content_type: &str,
+) -> CliResult<()> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    let bytes = fs::read(file_path)?;
+    let total = bytes.len();
+    let chunks = bytes
+        .chunks(CHUNK_SIZE)
+        .map(<[u8]>::to_vec)
+        .collect::<Vec<_>>();
+    let mut sent = 0usize;
+    let stream = futures_util::stream::iter(chunks).map(move |chunk| {
+        sent += chunk.len();
+        eprint!(
+            "\ruploading {sent}/{total} bytes ({}%)",
+            sent * 100 / total.max(1)
+        );
+        let _ = std::io::stderr().flush();
+        Ok::<_, std::io::Error>(chunk)
+    });
+
+    let response = client
+        .put(upload_url)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, total)
+        .body(Body::wrap_stream(stream))
+        .send()
+        .await?;
+    eprintln!();
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(Box::new(cli_error(format!(
+            "upload failed with {}: {}",
+            status.as_u16(),
+            body
+        ))));
+    }
+    Ok(())
+}
+
+async fn run_reactions(
+    command: ReactionCommands,
     base_url_flag: Option<String>,
     client: &Client,
+    output: OutputFormat,
 ) -> CliResult<()> {
     let mut session = load_session()?;
     session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
 
+    let columns = match &command {
+        ReactionCommands::List(_) => Some(REACTION_COLUMNS),
+        _ => None,
+    };
     let response = match command {
-        AuditCommands::List(args) => {
-            let query = cursor_limit_query(args.cursor, args.limit);
+        ReactionCommands::Add(args) => {
+            let path = format!("/messages/{}/reactions", args.message_id);
             send_authed_json(
                 client,
-                Method::GET,
+                Method::POST,
                 &mut session,
-                "/audit",
+                &path,
+                Some(json!({ "emoji": args.emoji })),
                 None,
-                Some(query),
             )
             .await?
         }
+        ReactionCommands::Remove(args) => {
+            let path = format!(
+                "/messages/{}/reactions/{}",
+                args.message_id,
+                encode_path_segment(args.emoji.trim())
+            );
+            send_authed_json(client, Method::DELETE, &mut session, &path, None, None).await?
+        }
+        ReactionCommands::List(args) => {
+            let path = format!("/messages/{}/reactions", args.message_id);
+            send_authed_json(client, Method::GET, &mut session, &path, None, None).await?
+        }
     };
 
     save_session(&session)?;
-    print_or_ok(response).await
+    print_output(response, output, columns).await
 }
 
-async fn run_users(
-    command: UserCommands,
+fn encode_path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+async fn run_audit(
+    command: AuditCommands,
     base_url_flag: Option<String>,
     client: &Client,
+    output: OutputFormat,
 ) -> CliResult<()> {
     let mut session = load_session()?;
     session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
 
-    let response = match command {
-        UserCommands::List => {
-            send_authed_json(client, Method::GET, &mut session, "/users", None, None).await?
-        }
-        UserCommands::Create(args) => {
-            let role = normalize_user_role(&args.role)?;
-            send_authed_json(
+    match command {
+        AuditCommands::List(args) => {
+            print_pages(
                 client,
-                Method::POST,
                 &mut session,
-                "/users",
-                Some(json!({
-                    "email": args.email,
-                    "name": args.name,
-                    "password": args.password,
-                    "role": role,
-                })),
-                None,
+                "/audit",
+                args.page,
+                output,
+                AUDIT_COLUMNS,
             )
-            .await?
+            .await
         }
-    };
+        AuditCommands::Export(args) => {
+            let since = args.since.as_deref().map(parse_time_arg).transpose()?;
+            let until = args.until.as_deref().map(parse_time_arg).transpose()?;
+            let mut entries = Vec::new();
+            let mut cursor = None;
+            'pages: loop {
+                let query = cursor_limit_query(cursor.take(), Some(MAX_PAGE_LIMIT));
+                let response = send_authed_json(
+                    client,
+                    Method::GET,
+                    &mut session,
+                    "/audit",
+                    None,
+                    Some(query),
+                )
+                .await?;
+                let page: Value = response.json().await?;
+                for entry in page["items"].as_array().into_iter().flatten() {
+                    let created_at = entry["created_at"].as_i64().unwrap_or_default();
+                    if since.is_some_and(|since| created_at < since) {
+                        break 'pages;
+                    }
+                    if until.is_none_or(|until| created_at <= until) {
+                        entries.push(entry.clone());
+                    }
+                }
+                match page["next_cursor"].as_str() {
+                    Some(next) => cursor = Some(next.to_string()),
+                    None => break,
+                }
+            }
+            save_session(&session)?;
 
-    save_session(&session)?;
-    print_or_ok(response).await
+            entries.reverse();
+            match args.format {
+                ExportFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+                ExportFormat::Jsonl => {
+                    for entry in &entries {
+                        println!("{}", serde_json::to_string(entry)?);
+                    }
+                }
+            }
+            eprintln!("exported {} audit entries", entries.len());
+            Ok(())
+        }
+        AuditCommands::Tail(args) => {
+            let interval = std::time::Duration::from_secs(args.interval.max(1));
+            let mut last_seen = (Utc::now().timestamp_millis(), String::new());
+            eprintln!("watching audit log; press Ctrl-C to stop");
+            loop {
+                let query = cursor_limit_query(None, Some(MAX_PAGE_LIMIT));
+                let response = send_authed_json(
+                    client,
+                    Method::GET,
+                    &mut session,
+                    "/audit",
+                    None,
+                    Some(query),
+                )
+                .await?;
+                save_session(&session)?;
+                let page: Value = response.json().await?;
+                let mut fresh = page["items"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|entry| audit_position(entry) > last_seen)
+                    .collect::<Vec<_>>();
+                fresh.reverse();
+                for entry in fresh {
+                    last_seen = audit_position(entry);
+                    if output == OutputFormat::Json {
+                        println!("{}", serde_json::to_string(entry)?);
+                    } else {
+                        let cells = AUDIT_COLUMNS
+                            .iter()
+                            .map(|column| render_cell(column, &entry[*column]))
+                            .collect::<Vec<_>>();
+                        let separator = if output == OutputFormat::Plain {
+                            "\t"
+                        } else {
+                            "  "
+                        };
+                        println!("{}", cells.join(separator));
+                    }
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = tokio::signal::ctrl_c() => return Ok(()),
+                }
+            }
+        }
+    }
 }
 
-fn normalize_user_role(value: &str) -> CliResult<&'static str> {
-    match value.trim().to_ascii_lowercase().as_str() {
+fn audit_position(entry: &Value) -> (i64, String) {
+    (
+        entry["created_at"].as_i64().unwrap_or_default(),
+        entry["id"].as_str().unwrap_or_default().to_string(),
+    )
+}
+
+fn parse_time_arg(value: &str) -> CliResult<i64> {
+    let value = value.trim();
+    if let Ok(millis) = value.parse::<i64>() {
+        return Ok(millis);
+    }
+    if let Some(unit) = value.chars().last()
+        && let Ok(amount) = value[..value.len() - unit.len_utf8()].parse::<i64>()
+    {
+        let seconds = match unit {
+            's' => Some(amount),
+            'm' => Some(amount * 60),
+            'h' => Some(amount * 60 * 60),
+            'd' => Some(amount * 24 * 60 * 60),
+            _ => None,
+        };
+        if let Some(seconds) = seconds {
+            return Ok(Utc::now().timestamp_millis() - seconds * 1000);
+        }
+    }
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(at.timestamp_millis());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date
+            .and_hms_opt(0, 0, 0)
+            .map(|at| at.and_utc().timestamp_millis())
+            .unwrap_or_default());
+    }
+    Err(Box::new(cli_error(format!(
+        "invalid time {value:?}: use RFC 3339, YYYY-MM-DD, epoch millis or an age like 24h"
+    ))))
+}
+
+async fn run_admin(
+    command: AdminCommands,
+    base_url_flag: Option<String>,
+    client: &Client,
+    output: OutputFormat,
+) -> CliResult<()> {
+    let (method, path, body) = match command {
+        AdminCommands::Stats => (Method::GET, "/admin/workspace/stats", None),
+        AdminCommands::Purge(args) => {
+            if !args.dry_run && !args.yes {
+                let prompt = format!(
+                    "permanently delete messages older than {} days?",
+                    args.older_than_days
+                );
+                if !std::io::stdin().is_terminal() {
+                    return Err(Box::new(cli_error(
+                        "refusing to purge without confirmation; pass --yes or --dry-run"
+                            .to_string(),
+                    )));
+                }
+                if !confirm(&prompt)? {
+                    return Ok(());
+                }
+            }
+            (
+                Method::POST,
+                "/admin/retention/purge",
+                Some(json!({
+                    "older_than_days": args.older_than_days,
+                    "dry_run": args.dry_run,
+                })),
+            )
+        }
+        AdminCommands::Gc(args) => (
+            Method::POST,
+            "/admin/attachments/gc",
+            Some(json!({ "dry_run": args.dry_run })),
+        ),
+        AdminCommands::Maintenance {
+            operator_token,
+            command,
+        } => return run_maintenance(command, operator_token, base_url_flag, client, output).await,
+    };
+
+    let mut session = load_session()?;
+    session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
+
+    let response = send_authed_json(client, method, &mut session, path, body, None).await?;
+    save_session(&session)?;
+    let value: Value = response.json().await?;
+    print_fields(&value, output)
+}
+
+/// Instance maintenance is an operator action: it authenticates with the
+/// `OPERATOR_TOKEN` instead of the stored user session.
+async fn run_maintenance(
+    command: MaintenanceCommands,
+    operator_token: Option<String>,
+    base_url_flag: Option<String>,
+    client: &Client,
+    output: OutputFormat,
+) -> CliResult<()> {
+    let operator_token = operator_token.ok_or_else(|| {
+        cli_error("maintenance requires --operator-token or GALYNX_OPERATOR_TOKEN".to_string())
+    })?;
+    let stored_base_url = load_session_if_exists()
+        .ok()
+        .flatten()
+        .map(|session| session.base_url);
+    let base_url = resolve_base_url(base_url_flag.as_deref(), stored_base_url.as_deref());
+    let (method, body) = match command {
+        MaintenanceCommands::Status => (Method::GET, None),
+        MaintenanceCommands::On { message } => (
+            Method::PUT,
+            Some(json!({ "enabled": true, "message": message })),
+        ),
+        MaintenanceCommands::Off => (Method::PUT, Some(json!({ "enabled": false }))),
+    };
+    let response = send_json(
+        client,
+        method,
+        &base_url,
+        "/admin/maintenance",
+        body,
+        None,
+        Some(&operator_token),
+    )
+    .await?;
+    let value: Value = response.json().await?;
+    print_fields(&value, output)
+}
+
+fn print_fields(value: &Value, output: OutputFormat) -> CliResult<()> {
+    let Some(fields) = value.as_object().filter(|_| output != OutputFormat::Json) else {
+        println!("{}", serde_json::to_string_pretty(value)?);
+        return Ok(());
+    };
+    let width = fields.keys().map(String::len).max().unwrap_or_default();
+    for (key, field) in fields {
+        let cell = render_cell(key, field);
+        if output == OutputFormat::Plain {
+            println!("{key}\t{cell}");
+        } else {
+            println!("{key:<width$}  {cell}");
+        }
+    }
+    Ok(())
+}
+
+async fn run_users(
+    command: UserCommands,
+    base_url_flag: Option<String>,
+    client: &Client,
+    output: OutputFormat,
+) -> CliResult<()> {
+    let mut session = load_session()?;
+    session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
+
+    let columns = match &command {
+        UserCommands::List => Some(USER_COLUMNS),
+        _ => None,
+    };
+    let response = match command {
+        UserCommands::List => {
+            send_authed_json(client, Method::GET, &mut session, "/users", None, None).await?
+        }
+        UserCommands::Create(CreateUserArgs {
+            json: Some(path), ..
+        }) => {
+            let results = create_users_from_json(client, &mut session, &path).await?;
+            save_session(&session)?;
+            println!("{}", serde_json::to_string_pretty(&results)?);
+            return Ok(());
+        }
+        UserCommands::Create(args) => {
+            let role = normalize_user_role(args.role.as_deref().unwrap_or_default())?;
+            send_authed_json(
+                client,
+                Method::POST,
+                &mut session,
+                "/users",
+                Some(json!({
+                    "email": args.email,
+                    "name": args.name,
+                    "password": args.password,
+                    "role": role,
+                })),
+                None,
+            )
+            .await?
+        }
+        UserCommands::Deactivate(args) => {
+            let path = format!("/users/{}", args.user_id);
+            send_authed_json(client, Method::DELETE, &mut session, &path, None, None).await?
+        }
+    };
+
+    save_session(&session)?;
+    print_output(response, output, columns).await
+}
+
+async fn create_users_from_json(
+    client: &Client,
+    session: &mut StoredSession,
+    path: &Path,
+) -> CliResult<Vec<Value>> {
+    let raw = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        fs::read_to_string(path)?
+    };
+    let users = match serde_json::from_str::<Value>(&raw)? {
+        Value::Array(items) => items,
+        item @ Value::Object(_) => vec![item],
+        _ => {
+            return Err(Box::new(cli_error(
+                "--json must contain a user object or an array of them".to_string(),
+            )));
+        }
+    };
+
+    let mut results = Vec::with_capacity(users.len());
+    for mut user in users {
+        let email = user["email"].as_str().unwrap_or_default().to_string();
+        let role = match normalize_user_role(user["role"].as_str().unwrap_or("member")) {
+            Ok(role) => role,
+            Err(error) => {
+                results.push(json!({ "email": email, "ok": false, "error": error.to_string() }));
+                continue;
+            }
+        };
+        user["role"] = json!(role);
+        match send_authed_json(client, Method::POST, session, "/users", Some(user), None).await {
+            Ok(response) => {
+                let created: Value = response.json().await?;
+                results.push(json!({ "email": email, "ok": true, "id": created["id"] }));
+            }
+            Err(error) => {
+                results.push(json!({ "email": email, "ok": false, "error": error.to_string() }));
+            }
+        }
+    }
+    Ok(results)
+}
+
+async fn run_ws(
+    command: WsCommands,
+    base_url_flag: Option<String>,
+    client: &Client,
+) -> CliResult<()> {
+    let mut session = load_session()?;
+    session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
+    if session.access_expires_at <= Utc::now().timestamp() {
+        refresh_session(client, &mut session).await?;
+    }
+    save_session(&session)?;
+
+    match command {
+        WsCommands::Tail(args) => {
+            let event_types = args
+                .event_type
+                .iter()
+                .map(|value| value.trim().to_ascii_uppercase())
+                .collect::<Vec<_>>();
+            let mut request = ws_endpoint(&session.base_url).into_client_request()?;
+            request.headers_mut().insert(
+                "Authorization",
+                HeaderValue::from_str(&format!("Bearer {}", session.access_token))?,
+            );
+            let (mut stream, _) = connect_async(request).await?;
+            eprintln!("connected; press Ctrl-C to stop");
+
+            loop {
+                let frame = tokio::select! {
+                    frame = stream.next() => frame,
+                    _ = tokio::signal::ctrl_c() => break,
+                };
+                let Some(frame) = frame else {
+                    eprintln!("connection closed by server");
+                    break;
+                };
+                let WsMessage::Text(text) = frame? else {
+                    continue;
+                };
+                let event: Value = serde_json::from_str(&text)?;
+                if !ws_event_matches(&event, args.channel.as_deref(), &event_types) {
+                    continue;
+                }
+                println!("{}", serde_json::to_string_pretty(&event)?);
+            }
+            let _ = stream.close(None).await;
+            Ok(())
+        }
+    }
+}
+
+async fn run_chat(args: ChatArgs, base_url_flag: Option<String>, client: &Client) -> CliResult<()> {
+    let mut session = load_session()?;
+    session.base_url = resolve_base_url(base_url_flag.as_deref(), Some(&session.base_url));
+    let channel_id = resolve_channel_id(client, &mut session, &args.channel).await?;
+    let me = send_authed_json(client, Method::GET, &mut session, "/me", None, None)
+        .await?
+        .json::<Value>()
+        .await?;
+    let my_id = me["id"].as_str().unwrap_or_default().to_string();
+    let names = chat_member_names(client, &mut session, &me).await;
+    let sender_name = |sender_id: &str| {
+        if sender_id == my_id {
+            return "you".to_string();
+        }
+        names
+            .get(sender_id)
+            .cloned()
+            .unwrap_or_else(|| sender_id.chars().take(8).collect())
+    };
+
+    if args.history > 0 {
+        let path = format!("/channels/{channel_id}/messages");
+        let query = cursor_limit_query(None, Some(args.history.min(MAX_PAGE_LIMIT)));
+        let page: Value =
+            send_authed_json(client, Method::GET, &mut session, &path, None, Some(query))
+                .await?
+                .json()
+                .await?;
+        let mut items = page["items"].as_array().cloned().unwrap_or_default();
+        items.sort_by_key(|item| item["created_at"].as_i64().unwrap_or_default());
+        for item in items.iter().filter(|item| item["deleted_at"].is_null()) {
+            print_chat_line(
+                item,
+                &sender_name(item["sender_id"].as_str().unwrap_or("-")),
+            );
+        }
+    }
+    save_session(&session)?;
+
+    let mut request = ws_endpoint(&session.base_url).into_client_request()?;
+    request.headers_mut().insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Bearer {}", session.access_token))?,
+    );
+    let (stream, _) = connect_async(request).await?;
+    let (mut sink, mut stream) = stream.split();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    eprintln!(
+        "connected to {}; type a message and press Enter, /quit to leave",
+        args.channel
+    );
+
+    loop {
+        tokio::select! {
+            frame = stream.next() => {
+                let Some(frame) = frame else {
+                    eprintln!("connection closed by server");
+                    break;
+                };
+                let WsMessage::Text(text) = frame? else {
+                    continue;
+                };
+                let event: Value = serde_json::from_str(&text)?;
+                match event["event_type"].as_str() {
+                    Some("ERROR") => eprintln!(
+                        "! {}",
+                        event["payload"]["error"].as_str().unwrap_or("server error")
+                    ),
+                    Some(event_type @ ("MESSAGE_CREATED" | "MESSAGE_UPDATED" | "MESSAGE_DELETED"))
+                        if event["channel_id"].as_str() == Some(channel_id.as_str()) =>
+                    {
+                        let message = &event["payload"];
+                        let sender = sender_name(message["sender_id"].as_str().unwrap_or("-"));
+                        match event_type {
+                            "MESSAGE_CREATED" => print_chat_line(message, &sender),
+                            "MESSAGE_UPDATED" => print_chat_line(message, &format!("{sender} (edited)")),
+                            _ => println!("* a message from {sender} was deleted"),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    break;
+                };
+                let body = line.trim();
+                if body.is_empty() {
+                    continue;
+                }
+                if body == "/quit" {
+                    break;
+                }
+                let command = json!({
+                    "command": "SEND_MESSAGE",
+                    "client_msg_id": uuid::Uuid::new_v4().to_string(),
+                    "payload": { "channel_id": channel_id, "body_md": body },
+                });
+                sink.send(WsMessage::Text(command.to_string())).await?;
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    let _ = sink.close().await;
+    Ok(())
+}
+
+/// Display names are only readable by owners/admins; members see short ids.
+async fn chat_member_names(
+    client: &Client,
+    session: &mut StoredSession,
+    me: &Value,
+) -> BTreeMap<String, String> {
+    let path = format!(
+        "/workspaces/{}/members",
+        me["workspace_id"].as_str().unwrap_or_default()
+    );
+    let Ok(response) = send_authed_json(client, Method::GET, session, &path, None, None).await
+    else {
+        return BTreeMap::new();
+    };
+    let members: Value = response.json().await.unwrap_or_default();
+    members
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|member| {
+            Some((
+                member["user_id"].as_str()?.to_string(),
+                member["name"].as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn print_chat_line(message: &Value, sender: &str) {
+    let at = message["created_at"]
+        .as_i64()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|at| at.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_default();
+    let thread = if message["thread_root_id"].is_null() {
+        ""
+    } else {
+        "(thread) "
+    };
+    println!(
+        "[{at}] {sender}: {thread}{}",
+        message["body_md"].as_str().unwrap_or_default()
+    );
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+fn report_check(status: CheckStatus, name: &str, detail: &str, hint: Option<&str>) {
+    let label = match status {
+        CheckStatus::Ok => "ok  ",
+        CheckStatus::Warn => "warn",
+        CheckStatus::Fail => "FAIL",
+    };
+    println!("[{label}] {name:<10} {detail}");
+    if let Some(hint) = hint.filter(|_| status != CheckStatus::Ok) {
+        println!("           -> {hint}");
+    }
+}
+
+async fn run_doctor(base_url_flag: Option<String>, client: &Client) -> CliResult<()> {
+    let stored = load_session_if_exists()?;
+    let base_url = resolve_base_url(
+        base_url_flag.as_deref(),
+        stored.as_ref().map(|session| session.base_url.as_str()),
+    );
+    let mut failed = false;
+    let mut check = |status: CheckStatus, name: &str, detail: &str, hint: Option<&str>| {
+        failed |= status == CheckStatus::Fail;
+        report_check(status, name, detail, hint);
+    };
+    println!("profile {} -> {base_url}", active_profile());
+
+    let started = std::time::Instant::now();
+    let health = client
+        .get(endpoint(&base_url, "/health"))
+        .timeout(std::time::Duration::from_secs(DOCTOR_TIMEOUT_SECONDS))
+        .send()
+        .await;
+    let server_date = match health {
+        Ok(response) => {
+            let elapsed = started.elapsed().as_millis();
+            let status = response.status();
+            let date = response
+                .headers()
+                .get(header::DATE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| chrono::DateTime::parse_from_rfc2822(value).ok());
+            check(
+                CheckStatus::Ok,
+                "reachable",
+                &format!("{base_url} answered in {elapsed} ms"),
+                None,
+            );
+            if status.is_success() {
+                check(CheckStatus::Ok, "health", "/health returned 200", None);
+            } else {
+                check(
+                    CheckStatus::Fail,
+                    "health",
+                    &format!("/health returned {status}"),
+                    Some("check that the base URL points at galynx-api and not a proxy page"),
+                );
+            }
+            date
+        }
+        Err(error) => {
+            check(
+                CheckStatus::Fail,
+                "reachable",
+                &format!("cannot reach {base_url}: {error}"),
+                Some("verify --base-url / GALYNX_API_BASE_URL, DNS and that the server is running"),
+            );
+            return doctor_result(failed);
+        }
+    };
+
+    match client.get(endpoint(&base_url, "/ready")).send().await {
+        Ok(response) if response.status().is_success() => {
+            check(CheckStatus::Ok, "ready", "/ready returned 200", None);
+        }
+        Ok(response) => {
+            let status = response.status();
+            let body: Value = response.json().await.unwrap_or_default();
+            let failing = body["checks"]
+                .as_object()
+                .map(|checks| {
+                    checks
+                        .iter()
+                        .filter(|(_, check)| check["status"] != "ok")
+                        .map(|(name, check)| {
+                            format!("{name}: {}", check["error"].as_str().unwrap_or("down"))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            check(
+                CheckStatus::Fail,
+                "ready",
+                &format!("/ready returned {status} {failing}"),
+                Some("a dependency (mongo, redis, object storage) is down; see server logs"),
+            );
+        }
+        Err(error) => check(
+            CheckStatus::Fail,
+            "ready",
+            &format!("/ready failed: {error}"),
+            None,
+        ),
+    }
+
+    match server_date {
+        Some(date) => {
+            let skew = (Utc::now().timestamp() - date.timestamp()).abs();
+            let status = if skew > MAX_CLOCK_SKEW_SECONDS {
+                CheckStatus::Fail
+            } else if skew > WARN_CLOCK_SKEW_SECONDS {
+                CheckStatus::Warn
+            } else {
+                CheckStatus::Ok
+            };
+            check(
+                status,
+                "clock",
+                &format!("local clock differs from server by {skew}s"),
+                Some("sync the local clock (NTP); token expiry checks depend on it"),
+            );
+        }
+        None => check(
+            CheckStatus::Warn,
+            "clock",
+            "server sent no Date header; skew not measured",
+            None,
+        ),
+    }
+
+    let Some(mut session) = stored else {
+        check(
+            CheckStatus::Warn,
+            "session",
+            "no stored session for this profile",
+            Some("run `galynx auth login` to check tokens and realtime"),
+        );
+        return doctor_result(failed);
+    };
+    session.base_url = base_url;
+    let now = Utc::now().timestamp();
+    if session.refresh_expires_at <= now {
+        check(
+            CheckStatus::Fail,
+            "token",
+            "refresh token expired",
+            Some("run `galynx auth login` again"),
+        );
+        return doctor_result(failed);
+    }
+    if session.access_expires_at <= now {
+        check(
+            CheckStatus::Warn,
+            "token",
+            "access token expired; it will be refreshed",
+            None,
+        );
+    } else {
+        check(
+            CheckStatus::Ok,
+            "token",
+            &format!(
+                "access token valid for {}s, refresh for {}h",
+                session.access_expires_at - now,
+                (session.refresh_expires_at - now) / 3600
+            ),
+            None,
+        );
+    }
+    match send_authed_json(client, Method::GET, &mut session, "/me", None, None).await {
+        Ok(response) => {
+            let me: Value = response.json().await.unwrap_or_default();
+            save_session(&session)?;
+            check(
+                CheckStatus::Ok,
+                "auth",
+                &format!(
+                    "authenticated as {} ({})",
+                    me["email"].as_str().unwrap_or("?"),
+                    me["role"].as_str().unwrap_or("?")
+                ),
+                None,
+            );
+        }
+        Err(error) => {
+            check(
+                CheckStatus::Fail,
+                "auth",
+                &format!("/me failed: {error}"),
+                Some("run `galynx auth login` again"),
+            );
+            return doctor_result(failed);
+        }
+    }
+
+    let mut request = ws_endpoint(&session.base_url).into_client_request()?;
+    request.headers_mut().insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Bearer {}", session.access_token))?,
+    );
+    let upgrade = tokio::time::timeout(
+        std::time::Duration::from_secs(DOCTOR_TIMEOUT_SECONDS),
+        connect_async(request),
+    )
+    .await;
+    match upgrade {
+        Ok(Ok((mut stream, _))) => {
+            let _ = stream.close(None).await;
+            check(
+                CheckStatus::Ok,
+                "websocket",
+                "upgrade to /ws succeeded",
+                None,
+            );
+        }
+        Ok(Err(error)) => check(
+            CheckStatus::Fail,
+            "websocket",
+            &format!("upgrade failed: {error}"),
+            Some("make sure proxies forward `Upgrade`/`Connection` headers for /api/v1/ws"),
+        ),
+        Err(_) => check(
+            CheckStatus::Fail,
+            "websocket",
+            "upgrade timed out",
+            Some("make sure proxies forward `Upgrade`/`Connection` headers for /api/v1/ws"),
+        ),
+    }
+
+    doctor_result(failed)
+}
+
+fn doctor_result(failed: bool) -> CliResult<()> {
+    if failed {
+        return Err(Box::new(cli_error("some checks failed".to_string())));
+    }
+    println!("no failing checks");
+    Ok(())
+}
+
+fn ws_event_matches(event: &Value, channel: Option<&str>, event_types: &[String]) -> bool {
+    if let Some(channel) = channel
+        && event["channel_id"].as_str() != Some(channel)
+    {
+        return false;
+    }
+    event_types.is_empty()
+        || event["event_type"]
+            .as_str()
+            .is_some_and(|event_type| event_types.iter().any(|wanted| wanted == event_type))
+}
+
+fn normalize_user_role(value: &str) -> CliResult<&'static str> {
+    match value.trim().to_ascii_lowercase().as_str() {
         "admin" => Ok("admin"),
         "member" => Ok("member"),
         "owner" => Ok("owner"),
@@ -792,6 +2128,182 @@ fn normalize_user_role(value: &str) -> CliResult<&'static str> {
     }
 }
 
+async fn search_messages(
+    client: &Client,
+    session: &mut StoredSession,
+    args: SearchMessagesArgs,
+    output: OutputFormat,
+) -> CliResult<()> {
+    let query_text = args.query.trim().to_string();
+    if query_text.is_empty() {
+        return Err(Box::new(cli_error("--query must not be empty".to_string())));
+    }
+    let mut query = cursor_limit_query(args.cursor, args.limit);
+    query.push(("q".to_string(), query_text.clone()));
+    if let Some(channel) = args.channel {
+        let channel_id = resolve_channel_id(client, session, &channel).await?;
+        query.push(("channel_id".to_string(), channel_id));
+    }
+
+    let response = send_authed_json(
+        client,
+        Method::GET,
+        session,
+        "/search/messages",
+        None,
+        Some(query),
+    )
+    .await
+    .map_err(|error| {
+        if extract_status(&*error) == Some(StatusCode::NOT_FOUND) {
+            Box::new(cli_error(
+                "message search is not available on this server".to_string(),
+            )) as Box<dyn std::error::Error + Send + Sync>
+        } else {
+            error
+        }
+    })?;
+    let page: Value = response.json().await?;
+    if output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&page)?);
+        return Ok(());
+    }
+
+    let highlight = output == OutputFormat::Table && std::io::stdout().is_terminal();
+    let items = page["items"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if items.is_empty() {
+        eprintln!("no messages match {query_text:?}");
+    }
+    for item in items {
+        let channel_id = item["channel_id"].as_str().unwrap_or("-");
+        let message_id = item["id"].as_str().unwrap_or("-");
+        let created_at = render_cell("created_at", &item["created_at"]);
+        let sender_id = item["sender_id"].as_str().unwrap_or("-");
+        let body = item["body_md"].as_str().unwrap_or_default();
+        let snippet = search_snippet(body, &query_text, highlight);
+        let permalink = message_permalink(&session.base_url, channel_id, message_id);
+        if output == OutputFormat::Plain {
+            println!("{created_at}\t{sender_id}\t{snippet}\t{permalink}");
+        } else {
+            println!("{created_at}  {sender_id}");
+            println!("  {snippet}");
+            println!("  {permalink}");
+        }
+    }
+    if let Some(cursor) = page["next_cursor"].as_str() {
+        eprintln!("next cursor: {cursor}");
+    }
+    Ok(())
+}
+
+async fn resolve_channel_id(
+    client: &Client,
+    session: &mut StoredSession,
+    channel: &str,
+) -> CliResult<String> {
+    if uuid::Uuid::parse_str(channel).is_ok() {
+        return Ok(channel.to_string());
+    }
+    let name = channel.trim_start_matches('#');
+    let response = send_authed_json(client, Method::GET, session, "/channels", None, None).await?;
+    let channels: Value = response.json().await?;
+    channels
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|item| item["name"].as_str() == Some(name))
+        .and_then(|item| item["id"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            Box::new(cli_error(format!("channel not found: {channel}")))
+                as Box<dyn std::error::Error + Send + Sync>
+        })
+}
+
+async fn resolve_user_id(
+    client: &Client,
+    session: &mut StoredSession,
+    user: &str,
+) -> CliResult<String> {
+    if uuid::Uuid::parse_str(user).is_ok() {
+        return Ok(user.to_string());
+    }
+    let response = send_authed_json(client, Method::GET, session, "/users", None, None).await?;
+    let users: Value = response.json().await?;
+    users
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|item| {
+            item["email"]
+                .as_str()
+                .is_some_and(|email| email.eq_ignore_ascii_case(user.trim()))
+        })
+        .and_then(|item| item["id"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            Box::new(cli_error(format!("user not found: {user}")))
+                as Box<dyn std::error::Error + Send + Sync>
+        })
+}
+
+async fn current_user_id(client: &Client, session: &mut StoredSession) -> CliResult<String> {
+    let response = send_authed_json(client, Method::GET, session, "/me", None, None).await?;
+    let me: Value = response.json().await?;
+    me["id"].as_str().map(str::to_string).ok_or_else(|| {
+        Box::new(cli_error("unexpected /me response".to_string()))
+            as Box<dyn std::error::Error + Send + Sync>
+    })
+}
+
+fn search_snippet(body: &str, query: &str, highlight: bool) -> String {
+    let body = body.replace(['\n', '\t'], " ");
+    let lower = body.to_lowercase();
+    let needle = query.to_lowercase();
+    let Some(start) = lower.find(&needle).filter(|_| lower.len() == body.len()) else {
+        return truncate_chars(&body, SNIPPET_CONTEXT * 2);
+    };
+    let end = start + needle.len();
+    let before = &body[..start];
+    let after = &body[end..];
+    let before_chars = before.chars().count();
+    let lead = if before_chars > SNIPPET_CONTEXT {
+        let skip = before_chars - SNIPPET_CONTEXT;
+        format!("...{}", before.chars().skip(skip).collect::<String>())
+    } else {
+        before.to_string()
+    };
+    let tail = truncate_chars(after, SNIPPET_CONTEXT);
+    let matched = &body[start..end];
+    if highlight {
+        format!("{lead}\x1b[1;33m{matched}\x1b[0m{tail}")
+    } else {
+        format!("{lead}[{matched}]{tail}")
+    }
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!("{}...", text.chars().take(max).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+fn message_permalink(base_url: &str, channel_id: &str, message_id: &str) -> String {
+    let web_url = env::var("GALYNX_WEB_URL")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| base_url.to_string());
+    format!(
+        "{}/channels/{channel_id}/messages/{message_id}",
+        web_url.trim_end_matches('/')
+    )
+}
+
 fn cursor_limit_query(cursor: Option<String>, limit: Option<usize>) -> Vec<(String, String)> {
     let mut query = Vec::new();
     if let Some(cursor) = cursor {
@@ -832,7 +2344,16 @@ async fn send_authed_json(
             if let Some(status) = extract_status(&*error)
                 && status == StatusCode::UNAUTHORIZED
             {
-                refresh_session(client, session).await?;
+                refresh_session(client, session).await.map_err(|error| {
+                    if extract_code(&*error).is_some_and(|code| code.starts_with("REFRESH_TOKEN_"))
+                    {
+                        Box::new(cli_error(format!(
+                            "session expired ({error}); run `galynx auth login` again"
+                        ))) as Box<dyn std::error::Error + Send + Sync>
+                    } else {
+                        error
+                    }
+                })?;
                 send_json(
                     client,
                     method,
@@ -901,18 +2422,51 @@ async fn send_json(
 
     let status = response.status();
     let body = response.text().await.unwrap_or_default();
-    let message = match serde_json::from_str::<ApiErrorResponse>(&body) {
-        Ok(parsed) => format!("{} ({}): {}", status.as_u16(), parsed.error, parsed.message),
-        Err(_) => format!("{}: {}", status.as_u16(), body),
+    let parsed = serde_json::from_str::<ApiErrorResponse>(&body).ok();
+    let code = parsed.as_ref().and_then(|parsed| parsed.code.clone());
+    let message = match parsed {
+        Some(parsed) => {
+            let label = parsed.code.unwrap_or(parsed.error);
+            let mut detail = parsed.message;
+            for (field, problem) in parsed.fields.unwrap_or_default() {
+                detail.push_str(&format!("\n  {field}: {problem}"));
+            }
+            match parsed.request_id {
+                Some(request_id) => format!(
+                    "{} ({}): {} [request_id={}]",
+                    status.as_u16(),
+                    label,
+                    detail,
+                    request_id
+                ),
+                None => format!("{} ({}): {}", status.as_u16(), label, detail),
+            }
+        }
+        None => format!("{}: {}", status.as_u16(), body),
     };
 
-    Err(Box::new(StatusedCliError { status, message }))
+    Err(Box::new(StatusedCliError {
+        status,
+        code,
+        message,
+    }))
 }
 
 fn endpoint(base_url: &str, path: &str) -> String {
     format!("{}/api/v1{}", normalize_base_url(base_url), path)
 }
 
+fn ws_endpoint(base_url: &str) -> String {
+    let url = endpoint(base_url, "/ws");
+    if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        url
+    }
+}
+
 fn resolve_base_url(flag: Option<&str>, stored: Option<&str>) -> String {
     if let Some(value) = flag {
         return normalize_base_url(value);
@@ -930,40 +2484,135 @@ fn normalize_base_url(value: &str) -> String {
     value.trim().trim_end_matches('/').to_string()
 }
 
+fn active_profile() -> &'static str {
+    ACTIVE_PROFILE
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_PROFILE)
+}
+
 fn save_session(session: &StoredSession) -> CliResult<()> {
-    let path = credentials_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
+    let mut credentials = read_credentials()?;
+    let previous = credentials.profiles.get(active_profile());
+    let mut stored = session.clone();
+    match CREDENTIAL_STORE
+        .get()
+        .copied()
+        .unwrap_or(CredentialStore::Keyring)
+    {
+        CredentialStore::Keyring => {
+            let tokens = KeyringTokens {
+                access_token: std::mem::take(&mut stored.access_token),
+                refresh_token: std::mem::take(&mut stored.refresh_token),
+            };
+            keyring_call(|entry| Ok(entry.set_password(&serde_json::to_string(&tokens)?)?))?;
+            stored.keyring = true;
+        }
+        CredentialStore::File => {
+            if previous.is_some_and(|previous| previous.keyring) {
+                let _ = keyring_call(|entry| Ok(entry.delete_credential()?));
+            }
+            stored.keyring = false;
+        }
     }
-    let payload = serde_json::to_vec_pretty(session)?;
-    fs::write(path, payload)?;
-    Ok(())
+    credentials
+        .profiles
+        .insert(active_profile().to_string(), stored);
+    write_credentials(&credentials)
 }
 
 fn load_session() -> CliResult<StoredSession> {
     load_session_if_exists()?.ok_or_else(|| {
-        Box::new(cli_error(
-            "no active session found; run `galynx auth login` first".to_string(),
-        )) as Box<dyn std::error::Error + Send + Sync>
+        let hint = match active_profile() {
+            DEFAULT_PROFILE => String::new(),
+            profile => format!(" --profile {profile}"),
+        };
+        Box::new(cli_error(format!(
+            "no active session found; run `galynx{hint} auth login` first"
+        ))) as Box<dyn std::error::Error + Send + Sync>
     })
 }
 
 fn load_session_if_exists() -> CliResult<Option<StoredSession>> {
-    let path = credentials_path()?;
-    if !path.exists() {
+    let Some(mut session) = read_credentials()?.profiles.remove(active_profile()) else {
         return Ok(None);
+    };
+    if session.keyring {
+        let tokens = match keyring_call(|entry| Ok(entry.get_password()?)) {
+            Ok(secret) => serde_json::from_str::<KeyringTokens>(&secret)?,
+            Err(error)
+                if error
+                    .downcast_ref::<keyring::Error>()
+                    .is_some_and(|error| matches!(error, keyring::Error::NoEntry)) =>
+            {
+                return Ok(None);
+            }
+            Err(error) => return Err(error),
+        };
+        session.access_token = tokens.access_token;
+        session.refresh_token = tokens.refresh_token;
     }
-
-    let raw = fs::read(path)?;
-    let session: StoredSession = serde_json::from_slice(&raw)?;
     Ok(Some(session))
 }
 
 fn clear_session_file() -> CliResult<()> {
+    let mut credentials = read_credentials()?;
+    if credentials
+        .profiles
+        .remove(active_profile())
+        .is_some_and(|session| session.keyring)
+    {
+        let _ = keyring_call(|entry| Ok(entry.delete_credential()?));
+    }
+    write_credentials(&credentials)
+}
+
+/// Runs a keyring operation on this profile's entry. Secret-service backends
+/// block on D-Bus, so the call is moved off the async worker.
+fn keyring_call<T>(operation: impl FnOnce(&keyring::Entry) -> CliResult<T>) -> CliResult<T> {
+    tokio::task::block_in_place(|| {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, active_profile())?;
+        operation(&entry)
+    })
+    .map_err(|error| match error.downcast::<keyring::Error>() {
+        Ok(error) if matches!(*error, keyring::Error::NoEntry) => error as Box<dyn std::error::Error + Send + Sync>,
+        Ok(error) => Box::new(cli_error(format!(
+            "OS keyring unavailable ({error}); use --credential-store file or GALYNX_CREDENTIAL_STORE=file"
+        ))),
+        Err(error) => error,
+    })
+}
+
+fn read_credentials() -> CliResult<CredentialsFile> {
     let path = credentials_path()?;
-    if path.exists() {
-        fs::remove_file(path)?;
+    if !path.exists() {
+        return Ok(CredentialsFile::default());
+    }
+
+    let raw: Value = serde_json::from_slice(&fs::read(path)?)?;
+    if raw.get("profiles").is_some() {
+        return Ok(serde_json::from_value(raw)?);
+    }
+    // Files written before profiles existed hold a single session.
+    let session: StoredSession = serde_json::from_value(raw)?;
+    Ok(CredentialsFile {
+        profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), session)]),
+    })
+}
+
+fn write_credentials(credentials: &CredentialsFile) -> CliResult<()> {
+    let path = credentials_path()?;
+    if credentials.profiles.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
+    let payload = serde_json::to_vec_pretty(credentials)?;
+    fs::write(path, payload)?;
     Ok(())
 }
 
@@ -1007,9 +2656,175 @@ async fn print_or_ok(response: reqwest::Response) -> CliResult<()> {
     print_json(response).await
 }
 
+async fn print_output(
+    response: reqwest::Response,
+    output: OutputFormat,
+    columns: Option<&[&str]>,
+) -> CliResult<()> {
+    if output == OutputFormat::Json || columns.is_none() {
+        return print_or_ok(response).await;
+    }
+    let value: Value = response.json().await?;
+    print_value(&value, output, columns)
+}
+
+async fn print_pages(
+    client: &Client,
+    session: &mut StoredSession,
+    path: &str,
+    page_args: PageArgs,
+    output: OutputFormat,
+    columns: &[&str],
+) -> CliResult<()> {
+    if page_args.all {
+        let limit = page_args.limit.or(Some(MAX_PAGE_LIMIT));
+        let mut cursor = page_args.cursor;
+        let mut items = Vec::new();
+        loop {
+            let query = cursor_limit_query(cursor.take(), limit);
+            let response =
+                send_authed_json(client, Method::GET, session, path, None, Some(query)).await?;
+            save_session(session)?;
+            let mut page: Value = response.json().await?;
+            if let Some(page_items) = page["items"].as_array_mut() {
+                items.append(page_items);
+            }
+            cursor = page["next_cursor"].as_str().map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+            if items.len() >= MAX_ALL_ITEMS {
+                eprintln!(
+                    "stopped after {} items (cap {MAX_ALL_ITEMS}); resume with --cursor",
+                    items.len()
+                );
+                break;
+            }
+        }
+        let page = json!({ "items": items, "next_cursor": cursor });
+        return print_value(&page, output, Some(columns));
+    }
+
+    let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let mut cursor = page_args.cursor;
+    loop {
+        let query = cursor_limit_query(cursor.take(), page_args.limit);
+        let response =
+            send_authed_json(client, Method::GET, session, path, None, Some(query)).await?;
+        save_session(session)?;
+        let page: Value = response.json().await?;
+        print_value(&page, output, Some(columns))?;
+
+        let Some(next_cursor) = page["next_cursor"].as_str() else {
+            return Ok(());
+        };
+        if !interactive || !confirm("fetch next page?")? {
+            return Ok(());
+        }
+        cursor = Some(next_cursor.to_string());
+    }
+}
+
+fn confirm(prompt: &str) -> CliResult<bool> {
+    eprint!("{prompt} [Y/n] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+        return Ok(false);
+    }
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "" | "y" | "yes"
+    ))
+}
+
+fn print_value(value: &Value, output: OutputFormat, columns: Option<&[&str]>) -> CliResult<()> {
+    let Some(columns) = columns.filter(|_| output != OutputFormat::Json) else {
+        println!("{}", serde_json::to_string_pretty(value)?);
+        return Ok(());
+    };
+    let (rows, next_cursor) = match value {
+        Value::Array(items) => (items.as_slice(), None),
+        Value::Object(page) => (
+            page.get("items")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            page.get("next_cursor").and_then(Value::as_str),
+        ),
+        _ => (&[][..], None),
+    };
+    let cells = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| render_cell(column, &row[*column]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    if output == OutputFormat::Plain {
+        for row in &cells {
+            println!("{}", row.join("\t"));
+        }
+    } else {
+        let widths = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                cells
+                    .iter()
+                    .map(|row| row[index].chars().count())
+                    .chain([column.len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let header = columns
+            .iter()
+            .map(|column| column.to_ascii_uppercase())
+            .collect::<Vec<_>>();
+        for row in std::iter::once(&header).chain(&cells) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            println!("{}", line.trim_end());
+        }
+    }
+    if let Some(cursor) = next_cursor {
+        eprintln!("next cursor: {cursor}");
+    }
+    Ok(())
+}
+
+fn render_cell(column: &str, value: &Value) -> String {
+    let text = match value {
+        Value::Null => "-".to_string(),
+        Value::String(text) => text.clone(),
+        Value::Number(number) if column.ends_with("_at") => number
+            .as_i64()
+            .and_then(chrono::DateTime::from_timestamp_millis)
+            .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| number.to_string()),
+        other => other.to_string(),
+    };
+    let text = text.replace(['\n', '\t'], " ");
+    if text.chars().count() > MAX_CELL_WIDTH {
+        let truncated = text.chars().take(MAX_CELL_WIDTH - 1).collect::<String>();
+        format!("{truncated}…")
+    } else {
+        text
+    }
+}
+
 #[derive(Debug)]
 struct StatusedCliError {
     status: StatusCode,
+    code: Option<String>,
     message: String,
 }
 
@@ -1028,6 +2843,12 @@ fn extract_status(error: &(dyn std::error::Error + 'static)) -> Option<StatusCod
     None
 }
 
+fn extract_code<'a>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a str> {
+    error
+        .downcast_ref::<StatusedCliError>()
+        .and_then(|statused| statused.code.as_deref())
+}
+
 fn cli_error(message: String) -> CliError {
     CliError { message }
 }
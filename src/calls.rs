@@ -0,0 +1,523 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::AuthContext,
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    realtime,
+    storage::{CallRecordStore, Storage},
+};
+
+const JOIN_URL_MAX_CHARS: usize = 2_048;
+const MAX_SIGNAL_BYTES: usize = 64 * 1024;
+
+#[derive(Clone)]
+pub struct CallService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct StartCallRequest {
+    /// External meeting link; omit when clients negotiate WebRTC through
+    /// `POST /api/v1/channels/{id}/call/signal`.
+    pub join_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CallSignalRequest {
+    pub to_user_id: Uuid,
+    /// Opaque to the server: SDP offer/answer, ICE candidate, etc.
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CallResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub started_by: Uuid,
+    pub join_url: Option<String>,
+    pub participant_ids: Vec<Uuid>,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/channels/:id/call",
+            get(get_call).post(start_call).delete(end_call),
+        )
+        .route("/api/v1/channels/:id/call/join", post(join_call))
+        .route("/api/v1/channels/:id/call/leave", post(leave_call))
+        .route("/api/v1/channels/:id/call/signal", post(signal_call))
+}
+
+impl From<&CallRecordStore> for CallResponse {
+    fn from(record: &CallRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            channel_id: record.channel_id,
+            started_by: record.started_by,
+            join_url: record.join_url.clone(),
+            participant_ids: record.participant_ids.clone(),
+            started_at: record.started_at,
+            ended_at: record.ended_at,
+        }
+    }
+}
+
+impl CallService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn active(&self, channel_id: Uuid) -> ApiResult<CallRecordStore> {
+        self.storage
+            .get_active_call(channel_id)
+            .await
+            .ok_or_else(|| ApiError::NotFound("no active call in this channel".to_string()))
+    }
+
+    /// Starts a call with the caller as its first participant.
+    pub async fn start(
+        &self,
+        context: &AuthContext,
+        channel_id: Uuid,
+        payload: StartCallRequest,
+    ) -> ApiResult<CallRecordStore> {
+        let join_url = payload
+            .join_url
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        if let Some(url) = &join_url {
+            let mut errors = FieldErrors::default();
+            if !url.starts_with("https://") {
+                errors.add("join_url", "must be an https URL");
+            } else if url.chars().count() > JOIN_URL_MAX_CHARS {
+                errors.add(
+                    "join_url",
+                    format!("must be at most {JOIN_URL_MAX_CHARS} characters"),
+                );
+            }
+            errors.into_result()?;
+        }
+        if self.storage.get_active_call(channel_id).await.is_some() {
+            return Err(ApiError::Conflict(
+                "a call is already active in this channel".to_string(),
+            ));
+        }
+
+        let call = CallRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id: context.workspace_id,
+            channel_id,
+            started_by: context.user_id,
+            join_url,
+            participant_ids: vec![context.user_id],
+            started_at: Utc::now().timestamp_millis(),
+            ended_at: None,
+        };
+        self.storage.put_call(call.clone()).await;
+        Ok(call)
+    }
+
+    /// Returns the call and whether the user was newly added.
+    pub async fn join(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> ApiResult<(CallRecordStore, bool)> {
+        let mut call = self.active(channel_id).await?;
+        if call.participant_ids.contains(&user_id) {
+            return Ok((call, false));
+        }
+        call.participant_ids.push(user_id);
+        self.storage.put_call(call.clone()).await;
+        Ok((call, true))
+    }
+
+    /// Removes the user; the call ends once nobody is left.
+    pub async fn leave(&self, user_id: Uuid, channel_id: Uuid) -> ApiResult<CallRecordStore> {
+        let mut call = self.active(channel_id).await?;
+        if !call.participant_ids.contains(&user_id) {
+            return Err(ApiError::BadRequest("you are not in this call".to_string()));
+        }
+        call.participant_ids
+            .retain(|participant| *participant != user_id);
+        if call.participant_ids.is_empty() {
+            call.ended_at = Some(Utc::now().timestamp_millis());
+        }
+        self.storage.put_call(call.clone()).await;
+        Ok(call)
+    }
+
+    pub async fn end(&self, channel_id: Uuid) -> ApiResult<CallRecordStore> {
+        let mut call = self.active(channel_id).await?;
+        call.participant_ids.clear();
+        call.ended_at = Some(Utc::now().timestamp_millis());
+        self.storage.put_call(call.clone()).await;
+        Ok(call)
+    }
+}
+
+async fn emit_call_event(
+    state: &AppState,
+    context: &AuthContext,
+    call: &CallRecordStore,
+    event_type: &str,
+    payload: Value,
+) {
+    realtime::emit_channel_event(
+        state,
+        context.workspace_id,
+        realtime::make_event(
+            event_type,
+            context.workspace_id,
+            Some(call.channel_id),
+            None,
+            payload,
+        ),
+    )
+    .await;
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/channels/{id}/call",
+    operation_id = "get_call",
+    tag = "calls",
+    responses(
+        (status = 200, description = "Active call", body = CallResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "No active call", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_call(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+) -> ApiResult<Json<CallResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state
+        .channels
+        .ensure_channel_access(&context, channel_id)
+        .await?;
+    let call = state.calls.active(channel_id).await?;
+    Ok(Json(CallResponse::from(&call)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/call",
+    operation_id = "start_call",
+    tag = "calls",
+    request_body = StartCallRequest,
+    responses(
+        (status = 201, description = "Call started", body = CallResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "A call is already active", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn start_call(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    payload: Option<Json<StartCallRequest>>,
+) -> ApiResult<(StatusCode, Json<CallResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state
+        .channels
+        .ensure_channel_access(&context, channel_id)
+        .await?;
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
+    let call = state.calls.start(&context, channel_id, payload).await?;
+    let response = CallResponse::from(&call);
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CALL_STARTED",
+            "channel",
+            Some(channel_id.to_string()),
+            json!({ "call_id": call.id, "join_url": call.join_url }),
+        )
+        .await;
+    emit_call_event(&state, &context, &call, "CALL_STARTED", json!(response)).await;
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/call/join",
+    operation_id = "join_call",
+    tag = "calls",
+    responses(
+        (status = 200, description = "Joined the call", body = CallResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "No active call", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn join_call(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+) -> ApiResult<Json<CallResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state
+        .channels
+        .ensure_channel_access(&context, channel_id)
+        .await?;
+    let (call, joined) = state.calls.join(context.user_id, channel_id).await?;
+    let response = CallResponse::from(&call);
+    if joined {
+        emit_call_event(
+            &state,
+            &context,
+            &call,
+            "CALL_PARTICIPANT_JOINED",
+            json!({
+                "call_id": call.id,
+                "channel_id": channel_id,
+                "user_id": context.user_id,
+                "participant_ids": call.participant_ids,
+            }),
+        )
+        .await;
+    }
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/call/leave",
+    operation_id = "leave_call",
+    tag = "calls",
+    responses(
+        (status = 200, description = "Left the call; `ended_at` is set if it was the last participant", body = CallResponse),
+        (status = 400, description = "Not a participant", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "No active call", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn leave_call(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+) -> ApiResult<Json<CallResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let call = state.calls.leave(context.user_id, channel_id).await?;
+    let response = CallResponse::from(&call);
+    emit_call_event(
+        &state,
+        &context,
+        &call,
+        "CALL_PARTICIPANT_LEFT",
+        json!({
+            "call_id": call.id,
+            "channel_id": channel_id,
+            "user_id": context.user_id,
+            "participant_ids": call.participant_ids,
+        }),
+    )
+    .await;
+    if call.ended_at.is_some() {
+        emit_call_event(&state, &context, &call, "CALL_ENDED", json!(response)).await;
+    }
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/channels/{id}/call",
+    operation_id = "end_call",
+    tag = "calls",
+    responses(
+        (status = 200, description = "Call ended", body = CallResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "No active call", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn end_call(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+) -> ApiResult<Json<CallResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let active = state.calls.active(channel_id).await?;
+    if active.started_by != context.user_id {
+        state
+            .channels
+            .ensure_channel_manager(&context, channel_id)
+            .await?;
+    }
+    let call = state.calls.end(channel_id).await?;
+    let response = CallResponse::from(&call);
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CALL_ENDED",
+            "channel",
+            Some(channel_id.to_string()),
+            json!({ "call_id": call.id }),
+        )
+        .await;
+    emit_call_event(&state, &context, &call, "CALL_ENDED", json!(response)).await;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/call/signal",
+    operation_id = "signal_call",
+    tag = "calls",
+    request_body = CallSignalRequest,
+    responses(
+        (status = 202, description = "Signal relayed to the participant"),
+        (status = 400, description = "Sender or recipient is not in the call", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "No active call", body = ErrorResponse),
+        (status = 422, description = "Payload too large", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn signal_call(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<CallSignalRequest>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let call = state.calls.active(channel_id).await?;
+    if !call.participant_ids.contains(&context.user_id)
+        || !call.participant_ids.contains(&payload.to_user_id)
+    {
+        return Err(ApiError::BadRequest(
+            "both sender and recipient must be in the call".to_string(),
+        ));
+    }
+    if payload.payload.to_string().len() > MAX_SIGNAL_BYTES {
+        let mut errors = FieldErrors::default();
+        errors.add(
+            "payload",
+            format!("must be at most {MAX_SIGNAL_BYTES} bytes"),
+        );
+        errors.into_result()?;
+    }
+    state
+        .realtime
+        .emit(
+            context.workspace_id,
+            realtime::make_user_event(
+                "CALL_SIGNAL",
+                context.workspace_id,
+                payload.to_user_id,
+                json!({
+                    "call_id": call.id,
+                    "channel_id": channel_id,
+                    "from_user_id": context.user_id,
+                    "payload": payload.payload,
+                }),
+            ),
+        )
+        .await;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::WorkspaceRole, storage::PersistenceBackend};
+
+    #[tokio::test]
+    async fn calls_start_once_and_end_when_last_participant_leaves() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let service = CallService::new(storage);
+        let (channel_id, guest_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let host = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            role: WorkspaceRole::Member,
+        };
+        assert!(matches!(
+            service
+                .start(
+                    &host,
+                    channel_id,
+                    StartCallRequest {
+                        join_url: Some("http://insecure.example".to_string()),
+                    },
+                )
+                .await,
+            Err(ApiError::Validation(_))
+        ));
+        let call = service
+            .start(&host, channel_id, StartCallRequest::default())
+            .await
+            .expect("call should start");
+        assert_eq!(call.participant_ids, vec![host.user_id]);
+        assert!(matches!(
+            service
+                .start(&host, channel_id, StartCallRequest::default())
+                .await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        let (_, joined) = service.join(guest_id, channel_id).await.unwrap();
+        assert!(joined);
+        let (_, joined) = service.join(guest_id, channel_id).await.unwrap();
+        assert!(!joined);
+        let after_host = service.leave(host.user_id, channel_id).await.unwrap();
+        assert_eq!(after_host.participant_ids, vec![guest_id]);
+        assert!(after_host.ended_at.is_none());
+        let after_guest = service.leave(guest_id, channel_id).await.unwrap();
+        assert!(after_guest.ended_at.is_some());
+        assert!(matches!(
+            service.active(channel_id).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
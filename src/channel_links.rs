@@ -0,0 +1,519 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorResponse},
+    realtime,
+    storage::{ChannelLinkRecordStore, Storage},
+};
+
+#[derive(Clone)]
+pub struct ChannelLinkService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelLinkStatus {
+    PendingApproval,
+    Active,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateChannelLinkRequest {
+    /// Workspace the channel is shared with.
+    pub workspace_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChannelLinkResponse {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub host_workspace_id: Uuid,
+    pub guest_workspace_id: Uuid,
+    pub status: ChannelLinkStatus,
+    pub host_approved_by: Uuid,
+    pub guest_approved_by: Option<Uuid>,
+    pub created_at: i64,
+    pub approved_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelLinkListResponse {
+    pub items: Vec<ChannelLinkResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/channel-links", get(list_channel_links))
+        .route("/api/v1/channels/:id/links", post(create_channel_link))
+        .route(
+            "/api/v1/channel-links/:id/approve",
+            post(approve_channel_link),
+        )
+        .route("/api/v1/channel-links/:id", delete(delete_channel_link))
+}
+
+impl ChannelLinkService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn list(&self, context: &AuthContext) -> ApiResult<ChannelLinkListResponse> {
+        ensure_workspace_admin(context)?;
+        let mut items = self
+            .storage
+            .list_channel_links(context.workspace_id)
+            .await
+            .iter()
+            .map(ChannelLinkResponse::from)
+            .collect::<Vec<_>>();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at));
+        Ok(ChannelLinkListResponse { items })
+    }
+
+    /// Requested by the host owner, which counts as the host-side approval.
+    pub async fn request(
+        &self,
+        context: &AuthContext,
+        channel_id: Uuid,
+        guest_workspace_id: Uuid,
+    ) -> ApiResult<ChannelLinkResponse> {
+        ensure_workspace_owner(context)?;
+        let channel = self
+            .storage
+            .get_channel(&channel_id)
+            .await
+            .filter(|channel| channel.workspace_id == context.workspace_id)
+            .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
+        if channel.e2ee {
+            return Err(ApiError::BadRequest(
+                "end-to-end encrypted channels cannot be shared".to_string(),
+            ));
+        }
+        if guest_workspace_id == context.workspace_id {
+            return Err(ApiError::BadRequest(
+                "a channel cannot be shared with its own workspace".to_string(),
+            ));
+        }
+        if self
+            .storage
+            .get_workspace(guest_workspace_id)
+            .await
+            .is_none()
+        {
+            return Err(ApiError::NotFound("workspace not found".to_string()));
+        }
+        if self
+            .storage
+            .list_channel_links_for_channel(channel.id)
+            .await
+            .iter()
+            .any(|link| link.guest_workspace_id == guest_workspace_id)
+        {
+            return Err(ApiError::Conflict(
+                "channel is already linked to that workspace".to_string(),
+            ));
+        }
+
+        let link = ChannelLinkRecordStore {
+            id: Uuid::new_v4(),
+            channel_id: channel.id,
+            host_workspace_id: context.workspace_id,
+            guest_workspace_id,
+            host_approved_by: context.user_id,
+            guest_approved_by: None,
+            created_at: Utc::now().timestamp_millis(),
+            approved_at: None,
+        };
+        self.storage.put_channel_link(link.clone()).await;
+        Ok(ChannelLinkResponse::from(&link))
+    }
+
+    pub async fn approve(
+        &self,
+        context: &AuthContext,
+        link_id: Uuid,
+    ) -> ApiResult<ChannelLinkResponse> {
+        ensure_workspace_owner(context)?;
+        let mut link = self.find(context.workspace_id, link_id).await?;
+        if link.guest_workspace_id != context.workspace_id {
+            return Err(ApiError::Forbidden(
+                "only the invited workspace can approve this link".to_string(),
+            ));
+        }
+        if link.is_active() {
+            return Err(ApiError::Conflict("link is already active".to_string()));
+        }
+        link.guest_approved_by = Some(context.user_id);
+        link.approved_at = Some(Utc::now().timestamp_millis());
+        self.storage.put_channel_link(link.clone()).await;
+        Ok(ChannelLinkResponse::from(&link))
+    }
+
+    /// Either owner may decline a pending link or disconnect an active one.
+    pub async fn remove(
+        &self,
+        context: &AuthContext,
+        link_id: Uuid,
+    ) -> ApiResult<ChannelLinkResponse> {
+        ensure_workspace_owner(context)?;
+        let link = self.find(context.workspace_id, link_id).await?;
+        self.storage.remove_channel_links(&[link.id]).await;
+        Ok(ChannelLinkResponse::from(&link))
+    }
+
+    async fn find(&self, workspace_id: Uuid, link_id: Uuid) -> ApiResult<ChannelLinkRecordStore> {
+        self.storage
+            .list_channel_links(workspace_id)
+            .await
+            .into_iter()
+            .find(|link| link.id == link_id)
+            .ok_or_else(|| ApiError::NotFound("channel link not found".to_string()))
+    }
+}
+
+impl From<&ChannelLinkRecordStore> for ChannelLinkResponse {
+    fn from(link: &ChannelLinkRecordStore) -> Self {
+        Self {
+            id: link.id,
+            channel_id: link.channel_id,
+            host_workspace_id: link.host_workspace_id,
+            guest_workspace_id: link.guest_workspace_id,
+            status: if link.is_active() {
+                ChannelLinkStatus::Active
+            } else {
+                ChannelLinkStatus::PendingApproval
+            },
+            host_approved_by: link.host_approved_by,
+            guest_approved_by: link.guest_approved_by,
+            created_at: link.created_at,
+            approved_at: link.approved_at,
+        }
+    }
+}
+
+fn ensure_workspace_owner(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner => Ok(()),
+        WorkspaceRole::Admin | WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "only workspace owners can manage shared channels".to_string(),
+        )),
+    }
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to view shared channels".to_string(),
+        )),
+    }
+}
+
+/// Audits the change in the actor's workspace and notifies both sides.
+async fn publish(
+    state: &AppState,
+    context: &AuthContext,
+    action: &str,
+    event_type: &str,
+    link: &ChannelLinkResponse,
+) {
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            action,
+            "channel",
+            Some(link.channel_id.to_string()),
+            json!({
+                "link_id": link.id,
+                "host_workspace_id": link.host_workspace_id,
+                "guest_workspace_id": link.guest_workspace_id,
+            }),
+        )
+        .await;
+    for workspace_id in [link.host_workspace_id, link.guest_workspace_id] {
+        state
+            .realtime
+            .emit(
+                workspace_id,
+                realtime::make_event(
+                    event_type,
+                    workspace_id,
+                    Some(link.channel_id),
+                    None,
+                    serde_json::to_value(link).unwrap_or_default(),
+                ),
+            )
+            .await;
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/channel-links",
+    operation_id = "list_channel_links",
+    tag = "channel_links",
+    responses(
+        (status = 200, description = "Links where the workspace is host or guest", body = ChannelLinkListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_channel_links(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<ChannelLinkListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.channel_links.list(&context).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/links",
+    operation_id = "create_channel_link",
+    tag = "channel_links",
+    request_body = CreateChannelLinkRequest,
+    responses(
+        (status = 201, description = "Link requested, pending approval by the other workspace", body = ChannelLinkResponse),
+        (status = 400, description = "Invalid target workspace", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel or workspace not found", body = ErrorResponse),
+        (status = 409, description = "Already linked", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_channel_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<CreateChannelLinkRequest>,
+) -> ApiResult<(StatusCode, Json<ChannelLinkResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let link = state
+        .channel_links
+        .request(&context, channel_id, payload.workspace_id)
+        .await?;
+    publish(
+        &state,
+        &context,
+        "CHANNEL_LINK_REQUESTED",
+        "CHANNEL_LINK_UPDATED",
+        &link,
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channel-links/{id}/approve",
+    operation_id = "approve_channel_link",
+    tag = "channel_links",
+    responses(
+        (status = 200, description = "Link active", body = ChannelLinkResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Link not found", body = ErrorResponse),
+        (status = 409, description = "Already active", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn approve_channel_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(link_id): Path<Uuid>,
+) -> ApiResult<Json<ChannelLinkResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let link = state.channel_links.approve(&context, link_id).await?;
+    publish(
+        &state,
+        &context,
+        "CHANNEL_LINK_APPROVED",
+        "CHANNEL_LINK_UPDATED",
+        &link,
+    )
+    .await;
+    Ok(Json(link))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/channel-links/{id}",
+    operation_id = "delete_channel_link",
+    tag = "channel_links",
+    responses(
+        (status = 204, description = "Link declined or disconnected"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Link not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn delete_channel_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(link_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let link = state.channel_links.remove(&context, link_id).await?;
+    publish(
+        &state,
+        &context,
+        "CHANNEL_LINK_REMOVED",
+        "CHANNEL_LINK_REMOVED",
+        &link,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channels::{ChannelService, CreateMessageRequest, MessageQuery},
+        storage::{PersistenceBackend, WorkspaceRecordStore},
+    };
+
+    #[tokio::test]
+    async fn linked_channel_is_visible_to_guest_only_after_approval() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let context = |workspace_id, role| AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role,
+        };
+        let host = context(Uuid::new_v4(), WorkspaceRole::Owner);
+        let guest = context(Uuid::new_v4(), WorkspaceRole::Owner);
+        let guest_member = context(guest.workspace_id, WorkspaceRole::Member);
+        for (workspace, name) in [(&host, "Host"), (&guest, "Guest")] {
+            storage
+                .put_workspace(WorkspaceRecordStore {
+                    id: workspace.workspace_id,
+                    name: name.to_string(),
+                    created_by: workspace.user_id,
+                    created_at: 0,
+                    suspended_at: None,
+                    suspended_reason: None,
+                })
+                .await;
+        }
+        let channels = ChannelService::new(storage.clone(), host.workspace_id, host.user_id);
+        let links = ChannelLinkService::new(storage.clone());
+        let channel_id = channels
+            .list_channels(host.workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+
+        let link = links
+            .request(&host, channel_id, guest.workspace_id)
+            .await
+            .expect("host owner should request link");
+        assert_eq!(link.status, ChannelLinkStatus::PendingApproval);
+        assert!(channels.list_channels(guest.workspace_id).await.is_empty());
+        assert!(matches!(
+            links.approve(&host, link.id).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(matches!(
+            links.approve(&guest_member, link.id).await,
+            Err(ApiError::Forbidden(_))
+        ));
+
+        let link = links
+            .approve(&guest, link.id)
+            .await
+            .expect("guest owner should approve");
+        assert_eq!(link.status, ChannelLinkStatus::Active);
+        assert_eq!(
+            channels.channel_workspaces(channel_id).await,
+            vec![host.workspace_id, guest.workspace_id]
+        );
+        let message = channels
+            .create_message(
+                &guest_member,
+                channel_id,
+                CreateMessageRequest {
+                    body_md: "hola desde guest".to_string(),
+                },
+                None,
+            )
+            .await
+            .expect("guest member should post in shared channel");
+        assert_eq!(message.workspace_id, host.workspace_id);
+        assert_eq!(message.origin_workspace_id, Some(guest.workspace_id));
+        let page = channels
+            .list_messages(
+                &host,
+                channel_id,
+                &MessageQuery {
+                    cursor: None,
+                    limit: None,
+                    after_seq: None,
+                },
+            )
+            .await
+            .expect("host should list messages");
+        assert!(page.items.iter().any(|item| item.id == message.id));
+
+        storage
+            .put_membership_role(host.workspace_id, host.user_id, "owner")
+            .await;
+        storage
+            .put_membership_role(guest.workspace_id, guest_member.user_id, "member")
+            .await;
+        storage.add_channel_member(channel_id, host.user_id).await;
+        assert!(matches!(
+            channels
+                .remove_channel_member(guest.workspace_id, channel_id, host.user_id)
+                .await,
+            Err(ApiError::Forbidden(_))
+        ));
+        channels
+            .add_channel_member(guest.workspace_id, channel_id, guest_member.user_id)
+            .await
+            .expect("guest admin adds its own member");
+        channels
+            .remove_channel_member(guest.workspace_id, channel_id, guest_member.user_id)
+            .await
+            .expect("guest admin removes its own member");
+
+        links
+            .remove(&guest, link.id)
+            .await
+            .expect("guest owner should disconnect");
+        assert!(channels.list_channels(guest.workspace_id).await.is_empty());
+    }
+}
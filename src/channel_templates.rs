@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    channels::MAX_TOPIC_CHARS,
+    config::Config,
+    storage::{ChannelRecordStore, Storage},
+};
+
+/// One entry of `CHANNEL_TEMPLATES_FILE`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChannelTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub topic: Option<String>,
+    #[serde(default)]
+    pub is_private: bool,
+    /// Add the workspace creator and every member onboarded later.
+    #[serde(default)]
+    pub default_membership: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemplatesFile {
+    channels: Vec<ChannelTemplate>,
+}
+
+/// Channels created for every new workspace. Without a templates file this is
+/// a single public `general` channel everyone joins.
+#[derive(Debug, Clone)]
+pub struct ChannelTemplates {
+    templates: Vec<ChannelTemplate>,
+}
+
+impl Default for ChannelTemplates {
+    fn default() -> Self {
+        Self {
+            templates: vec![ChannelTemplate {
+                name: "general".to_string(),
+                topic: None,
+                is_private: false,
+                default_membership: true,
+            }],
+        }
+    }
+}
+
+impl ChannelTemplates {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        match config.channel_templates_file.as_deref() {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|error| format!("failed to read CHANNEL_TEMPLATES_FILE: {error}"))?;
+                parse_templates(&contents)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reports an unreadable or invalid `CHANNEL_TEMPLATES_FILE` at startup.
+    pub(crate) fn check_config(config: &Config, problems: &mut Vec<String>) {
+        if let Err(problem) = Self::from_config(config) {
+            problems.push(problem);
+        }
+    }
+
+    /// Creates every template channel in `workspace_id` that does not exist yet.
+    pub async fn instantiate(&self, storage: &Storage, workspace_id: Uuid, created_by: Uuid) {
+        for template in &self.templates {
+            if storage
+                .channel_name_exists(workspace_id, &template.name)
+                .await
+            {
+                continue;
+            }
+            let channel = ChannelRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id,
+                name: template.name.clone(),
+                is_private: template.is_private,
+                topic: template.topic.clone(),
+                icon_emoji: None,
+                color: None,
+                e2ee: false,
+                key_epoch: 0,
+                created_by,
+                created_at: Utc::now().timestamp_millis(),
+            };
+            storage.insert_channel(channel.clone()).await;
+            if template.is_private || template.default_membership {
+                storage.add_channel_member(channel.id, created_by).await;
+            }
+        }
+    }
+
+    /// Adds `user_id` to the workspace channels created from templates with
+    /// `default_membership`.
+    pub async fn join_defaults(&self, storage: &Storage, workspace_id: Uuid, user_id: Uuid) {
+        let names = self
+            .templates
+            .iter()
+            .filter(|template| template.default_membership)
+            .map(|template| template.name.as_str())
+            .collect::<HashSet<_>>();
+        if names.is_empty() {
+            return;
+        }
+        for channel in storage.list_channels(workspace_id).await {
+            if names.contains(channel.name.as_str()) {
+                storage.add_channel_member(channel.id, user_id).await;
+            }
+        }
+    }
+}
+
+fn parse_templates(contents: &str) -> Result<ChannelTemplates, String> {
+    let file: TemplatesFile = serde_yaml::from_str(contents)
+        .map_err(|error| format!("invalid CHANNEL_TEMPLATES_FILE: {error}"))?;
+    let mut names = HashSet::new();
+    let mut templates = Vec::with_capacity(file.channels.len());
+    for mut template in file.channels {
+        template.name = template.name.trim().to_ascii_lowercase();
+        if template.name.is_empty() {
+            return Err("channel template name must not be empty".to_string());
+        }
+        if !names.insert(template.name.clone()) {
+            return Err(format!("duplicate channel template {:?}", template.name));
+        }
+        template.topic = template
+            .topic
+            .map(|topic| topic.trim().to_string())
+            .filter(|topic| !topic.is_empty());
+        if template
+            .topic
+            .as_ref()
+            .is_some_and(|topic| topic.chars().count() > MAX_TOPIC_CHARS)
+        {
+            return Err(format!(
+                "channel template {:?}: topic must be at most {MAX_TOPIC_CHARS} characters",
+                template.name
+            ));
+        }
+        templates.push(template);
+    }
+    Ok(ChannelTemplates { templates })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PersistenceBackend;
+
+    #[tokio::test]
+    async fn templates_create_channels_and_join_default_members() {
+        let templates = parse_templates(
+            r#"
+channels:
+  - name: " General "
+    topic: "Company-wide"
+    default_membership: true
+  - name: leads
+    is_private: true
+  - name: random
+"#,
+        )
+        .expect("templates should parse");
+        assert!(parse_templates("channels:\n  - name: a\n  - name: A\n").is_err());
+
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let (workspace_id, owner_id, member_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        templates
+            .instantiate(&storage, workspace_id, owner_id)
+            .await;
+        templates
+            .instantiate(&storage, workspace_id, owner_id)
+            .await;
+        templates
+            .join_defaults(&storage, workspace_id, member_id)
+            .await;
+
+        let mut channels = storage.list_channels(workspace_id).await;
+        channels.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            channels
+                .iter()
+                .map(|channel| (channel.name.as_str(), channel.is_private))
+                .collect::<Vec<_>>(),
+            vec![("general", false), ("leads", true), ("random", false)]
+        );
+        assert_eq!(channels[0].topic.as_deref(), Some("Company-wide"));
+        assert!(storage.is_channel_member(channels[0].id, member_id).await);
+        assert!(storage.is_channel_member(channels[1].id, owner_id).await);
+        assert!(!storage.is_channel_member(channels[1].id, member_id).await);
+        assert!(!storage.is_channel_member(channels[2].id, member_id).await);
+    }
+}
@@ -1,10 +1,14 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::{delete, get, patch},
+    response::Response,
+    routing::{get, patch, post, put},
 };
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -13,19 +17,40 @@ use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{
+    analytics,
     app::AppState,
     attachments::AttachmentResponse,
     auth::{AuthContext, WorkspaceRole},
-    errors::{ApiError, ApiResult, ErrorResponse},
-    realtime,
-    storage::{ChannelRecordStore, MessageRecordStore, Storage},
+    channel_templates::ChannelTemplates,
+    conditional, e2ee,
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    idempotency,
+    legal_holds::{LegalHolds, legal_hold_error},
+    moderation, mutes, notifications, outbox, realtime,
+    storage::{
+        ChannelRecordStore, MessageLimitsRecordStore, MessageRecordStore, ReadMarkerRecordStore,
+        Storage,
+    },
 };
 
+/// How far `SyncResponse::next_cursor` trails the clock, so writes that land
+/// while a sync is being built are picked up by the next one.
+const SYNC_OVERLAP_MS: i64 = 5_000;
+pub(crate) const MAX_TOPIC_CHARS: usize = 250;
+const DEFAULT_MAX_MESSAGE_CHARS: u32 = 40_000;
+const DEFAULT_MAX_ATTACHMENTS_PER_MESSAGE: u32 = 10;
+const DEFAULT_MAX_THREAD_DEPTH: u32 = 1_000;
+const MESSAGE_CHARS_CEILING: u32 = 100_000;
+const ATTACHMENTS_CEILING: u32 = 100;
+const THREAD_DEPTH_CEILING: u32 = 10_000;
+const MAX_CROSSPOST_CHANNELS: usize = 10;
+
 #[derive(Clone)]
 pub struct ChannelService {
     storage: Arc<Storage>,
     bootstrap_workspace_id: Uuid,
     bootstrap_creator_id: Uuid,
+    templates: Arc<ChannelTemplates>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -34,17 +59,35 @@ pub struct ChannelResponse {
     pub workspace_id: Uuid,
     pub name: String,
     pub is_private: bool,
+    pub topic: Option<String>,
+    pub icon_emoji: Option<String>,
+    /// Accent color as `#rrggbb`.
+    pub color: Option<String>,
+    /// Message bodies are client-side ciphertext; see `/api/v1/channels/{id}/e2ee`.
+    pub e2ee: bool,
+    pub key_epoch: i64,
     pub created_by: Uuid,
     pub created_at: i64,
 }
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateChannelRequest {
     pub name: String,
     pub is_private: bool,
+    /// End-to-end encrypted; only allowed for private channels and fixed at creation.
+    #[serde(default)]
+    pub e2ee: bool,
 }
 
+/// Omitted fields are left unchanged; an empty string clears the value.
 #[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateChannelRequest {
+    pub topic: Option<String>,
+    pub icon_emoji: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateMessageRequest {
     pub body_md: String,
 }
@@ -54,6 +97,20 @@ pub struct UpdateMessageRequest {
     pub body_md: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CrosspostMessageRequest {
+    /// 2 to 10 distinct channels; duplicates are ignored.
+    pub channel_ids: Vec<Uuid>,
+    pub body_md: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CrosspostMessageResponse {
+    pub crosspost_group_id: Uuid,
+    /// One copy per channel, in the order of `channel_ids`.
+    pub items: Vec<MessageResponse>,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct MessageResponse {
     pub id: Uuid,
@@ -62,10 +119,30 @@ pub struct MessageResponse {
     pub sender_id: Uuid,
     pub body_md: String,
     pub thread_root_id: Option<Uuid>,
+    /// Workspace of the sender when the message was posted from a linked workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_workspace_id: Option<Uuid>,
+    /// Position in the channel (replies included); gaps mean missed messages.
+    pub seq: i64,
     pub created_at: i64,
     pub edited_at: Option<i64>,
     pub deleted_at: Option<i64>,
+    /// Set on every copy of a cross-posted message; edits and deletes by the
+    /// sender apply to all of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crosspost_group_id: Option<Uuid>,
     pub attachments: Vec<AttachmentResponse>,
+    /// Reactions grouped by emoji; present in channel/thread listings and thread summaries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<Vec<MessageReactionSummary>>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MessageReactionSummary {
+    pub emoji: String,
+    pub count: usize,
+    /// Whether the requesting user reacted with this emoji.
+    pub me: bool,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -82,9 +159,140 @@ pub struct MessageListResponse {
     pub next_cursor: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Mention,
+    Reply,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityItemResponse {
+    pub kind: ActivityKind,
+    pub message: MessageResponse,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityListResponse {
+    pub items: Vec<ActivityItemResponse>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ActivityQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    /// Only return items of this kind.
+    pub kind: Option<ActivityKind>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateReadMarkerRequest {
+    pub last_read_seq: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReadMarkerResponse {
+    pub channel_id: Uuid,
+    pub last_read_seq: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MembershipChangeKind {
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MembershipChangeResponse {
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    pub kind: MembershipChangeKind,
+    pub changed_at: i64,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SyncQuery {
+    /// `next_cursor` from the previous sync; omit it for a cold start.
+    pub since: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncResponse {
+    /// Every channel the caller can currently see; drop any other cached channel.
+    pub channel_ids: Vec<Uuid>,
+    /// Channels created or updated since the cursor (all of them on a cold start).
+    pub channels: Vec<ChannelResponse>,
+    /// Created, edited and deleted messages, oldest change first. Deleted
+    /// messages come back with `deleted_at` set and an empty body.
+    pub messages: Vec<MessageResponse>,
+    pub membership_changes: Vec<MembershipChangeResponse>,
+    pub read_markers: Vec<ReadMarkerResponse>,
+    pub next_cursor: String,
+    /// More messages are pending; call again right away with `next_cursor`.
+    pub has_more: bool,
+}
+
+/// Limits applied to new and edited messages; body length counts Unicode
+/// characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MessageLimits {
+    pub max_message_chars: u32,
+    pub max_attachments_per_message: u32,
+    /// Replies a single thread can hold.
+    pub max_thread_depth: u32,
+}
+
+impl Default for MessageLimits {
+    fn default() -> Self {
+        Self {
+            max_message_chars: DEFAULT_MAX_MESSAGE_CHARS,
+            max_attachments_per_message: DEFAULT_MAX_ATTACHMENTS_PER_MESSAGE,
+            max_thread_depth: DEFAULT_MAX_THREAD_DEPTH,
+        }
+    }
+}
+
+impl From<&MessageLimitsRecordStore> for MessageLimits {
+    fn from(record: &MessageLimitsRecordStore) -> Self {
+        Self {
+            max_message_chars: u32::try_from(record.max_message_chars)
+                .unwrap_or(DEFAULT_MAX_MESSAGE_CHARS),
+            max_attachments_per_message: u32::try_from(record.max_attachments_per_message)
+                .unwrap_or(DEFAULT_MAX_ATTACHMENTS_PER_MESSAGE),
+            max_thread_depth: u32::try_from(record.max_thread_depth)
+                .unwrap_or(DEFAULT_MAX_THREAD_DEPTH),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMessageLimitsRequest {
+    pub max_message_chars: Option<u32>,
+    pub max_attachments_per_message: Option<u32>,
+    pub max_thread_depth: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMemberRole {
+    /// Manages members and deletes others' messages in this channel only.
+    Admin,
+    Member,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ChannelMemberResponse {
     pub user_id: Uuid,
+    pub role: ChannelMemberRole,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateChannelMemberRequest {
+    pub role: ChannelMemberRole,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -96,24 +304,32 @@ pub struct AddChannelMemberRequest {
 pub struct MessageQuery {
     pub cursor: Option<String>,
     pub limit: Option<usize>,
+    /// Channel listings only: return messages with a greater `seq`, oldest
+    /// first and including deleted ones, to fill a gap. Excludes `cursor`.
+    pub after_seq: Option<i64>,
 }
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/api/v1/channels", get(list_channels).post(create_channel))
-        .route("/api/v1/channels/:id", delete(delete_channel))
+        .route(
+            "/api/v1/channels/:id",
+            patch(update_channel).delete(delete_channel),
+        )
         .route(
             "/api/v1/channels/:id/members",
             get(list_channel_members).post(add_channel_member),
         )
         .route(
             "/api/v1/channels/:id/members/:user_id",
-            delete(remove_channel_member),
+            patch(update_channel_member).delete(remove_channel_member),
         )
+        .route("/api/v1/channels/:id/read-marker", put(update_read_marker))
         .route(
             "/api/v1/channels/:id/messages",
             get(list_messages).post(create_message),
         )
+        .route("/api/v1/messages", post(crosspost_message))
         .route(
             "/api/v1/messages/:id",
             patch(update_message).delete(delete_message),
@@ -126,12 +342,26 @@ impl ChannelService {
             storage,
             bootstrap_workspace_id: workspace_id,
             bootstrap_creator_id: creator_id,
+            templates: Arc::default(),
         }
     }
 
+    pub fn with_templates(mut self, templates: Arc<ChannelTemplates>) -> Self {
+        self.templates = templates;
+        self
+    }
+
     pub async fn list_channels(&self, workspace_id: Uuid) -> Vec<ChannelResponse> {
         self.ensure_bootstrap_seed().await;
-        let channels = self.storage.list_channels(workspace_id).await;
+        let mut channels = self.storage.list_channels(workspace_id).await;
+        for link in self.storage.list_channel_links(workspace_id).await {
+            if link.is_active()
+                && link.guest_workspace_id == workspace_id
+                && let Some(channel) = self.storage.get_channel(&link.channel_id).await
+            {
+                channels.push(channel);
+            }
+        }
         let mut items: Vec<ChannelResponse> = channels.iter().map(ChannelResponse::from).collect();
         items.sort_by(|a, b| {
             a.created_at
@@ -149,12 +379,18 @@ impl ChannelService {
     ) -> ApiResult<ChannelResponse> {
         self.ensure_bootstrap_seed().await;
         let name = payload.name.trim().to_ascii_lowercase();
+        let mut errors = FieldErrors::default();
         if name.is_empty() {
-            return Err(ApiError::BadRequest("channel name is required".to_string()));
+            errors.add("name", "must not be empty");
+        }
+        if payload.e2ee && !payload.is_private {
+            errors.add("e2ee", "end-to-end encrypted channels must be private");
         }
+        errors.into_result()?;
 
         if self.storage.channel_name_exists(workspace_id, &name).await {
-            return Err(ApiError::BadRequest(
+            return Err(ApiError::Coded(
+                ErrorCode::ChannelNameTaken,
                 "channel name already exists".to_string(),
             ));
         }
@@ -164,17 +400,72 @@ impl ChannelService {
             workspace_id,
             name,
             is_private: payload.is_private,
+            topic: None,
+            icon_emoji: None,
+            color: None,
+            e2ee: payload.e2ee,
+            key_epoch: 0,
             created_by,
             created_at: Utc::now().timestamp_millis(),
         };
-        let response = ChannelResponse::from(&channel);
         self.storage.insert_channel(channel.clone()).await;
         if channel.is_private {
             self.storage
                 .add_channel_member(channel.id, created_by)
                 .await;
         }
-        Ok(response)
+        // The creator joining opens the first key epoch of an `e2ee` channel.
+        let channel = self
+            .storage
+            .get_channel(&channel.id)
+            .await
+            .unwrap_or(channel);
+        Ok(ChannelResponse::from(&channel))
+    }
+
+    pub async fn update_channel(
+        &self,
+        workspace_id: Uuid,
+        channel_id: Uuid,
+        payload: UpdateChannelRequest,
+    ) -> ApiResult<ChannelResponse> {
+        self.ensure_bootstrap_seed().await;
+        let Some(mut channel) = self.storage.get_channel(&channel_id).await else {
+            return Err(ApiError::NotFound("channel not found".to_string()));
+        };
+        if channel.workspace_id != workspace_id {
+            return Err(ApiError::NotFound("channel not found".to_string()));
+        }
+
+        let mut errors = FieldErrors::default();
+        if let Some(topic) = payload.topic {
+            let topic = topic.trim();
+            if topic.chars().count() > MAX_TOPIC_CHARS {
+                errors.add(
+                    "topic",
+                    format!("must be at most {MAX_TOPIC_CHARS} characters"),
+                );
+            }
+            channel.topic = (!topic.is_empty()).then(|| topic.to_string());
+        }
+        if let Some(icon_emoji) = payload.icon_emoji {
+            let icon_emoji = icon_emoji.trim();
+            if icon_emoji.chars().count() > 32 || icon_emoji.chars().any(char::is_whitespace) {
+                errors.add("icon_emoji", "must be a single emoji or :shortcode:");
+            }
+            channel.icon_emoji = (!icon_emoji.is_empty()).then(|| icon_emoji.to_string());
+        }
+        if let Some(color) = payload.color {
+            let color = color.trim().to_ascii_lowercase();
+            if !color.is_empty() && !is_hex_color(&color) {
+                errors.add("color", "must be a hex color like #1a2b3c");
+            }
+            channel.color = (!color.is_empty()).then_some(color);
+        }
+        errors.into_result()?;
+
+        self.storage.insert_channel(channel.clone()).await;
+        Ok(ChannelResponse::from(&channel))
     }
 
     pub async fn delete_channel(&self, workspace_id: Uuid, channel_id: Uuid) -> ApiResult<()> {
@@ -185,9 +476,33 @@ impl ChannelService {
         if channel.workspace_id != workspace_id {
             return Err(ApiError::NotFound("channel not found".to_string()));
         }
+        let holds = LegalHolds::load(&self.storage, workspace_id).await;
+        if holds.covers_channel(channel_id)
+            || (!holds.is_empty()
+                && self
+                    .storage
+                    .list_messages(workspace_id)
+                    .await
+                    .iter()
+                    .any(|message| {
+                        message.channel_id == channel_id && holds.covers_message(message)
+                    }))
+        {
+            return Err(legal_hold_error());
+        }
 
+        let link_ids = self
+            .storage
+            .list_channel_links_for_channel(channel_id)
+            .await
+            .iter()
+            .map(|link| link.id)
+            .collect::<Vec<_>>();
+        self.storage.remove_channel_links(&link_ids).await;
         self.storage.remove_channel(&channel_id).await;
         self.storage.remove_channel_members(channel_id).await;
+        self.storage.remove_channel_admins(channel_id).await;
+        self.storage.remove_channel_keys(channel_id).await;
         self.storage.remove_messages_for_channel(channel_id).await;
         Ok(())
     }
@@ -198,39 +513,179 @@ impl ChannelService {
         channel_id: Uuid,
     ) -> ApiResult<Vec<ChannelMemberResponse>> {
         self.ensure_bootstrap_seed().await;
-        let channel = self
-            .storage
-            .get_channel(&channel_id)
-            .await
-            .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
-        if channel.workspace_id != workspace_id {
-            return Err(ApiError::NotFound("channel not found".to_string()));
-        }
+        self.visible_channel(workspace_id, channel_id).await?;
 
         let mut users = self.storage.list_channel_members(channel_id).await;
         users.sort_unstable();
         users.dedup();
+        let admins = self
+            .storage
+            .list_channel_admins(channel_id)
+            .await
+            .into_iter()
+            .collect::<HashSet<_>>();
         Ok(users
             .into_iter()
-            .map(|user_id| ChannelMemberResponse { user_id })
+            .map(|user_id| ChannelMemberResponse {
+                user_id,
+                role: if admins.contains(&user_id) {
+                    ChannelMemberRole::Admin
+                } else {
+                    ChannelMemberRole::Member
+                },
+            })
             .collect())
     }
 
-    pub async fn add_channel_member(
+    pub async fn update_channel_member_role(
         &self,
         workspace_id: Uuid,
         channel_id: Uuid,
         user_id: Uuid,
+        role: ChannelMemberRole,
     ) -> ApiResult<()> {
         self.ensure_bootstrap_seed().await;
-        let channel = self
+        let channel = self.visible_channel(workspace_id, channel_id).await?;
+        self.ensure_member_manageable(&channel, workspace_id, user_id)
+            .await?;
+        if !self.storage.is_channel_member(channel_id, user_id).await {
+            return Err(ApiError::NotFound("channel member not found".to_string()));
+        }
+        self.storage
+            .set_channel_admin(channel_id, user_id, role == ChannelMemberRole::Admin)
+            .await;
+        Ok(())
+    }
+
+    pub async fn message_limits(&self, workspace_id: Uuid) -> MessageLimits {
+        self.storage
+            .get_message_limits(workspace_id)
+            .await
+            .as_ref()
+            .map(MessageLimits::from)
+            .unwrap_or_default()
+    }
+
+    pub async fn update_message_limits(
+        &self,
+        context: &AuthContext,
+        payload: UpdateMessageLimitsRequest,
+    ) -> ApiResult<MessageLimits> {
+        let mut errors = FieldErrors::default();
+        for (field, value, ceiling) in [
+            (
+                "max_message_chars",
+                payload.max_message_chars,
+                MESSAGE_CHARS_CEILING,
+            ),
+            (
+                "max_attachments_per_message",
+                payload.max_attachments_per_message,
+                ATTACHMENTS_CEILING,
+            ),
+            (
+                "max_thread_depth",
+                payload.max_thread_depth,
+                THREAD_DEPTH_CEILING,
+            ),
+        ] {
+            if value.is_some_and(|value| !(1..=ceiling).contains(&value)) {
+                errors.add(field, format!("must be between 1 and {ceiling}"));
+            }
+        }
+        errors.into_result()?;
+
+        let current = self.message_limits(context.workspace_id).await;
+        let limits = MessageLimits {
+            max_message_chars: payload
+                .max_message_chars
+                .unwrap_or(current.max_message_chars),
+            max_attachments_per_message: payload
+                .max_attachments_per_message
+                .unwrap_or(current.max_attachments_per_message),
+            max_thread_depth: payload.max_thread_depth.unwrap_or(current.max_thread_depth),
+        };
+        self.storage
+            .put_message_limits(MessageLimitsRecordStore {
+                workspace_id: context.workspace_id,
+                max_message_chars: limits.max_message_chars.into(),
+                max_attachments_per_message: limits.max_attachments_per_message.into(),
+                max_thread_depth: limits.max_thread_depth.into(),
+                updated_by: context.user_id,
+                updated_at: Utc::now().timestamp_millis(),
+            })
+            .await;
+        Ok(limits)
+    }
+
+    /// Rejects a commit that would push `message_id` past the workspace's
+    /// attachment limit.
+    pub async fn ensure_attachment_capacity(
+        &self,
+        workspace_id: Uuid,
+        message_id: Uuid,
+    ) -> ApiResult<()> {
+        let max = self
+            .message_limits(workspace_id)
+            .await
+            .max_attachments_per_message;
+        let attached = self
             .storage
-            .get_channel(&channel_id)
+            .list_attachments_for_messages(workspace_id, &[message_id])
             .await
-            .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
-        if channel.workspace_id != workspace_id {
-            return Err(ApiError::NotFound("channel not found".to_string()));
+            .len();
+        if attached >= max as usize {
+            return Err(ApiError::Coded(
+                ErrorCode::TooManyAttachments,
+                format!("messages can have at most {max} attachments"),
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn check_message_length(
+        &self,
+        workspace_id: Uuid,
+        body: &str,
+    ) -> ApiResult<()> {
+        let max = self.message_limits(workspace_id).await.max_message_chars;
+        if body.chars().count() > max as usize {
+            return Err(ApiError::Coded(
+                ErrorCode::MessageTooLong,
+                format!("body_md must be at most {max} characters"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Workspace owners and admins manage every channel; channel admins only
+    /// the channels they were promoted in.
+    pub async fn ensure_channel_manager(
+        &self,
+        context: &AuthContext,
+        channel_id: Uuid,
+    ) -> ApiResult<()> {
+        if ensure_channel_admin(context).is_ok()
+            || self
+                .storage
+                .is_channel_admin(channel_id, context.user_id)
+                .await
+        {
+            return Ok(());
         }
+        Err(ApiError::Forbidden(
+            "you do not have permission to manage this channel".to_string(),
+        ))
+    }
+
+    pub async fn add_channel_member(
+        &self,
+        workspace_id: Uuid,
+        channel_id: Uuid,
+        user_id: Uuid,
+    ) -> ApiResult<()> {
+        self.ensure_bootstrap_seed().await;
+        self.visible_channel(workspace_id, channel_id).await?;
 
         let membership = self
             .storage
@@ -252,53 +707,146 @@ impl ChannelService {
         user_id: Uuid,
     ) -> ApiResult<()> {
         self.ensure_bootstrap_seed().await;
-        let channel = self
-            .storage
-            .get_channel(&channel_id)
-            .await
-            .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
-        if channel.workspace_id != workspace_id {
-            return Err(ApiError::NotFound("channel not found".to_string()));
-        }
+        let channel = self.visible_channel(workspace_id, channel_id).await?;
+        self.ensure_member_manageable(&channel, workspace_id, user_id)
+            .await?;
         self.storage
             .remove_channel_member(channel_id, user_id)
             .await;
+        self.storage
+            .set_channel_admin(channel_id, user_id, false)
+            .await;
         Ok(())
     }
 
+    /// Stores the message together with its `MESSAGE_CREATED` outbox event;
+    /// `correlation_id` is echoed on the event.
     pub async fn create_message(
         &self,
         context: &AuthContext,
         channel_id: Uuid,
         payload: CreateMessageRequest,
+        correlation_id: Option<String>,
     ) -> ApiResult<MessageResponse> {
         self.ensure_bootstrap_seed().await;
+        mutes::ensure_not_muted(&self.storage, context.workspace_id, context.user_id).await?;
         let body = payload.body_md.trim().to_string();
         if body.is_empty() {
-            return Err(ApiError::BadRequest("message body is required".to_string()));
+            let mut errors = FieldErrors::default();
+            errors.add("body_md", "must not be empty");
+            errors.into_result()?;
         }
 
-        self.assert_channel_access(context, channel_id).await?;
+        let channel = self.assert_channel_access(context, channel_id).await?;
+        e2ee::check_envelope(&channel, &body)?;
+        self.check_message_length(channel.workspace_id, &body)
+            .await?;
+
+        self.write_new_message(context, &channel, body, None, correlation_id)
+            .await
+    }
+
+    /// Posts the same body to several channels as linked copies sharing a
+    /// `crosspost_group_id`. Every channel is checked before anything is
+    /// written, so either all copies are created or none.
+    pub async fn crosspost_message(
+        &self,
+        context: &AuthContext,
+        payload: CrosspostMessageRequest,
+    ) -> ApiResult<CrosspostMessageResponse> {
+        self.ensure_bootstrap_seed().await;
+        mutes::ensure_not_muted(&self.storage, context.workspace_id, context.user_id).await?;
+        let body = payload.body_md.trim().to_string();
+        let mut channel_ids = Vec::new();
+        for channel_id in payload.channel_ids {
+            if !channel_ids.contains(&channel_id) {
+                channel_ids.push(channel_id);
+            }
+        }
+        let mut errors = FieldErrors::default();
+        if body.is_empty() {
+            errors.add("body_md", "must not be empty");
+        }
+        if !(2..=MAX_CROSSPOST_CHANNELS).contains(&channel_ids.len()) {
+            errors.add(
+                "channel_ids",
+                format!("must list between 2 and {MAX_CROSSPOST_CHANNELS} distinct channels"),
+            );
+        }
+        errors.into_result()?;
 
+        let mut channels = Vec::with_capacity(channel_ids.len());
+        for channel_id in channel_ids {
+            let channel = self.assert_channel_access(context, channel_id).await?;
+            if channel.e2ee {
+                return Err(ApiError::BadRequest(
+                    "messages cannot be cross-posted to encrypted channels".to_string(),
+                ));
+            }
+            self.check_message_length(channel.workspace_id, &body)
+                .await?;
+            channels.push(channel);
+        }
+
+        let crosspost_group_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+        let mut items = Vec::with_capacity(channels.len());
+        for channel in &channels {
+            items.push(
+                self.write_new_message(
+                    context,
+                    channel,
+                    body.clone(),
+                    Some(crosspost_group_id),
+                    None,
+                )
+                .await?,
+            );
+        }
+        Ok(CrosspostMessageResponse {
+            crosspost_group_id,
+            items,
+        })
+    }
+
+    async fn write_new_message(
+        &self,
+        context: &AuthContext,
+        channel: &ChannelRecordStore,
+        body: String,
+        crosspost_group_id: Option<Uuid>,
+        correlation_id: Option<String>,
+    ) -> ApiResult<MessageResponse> {
         let message = MessageRecordStore {
             id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
-            workspace_id: context.workspace_id,
-            channel_id,
+            workspace_id: channel.workspace_id,
+            channel_id: channel.id,
             sender_id: context.user_id,
             body_md: body,
             thread_root_id: None,
+            origin_workspace_id: origin_workspace(channel, context),
+            seq: self.storage.next_channel_seq(channel.id).await,
             created_at: Utc::now().timestamp_millis(),
             edited_at: None,
             deleted_at: None,
+            crosspost_group_id,
         };
 
         let response = self
-            .message_responses_with_attachments(context.workspace_id, vec![message.clone()])
+            .message_responses_with_attachments(message.workspace_id, vec![message.clone()])
             .await
             .into_iter()
             .next()
             .unwrap_or_else(|| MessageResponse::from(&message));
-        self.storage.insert_message(message).await;
+        let event = realtime::make_event(
+            "MESSAGE_CREATED",
+            context.workspace_id,
+            Some(channel.id),
+            correlation_id,
+            serde_json::to_value(&response).unwrap_or_default(),
+        );
+        self.storage
+            .write_message_with_event(message, outbox::record(context.workspace_id, &event))
+            .await?;
         Ok(response)
     }
 
@@ -309,7 +857,12 @@ impl ChannelService {
         query: &MessageQuery,
     ) -> ApiResult<MessageListResponse> {
         self.ensure_bootstrap_seed().await;
-        self.assert_channel_access(context, channel_id).await?;
+        let channel = self.assert_channel_access(context, channel_id).await?;
+        if query.after_seq.is_some() && query.cursor.is_some() {
+            return Err(ApiError::BadRequest(
+                "cursor and after_seq cannot be combined".to_string(),
+            ));
+        }
 
         let limit = query.limit.unwrap_or(50).clamp(1, 100);
         let before = query
@@ -317,18 +870,28 @@ impl ChannelService {
             .as_deref()
             .map(parse_cursor)
             .transpose()
-            .map_err(|error| ApiError::BadRequest(format!("invalid cursor: {error}")))?;
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
 
-        let messages = self.storage.list_messages(context.workspace_id).await;
+        let messages = self.storage.list_messages(channel.workspace_id).await;
         let mut channel_messages: Vec<&MessageRecordStore> = messages
             .iter()
-            .filter(|message| message.channel_id == channel_id && message.deleted_at.is_none())
+            .filter(|message| message.channel_id == channel_id)
+            .filter(|message| match query.after_seq {
+                Some(after_seq) => message.seq > after_seq,
+                None => message.deleted_at.is_none(),
+            })
             .collect();
-        channel_messages.sort_by(|a, b| {
-            b.created_at
-                .cmp(&a.created_at)
-                .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
-        });
+        if query.after_seq.is_some() {
+            channel_messages.sort_by_key(|message| message.seq);
+        } else {
+            channel_messages.sort_by(|a, b| {
+                b.created_at
+                    .cmp(&a.created_at)
+                    .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
+            });
+        }
 
         let filtered = channel_messages
             .into_iter()
@@ -342,13 +905,19 @@ impl ChannelService {
 
         let has_more = filtered.len() > limit;
         let message_items = filtered.into_iter().take(limit).collect::<Vec<_>>();
-        let items = self
+        let mut items = self
             .message_responses_with_attachments(
-                context.workspace_id,
+                channel.workspace_id,
                 message_items.into_iter().cloned().collect(),
             )
             .await;
-        let next_cursor = if has_more {
+        for item in items.iter_mut().filter(|item| item.deleted_at.is_some()) {
+            item.body_md.clear();
+            item.attachments.clear();
+        }
+        self.attach_reaction_summaries(context.user_id, &mut items)
+            .await;
+        let next_cursor = if has_more && query.after_seq.is_none() {
             items
                 .last()
                 .map(|message| format!("{}:{}", message.created_at, message.id.as_u128()))
@@ -364,11 +933,15 @@ impl ChannelService {
         context: &AuthContext,
         message_id: Uuid,
         payload: UpdateMessageRequest,
+        correlation_id: Option<String>,
     ) -> ApiResult<MessageResponse> {
         self.ensure_bootstrap_seed().await;
+        mutes::ensure_not_muted(&self.storage, context.workspace_id, context.user_id).await?;
         let body = payload.body_md.trim().to_string();
         if body.is_empty() {
-            return Err(ApiError::BadRequest("message body is required".to_string()));
+            let mut errors = FieldErrors::default();
+            errors.add("body_md", "must not be empty");
+            errors.into_result()?;
         }
 
         let mut message = self
@@ -377,28 +950,80 @@ impl ChannelService {
             .await
             .ok_or_else(|| ApiError::NotFound("message not found".to_string()))?;
 
-        if message.workspace_id != context.workspace_id {
+        if !self.message_visible(context.workspace_id, &message).await {
             return Err(ApiError::NotFound("message not found".to_string()));
         }
         if message.sender_id != context.user_id {
-            return Err(ApiError::Unauthorized(
+            return Err(ApiError::Forbidden(
                 "you can only edit your own messages".to_string(),
             ));
         }
-
+        if let Some(channel) = self.storage.get_channel(&message.channel_id).await {
+            e2ee::check_envelope(&channel, &body)?;
+        }
+        self.check_message_length(message.workspace_id, &body)
+            .await?;
+
+        let edited_at = Utc::now().timestamp_millis();
+        for mut sibling in self.crosspost_siblings(&message).await {
+            sibling.body_md = body.clone();
+            sibling.edited_at = Some(edited_at);
+            self.write_updated_message(context, sibling, None).await?;
+        }
         message.body_md = body;
-        message.edited_at = Some(Utc::now().timestamp_millis());
-        self.storage.update_message(message.clone()).await;
+        message.edited_at = Some(edited_at);
+        self.write_updated_message(context, message, correlation_id)
+            .await
+    }
+
+    async fn write_updated_message(
+        &self,
+        context: &AuthContext,
+        message: MessageRecordStore,
+        correlation_id: Option<String>,
+    ) -> ApiResult<MessageResponse> {
         let response = self
-            .message_responses_with_attachments(context.workspace_id, vec![message.clone()])
+            .message_responses_with_attachments(message.workspace_id, vec![message.clone()])
             .await
             .into_iter()
             .next()
             .unwrap_or_else(|| MessageResponse::from(&message));
-        Ok(response)
-    }
-
-    pub async fn delete_message(&self, context: &AuthContext, message_id: Uuid) -> ApiResult<()> {
+        let event = realtime::make_event(
+            "MESSAGE_UPDATED",
+            context.workspace_id,
+            Some(message.channel_id),
+            correlation_id,
+            serde_json::to_value(&response).unwrap_or_default(),
+        );
+        self.storage
+            .write_message_with_event(message, outbox::record(context.workspace_id, &event))
+            .await?;
+        Ok(response)
+    }
+
+    /// Live copies of a cross-posted message other than `message` itself.
+    async fn crosspost_siblings(&self, message: &MessageRecordStore) -> Vec<MessageRecordStore> {
+        let Some(group_id) = message.crosspost_group_id else {
+            return Vec::new();
+        };
+        self.storage
+            .list_crosspost_messages(group_id)
+            .await
+            .into_iter()
+            .filter(|sibling| {
+                sibling.id != message.id
+                    && sibling.sender_id == message.sender_id
+                    && sibling.deleted_at.is_none()
+            })
+            .collect()
+    }
+
+    pub async fn delete_message(
+        &self,
+        context: &AuthContext,
+        message_id: Uuid,
+        correlation_id: Option<String>,
+    ) -> ApiResult<()> {
         self.ensure_bootstrap_seed().await;
         let mut message = self
             .storage
@@ -406,18 +1031,64 @@ impl ChannelService {
             .await
             .ok_or_else(|| ApiError::NotFound("message not found".to_string()))?;
 
-        if message.workspace_id != context.workspace_id {
+        if !self.message_visible(context.workspace_id, &message).await {
             return Err(ApiError::NotFound("message not found".to_string()));
         }
-        let can_delete_other = matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin);
-        if message.sender_id != context.user_id && !can_delete_other {
-            return Err(ApiError::Unauthorized(
+        if !self.can_delete_message(context, &message).await {
+            return Err(ApiError::Forbidden(
                 "you do not have permission to delete this message".to_string(),
             ));
         }
 
-        message.deleted_at = Some(Utc::now().timestamp_millis());
-        self.storage.update_message(message).await;
+        let deleted_at = Utc::now().timestamp_millis();
+        // Copies of a cross-post go too, as far as the caller could delete
+        // each of them on its own.
+        for mut sibling in self.crosspost_siblings(&message).await {
+            if self.message_visible(context.workspace_id, &sibling).await
+                && self.can_delete_message(context, &sibling).await
+            {
+                sibling.deleted_at = Some(deleted_at);
+                self.write_deleted_message(context, sibling, None).await?;
+            }
+        }
+        message.deleted_at = Some(deleted_at);
+        self.write_deleted_message(context, message, correlation_id)
+            .await
+    }
+
+    async fn can_delete_message(
+        &self,
+        context: &AuthContext,
+        message: &MessageRecordStore,
+    ) -> bool {
+        if message.sender_id == context.user_id {
+            return true;
+        }
+        let sent_from = message.origin_workspace_id.unwrap_or(message.workspace_id);
+        (matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin)
+            && (message.workspace_id == context.workspace_id || sent_from == context.workspace_id))
+            || self
+                .storage
+                .is_channel_admin(message.channel_id, context.user_id)
+                .await
+    }
+
+    async fn write_deleted_message(
+        &self,
+        context: &AuthContext,
+        message: MessageRecordStore,
+        correlation_id: Option<String>,
+    ) -> ApiResult<()> {
+        let event = realtime::make_event(
+            "MESSAGE_DELETED",
+            context.workspace_id,
+            Some(message.channel_id),
+            correlation_id,
+            json!({ "message_id": message.id }),
+        );
+        self.storage
+            .write_message_with_event(message, outbox::record(context.workspace_id, &event))
+            .await?;
         Ok(())
     }
 
@@ -432,11 +1103,11 @@ impl ChannelService {
             .get_message(&message_id)
             .await
             .ok_or_else(|| ApiError::NotFound("message not found".to_string()))?;
-        if message.workspace_id != workspace_id || message.deleted_at.is_some() {
+        if message.deleted_at.is_some() || !self.message_visible(workspace_id, &message).await {
             return Err(ApiError::NotFound("message not found".to_string()));
         }
         let response = self
-            .message_responses_with_attachments(workspace_id, vec![message.clone()])
+            .message_responses_with_attachments(message.workspace_id, vec![message.clone()])
             .await
             .into_iter()
             .next()
@@ -449,7 +1120,9 @@ impl ChannelService {
         context: &AuthContext,
         channel_id: Uuid,
     ) -> ApiResult<()> {
-        self.assert_channel_access(context, channel_id).await
+        self.assert_channel_access(context, channel_id)
+            .await
+            .map(|_| ())
     }
 
     pub async fn thread_summary(
@@ -459,7 +1132,7 @@ impl ChannelService {
     ) -> ApiResult<ThreadSummaryResponse> {
         self.ensure_bootstrap_seed().await;
         let root_message = self.assert_thread_root(context, root_id).await?;
-        let messages = self.storage.list_messages(context.workspace_id).await;
+        let messages = self.storage.list_messages(root_message.workspace_id).await;
 
         let mut reply_count = 0usize;
         let mut last_reply_at = None;
@@ -476,12 +1149,20 @@ impl ChannelService {
             }
         }
 
-        let root_with_attachments = self
-            .message_responses_with_attachments(context.workspace_id, vec![root_message.clone()])
+        let mut root_with_attachments = self
+            .message_responses_with_attachments(
+                root_message.workspace_id,
+                vec![root_message.clone()],
+            )
             .await
             .into_iter()
             .next()
             .unwrap_or_else(|| MessageResponse::from(&root_message));
+        self.attach_reaction_summaries(
+            context.user_id,
+            std::slice::from_mut(&mut root_with_attachments),
+        )
+        .await;
         Ok(ThreadSummaryResponse {
             root_message: root_with_attachments,
             reply_count,
@@ -497,7 +1178,7 @@ impl ChannelService {
         query: &MessageQuery,
     ) -> ApiResult<MessageListResponse> {
         self.ensure_bootstrap_seed().await;
-        self.assert_thread_root(context, root_id).await?;
+        let root = self.assert_thread_root(context, root_id).await?;
 
         let limit = query.limit.unwrap_or(50).clamp(1, 100);
         let before = query
@@ -505,9 +1186,11 @@ impl ChannelService {
             .as_deref()
             .map(parse_cursor)
             .transpose()
-            .map_err(|error| ApiError::BadRequest(format!("invalid cursor: {error}")))?;
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
 
-        let messages = self.storage.list_messages(context.workspace_id).await;
+        let messages = self.storage.list_messages(root.workspace_id).await;
         let mut replies: Vec<&MessageRecordStore> = messages
             .iter()
             .filter(|message| {
@@ -531,12 +1214,14 @@ impl ChannelService {
             .collect::<Vec<_>>();
         let has_more = filtered.len() > limit;
         let reply_items = filtered.into_iter().take(limit).collect::<Vec<_>>();
-        let items = self
+        let mut items = self
             .message_responses_with_attachments(
-                context.workspace_id,
+                root.workspace_id,
                 reply_items.into_iter().cloned().collect(),
             )
             .await;
+        self.attach_reaction_summaries(context.user_id, &mut items)
+            .await;
         let next_cursor = if has_more {
             items
                 .last()
@@ -548,6 +1233,349 @@ impl ChannelService {
         Ok(MessageListResponse { items, next_cursor })
     }
 
+    pub async fn update_read_marker(
+        &self,
+        context: &AuthContext,
+        channel_id: Uuid,
+        payload: UpdateReadMarkerRequest,
+    ) -> ApiResult<ReadMarkerResponse> {
+        self.ensure_bootstrap_seed().await;
+        self.assert_channel_access(context, channel_id).await?;
+        if payload.last_read_seq < 0 {
+            let mut errors = FieldErrors::default();
+            errors.add("last_read_seq", "must not be negative");
+            errors.into_result()?;
+        }
+
+        let current = self
+            .storage
+            .list_read_markers(context.workspace_id, context.user_id)
+            .await
+            .into_iter()
+            .find(|marker| marker.channel_id == channel_id);
+        if let Some(current) =
+            current.filter(|marker| marker.last_read_seq >= payload.last_read_seq)
+        {
+            return Ok(ReadMarkerResponse::from(&current));
+        }
+        let marker = ReadMarkerRecordStore {
+            workspace_id: context.workspace_id,
+            user_id: context.user_id,
+            channel_id,
+            last_read_seq: payload.last_read_seq,
+            updated_at: Utc::now().timestamp_millis(),
+        };
+        self.storage.put_read_marker(marker.clone()).await;
+        Ok(ReadMarkerResponse::from(&marker))
+    }
+
+    /// Everything that changed in the caller's channels after `since`:
+    /// messages (paged by `limit`), channel metadata, membership and the
+    /// caller's read markers. The returned cursor trails the clock by
+    /// `SYNC_OVERLAP_MS`, so recent changes may be delivered twice.
+    pub async fn sync(&self, context: &AuthContext, query: &SyncQuery) -> ApiResult<SyncResponse> {
+        self.ensure_bootstrap_seed().await;
+        let limit = query.limit.unwrap_or(200).clamp(1, 500);
+        let since = query
+            .since
+            .as_deref()
+            .map(parse_cursor)
+            .transpose()
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
+        let (since_ts, since_id) = since.unwrap_or((0, 0));
+        let now = Utc::now().timestamp_millis();
+
+        let visible = self.visible_channels(context).await;
+        let channel_ids: HashSet<Uuid> = visible.iter().map(|channel| channel.id).collect();
+        let workspace_ids: HashSet<Uuid> =
+            visible.iter().map(|channel| channel.workspace_id).collect();
+
+        let mut changed = Vec::new();
+        for workspace_id in workspace_ids {
+            changed.extend(
+                self.storage
+                    .list_messages(workspace_id)
+                    .await
+                    .into_iter()
+                    .filter(|message| channel_ids.contains(&message.channel_id))
+                    .filter(|message| {
+                        (message_changed_at(message), message.id.as_u128()) > (since_ts, since_id)
+                    }),
+            );
+        }
+        changed.sort_by_key(|message| (message_changed_at(message), message.id.as_u128()));
+        let has_more = changed.len() > limit;
+        changed.truncate(limit);
+
+        let (until, next_cursor) = match changed.last() {
+            Some(last) if has_more => {
+                let changed_at = message_changed_at(last);
+                (changed_at, format!("{changed_at}:{}", last.id.as_u128()))
+            }
+            _ => {
+                let settled = (now - SYNC_OVERLAP_MS, 0).max((since_ts, since_id));
+                (now, format!("{}:{}", settled.0, settled.1))
+            }
+        };
+        let in_window = |at: i64| at > since_ts && at <= until;
+
+        let mut by_workspace: HashMap<Uuid, Vec<MessageRecordStore>> = HashMap::new();
+        for message in changed {
+            by_workspace
+                .entry(message.workspace_id)
+                .or_default()
+                .push(message);
+        }
+        let mut messages = Vec::new();
+        for (workspace_id, items) in by_workspace {
+            messages.extend(
+                self.message_responses_with_attachments(workspace_id, items)
+                    .await,
+            );
+        }
+        messages.sort_by_key(|item| {
+            (
+                last_change_at(item.created_at, item.edited_at, item.deleted_at),
+                item.id.as_u128(),
+            )
+        });
+        for item in messages.iter_mut().filter(|item| item.deleted_at.is_some()) {
+            item.body_md.clear();
+            item.attachments.clear();
+        }
+
+        let mut touched = HashSet::new();
+        let mut membership_changes = Vec::new();
+        for entry in self.storage.list_audit_entries(context.workspace_id).await {
+            if entry.target_type != "channel" || !in_window(entry.created_at) {
+                continue;
+            }
+            let Some(channel_id) = entry
+                .target_id
+                .as_deref()
+                .and_then(|value| Uuid::parse_str(value).ok())
+            else {
+                continue;
+            };
+            touched.insert(channel_id);
+            let kind = match entry.action.as_str() {
+                "CHANNEL_MEMBER_ADDED" => MembershipChangeKind::Added,
+                "CHANNEL_MEMBER_REMOVED" => MembershipChangeKind::Removed,
+                _ => continue,
+            };
+            let Some(user_id) = entry
+                .metadata
+                .get("member_user_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+            else {
+                continue;
+            };
+            if channel_ids.contains(&channel_id) || user_id == context.user_id {
+                membership_changes.push(MembershipChangeResponse {
+                    channel_id,
+                    user_id,
+                    kind,
+                    changed_at: entry.created_at,
+                });
+            }
+        }
+        membership_changes.sort_by_key(|change| change.changed_at);
+
+        let read_markers = self
+            .storage
+            .list_read_markers(context.workspace_id, context.user_id)
+            .await
+            .iter()
+            .filter(|marker| channel_ids.contains(&marker.channel_id))
+            .filter(|marker| since.is_none() || in_window(marker.updated_at))
+            .map(ReadMarkerResponse::from)
+            .collect();
+
+        Ok(SyncResponse {
+            channel_ids: visible.iter().map(|channel| channel.id).collect(),
+            channels: visible
+                .into_iter()
+                .filter(|channel| {
+                    since.is_none()
+                        || touched.contains(&channel.id)
+                        || in_window(channel.created_at)
+                })
+                .collect(),
+            messages,
+            membership_changes,
+            read_markers,
+            next_cursor,
+            has_more,
+        })
+    }
+
+    /// Channels of the workspace (shared ones included) whose messages the
+    /// caller can read: public ones, private ones they belong to, and every
+    /// channel for owners and admins.
+    pub async fn visible_channels(&self, context: &AuthContext) -> Vec<ChannelResponse> {
+        let can_bypass = matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin);
+        let mut visible = Vec::new();
+        for channel in self.list_channels(context.workspace_id).await {
+            if !channel.is_private
+                || can_bypass
+                || self
+                    .storage
+                    .is_channel_member(channel.id, context.user_id)
+                    .await
+            {
+                visible.push(channel);
+            }
+        }
+        visible
+    }
+
+    /// Responses for `messages`, kept in order, with their attachments and
+    /// the caller's reaction summaries.
+    pub async fn message_responses(
+        &self,
+        context: &AuthContext,
+        messages: Vec<MessageRecordStore>,
+    ) -> Vec<MessageResponse> {
+        let order = messages
+            .iter()
+            .map(|message| message.id)
+            .collect::<Vec<_>>();
+        let mut by_workspace: HashMap<Uuid, Vec<MessageRecordStore>> = HashMap::new();
+        for message in messages {
+            by_workspace
+                .entry(message.workspace_id)
+                .or_default()
+                .push(message);
+        }
+        let mut responses = HashMap::new();
+        for (workspace_id, items) in by_workspace {
+            for response in self
+                .message_responses_with_attachments(workspace_id, items)
+                .await
+            {
+                responses.insert(response.id, response);
+            }
+        }
+        let mut items = order
+            .iter()
+            .filter_map(|id| responses.remove(id))
+            .collect::<Vec<_>>();
+        self.attach_reaction_summaries(context.user_id, &mut items)
+            .await;
+        items
+    }
+
+    /// Messages from others that mention the caller (`<@user_id>`) or reply
+    /// to a thread the caller started, newest first.
+    pub async fn list_activity(
+        &self,
+        context: &AuthContext,
+        query: &ActivityQuery,
+    ) -> ApiResult<ActivityListResponse> {
+        self.ensure_bootstrap_seed().await;
+        let limit = query.limit.unwrap_or(50).clamp(1, 100);
+        let before = query
+            .cursor
+            .as_deref()
+            .map(parse_cursor)
+            .transpose()
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
+
+        let messages = self.storage.list_messages(context.workspace_id).await;
+        let own_roots = messages
+            .iter()
+            .filter(|message| {
+                message.sender_id == context.user_id && message.thread_root_id.is_none()
+            })
+            .map(|message| message.id)
+            .collect::<HashSet<_>>();
+
+        let mut candidates = messages
+            .iter()
+            .filter(|message| message.sender_id != context.user_id && message.deleted_at.is_none())
+            .filter_map(|message| {
+                let kind = if mentioned_user_ids(&message.body_md).contains(&context.user_id) {
+                    ActivityKind::Mention
+                } else if message
+                    .thread_root_id
+                    .is_some_and(|root_id| own_roots.contains(&root_id))
+                {
+                    ActivityKind::Reply
+                } else {
+                    return None;
+                };
+                query
+                    .kind
+                    .is_none_or(|wanted| wanted == kind)
+                    .then_some((kind, message))
+            })
+            .filter(|(_, message)| {
+                before.is_none_or(|(cursor_ts, cursor_id)| {
+                    (message.created_at, message.id.as_u128()) < (cursor_ts, cursor_id)
+                })
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(_, a), (_, b)| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
+        });
+
+        let mut channel_access = std::collections::HashMap::new();
+        let mut selected = Vec::new();
+        let mut has_more = false;
+        for (kind, message) in candidates {
+            let allowed = match channel_access.get(&message.channel_id) {
+                Some(allowed) => *allowed,
+                None => {
+                    let allowed = self
+                        .assert_channel_access(context, message.channel_id)
+                        .await
+                        .is_ok();
+                    channel_access.insert(message.channel_id, allowed);
+                    allowed
+                }
+            };
+            if !allowed {
+                continue;
+            }
+            if selected.len() == limit {
+                has_more = true;
+                break;
+            }
+            selected.push((kind, message.clone()));
+        }
+
+        let kinds = selected.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+        let mut responses = self
+            .message_responses_with_attachments(
+                context.workspace_id,
+                selected.into_iter().map(|(_, message)| message).collect(),
+            )
+            .await;
+        self.attach_reaction_summaries(context.user_id, &mut responses)
+            .await;
+        let next_cursor = if has_more {
+            responses
+                .last()
+                .map(|message| format!("{}:{}", message.created_at, message.id.as_u128()))
+        } else {
+            None
+        };
+        let items = kinds
+            .into_iter()
+            .zip(responses)
+            .map(|(kind, message)| ActivityItemResponse { kind, message })
+            .collect();
+
+        Ok(ActivityListResponse { items, next_cursor })
+    }
+
     pub async fn create_thread_reply(
         &self,
         context: &AuthContext,
@@ -555,51 +1583,85 @@ impl ChannelService {
         payload: CreateMessageRequest,
     ) -> ApiResult<MessageResponse> {
         self.ensure_bootstrap_seed().await;
+        mutes::ensure_not_muted(&self.storage, context.workspace_id, context.user_id).await?;
         let body = payload.body_md.trim().to_string();
         if body.is_empty() {
-            return Err(ApiError::BadRequest("message body is required".to_string()));
+            let mut errors = FieldErrors::default();
+            errors.add("body_md", "must not be empty");
+            errors.into_result()?;
         }
 
-        let (workspace_id, channel_id) = {
-            let messages = self.storage.list_messages(context.workspace_id).await;
-            let root = messages
-                .iter()
-                .find(|message| message.id == root_id)
-                .ok_or_else(|| ApiError::NotFound("thread root not found".to_string()))?;
-            if root.thread_root_id.is_some() {
-                return Err(ApiError::BadRequest(
-                    "thread replies must reference root message".to_string(),
-                ));
-            }
-            (root.workspace_id, root.channel_id)
-        };
-        if workspace_id != context.workspace_id {
+        let root = self
+            .storage
+            .get_message(&root_id)
+            .await
+            .ok_or_else(|| ApiError::NotFound("thread root not found".to_string()))?;
+        if !self.message_visible(context.workspace_id, &root).await {
             return Err(ApiError::NotFound("thread root not found".to_string()));
         }
-        self.assert_channel_access(context, channel_id).await?;
+        if root.thread_root_id.is_some() {
+            return Err(ApiError::BadRequest(
+                "thread replies must reference root message".to_string(),
+            ));
+        }
+        let channel = self.assert_channel_access(context, root.channel_id).await?;
+        e2ee::check_envelope(&channel, &body)?;
+        self.check_message_length(channel.workspace_id, &body)
+            .await?;
+        let max_thread_depth = self
+            .message_limits(channel.workspace_id)
+            .await
+            .max_thread_depth;
+        let replies = self
+            .storage
+            .list_messages(root.workspace_id)
+            .await
+            .iter()
+            .filter(|message| {
+                message.thread_root_id == Some(root_id) && message.deleted_at.is_none()
+            })
+            .count();
+        if replies >= max_thread_depth as usize {
+            return Err(ApiError::Coded(
+                ErrorCode::ThreadDepthExceeded,
+                format!("threads can hold at most {max_thread_depth} replies"),
+            ));
+        }
 
         let reply = MessageRecordStore {
             id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
-            workspace_id: context.workspace_id,
-            channel_id,
+            workspace_id: channel.workspace_id,
+            channel_id: channel.id,
             sender_id: context.user_id,
             body_md: body,
             thread_root_id: Some(root_id),
+            origin_workspace_id: origin_workspace(&channel, context),
+            seq: self.storage.next_channel_seq(channel.id).await,
             created_at: Utc::now().timestamp_millis(),
             edited_at: None,
             deleted_at: None,
+            crosspost_group_id: None,
         };
 
         let response = self
-            .message_responses_with_attachments(context.workspace_id, vec![reply.clone()])
+            .message_responses_with_attachments(reply.workspace_id, vec![reply.clone()])
             .await
             .into_iter()
             .next()
             .unwrap_or_else(|| MessageResponse::from(&reply));
-        self.storage.insert_message(reply).await;
+        self.storage.insert_message(reply).await?;
         Ok(response)
     }
 
+    async fn attach_reaction_summaries(&self, viewer_id: Uuid, items: &mut [MessageResponse]) {
+        let message_ids = items.iter().map(|message| message.id).collect::<Vec<_>>();
+        let mut reactions_by_message = self.storage.list_reactions_for_messages(&message_ids).await;
+        for item in items {
+            let reactions = reactions_by_message.remove(&item.id).unwrap_or_default();
+            item.reactions = Some(summarize_reactions(viewer_id, reactions));
+        }
+    }
+
     async fn message_responses_with_attachments(
         &self,
         workspace_id: Uuid,
@@ -631,29 +1693,97 @@ impl ChannelService {
                     sender_id: message.sender_id,
                     body_md: message.body_md,
                     thread_root_id: message.thread_root_id,
+                    origin_workspace_id: message.origin_workspace_id,
+                    seq: message.seq,
                     created_at: message.created_at,
                     edited_at: message.edited_at,
                     deleted_at: message.deleted_at,
+                    crosspost_group_id: message.crosspost_group_id,
                     attachments,
+                    reactions: None,
                 }
             })
             .collect()
     }
 
-    async fn assert_channel_access(
+    /// The host workspace plus every workspace with an active link to the channel.
+    pub async fn channel_workspaces(&self, channel_id: Uuid) -> Vec<Uuid> {
+        let Some(channel) = self.storage.get_channel(&channel_id).await else {
+            return Vec::new();
+        };
+        let mut workspaces = vec![channel.workspace_id];
+        workspaces.extend(
+            self.storage
+                .list_channel_links_for_channel(channel_id)
+                .await
+                .into_iter()
+                .filter(|link| link.is_active())
+                .map(|link| link.guest_workspace_id),
+        );
+        workspaces
+    }
+
+    async fn is_shared_with(&self, channel_id: Uuid, workspace_id: Uuid) -> bool {
+        self.storage
+            .list_channel_links_for_channel(channel_id)
+            .await
+            .iter()
+            .any(|link| link.is_active() && link.guest_workspace_id == workspace_id)
+    }
+
+    async fn visible_channel(
         &self,
-        context: &AuthContext,
+        workspace_id: Uuid,
         channel_id: Uuid,
-    ) -> ApiResult<()> {
+    ) -> ApiResult<ChannelRecordStore> {
         let channel = self
             .storage
             .get_channel(&channel_id)
             .await
             .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
-
-        if channel.workspace_id != context.workspace_id {
+        if channel.workspace_id != workspace_id
+            && !self.is_shared_with(channel_id, workspace_id).await
+        {
             return Err(ApiError::NotFound("channel not found".to_string()));
         }
+        Ok(channel)
+    }
+
+    /// A workspace a channel is shared with only manages its own people there;
+    /// the host's members stay under the host's control.
+    async fn ensure_member_manageable(
+        &self,
+        channel: &ChannelRecordStore,
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> ApiResult<()> {
+        if channel.workspace_id != workspace_id
+            && self
+                .storage
+                .get_membership_role(workspace_id, user_id)
+                .await
+                .is_none()
+        {
+            return Err(ApiError::Forbidden(
+                "only the host workspace can manage its members in a shared channel".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn message_visible(&self, workspace_id: Uuid, message: &MessageRecordStore) -> bool {
+        message.workspace_id == workspace_id
+            || self.is_shared_with(message.channel_id, workspace_id).await
+    }
+
+    async fn assert_channel_access(
+        &self,
+        context: &AuthContext,
+        channel_id: Uuid,
+    ) -> ApiResult<ChannelRecordStore> {
+        let channel = self
+            .visible_channel(context.workspace_id, channel_id)
+            .await?;
         if channel.is_private {
             let can_bypass = matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin);
             if !can_bypass
@@ -661,14 +1791,18 @@ impl ChannelService {
                     .storage
                     .is_channel_member(channel_id, context.user_id)
                     .await
+                && !self
+                    .storage
+                    .is_channel_admin(channel_id, context.user_id)
+                    .await
             {
-                return Err(ApiError::Unauthorized(
+                return Err(ApiError::Forbidden(
                     "you do not have access to this private channel".to_string(),
                 ));
             }
         }
 
-        Ok(())
+        Ok(channel)
     }
 
     async fn assert_thread_root(
@@ -681,7 +1815,8 @@ impl ChannelService {
             .get_message(&root_id)
             .await
             .ok_or_else(|| ApiError::NotFound("thread root not found".to_string()))?;
-        if root.workspace_id != context.workspace_id || root.thread_root_id.is_some() {
+        if root.thread_root_id.is_some() || !self.message_visible(context.workspace_id, &root).await
+        {
             return Err(ApiError::NotFound("thread root not found".to_string()));
         }
         self.assert_channel_access(context, root.channel_id).await?;
@@ -698,19 +1833,48 @@ impl ChannelService {
             return;
         }
 
-        let channel = ChannelRecordStore {
-            id: Uuid::new_v4(),
-            workspace_id: self.bootstrap_workspace_id,
-            name: "general".to_string(),
-            is_private: false,
-            created_by: self.bootstrap_creator_id,
-            created_at: Utc::now().timestamp_millis(),
+        self.templates
+            .instantiate(
+                &self.storage,
+                self.bootstrap_workspace_id,
+                self.bootstrap_creator_id,
+            )
+            .await;
+    }
+}
+
+/// User ids referenced as `<@uuid>` in a message body, in order of appearance.
+fn origin_workspace(channel: &ChannelRecordStore, context: &AuthContext) -> Option<Uuid> {
+    (channel.workspace_id != context.workspace_id).then_some(context.workspace_id)
+}
+
+pub fn mentioned_user_ids(body: &str) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    for segment in body.split("<@").skip(1) {
+        let Some((candidate, _)) = segment.split_once('>') else {
+            continue;
         };
-        self.storage.insert_channel(channel).await;
+        if let Ok(id) = Uuid::parse_str(candidate.trim())
+            && !ids.contains(&id)
+        {
+            ids.push(id);
+        }
     }
+    ids
+}
+
+fn last_change_at(created_at: i64, edited_at: Option<i64>, deleted_at: Option<i64>) -> i64 {
+    [edited_at, deleted_at]
+        .into_iter()
+        .flatten()
+        .fold(created_at, i64::max)
 }
 
-fn parse_cursor(cursor: &str) -> Result<(i64, u128), &'static str> {
+fn message_changed_at(message: &MessageRecordStore) -> i64 {
+    last_change_at(message.created_at, message.edited_at, message.deleted_at)
+}
+
+pub(crate) fn parse_cursor(cursor: &str) -> Result<(i64, u128), &'static str> {
     let mut segments = cursor.split(':');
     let created_at = segments
         .next()
@@ -725,6 +1889,16 @@ fn parse_cursor(cursor: &str) -> Result<(i64, u128), &'static str> {
     Ok((created_at, id))
 }
 
+impl From<&ReadMarkerRecordStore> for ReadMarkerResponse {
+    fn from(marker: &ReadMarkerRecordStore) -> Self {
+        Self {
+            channel_id: marker.channel_id,
+            last_read_seq: marker.last_read_seq,
+            updated_at: marker.updated_at,
+        }
+    }
+}
+
 impl From<&ChannelRecordStore> for ChannelResponse {
     fn from(channel: &ChannelRecordStore) -> Self {
         Self {
@@ -732,6 +1906,11 @@ impl From<&ChannelRecordStore> for ChannelResponse {
             workspace_id: channel.workspace_id,
             name: channel.name.clone(),
             is_private: channel.is_private,
+            topic: channel.topic.clone(),
+            icon_emoji: channel.icon_emoji.clone(),
+            color: channel.color.clone(),
+            e2ee: channel.e2ee,
+            key_epoch: channel.key_epoch,
             created_by: channel.created_by,
             created_at: channel.created_at,
         }
@@ -747,18 +1926,50 @@ impl From<&MessageRecordStore> for MessageResponse {
             sender_id: message.sender_id,
             body_md: message.body_md.clone(),
             thread_root_id: message.thread_root_id,
+            origin_workspace_id: message.origin_workspace_id,
+            seq: message.seq,
             created_at: message.created_at,
             edited_at: message.edited_at,
             deleted_at: message.deleted_at,
+            crosspost_group_id: message.crosspost_group_id,
             attachments: Vec::new(),
+            reactions: None,
         }
     }
 }
 
-fn ensure_channel_admin(context: &AuthContext) -> ApiResult<()> {
-    match context.role {
+fn summarize_reactions(
+    viewer_id: Uuid,
+    reactions: Vec<(String, Uuid)>,
+) -> Vec<MessageReactionSummary> {
+    let mut grouped = std::collections::BTreeMap::<String, Vec<Uuid>>::new();
+    for (emoji, user_id) in reactions {
+        grouped.entry(emoji).or_default().push(user_id);
+    }
+    grouped
+        .into_iter()
+        .map(|(emoji, mut user_ids)| {
+            user_ids.sort_unstable();
+            user_ids.dedup();
+            MessageReactionSummary {
+                emoji,
+                count: user_ids.len(),
+                me: user_ids.contains(&viewer_id),
+            }
+        })
+        .collect()
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|ch| ch.is_ascii_hexdigit())
+}
+
+pub(crate) fn ensure_channel_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
         WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
-        WorkspaceRole::Member => Err(ApiError::Unauthorized(
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
             "you do not have permission to manage channels".to_string(),
         )),
     }
@@ -767,43 +1978,59 @@ fn ensure_channel_admin(context: &AuthContext) -> ApiResult<()> {
 #[utoipa::path(
     get,
     path = "/api/v1/channels",
+    operation_id = "list_channels",
+    tag = "channels",
     responses(
         (status = 200, description = "List channels", body = [ChannelResponse]),
+        (status = 304, description = "Not modified (If-None-Match matched the ETag)"),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     )
 )]
 pub(crate) async fn list_channels(
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> ApiResult<Json<Vec<ChannelResponse>>> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
     let items = state.channels.list_channels(context.workspace_id).await;
-    Ok(Json(items))
+    conditional::json_with_etag(&headers, &items)
 }
 
 #[utoipa::path(
     post,
     path = "/api/v1/channels",
+    operation_id = "create_channel",
+    tag = "channels",
     request_body = CreateChannelRequest,
     responses(
         (status = 201, description = "Channel created", body = ChannelResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 400, description = "Validation error", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Channel name already exists or Idempotency-Key in use", body = ErrorResponse),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 422, description = "Invalid fields or Idempotency-Key reused", body = ErrorResponse)
     )
 )]
 pub(crate) async fn create_channel(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<CreateChannelRequest>,
-) -> ApiResult<(StatusCode, Json<ChannelResponse>)> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
+    let mut idempotency = idempotency::scope(&headers, &context, "create_channel", &payload)?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
     ensure_channel_admin(&context)?;
+    state
+        .rate_limit
+        .check_channel_create(context.user_id)
+        .await?;
     let item = state
         .channels
         .create_channel(context.workspace_id, context.user_id, payload)
@@ -832,16 +2059,77 @@ pub(crate) async fn create_channel(
             ),
         )
         .await;
-    Ok((StatusCode::CREATED, Json(item)))
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::CREATED, &item)
+        .await)
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/channels/{id}",
+    operation_id = "update_channel",
+    tag = "channels",
+    request_body = UpdateChannelRequest,
+    responses(
+        (status = 200, description = "Channel updated", body = ChannelResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<UpdateChannelRequest>,
+) -> ApiResult<Json<ChannelResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_channel_admin(&context)?;
+    let item = state
+        .channels
+        .update_channel(context.workspace_id, channel_id, payload)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CHANNEL_UPDATED",
+            "channel",
+            Some(item.id.to_string()),
+            json!({ "topic": item.topic, "icon_emoji": item.icon_emoji, "color": item.color }),
+        )
+        .await;
+    realtime::emit_channel_event(
+        &state,
+        context.workspace_id,
+        realtime::make_event(
+            "CHANNEL_UPDATED",
+            context.workspace_id,
+            Some(item.id),
+            None,
+            serde_json::to_value(&item).unwrap_or_default(),
+        ),
+    )
+    .await;
+    Ok(Json(item))
 }
 
 #[utoipa::path(
     delete,
     path = "/api/v1/channels/{id}",
+    operation_id = "delete_channel",
+    tag = "channels",
     responses(
         (status = 204, description = "Channel deleted"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 404, description = "Channel not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "Channel content is under legal hold", body = ErrorResponse)
     )
 )]
 pub(crate) async fn delete_channel(
@@ -888,9 +2176,13 @@ pub(crate) async fn delete_channel(
 #[utoipa::path(
     get,
     path = "/api/v1/channels/{id}/members",
+    operation_id = "list_channel_members",
+    tag = "channels",
     responses(
         (status = 200, description = "List channel members", body = [ChannelMemberResponse]),
+        (status = 304, description = "Not modified (If-None-Match matched the ETag)"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Channel not found", body = ErrorResponse)
     )
 )]
@@ -898,26 +2190,32 @@ pub(crate) async fn list_channel_members(
     State(state): State<AppState>,
     headers: HeaderMap,
     Path(channel_id): Path<Uuid>,
-) -> ApiResult<Json<Vec<ChannelMemberResponse>>> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
-    ensure_channel_admin(&context)?;
+    state
+        .channels
+        .ensure_channel_manager(&context, channel_id)
+        .await?;
     let items = state
         .channels
         .list_channel_members(context.workspace_id, channel_id)
         .await?;
-    Ok(Json(items))
+    conditional::json_with_etag(&headers, &items)
 }
 
 #[utoipa::path(
     post,
     path = "/api/v1/channels/{id}/members",
+    operation_id = "add_channel_member",
+    tag = "channels",
     request_body = AddChannelMemberRequest,
     responses(
         (status = 204, description = "Channel member added"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Channel not found", body = ErrorResponse)
     )
 )]
@@ -931,7 +2229,10 @@ pub(crate) async fn add_channel_member(
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
-    ensure_channel_admin(&context)?;
+    state
+        .channels
+        .ensure_channel_manager(&context, channel_id)
+        .await?;
     state
         .channels
         .add_channel_member(context.workspace_id, channel_id, payload.user_id)
@@ -947,15 +2248,19 @@ pub(crate) async fn add_channel_member(
             json!({ "member_user_id": payload.user_id }),
         )
         .await;
+    notifications::notify_channel_invite(&state, &context, channel_id, payload.user_id).await;
     Ok(StatusCode::NO_CONTENT)
 }
 
 #[utoipa::path(
     delete,
     path = "/api/v1/channels/{id}/members/{user_id}",
+    operation_id = "remove_channel_member",
+    tag = "channels",
     responses(
         (status = 204, description = "Channel member removed"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Channel not found", body = ErrorResponse)
     )
 )]
@@ -968,7 +2273,10 @@ pub(crate) async fn remove_channel_member(
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
-    ensure_channel_admin(&context)?;
+    state
+        .channels
+        .ensure_channel_manager(&context, channel_id)
+        .await?;
     state
         .channels
         .remove_channel_member(context.workspace_id, channel_id, user_id)
@@ -987,14 +2295,104 @@ pub(crate) async fn remove_channel_member(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/v1/channels/{id}/members/{user_id}",
+    operation_id = "update_channel_member",
+    tag = "channels",
+    request_body = UpdateChannelMemberRequest,
+    responses(
+        (status = 204, description = "Channel member role updated"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel or member not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_channel_member(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((channel_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateChannelMemberRequest>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_channel_admin(&context)?;
+    state
+        .channels
+        .update_channel_member_role(context.workspace_id, channel_id, user_id, payload.role)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CHANNEL_MEMBER_ROLE_UPDATED",
+            "channel",
+            Some(channel_id.to_string()),
+            json!({ "member_user_id": user_id, "role": payload.role }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/channels/{id}/read-marker",
+    operation_id = "update_read_marker",
+    tag = "channels",
+    request_body = UpdateReadMarkerRequest,
+    responses(
+        (status = 200, description = "Read marker (never moves backwards)", body = ReadMarkerResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_read_marker(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<UpdateReadMarkerRequest>,
+) -> ApiResult<Json<ReadMarkerResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let marker = state
+        .channels
+        .update_read_marker(&context, channel_id, payload)
+        .await?;
+    state
+        .realtime
+        .emit(
+            context.workspace_id,
+            realtime::make_user_event(
+                "READ_MARKER_UPDATED",
+                context.workspace_id,
+                context.user_id,
+                serde_json::to_value(&marker).unwrap_or_default(),
+            ),
+        )
+        .await;
+    Ok(Json(marker))
+}
+
 #[utoipa::path(
     post,
     path = "/api/v1/channels/{id}/messages",
+    operation_id = "create_message",
+    tag = "messages",
     request_body = CreateMessageRequest,
     responses(
         (status = 201, description = "Message created", body = MessageResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 404, description = "Channel not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key in use", body = ErrorResponse),
+        (status = 422, description = "Invalid fields or Idempotency-Key reused", body = ErrorResponse)
     )
 )]
 pub(crate) async fn create_message(
@@ -1002,15 +2400,53 @@ pub(crate) async fn create_message(
     headers: HeaderMap,
     Path(channel_id): Path<Uuid>,
     Json(payload): Json<CreateMessageRequest>,
-) -> ApiResult<(StatusCode, Json<MessageResponse>)> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
+    let mut idempotency = idempotency::scope(
+        &headers,
+        &context,
+        &format!("create_message:{channel_id}"),
+        &payload,
+    )?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
+    let item = post_message(&state, &context, channel_id, payload.body_md).await?;
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::CREATED, &item)
+        .await)
+}
+
+/// Everything a new channel message goes through after authentication; shared by
+/// the REST handler and the gRPC service.
+pub(crate) async fn post_message(
+    state: &AppState,
+    context: &AuthContext,
+    channel_id: Uuid,
+    body_md: String,
+) -> ApiResult<MessageResponse> {
+    state
+        .rate_limit
+        .check_message_write(context.user_id)
+        .await?;
+    let moderated = moderation::moderate_message(state, context, channel_id, None, body_md).await?;
     let item = state
         .channels
-        .create_message(&context, channel_id, payload)
+        .create_message(
+            context,
+            channel_id,
+            CreateMessageRequest {
+                body_md: moderated.body.clone(),
+            },
+            None,
+        )
         .await?;
+    moderated.open_case(state, &item).await;
+    state.metrics.record_message_created(context.workspace_id);
+    analytics::record_message(state, &item).await;
     state
         .audit
         .write(
@@ -1022,29 +2458,97 @@ pub(crate) async fn create_message(
             json!({ "channel_id": item.channel_id, "thread_root_id": item.thread_root_id }),
         )
         .await;
+    notifications::notify_message(state, &item).await;
+    Ok(item)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/messages",
+    operation_id = "crosspost_message",
+    tag = "messages",
+    request_body = CrosspostMessageRequest,
+    responses(
+        (status = 201, description = "One linked copy created per channel", body = CrosspostMessageResponse),
+        (status = 400, description = "Encrypted channel", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key in use", body = ErrorResponse),
+        (status = 422, description = "Invalid fields or Idempotency-Key reused", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn crosspost_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CrosspostMessageRequest>,
+) -> ApiResult<Response> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mut idempotency = idempotency::scope(&headers, &context, "crosspost_message", &payload)?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
     state
-        .realtime
-        .emit(
-            context.workspace_id,
-            realtime::make_event(
-                "MESSAGE_CREATED",
-                context.workspace_id,
-                Some(item.channel_id),
-                None,
-                serde_json::to_value(&item).unwrap_or_default(),
-            ),
+        .rate_limit
+        .check_message_write(context.user_id)
+        .await?;
+    // Cross-posts never reach encrypted channels, so the first channel stands
+    // in for all of them.
+    let moderated = match payload.channel_ids.first() {
+        Some(&channel_id) => {
+            moderation::moderate_message(&state, &context, channel_id, None, payload.body_md)
+                .await?
+        }
+        None => moderation::ModeratedBody::unchanged(payload.body_md),
+    };
+    let crosspost = state
+        .channels
+        .crosspost_message(
+            &context,
+            CrosspostMessageRequest {
+                channel_ids: payload.channel_ids,
+                body_md: moderated.body.clone(),
+            },
         )
-        .await;
-    Ok((StatusCode::CREATED, Json(item)))
+        .await?;
+    for item in &crosspost.items {
+        moderated.open_case(&state, item).await;
+        state.metrics.record_message_created(context.workspace_id);
+        analytics::record_message(&state, item).await;
+        state
+            .audit
+            .write(
+                context.workspace_id,
+                Some(context.user_id),
+                "MESSAGE_CREATED",
+                "message",
+                Some(item.id.to_string()),
+                json!({
+                    "channel_id": item.channel_id,
+                    "crosspost_group_id": crosspost.crosspost_group_id,
+                }),
+            )
+            .await;
+        notifications::notify_message(&state, item).await;
+    }
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::CREATED, &crosspost)
+        .await)
 }
 
 #[utoipa::path(
     get,
     path = "/api/v1/channels/{id}/messages",
+    operation_id = "list_messages",
+    tag = "messages",
     params(MessageQuery),
     responses(
         (status = 200, description = "Messages page", body = MessageListResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Channel not found", body = ErrorResponse)
     )
 )]
@@ -1068,11 +2572,15 @@ pub(crate) async fn list_messages(
 #[utoipa::path(
     patch,
     path = "/api/v1/messages/{id}",
+    operation_id = "update_message",
+    tag = "messages",
     request_body = UpdateMessageRequest,
     responses(
         (status = 200, description = "Message updated", body = MessageResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 404, description = "Message not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Message not found", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
     )
 )]
 pub(crate) async fn update_message(
@@ -1085,10 +2593,24 @@ pub(crate) async fn update_message(
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
+    state
+        .rate_limit
+        .check_message_write(context.user_id)
+        .await?;
+    let moderated =
+        moderation::moderate_message_edit(&state, &context, message_id, payload.body_md).await?;
     let item = state
         .channels
-        .update_message(&context, message_id, payload)
+        .update_message(
+            &context,
+            message_id,
+            UpdateMessageRequest {
+                body_md: moderated.body.clone(),
+            },
+            None,
+        )
         .await?;
+    moderated.open_case(&state, &item).await;
     state
         .audit
         .write(
@@ -1100,28 +2622,18 @@ pub(crate) async fn update_message(
             json!({ "channel_id": item.channel_id }),
         )
         .await;
-    state
-        .realtime
-        .emit(
-            context.workspace_id,
-            realtime::make_event(
-                "MESSAGE_UPDATED",
-                context.workspace_id,
-                Some(item.channel_id),
-                None,
-                serde_json::to_value(&item).unwrap_or_default(),
-            ),
-        )
-        .await;
     Ok(Json(item))
 }
 
 #[utoipa::path(
     delete,
     path = "/api/v1/messages/{id}",
+    operation_id = "delete_message",
+    tag = "messages",
     responses(
         (status = 204, description = "Message deleted"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Message not found", body = ErrorResponse)
     )
 )]
@@ -1134,7 +2646,10 @@ pub(crate) async fn delete_message(
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
-    state.channels.delete_message(&context, message_id).await?;
+    state
+        .channels
+        .delete_message(&context, message_id, None)
+        .await?;
     state
         .audit
         .write(
@@ -1146,19 +2661,6 @@ pub(crate) async fn delete_message(
             json!({}),
         )
         .await;
-    state
-        .realtime
-        .emit(
-            context.workspace_id,
-            realtime::make_event(
-                "MESSAGE_DELETED",
-                context.workspace_id,
-                None,
-                None,
-                json!({ "message_id": message_id }),
-            ),
-        )
-        .await;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -1200,6 +2702,7 @@ mod tests {
                     CreateMessageRequest {
                         body_md: format!("message {idx}"),
                     },
+                    None,
                 )
                 .await
                 .expect("message creation should succeed");
@@ -1212,6 +2715,7 @@ mod tests {
                 &MessageQuery {
                     cursor: None,
                     limit: Some(2),
+                    after_seq: None,
                 },
             )
             .await
@@ -1226,6 +2730,7 @@ mod tests {
                 &MessageQuery {
                     cursor: first_page.next_cursor,
                     limit: Some(2),
+                    after_seq: None,
                 },
             )
             .await
@@ -1273,6 +2778,7 @@ mod tests {
                 CreateMessageRequest {
                     body_md: "root".to_string(),
                 },
+                None,
             )
             .await
             .expect("root message should be created");
@@ -1336,6 +2842,7 @@ mod tests {
                 CreateChannelRequest {
                     name: "private-team".to_string(),
                     is_private: true,
+                    e2ee: false,
                 },
             )
             .await
@@ -1348,10 +2855,11 @@ mod tests {
                 CreateMessageRequest {
                     body_md: "hi".to_string(),
                 },
+                None,
             )
             .await
             .expect_err("member should not access private channel");
-        assert!(matches!(denied, ApiError::Unauthorized(_)));
+        assert!(matches!(denied, ApiError::Forbidden(_)));
 
         storage
             .add_channel_member(private_channel.id, member_id)
@@ -1364,6 +2872,7 @@ mod tests {
                 CreateMessageRequest {
                     body_md: "hi".to_string(),
                 },
+                None,
             )
             .await
             .expect("member should access private channel after membership");
@@ -1377,6 +2886,7 @@ mod tests {
                 CreateMessageRequest {
                     body_md: "owner".to_string(),
                 },
+                None,
             )
             .await
             .expect("owner should access private channel");
@@ -1405,6 +2915,7 @@ mod tests {
                 CreateChannelRequest {
                     name: "ops-private".to_string(),
                     is_private: true,
+                    e2ee: false,
                 },
             )
             .await
@@ -1431,4 +2942,692 @@ mod tests {
             .expect("list members should work after removal");
         assert!(!members_after.iter().any(|item| item.user_id == member_id));
     }
+
+    #[tokio::test]
+    async fn message_limits_are_enforced_with_specific_codes() {
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let service = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let context = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let channel_id = service
+            .list_channels(workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+        assert_eq!(
+            service.message_limits(workspace_id).await,
+            MessageLimits::default()
+        );
+        assert!(matches!(
+            service
+                .update_message_limits(
+                    &context,
+                    UpdateMessageLimitsRequest {
+                        max_message_chars: Some(0),
+                        max_attachments_per_message: None,
+                        max_thread_depth: Some(THREAD_DEPTH_CEILING + 1),
+                    },
+                )
+                .await,
+            Err(ApiError::Validation(fields)) if fields.len() == 2
+        ));
+        let limits = service
+            .update_message_limits(
+                &context,
+                UpdateMessageLimitsRequest {
+                    max_message_chars: Some(5),
+                    max_attachments_per_message: Some(1),
+                    max_thread_depth: Some(1),
+                },
+            )
+            .await
+            .expect("limits should update");
+        assert_eq!(service.message_limits(workspace_id).await, limits);
+
+        let post = |body: &str| CreateMessageRequest {
+            body_md: body.to_string(),
+        };
+        let root = service
+            .create_message(&context, channel_id, post("héllo"), None)
+            .await
+            .expect("five characters fit even with a multi-byte one");
+        assert!(matches!(
+            service
+                .create_message(&context, channel_id, post("hello!"), None)
+                .await,
+            Err(ApiError::Coded(ErrorCode::MessageTooLong, _))
+        ));
+
+        service
+            .create_thread_reply(&context, root.id, post("ok"))
+            .await
+            .expect("first reply should fit");
+        assert!(matches!(
+            service
+                .create_thread_reply(&context, root.id, post("again"))
+                .await,
+            Err(ApiError::Coded(ErrorCode::ThreadDepthExceeded, _))
+        ));
+
+        service
+            .ensure_attachment_capacity(workspace_id, root.id)
+            .await
+            .expect("no attachments yet");
+        storage
+            .put_attachment(crate::storage::AttachmentRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id,
+                channel_id,
+                message_id: Some(root.id),
+                uploader_id: owner_id,
+                filename: "a.txt".to_string(),
+                content_type: "text/plain".to_string(),
+                size_bytes: 1,
+                bucket: "bucket".to_string(),
+                key: "key".to_string(),
+                thumbnail_key: None,
+                region: "us-east-1".to_string(),
+                created_at: 0,
+            })
+            .await;
+        assert!(matches!(
+            service
+                .ensure_attachment_capacity(workspace_id, root.id)
+                .await,
+            Err(ApiError::Coded(ErrorCode::TooManyAttachments, _))
+        ));
+    }
+
+    #[tokio::test]
+    async fn channel_admins_manage_their_channel_only() {
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let lead_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        storage
+            .put_membership_role(workspace_id, lead_id, "member")
+            .await;
+        let service = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let owner_ctx = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let lead_ctx = AuthContext {
+            user_id: lead_id,
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let general_id = service
+            .list_channels(workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+        let team = service
+            .create_channel(
+                workspace_id,
+                owner_id,
+                CreateChannelRequest {
+                    name: "team".to_string(),
+                    is_private: false,
+                    e2ee: false,
+                },
+            )
+            .await
+            .expect("channel should be created");
+
+        assert!(matches!(
+            service
+                .update_channel_member_role(
+                    workspace_id,
+                    team.id,
+                    lead_id,
+                    ChannelMemberRole::Admin
+                )
+                .await,
+            Err(ApiError::NotFound(_))
+        ));
+        service
+            .add_channel_member(workspace_id, team.id, lead_id)
+            .await
+            .expect("add member should work");
+        service
+            .update_channel_member_role(workspace_id, team.id, lead_id, ChannelMemberRole::Admin)
+            .await
+            .expect("promotion should work");
+        assert!(
+            service
+                .ensure_channel_manager(&lead_ctx, team.id)
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            service.ensure_channel_manager(&lead_ctx, general_id).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        let members = service
+            .list_channel_members(workspace_id, team.id)
+            .await
+            .expect("list members should work");
+        assert!(
+            members
+                .iter()
+                .any(|item| item.user_id == lead_id && item.role == ChannelMemberRole::Admin)
+        );
+
+        let in_team = service
+            .create_message(
+                &owner_ctx,
+                team.id,
+                CreateMessageRequest {
+                    body_md: "team post".to_string(),
+                },
+                None,
+            )
+            .await
+            .expect("message should be created");
+        let in_general = service
+            .create_message(
+                &owner_ctx,
+                general_id,
+                CreateMessageRequest {
+                    body_md: "general post".to_string(),
+                },
+                None,
+            )
+            .await
+            .expect("message should be created");
+        assert!(matches!(
+            service.delete_message(&lead_ctx, in_general.id, None).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        service
+            .delete_message(&lead_ctx, in_team.id, None)
+            .await
+            .expect("channel admin should delete others' messages");
+
+        service
+            .remove_channel_member(workspace_id, team.id, lead_id)
+            .await
+            .expect("remove member should work");
+        assert!(
+            service
+                .ensure_channel_manager(&lead_ctx, team.id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn message_list_includes_reaction_summaries() {
+        let workspace_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let other_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let service = ChannelService::new(storage.clone(), workspace_id, user_id);
+        let context = AuthContext {
+            user_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let channel_id = service
+            .list_channels(workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+        let message = service
+            .create_message(
+                &context,
+                channel_id,
+                CreateMessageRequest {
+                    body_md: "hello".to_string(),
+                },
+                None,
+            )
+            .await
+            .expect("message creation should succeed");
+        storage.add_reaction(message.id, "👍", user_id).await;
+        storage.add_reaction(message.id, "👍", other_id).await;
+        storage.add_reaction(message.id, "🎉", other_id).await;
+
+        let page = service
+            .list_messages(
+                &context,
+                channel_id,
+                &MessageQuery {
+                    cursor: None,
+                    limit: None,
+                    after_seq: None,
+                },
+            )
+            .await
+            .expect("list should succeed");
+        let reactions = page.items[0]
+            .reactions
+            .as_ref()
+            .expect("listing should carry reactions");
+        let summary = reactions
+            .iter()
+            .map(|reaction| (reaction.emoji.as_str(), reaction.count, reaction.me))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![("🎉", 1, false), ("👍", 2, true)]);
+    }
+
+    #[tokio::test]
+    async fn activity_feed_lists_mentions_and_replies_to_own_threads() {
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let member_id = Uuid::new_v4();
+        let service = ChannelService::new(
+            Arc::new(
+                Storage::new(PersistenceBackend::Memory, None)
+                    .await
+                    .expect("memory storage should init"),
+            ),
+            workspace_id,
+            owner_id,
+        );
+        let owner = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let member = AuthContext {
+            user_id: member_id,
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let channel_id = service
+            .list_channels(workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+        let post = |context: &AuthContext, body: String| {
+            let service = service.clone();
+            let context = context.clone();
+            async move {
+                service
+                    .create_message(
+                        &context,
+                        channel_id,
+                        CreateMessageRequest { body_md: body },
+                        None,
+                    )
+                    .await
+                    .expect("message creation should succeed")
+            }
+        };
+
+        let root = post(&owner, "release plan".to_string()).await;
+        post(&member, format!("ping <@{owner_id}>")).await;
+        post(&member, "unrelated".to_string()).await;
+        post(&owner, format!("self <@{owner_id}>")).await;
+        service
+            .create_thread_reply(
+                &member,
+                root.id,
+                CreateMessageRequest {
+                    body_md: "looks good".to_string(),
+                },
+            )
+            .await
+            .expect("reply should succeed");
+
+        let feed = service
+            .list_activity(
+                &owner,
+                &ActivityQuery {
+                    cursor: None,
+                    limit: None,
+                    kind: None,
+                },
+            )
+            .await
+            .expect("activity should list");
+        let mut kinds = feed
+            .items
+            .iter()
+            .map(|item| format!("{:?}", item.kind))
+            .collect::<Vec<_>>();
+        kinds.sort();
+        assert_eq!(kinds, vec!["Mention", "Reply"]);
+
+        let mentions = service
+            .list_activity(
+                &owner,
+                &ActivityQuery {
+                    cursor: None,
+                    limit: Some(1),
+                    kind: Some(ActivityKind::Mention),
+                },
+            )
+            .await
+            .expect("activity should list");
+        assert_eq!(mentions.items.len(), 1);
+        assert!(mentions.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn update_channel_sets_and_clears_icon_and_color() {
+        let workspace_id = Uuid::new_v4();
+        let service = ChannelService::new(
+            Arc::new(
+                Storage::new(PersistenceBackend::Memory, None)
+                    .await
+                    .expect("memory storage should init"),
+            ),
+            workspace_id,
+            Uuid::new_v4(),
+        );
+        let channel_id = service.list_channels(workspace_id).await[0].id;
+        let update = |icon_emoji: Option<&str>, color: Option<&str>| UpdateChannelRequest {
+            topic: None,
+            icon_emoji: icon_emoji.map(str::to_string),
+            color: color.map(str::to_string),
+        };
+
+        let updated = service
+            .update_channel(
+                workspace_id,
+                channel_id,
+                update(Some(" 🚀 "), Some("#1A2B3C")),
+            )
+            .await
+            .expect("channel should update");
+        assert_eq!(updated.icon_emoji.as_deref(), Some("🚀"));
+        assert_eq!(updated.color.as_deref(), Some("#1a2b3c"));
+
+        let invalid = service
+            .update_channel(workspace_id, channel_id, update(None, Some("blue")))
+            .await;
+        assert!(matches!(invalid, Err(ApiError::Validation(_))));
+
+        let cleared = service
+            .update_channel(workspace_id, channel_id, update(Some(""), None))
+            .await
+            .expect("channel should update");
+        assert_eq!(cleared.icon_emoji, None);
+        assert_eq!(cleared.color.as_deref(), Some("#1a2b3c"));
+
+        let missing = service
+            .update_channel(Uuid::new_v4(), channel_id, update(None, None))
+            .await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn messages_get_channel_seq_and_after_seq_fills_gaps() {
+        let workspace_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let service = ChannelService::new(
+            Arc::new(
+                Storage::new(PersistenceBackend::Memory, None)
+                    .await
+                    .expect("memory storage should init"),
+            ),
+            workspace_id,
+            user_id,
+        );
+        let context = AuthContext {
+            user_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let channel_id = service.list_channels(workspace_id).await[0].id;
+        let body = |body_md: &str| CreateMessageRequest {
+            body_md: body_md.to_string(),
+        };
+
+        let root = service
+            .create_message(&context, channel_id, body("root"), None)
+            .await
+            .expect("root message");
+        let reply = service
+            .create_thread_reply(&context, root.id, body("reply"))
+            .await
+            .expect("reply");
+        let last = service
+            .create_message(&context, channel_id, body("last"), None)
+            .await
+            .expect("last message");
+        assert_eq!((root.seq, reply.seq, last.seq), (1, 2, 3));
+
+        service
+            .delete_message(&context, reply.id, None)
+            .await
+            .expect("delete reply");
+        let gap = service
+            .list_messages(
+                &context,
+                channel_id,
+                &MessageQuery {
+                    cursor: None,
+                    limit: None,
+                    after_seq: Some(1),
+                },
+            )
+            .await
+            .expect("range should list");
+        assert_eq!(
+            gap.items.iter().map(|item| item.seq).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(gap.items[0].deleted_at.is_some());
+        assert!(gap.items[0].body_md.is_empty());
+    }
+
+    #[tokio::test]
+    async fn crossposts_share_a_group_for_edits_and_deletes() {
+        let workspace_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let service = ChannelService::new(storage.clone(), workspace_id, user_id);
+        let context = AuthContext {
+            user_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let general = service.list_channels(workspace_id).await[0].id;
+        let random = service
+            .create_channel(
+                workspace_id,
+                user_id,
+                CreateChannelRequest {
+                    name: "random".to_string(),
+                    is_private: false,
+                    e2ee: false,
+                },
+            )
+            .await
+            .expect("channel")
+            .id;
+        let crosspost = |channel_ids: Vec<Uuid>| CrosspostMessageRequest {
+            channel_ids,
+            body_md: "release is out".to_string(),
+        };
+
+        assert!(matches!(
+            service
+                .crosspost_message(&context, crosspost(vec![general, general]))
+                .await,
+            Err(ApiError::Validation(_))
+        ));
+        let posted = service
+            .crosspost_message(&context, crosspost(vec![general, random]))
+            .await
+            .expect("cross-posted");
+        assert_eq!(
+            posted
+                .items
+                .iter()
+                .map(|item| (item.channel_id, item.crosspost_group_id))
+                .collect::<Vec<_>>(),
+            vec![
+                (general, Some(posted.crosspost_group_id)),
+                (random, Some(posted.crosspost_group_id))
+            ]
+        );
+
+        service
+            .update_message(
+                &context,
+                posted.items[0].id,
+                UpdateMessageRequest {
+                    body_md: "release is out, notes inside".to_string(),
+                },
+                None,
+            )
+            .await
+            .expect("edited");
+        let copy = storage
+            .get_message(&posted.items[1].id)
+            .await
+            .expect("copy");
+        assert_eq!(copy.body_md, "release is out, notes inside");
+        assert!(copy.edited_at.is_some());
+
+        service
+            .delete_message(&context, posted.items[1].id, None)
+            .await
+            .expect("deleted");
+        for item in &posted.items {
+            let message = storage.get_message(&item.id).await.expect("message");
+            assert!(message.deleted_at.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_pages_changes_and_returns_tombstones_and_read_markers() {
+        let workspace_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let service = ChannelService::new(
+            Arc::new(
+                Storage::new(PersistenceBackend::Memory, None)
+                    .await
+                    .expect("memory storage should init"),
+            ),
+            workspace_id,
+            user_id,
+        );
+        let context = AuthContext {
+            user_id,
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let channel_id = service.list_channels(workspace_id).await[0].id;
+        let mut ids = Vec::new();
+        for body_md in ["first", "second"] {
+            let message = service
+                .create_message(
+                    &context,
+                    channel_id,
+                    CreateMessageRequest {
+                        body_md: body_md.to_string(),
+                    },
+                    None,
+                )
+                .await
+                .expect("message creation should succeed");
+            ids.push(message.id);
+        }
+        service
+            .delete_message(&context, ids[0], None)
+            .await
+            .expect("delete first");
+        service
+            .update_read_marker(
+                &context,
+                channel_id,
+                UpdateReadMarkerRequest { last_read_seq: 2 },
+            )
+            .await
+            .expect("mark read");
+        let stale = service
+            .update_read_marker(
+                &context,
+                channel_id,
+                UpdateReadMarkerRequest { last_read_seq: 1 },
+            )
+            .await
+            .expect("stale marker is ignored");
+        assert_eq!(stale.last_read_seq, 2);
+
+        let first = service
+            .sync(
+                &context,
+                &SyncQuery {
+                    since: None,
+                    limit: Some(1),
+                },
+            )
+            .await
+            .expect("cold start");
+        assert!(first.has_more);
+        assert_eq!(first.channel_ids, vec![channel_id]);
+        assert_eq!(first.channels.len(), 1);
+        assert_eq!(first.read_markers[0].last_read_seq, 2);
+
+        let second = service
+            .sync(
+                &context,
+                &SyncQuery {
+                    since: Some(first.next_cursor),
+                    limit: Some(1),
+                },
+            )
+            .await
+            .expect("next page");
+        assert!(!second.has_more);
+        let synced = first
+            .messages
+            .iter()
+            .chain(&second.messages)
+            .collect::<Vec<_>>();
+        assert_eq!(synced.len(), 2);
+        let tombstone = synced
+            .iter()
+            .find(|message| message.id == ids[0])
+            .expect("deleted message is synced");
+        assert!(tombstone.deleted_at.is_some());
+        assert!(tombstone.body_md.is_empty());
+
+        let invalid = service
+            .sync(
+                &context,
+                &SyncQuery {
+                    since: Some("yesterday".to_string()),
+                    limit: None,
+                },
+            )
+            .await;
+        assert!(matches!(
+            invalid,
+            Err(ApiError::Coded(ErrorCode::InvalidCursor, _))
+        ));
+    }
 }
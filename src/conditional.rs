@@ -0,0 +1,80 @@
+use axum::{
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{ApiError, ApiResult};
+
+/// Serializes `body` as JSON with a weak ETag derived from its bytes, answering
+/// `304 Not Modified` when the request's `If-None-Match` already matches it.
+pub fn json_with_etag<T: Serialize>(headers: &HeaderMap, body: &T) -> ApiResult<Response> {
+    let bytes = serde_json::to_vec(body)
+        .map_err(|error| ApiError::Internal(format!("failed to serialize response: {error}")))?;
+    let etag = weak_etag(&bytes);
+    let etag_value = HeaderValue::from_str(&etag)
+        .map_err(|error| ApiError::Internal(format!("invalid etag: {error}")))?;
+
+    if if_none_match(headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag_value)]).into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+            (ETAG, etag_value),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+fn weak_etag(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let hex = format!("{digest:x}");
+    format!("W/\"{}\"", &hex[..32])
+}
+
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = etag.trim_start_matches("W/");
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == opaque)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_if_none_match_returns_not_modified() {
+        let body = serde_json::json!({ "items": [1, 2, 3] });
+        let first = json_with_etag(&HeaderMap::new(), &body).expect("response");
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers().get(ETAG).cloned().expect("etag header");
+        assert!(etag.to_str().expect("ascii").starts_with("W/\""));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", {}", etag.to_str().expect("ascii")))
+                .expect("header"),
+        );
+        let second = json_with_etag(&headers, &body).expect("response");
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers().get(ETAG), Some(&etag));
+
+        let changed =
+            json_with_etag(&headers, &serde_json::json!({ "items": [] })).expect("response");
+        assert_eq!(changed.status(), StatusCode::OK);
+    }
+}
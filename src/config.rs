@@ -1,18 +1,151 @@
-use crate::storage::PersistenceBackend;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-#[derive(Debug, Clone)]
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::Serialize;
+use serde_json::Value;
+use tracing_subscriber::EnvFilter;
+use utoipa::ToSchema;
+
+use crate::{encryption::MessageEncryption, storage::PersistenceBackend};
+
+pub const CONFIG_PATH_ENV: &str = "GALYNX_CONFIG";
+const MIN_JWT_SECRET_LEN: usize = 32;
+/// Fallback signing secret; only accepted with `DEV_MODE=true`.
+const DEV_JWT_SECRET: &str = "dev-only-change-me-in-prod";
+const MIN_OPERATOR_TOKEN_LEN: usize = 32;
+const DEFAULT_MAX_ATTACHMENT_SIZE_BYTES: u64 = 100 * 1024 * 1024;
+const DEFAULT_CORS_METHODS: &str = "GET,POST,PUT,PATCH,DELETE,OPTIONS";
+const DEFAULT_CORS_HEADERS: &str =
+    "authorization,content-type,accept,x-request-id,idempotency-key,if-none-match";
+const MODERATION_ACTIONS: [&str; 3] = ["block", "flag", "redact"];
+const SMTP_SECURITY_MODES: [&str; 3] = ["starttls", "tls", "none"];
+const SECRET_FILE_KEYS: [&str; 11] = [
+    "JWT_SECRET",
+    "MONGO_URI",
+    "REDIS_URL",
+    "BOOTSTRAP_PASSWORD",
+    "METRICS_TOKEN",
+    "OPERATOR_TOKEN",
+    "S3_ACCESS_KEY_ID",
+    "S3_SECRET_ACCESS_KEY",
+    "MODERATION_API_TOKEN",
+    "MESSAGE_ENCRYPTION_KEY",
+    "SMTP_PASSWORD",
+];
+
+/// Check for settings whose parser lives outside this module (channel
+/// templates, moderation rules, SMTP transport); its problems join the same
+/// report as `Config::validate`.
+pub type ConfigCheck = fn(&Config, &mut Vec<String>);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {source}")]
+    ReadFile {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to parse config file {path}: {message}")]
+    ParseFile { path: String, message: String },
+    #[error("invalid configuration:\n{}", format_problems(.0))]
+    Invalid(Vec<String>),
+    #[error("unsupported config file extension for {0} (expected .toml, .yaml or .yml)")]
+    UnsupportedFormat(String),
+}
+
+/// Values set on the command line; they take precedence over the environment
+/// and the config file and are kept so runtime reloads resolve the same way.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub config_path: Option<PathBuf>,
+    pub values: HashMap<String, String>,
+}
+
+impl ConfigOverrides {
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        self.values.insert(key.to_string(), value.to_string());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Compact,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct RateLimitSettings {
+    pub auth_per_minute: u32,
+    pub ws_connect_per_minute: u32,
+    pub ws_command_per_minute: u32,
+    pub message_write_per_minute: u32,
+    pub channel_create_per_minute: u32,
+    pub attachment_presign_per_minute: u32,
+    pub invite_per_minute: u32,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            auth_per_minute: 30,
+            ws_connect_per_minute: 12,
+            ws_command_per_minute: 600,
+            message_write_per_minute: 120,
+            channel_create_per_minute: 10,
+            attachment_presign_per_minute: 30,
+            invite_per_minute: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
     pub port: u16,
+    pub internal_port: Option<u16>,
+    pub grpc_port: Option<u16>,
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    #[serde(serialize_with = "serialize_secret")]
     pub jwt_secret: String,
+    pub dev_mode: bool,
     pub access_ttl_minutes: i64,
     pub refresh_ttl_days: i64,
     pub bootstrap_workspace_name: String,
     pub bootstrap_email: String,
+    #[serde(serialize_with = "serialize_secret")]
     pub bootstrap_password: String,
     pub persistence_backend: PersistenceBackend,
+    #[serde(serialize_with = "serialize_url")]
     pub mongo_uri: Option<String>,
+    #[serde(serialize_with = "serialize_url")]
     pub redis_url: Option<String>,
+    pub log_format: LogFormat,
+    pub log_filter: Option<String>,
+    pub slow_operation_threshold_ms: u64,
     pub metrics_enabled: bool,
+    pub api_docs_enabled: bool,
+    pub graphql_enabled: bool,
+    pub maintenance_mode: bool,
+    pub maintenance_message: Option<String>,
+    pub migrate_on_startup: bool,
+    #[serde(serialize_with = "serialize_optional_secret")]
+    pub metrics_token: Option<String>,
+    #[serde(serialize_with = "serialize_optional_secret")]
+    pub operator_token: Option<String>,
+    pub trusted_proxies: Vec<String>,
+    pub cors_enabled: bool,
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    pub cors_allowed_headers: Vec<String>,
+    pub cors_allow_credentials: bool,
+    pub cors_max_age_secs: u64,
+    pub rate_limits: RateLimitSettings,
+    pub max_attachment_size_bytes: u64,
     pub otel_exporter_otlp_endpoint: Option<String>,
     pub otel_service_name: String,
     pub otel_sample_ratio: f64,
@@ -20,56 +153,424 @@ pub struct Config {
     pub s3_region: String,
     pub s3_endpoint: Option<String>,
     pub s3_public_endpoint: Option<String>,
+    #[serde(serialize_with = "serialize_optional_secret")]
     pub s3_access_key_id: Option<String>,
+    #[serde(serialize_with = "serialize_optional_secret")]
     pub s3_secret_access_key: Option<String>,
     pub s3_force_path_style: bool,
+    pub moderation_rules_file: Option<String>,
+    pub channel_templates_file: Option<String>,
+    pub moderation_api_url: Option<String>,
+    #[serde(serialize_with = "serialize_optional_secret")]
+    pub moderation_api_token: Option<String>,
+    pub moderation_api_action: String,
+    pub moderation_api_timeout_ms: u64,
+    #[serde(serialize_with = "serialize_optional_secret")]
+    pub message_encryption_key: Option<String>,
+    /// Outgoing mail is only logged while no SMTP host is configured.
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_security: String,
+    pub smtp_username: Option<String>,
+    #[serde(serialize_with = "serialize_optional_secret")]
+    pub smtp_password: Option<String>,
+    pub email_from: String,
+    /// Public URL of the web app, used for links in emails.
+    pub app_base_url: String,
+    /// Minutes between unread-notification digests; 0 disables them.
+    pub email_digest_interval_minutes: u64,
+    /// Seconds without WebSocket commands before a connected user shows as away.
+    pub presence_away_secs: u64,
+    #[serde(skip)]
+    pub overrides: ConfigOverrides,
 }
 
 impl Config {
-    pub fn from_env() -> Self {
+    pub fn load_with(
+        overrides: ConfigOverrides,
+        checks: &[ConfigCheck],
+    ) -> Result<Self, ConfigError> {
+        Self::load(overrides, true, checks)
+    }
+
+    /// Loads the configuration for tools that never sign or verify access
+    /// tokens (`galynx-bootstrap`), so `JWT_SECRET` is not required there.
+    #[allow(dead_code)] // only the bootstrap binary calls it
+    pub fn load_without_jwt_secret(overrides: ConfigOverrides) -> Result<Self, ConfigError> {
+        Self::load(overrides, false, &[])
+    }
+
+    fn load(
+        overrides: ConfigOverrides,
+        requires_jwt_secret: bool,
+        checks: &[ConfigCheck],
+    ) -> Result<Self, ConfigError> {
+        let config_path = overrides
+            .config_path
+            .clone()
+            .or_else(|| read_env(CONFIG_PATH_ENV).map(PathBuf::from));
+        let source = match config_path {
+            Some(path) => ConfigSource::from_file(&path)?,
+            None => ConfigSource::default(),
+        };
+        Self::resolve(source, overrides, requires_jwt_secret, checks)
+    }
+
+    fn resolve(
+        mut source: ConfigSource,
+        overrides: ConfigOverrides,
+        requires_jwt_secret: bool,
+        checks: &[ConfigCheck],
+    ) -> Result<Self, ConfigError> {
+        source.overrides = overrides.values.clone();
+        let mut problems = Vec::new();
+        source.load_secret_files(&mut problems);
+        let mut config = Self::from_source(&source, &mut problems);
+        config.overrides = overrides;
+        if requires_jwt_secret {
+            config.validate_jwt_secret(&mut problems);
+        }
+        config.validate(&mut problems);
+        for check in checks {
+            check(&config, &mut problems);
+        }
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    fn validate_jwt_secret(&self, problems: &mut Vec<String>) {
+        if self.dev_mode {
+            return;
+        }
+        if self.jwt_secret == DEV_JWT_SECRET {
+            problems.push(
+                "JWT_SECRET must be set; the built-in development secret is only accepted with DEV_MODE=true"
+                    .to_string(),
+            );
+        } else if self.jwt_secret.len() < MIN_JWT_SECRET_LEN {
+            problems.push(format!(
+                "JWT_SECRET must be at least {MIN_JWT_SECRET_LEN} bytes (or set DEV_MODE=true)"
+            ));
+        }
+    }
+
+    fn validate(&self, problems: &mut Vec<String>) {
+        if self
+            .operator_token
+            .as_ref()
+            .is_some_and(|token| token.chars().count() < MIN_OPERATOR_TOKEN_LEN)
+        {
+            problems.push(format!(
+                "OPERATOR_TOKEN must be at least {MIN_OPERATOR_TOKEN_LEN} characters"
+            ));
+        }
+        if self.access_ttl_minutes <= 0 {
+            problems.push("ACCESS_TTL_MINUTES must be greater than 0".to_string());
+        }
+        if self.refresh_ttl_days <= 0 {
+            problems.push("REFRESH_TTL_DAYS must be greater than 0".to_string());
+        }
+        if self.access_ttl_minutes > 0
+            && self.refresh_ttl_days > 0
+            && self.access_ttl_minutes >= self.refresh_ttl_days * 24 * 60
+        {
+            problems.push("ACCESS_TTL_MINUTES must be shorter than REFRESH_TTL_DAYS".to_string());
+        }
+        if self.internal_port == Some(self.port) {
+            problems.push("INTERNAL_PORT must be different from PORT".to_string());
+        }
+        if self.grpc_port.is_some()
+            && (self.grpc_port == Some(self.port) || self.grpc_port == self.internal_port)
+        {
+            problems.push("GRPC_PORT must be different from PORT and INTERNAL_PORT".to_string());
+        }
+        if self.tls_cert_path.is_some() != self.tls_key_path.is_some() {
+            problems.push("TLS_CERT_PATH and TLS_KEY_PATH must be set together".to_string());
+        }
+        for (key, path) in [
+            ("TLS_CERT_PATH", &self.tls_cert_path),
+            ("TLS_KEY_PATH", &self.tls_key_path),
+        ] {
+            if let Some(path) = path
+                && !Path::new(path).is_file()
+            {
+                problems.push(format!("{key} does not point to a readable file: {path}"));
+            }
+        }
+        if self.persistence_backend == PersistenceBackend::Mongo && self.mongo_uri.is_none() {
+            problems.push("PERSISTENCE_BACKEND=mongo requires MONGO_URI".to_string());
+        }
+        if self.s3_bucket.is_some() && self.s3_region.trim().is_empty() {
+            problems.push("S3_BUCKET requires S3_REGION".to_string());
+        }
+        if self.s3_access_key_id.is_some() != self.s3_secret_access_key.is_some() {
+            problems
+                .push("S3_ACCESS_KEY_ID and S3_SECRET_ACCESS_KEY must be set together".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.otel_sample_ratio) {
+            problems.push("OTEL_SAMPLE_RATIO must be between 0 and 1".to_string());
+        }
+        if let Some(log_filter) = self.log_filter.as_deref()
+            && let Err(error) = EnvFilter::try_new(log_filter)
+        {
+            problems.push(format!("LOG_FILTER is invalid: {error}"));
+        }
+        if self.max_attachment_size_bytes == 0 {
+            problems.push("MAX_ATTACHMENT_SIZE_BYTES must be greater than 0".to_string());
+        }
+        for (key, value) in self.rate_limits.entries() {
+            if value == 0 {
+                problems.push(format!("{key} must be greater than 0"));
+            }
+        }
+        if self.cors_enabled {
+            self.validate_cors(problems);
+        }
+        if !MODERATION_ACTIONS.contains(&self.moderation_api_action.as_str()) {
+            problems.push(format!(
+                "MODERATION_API_ACTION must be one of {}",
+                MODERATION_ACTIONS.join(", ")
+            ));
+        }
+        if self.moderation_api_timeout_ms == 0 {
+            problems.push("MODERATION_API_TIMEOUT_MS must be greater than 0".to_string());
+        }
+        if self.presence_away_secs == 0 {
+            problems.push("PRESENCE_AWAY_SECS must be greater than 0".to_string());
+        }
+        if self.otel_service_name.trim().is_empty() {
+            problems.push("OTEL_SERVICE_NAME must not be empty".to_string());
+        }
+        if let Some(key) = self.message_encryption_key.as_deref()
+            && let Err(problem) = MessageEncryption::from_base64(key)
+        {
+            problems.push(problem);
+        }
+        self.validate_email(problems);
+    }
+
+    fn validate_email(&self, problems: &mut Vec<String>) {
+        if !SMTP_SECURITY_MODES.contains(&self.smtp_security.as_str()) {
+            problems.push(format!(
+                "SMTP_SECURITY must be one of {}",
+                SMTP_SECURITY_MODES.join(", ")
+            ));
+        }
+        if self.smtp_username.is_some() != self.smtp_password.is_some() {
+            problems.push("SMTP_USERNAME and SMTP_PASSWORD must be set together".to_string());
+        }
+        if self.email_from.parse::<lettre::message::Mailbox>().is_err() {
+            problems.push(format!(
+                "EMAIL_FROM is not a valid mailbox: {:?}",
+                self.email_from
+            ));
+        }
+        if !self.app_base_url.starts_with("http://") && !self.app_base_url.starts_with("https://") {
+            problems.push("APP_BASE_URL must be an http(s) URL".to_string());
+        }
+    }
+
+    fn validate_cors(&self, problems: &mut Vec<String>) {
+        let any_origin = self.cors_allowed_origins.iter().any(|origin| origin == "*");
+        if any_origin && self.cors_allow_credentials {
+            problems.push(
+                "CORS_ALLOW_CREDENTIALS requires explicit CORS_ALLOWED_ORIGINS, not *".to_string(),
+            );
+        }
+        for origin in self
+            .cors_allowed_origins
+            .iter()
+            .filter(|origin| *origin != "*")
+        {
+            if HeaderValue::from_str(origin).is_err() || !origin.contains("://") {
+                problems.push(format!(
+                    "CORS_ALLOWED_ORIGINS has an invalid origin: {origin:?}"
+                ));
+            }
+        }
+        for method in &self.cors_allowed_methods {
+            if Method::from_bytes(method.as_bytes()).is_err() {
+                problems.push(format!(
+                    "CORS_ALLOWED_METHODS has an invalid method: {method:?}"
+                ));
+            }
+        }
+        for name in &self.cors_allowed_headers {
+            if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                problems.push(format!(
+                    "CORS_ALLOWED_HEADERS has an invalid header: {name:?}"
+                ));
+            }
+        }
+    }
+
+    fn from_source(source: &ConfigSource, problems: &mut Vec<String>) -> Self {
         Self {
-            port: read_env("PORT")
-                .and_then(|value| value.parse::<u16>().ok())
-                .unwrap_or(3000),
-            jwt_secret: read_env("JWT_SECRET")
-                .unwrap_or_else(|| "dev-only-change-me-in-prod".to_string()),
-            access_ttl_minutes: read_env("ACCESS_TTL_MINUTES")
-                .and_then(|value| value.parse::<i64>().ok())
+            port: source.parse::<u16>("PORT", problems).unwrap_or(3000),
+            internal_port: source.parse::<u16>("INTERNAL_PORT", problems),
+            grpc_port: source.parse::<u16>("GRPC_PORT", problems),
+            tls_cert_path: source.read("TLS_CERT_PATH"),
+            tls_key_path: source.read("TLS_KEY_PATH"),
+            jwt_secret: source
+                .read("JWT_SECRET")
+                .unwrap_or_else(|| DEV_JWT_SECRET.to_string()),
+            dev_mode: source
+                .read("DEV_MODE")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            access_ttl_minutes: source
+                .parse::<i64>("ACCESS_TTL_MINUTES", problems)
                 .unwrap_or(15),
-            refresh_ttl_days: read_env("REFRESH_TTL_DAYS")
-                .and_then(|value| value.parse::<i64>().ok())
+            refresh_ttl_days: source
+                .parse::<i64>("REFRESH_TTL_DAYS", problems)
                 .unwrap_or(30),
-            bootstrap_workspace_name: read_env("BOOTSTRAP_WORKSPACE_NAME")
+            bootstrap_workspace_name: source
+                .read("BOOTSTRAP_WORKSPACE_NAME")
                 .unwrap_or_else(|| "Galynx".to_string()),
-            bootstrap_email: read_env("BOOTSTRAP_EMAIL")
+            bootstrap_email: source
+                .read("BOOTSTRAP_EMAIL")
                 .unwrap_or_else(|| "owner@galynx.local".to_string()),
-            bootstrap_password: read_env("BOOTSTRAP_PASSWORD")
+            bootstrap_password: source
+                .read("BOOTSTRAP_PASSWORD")
                 .unwrap_or_else(|| "ChangeMe123!".to_string()),
-            persistence_backend: read_env("PERSISTENCE_BACKEND")
+            persistence_backend: source
+                .read("PERSISTENCE_BACKEND")
                 .as_deref()
                 .map(PersistenceBackend::from_env_value)
                 .unwrap_or(PersistenceBackend::Memory),
-            mongo_uri: read_env("MONGO_URI"),
-            redis_url: read_env("REDIS_URL"),
-            metrics_enabled: read_env("METRICS_ENABLED")
+            mongo_uri: source.read("MONGO_URI"),
+            redis_url: source.read("REDIS_URL"),
+            log_format: source
+                .read("LOG_FORMAT")
+                .as_deref()
+                .map(LogFormat::from_env_value)
+                .unwrap_or(LogFormat::Compact),
+            log_filter: source.read("LOG_FILTER"),
+            slow_operation_threshold_ms: source
+                .parse::<u64>("SLOW_OPERATION_THRESHOLD_MS", problems)
+                .unwrap_or(1000),
+            metrics_enabled: source
+                .read("METRICS_ENABLED")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(true),
+            api_docs_enabled: source
+                .read("API_DOCS_ENABLED")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            graphql_enabled: source
+                .read("GRAPHQL_ENABLED")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            maintenance_mode: source
+                .read("MAINTENANCE_MODE")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            maintenance_message: source
+                .read("MAINTENANCE_MESSAGE")
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+            migrate_on_startup: source
+                .read("MIGRATE_ON_STARTUP")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            metrics_token: source.read("METRICS_TOKEN"),
+            operator_token: source.read("OPERATOR_TOKEN"),
+            trusted_proxies: source
+                .read("TRUSTED_PROXIES")
+                .map(|value| parse_list(&value))
+                .unwrap_or_default(),
+            cors_enabled: source
+                .read("CORS_ENABLED")
                 .map(|value| parse_bool(&value))
                 .unwrap_or(true),
-            otel_exporter_otlp_endpoint: read_env("OTEL_EXPORTER_OTLP_ENDPOINT"),
-            otel_service_name: read_env("OTEL_SERVICE_NAME")
+            cors_allowed_origins: parse_list(
+                &source
+                    .read("CORS_ALLOWED_ORIGINS")
+                    .unwrap_or_else(|| "*".to_string()),
+            ),
+            cors_allowed_methods: parse_list(
+                &source
+                    .read("CORS_ALLOWED_METHODS")
+                    .unwrap_or_else(|| DEFAULT_CORS_METHODS.to_string()),
+            )
+            .into_iter()
+            .map(|method| method.to_ascii_uppercase())
+            .collect(),
+            cors_allowed_headers: parse_list(
+                &source
+                    .read("CORS_ALLOWED_HEADERS")
+                    .unwrap_or_else(|| DEFAULT_CORS_HEADERS.to_string()),
+            )
+            .into_iter()
+            .map(|name| name.to_ascii_lowercase())
+            .collect(),
+            cors_allow_credentials: source
+                .read("CORS_ALLOW_CREDENTIALS")
+                .map(|value| parse_bool(&value))
+                .unwrap_or(false),
+            cors_max_age_secs: source
+                .parse::<u64>("CORS_MAX_AGE_SECS", problems)
+                .unwrap_or(600),
+            rate_limits: RateLimitSettings::from_source(source, problems),
+            max_attachment_size_bytes: source
+                .parse::<u64>("MAX_ATTACHMENT_SIZE_BYTES", problems)
+                .unwrap_or(DEFAULT_MAX_ATTACHMENT_SIZE_BYTES),
+            otel_exporter_otlp_endpoint: source.read("OTEL_EXPORTER_OTLP_ENDPOINT"),
+            otel_service_name: source
+                .read("OTEL_SERVICE_NAME")
                 .unwrap_or_else(|| "galynx-api".to_string()),
-            otel_sample_ratio: read_env("OTEL_SAMPLE_RATIO")
-                .and_then(|value| value.parse::<f64>().ok())
-                .map(|value| value.clamp(0.0, 1.0))
+            otel_sample_ratio: source
+                .parse::<f64>("OTEL_SAMPLE_RATIO", problems)
                 .unwrap_or(1.0),
-            s3_bucket: read_env("S3_BUCKET"),
-            s3_region: read_env("S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
-            s3_endpoint: read_env("S3_ENDPOINT"),
-            s3_public_endpoint: read_env("S3_PUBLIC_ENDPOINT"),
-            s3_access_key_id: read_env("S3_ACCESS_KEY_ID"),
-            s3_secret_access_key: read_env("S3_SECRET_ACCESS_KEY"),
-            s3_force_path_style: read_env("S3_FORCE_PATH_STYLE")
+            s3_bucket: source.read("S3_BUCKET"),
+            s3_region: source
+                .read("S3_REGION")
+                .unwrap_or_else(|| "us-east-1".to_string()),
+            s3_endpoint: source.read("S3_ENDPOINT"),
+            s3_public_endpoint: source.read("S3_PUBLIC_ENDPOINT"),
+            s3_access_key_id: source.read("S3_ACCESS_KEY_ID"),
+            s3_secret_access_key: source.read("S3_SECRET_ACCESS_KEY"),
+            s3_force_path_style: source
+                .read("S3_FORCE_PATH_STYLE")
                 .map(|value| parse_bool(&value))
                 .unwrap_or(true),
+            moderation_rules_file: source.read("MODERATION_RULES_FILE"),
+            channel_templates_file: source.read("CHANNEL_TEMPLATES_FILE"),
+            moderation_api_url: source.read("MODERATION_API_URL"),
+            moderation_api_token: source.read("MODERATION_API_TOKEN"),
+            moderation_api_action: source
+                .read("MODERATION_API_ACTION")
+                .map(|value| value.to_ascii_lowercase())
+                .unwrap_or_else(|| "flag".to_string()),
+            moderation_api_timeout_ms: source
+                .parse::<u64>("MODERATION_API_TIMEOUT_MS", problems)
+                .unwrap_or(2000),
+            message_encryption_key: source.read("MESSAGE_ENCRYPTION_KEY"),
+            smtp_host: source.read("SMTP_HOST"),
+            smtp_port: source.parse::<u16>("SMTP_PORT", problems).unwrap_or(587),
+            smtp_security: source
+                .read("SMTP_SECURITY")
+                .map(|value| value.to_ascii_lowercase())
+                .unwrap_or_else(|| "starttls".to_string()),
+            smtp_username: source.read("SMTP_USERNAME"),
+            smtp_password: source.read("SMTP_PASSWORD"),
+            email_from: source
+                .read("EMAIL_FROM")
+                .unwrap_or_else(|| "Galynx <no-reply@galynx.local>".to_string()),
+            app_base_url: source
+                .read("APP_BASE_URL")
+                .map(|value| value.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| "http://localhost:3000".to_string()),
+            email_digest_interval_minutes: source
+                .parse::<u64>("EMAIL_DIGEST_INTERVAL_MINUTES", problems)
+                .unwrap_or(24 * 60),
+            presence_away_secs: source
+                .parse::<u64>("PRESENCE_AWAY_SECS", problems)
+                .unwrap_or(300),
+            overrides: ConfigOverrides::default(),
         }
     }
 }
@@ -81,6 +582,194 @@ fn parse_bool(value: &str) -> bool {
     )
 }
 
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct ConfigSource {
+    overrides: HashMap<String, String>,
+    file_values: HashMap<String, String>,
+    secret_values: HashMap<String, String>,
+}
+
+impl ConfigSource {
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let display = path.display().to_string();
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::ReadFile {
+            path: display.clone(),
+            source,
+        })?;
+        let extension = path
+            .extension()
+            .and_then(|value| value.to_str())
+            .map(str::to_ascii_lowercase);
+        let parsed = match extension.as_deref() {
+            Some("toml") => toml::from_str::<Value>(&contents).map_err(|error| error.to_string()),
+            Some("yaml" | "yml") => {
+                serde_yaml::from_str::<Value>(&contents).map_err(|error| error.to_string())
+            }
+            _ => return Err(ConfigError::UnsupportedFormat(display)),
+        }
+        .map_err(|message| ConfigError::ParseFile {
+            path: display,
+            message,
+        })?;
+
+        let mut file_values = HashMap::new();
+        flatten_config_value(None, &parsed, &mut file_values);
+        Ok(Self {
+            file_values,
+            ..Self::default()
+        })
+    }
+
+    fn load_secret_files(&mut self, problems: &mut Vec<String>) {
+        for key in SECRET_FILE_KEYS {
+            let file_key = format!("{key}_FILE");
+            let Some(path) = read_env(&file_key).or_else(|| self.file_value(&file_key)) else {
+                continue;
+            };
+            if read_env(key).is_some() {
+                problems.push(format!("set either {key} or {file_key}, not both"));
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(contents) if !contents.trim().is_empty() => {
+                    self.secret_values
+                        .insert(key.to_string(), contents.trim().to_string());
+                }
+                Ok(_) => problems.push(format!("{file_key} points to an empty file: {path}")),
+                Err(error) => problems.push(format!("failed to read {file_key} ({path}): {error}")),
+            }
+        }
+    }
+
+    fn file_value(&self, key: &str) -> Option<String> {
+        self.file_values
+            .get(&key.to_ascii_lowercase())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn parse<T: FromStr>(&self, key: &str, problems: &mut Vec<String>) -> Option<T> {
+        let value = self.read(key)?;
+        match value.parse::<T>() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                problems.push(format!("{key} has an invalid value: {value:?}"));
+                None
+            }
+        }
+    }
+
+    fn read(&self, key: &str) -> Option<String> {
+        self.overrides
+            .get(key)
+            .cloned()
+            .or_else(|| read_env(key))
+            .or_else(|| self.secret_values.get(key).cloned())
+            .or_else(|| self.file_value(key))
+    }
+}
+
+const REDACTED: &str = "<redacted>";
+
+fn serialize_secret<S: serde::Serializer>(_value: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
+fn serialize_optional_secret<S: serde::Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_str(REDACTED),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_url<S: serde::Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(url) => serializer.serialize_str(&redact_url_credentials(url)),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn redact_url_credentials(url: &str) -> String {
+    let Some(scheme_end) = url.find("://").map(|index| index + 3) else {
+        return url.to_string();
+    };
+    let authority_end = url[scheme_end..]
+        .find('/')
+        .map_or(url.len(), |index| scheme_end + index);
+    let Some(at) = url[scheme_end..authority_end].rfind('@') else {
+        return url.to_string();
+    };
+    let userinfo = &url[scheme_end..scheme_end + at];
+    let user = userinfo.split(':').next().unwrap_or_default();
+    format!(
+        "{}{user}:{REDACTED}{}",
+        &url[..scheme_end],
+        &url[scheme_end + at..]
+    )
+}
+
+fn format_problems(problems: &[String]) -> String {
+    problems
+        .iter()
+        .map(|problem| format!("  - {problem}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn flatten_config_value(prefix: Option<&str>, value: &Value, out: &mut HashMap<String, String>) {
+    let key = prefix.map(str::to_ascii_lowercase);
+    match value {
+        Value::Object(map) => {
+            for (child, child_value) in map {
+                let child_key = match &key {
+                    Some(key) => format!("{key}_{child}"),
+                    None => child.clone(),
+                };
+                flatten_config_value(Some(&child_key), child_value, out);
+            }
+        }
+        Value::Array(items) => {
+            if let Some(key) = key {
+                let joined = items
+                    .iter()
+                    .filter_map(scalar_to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                out.insert(key, joined);
+            }
+        }
+        scalar => {
+            if let (Some(key), Some(value)) = (key, scalar_to_string(scalar)) {
+                out.insert(key, value);
+            }
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
 fn read_env(key: &str) -> Option<String> {
     std::env::var(key)
         .ok()
@@ -88,6 +777,74 @@ fn read_env(key: &str) -> Option<String> {
         .filter(|value| !value.is_empty())
 }
 
+impl RateLimitSettings {
+    fn from_source(source: &ConfigSource, problems: &mut Vec<String>) -> Self {
+        let defaults = Self::default();
+        let mut read =
+            |key: &str, default: u32| source.parse::<u32>(key, problems).unwrap_or(default);
+        Self {
+            auth_per_minute: read("RATE_LIMIT_AUTH_PER_MINUTE", defaults.auth_per_minute),
+            ws_connect_per_minute: read(
+                "RATE_LIMIT_WS_CONNECT_PER_MINUTE",
+                defaults.ws_connect_per_minute,
+            ),
+            ws_command_per_minute: read(
+                "RATE_LIMIT_WS_COMMAND_PER_MINUTE",
+                defaults.ws_command_per_minute,
+            ),
+            message_write_per_minute: read(
+                "RATE_LIMIT_MESSAGE_WRITE_PER_MINUTE",
+                defaults.message_write_per_minute,
+            ),
+            channel_create_per_minute: read(
+                "RATE_LIMIT_CHANNEL_CREATE_PER_MINUTE",
+                defaults.channel_create_per_minute,
+            ),
+            attachment_presign_per_minute: read(
+                "RATE_LIMIT_ATTACHMENT_PRESIGN_PER_MINUTE",
+                defaults.attachment_presign_per_minute,
+            ),
+            invite_per_minute: read("RATE_LIMIT_INVITE_PER_MINUTE", defaults.invite_per_minute),
+        }
+    }
+
+    fn entries(&self) -> [(&'static str, u32); 7] {
+        [
+            ("RATE_LIMIT_AUTH_PER_MINUTE", self.auth_per_minute),
+            (
+                "RATE_LIMIT_WS_CONNECT_PER_MINUTE",
+                self.ws_connect_per_minute,
+            ),
+            (
+                "RATE_LIMIT_WS_COMMAND_PER_MINUTE",
+                self.ws_command_per_minute,
+            ),
+            (
+                "RATE_LIMIT_MESSAGE_WRITE_PER_MINUTE",
+                self.message_write_per_minute,
+            ),
+            (
+                "RATE_LIMIT_CHANNEL_CREATE_PER_MINUTE",
+                self.channel_create_per_minute,
+            ),
+            (
+                "RATE_LIMIT_ATTACHMENT_PRESIGN_PER_MINUTE",
+                self.attachment_presign_per_minute,
+            ),
+            ("RATE_LIMIT_INVITE_PER_MINUTE", self.invite_per_minute),
+        ]
+    }
+}
+
+impl LogFormat {
+    fn from_env_value(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Self::Json,
+            _ => Self::Compact,
+        }
+    }
+}
+
 impl PersistenceBackend {
     fn from_env_value(value: &str) -> Self {
         match value.trim().to_ascii_lowercase().as_str() {
@@ -96,3 +853,281 @@ impl PersistenceBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_config_flattens_tables_and_lists() {
+        let parsed = toml::from_str::<Value>(
+            r#"
+port = 4000
+metrics_enabled = false
+trusted_proxies = ["10.0.0.0/8", "127.0.0.1"]
+
+[s3]
+bucket = "galynx-files"
+force_path_style = true
+"#,
+        )
+        .unwrap();
+        let mut file_values = HashMap::new();
+        flatten_config_value(None, &parsed, &mut file_values);
+        let config = Config::from_source(
+            &ConfigSource {
+                file_values,
+                ..ConfigSource::default()
+            },
+            &mut Vec::new(),
+        );
+
+        assert_eq!(config.port, 4000);
+        assert!(!config.metrics_enabled);
+        assert_eq!(config.trusted_proxies, vec!["10.0.0.0/8", "127.0.0.1"]);
+        assert_eq!(config.s3_bucket.as_deref(), Some("galynx-files"));
+        assert!(config.s3_force_path_style);
+    }
+
+    #[test]
+    fn yaml_config_uses_same_keys() {
+        let parsed = serde_yaml::from_str::<Value>(
+            "persistence_backend: mongo\nmongo_uri: mongodb://db:27017\nlog_format: json\n",
+        )
+        .unwrap();
+        let mut file_values = HashMap::new();
+        flatten_config_value(None, &parsed, &mut file_values);
+        let config = Config::from_source(
+            &ConfigSource {
+                file_values,
+                ..ConfigSource::default()
+            },
+            &mut Vec::new(),
+        );
+
+        assert_eq!(config.persistence_backend, PersistenceBackend::Mongo);
+        assert_eq!(config.mongo_uri.as_deref(), Some("mongodb://db:27017"));
+        assert_eq!(config.log_format, LogFormat::Json);
+    }
+
+    #[test]
+    fn command_line_overrides_take_precedence() {
+        let mut file_values = HashMap::new();
+        file_values.insert("port".to_string(), "4000".to_string());
+        file_values.insert("persistence_backend".to_string(), "mongo".to_string());
+        let mut overrides = ConfigOverrides::default();
+        overrides.set("PORT", 5000);
+        let config = Config::from_source(
+            &ConfigSource {
+                overrides: overrides.values,
+                file_values,
+                ..ConfigSource::default()
+            },
+            &mut Vec::new(),
+        );
+
+        assert_eq!(config.port, 5000);
+        assert_eq!(config.persistence_backend, PersistenceBackend::Mongo);
+    }
+
+    #[test]
+    fn serialized_config_redacts_secrets() {
+        let mut file_values = HashMap::new();
+        file_values.insert(
+            "jwt_secret".to_string(),
+            "super-secret-value-that-is-long-enough".to_string(),
+        );
+        file_values.insert(
+            "mongo_uri".to_string(),
+            "mongodb://root:hunter2@db:27017/?authSource=admin".to_string(),
+        );
+        let config = Config::from_source(
+            &ConfigSource {
+                file_values,
+                ..ConfigSource::default()
+            },
+            &mut Vec::new(),
+        );
+        let rendered = serde_json::to_value(&config).unwrap();
+
+        assert_eq!(rendered["jwt_secret"], "<redacted>");
+        assert_eq!(
+            rendered["mongo_uri"],
+            "mongodb://root:<redacted>@db:27017/?authSource=admin"
+        );
+        assert_eq!(rendered["metrics_token"], Value::Null);
+        assert_eq!(rendered["persistence_backend"], "memory");
+        assert!(!rendered.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn cors_credentials_require_explicit_origins() {
+        let mut file_values = HashMap::new();
+        file_values.insert("cors_allow_credentials".to_string(), "true".to_string());
+        let source = ConfigSource {
+            file_values,
+            ..ConfigSource::default()
+        };
+        let mut problems = Vec::new();
+        let config = Config::from_source(&source, &mut problems);
+        config.validate(&mut problems);
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("CORS_ALLOW_CREDENTIALS"))
+        );
+
+        let mut problems = Vec::new();
+        let mut config = Config::from_source(&source, &mut problems);
+        config.cors_allowed_origins = vec!["https://app.galynx.local".to_string()];
+        config.validate(&mut problems);
+        assert!(problems.iter().all(|problem| !problem.starts_with("CORS")));
+    }
+
+    #[test]
+    fn validation_reports_every_problem() {
+        let parsed = toml::from_str::<Value>(
+            r#"
+port = "not-a-port"
+internal_port = 3000
+grpc_port = 3000
+jwt_secret = "short"
+access_ttl_minutes = 0
+persistence_backend = "mongo"
+s3_access_key_id = "key"
+"#,
+        )
+        .unwrap();
+        let mut file_values = HashMap::new();
+        flatten_config_value(None, &parsed, &mut file_values);
+        let mut problems = Vec::new();
+        let config = Config::from_source(
+            &ConfigSource {
+                file_values,
+                ..ConfigSource::default()
+            },
+            &mut problems,
+        );
+        config.validate_jwt_secret(&mut problems);
+        config.validate(&mut problems);
+
+        assert!(problems.iter().any(|problem| problem.starts_with("PORT")));
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("INTERNAL_PORT"))
+        );
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("GRPC_PORT"))
+        );
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("JWT_SECRET"))
+        );
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("ACCESS_TTL_MINUTES"))
+        );
+        assert!(problems.iter().any(|problem| problem.contains("MONGO_URI")));
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.contains("S3_SECRET_ACCESS_KEY"))
+        );
+    }
+
+    #[test]
+    fn weak_jwt_secrets_are_only_accepted_in_dev_mode() {
+        let resolve = |values: &[(&str, &str)], requires_jwt_secret| {
+            let file_values = values
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            Config::resolve(
+                ConfigSource {
+                    file_values,
+                    ..ConfigSource::default()
+                },
+                ConfigOverrides::default(),
+                requires_jwt_secret,
+                &[],
+            )
+        };
+        let jwt_problems = |values: &[(&str, &str)]| match resolve(values, true) {
+            Err(ConfigError::Invalid(problems)) => problems
+                .into_iter()
+                .filter(|problem| problem.starts_with("JWT_SECRET"))
+                .count(),
+            _ => 0,
+        };
+
+        assert_eq!(jwt_problems(&[]), 1);
+        assert_eq!(jwt_problems(&[("jwt_secret", "short-but-not-default")]), 1);
+        assert_eq!(jwt_problems(&[("dev_mode", "true")]), 0);
+        assert_eq!(
+            jwt_problems(&[("dev_mode", "true"), ("jwt_secret", "short")]),
+            0
+        );
+        assert_eq!(
+            jwt_problems(&[("jwt_secret", "0123456789abcdef0123456789abcdef")]),
+            0
+        );
+        // galynx-bootstrap never signs tokens, so the migrate job runs without one.
+        assert!(resolve(&[("persistence_backend", "memory")], false).is_ok());
+    }
+
+    #[test]
+    fn external_checks_join_the_validation_report() {
+        let mut file_values = HashMap::new();
+        file_values.insert("port".to_string(), "not-a-port".to_string());
+        file_values.insert("dev_mode".to_string(), "true".to_string());
+        let result = Config::resolve(
+            ConfigSource {
+                file_values,
+                ..ConfigSource::default()
+            },
+            ConfigOverrides::default(),
+            true,
+            &[|_, problems| problems.push("MODERATION_RULES_FILE is broken".to_string())],
+        );
+
+        let Err(ConfigError::Invalid(problems)) = result else {
+            panic!("configuration should be invalid");
+        };
+        assert!(problems.iter().any(|problem| problem.starts_with("PORT")));
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("MODERATION"))
+        );
+    }
+
+    #[test]
+    fn secret_files_are_read_and_trimmed() {
+        let path = std::env::temp_dir().join(format!("galynx-jwt-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "super-secret-from-file\n").unwrap();
+        let mut source = ConfigSource::default();
+        source
+            .file_values
+            .insert("jwt_secret_file".to_string(), path.display().to_string());
+        source.file_values.insert(
+            "metrics_token_file".to_string(),
+            "/nonexistent/galynx-metrics-token".to_string(),
+        );
+
+        let mut problems = Vec::new();
+        source.load_secret_files(&mut problems);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            source.read("JWT_SECRET").as_deref(),
+            Some("super-secret-from-file")
+        );
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("METRICS_TOKEN_FILE"));
+    }
+}
@@ -0,0 +1,591 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::AuthContext,
+    channels::{ChannelService, CreateMessageRequest, parse_cursor},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    idempotency, moderation, mutes, outbox, realtime,
+    storage::{DmConversationRecordStore, DmMessageRecordStore, Storage},
+};
+
+/// Other participants a conversation may have besides its creator.
+const MAX_DM_RECIPIENTS: usize = 8;
+
+#[derive(Clone)]
+pub struct DmService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OpenDmRequest {
+    /// Other participants; the caller is always added.
+    pub user_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DmConversationResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Every participant, including the caller.
+    pub member_ids: Vec<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub last_message_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DmConversationListResponse {
+    pub items: Vec<DmConversationResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DmMessageResponse {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub workspace_id: Uuid,
+    pub sender_id: Uuid,
+    pub body_md: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DmMessageListResponse {
+    pub items: Vec<DmMessageResponse>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DmMessageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/dms", get(list_dms).post(open_dm))
+        .route(
+            "/api/v1/dms/:id/messages",
+            get(list_dm_messages).post(create_dm_message),
+        )
+}
+
+impl From<DmConversationRecordStore> for DmConversationResponse {
+    fn from(conversation: DmConversationRecordStore) -> Self {
+        Self {
+            id: conversation.id,
+            workspace_id: conversation.workspace_id,
+            member_ids: conversation.member_ids,
+            created_by: conversation.created_by,
+            created_at: conversation.created_at,
+            last_message_at: conversation.last_message_at,
+        }
+    }
+}
+
+impl From<DmMessageRecordStore> for DmMessageResponse {
+    fn from(message: DmMessageRecordStore) -> Self {
+        Self {
+            id: message.id,
+            conversation_id: message.conversation_id,
+            workspace_id: message.workspace_id,
+            sender_id: message.sender_id,
+            body_md: message.body_md,
+            created_at: message.created_at,
+        }
+    }
+}
+
+impl DmService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Returns the caller's conversation with exactly `user_ids`, creating it
+    /// when there is none yet; the flag is true when it was created.
+    pub async fn open_conversation(
+        &self,
+        context: &AuthContext,
+        payload: OpenDmRequest,
+    ) -> ApiResult<(DmConversationResponse, bool)> {
+        let mut member_ids = payload.user_ids;
+        member_ids.retain(|user_id| *user_id != context.user_id);
+        member_ids.sort();
+        member_ids.dedup();
+        if !(1..=MAX_DM_RECIPIENTS).contains(&member_ids.len()) {
+            let mut errors = FieldErrors::default();
+            errors.add(
+                "user_ids",
+                format!("must list between 1 and {MAX_DM_RECIPIENTS} other users"),
+            );
+            errors.into_result()?;
+        }
+        for user_id in &member_ids {
+            if self
+                .storage
+                .get_membership_role(context.workspace_id, *user_id)
+                .await
+                .is_none()
+            {
+                return Err(ApiError::NotFound(format!(
+                    "user {user_id} is not a member of this workspace"
+                )));
+            }
+        }
+        member_ids.push(context.user_id);
+        member_ids.sort();
+
+        let (conversation, created) = self
+            .storage
+            .insert_dm_conversation(DmConversationRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id: context.workspace_id,
+                member_ids,
+                created_by: context.user_id,
+                created_at: Utc::now().timestamp_millis(),
+                last_message_at: None,
+            })
+            .await;
+        Ok((conversation.into(), created))
+    }
+
+    /// The caller's conversations, most recently active first.
+    pub async fn list_conversations(&self, context: &AuthContext) -> Vec<DmConversationResponse> {
+        let mut conversations = self
+            .storage
+            .list_dm_conversations(context.workspace_id, context.user_id)
+            .await;
+        conversations.sort_by(|a, b| {
+            let activity = |conversation: &DmConversationRecordStore| {
+                conversation
+                    .last_message_at
+                    .unwrap_or(conversation.created_at)
+            };
+            activity(b).cmp(&activity(a)).then(a.id.cmp(&b.id))
+        });
+        conversations.into_iter().map(Into::into).collect()
+    }
+
+    /// Conversations are private to their participants; anyone else, admins
+    /// included, gets a 404.
+    async fn participant_conversation(
+        &self,
+        context: &AuthContext,
+        conversation_id: Uuid,
+    ) -> ApiResult<DmConversationRecordStore> {
+        self.storage
+            .get_dm_conversation(conversation_id)
+            .await
+            .filter(|conversation| {
+                conversation.workspace_id == context.workspace_id
+                    && conversation.member_ids.contains(&context.user_id)
+            })
+            .ok_or_else(|| ApiError::NotFound("conversation not found".to_string()))
+    }
+
+    pub async fn list_messages(
+        &self,
+        context: &AuthContext,
+        conversation_id: Uuid,
+        query: &DmMessageQuery,
+    ) -> ApiResult<DmMessageListResponse> {
+        self.participant_conversation(context, conversation_id)
+            .await?;
+        let limit = query.limit.unwrap_or(50).clamp(1, 100);
+        let before = query
+            .cursor
+            .as_deref()
+            .map(parse_cursor)
+            .transpose()
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
+        let mut found = self
+            .storage
+            .list_dm_messages(conversation_id, before, limit + 1)
+            .await;
+        let has_more = found.len() > limit;
+        found.truncate(limit);
+        let next_cursor = found
+            .last()
+            .filter(|_| has_more)
+            .map(|message| format!("{}:{}", message.created_at, message.id.as_u128()));
+        Ok(DmMessageListResponse {
+            items: found.into_iter().map(Into::into).collect(),
+            next_cursor,
+        })
+    }
+
+    /// Stores a message from the caller and queues `DM_MESSAGE_CREATED` for
+    /// every participant in the realtime outbox with it.
+    pub async fn create_message(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        conversation_id: Uuid,
+        payload: CreateMessageRequest,
+    ) -> ApiResult<DmMessageResponse> {
+        mutes::ensure_not_muted(&self.storage, context.workspace_id, context.user_id).await?;
+        let conversation = self
+            .participant_conversation(context, conversation_id)
+            .await?;
+        let body = payload.body_md.trim().to_string();
+        if body.is_empty() {
+            let mut errors = FieldErrors::default();
+            errors.add("body_md", "must not be empty");
+            errors.into_result()?;
+        }
+        channels
+            .check_message_length(context.workspace_id, &body)
+            .await?;
+
+        let message = DmMessageRecordStore {
+            id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            conversation_id,
+            workspace_id: context.workspace_id,
+            sender_id: context.user_id,
+            body_md: body,
+            created_at: Utc::now().timestamp_millis(),
+        };
+        let response = DmMessageResponse::from(message.clone());
+        let payload = serde_json::to_value(&response).unwrap_or_default();
+        let events = conversation
+            .member_ids
+            .iter()
+            .map(|member_id| {
+                outbox::record(
+                    context.workspace_id,
+                    &realtime::make_user_event(
+                        "DM_MESSAGE_CREATED",
+                        context.workspace_id,
+                        *member_id,
+                        payload.clone(),
+                    ),
+                )
+            })
+            .collect();
+        self.storage
+            .write_dm_message_with_events(message, events)
+            .await?;
+        Ok(response)
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/dms",
+    operation_id = "open_dm",
+    tag = "dms",
+    request_body = OpenDmRequest,
+    responses(
+        (status = 200, description = "Existing conversation with these users", body = DmConversationResponse),
+        (status = 201, description = "Conversation created", body = DmConversationResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "User not in the workspace", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn open_dm(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<OpenDmRequest>,
+) -> ApiResult<Response> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let (conversation, created) = state.dms.open_conversation(&context, payload).await?;
+    if !created {
+        return Ok(Json(conversation).into_response());
+    }
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "DM_CREATED",
+            "dm",
+            Some(conversation.id.to_string()),
+            json!({ "member_ids": conversation.member_ids }),
+        )
+        .await;
+    for member_id in &conversation.member_ids {
+        state
+            .realtime
+            .emit(
+                context.workspace_id,
+                realtime::make_user_event(
+                    "DM_CREATED",
+                    context.workspace_id,
+                    *member_id,
+                    serde_json::to_value(&conversation).unwrap_or_default(),
+                ),
+            )
+            .await;
+    }
+    Ok((StatusCode::CREATED, Json(conversation)).into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/dms",
+    operation_id = "list_dms",
+    tag = "dms",
+    responses(
+        (status = 200, description = "Caller's conversations, most recently active first", body = DmConversationListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_dms(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<DmConversationListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(DmConversationListResponse {
+        items: state.dms.list_conversations(&context).await,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/dms/{id}/messages",
+    operation_id = "list_dm_messages",
+    tag = "dms",
+    params(DmMessageQuery),
+    responses(
+        (status = 200, description = "Messages page, newest first", body = DmMessageListResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Conversation not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_dm_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(conversation_id): Path<Uuid>,
+    Query(query): Query<DmMessageQuery>,
+) -> ApiResult<Json<DmMessageListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(
+        state
+            .dms
+            .list_messages(&context, conversation_id, &query)
+            .await?,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/dms/{id}/messages",
+    operation_id = "create_dm_message",
+    tag = "dms",
+    request_body = CreateMessageRequest,
+    responses(
+        (status = 201, description = "Message sent", body = DmMessageResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Caller is muted", body = ErrorResponse),
+        (status = 404, description = "Conversation not found", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key in use", body = ErrorResponse),
+        (status = 422, description = "Empty, too long or blocked body, or Idempotency-Key reused", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_dm_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(conversation_id): Path<Uuid>,
+    Json(payload): Json<CreateMessageRequest>,
+) -> ApiResult<Response> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mut idempotency = idempotency::scope(
+        &headers,
+        &context,
+        &format!("create_dm_message:{conversation_id}"),
+        &payload,
+    )?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
+    state
+        .rate_limit
+        .check_message_write(context.user_id)
+        .await?;
+    let moderated =
+        moderation::moderate_dm_message(&state, &context, conversation_id, payload.body_md).await?;
+    let item = state
+        .dms
+        .create_message(
+            &state.channels,
+            &context,
+            conversation_id,
+            CreateMessageRequest {
+                body_md: moderated.body,
+            },
+        )
+        .await?;
+    state.metrics.record_message_created(context.workspace_id);
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "DM_MESSAGE_CREATED",
+            "dm_message",
+            Some(item.id.to_string()),
+            json!({ "conversation_id": conversation_id }),
+        )
+        .await;
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::CREATED, &item)
+        .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::WorkspaceRole, storage::PersistenceBackend};
+
+    #[tokio::test]
+    async fn conversations_are_unique_per_member_set_and_private() {
+        let workspace_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let channels = ChannelService::new(storage.clone(), workspace_id, Uuid::new_v4());
+        let dms = DmService::new(storage.clone());
+        let member = |user_id| AuthContext {
+            user_id,
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let (alice, bob, carol) = (
+            member(Uuid::new_v4()),
+            member(Uuid::new_v4()),
+            member(Uuid::new_v4()),
+        );
+        for context in [&alice, &bob, &carol] {
+            storage
+                .put_membership_role(workspace_id, context.user_id, "member")
+                .await;
+        }
+
+        let (opened, created) = dms
+            .open_conversation(
+                &alice,
+                OpenDmRequest {
+                    user_ids: vec![bob.user_id, bob.user_id, alice.user_id],
+                },
+            )
+            .await
+            .expect("opened");
+        assert!(created);
+        assert_eq!(opened.member_ids.len(), 2);
+        let (again, created) = dms
+            .open_conversation(
+                &bob,
+                OpenDmRequest {
+                    user_ids: vec![alice.user_id],
+                },
+            )
+            .await
+            .expect("reopened");
+        assert!(!created);
+        assert_eq!(again.id, opened.id);
+        assert!(matches!(
+            dms.open_conversation(
+                &alice,
+                OpenDmRequest {
+                    user_ids: vec![Uuid::new_v4()],
+                },
+            )
+            .await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        for body in ["hi", "there", "bob"] {
+            dms.create_message(
+                &channels,
+                &alice,
+                opened.id,
+                CreateMessageRequest {
+                    body_md: body.to_string(),
+                },
+            )
+            .await
+            .expect("sent");
+        }
+        let now = Utc::now().timestamp_millis();
+        let queued = storage.claim_outbox_events(now, now + 1_000, 10).await;
+        assert_eq!(queued.len(), 6);
+        let event: realtime::WsEventEnvelope =
+            serde_json::from_str(&queued[0].event_json).expect("event should round-trip");
+        assert_eq!(event.event_type, "DM_MESSAGE_CREATED");
+        assert!(event.recipient_id.is_some());
+        let page = dms
+            .list_messages(
+                &bob,
+                opened.id,
+                &DmMessageQuery {
+                    cursor: None,
+                    limit: Some(2),
+                },
+            )
+            .await
+            .expect("listed");
+        assert_eq!(page.items.len(), 2);
+        let rest = dms
+            .list_messages(
+                &bob,
+                opened.id,
+                &DmMessageQuery {
+                    cursor: page.next_cursor.clone(),
+                    limit: Some(2),
+                },
+            )
+            .await
+            .expect("second page");
+        assert_eq!(rest.items.len(), 1);
+        assert_eq!(rest.next_cursor, None);
+        let listed = dms.list_conversations(&bob).await;
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_message_at.is_some());
+
+        assert!(dms.list_conversations(&carol).await.is_empty());
+        assert!(matches!(
+            dms.create_message(
+                &channels,
+                &carol,
+                opened.id,
+                CreateMessageRequest {
+                    body_md: "let me in".to_string(),
+                },
+            )
+            .await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
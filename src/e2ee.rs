@@ -0,0 +1,441 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, put},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::AuthContext,
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    realtime,
+    storage::{ChannelKeyRecordStore, ChannelRecordStore, Storage, UserPublicKeyRecordStore},
+};
+
+/// Bodies of `e2ee` channels are `e2ee:<key_epoch>:<base64 ciphertext>`.
+const ENVELOPE_PREFIX: &str = "e2ee:";
+const MAX_KEY_CHARS: usize = 4_096;
+const MAX_KEYS_PER_REQUEST: usize = 1_000;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdatePublicKeyRequest {
+    /// Base64 public key; the server never interprets it.
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PublicKeyResponse {
+    pub user_id: Uuid,
+    pub public_key: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct E2eeMemberResponse {
+    pub user_id: Uuid,
+    pub public_key: Option<String>,
+    /// Whether someone already wrapped the current epoch's key for this member.
+    pub has_current_key: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WrappedKeyResponse {
+    pub key_epoch: i64,
+    pub wrapped_key: String,
+    pub wrapped_by: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelKeysResponse {
+    pub channel_id: Uuid,
+    pub key_epoch: i64,
+    pub members: Vec<E2eeMemberResponse>,
+    /// The caller's wrapped keys for every epoch they were given, oldest first.
+    pub wrapped_keys: Vec<WrappedKeyResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WrappedKeyInput {
+    pub user_id: Uuid,
+    pub wrapped_key: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DistributeChannelKeysRequest {
+    /// Must equal the channel's current `key_epoch`.
+    pub key_epoch: i64,
+    pub keys: Vec<WrappedKeyInput>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/me/public-key", put(update_public_key))
+        .route("/api/v1/channels/:id/e2ee", get(get_channel_keys))
+        .route(
+            "/api/v1/channels/:id/e2ee/keys",
+            put(distribute_channel_keys),
+        )
+}
+
+/// Rejects bodies that are not an envelope for the channel's current key
+/// epoch; every body is accepted in channels without `e2ee`.
+pub(crate) fn check_envelope(channel: &ChannelRecordStore, body: &str) -> ApiResult<()> {
+    if !channel.e2ee {
+        return Ok(());
+    }
+    let envelope = body
+        .strip_prefix(ENVELOPE_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .and_then(|(epoch, ciphertext)| Some((epoch.parse::<i64>().ok()?, ciphertext)))
+        .filter(|(_, ciphertext)| is_base64(ciphertext));
+    let Some((key_epoch, _)) = envelope else {
+        let mut errors = FieldErrors::default();
+        errors.add(
+            "body_md",
+            "must be an e2ee:<key_epoch>:<base64> envelope in end-to-end encrypted channels",
+        );
+        return errors.into_result();
+    };
+    if key_epoch != channel.key_epoch {
+        return Err(ApiError::Coded(
+            ErrorCode::StaleKeyEpoch,
+            format!(
+                "message must be encrypted for key epoch {}",
+                channel.key_epoch
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Features that read message bodies (moderation, keyword alerts) skip
+/// channels the server cannot decrypt.
+pub(crate) async fn is_encrypted_channel(storage: &Storage, channel_id: Uuid) -> bool {
+    storage
+        .get_channel(&channel_id)
+        .await
+        .is_some_and(|channel| channel.e2ee)
+}
+
+fn is_base64(value: &str) -> bool {
+    STANDARD.decode(value).is_ok_and(|bytes| !bytes.is_empty())
+}
+
+fn check_key(errors: &mut FieldErrors, field: &str, value: &str) {
+    if value.len() > MAX_KEY_CHARS {
+        errors.add(field, format!("must be at most {MAX_KEY_CHARS} characters"));
+    } else if !is_base64(value) {
+        errors.add(field, "must be non-empty base64");
+    }
+}
+
+/// Looks up an `e2ee` channel the caller is a member of. Workspace admins can
+/// read private channels but only members take part in key distribution.
+async fn member_channel(
+    state: &AppState,
+    context: &AuthContext,
+    channel_id: Uuid,
+) -> ApiResult<ChannelRecordStore> {
+    let channel = state
+        .storage
+        .get_channel(&channel_id)
+        .await
+        .filter(|channel| channel.workspace_id == context.workspace_id)
+        .ok_or_else(|| ApiError::NotFound("channel not found".to_string()))?;
+    if !channel.e2ee {
+        return Err(ApiError::BadRequest(
+            "channel is not end-to-end encrypted".to_string(),
+        ));
+    }
+    if !state
+        .storage
+        .is_channel_member(channel.id, context.user_id)
+        .await
+    {
+        return Err(ApiError::Forbidden(
+            "only channel members can access channel keys".to_string(),
+        ));
+    }
+    Ok(channel)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/me/public-key",
+    operation_id = "update_public_key",
+    tag = "e2ee",
+    request_body = UpdatePublicKeyRequest,
+    responses(
+        (status = 200, description = "Public key stored", body = PublicKeyResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_public_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdatePublicKeyRequest>,
+) -> ApiResult<Json<PublicKeyResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let public_key = payload.public_key.trim().to_string();
+    let mut errors = FieldErrors::default();
+    check_key(&mut errors, "public_key", &public_key);
+    errors.into_result()?;
+
+    let record = UserPublicKeyRecordStore {
+        user_id: context.user_id,
+        public_key,
+        updated_at: Utc::now().timestamp_millis(),
+    };
+    state.storage.put_user_public_key(record.clone()).await;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "USER_PUBLIC_KEY_UPDATED",
+            "user",
+            Some(context.user_id.to_string()),
+            json!({}),
+        )
+        .await;
+    Ok(Json(PublicKeyResponse {
+        user_id: record.user_id,
+        public_key: record.public_key,
+        updated_at: record.updated_at,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/channels/{id}/e2ee",
+    operation_id = "get_channel_keys",
+    tag = "e2ee",
+    responses(
+        (status = 200, description = "Key epoch, member public keys and the caller's wrapped keys", body = ChannelKeysResponse),
+        (status = 400, description = "Channel is not end-to-end encrypted", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not a channel member", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_channel_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+) -> ApiResult<Json<ChannelKeysResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let channel = member_channel(&state, &context, channel_id).await?;
+
+    let mut member_ids = state.storage.list_channel_members(channel.id).await;
+    member_ids.sort_unstable();
+    member_ids.dedup();
+    let mut members = Vec::with_capacity(member_ids.len());
+    for user_id in member_ids {
+        let has_current_key = state
+            .storage
+            .list_channel_keys(channel.id, user_id)
+            .await
+            .iter()
+            .any(|key| key.key_epoch == channel.key_epoch);
+        members.push(E2eeMemberResponse {
+            user_id,
+            public_key: state
+                .storage
+                .get_user_public_key(user_id)
+                .await
+                .map(|key| key.public_key),
+            has_current_key,
+        });
+    }
+    let mut wrapped_keys = state
+        .storage
+        .list_channel_keys(channel.id, context.user_id)
+        .await
+        .into_iter()
+        .map(|key| WrappedKeyResponse {
+            key_epoch: key.key_epoch,
+            wrapped_key: key.wrapped_key,
+            wrapped_by: key.wrapped_by,
+        })
+        .collect::<Vec<_>>();
+    wrapped_keys.sort_by_key(|key| key.key_epoch);
+
+    Ok(Json(ChannelKeysResponse {
+        channel_id: channel.id,
+        key_epoch: channel.key_epoch,
+        members,
+        wrapped_keys,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/channels/{id}/e2ee/keys",
+    operation_id = "distribute_channel_keys",
+    tag = "e2ee",
+    request_body = DistributeChannelKeysRequest,
+    responses(
+        (status = 204, description = "Wrapped keys stored"),
+        (status = 400, description = "Channel is not end-to-end encrypted", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not a channel member", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "Key epoch is no longer current", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn distribute_channel_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<DistributeChannelKeysRequest>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let channel = member_channel(&state, &context, channel_id).await?;
+
+    let mut errors = FieldErrors::default();
+    if payload.keys.is_empty() || payload.keys.len() > MAX_KEYS_PER_REQUEST {
+        errors.add(
+            "keys",
+            format!("must contain between 1 and {MAX_KEYS_PER_REQUEST} keys"),
+        );
+    }
+    let members = state.storage.list_channel_members(channel.id).await;
+    for (index, key) in payload.keys.iter().enumerate() {
+        if !members.contains(&key.user_id) {
+            errors.add(
+                &format!("keys[{index}].user_id"),
+                "must be a channel member",
+            );
+        }
+        check_key(
+            &mut errors,
+            &format!("keys[{index}].wrapped_key"),
+            &key.wrapped_key,
+        );
+    }
+    errors.into_result()?;
+    if payload.key_epoch != channel.key_epoch {
+        return Err(ApiError::Coded(
+            ErrorCode::StaleKeyEpoch,
+            format!("current key epoch is {}", channel.key_epoch),
+        ));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let mut user_ids = Vec::with_capacity(payload.keys.len());
+    for key in payload.keys {
+        user_ids.push(key.user_id);
+        state
+            .storage
+            .put_channel_key(ChannelKeyRecordStore {
+                channel_id: channel.id,
+                key_epoch: channel.key_epoch,
+                user_id: key.user_id,
+                wrapped_key: key.wrapped_key,
+                wrapped_by: context.user_id,
+                created_at: now,
+            })
+            .await;
+    }
+    let details = json!({ "key_epoch": channel.key_epoch, "user_ids": user_ids });
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CHANNEL_KEYS_DISTRIBUTED",
+            "channel",
+            Some(channel.id.to_string()),
+            details.clone(),
+        )
+        .await;
+    realtime::emit_channel_event(
+        &state,
+        context.workspace_id,
+        realtime::make_event(
+            "CHANNEL_KEYS_DISTRIBUTED",
+            context.workspace_id,
+            Some(channel.id),
+            None,
+            details,
+        ),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PersistenceBackend;
+
+    #[tokio::test]
+    async fn membership_changes_advance_the_key_epoch_checked_by_envelopes() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let (owner_id, member_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let channel_id = Uuid::new_v4();
+        storage
+            .insert_channel(ChannelRecordStore {
+                id: channel_id,
+                workspace_id: Uuid::new_v4(),
+                name: "vault".to_string(),
+                is_private: true,
+                topic: None,
+                icon_emoji: None,
+                color: None,
+                e2ee: true,
+                key_epoch: 0,
+                created_by: owner_id,
+                created_at: 0,
+            })
+            .await;
+        storage.add_channel_member(channel_id, owner_id).await;
+        storage.add_channel_member(channel_id, owner_id).await;
+        storage.add_channel_member(channel_id, member_id).await;
+        storage.remove_channel_member(channel_id, member_id).await;
+        let channel = storage.get_channel(&channel_id).await.expect("channel");
+        assert_eq!(channel.key_epoch, 3);
+
+        assert!(check_envelope(&channel, "e2ee:3:c2VjcmV0").is_ok());
+        assert!(matches!(
+            check_envelope(&channel, "e2ee:2:c2VjcmV0"),
+            Err(ApiError::Coded(ErrorCode::StaleKeyEpoch, _))
+        ));
+        for invalid in [
+            "plain text",
+            "e2ee:3:",
+            "e2ee:x:c2VjcmV0",
+            "e2ee:3:not base64!",
+        ] {
+            assert!(matches!(
+                check_envelope(&channel, invalid),
+                Err(ApiError::Validation(_))
+            ));
+        }
+        let plain = ChannelRecordStore {
+            e2ee: false,
+            ..channel
+        };
+        assert!(check_envelope(&plain, "plain text").is_ok());
+    }
+}
@@ -0,0 +1,472 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use rand::RngCore;
+use tokio::{sync::mpsc, time::sleep};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth,
+    config::Config,
+    observability::AppMetrics,
+    storage::{AuthUserRecordStore, EmailTokenRecordStore, Storage},
+};
+
+const QUEUE_CAPACITY: usize = 1_000;
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+const SMTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Messages the API sends; each renders to a plain-text subject and body.
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+    Invite {
+        workspace_name: String,
+        inviter_name: String,
+    },
+    PasswordReset {
+        token: String,
+        expires_minutes: i64,
+    },
+    EmailVerification {
+        token: String,
+    },
+    /// `items` previews the oldest of `unread_count` notifications.
+    Digest {
+        workspace_name: String,
+        unread_count: usize,
+        items: Vec<String>,
+    },
+    NewDeviceLogin {
+        ip: String,
+        user_agent: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailTemplate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Invite { .. } => "invite",
+            Self::PasswordReset { .. } => "password_reset",
+            Self::EmailVerification { .. } => "email_verification",
+            Self::Digest { .. } => "digest",
+            Self::NewDeviceLogin { .. } => "new_device_login",
+        }
+    }
+
+    /// The same email with its one-time tokens masked, for logging.
+    pub fn redacted(&self) -> Self {
+        const REDACTED: &str = "<redacted>";
+        match self {
+            Self::PasswordReset {
+                expires_minutes, ..
+            } => Self::PasswordReset {
+                token: REDACTED.to_string(),
+                expires_minutes: *expires_minutes,
+            },
+            Self::EmailVerification { .. } => Self::EmailVerification {
+                token: REDACTED.to_string(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    pub fn render(&self, recipient_name: &str, app_base_url: &str) -> RenderedEmail {
+        let greeting = format!("Hi {recipient_name},");
+        let (subject, text) = match self {
+            Self::Invite {
+                workspace_name,
+                inviter_name,
+            } => (
+                format!("You have been added to {workspace_name} on Galynx"),
+                format!(
+                    "{inviter_name} added you to the {workspace_name} workspace.\n\n\
+                     Sign in at {app_base_url}/login to get started."
+                ),
+            ),
+            Self::PasswordReset {
+                token,
+                expires_minutes,
+            } => (
+                "Reset your Galynx password".to_string(),
+                format!(
+                    "Someone asked to reset the password of your Galynx account. Choose a new \
+                     one at {app_base_url}/reset-password?token={token}\n\n\
+                     The link expires in {expires_minutes} minutes. If you did not ask for it, \
+                     ignore this email and your password stays the same."
+                ),
+            ),
+            Self::EmailVerification { token } => (
+                "Verify your Galynx email address".to_string(),
+                format!(
+                    "Confirm this address for your Galynx account at \
+                     {app_base_url}/verify-email?token={token}"
+                ),
+            ),
+            Self::Digest {
+                workspace_name,
+                unread_count,
+                items,
+            } => {
+                let lines = items
+                    .iter()
+                    .map(|item| format!("- {item}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (
+                    format!(
+                        "{unread_count} unread notification{} in {workspace_name}",
+                        if *unread_count == 1 { "" } else { "s" }
+                    ),
+                    format!(
+                        "While you were away:\n\n{lines}\n\n\
+                         Catch up at {app_base_url}/notifications"
+                    ),
+                )
+            }
+            Self::NewDeviceLogin { ip, user_agent } => (
+                "New sign-in to your Galynx account".to_string(),
+                format!(
+                    "Your account just signed in from a device we have not seen before:\n\n\
+                     Address: {ip}\nBrowser: {user_agent}\n\n\
+                     If this was not you, end the session at {app_base_url}/settings/sessions \
+                     and change your password."
+                ),
+            ),
+        };
+        RenderedEmail {
+            subject,
+            body: format!("{greeting}\n\n{text}\n\n-- \nGalynx\n"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OutgoingEmail {
+    to_email: String,
+    to_name: String,
+    template: EmailTemplate,
+}
+
+/// Where queued emails end up: an SMTP relay, or the log when `SMTP_HOST` is
+/// unset so development setups never send real mail. The log only ever sees
+/// the redacted rendering, so reset and verification links stay out of it.
+enum EmailTransport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    Log,
+}
+
+impl EmailTransport {
+    fn from_config(config: &Config) -> Result<Self, String> {
+        let Some(host) = config.smtp_host.as_deref() else {
+            return Ok(Self::Log);
+        };
+        let builder = match config.smtp_security.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        }
+        .map_err(|error| format!("invalid SMTP_HOST {host:?}: {error}"))?;
+        let mut builder = builder.port(config.smtp_port).timeout(Some(SMTP_TIMEOUT));
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(Self::Smtp(builder.build()))
+    }
+
+    async fn send(&self, message: Message, redacted: &RenderedEmail) -> Result<(), String> {
+        match self {
+            Self::Smtp(transport) => transport
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|error| error.to_string()),
+            Self::Log => {
+                let to = message
+                    .envelope()
+                    .to()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                info!(
+                    target: "galynx_api::email",
+                    to = %to,
+                    subject = %redacted.subject,
+                    "email not sent (no SMTP_HOST):\n{}",
+                    redacted.body
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Queues templated emails and delivers them in the background, retrying
+/// transient SMTP failures a few times before giving up.
+pub struct EmailService {
+    queue: mpsc::Sender<OutgoingEmail>,
+    metrics: Arc<AppMetrics>,
+}
+
+impl EmailService {
+    /// Starts the delivery worker, so it must run inside the Tokio runtime.
+    pub fn from_config(config: &Config, metrics: Arc<AppMetrics>) -> Result<Self, String> {
+        let transport = EmailTransport::from_config(config)?;
+        let from = config
+            .email_from
+            .parse::<Mailbox>()
+            .map_err(|error| format!("invalid EMAIL_FROM: {error}"))?;
+        let (queue, rx) = mpsc::channel(QUEUE_CAPACITY);
+        spawn_delivery(
+            transport,
+            from,
+            config.app_base_url.clone(),
+            rx,
+            metrics.clone(),
+        );
+        Ok(Self { queue, metrics })
+    }
+
+    /// Reports an `SMTP_HOST` the transport cannot be built for at startup.
+    pub(crate) fn check_config(config: &Config, problems: &mut Vec<String>) {
+        if let Err(problem) = EmailTransport::from_config(config) {
+            problems.push(problem);
+        }
+    }
+
+    /// Queues `template` for `to_email` without waiting for delivery; the
+    /// email is dropped when the queue is full.
+    pub fn send(&self, to_email: &str, to_name: &str, template: EmailTemplate) {
+        let email = OutgoingEmail {
+            to_email: to_email.to_string(),
+            to_name: to_name.to_string(),
+            template,
+        };
+        if let Err(error) = self.queue.try_send(email) {
+            let template = match &error {
+                mpsc::error::TrySendError::Full(email)
+                | mpsc::error::TrySendError::Closed(email) => email.template.name(),
+            };
+            self.metrics.record_email(template, "dropped");
+            warn!("email queue unavailable, dropping {} email", template);
+        }
+    }
+}
+
+fn spawn_delivery(
+    transport: EmailTransport,
+    from: Mailbox,
+    app_base_url: String,
+    mut rx: mpsc::Receiver<OutgoingEmail>,
+    metrics: Arc<AppMetrics>,
+) {
+    tokio::spawn(async move {
+        while let Some(email) = rx.recv().await {
+            let template = email.template.name();
+            let outcome = match deliver(&transport, &from, &app_base_url, &email).await {
+                Ok(()) => "sent",
+                Err(error) => {
+                    warn!(
+                        target: "galynx_api::email",
+                        template,
+                        error = %error,
+                        "email delivery gave up"
+                    );
+                    "failed"
+                }
+            };
+            metrics.record_email(template, outcome);
+        }
+    });
+}
+
+async fn deliver(
+    transport: &EmailTransport,
+    from: &Mailbox,
+    app_base_url: &str,
+    email: &OutgoingEmail,
+) -> Result<(), String> {
+    let rendered = email.template.render(&email.to_name, app_base_url);
+    let to = Mailbox::new(
+        Some(email.to_name.clone()),
+        email
+            .to_email
+            .parse()
+            .map_err(|error| format!("invalid recipient address: {error}"))?,
+    );
+    let message = Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(rendered.subject.clone())
+        .header(ContentType::TEXT_PLAIN)
+        .body(rendered.body)
+        .map_err(|error| format!("failed to build email: {error}"))?;
+    let redacted = email
+        .template
+        .redacted()
+        .render(&email.to_name, app_base_url);
+
+    let mut attempt = 1;
+    loop {
+        match transport.send(message.clone(), &redacted).await {
+            Ok(()) => return Ok(()),
+            Err(error) if attempt >= MAX_ATTEMPTS => return Err(error),
+            Err(error) => {
+                warn!(
+                    "email delivery failed (attempt {attempt}/{MAX_ATTEMPTS}), retrying: {}",
+                    error
+                );
+                sleep(RETRY_DELAY * attempt).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Tells a user added to `workspace_id` by `inviter_id` where to sign in.
+pub(crate) async fn send_invite(
+    state: &AppState,
+    inviter_id: Uuid,
+    workspace_id: Uuid,
+    to_email: &str,
+    to_name: &str,
+) {
+    let Some(workspace) = state.storage.get_workspace(workspace_id).await else {
+        return;
+    };
+    let inviter_name = state
+        .storage
+        .get_auth_user_by_id(inviter_id)
+        .await
+        .map_or_else(|| "A workspace admin".to_string(), |user| user.name);
+    state.email.send(
+        to_email,
+        to_name,
+        EmailTemplate::Invite {
+            workspace_name: workspace.name,
+            inviter_name,
+        },
+    );
+}
+
+/// Stores a fresh single-use `purpose` token for `user`, invalidating earlier
+/// ones, and returns the value to put in the emailed link.
+pub(crate) async fn issue_token(
+    storage: &Storage,
+    user: &AuthUserRecordStore,
+    purpose: &str,
+    ttl_minutes: i64,
+) -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let now = Utc::now().timestamp();
+    storage.remove_email_tokens(user.id, purpose).await;
+    storage
+        .put_email_token(
+            auth::token_hash(&token),
+            EmailTokenRecordStore {
+                purpose: purpose.to_string(),
+                user_id: user.id,
+                email: user.email.clone(),
+                created_at: now,
+                expires_at: now + ttl_minutes * 60,
+            },
+        )
+        .await;
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PersistenceBackend;
+
+    #[test]
+    fn templates_render_links_against_app_base_url() {
+        let reset = EmailTemplate::PasswordReset {
+            token: "abc123".to_string(),
+            expires_minutes: 60,
+        }
+        .render("Ana", "https://app.galynx.io");
+        assert_eq!(reset.subject, "Reset your Galynx password");
+        assert!(reset.body.starts_with("Hi Ana,"));
+        assert!(
+            reset
+                .body
+                .contains("https://app.galynx.io/reset-password?token=abc123")
+        );
+
+        let redacted = EmailTemplate::PasswordReset {
+            token: "abc123".to_string(),
+            expires_minutes: 60,
+        }
+        .redacted()
+        .render("Ana", "https://app.galynx.io");
+        assert!(!redacted.body.contains("abc123"));
+        assert!(redacted.body.contains("token=<redacted>"));
+
+        let digest = EmailTemplate::Digest {
+            workspace_name: "Galynx".to_string(),
+            unread_count: 1,
+            items: vec!["Ana mentioned you".to_string()],
+        }
+        .render("Bo", "https://app.galynx.io");
+        assert_eq!(digest.subject, "1 unread notification in Galynx");
+        assert!(digest.body.contains("- Ana mentioned you"));
+    }
+
+    #[tokio::test]
+    async fn email_tokens_are_single_use_and_replace_older_ones() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let user = AuthUserRecordStore {
+            id: Uuid::new_v4(),
+            email: "ana@galynx.local".to_string(),
+            name: "Ana".to_string(),
+            password_hash: String::new(),
+            email_verified_at: None,
+        };
+        let now = Utc::now().timestamp();
+        let first = issue_token(&storage, &user, "password_reset", 60).await;
+        let second = issue_token(&storage, &user, "password_reset", 60).await;
+
+        let (first, second) = (auth::token_hash(&first), auth::token_hash(&second));
+        let taken = storage
+            .take_email_token(&first, "password_reset", now)
+            .await;
+        assert!(taken.is_none());
+        let taken = storage
+            .take_email_token(&second, "email_verification", now)
+            .await;
+        assert!(taken.is_none());
+        let taken = storage
+            .take_email_token(&second, "password_reset", now)
+            .await;
+        assert_eq!(taken.map(|token| token.user_id), Some(user.id));
+        let taken = storage
+            .take_email_token(&second, "password_reset", now)
+            .await;
+        assert!(taken.is_none());
+    }
+}
@@ -0,0 +1,134 @@
+use std::net::SocketAddr;
+
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{
+    app::AppState,
+    auth::token_hash,
+    email::{self, EmailTemplate},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse},
+};
+
+const PURPOSE: &str = "email_verification";
+const TOKEN_TTL_MINUTES: i64 = 48 * 60;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyEmailRequest {
+    /// Token from the link in the verification email.
+    pub token: String,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/me/email/verification",
+            post(request_email_verification),
+        )
+        .route("/api/v1/auth/email/verify", post(verify_email))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/email/verification",
+    operation_id = "request_email_verification",
+    tag = "auth",
+    responses(
+        (status = 202, description = "Verification email queued"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "Email already verified", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn request_email_verification(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let user = state
+        .storage
+        .get_auth_user_by_id(context.user_id)
+        .await
+        .ok_or_else(|| ApiError::Unauthorized("user not found".to_string()))?;
+    if user.email_verified_at.is_some() {
+        return Err(ApiError::Conflict("email already verified".to_string()));
+    }
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    state
+        .rate_limit
+        .check_auth(&client_ip, Some(&user.email))
+        .await?;
+
+    let token = email::issue_token(&state.storage, &user, PURPOSE, TOKEN_TTL_MINUTES).await;
+    state.email.send(
+        &user.email,
+        &user.name,
+        EmailTemplate::EmailVerification { token },
+    );
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/email/verify",
+    operation_id = "verify_email",
+    tag = "auth",
+    security(()),
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 204, description = "Email verified"),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> ApiResult<StatusCode> {
+    let now = Utc::now().timestamp();
+    let invalid = || {
+        ApiError::Coded(
+            ErrorCode::EmailTokenInvalid,
+            "invalid or expired verification token".to_string(),
+        )
+    };
+    let token = state
+        .storage
+        .take_email_token(&token_hash(payload.token.trim()), PURPOSE, now)
+        .await
+        .ok_or_else(invalid)?;
+    // A token mailed before an address change does not verify the new one.
+    let mut user = state
+        .storage
+        .get_auth_user_by_id(token.user_id)
+        .await
+        .filter(|user| user.email.eq_ignore_ascii_case(&token.email))
+        .ok_or_else(invalid)?;
+
+    if user.email_verified_at.is_none() {
+        user.email_verified_at = Some(now);
+        state.storage.put_auth_user(user.clone()).await;
+        state
+            .audit
+            .write_for_user(
+                user.id,
+                "USER_EMAIL_VERIFIED",
+                json!({ "email": user.email }),
+            )
+            .await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,140 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+use uuid::Uuid;
+
+/// Marks a stored value as sealed; anything else is read back as plaintext,
+/// so bodies written before encryption was enabled stay readable.
+const SEALED_PREFIX: &str = "enc:v1:";
+pub const KEY_LEN: usize = 32;
+
+/// Key-encryption key from `MESSAGE_ENCRYPTION_KEY`. It never touches message
+/// bodies directly: it wraps one random data key per workspace.
+pub struct MessageEncryption {
+    kek: LessSafeKey,
+}
+
+/// Per-workspace AES-256-GCM key that seals message bodies.
+pub struct DataKey(LessSafeKey);
+
+impl MessageEncryption {
+    pub fn from_base64(value: &str) -> Result<Self, String> {
+        let bytes = STANDARD
+            .decode(value.trim())
+            .map_err(|error| format!("MESSAGE_ENCRYPTION_KEY is not valid base64: {error}"))?;
+        Ok(Self {
+            kek: aes_key(&bytes)
+                .ok_or_else(|| format!("MESSAGE_ENCRYPTION_KEY must decode to {KEY_LEN} bytes"))?,
+        })
+    }
+
+    /// Returns a fresh data key and its wrapped form for storage.
+    pub fn generate_data_key(&self, workspace_id: Uuid) -> Option<(DataKey, String)> {
+        let mut bytes = [0_u8; KEY_LEN];
+        SystemRandom::new().fill(&mut bytes).ok()?;
+        let wrapped = seal(&self.kek, workspace_id.as_bytes(), &bytes)?;
+        Some((DataKey(aes_key(&bytes)?), wrapped))
+    }
+
+    pub fn unwrap_data_key(&self, workspace_id: Uuid, wrapped: &str) -> Option<DataKey> {
+        let bytes = open(&self.kek, workspace_id.as_bytes(), wrapped)?;
+        aes_key(&bytes).map(DataKey)
+    }
+}
+
+impl DataKey {
+    /// Seals `plaintext` bound to `record_id`, so a sealed body cannot be
+    /// moved onto another message.
+    pub fn seal(&self, record_id: Uuid, plaintext: &str) -> Option<String> {
+        seal(&self.0, record_id.as_bytes(), plaintext.as_bytes())
+    }
+
+    /// Opens a sealed value, or returns unsealed values unchanged.
+    pub fn open(&self, record_id: Uuid, stored: &str) -> Option<String> {
+        if !is_sealed(stored) {
+            return Some(stored.to_string());
+        }
+        String::from_utf8(open(&self.0, record_id.as_bytes(), stored)?).ok()
+    }
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+fn aes_key(bytes: &[u8]) -> Option<LessSafeKey> {
+    if bytes.len() != KEY_LEN {
+        return None;
+    }
+    UnboundKey::new(&AES_256_GCM, bytes)
+        .ok()
+        .map(LessSafeKey::new)
+}
+
+fn seal(key: &LessSafeKey, aad: &[u8], plaintext: &[u8]) -> Option<String> {
+    let mut nonce = [0_u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce).ok()?;
+    let mut buffer = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad),
+        &mut buffer,
+    )
+    .ok()?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&buffer);
+    Some(format!("{SEALED_PREFIX}{}", STANDARD.encode(sealed)))
+}
+
+fn open(key: &LessSafeKey, aad: &[u8], stored: &str) -> Option<Vec<u8>> {
+    let mut sealed = STANDARD.decode(stored.strip_prefix(SEALED_PREFIX)?).ok()?;
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let mut ciphertext = sealed.split_off(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(&sealed).ok()?;
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(aad), &mut ciphertext)
+        .ok()?;
+    Some(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_keys_wrap_and_seal_bodies_bound_to_their_record() {
+        assert!(MessageEncryption::from_base64("c2hvcnQ=").is_err());
+        let encryption =
+            MessageEncryption::from_base64(&STANDARD.encode([7_u8; KEY_LEN])).expect("valid key");
+        let workspace_id = Uuid::new_v4();
+        let (key, wrapped) = encryption
+            .generate_data_key(workspace_id)
+            .expect("data key");
+        assert!(
+            encryption
+                .unwrap_data_key(Uuid::new_v4(), &wrapped)
+                .is_none()
+        );
+        let key_again = encryption
+            .unwrap_data_key(workspace_id, &wrapped)
+            .expect("wrapped key should open");
+
+        let message_id = Uuid::new_v4();
+        let sealed = key.seal(message_id, "hola 👋").expect("seal");
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("hola"));
+        assert_eq!(
+            key_again.open(message_id, &sealed).as_deref(),
+            Some("hola 👋")
+        );
+        assert!(key.open(Uuid::new_v4(), &sealed).is_none());
+        assert_eq!(
+            key.open(message_id, "legacy plaintext").as_deref(),
+            Some("legacy plaintext")
+        );
+    }
+}
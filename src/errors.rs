@@ -1,63 +1,349 @@
+use std::collections::BTreeMap;
+
 use axum::{
     Json,
-    http::StatusCode,
+    body::Body,
+    http::{HeaderValue, Request, StatusCode, header},
+    middleware::Next,
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use crate::{
+    observability,
+    storage::{SealError, WriteError},
+    versioning::ApiVersion,
+};
+
 pub type ApiResult<T> = Result<T, ApiError>;
 
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+tokio::task_local! {
+    static PROBLEM_INSTANCE: String;
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ApiError {
     #[error("{0}")]
     Unauthorized(String),
     #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
     BadRequest(String),
     #[error("{0}")]
     TooManyRequests(String),
     #[error("{0}")]
     NotFound(String),
     #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
     Internal(String),
+    #[error("{1}")]
+    Coded(ErrorCode, String),
+    #[error("request validation failed")]
+    Validation(BTreeMap<String, String>),
+}
+
+/// Stable identifiers clients can branch on; the HTTP status is derived from the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Unauthorized,
+    Forbidden,
+    BadRequest,
+    RateLimited,
+    NotFound,
+    Conflict,
+    InternalError,
+    ValidationFailed,
+    InvalidCredentials,
+    MissingToken,
+    InvalidToken,
+    RefreshTokenInvalid,
+    RefreshTokenExpired,
+    RefreshTokenReused,
+    MembershipRevoked,
+    WorkspaceMismatch,
+    ChannelNameTaken,
+    EmailTaken,
+    WorkspaceSuspended,
+    AttachmentTooLarge,
+    UploadExpired,
+    InvalidCursor,
+    MaintenanceMode,
+    ContentBlocked,
+    LegalHold,
+    MessageTooLong,
+    TooManyAttachments,
+    ThreadDepthExceeded,
+    StaleKeyEpoch,
+    IpNotAllowed,
+    EmailTokenInvalid,
+    UserMuted,
+    IdempotencyKeyInUse,
+    IdempotencyKeyReused,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
+    pub code: ErrorCode,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<BTreeMap<String, String>>,
+    pub request_id: Option<String>,
 }
 
-impl ApiError {
-    fn status_code(&self) -> StatusCode {
+/// RFC 7807 body, returned instead of `ErrorResponse` when the client accepts
+/// `application/problem+json`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    pub instance: String,
+    pub code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<BTreeMap<String, String>>,
+    pub request_id: Option<String>,
+}
+
+/// Collects per-field problems so a request body is rejected once with all of them.
+#[derive(Debug, Default)]
+pub struct FieldErrors(BTreeMap<String, String>);
+
+impl FieldErrors {
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0
+            .entry(field.to_string())
+            .or_insert_with(|| message.into());
+    }
+
+    pub fn into_result(self) -> ApiResult<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.0))
+        }
+    }
+}
+
+impl ErrorCode {
+    pub fn status_code(self) -> StatusCode {
         match self {
-            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
-            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
-            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
-            Self::NotFound(_) => StatusCode::NOT_FOUND,
-            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Unauthorized
+            | Self::InvalidCredentials
+            | Self::MissingToken
+            | Self::InvalidToken
+            | Self::RefreshTokenInvalid
+            | Self::RefreshTokenExpired
+            | Self::RefreshTokenReused
+            | Self::MembershipRevoked => StatusCode::UNAUTHORIZED,
+            Self::Forbidden
+            | Self::WorkspaceMismatch
+            | Self::WorkspaceSuspended
+            | Self::IpNotAllowed
+            | Self::UserMuted => StatusCode::FORBIDDEN,
+            Self::BadRequest
+            | Self::UploadExpired
+            | Self::InvalidCursor
+            | Self::EmailTokenInvalid => StatusCode::BAD_REQUEST,
+            Self::ValidationFailed
+            | Self::ContentBlocked
+            | Self::MessageTooLong
+            | Self::AttachmentTooLarge
+            | Self::TooManyAttachments
+            | Self::ThreadDepthExceeded
+            | Self::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::Conflict
+            | Self::ChannelNameTaken
+            | Self::EmailTaken
+            | Self::LegalHold
+            | Self::StaleKeyEpoch
+            | Self::IdempotencyKeyInUse => StatusCode::CONFLICT,
+            Self::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::MaintenanceMode => StatusCode::SERVICE_UNAVAILABLE,
         }
     }
+}
 
-    fn code(&self) -> &'static str {
+impl ApiError {
+    pub fn code(&self) -> ErrorCode {
         match self {
-            Self::Unauthorized(_) => "unauthorized",
-            Self::BadRequest(_) => "bad_request",
-            Self::TooManyRequests(_) => "too_many_requests",
-            Self::NotFound(_) => "not_found",
-            Self::Internal(_) => "internal_error",
+            Self::Unauthorized(_) => ErrorCode::Unauthorized,
+            Self::Forbidden(_) => ErrorCode::Forbidden,
+            Self::BadRequest(_) => ErrorCode::BadRequest,
+            Self::TooManyRequests(_) => ErrorCode::RateLimited,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::Conflict(_) => ErrorCode::Conflict,
+            Self::Internal(_) => ErrorCode::InternalError,
+            Self::Coded(code, _) => *code,
+            Self::Validation(_) => ErrorCode::ValidationFailed,
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.code().status_code()
+    }
+
+    fn category(&self) -> &'static str {
+        match self.status_code() {
+            StatusCode::UNAUTHORIZED => "unauthorized",
+            StatusCode::FORBIDDEN => "forbidden",
+            StatusCode::BAD_REQUEST => "bad_request",
+            StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+            StatusCode::TOO_MANY_REQUESTS => "too_many_requests",
+            StatusCode::NOT_FOUND => "not_found",
+            StatusCode::CONFLICT => "conflict",
+            StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+            _ => "internal_error",
         }
     }
 }
 
+impl From<SealError> for ApiError {
+    fn from(error: SealError) -> Self {
+        Self::Internal(error.to_string())
+    }
+}
+
+impl From<WriteError> for ApiError {
+    fn from(error: WriteError) -> Self {
+        Self::Internal(error.to_string())
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let status = self.status_code();
+        let message = self.to_string();
+        let error = self.category().to_string();
+        let code = self.code();
+        let fields = match self {
+            Self::Validation(fields) => Some(fields),
+            _ => None,
+        };
+        let request_id = observability::current_request_id();
+
+        if let Ok(instance) = PROBLEM_INSTANCE.try_with(Clone::clone) {
+            let code_name = serde_json::to_value(code)
+                .ok()
+                .and_then(|value| value.as_str().map(ToString::to_string))
+                .unwrap_or_default();
+            let body = ProblemDetails {
+                problem_type: format!("urn:galynx:error:{code_name}"),
+                title: status.canonical_reason().unwrap_or("Error").to_string(),
+                status: status.as_u16(),
+                detail: message,
+                instance,
+                code,
+                fields,
+                request_id,
+            };
+            let mut response = (status, Json(body)).into_response();
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+            );
+            return response;
+        }
+
         let body = ErrorResponse {
-            error: self.code().to_string(),
-            message: self.to_string(),
+            error,
+            code,
+            message,
+            fields,
+            request_id,
         };
 
         (status, Json(body)).into_response()
     }
 }
+
+pub async fn problem_details_middleware(request: Request<Body>, next: Next) -> Response {
+    let accepts_problem_json = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(PROBLEM_JSON_CONTENT_TYPE));
+    let version_default =
+        ApiVersion::from_path(request.uri().path()).is_some_and(ApiVersion::prefers_problem_json);
+    if !accepts_problem_json && !version_default {
+        return next.run(request).await;
+    }
+    let instance = request.uri().path().to_string();
+    PROBLEM_INSTANCE.scope(instance, next.run(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coded_errors_keep_status_and_serialize_code() {
+        let error = ApiError::Coded(ErrorCode::UploadExpired, "expired".to_string());
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.category(), "bad_request");
+        assert_eq!(
+            serde_json::to_value(error.code()).unwrap(),
+            "UPLOAD_EXPIRED"
+        );
+        let conflict = ApiError::Coded(ErrorCode::ChannelNameTaken, "taken".to_string());
+        assert_eq!(conflict.status_code(), StatusCode::CONFLICT);
+        assert_eq!(
+            serde_json::to_value(conflict.code()).unwrap(),
+            "CHANNEL_NAME_TAKEN"
+        );
+        assert_eq!(
+            ApiError::TooManyRequests("slow down".to_string()).code(),
+            ErrorCode::RateLimited
+        );
+    }
+
+    #[tokio::test]
+    async fn problem_json_is_used_inside_negotiated_scope() {
+        let response = PROBLEM_INSTANCE
+            .scope("/api/v1/channels".to_string(), async {
+                ApiError::Coded(ErrorCode::ChannelNameTaken, "taken".to_string()).into_response()
+            })
+            .await;
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            PROBLEM_JSON_CONTENT_TYPE
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["type"], "urn:galynx:error:CHANNEL_NAME_TAKEN");
+        assert_eq!(body["status"], 409);
+        assert_eq!(body["instance"], "/api/v1/channels");
+
+        let plain = ApiError::NotFound("missing".to_string()).into_response();
+        assert_eq!(plain.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[test]
+    fn field_errors_keep_first_message_per_field() {
+        let mut errors = FieldErrors::default();
+        assert!(FieldErrors::default().into_result().is_ok());
+        errors.add("name", "must not be empty");
+        errors.add("name", "is too long");
+        errors.add("size_bytes", "exceeds limit");
+        let Err(ApiError::Validation(fields)) = errors.into_result() else {
+            panic!("expected validation error");
+        };
+        assert_eq!(fields["name"], "must not be empty");
+        assert_eq!(fields.len(), 2);
+        assert_eq!(
+            ApiError::Validation(fields).status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,848 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+};
+use chrono::Utc;
+use rand::RngCore;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{RwLock, Semaphore, broadcast};
+use tracing::warn;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    channels::ChannelService,
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    realtime::WsEventEnvelope,
+    storage::{EventSubscriptionRecordStore, Storage},
+};
+
+const MAX_SUBSCRIPTIONS_PER_USER: usize = 20;
+const MAX_EVENT_TYPES: usize = 50;
+const MAX_CHANNELS: usize = 100;
+const MAX_EVENT_TYPE_LEN: usize = 64;
+const WILDCARD: &str = "*";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Webhook requests in flight per instance; delivery waits for a free slot.
+const MAX_CONCURRENT_WEBHOOKS: usize = 32;
+/// Other replicas may change subscriptions, so cached lists are reloaded
+/// periodically even without a local change.
+const WEBHOOK_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Webhook subscriptions per workspace, so delivery does not query storage
+/// for every emitted event.
+pub struct WebhookSubscriptions {
+    storage: Arc<Storage>,
+    cached: RwLock<HashMap<Uuid, CachedWebhooks>>,
+}
+
+struct CachedWebhooks {
+    subscriptions: Arc<Vec<EventSubscriptionRecordStore>>,
+    loaded_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventDelivery {
+    /// Filters the subscriber's own WebSocket connections.
+    Websocket,
+    /// POSTs each matching event to `webhook_url`.
+    Webhook,
+}
+
+impl EventDelivery {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Websocket => "websocket",
+            Self::Webhook => "webhook",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "webhook" => Self::Webhook,
+            _ => Self::Websocket,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateEventSubscriptionRequest {
+    /// Event types such as `MESSAGE_CREATED`, or `*` for every type.
+    pub event_types: Vec<String>,
+    /// Restricts delivery to events of these channels; empty means any event.
+    #[serde(default)]
+    pub channel_ids: Vec<Uuid>,
+    pub delivery: EventDelivery,
+    /// Required for `webhook` delivery; must be `https`.
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventSubscriptionResponse {
+    pub id: Uuid,
+    pub event_types: Vec<String>,
+    pub channel_ids: Vec<Uuid>,
+    pub delivery: EventDelivery,
+    pub webhook_url: Option<String>,
+    /// Signing secret for webhook payloads; only returned on creation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventSubscriptionListResponse {
+    pub items: Vec<EventSubscriptionResponse>,
+}
+
+impl From<&EventSubscriptionRecordStore> for EventSubscriptionResponse {
+    fn from(record: &EventSubscriptionRecordStore) -> Self {
+        Self {
+            id: record.id,
+            event_types: record.event_types.clone(),
+            channel_ids: record.channel_ids.clone(),
+            delivery: EventDelivery::parse(&record.delivery),
+            webhook_url: record.webhook_url.clone(),
+            webhook_secret: None,
+            created_at: record.created_at,
+        }
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/me/event-subscriptions",
+            get(list_event_subscriptions).post(create_event_subscription),
+        )
+        .route(
+            "/api/v1/me/event-subscriptions/:id",
+            delete(delete_event_subscription),
+        )
+}
+
+/// Whether `event` sent to the workspace should reach `subscription`. Directed
+/// events only match their recipient's subscriptions, and a channel filter
+/// excludes events that are not tied to a channel.
+pub(crate) fn matches(
+    subscription: &EventSubscriptionRecordStore,
+    event: &WsEventEnvelope,
+) -> bool {
+    if event
+        .recipient_id
+        .is_some_and(|recipient| recipient != subscription.user_id)
+    {
+        return false;
+    }
+    let type_matches = subscription
+        .event_types
+        .iter()
+        .any(|event_type| event_type == WILDCARD || *event_type == event.event_type);
+    let channel_matches = subscription.channel_ids.is_empty()
+        || event
+            .channel_id
+            .is_some_and(|channel_id| subscription.channel_ids.contains(&channel_id));
+    type_matches && channel_matches
+}
+
+/// The caller's WebSocket subscriptions, read when a socket connects. An
+/// empty list means the socket receives the full workspace stream.
+pub(crate) async fn websocket_filters(
+    storage: &Storage,
+    context: &AuthContext,
+) -> Vec<EventSubscriptionRecordStore> {
+    storage
+        .list_event_subscriptions(context.workspace_id, Some(context.user_id))
+        .await
+        .into_iter()
+        .filter(|subscription| {
+            EventDelivery::parse(&subscription.delivery) == EventDelivery::Websocket
+        })
+        .collect()
+}
+
+impl WebhookSubscriptions {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self {
+            storage,
+            cached: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn for_workspace(&self, workspace_id: Uuid) -> Arc<Vec<EventSubscriptionRecordStore>> {
+        if let Some(cached) = self.cached.read().await.get(&workspace_id)
+            && cached.loaded_at.elapsed() < WEBHOOK_CACHE_TTL
+        {
+            return cached.subscriptions.clone();
+        }
+        let subscriptions = Arc::new(
+            self.storage
+                .list_event_subscriptions(workspace_id, None)
+                .await
+                .into_iter()
+                .filter(|subscription| {
+                    EventDelivery::parse(&subscription.delivery) == EventDelivery::Webhook
+                })
+                .collect::<Vec<_>>(),
+        );
+        self.cached.write().await.insert(
+            workspace_id,
+            CachedWebhooks {
+                subscriptions: subscriptions.clone(),
+                loaded_at: Instant::now(),
+            },
+        );
+        subscriptions
+    }
+
+    /// Drops the cached list after a subscription of the workspace changes.
+    pub(crate) async fn invalidate(&self, workspace_id: Uuid) {
+        self.cached.write().await.remove(&workspace_id);
+    }
+}
+
+/// Resolves webhook hosts keeping only public addresses, so a subscription
+/// cannot reach internal services even if its DNS record changes later.
+struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect::<Vec<_>>();
+            if addrs.is_empty() {
+                return Err(format!("{host} does not resolve to a public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Forwards events emitted by this instance to matching webhook subscriptions.
+pub(crate) fn spawn_webhook_delivery(state: AppState) {
+    let client = match reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .dns_resolver(Arc::new(PublicAddressResolver))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(client) => client,
+        Err(error) => {
+            warn!("failed to build event webhook client: {}", error);
+            return;
+        }
+    };
+    let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_WEBHOOKS));
+    let mut rx = state.realtime.subscribe_emitted();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok((workspace_id, event)) => {
+                    deliver_webhooks(&state, &client, &slots, workspace_id, &event).await;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("event webhook delivery lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+/// Queues a delivery task for each webhook subscription matching `event`.
+/// Nothing here waits on an endpoint, so slow receivers cannot make the
+/// emitted-event consumer lag.
+async fn deliver_webhooks(
+    state: &AppState,
+    client: &reqwest::Client,
+    slots: &Arc<Semaphore>,
+    workspace_id: Uuid,
+    event: &WsEventEnvelope,
+) {
+    let subscriptions = state
+        .webhook_subscriptions
+        .for_workspace(workspace_id)
+        .await;
+    let mut body: Option<Arc<Vec<u8>>> = None;
+    for subscription in subscriptions.iter() {
+        if !matches(subscription, event) {
+            continue;
+        }
+        let (Some(url), Some(secret)) = (&subscription.webhook_url, &subscription.webhook_secret)
+        else {
+            continue;
+        };
+        if !is_valid_webhook_url(url) {
+            warn!(
+                "event webhook {} skipped: {} is not a public https URL",
+                subscription.id, url
+            );
+            continue;
+        }
+        let body = body
+            .get_or_insert_with(|| Arc::new(serde_json::to_vec(event).unwrap_or_default()))
+            .clone();
+        let (state, client, slots) = (state.clone(), client.clone(), slots.clone());
+        let (subscription_id, user_id, url, secret) = (
+            subscription.id,
+            subscription.user_id,
+            url.clone(),
+            secret.clone(),
+        );
+        let channel_id = event.channel_id;
+        tokio::spawn(async move {
+            if let Some(channel_id) = channel_id
+                && !subscriber_can_view(
+                    &state.storage,
+                    &state.channels,
+                    workspace_id,
+                    user_id,
+                    channel_id,
+                )
+                .await
+            {
+                return;
+            }
+            let Ok(_slot) = slots.acquire_owned().await else {
+                return;
+            };
+            let signature = sign(&secret, &body);
+            let response = client
+                .post(&url)
+                .header("content-type", "application/json")
+                .header("x-galynx-subscription", subscription_id.to_string())
+                .header("x-galynx-signature", format!("sha256={signature}"))
+                .body(body.to_vec())
+                .send()
+                .await;
+            match response {
+                Ok(response) if !response.status().is_success() => {
+                    warn!(
+                        "event webhook {} answered {}",
+                        subscription_id,
+                        response.status()
+                    );
+                }
+                Ok(_) => {}
+                Err(error) => warn!("event webhook {} failed: {}", subscription_id, error),
+            }
+        });
+    }
+}
+
+/// Applies the same channel access rules as the API, so guest workspaces of
+/// a shared channel receive its events like the host does.
+async fn subscriber_can_view(
+    storage: &Storage,
+    channels: &ChannelService,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    channel_id: Uuid,
+) -> bool {
+    let Some(role) = storage
+        .get_membership_role(workspace_id, user_id)
+        .await
+        .and_then(|role| WorkspaceRole::from_storage_role(&role).ok())
+    else {
+        return false;
+    };
+    let context = AuthContext {
+        user_id,
+        workspace_id,
+        role,
+    };
+    channels
+        .ensure_channel_access(&context, channel_id)
+        .await
+        .is_ok()
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    to_hex(hmac::sign(&key, body).as_ref())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Trims and dedups `values`, reporting invalid entries under `field`.
+pub(crate) fn parse_event_types(
+    values: &[String],
+    field: &str,
+    errors: &mut FieldErrors,
+) -> Vec<String> {
+    let mut event_types: Vec<String> = Vec::with_capacity(values.len());
+    for (index, value) in values.iter().enumerate() {
+        let value = value.trim();
+        if !is_valid_event_type(value) {
+            errors.add(
+                &format!("{field}[{index}]"),
+                "must be an event type like MESSAGE_CREATED or *",
+            );
+        } else if !event_types.iter().any(|event_type| event_type == value) {
+            event_types.push(value.to_string());
+        }
+    }
+    event_types
+}
+
+/// An `https` URL whose host is not `localhost` or a literal non-public IP.
+/// Hostnames are checked again when resolved for each delivery.
+pub(crate) fn is_valid_webhook_url(value: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(value) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str().filter(|_| parsed.scheme() == "https") else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    }
+}
+
+/// False for loopback, private, link-local (including cloud metadata),
+/// shared, multicast and other non-routable addresses.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(mapped),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || first == 0
+        || (first == 100 && (64..128).contains(&second))
+        || first >= 240)
+}
+
+/// A workspace-wide webhook subscription with a fresh signing secret.
+pub(crate) fn webhook_subscription(
+    workspace_id: Uuid,
+    user_id: Uuid,
+    event_types: Vec<String>,
+    webhook_url: String,
+) -> EventSubscriptionRecordStore {
+    EventSubscriptionRecordStore {
+        delivery: EventDelivery::Webhook.as_str().to_string(),
+        webhook_url: Some(webhook_url),
+        webhook_secret: Some(generate_secret()),
+        ..new_record(workspace_id, user_id, event_types)
+    }
+}
+
+fn new_record(
+    workspace_id: Uuid,
+    user_id: Uuid,
+    event_types: Vec<String>,
+) -> EventSubscriptionRecordStore {
+    EventSubscriptionRecordStore {
+        id: Uuid::new_v4(),
+        workspace_id,
+        user_id,
+        event_types,
+        channel_ids: Vec::new(),
+        delivery: EventDelivery::Websocket.as_str().to_string(),
+        webhook_url: None,
+        webhook_secret: None,
+        created_at: Utc::now().timestamp_millis(),
+    }
+}
+
+fn is_valid_event_type(value: &str) -> bool {
+    value == WILDCARD
+        || (!value.is_empty()
+            && value.len() <= MAX_EVENT_TYPE_LEN
+            && value.chars().all(|ch| ch.is_ascii_uppercase() || ch == '_'))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/event-subscriptions",
+    operation_id = "list_event_subscriptions",
+    tag = "event_subscriptions",
+    responses(
+        (status = 200, description = "Caller's event subscriptions", body = EventSubscriptionListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_event_subscriptions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<EventSubscriptionListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mut items = state
+        .storage
+        .list_event_subscriptions(context.workspace_id, Some(context.user_id))
+        .await
+        .iter()
+        .map(EventSubscriptionResponse::from)
+        .collect::<Vec<_>>();
+    items.sort_by_key(|item| (item.created_at, item.id));
+    Ok(Json(EventSubscriptionListResponse { items }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/event-subscriptions",
+    operation_id = "create_event_subscription",
+    tag = "event_subscriptions",
+    request_body = CreateEventSubscriptionRequest,
+    responses(
+        (status = 201, description = "Subscription created", body = EventSubscriptionResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Channel not accessible", body = ErrorResponse),
+        (status = 409, description = "Too many subscriptions", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_event_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateEventSubscriptionRequest>,
+) -> ApiResult<(StatusCode, Json<EventSubscriptionResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+
+    let mut errors = FieldErrors::default();
+    if payload.event_types.is_empty() || payload.event_types.len() > MAX_EVENT_TYPES {
+        errors.add(
+            "event_types",
+            format!("must contain between 1 and {MAX_EVENT_TYPES} entries"),
+        );
+    }
+    let event_types = parse_event_types(&payload.event_types, "event_types", &mut errors);
+    if payload.channel_ids.len() > MAX_CHANNELS {
+        errors.add(
+            "channel_ids",
+            format!("must contain at most {MAX_CHANNELS} entries"),
+        );
+    }
+    let webhook_url = match (payload.delivery, payload.webhook_url.as_deref()) {
+        (EventDelivery::Webhook, Some(url)) => {
+            let url = url.trim();
+            if !is_valid_webhook_url(url) {
+                errors.add("webhook_url", "must be an https URL to a public host");
+            }
+            Some(url.to_string())
+        }
+        (EventDelivery::Webhook, None) => {
+            errors.add("webhook_url", "is required for webhook delivery");
+            None
+        }
+        (EventDelivery::Websocket, Some(_)) => {
+            errors.add("webhook_url", "is only allowed for webhook delivery");
+            None
+        }
+        (EventDelivery::Websocket, None) => None,
+    };
+    errors.into_result()?;
+
+    let mut channel_ids = Vec::with_capacity(payload.channel_ids.len());
+    for channel_id in payload.channel_ids {
+        if !channel_ids.contains(&channel_id) {
+            state
+                .channels
+                .ensure_channel_access(&context, channel_id)
+                .await?;
+            channel_ids.push(channel_id);
+        }
+    }
+    if state
+        .storage
+        .list_event_subscriptions(context.workspace_id, Some(context.user_id))
+        .await
+        .len()
+        >= MAX_SUBSCRIPTIONS_PER_USER
+    {
+        return Err(ApiError::Conflict(format!(
+            "at most {MAX_SUBSCRIPTIONS_PER_USER} event subscriptions per user"
+        )));
+    }
+
+    let record = EventSubscriptionRecordStore {
+        channel_ids,
+        delivery: payload.delivery.as_str().to_string(),
+        webhook_secret: webhook_url.as_ref().map(|_| generate_secret()),
+        webhook_url,
+        ..new_record(context.workspace_id, context.user_id, event_types)
+    };
+    state.storage.put_event_subscription(record.clone()).await;
+    state
+        .webhook_subscriptions
+        .invalidate(context.workspace_id)
+        .await;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "EVENT_SUBSCRIPTION_CREATED",
+            "event_subscription",
+            Some(record.id.to_string()),
+            json!({
+                "event_types": record.event_types,
+                "channel_ids": record.channel_ids,
+                "delivery": record.delivery,
+                "webhook_url": record.webhook_url,
+            }),
+        )
+        .await;
+    let response = EventSubscriptionResponse {
+        webhook_secret: record.webhook_secret.clone(),
+        ..EventSubscriptionResponse::from(&record)
+    };
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/event-subscriptions/{id}",
+    operation_id = "delete_event_subscription",
+    tag = "event_subscriptions",
+    responses(
+        (status = 204, description = "Subscription deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Subscription not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn delete_event_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subscription_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    if !state
+        .storage
+        .remove_event_subscription(context.workspace_id, context.user_id, subscription_id)
+        .await
+    {
+        return Err(ApiError::NotFound(
+            "event subscription not found".to_string(),
+        ));
+    }
+    state
+        .webhook_subscriptions
+        .invalidate(context.workspace_id)
+        .await;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "EVENT_SUBSCRIPTION_DELETED",
+            "event_subscription",
+            Some(subscription_id.to_string()),
+            json!({}),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channel_links::ChannelLinkService,
+        realtime,
+        storage::{PersistenceBackend, WorkspaceRecordStore},
+    };
+
+    #[test]
+    fn subscriptions_filter_by_type_channel_and_recipient() {
+        let (workspace_id, user_id, channel_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let subscription =
+            |event_types: &[&str], channel_ids: Vec<Uuid>| EventSubscriptionRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id,
+                user_id,
+                event_types: event_types.iter().map(ToString::to_string).collect(),
+                channel_ids,
+                delivery: "websocket".to_string(),
+                webhook_url: None,
+                webhook_secret: None,
+                created_at: 0,
+            };
+        let created = realtime::make_event(
+            "MESSAGE_CREATED",
+            workspace_id,
+            Some(channel_id),
+            None,
+            json!({}),
+        );
+        let other_channel = realtime::make_event(
+            "MESSAGE_CREATED",
+            workspace_id,
+            Some(Uuid::new_v4()),
+            None,
+            json!({}),
+        );
+        let presence =
+            realtime::make_event("PRESENCE_CHANGED", workspace_id, None, None, json!({}));
+
+        let by_type = subscription(&["MESSAGE_CREATED"], Vec::new());
+        assert!(matches(&by_type, &created));
+        assert!(matches(&by_type, &other_channel));
+        assert!(!matches(&by_type, &presence));
+
+        let by_channel = subscription(&["*"], vec![channel_id]);
+        assert!(matches(&by_channel, &created));
+        assert!(!matches(&by_channel, &other_channel));
+        assert!(!matches(&by_channel, &presence));
+
+        let everything = subscription(&["*"], Vec::new());
+        let mine = realtime::make_user_event("REMINDER_DUE", workspace_id, user_id, json!({}));
+        let theirs =
+            realtime::make_user_event("REMINDER_DUE", workspace_id, Uuid::new_v4(), json!({}));
+        assert!(matches(&everything, &mine));
+        assert!(!matches(&everything, &theirs));
+
+        assert!(is_valid_webhook_url("https://hooks.example.com/galynx"));
+        assert!(is_valid_webhook_url("https://93.184.216.34/hook"));
+        for url in [
+            "http://hooks.example.com/galynx",
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://10.0.0.5/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://100.64.0.1/hook",
+            "https://[::1]/hook",
+            "https://[fd00:ec2::254]/hook",
+            "https://[::ffff:192.168.1.1]/hook",
+        ] {
+            assert!(!is_valid_webhook_url(url), "{url}");
+        }
+
+        assert!(is_valid_event_type("MESSAGE_CREATED"));
+        assert!(!is_valid_event_type("message_created"));
+        assert!(!is_valid_event_type(""));
+    }
+
+    #[tokio::test]
+    async fn webhooks_reach_guest_workspaces_and_reload_after_changes() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let context = |workspace_id| AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let host = context(Uuid::new_v4());
+        let guest = context(Uuid::new_v4());
+        for (workspace, name) in [(&host, "Host"), (&guest, "Guest")] {
+            storage
+                .put_workspace(WorkspaceRecordStore {
+                    id: workspace.workspace_id,
+                    name: name.to_string(),
+                    created_by: workspace.user_id,
+                    created_at: 0,
+                    suspended_at: None,
+                    suspended_reason: None,
+                })
+                .await;
+        }
+        let guest_member = Uuid::new_v4();
+        storage
+            .put_membership_role(guest.workspace_id, guest_member, "member")
+            .await;
+        let channels = ChannelService::new(storage.clone(), host.workspace_id, host.user_id);
+        let links = ChannelLinkService::new(storage.clone());
+        let channel_id = channels
+            .list_channels(host.workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+
+        let can_view = || {
+            subscriber_can_view(
+                &storage,
+                &channels,
+                guest.workspace_id,
+                guest_member,
+                channel_id,
+            )
+        };
+        assert!(!can_view().await);
+        let link = links
+            .request(&host, channel_id, guest.workspace_id)
+            .await
+            .expect("host owner should request link");
+        links
+            .approve(&guest, link.id)
+            .await
+            .expect("guest owner should approve");
+        assert!(can_view().await);
+
+        let webhooks = WebhookSubscriptions::new(storage.clone());
+        assert!(webhooks.for_workspace(guest.workspace_id).await.is_empty());
+        let subscription = webhook_subscription(
+            guest.workspace_id,
+            guest_member,
+            vec![WILDCARD.to_string()],
+            "https://hooks.example.com/galynx".to_string(),
+        );
+        storage.put_event_subscription(subscription.clone()).await;
+        assert!(webhooks.for_workspace(guest.workspace_id).await.is_empty());
+        webhooks.invalidate(guest.workspace_id).await;
+        let cached = webhooks.for_workspace(guest.workspace_id).await;
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].id, subscription.id);
+    }
+}
@@ -0,0 +1,355 @@
+use async_graphql::{Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Schema};
+use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    attachments::AttachmentResponse,
+    auth::AuthContext,
+    channels::{
+        ChannelResponse, MessageListResponse, MessageQuery, MessageReactionSummary,
+        MessageResponse, ThreadSummaryResponse,
+    },
+    errors::{ApiError, ApiResult, ErrorResponse},
+};
+
+const MAX_DEPTH: usize = 8;
+const MAX_COMPLEXITY: usize = 1_000;
+
+pub type GraphqlSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn schema() -> GraphqlSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/v1/graphql", post(graphql))
+}
+
+/// Read-only GraphQL over the same services as REST, so access rules match:
+/// private channel messages need membership, member lists need admin.
+#[utoipa::path(
+    post,
+    path = "/api/v1/graphql",
+    operation_id = "graphql",
+    tag = "graphql",
+    request_body(content = Object, description = "GraphQL request (`query`, `variables`, `operationName`)"),
+    responses(
+        (status = 200, description = "GraphQL response with `data` and `errors`", body = Object),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn graphql(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> ApiResult<Json<async_graphql::Response>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let schema = state.graphql.clone();
+    let response = schema.execute(request.data(state).data(context)).await;
+    Ok(Json(response))
+}
+
+fn graphql_error(error: ApiError) -> async_graphql::Error {
+    let code = serde_json::to_value(error.code())
+        .ok()
+        .and_then(|value| value.as_str().map(ToString::to_string))
+        .unwrap_or_default();
+    async_graphql::Error::new(error.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", code);
+    })
+}
+
+fn request_scope<'a>(ctx: &Context<'a>) -> async_graphql::Result<(&'a AppState, &'a AuthContext)> {
+    Ok((ctx.data::<AppState>()?, ctx.data::<AuthContext>()?))
+}
+
+fn page_query(limit: Option<usize>, cursor: Option<String>) -> MessageQuery {
+    MessageQuery {
+        cursor,
+        limit,
+        after_seq: None,
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn channels(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Channel>> {
+        let (state, context) = request_scope(ctx)?;
+        Ok(state
+            .channels
+            .list_channels(context.workspace_id)
+            .await
+            .into_iter()
+            .map(Channel)
+            .collect())
+    }
+
+    async fn channel(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Channel>> {
+        let (state, context) = request_scope(ctx)?;
+        Ok(state
+            .channels
+            .list_channels(context.workspace_id)
+            .await
+            .into_iter()
+            .find(|channel| channel.id == id)
+            .map(Channel))
+    }
+
+    async fn thread(&self, ctx: &Context<'_>, root_id: Uuid) -> async_graphql::Result<Thread> {
+        let (state, context) = request_scope(ctx)?;
+        state
+            .channels
+            .thread_summary(context, root_id)
+            .await
+            .map(Thread)
+            .map_err(graphql_error)
+    }
+}
+
+pub struct Channel(ChannelResponse);
+
+#[Object]
+impl Channel {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn workspace_id(&self) -> Uuid {
+        self.0.workspace_id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn is_private(&self) -> bool {
+        self.0.is_private
+    }
+
+    async fn topic(&self) -> Option<&str> {
+        self.0.topic.as_deref()
+    }
+
+    async fn icon_emoji(&self) -> Option<&str> {
+        self.0.icon_emoji.as_deref()
+    }
+
+    async fn color(&self) -> Option<&str> {
+        self.0.color.as_deref()
+    }
+
+    async fn created_by(&self) -> Uuid {
+        self.0.created_by
+    }
+
+    async fn created_at(&self) -> i64 {
+        self.0.created_at
+    }
+
+    /// Newest first; same paging as `GET /api/v1/channels/:id/messages`.
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<usize>,
+        cursor: Option<String>,
+    ) -> async_graphql::Result<MessagePage> {
+        let (state, context) = request_scope(ctx)?;
+        state
+            .channels
+            .list_messages(context, self.0.id, &page_query(limit, cursor))
+            .await
+            .map(MessagePage)
+            .map_err(graphql_error)
+    }
+
+    /// User ids of explicit members; workspace or channel admins only, like
+    /// the REST endpoint.
+    async fn members(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Uuid>> {
+        let (state, context) = request_scope(ctx)?;
+        state
+            .channels
+            .ensure_channel_manager(context, self.0.id)
+            .await
+            .map_err(graphql_error)?;
+        state
+            .channels
+            .list_channel_members(context.workspace_id, self.0.id)
+            .await
+            .map(|members| members.into_iter().map(|member| member.user_id).collect())
+            .map_err(graphql_error)
+    }
+}
+
+pub struct MessagePage(MessageListResponse);
+
+#[Object]
+impl MessagePage {
+    async fn items(&self) -> Vec<Message<'_>> {
+        self.0.items.iter().map(Message).collect()
+    }
+
+    async fn next_cursor(&self) -> Option<&str> {
+        self.0.next_cursor.as_deref()
+    }
+}
+
+pub struct Message<'a>(&'a MessageResponse);
+
+#[Object]
+impl Message<'_> {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn channel_id(&self) -> Uuid {
+        self.0.channel_id
+    }
+
+    async fn sender_id(&self) -> Uuid {
+        self.0.sender_id
+    }
+
+    async fn body_md(&self) -> &str {
+        &self.0.body_md
+    }
+
+    async fn thread_root_id(&self) -> Option<Uuid> {
+        self.0.thread_root_id
+    }
+
+    async fn seq(&self) -> i64 {
+        self.0.seq
+    }
+
+    async fn created_at(&self) -> i64 {
+        self.0.created_at
+    }
+
+    async fn edited_at(&self) -> Option<i64> {
+        self.0.edited_at
+    }
+
+    async fn attachments(&self) -> Vec<Attachment<'_>> {
+        self.0.attachments.iter().map(Attachment).collect()
+    }
+
+    async fn reactions(&self) -> Vec<Reaction<'_>> {
+        self.0.reactions.iter().flatten().map(Reaction).collect()
+    }
+
+    /// Thread replies, newest first; empty for messages that are replies.
+    async fn replies(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<usize>,
+        cursor: Option<String>,
+    ) -> async_graphql::Result<Option<MessagePage>> {
+        if self.0.thread_root_id.is_some() {
+            return Ok(None);
+        }
+        let (state, context) = request_scope(ctx)?;
+        state
+            .channels
+            .list_thread_replies(context, self.0.id, &page_query(limit, cursor))
+            .await
+            .map(|page| Some(MessagePage(page)))
+            .map_err(graphql_error)
+    }
+}
+
+pub struct Reaction<'a>(&'a MessageReactionSummary);
+
+#[Object]
+impl Reaction<'_> {
+    async fn emoji(&self) -> &str {
+        &self.0.emoji
+    }
+
+    async fn count(&self) -> usize {
+        self.0.count
+    }
+
+    async fn me(&self) -> bool {
+        self.0.me
+    }
+}
+
+pub struct Attachment<'a>(&'a AttachmentResponse);
+
+#[Object]
+impl Attachment<'_> {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn filename(&self) -> &str {
+        &self.0.filename
+    }
+
+    async fn content_type(&self) -> &str {
+        &self.0.content_type
+    }
+
+    async fn size_bytes(&self) -> u64 {
+        self.0.size_bytes
+    }
+
+    async fn thumbnail_key(&self) -> Option<&str> {
+        self.0.thumbnail_key.as_deref()
+    }
+}
+
+pub struct Thread(ThreadSummaryResponse);
+
+#[Object]
+impl Thread {
+    async fn root_message(&self) -> Message<'_> {
+        Message(&self.0.root_message)
+    }
+
+    async fn reply_count(&self) -> usize {
+        self.0.reply_count
+    }
+
+    async fn last_reply_at(&self) -> Option<i64> {
+        self.0.last_reply_at
+    }
+
+    async fn participants(&self) -> &[Uuid] {
+        &self.0.participants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn schema_rejects_queries_nested_past_the_depth_limit() {
+        let schema = schema();
+        let sdl = schema.sdl();
+        assert!(sdl.contains("messages(limit: Int, cursor: String): MessagePage!"));
+        assert!(!sdl.contains("type Mutation"));
+
+        let response = schema
+            .execute(
+                "{ channels { messages { items { replies { items { replies { items \
+                 { replies { items { id } } } } } } } } } }",
+            )
+            .await;
+        assert!(
+            response.errors[0].message.contains("nested too deep"),
+            "{:?}",
+            response.errors
+        );
+    }
+}
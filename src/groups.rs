@@ -0,0 +1,584 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, patch, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    notifications, realtime,
+    storage::{Storage, UserGroupRecordStore},
+};
+
+const HANDLE_MIN_CHARS: usize = 2;
+const HANDLE_MAX_CHARS: usize = 32;
+const NAME_MAX_CHARS: usize = 100;
+const MAX_GROUP_MEMBERS: usize = 500;
+
+#[derive(Clone)]
+pub struct GroupService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateGroupRequest {
+    /// Mention handle without `@`: lowercase letters, digits, `-` and `_`.
+    pub handle: String,
+    /// Display name; defaults to the handle.
+    pub name: Option<String>,
+    #[serde(default)]
+    pub member_ids: Vec<Uuid>,
+}
+
+/// Omitted fields are left unchanged; `member_ids` replaces the whole list.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateGroupRequest {
+    pub name: Option<String>,
+    pub member_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GroupResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub handle: String,
+    pub name: String,
+    pub member_ids: Vec<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupListResponse {
+    pub items: Vec<GroupResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddChannelGroupRequest {
+    pub group_id: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelGroupAddedResponse {
+    pub channel_id: Uuid,
+    pub group_id: Uuid,
+    /// Group members added to the channel; members who already left the
+    /// workspace are skipped.
+    pub added_user_ids: Vec<Uuid>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/groups", get(list_groups).post(create_group))
+        .route(
+            "/api/v1/groups/:id",
+            patch(update_group).delete(delete_group),
+        )
+        .route("/api/v1/channels/:id/groups", post(add_channel_group))
+}
+
+impl GroupService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn list(&self, workspace_id: Uuid) -> GroupListResponse {
+        let mut items = self
+            .storage
+            .list_user_groups(workspace_id)
+            .await
+            .iter()
+            .map(GroupResponse::from)
+            .collect::<Vec<_>>();
+        items.sort_by(|a, b| a.handle.cmp(&b.handle));
+        GroupListResponse { items }
+    }
+
+    pub async fn get(&self, workspace_id: Uuid, group_id: Uuid) -> ApiResult<UserGroupRecordStore> {
+        self.storage
+            .list_user_groups(workspace_id)
+            .await
+            .into_iter()
+            .find(|group| group.id == group_id)
+            .ok_or_else(|| ApiError::NotFound("group not found".to_string()))
+    }
+
+    pub async fn create(
+        &self,
+        context: &AuthContext,
+        payload: CreateGroupRequest,
+    ) -> ApiResult<GroupResponse> {
+        ensure_group_admin(context)?;
+        let handle = payload
+            .handle
+            .trim()
+            .trim_start_matches('@')
+            .to_ascii_lowercase();
+        let name = payload
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| handle.clone());
+        let mut errors = FieldErrors::default();
+        if !is_valid_handle(&handle) {
+            errors.add(
+                "handle",
+                format!(
+                    "must be {HANDLE_MIN_CHARS} to {HANDLE_MAX_CHARS} lowercase letters, digits, - or _"
+                ),
+            );
+        }
+        if name.chars().count() > NAME_MAX_CHARS {
+            errors.add(
+                "name",
+                format!("must be at most {NAME_MAX_CHARS} characters"),
+            );
+        }
+        let member_ids = self
+            .validate_members(context.workspace_id, payload.member_ids, &mut errors)
+            .await;
+        errors.into_result()?;
+        if self
+            .storage
+            .list_user_groups(context.workspace_id)
+            .await
+            .iter()
+            .any(|group| group.handle == handle)
+        {
+            return Err(ApiError::Conflict(
+                "group handle already exists".to_string(),
+            ));
+        }
+
+        let now = Utc::now().timestamp_millis();
+        let record = UserGroupRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id: context.workspace_id,
+            handle,
+            name,
+            member_ids,
+            created_by: context.user_id,
+            created_at: now,
+            updated_at: now,
+        };
+        self.storage.put_user_group(record.clone()).await;
+        Ok(GroupResponse::from(&record))
+    }
+
+    pub async fn update(
+        &self,
+        context: &AuthContext,
+        group_id: Uuid,
+        payload: UpdateGroupRequest,
+    ) -> ApiResult<GroupResponse> {
+        ensure_group_admin(context)?;
+        let mut group = self.get(context.workspace_id, group_id).await?;
+        let mut errors = FieldErrors::default();
+        if let Some(name) = payload.name {
+            let name = name.trim();
+            if name.chars().count() > NAME_MAX_CHARS {
+                errors.add(
+                    "name",
+                    format!("must be at most {NAME_MAX_CHARS} characters"),
+                );
+            }
+            group.name = if name.is_empty() {
+                group.handle.clone()
+            } else {
+                name.to_string()
+            };
+        }
+        if let Some(member_ids) = payload.member_ids {
+            group.member_ids = self
+                .validate_members(context.workspace_id, member_ids, &mut errors)
+                .await;
+        }
+        errors.into_result()?;
+
+        group.updated_at = Utc::now().timestamp_millis();
+        self.storage.put_user_group(group.clone()).await;
+        Ok(GroupResponse::from(&group))
+    }
+
+    pub async fn remove(&self, context: &AuthContext, group_id: Uuid) -> ApiResult<()> {
+        ensure_group_admin(context)?;
+        if !self
+            .storage
+            .remove_user_group(context.workspace_id, group_id)
+            .await
+        {
+            return Err(ApiError::NotFound("group not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Deduplicates `member_ids` and flags any that are not workspace members.
+    async fn validate_members(
+        &self,
+        workspace_id: Uuid,
+        member_ids: Vec<Uuid>,
+        errors: &mut FieldErrors,
+    ) -> Vec<Uuid> {
+        let workspace_members = self
+            .storage
+            .list_workspace_memberships(workspace_id)
+            .await
+            .into_iter()
+            .map(|(user_id, _)| user_id)
+            .collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        let member_ids = member_ids
+            .into_iter()
+            .filter(|user_id| seen.insert(*user_id))
+            .collect::<Vec<_>>();
+        if member_ids.len() > MAX_GROUP_MEMBERS {
+            errors.add(
+                "member_ids",
+                format!("at most {MAX_GROUP_MEMBERS} members per group"),
+            );
+        } else if let Some(unknown) = member_ids
+            .iter()
+            .find(|user_id| !workspace_members.contains(user_id))
+        {
+            errors.add(
+                "member_ids",
+                format!("user {unknown} does not belong to workspace"),
+            );
+        }
+        member_ids
+    }
+}
+
+impl From<&UserGroupRecordStore> for GroupResponse {
+    fn from(record: &UserGroupRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            handle: record.handle.clone(),
+            name: record.name.clone(),
+            member_ids: record.member_ids.clone(),
+            created_by: record.created_by,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+fn is_handle_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_'
+}
+
+fn is_valid_handle(handle: &str) -> bool {
+    (HANDLE_MIN_CHARS..=HANDLE_MAX_CHARS).contains(&handle.len())
+        && handle.chars().all(is_handle_char)
+}
+
+/// Lowercased handles written as `@handle` in a message body. An `@` preceded
+/// by a letter or digit (e-mail addresses) or by `<` (`<@user_id>`) is ignored.
+pub fn mentioned_group_handles(body: &str) -> Vec<String> {
+    let mut handles = Vec::new();
+    let mut previous = None;
+    for (index, c) in body.char_indices() {
+        if c == '@' && !previous.is_some_and(|p: char| p.is_alphanumeric() || p == '<') {
+            let handle = body[index + 1..]
+                .chars()
+                .map(|c| c.to_ascii_lowercase())
+                .take_while(|c| is_handle_char(*c))
+                .collect::<String>();
+            if is_valid_handle(&handle) && !handles.contains(&handle) {
+                handles.push(handle);
+            }
+        }
+        previous = Some(c);
+    }
+    handles
+}
+
+/// Members of every group whose `@handle` appears in `body`.
+pub(crate) async fn mentioned_group_members(
+    storage: &Storage,
+    workspace_id: Uuid,
+    body: &str,
+) -> Vec<Uuid> {
+    let handles = mentioned_group_handles(body);
+    if handles.is_empty() {
+        return Vec::new();
+    }
+    let mut members = Vec::new();
+    for group in storage.list_user_groups(workspace_id).await {
+        if handles.contains(&group.handle) {
+            for user_id in group.member_ids {
+                if !members.contains(&user_id) {
+                    members.push(user_id);
+                }
+            }
+        }
+    }
+    members
+}
+
+fn ensure_group_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to manage groups".to_string(),
+        )),
+    }
+}
+
+async fn publish(
+    state: &AppState,
+    context: &AuthContext,
+    event_type: &str,
+    payload: serde_json::Value,
+) {
+    state
+        .realtime
+        .emit(
+            context.workspace_id,
+            realtime::make_event(event_type, context.workspace_id, None, None, payload),
+        )
+        .await;
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/groups",
+    operation_id = "list_groups",
+    tag = "groups",
+    responses(
+        (status = 200, description = "Workspace user groups by handle", body = GroupListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_groups(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<GroupListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.groups.list(context.workspace_id).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/groups",
+    operation_id = "create_group",
+    tag = "groups",
+    request_body = CreateGroupRequest,
+    responses(
+        (status = 201, description = "Group created", body = GroupResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Handle already exists", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_group(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateGroupRequest>,
+) -> ApiResult<(StatusCode, Json<GroupResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let group = state.groups.create(&context, payload).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "GROUP_CREATED",
+            "group",
+            Some(group.id.to_string()),
+            json!({ "handle": group.handle, "members": group.member_ids.len() }),
+        )
+        .await;
+    publish(
+        &state,
+        &context,
+        "GROUP_UPDATED",
+        serde_json::to_value(&group).unwrap_or_default(),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/groups/{id}",
+    operation_id = "update_group",
+    tag = "groups",
+    request_body = UpdateGroupRequest,
+    responses(
+        (status = 200, description = "Group updated", body = GroupResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Group not found", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_group(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(group_id): Path<Uuid>,
+    Json(payload): Json<UpdateGroupRequest>,
+) -> ApiResult<Json<GroupResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let group = state.groups.update(&context, group_id, payload).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "GROUP_UPDATED",
+            "group",
+            Some(group.id.to_string()),
+            json!({ "name": group.name, "members": group.member_ids.len() }),
+        )
+        .await;
+    publish(
+        &state,
+        &context,
+        "GROUP_UPDATED",
+        serde_json::to_value(&group).unwrap_or_default(),
+    )
+    .await;
+    Ok(Json(group))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/groups/{id}",
+    operation_id = "delete_group",
+    tag = "groups",
+    responses(
+        (status = 204, description = "Group deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Group not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn delete_group(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(group_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state.groups.remove(&context, group_id).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "GROUP_DELETED",
+            "group",
+            Some(group_id.to_string()),
+            json!({}),
+        )
+        .await;
+    publish(&state, &context, "GROUP_REMOVED", json!({ "id": group_id })).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/{id}/groups",
+    operation_id = "add_channel_group",
+    tag = "groups",
+    request_body = AddChannelGroupRequest,
+    responses(
+        (status = 200, description = "Group members added to the channel", body = ChannelGroupAddedResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel or group not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn add_channel_group(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<Uuid>,
+    Json(payload): Json<AddChannelGroupRequest>,
+) -> ApiResult<Json<ChannelGroupAddedResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state
+        .channels
+        .ensure_channel_manager(&context, channel_id)
+        .await?;
+    let group = state
+        .groups
+        .get(context.workspace_id, payload.group_id)
+        .await?;
+    let mut added_user_ids = Vec::new();
+    for user_id in group.member_ids {
+        match state
+            .channels
+            .add_channel_member(context.workspace_id, channel_id, user_id)
+            .await
+        {
+            Ok(()) => added_user_ids.push(user_id),
+            Err(ApiError::BadRequest(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "CHANNEL_GROUP_ADDED",
+            "channel",
+            Some(channel_id.to_string()),
+            json!({ "group_id": group.id, "handle": group.handle, "added": added_user_ids.len() }),
+        )
+        .await;
+    for user_id in &added_user_ids {
+        notifications::notify_channel_invite(&state, &context, channel_id, *user_id).await;
+    }
+    Ok(Json(ChannelGroupAddedResponse {
+        channel_id,
+        group_id: group.id,
+        added_user_ids,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_handles_are_parsed_from_message_bodies() {
+        assert_eq!(
+            mentioned_group_handles(
+                "@Backend and @oncall, ping <@0196a2b0-0000-7000-8000-000000000000> or ops@corp.io (@backend)"
+            ),
+            vec!["backend", "oncall"]
+        );
+        assert!(mentioned_group_handles("@ @x mail@team").is_empty());
+        assert!(is_valid_handle("on-call_2"));
+        assert!(!is_valid_handle("On Call"));
+    }
+}
@@ -0,0 +1,295 @@
+use std::{net::SocketAddr, pin::Pin};
+
+use axum::http::StatusCode;
+use futures_util::{Stream, stream};
+use tokio::sync::broadcast;
+use tonic::{Code, Request, Response, Status, metadata::MetadataValue};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    analytics,
+    app::AppState,
+    auth::AuthContext,
+    channels::{self, MessageQuery, MessageResponse},
+    errors::{ApiError, ApiResult, ErrorCode},
+    realtime::WsEventEnvelope,
+    threads,
+};
+
+pub mod pb {
+    tonic::include_proto!("galynx.v1");
+}
+
+use pb::galynx_server::{Galynx, GalynxServer};
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<pb::Event, Status>> + Send>>;
+
+pub async fn serve(state: AppState, port: u16) {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    info!("starting galynx-api gRPC listener on http://{}", addr);
+    let service = GalynxServer::new(GrpcService { state });
+    tokio::spawn(async move {
+        if let Err(error) = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve(addr)
+            .await
+        {
+            tracing::error!(error = %error, "gRPC listener terminated with error");
+        }
+    });
+}
+
+/// Thin adapter over the same service functions as the axum handlers, so rate
+/// limits, moderation, audit and realtime fan-out behave identically.
+struct GrpcService {
+    state: AppState,
+}
+
+impl GrpcService {
+    async fn authenticate<T>(&self, request: &Request<T>) -> Result<AuthContext, Status> {
+        if self.state.maintenance.is_enabled() {
+            return Err(grpc_status(ApiError::Coded(
+                ErrorCode::MaintenanceMode,
+                self.state.maintenance.message(),
+            )));
+        }
+        let headers = request.metadata().clone().into_headers();
+        self.state
+            .auth
+            .authenticate_headers(&headers, &self.state.config.jwt_secret)
+            .await
+            .map_err(grpc_status)
+    }
+}
+
+#[tonic::async_trait]
+impl Galynx for GrpcService {
+    async fn send_message(
+        &self,
+        request: Request<pb::SendMessageRequest>,
+    ) -> Result<Response<pb::Message>, Status> {
+        let context = self.authenticate(&request).await?;
+        let payload = request.into_inner();
+        let channel_id = parse_id("channel_id", &payload.channel_id).map_err(grpc_status)?;
+        let message = match payload.thread_root_id {
+            Some(root_id) => {
+                let root_id = parse_id("thread_root_id", &root_id).map_err(grpc_status)?;
+                let thread = self
+                    .state
+                    .channels
+                    .thread_summary(&context, root_id)
+                    .await
+                    .map_err(grpc_status)?;
+                if thread.root_message.channel_id != channel_id {
+                    return Err(Status::invalid_argument(
+                        "thread_root_id does not belong to channel_id",
+                    ));
+                }
+                threads::post_reply(&self.state, &context, root_id, payload.body_md)
+                    .await
+                    .map_err(grpc_status)?
+            }
+            None => channels::post_message(&self.state, &context, channel_id, payload.body_md)
+                .await
+                .map_err(grpc_status)?,
+        };
+        Ok(Response::new(message.into()))
+    }
+
+    async fn list_messages(
+        &self,
+        request: Request<pb::ListMessagesRequest>,
+    ) -> Result<Response<pb::ListMessagesResponse>, Status> {
+        let context = self.authenticate(&request).await?;
+        let payload = request.into_inner();
+        let channel_id = parse_id("channel_id", &payload.channel_id).map_err(grpc_status)?;
+        let query = MessageQuery {
+            cursor: payload.cursor,
+            limit: payload.limit.map(|limit| limit as usize),
+            after_seq: payload.after_seq,
+        };
+        let page = self
+            .state
+            .channels
+            .list_messages(&context, channel_id, &query)
+            .await
+            .map_err(grpc_status)?;
+        Ok(Response::new(pb::ListMessagesResponse {
+            items: page.items.into_iter().map(Into::into).collect(),
+            next_cursor: page.next_cursor,
+        }))
+    }
+
+    type SubscribeEventsStream = EventStream;
+
+    async fn subscribe_events(
+        &self,
+        request: Request<pb::SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let context = self.authenticate(&request).await?;
+        let payload = request.into_inner();
+        let channel_ids = payload
+            .channel_ids
+            .iter()
+            .map(|id| parse_id("channel_ids", id))
+            .collect::<ApiResult<Vec<_>>>()
+            .map_err(grpc_status)?;
+        let filter = EventFilter {
+            user_id: context.user_id,
+            event_types: payload.event_types,
+            channel_ids,
+        };
+        let receiver = self.state.realtime.subscribe(context.workspace_id).await;
+        analytics::record_connection(&self.state, &context).await;
+        let events = stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if filter.matches(&event) => {
+                        return Some((Ok(event.into()), (receiver, filter)));
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("gRPC event stream lagged, skipped {} messages", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(events)))
+    }
+}
+
+struct EventFilter {
+    user_id: Uuid,
+    event_types: Vec<String>,
+    channel_ids: Vec<Uuid>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &WsEventEnvelope) -> bool {
+        if event
+            .recipient_id
+            .is_some_and(|recipient| recipient != self.user_id)
+        {
+            return false;
+        }
+        if !self.event_types.is_empty() && !self.event_types.contains(&event.event_type) {
+            return false;
+        }
+        self.channel_ids.is_empty()
+            || event
+                .channel_id
+                .is_some_and(|channel_id| self.channel_ids.contains(&channel_id))
+    }
+}
+
+fn parse_id(field: &str, value: &str) -> ApiResult<Uuid> {
+    Uuid::parse_str(value)
+        .map_err(|_| ApiError::BadRequest(format!("{field} must be a valid UUID")))
+}
+
+/// Maps API errors onto gRPC codes; the stable `ErrorCode` travels in the
+/// `error-code` metadata entry.
+fn grpc_status(error: ApiError) -> Status {
+    let code = match error.status_code() {
+        StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+        StatusCode::FORBIDDEN => Code::PermissionDenied,
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Code::InvalidArgument,
+        StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+        StatusCode::NOT_FOUND => Code::NotFound,
+        StatusCode::CONFLICT => Code::FailedPrecondition,
+        StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    let message = match &error {
+        ApiError::Validation(fields) => fields
+            .iter()
+            .map(|(field, message)| format!("{field}: {message}"))
+            .collect::<Vec<_>>()
+            .join("; "),
+        other => other.to_string(),
+    };
+    let mut status = Status::new(code, message);
+    if let Some(value) = serde_json::to_value(error.code()).ok().and_then(|value| {
+        value
+            .as_str()
+            .and_then(|code| MetadataValue::try_from(code).ok())
+    }) {
+        status.metadata_mut().insert("error-code", value);
+    }
+    status
+}
+
+impl From<MessageResponse> for pb::Message {
+    fn from(message: MessageResponse) -> Self {
+        Self {
+            id: message.id.to_string(),
+            workspace_id: message.workspace_id.to_string(),
+            channel_id: message.channel_id.to_string(),
+            sender_id: message.sender_id.to_string(),
+            body_md: message.body_md,
+            thread_root_id: message.thread_root_id.map(|id| id.to_string()),
+            seq: message.seq,
+            created_at: message.created_at,
+            edited_at: message.edited_at,
+            deleted_at: message.deleted_at,
+        }
+    }
+}
+
+impl From<WsEventEnvelope> for pb::Event {
+    fn from(event: WsEventEnvelope) -> Self {
+        Self {
+            event_type: event.event_type,
+            workspace_id: event.workspace_id.map(|id| id.to_string()),
+            channel_id: event.channel_id.map(|id| id.to_string()),
+            server_ts: event.server_ts,
+            payload_json: event.payload.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_errors_map_to_grpc_codes_with_error_code_metadata() {
+        let status = grpc_status(ApiError::NotFound("channel not found".to_string()));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "channel not found");
+        assert_eq!(status.metadata().get("error-code").unwrap(), "NOT_FOUND");
+
+        let status = grpc_status(ApiError::Coded(
+            ErrorCode::RateLimited,
+            "slow down".to_string(),
+        ));
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.metadata().get("error-code").unwrap(), "RATE_LIMITED");
+
+        let user_id = Uuid::new_v4();
+        let channel_id = Uuid::new_v4();
+        let filter = EventFilter {
+            user_id,
+            event_types: vec!["MESSAGE_CREATED".to_string()],
+            channel_ids: vec![channel_id],
+        };
+        let event = crate::realtime::make_event(
+            "MESSAGE_CREATED",
+            Uuid::new_v4(),
+            Some(channel_id),
+            None,
+            serde_json::json!({}),
+        );
+        assert!(filter.matches(&event));
+        assert!(!filter.matches(&WsEventEnvelope {
+            recipient_id: Some(Uuid::new_v4()),
+            ..event.clone()
+        }));
+        assert!(!filter.matches(&WsEventEnvelope {
+            channel_id: None,
+            ..event
+        }));
+    }
+}
@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthContext,
+    errors::{ApiError, ApiResult, ErrorCode},
+    storage::{IdempotencyRecordStore, Storage},
+};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+const MAX_KEY_LEN: usize = 255;
+const RETENTION_MS: i64 = 24 * 60 * 60 * 1000;
+/// A key still pending after this long belongs to a request that never
+/// finished (e.g. its instance crashed) and can be claimed again.
+const PENDING_TIMEOUT_MS: i64 = 60 * 1000;
+
+/// Per-request idempotency scope. Keys are namespaced by workspace, user and
+/// operation so the same client key can be reused across endpoints.
+///
+/// `begin` reserves the key before the operation runs; the reservation is
+/// turned into the stored response by `respond`, or released when the scope
+/// is dropped without one (the handler returned an error).
+#[derive(Default)]
+pub struct IdempotencyScope {
+    workspace_id: Uuid,
+    storage_key: Option<String>,
+    request_hash: String,
+    reservation: Option<(Arc<Storage>, Uuid)>,
+}
+
+pub fn scope(
+    headers: &HeaderMap,
+    context: &AuthContext,
+    operation: &str,
+    request: &impl Serialize,
+) -> ApiResult<IdempotencyScope> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(IdempotencyScope::default());
+    };
+    let key = value
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| ApiError::BadRequest("invalid idempotency-key header".to_string()))?;
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "idempotency-key must have between 1 and {MAX_KEY_LEN} characters"
+        )));
+    }
+
+    let request = serde_json::to_vec(request).unwrap_or_default();
+    Ok(IdempotencyScope {
+        workspace_id: context.workspace_id,
+        storage_key: Some(format!(
+            "{}:{}:{operation}:{key}",
+            context.workspace_id, context.user_id
+        )),
+        request_hash: format!("{:x}", Sha256::digest(&request)),
+        reservation: None,
+    })
+}
+
+impl IdempotencyScope {
+    /// Reserves the key for this request. Returns the stored response when the
+    /// key was already used with the same body, `409` while that first request
+    /// is still running and `422` when the body differs.
+    pub async fn begin(&mut self, storage: &Arc<Storage>) -> ApiResult<Option<Response>> {
+        let Some(key) = self.storage_key.as_deref() else {
+            return Ok(None);
+        };
+        let now = Utc::now().timestamp_millis();
+        let id = Uuid::new_v4();
+        let holder = storage
+            .reserve_idempotency_key(
+                key,
+                IdempotencyRecordStore {
+                    id,
+                    workspace_id: self.workspace_id,
+                    request_hash: self.request_hash.clone(),
+                    status: None,
+                    body: Value::Null,
+                    created_at: now,
+                },
+                |holder| {
+                    let timeout = match holder.status {
+                        Some(_) => RETENTION_MS,
+                        None => PENDING_TIMEOUT_MS,
+                    };
+                    now - holder.created_at > timeout
+                },
+            )
+            .await;
+        let Some(holder) = holder else {
+            self.reservation = Some((storage.clone(), id));
+            return Ok(None);
+        };
+
+        // Records stored before request hashes were kept have an empty one.
+        if !holder.request_hash.is_empty() && holder.request_hash != self.request_hash {
+            return Err(ApiError::Coded(
+                ErrorCode::IdempotencyKeyReused,
+                "idempotency-key was already used with a different request body".to_string(),
+            ));
+        }
+        let Some(status) = holder
+            .status
+            .and_then(|status| StatusCode::from_u16(status).ok())
+        else {
+            return Err(ApiError::Coded(
+                ErrorCode::IdempotencyKeyInUse,
+                "a request with this idempotency-key is still in progress".to_string(),
+            ));
+        };
+        let mut response = (status, Json(holder.body)).into_response();
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        Ok(Some(response))
+    }
+
+    pub async fn respond<T: Serialize>(
+        mut self,
+        storage: &Storage,
+        status: StatusCode,
+        body: &T,
+    ) -> Response {
+        let body = serde_json::to_value(body).unwrap_or_default();
+        if let Some(key) = self.storage_key.as_deref() {
+            let id = self
+                .reservation
+                .as_ref()
+                .map_or_else(Uuid::new_v4, |(_, id)| *id);
+            match storage
+                .put_idempotency_record(
+                    key,
+                    IdempotencyRecordStore {
+                        id,
+                        workspace_id: self.workspace_id,
+                        request_hash: self.request_hash.clone(),
+                        status: Some(status.as_u16()),
+                        body: body.clone(),
+                        created_at: Utc::now().timestamp_millis(),
+                    },
+                )
+                .await
+            {
+                Ok(()) => self.reservation = None,
+                Err(error) => tracing::warn!("idempotent response not stored: {}", error),
+            }
+        }
+        (status, Json(body)).into_response()
+    }
+}
+
+impl Drop for IdempotencyScope {
+    fn drop(&mut self) {
+        let (Some(key), Some((storage, id))) = (self.storage_key.take(), self.reservation.take())
+        else {
+            return;
+        };
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move { storage.release_idempotency_key(&key, id).await });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::WorkspaceRole, storage::PersistenceBackend};
+
+    fn context() -> AuthContext {
+        AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            role: WorkspaceRole::Member,
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_are_reserved_then_replayed_for_the_same_body() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage"),
+        );
+        let context = context();
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static("retry-1"));
+        let request = serde_json::json!({ "body_md": "hi" });
+        let status = |result: ApiResult<Option<Response>>| match result {
+            Ok(response) => response.map(|response| response.status()),
+            Err(error) => Some(error.status_code()),
+        };
+
+        let mut first = scope(&headers, &context, "create_message", &request).expect("valid key");
+        assert_eq!(status(first.begin(&storage).await), None);
+        let mut duplicate =
+            scope(&headers, &context, "create_message", &request).expect("valid key");
+        assert_eq!(
+            status(duplicate.begin(&storage).await),
+            Some(StatusCode::CONFLICT)
+        );
+        first
+            .respond(
+                &storage,
+                StatusCode::CREATED,
+                &serde_json::json!({ "id": 1 }),
+            )
+            .await;
+
+        let replayed = duplicate
+            .begin(&storage)
+            .await
+            .expect("same body")
+            .expect("replayed response");
+        assert_eq!(replayed.status(), StatusCode::CREATED);
+        assert_eq!(
+            replayed.headers().get(IDEMPOTENT_REPLAYED_HEADER),
+            Some(&HeaderValue::from_static("true"))
+        );
+        let mut changed = scope(
+            &headers,
+            &context,
+            "create_message",
+            &serde_json::json!({ "body_md": "bye" }),
+        )
+        .expect("valid key");
+        assert_eq!(
+            status(changed.begin(&storage).await),
+            Some(StatusCode::UNPROCESSABLE_ENTITY)
+        );
+
+        let mut failed = scope(&headers, &context, "create_channel", &request).expect("valid key");
+        assert_eq!(status(failed.begin(&storage).await), None);
+        drop(failed);
+        tokio::task::yield_now().await;
+        let mut retried = scope(&headers, &context, "create_channel", &request).expect("valid key");
+        assert_eq!(status(retried.begin(&storage).await), None);
+    }
+}
@@ -0,0 +1,233 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    routing::get,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    rate_limit::IpCidr,
+    storage::IpAllowlistRecordStore,
+    workspaces::ensure_context_workspace,
+};
+
+const MAX_ALLOWLIST_ENTRIES: usize = 100;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateIpAllowlistRequest {
+    /// CIDR ranges or single addresses; an empty list lifts the restriction.
+    pub cidrs: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IpAllowlistResponse {
+    pub workspace_id: Uuid,
+    pub cidrs: Vec<String>,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: Option<i64>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/api/v1/workspaces/:id/ip-allowlist",
+        get(get_ip_allowlist).put(update_ip_allowlist),
+    )
+}
+
+/// Whether `client_ip` may authenticate under `cidrs`. Unresolvable addresses
+/// are only accepted while the list is empty.
+fn is_allowed(cidrs: &[String], client_ip: &str) -> bool {
+    if cidrs.is_empty() {
+        return true;
+    }
+    let Ok(ip) = client_ip.parse::<IpAddr>() else {
+        return false;
+    };
+    cidrs
+        .iter()
+        .filter_map(|cidr| IpCidr::parse(cidr))
+        .any(|cidr| cidr.contains(ip))
+}
+
+/// Rejects authentication from addresses outside the workspace allowlist and
+/// records the attempt; `stage` is `login`, `refresh` or `ws`.
+pub(crate) async fn enforce(
+    state: &AppState,
+    context: &AuthContext,
+    client_ip: &str,
+    stage: &str,
+) -> ApiResult<()> {
+    let Some(allowlist) = state.storage.get_ip_allowlist(context.workspace_id).await else {
+        return Ok(());
+    };
+    if is_allowed(&allowlist.cidrs, client_ip) {
+        return Ok(());
+    }
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "AUTH_IP_BLOCKED",
+            "user",
+            Some(context.user_id.to_string()),
+            json!({ "ip": client_ip, "stage": stage }),
+        )
+        .await;
+    Err(ApiError::Coded(
+        ErrorCode::IpNotAllowed,
+        "your address is not allowed to access this workspace".to_string(),
+    ))
+}
+
+fn response(workspace_id: Uuid, record: Option<IpAllowlistRecordStore>) -> IpAllowlistResponse {
+    match record {
+        Some(record) => IpAllowlistResponse {
+            workspace_id,
+            cidrs: record.cidrs,
+            updated_by: Some(record.updated_by),
+            updated_at: Some(record.updated_at),
+        },
+        None => IpAllowlistResponse {
+            workspace_id,
+            cidrs: Vec::new(),
+            updated_by: None,
+            updated_at: None,
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/workspaces/{id}/ip-allowlist",
+    operation_id = "get_ip_allowlist",
+    tag = "workspaces",
+    responses(
+        (status = 200, description = "IP allowlist in effect", body = IpAllowlistResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_ip_allowlist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+) -> ApiResult<Json<IpAllowlistResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    if !matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin) {
+        return Err(ApiError::Forbidden(
+            "you do not have permission to view the IP allowlist".to_string(),
+        ));
+    }
+    let record = state.storage.get_ip_allowlist(workspace_id).await;
+    Ok(Json(response(workspace_id, record)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/workspaces/{id}/ip-allowlist",
+    operation_id = "update_ip_allowlist",
+    tag = "workspaces",
+    request_body = UpdateIpAllowlistRequest,
+    responses(
+        (status = 200, description = "IP allowlist replaced", body = IpAllowlistResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_ip_allowlist(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<UpdateIpAllowlistRequest>,
+) -> ApiResult<Json<IpAllowlistResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    if context.role != WorkspaceRole::Owner {
+        return Err(ApiError::Forbidden(
+            "only workspace owners can change the IP allowlist".to_string(),
+        ));
+    }
+
+    let mut errors = FieldErrors::default();
+    if payload.cidrs.len() > MAX_ALLOWLIST_ENTRIES {
+        errors.add(
+            "cidrs",
+            format!("must contain at most {MAX_ALLOWLIST_ENTRIES} entries"),
+        );
+    }
+    let mut cidrs = Vec::with_capacity(payload.cidrs.len());
+    for (index, value) in payload.cidrs.iter().enumerate() {
+        let value = value.trim();
+        if IpCidr::parse(value).is_none() {
+            errors.add(&format!("cidrs[{index}]"), "must be an IP address or CIDR");
+        } else if !cidrs.iter().any(|cidr| cidr == value) {
+            cidrs.push(value.to_string());
+        }
+    }
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    if !is_allowed(&cidrs, &client_ip) {
+        errors.add(
+            "cidrs",
+            format!("must include your current address ({client_ip})"),
+        );
+    }
+    errors.into_result()?;
+
+    let record = IpAllowlistRecordStore {
+        workspace_id,
+        cidrs,
+        updated_by: context.user_id,
+        updated_at: Utc::now().timestamp_millis(),
+    };
+    state.storage.put_ip_allowlist(record.clone()).await;
+    state
+        .audit
+        .write(
+            workspace_id,
+            Some(context.user_id),
+            "IP_ALLOWLIST_UPDATED",
+            "workspace",
+            Some(workspace_id.to_string()),
+            json!({ "cidrs": record.cidrs }),
+        )
+        .await;
+    Ok(Json(response(workspace_id, Some(record))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowlist_matches_ranges_and_rejects_unknown_addresses() {
+        let cidrs = vec!["10.0.0.0/8".to_string(), "2001:db8::/32".to_string()];
+        assert!(is_allowed(&cidrs, "10.1.2.3"));
+        assert!(is_allowed(&cidrs, "2001:db8::1"));
+        assert!(is_allowed(&cidrs, "::ffff:10.9.9.9"));
+        assert!(!is_allowed(&cidrs, "192.168.1.1"));
+        assert!(!is_allowed(&cidrs, "unknown"));
+        assert!(is_allowed(&[], "unknown"));
+    }
+}
@@ -0,0 +1,463 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use aho_corasick::AhoCorasick;
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::AuthContext,
+    channels::MessageResponse,
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    notifications::{NewNotification, NotificationKind},
+    realtime,
+    storage::{KeywordSubscriptionRecordStore, Storage},
+};
+
+const MAX_KEYWORDS_PER_USER: usize = 50;
+const KEYWORD_MIN_CHARS: usize = 2;
+const KEYWORD_MAX_CHARS: usize = 100;
+/// Other replicas may add subscriptions, so cached matchers are rebuilt
+/// periodically even without a local change.
+const MATCHER_TTL: Duration = Duration::from_secs(30);
+
+pub struct KeywordService {
+    storage: Arc<Storage>,
+    matchers: RwLock<HashMap<Uuid, Arc<KeywordMatcher>>>,
+}
+
+/// All keywords of one workspace compiled into a single automaton, so a
+/// message is scanned once regardless of how many users subscribed.
+struct KeywordMatcher {
+    automaton: Option<AhoCorasick>,
+    keywords: Vec<String>,
+    subscribers: Vec<Vec<Uuid>>,
+    built_at: Instant,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateKeywordRequest {
+    pub keyword: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct KeywordSubscriptionResponse {
+    pub id: Uuid,
+    pub keyword: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KeywordListResponse {
+    pub items: Vec<KeywordSubscriptionResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/me/keywords",
+            get(list_keywords).post(create_keyword),
+        )
+        .route("/api/v1/me/keywords/:id", delete(delete_keyword))
+}
+
+impl KeywordService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self {
+            storage,
+            matchers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn list(&self, context: &AuthContext) -> KeywordListResponse {
+        let mut items = self
+            .storage
+            .list_keyword_subscriptions(context.workspace_id)
+            .await
+            .into_iter()
+            .filter(|record| record.user_id == context.user_id)
+            .map(|record| KeywordSubscriptionResponse::from(&record))
+            .collect::<Vec<_>>();
+        items.sort_by(|a, b| a.keyword.cmp(&b.keyword));
+        KeywordListResponse { items }
+    }
+
+    pub async fn add(
+        &self,
+        context: &AuthContext,
+        payload: CreateKeywordRequest,
+    ) -> ApiResult<KeywordSubscriptionResponse> {
+        let keyword = normalize(&payload.keyword);
+        let mut errors = FieldErrors::default();
+        let length = keyword.chars().count();
+        if !(KEYWORD_MIN_CHARS..=KEYWORD_MAX_CHARS).contains(&length) {
+            errors.add(
+                "keyword",
+                format!("must be between {KEYWORD_MIN_CHARS} and {KEYWORD_MAX_CHARS} characters"),
+            );
+        }
+        let existing = self
+            .storage
+            .list_keyword_subscriptions(context.workspace_id)
+            .await
+            .into_iter()
+            .filter(|record| record.user_id == context.user_id)
+            .collect::<Vec<_>>();
+        if existing.len() >= MAX_KEYWORDS_PER_USER {
+            errors.add(
+                "keyword",
+                format!("at most {MAX_KEYWORDS_PER_USER} keywords per user"),
+            );
+        }
+        errors.into_result()?;
+        if existing.iter().any(|record| record.keyword == keyword) {
+            return Err(ApiError::Conflict("keyword already registered".to_string()));
+        }
+
+        let record = KeywordSubscriptionRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id: context.workspace_id,
+            user_id: context.user_id,
+            keyword,
+            created_at: Utc::now().timestamp_millis(),
+        };
+        self.storage.put_keyword_subscription(record.clone()).await;
+        self.invalidate(context.workspace_id).await;
+        Ok(KeywordSubscriptionResponse::from(&record))
+    }
+
+    pub async fn remove(&self, context: &AuthContext, subscription_id: Uuid) -> ApiResult<()> {
+        if !self
+            .storage
+            .remove_keyword_subscription(context.workspace_id, context.user_id, subscription_id)
+            .await
+        {
+            return Err(ApiError::NotFound("keyword not found".to_string()));
+        }
+        self.invalidate(context.workspace_id).await;
+        Ok(())
+    }
+
+    /// Users whose keywords appear in `body`, with the keywords that matched.
+    pub async fn matches(&self, workspace_id: Uuid, body: &str) -> Vec<(Uuid, Vec<String>)> {
+        let matcher = self.matcher(workspace_id).await;
+        let mut by_user: HashMap<Uuid, BTreeSet<String>> = HashMap::new();
+        for index in matcher.find(body) {
+            for user_id in &matcher.subscribers[index] {
+                by_user
+                    .entry(*user_id)
+                    .or_default()
+                    .insert(matcher.keywords[index].clone());
+            }
+        }
+        by_user
+            .into_iter()
+            .map(|(user_id, keywords)| (user_id, keywords.into_iter().collect()))
+            .collect()
+    }
+
+    async fn matcher(&self, workspace_id: Uuid) -> Arc<KeywordMatcher> {
+        if let Some(matcher) = self.matchers.read().await.get(&workspace_id)
+            && matcher.built_at.elapsed() < MATCHER_TTL
+        {
+            return matcher.clone();
+        }
+        let subscriptions = self.storage.list_keyword_subscriptions(workspace_id).await;
+        let matcher = Arc::new(KeywordMatcher::build(&subscriptions));
+        self.matchers
+            .write()
+            .await
+            .insert(workspace_id, matcher.clone());
+        matcher
+    }
+
+    async fn invalidate(&self, workspace_id: Uuid) {
+        self.matchers.write().await.remove(&workspace_id);
+    }
+}
+
+impl KeywordMatcher {
+    fn build(subscriptions: &[KeywordSubscriptionRecordStore]) -> Self {
+        let mut grouped: HashMap<&str, Vec<Uuid>> = HashMap::new();
+        for subscription in subscriptions {
+            grouped
+                .entry(subscription.keyword.as_str())
+                .or_default()
+                .push(subscription.user_id);
+        }
+        let (keywords, subscribers): (Vec<String>, Vec<Vec<Uuid>>) = grouped
+            .into_iter()
+            .map(|(keyword, users)| (keyword.to_string(), users))
+            .unzip();
+        let automaton = if keywords.is_empty() {
+            None
+        } else {
+            AhoCorasick::new(&keywords).ok()
+        };
+        Self {
+            automaton,
+            keywords,
+            subscribers,
+            built_at: Instant::now(),
+        }
+    }
+
+    /// Indexes of keywords found in `body` as whole words.
+    fn find(&self, body: &str) -> HashSet<usize> {
+        let Some(automaton) = &self.automaton else {
+            return HashSet::new();
+        };
+        let haystack = normalize(body);
+        automaton
+            .find_overlapping_iter(&haystack)
+            .filter(|found| {
+                let before = haystack[..found.start()].chars().next_back();
+                let after = haystack[found.end()..].chars().next();
+                !before.is_some_and(char::is_alphanumeric)
+                    && !after.is_some_and(char::is_alphanumeric)
+            })
+            .map(|found| found.pattern().as_usize())
+            .collect()
+    }
+}
+
+impl From<&KeywordSubscriptionRecordStore> for KeywordSubscriptionResponse {
+    fn from(record: &KeywordSubscriptionRecordStore) -> Self {
+        Self {
+            id: record.id,
+            keyword: record.keyword.clone(),
+            created_at: record.created_at,
+        }
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Stores a `keyword_alert` notification and pushes a `KEYWORD_ALERT` event to
+/// every subscriber whose keywords match `message`, skipping its author,
+/// anyone in `already_notified` and users who cannot see the channel.
+pub(crate) async fn alert_keywords(
+    state: &AppState,
+    message: &MessageResponse,
+    already_notified: &HashSet<Uuid>,
+) {
+    for (user_id, keywords) in state
+        .keywords
+        .matches(message.workspace_id, &message.body_md)
+        .await
+    {
+        if user_id == message.sender_id
+            || already_notified.contains(&user_id)
+            || !state
+                .notifications
+                .can_view_channel(message.workspace_id, user_id, message.channel_id)
+                .await
+        {
+            continue;
+        }
+        let notification = state
+            .notifications
+            .create(NewNotification {
+                workspace_id: message.workspace_id,
+                user_id,
+                kind: NotificationKind::KeywordAlert,
+                actor_id: Some(message.sender_id),
+                channel_id: Some(message.channel_id),
+                message_id: Some(message.id),
+                body: message.body_md.clone(),
+            })
+            .await;
+        state
+            .realtime
+            .emit(
+                message.workspace_id,
+                realtime::make_user_event(
+                    "KEYWORD_ALERT",
+                    message.workspace_id,
+                    user_id,
+                    json!({ "keywords": keywords, "notification": notification }),
+                ),
+            )
+            .await;
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/keywords",
+    operation_id = "list_keywords",
+    tag = "keywords",
+    responses(
+        (status = 200, description = "Keywords the caller is alerted on", body = KeywordListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_keywords(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<KeywordListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.keywords.list(&context).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/keywords",
+    operation_id = "create_keyword",
+    tag = "keywords",
+    request_body = CreateKeywordRequest,
+    responses(
+        (status = 201, description = "Keyword registered", body = KeywordSubscriptionResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "Keyword already registered", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_keyword(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateKeywordRequest>,
+) -> ApiResult<(StatusCode, Json<KeywordSubscriptionResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let item = state.keywords.add(&context, payload).await?;
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/keywords/{id}",
+    operation_id = "delete_keyword",
+    tag = "keywords",
+    responses(
+        (status = 204, description = "Keyword removed"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Keyword not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn delete_keyword(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subscription_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state.keywords.remove(&context, subscription_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::WorkspaceRole, storage::PersistenceBackend};
+
+    #[tokio::test]
+    async fn keywords_match_whole_words_per_subscriber() {
+        let workspace_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let keywords = KeywordService::new(storage);
+        let ana = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let bruno = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        for (context, keyword) in [
+            (&ana, "Deploy"),
+            (&ana, "on  call"),
+            (&bruno, "deploy"),
+            (&bruno, "db"),
+        ] {
+            keywords
+                .add(
+                    context,
+                    CreateKeywordRequest {
+                        keyword: keyword.to_string(),
+                    },
+                )
+                .await
+                .expect("keyword should be added");
+        }
+        let duplicate = keywords
+            .add(
+                &ana,
+                CreateKeywordRequest {
+                    keyword: "DEPLOY".to_string(),
+                },
+            )
+            .await;
+        assert!(matches!(duplicate, Err(ApiError::Conflict(_))));
+
+        let mut found = keywords
+            .matches(
+                workspace_id,
+                "Who is ON CALL for the deploy? dbadmin says hi",
+            )
+            .await;
+        found.sort_by_key(|(user_id, _)| *user_id != ana.user_id);
+        assert_eq!(
+            found,
+            vec![
+                (
+                    ana.user_id,
+                    vec!["deploy".to_string(), "on call".to_string()]
+                ),
+                (bruno.user_id, vec!["deploy".to_string()]),
+            ]
+        );
+
+        let ana_deploy = keywords
+            .list(&ana)
+            .await
+            .items
+            .into_iter()
+            .find(|item| item.keyword == "deploy")
+            .expect("deploy keyword should be listed");
+        keywords
+            .remove(&ana, ana_deploy.id)
+            .await
+            .expect("keyword should be removed");
+        assert!(
+            keywords
+                .matches(workspace_id, "deploy done")
+                .await
+                .iter()
+                .all(|(user_id, _)| *user_id == bruno.user_id)
+        );
+    }
+}
@@ -0,0 +1,469 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    storage::{DmMessageRecordStore, LegalHoldRecordStore, MessageRecordStore, Storage},
+};
+
+const REASON_MAX_CHARS: usize = 500;
+
+#[derive(Clone)]
+pub struct LegalHoldService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LegalHoldScope {
+    Workspace,
+    Channel,
+    User,
+}
+
+impl LegalHoldScope {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Workspace => "workspace",
+            Self::Channel => "channel",
+            Self::User => "user",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "channel" => Self::Channel,
+            "user" => Self::User,
+            _ => Self::Workspace,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateLegalHoldRequest {
+    pub scope: LegalHoldScope,
+    /// Channel or user id; ignored for `workspace` holds.
+    pub target_id: Option<Uuid>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LegalHoldResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub scope: LegalHoldScope,
+    pub target_id: Uuid,
+    pub reason: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LegalHoldListResponse {
+    pub items: Vec<LegalHoldResponse>,
+}
+
+/// Active holds of a workspace, loaded once per operation that deletes content.
+#[derive(Debug, Default)]
+pub struct LegalHolds {
+    workspace: bool,
+    channels: Vec<Uuid>,
+    users: Vec<Uuid>,
+}
+
+impl LegalHolds {
+    pub async fn load(storage: &Storage, workspace_id: Uuid) -> Self {
+        let mut holds = Self::default();
+        for record in storage.list_legal_holds(workspace_id).await {
+            match LegalHoldScope::parse(&record.scope) {
+                LegalHoldScope::Workspace => holds.workspace = true,
+                LegalHoldScope::Channel => holds.channels.push(record.target_id),
+                LegalHoldScope::User => holds.users.push(record.target_id),
+            }
+        }
+        holds
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.workspace && self.channels.is_empty() && self.users.is_empty()
+    }
+
+    pub fn covers_channel(&self, channel_id: Uuid) -> bool {
+        self.workspace || self.channels.contains(&channel_id)
+    }
+
+    pub fn covers_user(&self, user_id: Uuid) -> bool {
+        self.workspace || self.users.contains(&user_id)
+    }
+
+    pub fn covers_message(&self, message: &MessageRecordStore) -> bool {
+        self.covers_channel(message.channel_id) || self.users.contains(&message.sender_id)
+    }
+
+    /// DMs have no channel, so only workspace and sender holds apply.
+    pub fn covers_dm_message(&self, message: &DmMessageRecordStore) -> bool {
+        self.covers_user(message.sender_id)
+    }
+}
+
+pub fn legal_hold_error() -> ApiError {
+    ApiError::Coded(
+        ErrorCode::LegalHold,
+        "content is under legal hold".to_string(),
+    )
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/admin/legal-holds",
+            get(list_legal_holds).post(create_legal_hold),
+        )
+        .route("/api/v1/admin/legal-holds/:id", delete(release_legal_hold))
+}
+
+impl LegalHoldService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn list(&self, context: &AuthContext) -> ApiResult<LegalHoldListResponse> {
+        ensure_workspace_admin(context)?;
+        let mut records = self.storage.list_legal_holds(context.workspace_id).await;
+        records.sort_by_key(|record| std::cmp::Reverse(record.created_at));
+        Ok(LegalHoldListResponse {
+            items: records.iter().map(LegalHoldResponse::from).collect(),
+        })
+    }
+
+    pub async fn create(
+        &self,
+        context: &AuthContext,
+        payload: CreateLegalHoldRequest,
+    ) -> ApiResult<LegalHoldResponse> {
+        ensure_workspace_admin(context)?;
+        let reason = payload
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+        let mut errors = FieldErrors::default();
+        if reason
+            .as_ref()
+            .is_some_and(|reason| reason.chars().count() > REASON_MAX_CHARS)
+        {
+            errors.add(
+                "reason",
+                format!("must be at most {REASON_MAX_CHARS} characters"),
+            );
+        }
+        if payload.scope != LegalHoldScope::Workspace && payload.target_id.is_none() {
+            errors.add("target_id", "is required for channel and user holds");
+        }
+        errors.into_result()?;
+        let target_id = payload
+            .target_id
+            .filter(|_| payload.scope != LegalHoldScope::Workspace)
+            .unwrap_or(context.workspace_id);
+
+        let exists = match payload.scope {
+            LegalHoldScope::Workspace => true,
+            LegalHoldScope::Channel => self
+                .storage
+                .get_channel(&target_id)
+                .await
+                .is_some_and(|channel| channel.workspace_id == context.workspace_id),
+            LegalHoldScope::User => self
+                .storage
+                .get_membership_role(context.workspace_id, target_id)
+                .await
+                .is_some(),
+        };
+        if !exists {
+            return Err(ApiError::NotFound(format!(
+                "{} not found",
+                payload.scope.as_str()
+            )));
+        }
+        if self
+            .storage
+            .list_legal_holds(context.workspace_id)
+            .await
+            .iter()
+            .any(|hold| hold.scope == payload.scope.as_str() && hold.target_id == target_id)
+        {
+            return Err(ApiError::Conflict(
+                "a legal hold already covers this target".to_string(),
+            ));
+        }
+
+        let record = LegalHoldRecordStore {
+            id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            workspace_id: context.workspace_id,
+            scope: payload.scope.as_str().to_string(),
+            target_id,
+            reason,
+            created_by: context.user_id,
+            created_at: Utc::now().timestamp_millis(),
+        };
+        self.storage.put_legal_hold(record.clone()).await;
+        Ok(LegalHoldResponse::from(&record))
+    }
+
+    pub async fn release(
+        &self,
+        context: &AuthContext,
+        hold_id: Uuid,
+    ) -> ApiResult<LegalHoldResponse> {
+        ensure_workspace_admin(context)?;
+        self.storage
+            .remove_legal_hold(context.workspace_id, hold_id)
+            .await
+            .map(|record| LegalHoldResponse::from(&record))
+            .ok_or_else(|| ApiError::NotFound("legal hold not found".to_string()))
+    }
+}
+
+impl From<&LegalHoldRecordStore> for LegalHoldResponse {
+    fn from(record: &LegalHoldRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            scope: LegalHoldScope::parse(&record.scope),
+            target_id: record.target_id,
+            reason: record.reason.clone(),
+            created_by: record.created_by,
+            created_at: record.created_at,
+        }
+    }
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to manage legal holds".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/legal-holds",
+    operation_id = "list_legal_holds",
+    tag = "legal_holds",
+    responses(
+        (status = 200, description = "Active legal holds, newest first", body = LegalHoldListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_legal_holds(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<LegalHoldListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.legal_holds.list(&context).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/legal-holds",
+    operation_id = "create_legal_hold",
+    tag = "legal_holds",
+    request_body = CreateLegalHoldRequest,
+    responses(
+        (status = 201, description = "Legal hold placed", body = LegalHoldResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel or user not found", body = ErrorResponse),
+        (status = 409, description = "Target already on hold", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_legal_hold(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateLegalHoldRequest>,
+) -> ApiResult<(StatusCode, Json<LegalHoldResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let hold = state.legal_holds.create(&context, payload).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "LEGAL_HOLD_PLACED",
+            "legal_hold",
+            Some(hold.id.to_string()),
+            json!({
+                "scope": hold.scope,
+                "target_id": hold.target_id,
+                "reason": hold.reason,
+            }),
+        )
+        .await;
+    Ok((StatusCode::CREATED, Json(hold)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/legal-holds/{id}",
+    operation_id = "release_legal_hold",
+    tag = "legal_holds",
+    responses(
+        (status = 204, description = "Legal hold released"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Legal hold not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn release_legal_hold(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(hold_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let hold = state.legal_holds.release(&context, hold_id).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "LEGAL_HOLD_RELEASED",
+            "legal_hold",
+            Some(hold.id.to_string()),
+            json!({
+                "scope": hold.scope,
+                "target_id": hold.target_id,
+                "placed_by": hold.created_by,
+                "placed_at": hold.created_at,
+            }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{admin::purge_messages_before, storage::PersistenceBackend};
+
+    #[tokio::test]
+    async fn holds_freeze_purges_until_released() {
+        let workspace_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let holds = LegalHoldService::new(storage.clone());
+        let owner = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let held_user = Uuid::new_v4();
+        storage
+            .put_membership_role(workspace_id, held_user, "member")
+            .await;
+        let message = |sender_id| MessageRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id,
+            channel_id: Uuid::new_v4(),
+            sender_id,
+            body_md: "evidence".to_string(),
+            thread_root_id: None,
+            origin_workspace_id: None,
+            seq: 0,
+            created_at: 1_000,
+            edited_at: None,
+            deleted_at: None,
+            crosspost_group_id: None,
+        };
+        storage
+            .insert_message(message(held_user))
+            .await
+            .expect("message stored");
+        storage
+            .insert_message(message(owner.user_id))
+            .await
+            .expect("message stored");
+        storage
+            .write_dm_message_with_events(
+                DmMessageRecordStore {
+                    id: Uuid::new_v4(),
+                    conversation_id: Uuid::new_v4(),
+                    workspace_id,
+                    sender_id: held_user,
+                    body_md: "off the record".to_string(),
+                    created_at: 1_000,
+                },
+                Vec::new(),
+            )
+            .await
+            .expect("dm stored");
+
+        assert!(matches!(
+            holds
+                .create(
+                    &owner,
+                    CreateLegalHoldRequest {
+                        scope: LegalHoldScope::User,
+                        target_id: None,
+                        reason: None,
+                    },
+                )
+                .await,
+            Err(ApiError::Validation(_))
+        ));
+        let request = || CreateLegalHoldRequest {
+            scope: LegalHoldScope::User,
+            target_id: Some(held_user),
+            reason: Some("case 42".to_string()),
+        };
+        let hold = holds.create(&owner, request()).await.expect("hold placed");
+        assert!(matches!(
+            holds.create(&owner, request()).await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        assert_eq!(
+            purge_messages_before(&storage, workspace_id, 5_000, false).await,
+            (1, 2)
+        );
+
+        holds.release(&owner, hold.id).await.expect("hold released");
+        assert_eq!(
+            purge_messages_before(&storage, workspace_id, 5_000, false).await,
+            (2, 0)
+        );
+        assert!(
+            storage
+                .list_workspace_dm_messages(workspace_id)
+                .await
+                .is_empty()
+        );
+    }
+}
@@ -1,58 +1,277 @@
+mod access_requests;
+mod activity;
+mod admin;
+mod analytics;
+mod announcements;
+mod anonymize;
 mod app;
+mod apps;
 mod attachments;
 mod audit;
 mod auth;
+mod calls;
+mod channel_links;
+mod channel_templates;
 mod channels;
+mod conditional;
 mod config;
+mod dms;
+mod e2ee;
+mod email;
+mod email_verification;
+mod encryption;
 mod errors;
+mod event_subscriptions;
+mod graphql;
+mod groups;
+mod grpc;
+mod idempotency;
+mod ip_allowlists;
+mod keywords;
+mod legal_holds;
+mod moderation;
+mod moderation_cases;
+mod mutes;
+mod notifications;
 mod observability;
+mod operator;
+mod outbox;
+mod password_reset;
+mod presence;
 mod rate_limit;
 mod reactions;
 mod realtime;
+mod recurring_posts;
+mod reminders;
+mod search;
+mod session_limits;
+mod sessions;
 mod storage;
+mod sync;
 mod threads;
+mod usage;
 mod users;
+mod versioning;
 mod workspaces;
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
+
+use axum_server::tls_rustls::RustlsConfig;
+use clap::{Parser, Subcommand};
 
 use opentelemetry::KeyValue;
+use opentelemetry::global;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{
     Resource,
+    metrics::SdkMeterProvider,
     trace::{Sampler, SdkTracerProvider},
 };
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+#[derive(Parser, Debug)]
+#[command(name = "galynx-api", version, about = "Galynx API server")]
+struct Cli {
+    #[arg(long, help = "Port to listen on (overrides PORT)")]
+    port: Option<u16>,
+    #[arg(long, help = "TOML/YAML config file (overrides GALYNX_CONFIG)")]
+    config: Option<PathBuf>,
+    #[arg(
+        long,
+        value_parser = ["memory", "mongo"],
+        help = "Persistence backend (overrides PERSISTENCE_BACKEND)"
+    )]
+    backend: Option<String>,
+    #[arg(
+        long,
+        help = "Print the resolved configuration as JSON (secrets redacted) and exit"
+    )]
+    print_config: bool,
+    #[command(subcommand)]
+    command: Option<ServerCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ServerCommand {
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    Show,
+}
+
+impl Cli {
+    fn overrides(&self) -> config::ConfigOverrides {
+        let mut overrides = config::ConfigOverrides {
+            config_path: self.config.clone(),
+            ..Default::default()
+        };
+        if let Some(port) = self.port {
+            overrides.set("PORT", port);
+        }
+        if let Some(backend) = &self.backend {
+            overrides.set("PERSISTENCE_BACKEND", backend);
+        }
+        overrides
+    }
+}
+
 #[tokio::main]
 async fn main() {
-    let config = config::Config::from_env();
-    let _telemetry = setup_tracing(&config);
-    let app_state = app::build_state(config).await;
+    let cli = Cli::parse();
+    let config =
+        config::Config::load_with(cli.overrides(), app::CONFIG_CHECKS).unwrap_or_else(|error| {
+            eprintln!("galynx-api: {error}");
+            std::process::exit(1);
+        });
+    let show_config = matches!(
+        cli.command,
+        Some(ServerCommand::Config {
+            command: ConfigCommand::Show
+        })
+    );
+    if cli.print_config || show_config {
+        let rendered = serde_json::to_string_pretty(&config).expect("config serializes to json");
+        println!("{rendered}");
+        return;
+    }
+    let (_telemetry, log_filter) = setup_tracing(&config);
+    let app_state = app::build_state(config, log_filter)
+        .await
+        .unwrap_or_else(|error| {
+            eprintln!("galynx-api: {error}");
+            std::process::exit(1);
+        });
+    if app_state.config.migrate_on_startup {
+        match app_state.storage.migrate().await {
+            Ok(report) => info!(
+                "schema migrations: {} applied, {} already applied",
+                report.applied.len(),
+                report.already_applied.len()
+            ),
+            Err(error) => {
+                tracing::error!("schema migration failed: {}", error);
+                std::process::exit(1);
+            }
+        }
+    } else if let Ok(plan) = app_state.storage.schema_plan().await
+        && !plan.pending_migrations.is_empty()
+    {
+        tracing::warn!(
+            "pending schema migrations: {}; run `galynx-bootstrap migrate` or set MIGRATE_ON_STARTUP=true",
+            plan.pending_migrations.join(", ")
+        );
+    }
     let backend = app_state.storage.backend();
     let port = app_state.config.port;
+    let tls_paths = app_state
+        .config
+        .tls_cert_path
+        .clone()
+        .zip(app_state.config.tls_key_path.clone());
+    spawn_sighup_reload(app_state.clone());
+    reminders::spawn_delivery(app_state.clone());
+    recurring_posts::spawn_runner(app_state.clone());
+    event_subscriptions::spawn_webhook_delivery(app_state.clone());
+    outbox::spawn_dispatcher(app_state.clone());
+    notifications::spawn_email_digests(app_state.clone());
+    presence::spawn_ticker(app_state.clone());
+    if let Some(internal_port) = app_state.config.internal_port {
+        spawn_internal_listener(app_state.clone(), internal_port).await;
+    }
+    if let Some(grpc_port) = app_state.config.grpc_port {
+        grpc::serve(app_state.clone(), grpc_port).await;
+    }
     let app = app::router(app_state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    info!("starting galynx-api on {}", addr);
+    let scheme = if tls_paths.is_some() { "https" } else { "http" };
+    info!("starting galynx-api on {}://{}", scheme, addr);
     match backend {
         storage::PersistenceBackend::Memory => info!("persistence backend: memory"),
         storage::PersistenceBackend::Mongo => info!("persistence backend: mongo"),
     }
 
+    if let Some((cert_path, key_path)) = tls_paths {
+        // Several dependencies enable different rustls backends, so pick one explicitly.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let tls_config = RustlsConfig::from_pem_file(&cert_path, &key_path)
+            .await
+            .expect("failed to load TLS certificate or key");
+        axum_server::bind_rustls(addr, tls_config)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .expect("server terminated with error");
+        return;
+    }
+
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .expect("failed to bind address");
 
-    axum::serve(listener, app)
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("server terminated with error");
+}
+
+async fn spawn_internal_listener(state: app::AppState, port: u16) {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .expect("failed to bind internal address");
+    info!("starting galynx-api internal listener on http://{}", addr);
+    let app = app::internal_router(state);
+    tokio::spawn(async move {
+        if let Err(error) = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
         .await
-        .expect("server terminated with error");
+        {
+            tracing::error!(error = %error, "internal listener terminated with error");
+        }
+    });
 }
 
+#[cfg(unix)]
+fn spawn_sighup_reload(state: app::AppState) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(error) => {
+                warn!("failed to install SIGHUP handler: {}", error);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            match admin::reload_runtime_config(&state).await {
+                Ok(applied) => info!("configuration reloaded on SIGHUP: {:?}", applied),
+                Err(error) => warn!(
+                    "configuration reload failed, keeping current values: {}",
+                    error
+                ),
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_sighup_reload(_state: app::AppState) {}
+
 struct TelemetryGuard {
     provider: Option<SdkTracerProvider>,
+    meter_provider: Option<SdkMeterProvider>,
 }
 
 impl Drop for TelemetryGuard {
@@ -60,18 +279,39 @@ impl Drop for TelemetryGuard {
         if let Some(provider) = self.provider.take() {
             let _ = provider.shutdown();
         }
+        if let Some(meter_provider) = self.meter_provider.take() {
+            let _ = meter_provider.shutdown();
+        }
     }
 }
 
-fn setup_tracing(config: &config::Config) -> TelemetryGuard {
-    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "galynx_api=debug,tower_http=info".into());
-    let fmt_layer = tracing_subscriber::fmt::layer()
-        .with_target(false)
-        .compact();
+fn setup_tracing(config: &config::Config) -> (TelemetryGuard, observability::LogFilterHandle) {
+    let env_filter = config
+        .log_filter
+        .as_deref()
+        .and_then(|directives| tracing_subscriber::EnvFilter::try_new(directives).ok())
+        .or_else(|| tracing_subscriber::EnvFilter::try_from_default_env().ok())
+        .unwrap_or_else(|| "galynx_api=debug,tower_http=info".into());
+    let (env_filter, log_filter) = tracing_subscriber::reload::Layer::new(env_filter);
+    let json_logs = config.log_format == config::LogFormat::Json;
+    let compact_layer = (!json_logs).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_target(false)
+            .compact()
+    });
+    let json_layer = json_logs.then(|| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(false)
+            .with_span_list(true)
+    });
     let registry = tracing_subscriber::registry()
         .with(env_filter)
-        .with(fmt_layer);
+        .with(observability::SlowStorageLayer::new(
+            config.slow_operation_threshold_ms,
+        ))
+        .with(compact_layer)
+        .with(json_layer);
 
     if let Some(endpoint) = config
         .otel_exporter_otlp_endpoint
@@ -84,29 +324,50 @@ fn setup_tracing(config: &config::Config) -> TelemetryGuard {
             .build()
             .expect("failed to initialize OTLP exporter");
 
+        let resource = Resource::builder()
+            .with_service_name(config.otel_service_name.clone())
+            .with_attributes([KeyValue::new(
+                "service.version",
+                env!("CARGO_PKG_VERSION").to_string(),
+            )])
+            .build();
+
         let provider = SdkTracerProvider::builder()
-            .with_resource(
-                Resource::builder()
-                    .with_service_name(config.otel_service_name.clone())
-                    .with_attributes([KeyValue::new(
-                        "service.version",
-                        env!("CARGO_PKG_VERSION").to_string(),
-                    )])
-                    .build(),
-            )
+            .with_resource(resource.clone())
             .with_sampler(Sampler::TraceIdRatioBased(config.otel_sample_ratio))
             .with_batch_exporter(exporter)
             .build();
 
+        let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint.to_string())
+            .build()
+            .expect("failed to initialize OTLP metric exporter");
+        let meter_provider = SdkMeterProvider::builder()
+            .with_resource(resource)
+            .with_periodic_exporter(metric_exporter)
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
         let tracer = provider.tracer(config.otel_service_name.clone());
         let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
         registry.with(otel_layer).init();
-        info!("otlp tracing enabled");
-        TelemetryGuard {
-            provider: Some(provider),
-        }
+        info!("otlp tracing and metrics enabled");
+        (
+            TelemetryGuard {
+                provider: Some(provider),
+                meter_provider: Some(meter_provider),
+            },
+            log_filter,
+        )
     } else {
         registry.init();
-        TelemetryGuard { provider: None }
+        (
+            TelemetryGuard {
+                provider: None,
+                meter_provider: None,
+            },
+            log_filter,
+        )
     }
 }
@@ -0,0 +1,444 @@
+use std::time::Duration;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::AuthContext,
+    channels::MessageResponse,
+    config::Config,
+    e2ee,
+    errors::{ApiError, ApiResult, ErrorCode},
+};
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    Block,
+    Flag,
+    Redact,
+}
+
+impl ModerationAction {
+    fn from_config(value: &str) -> Self {
+        match value {
+            "block" => Self::Block,
+            "redact" => Self::Redact,
+            _ => Self::Flag,
+        }
+    }
+}
+
+/// One entry of `MODERATION_RULES_FILE`: either a regex `pattern` or a list of
+/// `words` matched on word boundaries.
+#[derive(Debug, Deserialize)]
+struct RuleSpec {
+    name: String,
+    #[serde(default)]
+    pattern: Option<String>,
+    #[serde(default)]
+    words: Vec<String>,
+    action: ModerationAction,
+    #[serde(default)]
+    case_sensitive: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Debug)]
+struct ModerationRule {
+    name: String,
+    matcher: Regex,
+    action: ModerationAction,
+}
+
+#[derive(Debug)]
+struct ExternalModeration {
+    url: String,
+    token: Option<String>,
+    action: ModerationAction,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExternalVerdict {
+    flagged: bool,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Intervention {
+    pub rule: String,
+    pub action: ModerationAction,
+    pub source: &'static str,
+}
+
+#[derive(Debug, Default)]
+pub struct ModerationOutcome {
+    pub body: String,
+    pub interventions: Vec<Intervention>,
+}
+
+impl ModerationOutcome {
+    pub fn blocked_by(&self) -> Option<&Intervention> {
+        self.interventions
+            .iter()
+            .find(|intervention| intervention.action == ModerationAction::Block)
+    }
+}
+
+/// Body that passed moderation. `flagged_by` names the first `flag` rule (or
+/// external reason) that matched, so a case can be opened once the message exists.
+#[derive(Debug)]
+pub struct ModeratedBody {
+    pub body: String,
+    pub flagged_by: Option<String>,
+}
+
+impl ModeratedBody {
+    pub(crate) fn unchanged(body: String) -> Self {
+        Self {
+            body,
+            flagged_by: None,
+        }
+    }
+
+    /// Opens a `flag` moderation case for the stored message when a rule flagged it.
+    pub(crate) async fn open_case(&self, state: &AppState, message: &MessageResponse) {
+        if let Some(rule) = &self.flagged_by {
+            state.moderation_cases.flag(message, rule).await;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModerationPipeline {
+    rules: Vec<ModerationRule>,
+    external: Option<ExternalModeration>,
+}
+
+impl ModerationPipeline {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let rules = match config.moderation_rules_file.as_deref() {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|error| format!("failed to read MODERATION_RULES_FILE: {error}"))?;
+                parse_rules(&contents)?
+            }
+            None => Vec::new(),
+        };
+        let external = config
+            .moderation_api_url
+            .clone()
+            .map(|url| {
+                reqwest::Client::builder()
+                    .timeout(Duration::from_millis(config.moderation_api_timeout_ms))
+                    .build()
+                    .map(|client| ExternalModeration {
+                        url,
+                        token: config.moderation_api_token.clone(),
+                        action: ModerationAction::from_config(&config.moderation_api_action),
+                        client,
+                    })
+                    .map_err(|error| format!("failed to build moderation API client: {error}"))
+            })
+            .transpose()?;
+        Ok(Self { rules, external })
+    }
+
+    /// Reports an unreadable or invalid `MODERATION_RULES_FILE` at startup.
+    pub(crate) fn check_config(config: &Config, problems: &mut Vec<String>) {
+        if let Err(problem) = Self::from_config(config) {
+            problems.push(problem);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.rules.is_empty() || self.external.is_some()
+    }
+
+    /// Runs every rule in order and then the external API. Redactions apply to
+    /// the body seen by later rules; a block short-circuits the rest.
+    pub async fn review(&self, body: &str) -> ModerationOutcome {
+        let mut outcome = ModerationOutcome {
+            body: body.to_string(),
+            interventions: Vec::new(),
+        };
+        for rule in &self.rules {
+            if !rule.matcher.is_match(&outcome.body) {
+                continue;
+            }
+            outcome.interventions.push(Intervention {
+                rule: rule.name.clone(),
+                action: rule.action,
+                source: "rule",
+            });
+            match rule.action {
+                ModerationAction::Block => return outcome,
+                ModerationAction::Redact => {
+                    outcome.body = rule
+                        .matcher
+                        .replace_all(&outcome.body, REDACTED)
+                        .into_owned();
+                }
+                ModerationAction::Flag => {}
+            }
+        }
+
+        if let Some(external) = &self.external
+            && let Some(reason) = external.check(&outcome.body).await
+        {
+            outcome.interventions.push(Intervention {
+                rule: reason,
+                action: external.action,
+                source: "external",
+            });
+            if external.action == ModerationAction::Redact {
+                outcome.body = REDACTED.to_string();
+            }
+        }
+        outcome
+    }
+}
+
+impl ExternalModeration {
+    /// Returns the reason when the API flags `text`. Errors fail open so an
+    /// unavailable provider never blocks messaging.
+    async fn check(&self, text: &str) -> Option<String> {
+        let mut request = self.client.post(&self.url).json(&json!({ "text": text }));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let verdict = match request.send().await {
+            Ok(response) => match response.error_for_status() {
+                Ok(response) => response.json::<ExternalVerdict>().await,
+                Err(error) => Err(error),
+            },
+            Err(error) => Err(error),
+        };
+        match verdict {
+            Ok(verdict) if verdict.flagged => {
+                Some(verdict.reason.unwrap_or_else(|| "external".to_string()))
+            }
+            Ok(_) => None,
+            Err(error) => {
+                tracing::warn!("moderation API call failed, allowing message: {}", error);
+                None
+            }
+        }
+    }
+}
+
+fn parse_rules(contents: &str) -> Result<Vec<ModerationRule>, String> {
+    let file: RulesFile = serde_yaml::from_str(contents)
+        .map_err(|error| format!("invalid MODERATION_RULES_FILE: {error}"))?;
+    file.rules
+        .into_iter()
+        .map(|spec| {
+            let pattern = match (&spec.pattern, spec.words.is_empty()) {
+                (Some(pattern), true) => pattern.clone(),
+                (None, false) => format!(
+                    r"\b(?:{})\b",
+                    spec.words
+                        .iter()
+                        .map(|word| regex::escape(word.trim()))
+                        .collect::<Vec<_>>()
+                        .join("|")
+                ),
+                _ => {
+                    return Err(format!(
+                        "moderation rule {:?} needs exactly one of pattern or words",
+                        spec.name
+                    ));
+                }
+            };
+            let matcher = RegexBuilder::new(&pattern)
+                .case_insensitive(!spec.case_sensitive)
+                .build()
+                .map_err(|error| format!("moderation rule {:?}: {error}", spec.name))?;
+            Ok(ModerationRule {
+                name: spec.name,
+                matcher,
+                action: spec.action,
+            })
+        })
+        .collect()
+}
+
+/// Moderates a message body before it is stored, auditing every intervention.
+/// Returns the (possibly redacted) body or `CONTENT_BLOCKED`.
+pub(crate) async fn moderate_message(
+    state: &AppState,
+    context: &AuthContext,
+    channel_id: Uuid,
+    message_id: Option<Uuid>,
+    body: String,
+) -> ApiResult<ModeratedBody> {
+    if !state.moderation.is_enabled()
+        || e2ee::is_encrypted_channel(&state.storage, channel_id).await
+    {
+        return Ok(ModeratedBody::unchanged(body));
+    }
+    review_body(state, context, "channel", channel_id, message_id, body).await
+}
+
+/// Moderates a direct message to `conversation_id`. Redact and block rules
+/// apply as in channels; a `flag` match is only audited, since moderation
+/// cases track channel messages.
+pub(crate) async fn moderate_dm_message(
+    state: &AppState,
+    context: &AuthContext,
+    conversation_id: Uuid,
+    body: String,
+) -> ApiResult<ModeratedBody> {
+    if !state.moderation.is_enabled() {
+        return Ok(ModeratedBody::unchanged(body));
+    }
+    let mut moderated = review_body(state, context, "dm", conversation_id, None, body).await?;
+    moderated.flagged_by = None;
+    Ok(moderated)
+}
+
+/// Runs the pipeline over `body`, auditing every intervention against the
+/// `target_type` / `target_id` the message is posted to.
+async fn review_body(
+    state: &AppState,
+    context: &AuthContext,
+    target_type: &str,
+    target_id: Uuid,
+    message_id: Option<Uuid>,
+    body: String,
+) -> ApiResult<ModeratedBody> {
+    let outcome = state.moderation.review(&body).await;
+    for intervention in &outcome.interventions {
+        state
+            .audit
+            .write(
+                context.workspace_id,
+                Some(context.user_id),
+                "MESSAGE_MODERATED",
+                target_type,
+                Some(target_id.to_string()),
+                json!({
+                    "message_id": message_id,
+                    "rule": intervention.rule,
+                    "action": intervention.action,
+                    "source": intervention.source,
+                }),
+            )
+            .await;
+    }
+    if let Some(blocked) = outcome.blocked_by() {
+        return Err(ApiError::Coded(
+            ErrorCode::ContentBlocked,
+            format!("message blocked by moderation rule {:?}", blocked.rule),
+        ));
+    }
+    let flagged_by = outcome
+        .interventions
+        .iter()
+        .find(|intervention| intervention.action == ModerationAction::Flag)
+        .map(|intervention| intervention.rule.clone());
+    Ok(ModeratedBody {
+        body: outcome.body,
+        flagged_by,
+    })
+}
+
+/// Moderates an edit of `message_id`. Messages the caller cannot edit are left
+/// for `ChannelService::update_message` to reject.
+pub(crate) async fn moderate_message_edit(
+    state: &AppState,
+    context: &AuthContext,
+    message_id: Uuid,
+    body: String,
+) -> ApiResult<ModeratedBody> {
+    match state.storage.get_message(&message_id).await {
+        Some(message)
+            if message.workspace_id == context.workspace_id
+                && message.sender_id == context.user_id =>
+        {
+            moderate_message(state, context, message.channel_id, Some(message_id), body).await
+        }
+        _ => Ok(ModeratedBody::unchanged(body)),
+    }
+}
+
+pub(crate) async fn moderate_thread_reply(
+    state: &AppState,
+    context: &AuthContext,
+    root_id: Uuid,
+    body: String,
+) -> ApiResult<ModeratedBody> {
+    match state.storage.get_message(&root_id).await {
+        Some(root) if root.workspace_id == context.workspace_id => {
+            moderate_message(state, context, root.channel_id, None, body).await
+        }
+        _ => Ok(ModeratedBody::unchanged(body)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rules_redact_flag_and_block_in_order() {
+        let rules = parse_rules(
+            r#"
+rules:
+  - name: profanity
+    words: ["darn", "heck"]
+    action: redact
+  - name: card-number
+    pattern: '\b\d{4}-\d{4}-\d{4}-\d{4}\b'
+    action: block
+  - name: urgent
+    words: ["URGENT"]
+    action: flag
+"#,
+        )
+        .expect("rules should parse");
+        let pipeline = ModerationPipeline {
+            rules,
+            external: None,
+        };
+
+        let outcome = pipeline.review("Darn it, this is urgent").await;
+        assert_eq!(outcome.body, "[redacted] it, this is urgent");
+        assert_eq!(
+            outcome
+                .interventions
+                .iter()
+                .map(|intervention| (intervention.rule.as_str(), intervention.action))
+                .collect::<Vec<_>>(),
+            vec![
+                ("profanity", ModerationAction::Redact),
+                ("urgent", ModerationAction::Flag)
+            ]
+        );
+        assert!(outcome.blocked_by().is_none());
+
+        let outcome = pipeline.review("pay to 1234-5678-9012-3456").await;
+        assert_eq!(
+            outcome.blocked_by().map(|blocked| blocked.rule.as_str()),
+            Some("card-number")
+        );
+    }
+
+    #[test]
+    fn rule_requires_pattern_or_words() {
+        let error = parse_rules("rules:\n  - name: empty\n    action: flag\n")
+            .expect_err("rule without matcher should fail");
+        assert!(error.contains("exactly one of pattern or words"));
+    }
+}
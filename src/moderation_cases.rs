@@ -0,0 +1,714 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    channels::{ChannelService, MessageResponse},
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    mutes::MuteUserRequest,
+    notifications,
+    storage::{ModerationCaseRecordStore, Storage},
+};
+
+const DETAILS_MAX_CHARS: usize = 500;
+const DEFAULT_QUEUE_LIMIT: usize = 50;
+const MAX_QUEUE_LIMIT: usize = 100;
+
+#[derive(Clone)]
+pub struct ModerationCaseService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportCategory {
+    Spam,
+    Harassment,
+    HateSpeech,
+    Violence,
+    SexualContent,
+    Other,
+}
+
+impl ReportCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Spam => "spam",
+            Self::Harassment => "harassment",
+            Self::HateSpeech => "hate_speech",
+            Self::Violence => "violence",
+            Self::SexualContent => "sexual_content",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Spam => "spam",
+            Self::Harassment => "harassment",
+            Self::HateSpeech => "hate speech",
+            Self::Violence => "violence",
+            Self::SexualContent => "sexual content",
+            Self::Other => "other",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "spam" => Self::Spam,
+            "harassment" => Self::Harassment,
+            "hate_speech" => Self::HateSpeech,
+            "violence" => Self::Violence,
+            "sexual_content" => Self::SexualContent,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseStatus {
+    Open,
+    Dismissed,
+    Resolved,
+}
+
+impl CaseStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Dismissed => "dismissed",
+            Self::Resolved => "resolved",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "dismissed" => Self::Dismissed,
+            "resolved" => Self::Resolved,
+            _ => Self::Open,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseAction {
+    Dismiss,
+    DeleteMessage,
+    MuteAuthor,
+}
+
+impl CaseAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Dismiss => "dismiss",
+            Self::DeleteMessage => "delete_message",
+            Self::MuteAuthor => "mute_author",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dismiss" => Some(Self::Dismiss),
+            "delete_message" => Some(Self::DeleteMessage),
+            "mute_author" => Some(Self::MuteAuthor),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReportMessageRequest {
+    pub category: ReportCategory,
+    /// Optional context for the moderators, up to 500 characters.
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ModerationCaseResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub message_id: Uuid,
+    pub channel_id: Uuid,
+    pub author_id: Uuid,
+    /// `report` for member reports, `flag` for messages flagged by a
+    /// moderation rule (the rule is in `details`).
+    pub source: String,
+    pub category: ReportCategory,
+    pub details: Option<String>,
+    pub reporter_id: Option<Uuid>,
+    pub status: CaseStatus,
+    pub created_at: i64,
+    /// Action that closed the case.
+    pub resolution: Option<CaseAction>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ModerationQueueQuery {
+    /// Defaults to `open`.
+    pub status: Option<CaseStatus>,
+    /// 1 to 100, defaults to 50.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ModerationQueueItem {
+    pub case: ModerationCaseResponse,
+    /// Current body of the message; `null` once it was deleted.
+    pub message_body: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ModerationQueueResponse {
+    /// Open cases oldest first; closed cases most recently closed first.
+    pub items: Vec<ModerationQueueItem>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveCaseRequest {
+    pub action: CaseAction,
+    /// Required for `mute_author`, 1 minute to 30 days.
+    pub duration_minutes: Option<u32>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/messages/:id/report", post(report_message))
+        .route("/api/v1/moderation/queue", get(moderation_queue))
+        .route("/api/v1/moderation/cases/:id/resolve", post(resolve_case))
+}
+
+impl ModerationCaseService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Opens a case for a message the reporter can see. Each member can have
+    /// one open report per message.
+    pub async fn report(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        message_id: Uuid,
+        payload: ReportMessageRequest,
+    ) -> ApiResult<ModerationCaseResponse> {
+        let details = payload
+            .details
+            .map(|details| details.trim().to_string())
+            .filter(|details| !details.is_empty());
+        let mut errors = FieldErrors::default();
+        if details
+            .as_ref()
+            .is_some_and(|details| details.chars().count() > DETAILS_MAX_CHARS)
+        {
+            errors.add(
+                "details",
+                format!("must be at most {DETAILS_MAX_CHARS} characters"),
+            );
+        }
+        errors.into_result()?;
+
+        let message = channels
+            .get_message(context.workspace_id, message_id)
+            .await?;
+        channels
+            .ensure_channel_access(context, message.channel_id)
+            .await?;
+        if message.sender_id == context.user_id {
+            return Err(ApiError::BadRequest(
+                "you cannot report your own message".to_string(),
+            ));
+        }
+        if self
+            .storage
+            .list_moderation_cases(context.workspace_id, Some(CaseStatus::Open.as_str()))
+            .await
+            .iter()
+            .any(|case| case.message_id == message_id && case.reporter_id == Some(context.user_id))
+        {
+            return Err(ApiError::Conflict(
+                "you already reported this message".to_string(),
+            ));
+        }
+
+        let record = ModerationCaseRecordStore {
+            id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            workspace_id: context.workspace_id,
+            message_id,
+            channel_id: message.channel_id,
+            author_id: message.sender_id,
+            source: "report".to_string(),
+            category: payload.category.as_str().to_string(),
+            details,
+            reporter_id: Some(context.user_id),
+            status: CaseStatus::Open.as_str().to_string(),
+            created_at: Utc::now().timestamp_millis(),
+            resolution: None,
+            resolved_by: None,
+            resolved_at: None,
+        };
+        self.storage.put_moderation_case(record.clone()).await;
+        Ok(ModerationCaseResponse::from(&record))
+    }
+
+    /// Opens a case for a message a moderation rule flagged, unless one is
+    /// already open for it.
+    pub async fn flag(&self, message: &MessageResponse, rule: &str) {
+        if self
+            .storage
+            .list_moderation_cases(message.workspace_id, Some(CaseStatus::Open.as_str()))
+            .await
+            .iter()
+            .any(|case| case.message_id == message.id && case.source == "flag")
+        {
+            return;
+        }
+        self.storage
+            .put_moderation_case(ModerationCaseRecordStore {
+                id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                workspace_id: message.workspace_id,
+                message_id: message.id,
+                channel_id: message.channel_id,
+                author_id: message.sender_id,
+                source: "flag".to_string(),
+                category: ReportCategory::Other.as_str().to_string(),
+                details: Some(rule.to_string()),
+                reporter_id: None,
+                status: CaseStatus::Open.as_str().to_string(),
+                created_at: Utc::now().timestamp_millis(),
+                resolution: None,
+                resolved_by: None,
+                resolved_at: None,
+            })
+            .await;
+    }
+
+    pub async fn queue(
+        &self,
+        context: &AuthContext,
+        query: &ModerationQueueQuery,
+    ) -> ApiResult<ModerationQueueResponse> {
+        ensure_workspace_admin(context)?;
+        let status = query.status.unwrap_or(CaseStatus::Open);
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_QUEUE_LIMIT)
+            .clamp(1, MAX_QUEUE_LIMIT);
+        let mut records = self
+            .storage
+            .list_moderation_cases(context.workspace_id, Some(status.as_str()))
+            .await;
+        if status == CaseStatus::Open {
+            records.sort_by_key(|record| (record.created_at, record.id));
+        } else {
+            records.sort_by_key(|record| std::cmp::Reverse((record.resolved_at, record.id)));
+        }
+        records.truncate(limit);
+
+        let mut items = Vec::with_capacity(records.len());
+        for record in &records {
+            let message_body = self
+                .storage
+                .get_message(&record.message_id)
+                .await
+                .filter(|message| message.deleted_at.is_none())
+                .map(|message| message.body_md);
+            items.push(ModerationQueueItem {
+                case: ModerationCaseResponse::from(record),
+                message_body,
+            });
+        }
+        Ok(ModerationQueueResponse { items })
+    }
+
+    /// Loads an open case of the caller's workspace for an admin decision.
+    pub async fn open_case(
+        &self,
+        context: &AuthContext,
+        case_id: Uuid,
+    ) -> ApiResult<ModerationCaseRecordStore> {
+        ensure_workspace_admin(context)?;
+        let case = self
+            .storage
+            .get_moderation_case(case_id)
+            .await
+            .filter(|case| case.workspace_id == context.workspace_id)
+            .ok_or_else(|| ApiError::NotFound("moderation case not found".to_string()))?;
+        if case.status != CaseStatus::Open.as_str() {
+            return Err(ApiError::Conflict(
+                "moderation case is already closed".to_string(),
+            ));
+        }
+        Ok(case)
+    }
+
+    /// Records `action` on the case. Deleting the message also closes every
+    /// other open case about it. Returns the closed cases, `case` first.
+    pub async fn close(
+        &self,
+        context: &AuthContext,
+        case: ModerationCaseRecordStore,
+        action: CaseAction,
+    ) -> Vec<ModerationCaseResponse> {
+        let mut cases = vec![case.clone()];
+        if action == CaseAction::DeleteMessage {
+            cases.extend(
+                self.storage
+                    .list_moderation_cases(context.workspace_id, Some(CaseStatus::Open.as_str()))
+                    .await
+                    .into_iter()
+                    .filter(|other| other.message_id == case.message_id && other.id != case.id),
+            );
+        }
+        let status = match action {
+            CaseAction::Dismiss => CaseStatus::Dismissed,
+            CaseAction::DeleteMessage | CaseAction::MuteAuthor => CaseStatus::Resolved,
+        };
+        let now = Utc::now().timestamp_millis();
+        let mut closed = Vec::with_capacity(cases.len());
+        for mut record in cases {
+            record.status = status.as_str().to_string();
+            record.resolution = Some(action.as_str().to_string());
+            record.resolved_by = Some(context.user_id);
+            record.resolved_at = Some(now);
+            self.storage.put_moderation_case(record.clone()).await;
+            closed.push(ModerationCaseResponse::from(&record));
+        }
+        closed
+    }
+}
+
+impl From<&ModerationCaseRecordStore> for ModerationCaseResponse {
+    fn from(record: &ModerationCaseRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            message_id: record.message_id,
+            channel_id: record.channel_id,
+            author_id: record.author_id,
+            source: record.source.clone(),
+            category: ReportCategory::parse(&record.category),
+            details: record.details.clone(),
+            reporter_id: record.reporter_id,
+            status: CaseStatus::parse(&record.status),
+            created_at: record.created_at,
+            resolution: record.resolution.as_deref().and_then(CaseAction::parse),
+            resolved_by: record.resolved_by,
+            resolved_at: record.resolved_at,
+        }
+    }
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to moderate this workspace".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/messages/{id}/report",
+    operation_id = "report_message",
+    tag = "moderation",
+    request_body = ReportMessageRequest,
+    responses(
+        (status = 201, description = "Moderation case opened and admins notified", body = ModerationCaseResponse),
+        (status = 400, description = "Own message", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Message not found", body = ErrorResponse),
+        (status = 409, description = "Already reported by this member", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn report_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(message_id): Path<Uuid>,
+    Json(payload): Json<ReportMessageRequest>,
+) -> ApiResult<(StatusCode, Json<ModerationCaseResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let case = state
+        .moderation_cases
+        .report(&state.channels, &context, message_id, payload)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "MESSAGE_REPORTED",
+            "message",
+            Some(message_id.to_string()),
+            json!({
+                "case_id": case.id,
+                "channel_id": case.channel_id,
+                "category": case.category,
+            }),
+        )
+        .await;
+    notifications::notify_message_report(&state, &context, &case).await;
+    Ok((StatusCode::CREATED, Json(case)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/moderation/queue",
+    operation_id = "moderation_queue",
+    tag = "moderation",
+    params(ModerationQueueQuery),
+    responses(
+        (status = 200, description = "Reported and flagged messages", body = ModerationQueueResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn moderation_queue(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ModerationQueueQuery>,
+) -> ApiResult<Json<ModerationQueueResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.moderation_cases.queue(&context, &query).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/moderation/cases/{id}/resolve",
+    operation_id = "resolve_moderation_case",
+    tag = "moderation",
+    request_body = ResolveCaseRequest,
+    responses(
+        (status = 200, description = "Case closed with the action applied", body = ModerationCaseResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden, or the author cannot be muted", body = ErrorResponse),
+        (status = 404, description = "Case not found", body = ErrorResponse),
+        (status = 409, description = "Case already closed, or message under legal hold", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn resolve_case(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(case_id): Path<Uuid>,
+    Json(payload): Json<ResolveCaseRequest>,
+) -> ApiResult<Json<ModerationCaseResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let case = state.moderation_cases.open_case(&context, case_id).await?;
+    match payload.action {
+        CaseAction::Dismiss => {}
+        CaseAction::DeleteMessage => {
+            let deleted = state
+                .storage
+                .get_message(&case.message_id)
+                .await
+                .is_none_or(|message| message.deleted_at.is_some());
+            if !deleted {
+                state
+                    .channels
+                    .delete_message(&context, case.message_id, None)
+                    .await?;
+                state
+                    .audit
+                    .write(
+                        context.workspace_id,
+                        Some(context.user_id),
+                        "MESSAGE_DELETED",
+                        "message",
+                        Some(case.message_id.to_string()),
+                        json!({ "case_id": case.id }),
+                    )
+                    .await;
+            }
+        }
+        CaseAction::MuteAuthor => {
+            let mute = state
+                .mutes
+                .mute(
+                    &context,
+                    MuteUserRequest {
+                        user_id: case.author_id,
+                        duration_minutes: payload.duration_minutes.unwrap_or_default(),
+                        reason: Some(format!("moderation case {}", case.id)),
+                    },
+                )
+                .await?;
+            state
+                .audit
+                .write(
+                    context.workspace_id,
+                    Some(context.user_id),
+                    "USER_MUTED",
+                    "user",
+                    Some(mute.user_id.to_string()),
+                    json!({
+                        "expires_at": mute.expires_at,
+                        "reason": mute.reason,
+                        "case_id": case.id,
+                    }),
+                )
+                .await;
+        }
+    }
+
+    let mut closed = state
+        .moderation_cases
+        .close(&context, case, payload.action)
+        .await;
+    for item in &closed {
+        state
+            .audit
+            .write(
+                context.workspace_id,
+                Some(context.user_id),
+                if item.status == CaseStatus::Dismissed {
+                    "MODERATION_CASE_DISMISSED"
+                } else {
+                    "MODERATION_CASE_RESOLVED"
+                },
+                "moderation_case",
+                Some(item.id.to_string()),
+                json!({
+                    "action": payload.action,
+                    "message_id": item.message_id,
+                    "author_id": item.author_id,
+                }),
+            )
+            .await;
+    }
+    Ok(Json(closed.swap_remove(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::WorkspaceRole, channels::CreateMessageRequest, storage::PersistenceBackend};
+
+    #[tokio::test]
+    async fn reports_and_flags_share_the_queue_until_resolved() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let (workspace_id, owner_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let channels = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let cases = ModerationCaseService::new(storage.clone());
+        let owner = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let member = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        storage
+            .put_membership_role(workspace_id, member.user_id, "member")
+            .await;
+        let channel_id = channels
+            .list_channels(workspace_id)
+            .await
+            .first()
+            .expect("channel should exist")
+            .id;
+        let message = channels
+            .create_message(
+                &owner,
+                channel_id,
+                CreateMessageRequest {
+                    body_md: "buy cheap watches".to_string(),
+                },
+                None,
+            )
+            .await
+            .expect("message");
+        let report = || ReportMessageRequest {
+            category: ReportCategory::Spam,
+            details: Some("  bot  ".to_string()),
+        };
+
+        assert!(matches!(
+            cases.report(&channels, &owner, message.id, report()).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        let case = cases
+            .report(&channels, &member, message.id, report())
+            .await
+            .expect("reported");
+        assert_eq!(case.status, CaseStatus::Open);
+        assert_eq!(case.author_id, owner_id);
+        assert_eq!(case.details.as_deref(), Some("bot"));
+        assert!(matches!(
+            cases.report(&channels, &member, message.id, report()).await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        cases.flag(&message, "spam-links").await;
+        cases.flag(&message, "spam-links").await;
+        let query = ModerationQueueQuery {
+            status: None,
+            limit: None,
+        };
+        assert!(matches!(
+            cases.queue(&member, &query).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        let queue = cases.queue(&owner, &query).await.expect("queue");
+        assert_eq!(queue.items.len(), 2);
+        // Both cases can share a millisecond, so their order is not asserted.
+        assert!(queue.items.iter().any(|item| item.case.source == "flag"));
+        assert!(
+            queue
+                .items
+                .iter()
+                .all(|item| item.message_body.as_deref() == Some("buy cheap watches"))
+        );
+
+        let open = cases.open_case(&owner, case.id).await.expect("open case");
+        let closed = cases.close(&owner, open, CaseAction::DeleteMessage).await;
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].id, case.id);
+        assert!(closed.iter().all(|item| item.status == CaseStatus::Resolved
+            && item.resolution == Some(CaseAction::DeleteMessage)));
+        assert!(matches!(
+            cases.open_case(&owner, case.id).await,
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(
+            cases
+                .queue(&owner, &query)
+                .await
+                .expect("queue")
+                .items
+                .is_empty()
+        );
+    }
+}
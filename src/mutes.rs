@@ -0,0 +1,352 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    storage::{Storage, UserMuteRecordStore},
+};
+
+const REASON_MAX_CHARS: usize = 500;
+const MAX_MUTE_MINUTES: u32 = 30 * 24 * 60;
+
+#[derive(Clone)]
+pub struct MuteService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MuteUserRequest {
+    pub user_id: Uuid,
+    /// How long the mute lasts, 1 minute to 30 days. Muting an already muted
+    /// user replaces the previous expiry.
+    pub duration_minutes: u32,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MuteResponse {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub reason: Option<String>,
+    pub muted_by: Uuid,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MuteListResponse {
+    pub items: Vec<MuteResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/moderation/mutes", get(list_mutes).post(mute_user))
+        .route("/api/v1/moderation/mutes/:user_id", delete(unmute_user))
+}
+
+/// Rejects writes (messages, edits, replies, reactions) from a user muted in
+/// the workspace with `403 USER_MUTED`.
+pub async fn ensure_not_muted(
+    storage: &Storage,
+    workspace_id: Uuid,
+    user_id: Uuid,
+) -> ApiResult<()> {
+    let now = Utc::now().timestamp_millis();
+    match storage
+        .get_active_user_mute(workspace_id, user_id, now)
+        .await
+    {
+        Some(mute) => Err(ApiError::Coded(
+            ErrorCode::UserMuted,
+            format!("you are muted in this workspace until {}", mute.expires_at),
+        )),
+        None => Ok(()),
+    }
+}
+
+impl MuteService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn list(&self, context: &AuthContext) -> ApiResult<MuteListResponse> {
+        ensure_workspace_admin(context)?;
+        let mut records = self
+            .storage
+            .list_active_user_mutes(context.workspace_id, Utc::now().timestamp_millis())
+            .await;
+        records.sort_by_key(|record| record.expires_at);
+        Ok(MuteListResponse {
+            items: records.iter().map(MuteResponse::from).collect(),
+        })
+    }
+
+    pub async fn mute(
+        &self,
+        context: &AuthContext,
+        payload: MuteUserRequest,
+    ) -> ApiResult<MuteResponse> {
+        ensure_workspace_admin(context)?;
+        let reason = payload
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+        let mut errors = FieldErrors::default();
+        if !(1..=MAX_MUTE_MINUTES).contains(&payload.duration_minutes) {
+            errors.add(
+                "duration_minutes",
+                format!("must be between 1 and {MAX_MUTE_MINUTES}"),
+            );
+        }
+        if reason
+            .as_ref()
+            .is_some_and(|reason| reason.chars().count() > REASON_MAX_CHARS)
+        {
+            errors.add(
+                "reason",
+                format!("must be at most {REASON_MAX_CHARS} characters"),
+            );
+        }
+        errors.into_result()?;
+        if payload.user_id == context.user_id {
+            return Err(ApiError::BadRequest("you cannot mute yourself".to_string()));
+        }
+
+        let role = self
+            .storage
+            .get_membership_role(context.workspace_id, payload.user_id)
+            .await
+            .ok_or_else(|| ApiError::NotFound("user not found".to_string()))?;
+        let allowed = match role.as_str() {
+            "owner" => false,
+            "admin" => context.role == WorkspaceRole::Owner,
+            _ => true,
+        };
+        if !allowed {
+            return Err(ApiError::Forbidden(
+                "you do not have permission to mute this user".to_string(),
+            ));
+        }
+
+        let now = Utc::now().timestamp_millis();
+        let record = UserMuteRecordStore {
+            workspace_id: context.workspace_id,
+            user_id: payload.user_id,
+            reason,
+            muted_by: context.user_id,
+            created_at: now,
+            expires_at: now + i64::from(payload.duration_minutes) * 60_000,
+        };
+        self.storage.put_user_mute(record.clone()).await;
+        Ok(MuteResponse::from(&record))
+    }
+
+    pub async fn unmute(&self, context: &AuthContext, user_id: Uuid) -> ApiResult<MuteResponse> {
+        ensure_workspace_admin(context)?;
+        let now = Utc::now().timestamp_millis();
+        self.storage
+            .remove_user_mute(context.workspace_id, user_id)
+            .await
+            .filter(|record| record.expires_at > now)
+            .map(|record| MuteResponse::from(&record))
+            .ok_or_else(|| ApiError::NotFound("user is not muted".to_string()))
+    }
+}
+
+impl From<&UserMuteRecordStore> for MuteResponse {
+    fn from(record: &UserMuteRecordStore) -> Self {
+        Self {
+            workspace_id: record.workspace_id,
+            user_id: record.user_id,
+            reason: record.reason.clone(),
+            muted_by: record.muted_by,
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+        }
+    }
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to mute users".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/moderation/mutes",
+    operation_id = "list_mutes",
+    tag = "moderation",
+    responses(
+        (status = 200, description = "Mutes in force, soonest to expire first", body = MuteListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_mutes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<MuteListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.mutes.list(&context).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/moderation/mutes",
+    operation_id = "mute_user",
+    tag = "moderation",
+    request_body = MuteUserRequest,
+    responses(
+        (status = 201, description = "User muted", body = MuteResponse),
+        (status = 400, description = "Cannot mute yourself", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn mute_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<MuteUserRequest>,
+) -> ApiResult<(StatusCode, Json<MuteResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mute = state.mutes.mute(&context, payload).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "USER_MUTED",
+            "user",
+            Some(mute.user_id.to_string()),
+            json!({ "expires_at": mute.expires_at, "reason": mute.reason }),
+        )
+        .await;
+    Ok((StatusCode::CREATED, Json(mute)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/moderation/mutes/{user_id}",
+    operation_id = "unmute_user",
+    tag = "moderation",
+    responses(
+        (status = 204, description = "Mute lifted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User is not muted", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn unmute_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mute = state.mutes.unmute(&context, user_id).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "USER_UNMUTED",
+            "user",
+            Some(mute.user_id.to_string()),
+            json!({
+                "muted_by": mute.muted_by,
+                "expires_at": mute.expires_at,
+            }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PersistenceBackend;
+
+    #[tokio::test]
+    async fn mutes_block_writes_until_lifted() {
+        let workspace_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let mutes = MuteService::new(storage.clone());
+        let context = |role| AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role,
+        };
+        let (owner, admin) = (context(WorkspaceRole::Owner), context(WorkspaceRole::Admin));
+        let (member, other_admin) = (Uuid::new_v4(), Uuid::new_v4());
+        storage
+            .put_membership_role(workspace_id, member, "member")
+            .await;
+        storage
+            .put_membership_role(workspace_id, other_admin, "admin")
+            .await;
+        let request = |user_id| MuteUserRequest {
+            user_id,
+            duration_minutes: 60,
+            reason: Some(" spam ".to_string()),
+        };
+
+        assert!(matches!(
+            mutes.mute(&admin, request(other_admin)).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        let mute = mutes.mute(&admin, request(member)).await.expect("muted");
+        assert_eq!(mute.reason.as_deref(), Some("spam"));
+        assert!(matches!(
+            ensure_not_muted(&storage, workspace_id, member).await,
+            Err(ApiError::Coded(ErrorCode::UserMuted, _))
+        ));
+        assert!(
+            ensure_not_muted(&storage, Uuid::new_v4(), member)
+                .await
+                .is_ok()
+        );
+        assert_eq!(mutes.list(&owner).await.expect("listed").items.len(), 1);
+
+        mutes.unmute(&owner, member).await.expect("unmuted");
+        assert!(
+            ensure_not_muted(&storage, workspace_id, member)
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            mutes.unmute(&owner, member).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
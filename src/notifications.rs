@@ -0,0 +1,824 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    access_requests::{AccessRequestResponse, AccessRequestStatus},
+    app::AppState,
+    auth::AuthContext,
+    channels::{self, MessageResponse},
+    e2ee,
+    email::EmailTemplate,
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse},
+    groups, keywords,
+    moderation_cases::ModerationCaseResponse,
+    realtime,
+    storage::{NotificationRecordStore, Storage},
+};
+
+const PREVIEW_CHARS: usize = 140;
+const DIGEST_MAX_ITEMS: usize = 10;
+
+#[derive(Clone)]
+pub struct NotificationService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Mention,
+    ThreadReply,
+    ChannelInvite,
+    AdminAnnouncement,
+    KeywordAlert,
+    Reminder,
+    GroupMention,
+    MessageReport,
+    AccessRequest,
+    AccessRequestResolved,
+    NewDeviceLogin,
+}
+
+impl NotificationKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mention => "mention",
+            Self::ThreadReply => "thread_reply",
+            Self::ChannelInvite => "channel_invite",
+            Self::AdminAnnouncement => "admin_announcement",
+            Self::KeywordAlert => "keyword_alert",
+            Self::Reminder => "reminder",
+            Self::GroupMention => "group_mention",
+            Self::MessageReport => "message_report",
+            Self::AccessRequest => "access_request",
+            Self::AccessRequestResolved => "access_request_resolved",
+            Self::NewDeviceLogin => "new_device_login",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Mention => "Mention",
+            Self::ThreadReply => "Thread reply",
+            Self::ChannelInvite => "Channel invite",
+            Self::AdminAnnouncement => "Announcement",
+            Self::KeywordAlert => "Keyword alert",
+            Self::Reminder => "Reminder",
+            Self::GroupMention => "Group mention",
+            Self::MessageReport => "Message report",
+            Self::AccessRequest => "Access request",
+            Self::AccessRequestResolved => "Access request resolved",
+            Self::NewDeviceLogin => "New device login",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "mention" => Some(Self::Mention),
+            "thread_reply" => Some(Self::ThreadReply),
+            "channel_invite" => Some(Self::ChannelInvite),
+            "admin_announcement" => Some(Self::AdminAnnouncement),
+            "keyword_alert" => Some(Self::KeywordAlert),
+            "reminder" => Some(Self::Reminder),
+            "group_mention" => Some(Self::GroupMention),
+            "message_report" => Some(Self::MessageReport),
+            "access_request" => Some(Self::AccessRequest),
+            "access_request_resolved" => Some(Self::AccessRequestResolved),
+            "new_device_login" => Some(Self::NewDeviceLogin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub kind: NotificationKind,
+    pub actor_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+    pub message_id: Option<Uuid>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub kind: NotificationKind,
+    pub actor_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+    pub message_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: i64,
+    pub read_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationListResponse {
+    pub items: Vec<NotificationResponse>,
+    pub unread_count: usize,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct NotificationQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    /// Only return notifications that have not been read.
+    #[serde(default)]
+    pub unread: bool,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct MarkNotificationsReadRequest {
+    /// Notifications to mark; omit to mark every unread notification.
+    pub ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ClearNotificationsQuery {
+    /// Only delete notifications that were already read.
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationsUpdatedResponse {
+    pub updated: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationsClearedResponse {
+    pub deleted: u64,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/me/notifications",
+            get(list_notifications).delete(clear_notifications),
+        )
+        .route(
+            "/api/v1/me/notifications/read",
+            post(mark_notifications_read),
+        )
+}
+
+impl NotificationService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn create(&self, new: NewNotification) -> NotificationResponse {
+        let record = NotificationRecordStore {
+            id: Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            workspace_id: new.workspace_id,
+            user_id: new.user_id,
+            kind: new.kind.as_str().to_string(),
+            actor_id: new.actor_id,
+            channel_id: new.channel_id,
+            message_id: new.message_id,
+            body: preview(&new.body),
+            created_at: Utc::now().timestamp_millis(),
+            read_at: None,
+        };
+        if let Err(error) = self.storage.put_notification(record.clone()).await {
+            tracing::warn!(notification_id = %record.id, "notification not stored: {}", error);
+        }
+        NotificationResponse::from_record(&record, new.kind)
+    }
+
+    /// Notifications for users mentioned in `message` directly or through a
+    /// group `@handle`, and for the author of the thread it replies to.
+    /// Returns `(recipient, notification)` pairs.
+    pub async fn for_message(
+        &self,
+        message: &MessageResponse,
+    ) -> Vec<(Uuid, NotificationResponse)> {
+        let mut recipients = channels::mentioned_user_ids(&message.body_md)
+            .into_iter()
+            .map(|user_id| (user_id, NotificationKind::Mention))
+            .collect::<Vec<_>>();
+        for user_id in
+            groups::mentioned_group_members(&self.storage, message.workspace_id, &message.body_md)
+                .await
+        {
+            if !recipients
+                .iter()
+                .any(|(recipient, _)| *recipient == user_id)
+            {
+                recipients.push((user_id, NotificationKind::GroupMention));
+            }
+        }
+        if let Some(root_id) = message.thread_root_id
+            && let Some(root) = self.storage.get_message(&root_id).await
+            && !recipients
+                .iter()
+                .any(|(user_id, _)| *user_id == root.sender_id)
+        {
+            recipients.push((root.sender_id, NotificationKind::ThreadReply));
+        }
+
+        let mut created = Vec::new();
+        for (user_id, kind) in recipients {
+            if user_id == message.sender_id
+                || !self
+                    .can_view_channel(message.workspace_id, user_id, message.channel_id)
+                    .await
+            {
+                continue;
+            }
+            let notification = self
+                .create(NewNotification {
+                    workspace_id: message.workspace_id,
+                    user_id,
+                    kind,
+                    actor_id: Some(message.sender_id),
+                    channel_id: Some(message.channel_id),
+                    message_id: Some(message.id),
+                    body: message.body_md.clone(),
+                })
+                .await;
+            created.push((user_id, notification));
+        }
+        created
+    }
+
+    pub async fn list(
+        &self,
+        context: &AuthContext,
+        query: &NotificationQuery,
+    ) -> ApiResult<NotificationListResponse> {
+        let limit = query.limit.unwrap_or(50).clamp(1, 100);
+        let before = query
+            .cursor
+            .as_deref()
+            .map(channels::parse_cursor)
+            .transpose()
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
+
+        let mut records = self
+            .storage
+            .list_notifications(context.workspace_id, context.user_id)
+            .await;
+        let unread_count = records
+            .iter()
+            .filter(|record| record.read_at.is_none())
+            .count();
+        records.retain(|record| {
+            (!query.unread || record.read_at.is_none())
+                && before.is_none_or(|(cursor_ts, cursor_id)| {
+                    (record.created_at, record.id.as_u128()) < (cursor_ts, cursor_id)
+                })
+        });
+        records.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
+        });
+
+        let has_more = records.len() > limit;
+        let items = records
+            .iter()
+            .take(limit)
+            .filter_map(|record| {
+                NotificationKind::parse(&record.kind)
+                    .map(|kind| NotificationResponse::from_record(record, kind))
+            })
+            .collect::<Vec<_>>();
+        let next_cursor = if has_more {
+            records
+                .get(limit - 1)
+                .map(|record| format!("{}:{}", record.created_at, record.id.as_u128()))
+        } else {
+            None
+        };
+
+        Ok(NotificationListResponse {
+            items,
+            unread_count,
+            next_cursor,
+        })
+    }
+
+    pub async fn mark_read(&self, context: &AuthContext, ids: Option<&[Uuid]>) -> u64 {
+        self.storage
+            .mark_notifications_read(
+                context.workspace_id,
+                context.user_id,
+                ids,
+                Utc::now().timestamp_millis(),
+            )
+            .await
+    }
+
+    pub async fn clear(&self, context: &AuthContext, read_only: bool) -> u64 {
+        self.storage
+            .remove_notifications(context.workspace_id, context.user_id, read_only)
+            .await
+    }
+
+    pub(crate) async fn can_view_channel(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        channel_id: Uuid,
+    ) -> bool {
+        let Some(role) = self
+            .storage
+            .get_membership_role(workspace_id, user_id)
+            .await
+        else {
+            return false;
+        };
+        let Some(channel) = self.storage.get_channel(&channel_id).await else {
+            return false;
+        };
+        channel.workspace_id == workspace_id
+            && (!channel.is_private
+                || matches!(role.as_str(), "owner" | "admin")
+                || self.storage.is_channel_member(channel_id, user_id).await)
+    }
+}
+
+impl NotificationResponse {
+    fn from_record(record: &NotificationRecordStore, kind: NotificationKind) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            kind,
+            actor_id: record.actor_id,
+            channel_id: record.channel_id,
+            message_id: record.message_id,
+            body: record.body.clone(),
+            created_at: record.created_at,
+            read_at: record.read_at,
+        }
+    }
+}
+
+fn preview(body: &str) -> String {
+    let body = body.trim();
+    if body.chars().count() <= PREVIEW_CHARS {
+        return body.to_string();
+    }
+    let mut truncated = body.chars().take(PREVIEW_CHARS - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+/// Emails every member a digest of the notifications they received and left
+/// unread during each `EMAIL_DIGEST_INTERVAL_MINUTES` period.
+pub(crate) fn spawn_email_digests(state: AppState) {
+    let minutes = state.config.email_digest_interval_minutes;
+    if minutes == 0 {
+        return;
+    }
+    let period = Duration::from_secs(minutes * 60);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let since = Utc::now().timestamp_millis() - period.as_millis() as i64;
+            send_digests(&state, since).await;
+        }
+    });
+}
+
+async fn send_digests(state: &AppState, since: i64) {
+    let mut unread = BTreeMap::<(Uuid, Uuid), Vec<NotificationRecordStore>>::new();
+    for record in state.storage.list_unread_notifications_since(since).await {
+        unread
+            .entry((record.workspace_id, record.user_id))
+            .or_default()
+            .push(record);
+    }
+    for ((workspace_id, user_id), mut records) in unread {
+        if state
+            .storage
+            .get_membership_role(workspace_id, user_id)
+            .await
+            .is_none()
+        {
+            continue;
+        }
+        let (Some(user), Some(workspace)) = (
+            state.storage.get_auth_user_by_id(user_id).await,
+            state.storage.get_workspace(workspace_id).await,
+        ) else {
+            continue;
+        };
+        records.sort_by_key(|record| record.created_at);
+        let items = records
+            .iter()
+            .take(DIGEST_MAX_ITEMS)
+            .map(|record| {
+                let label = NotificationKind::parse(&record.kind)
+                    .map_or("Notification", NotificationKind::label);
+                format!("{label}: {}", record.body)
+            })
+            .collect();
+        state.email.send(
+            &user.email,
+            &user.name,
+            EmailTemplate::Digest {
+                workspace_name: workspace.name,
+                unread_count: records.len(),
+                items,
+            },
+        );
+    }
+}
+
+/// Stores notifications for a newly created message and pushes a
+/// `NOTIFICATION_CREATED` event to each recipient, then delivers keyword
+/// alerts to subscribers who were not already notified.
+pub(crate) async fn notify_message(state: &AppState, message: &MessageResponse) {
+    let mut notified = HashSet::new();
+    for (recipient, notification) in state.notifications.for_message(message).await {
+        publish(state, message.workspace_id, recipient, &notification).await;
+        notified.insert(recipient);
+    }
+    if !e2ee::is_encrypted_channel(&state.storage, message.channel_id).await {
+        keywords::alert_keywords(state, message, &notified).await;
+    }
+}
+
+pub(crate) async fn notify_channel_invite(
+    state: &AppState,
+    context: &AuthContext,
+    channel_id: Uuid,
+    user_id: Uuid,
+) {
+    if user_id == context.user_id {
+        return;
+    }
+    let channel_name = state
+        .storage
+        .get_channel(&channel_id)
+        .await
+        .map(|channel| channel.name)
+        .unwrap_or_default();
+    let notification = state
+        .notifications
+        .create(NewNotification {
+            workspace_id: context.workspace_id,
+            user_id,
+            kind: NotificationKind::ChannelInvite,
+            actor_id: Some(context.user_id),
+            channel_id: Some(channel_id),
+            message_id: None,
+            body: format!("You were added to #{channel_name}"),
+        })
+        .await;
+    publish(state, context.workspace_id, user_id, &notification).await;
+}
+
+/// Tells every workspace owner and admin except the reporter that a message
+/// was reported.
+pub(crate) async fn notify_message_report(
+    state: &AppState,
+    context: &AuthContext,
+    case: &ModerationCaseResponse,
+) {
+    for (user_id, role) in state
+        .storage
+        .list_workspace_memberships(context.workspace_id)
+        .await
+    {
+        if user_id == context.user_id || !matches!(role.as_str(), "owner" | "admin") {
+            continue;
+        }
+        let notification = state
+            .notifications
+            .create(NewNotification {
+                workspace_id: context.workspace_id,
+                user_id,
+                kind: NotificationKind::MessageReport,
+                actor_id: Some(context.user_id),
+                channel_id: Some(case.channel_id),
+                message_id: Some(case.message_id),
+                body: format!("A message was reported as {}", case.category.label()),
+            })
+            .await;
+        publish(state, context.workspace_id, user_id, &notification).await;
+    }
+}
+
+/// Tells the channel admins, or the workspace owners and admins when the
+/// channel has none, that a member asked to join.
+pub(crate) async fn notify_access_request(
+    state: &AppState,
+    context: &AuthContext,
+    request: &AccessRequestResponse,
+) {
+    let mut recipients = state.storage.list_channel_admins(request.channel_id).await;
+    if recipients.is_empty() {
+        recipients = state
+            .storage
+            .list_workspace_memberships(context.workspace_id)
+            .await
+            .into_iter()
+            .filter(|(_, role)| matches!(role.as_str(), "owner" | "admin"))
+            .map(|(user_id, _)| user_id)
+            .collect();
+    }
+    let channel_name = state
+        .storage
+        .get_channel(&request.channel_id)
+        .await
+        .map(|channel| channel.name)
+        .unwrap_or_default();
+    for user_id in recipients {
+        if user_id == context.user_id {
+            continue;
+        }
+        let notification = state
+            .notifications
+            .create(NewNotification {
+                workspace_id: context.workspace_id,
+                user_id,
+                kind: NotificationKind::AccessRequest,
+                actor_id: Some(context.user_id),
+                channel_id: Some(request.channel_id),
+                message_id: None,
+                body: format!("Someone asked to join #{channel_name}"),
+            })
+            .await;
+        publish(state, context.workspace_id, user_id, &notification).await;
+    }
+}
+
+pub(crate) async fn notify_access_decision(
+    state: &AppState,
+    context: &AuthContext,
+    request: &AccessRequestResponse,
+) {
+    let channel_name = state
+        .storage
+        .get_channel(&request.channel_id)
+        .await
+        .map(|channel| channel.name)
+        .unwrap_or_default();
+    let outcome = match request.status {
+        AccessRequestStatus::Approved => "approved",
+        _ => "denied",
+    };
+    let notification = state
+        .notifications
+        .create(NewNotification {
+            workspace_id: context.workspace_id,
+            user_id: request.user_id,
+            kind: NotificationKind::AccessRequestResolved,
+            actor_id: Some(context.user_id),
+            channel_id: Some(request.channel_id),
+            message_id: None,
+            body: format!("Your request to join #{channel_name} was {outcome}"),
+        })
+        .await;
+    publish(state, context.workspace_id, request.user_id, &notification).await;
+}
+
+/// Warns the user in-app and by email that their account signed in from an
+/// address and browser pair it had not used before.
+pub(crate) async fn notify_new_device_login(
+    state: &AppState,
+    context: &AuthContext,
+    ip: &str,
+    user_agent: &str,
+) {
+    let notification = state
+        .notifications
+        .create(NewNotification {
+            workspace_id: context.workspace_id,
+            user_id: context.user_id,
+            kind: NotificationKind::NewDeviceLogin,
+            actor_id: None,
+            channel_id: None,
+            message_id: None,
+            body: format!("New sign-in from {ip} ({user_agent})"),
+        })
+        .await;
+    publish(state, context.workspace_id, context.user_id, &notification).await;
+    if let Some(user) = state.storage.get_auth_user_by_id(context.user_id).await {
+        state.email.send(
+            &user.email,
+            &user.name,
+            EmailTemplate::NewDeviceLogin {
+                ip: ip.to_string(),
+                user_agent: user_agent.to_string(),
+            },
+        );
+    }
+}
+
+async fn publish(
+    state: &AppState,
+    workspace_id: Uuid,
+    recipient: Uuid,
+    notification: &NotificationResponse,
+) {
+    state
+        .realtime
+        .emit(
+            workspace_id,
+            realtime::make_user_event(
+                "NOTIFICATION_CREATED",
+                workspace_id,
+                recipient,
+                serde_json::to_value(notification).unwrap_or_default(),
+            ),
+        )
+        .await;
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/notifications",
+    operation_id = "list_notifications",
+    tag = "notifications",
+    params(NotificationQuery),
+    responses(
+        (status = 200, description = "Notification inbox, newest first", body = NotificationListResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_notifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<NotificationQuery>,
+) -> ApiResult<Json<NotificationListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let page = state.notifications.list(&context, &query).await?;
+    Ok(Json(page))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/notifications/read",
+    operation_id = "mark_notifications_read",
+    tag = "notifications",
+    request_body = MarkNotificationsReadRequest,
+    responses(
+        (status = 200, description = "Notifications marked as read", body = NotificationsUpdatedResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn mark_notifications_read(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Option<Json<MarkNotificationsReadRequest>>,
+) -> ApiResult<Json<NotificationsUpdatedResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
+    let updated = state
+        .notifications
+        .mark_read(&context, payload.ids.as_deref())
+        .await;
+    Ok(Json(NotificationsUpdatedResponse { updated }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/notifications",
+    operation_id = "clear_notifications",
+    tag = "notifications",
+    params(ClearNotificationsQuery),
+    responses(
+        (status = 200, description = "Notifications deleted", body = NotificationsClearedResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn clear_notifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ClearNotificationsQuery>,
+) -> ApiResult<Json<NotificationsClearedResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let deleted = state.notifications.clear(&context, query.read_only).await;
+    Ok(Json(NotificationsClearedResponse { deleted }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::WorkspaceRole,
+        channels::{ChannelService, CreateMessageRequest},
+        storage::PersistenceBackend,
+    };
+
+    #[tokio::test]
+    async fn mentions_and_replies_create_notifications_that_can_be_read_and_cleared() {
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let member_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        storage
+            .put_membership_role(workspace_id, owner_id, "owner")
+            .await;
+        storage
+            .put_membership_role(workspace_id, member_id, "member")
+            .await;
+        let channels = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let notifications = NotificationService::new(storage.clone());
+        let owner = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let member = AuthContext {
+            user_id: member_id,
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        let channel_id = channels
+            .list_channels(workspace_id)
+            .await
+            .first()
+            .expect("general channel should exist")
+            .id;
+
+        let root = channels
+            .create_message(
+                &member,
+                channel_id,
+                CreateMessageRequest {
+                    body_md: format!("hey <@{owner_id}> and <@{member_id}>"),
+                },
+                None,
+            )
+            .await
+            .expect("message creation should succeed");
+        let created = notifications.for_message(&root).await;
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].0, owner_id);
+        assert_eq!(created[0].1.kind, NotificationKind::Mention);
+
+        let reply = channels
+            .create_thread_reply(
+                &owner,
+                root.id,
+                CreateMessageRequest {
+                    body_md: "on it".to_string(),
+                },
+            )
+            .await
+            .expect("reply should succeed");
+        let created = notifications.for_message(&reply).await;
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].0, member_id);
+        assert_eq!(created[0].1.kind, NotificationKind::ThreadReply);
+
+        let query = NotificationQuery {
+            cursor: None,
+            limit: None,
+            unread: true,
+        };
+        let inbox = notifications
+            .list(&owner, &query)
+            .await
+            .expect("list should succeed");
+        assert_eq!(inbox.unread_count, 1);
+        assert_eq!(notifications.mark_read(&owner, None).await, 1);
+        let inbox = notifications
+            .list(&owner, &query)
+            .await
+            .expect("list should succeed");
+        assert!(inbox.items.is_empty());
+        assert_eq!(notifications.clear(&owner, true).await, 1);
+        assert_eq!(notifications.clear(&member, true).await, 0);
+    }
+}
@@ -1,16 +1,51 @@
 use std::{
-    sync::atomic::{AtomicU64, Ordering},
-    time::Duration,
+    cell::Cell,
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use axum::{
-    extract::State,
-    http::Request,
+    extract::{MatchedPath, State},
+    http::{HeaderMap, HeaderValue, Request, header},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use opentelemetry::{
+    KeyValue, global,
+    metrics::{Counter, Histogram, UpDownCounter},
+};
+use tracing::{
+    Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    warn,
+};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, layer::Context, registry::LookupSpan, reload,
+};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    errors::{ApiError, ApiResult, ErrorResponse},
+};
 
-use crate::app::AppState;
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+    /// Workspace of the authenticated caller, filled in by auth so the
+    /// slow-request warning can name it.
+    static REQUEST_WORKSPACE: Cell<Option<Uuid>>;
+}
 
 #[derive(Debug)]
 pub struct AppMetrics {
@@ -27,6 +62,96 @@ pub struct AppMetrics {
     latency_ms_le_2500: AtomicU64,
     latency_ms_le_5000: AtomicU64,
     latency_ms_inf: AtomicU64,
+    routes: Mutex<BTreeMap<RouteKey, RouteMetrics>>,
+    domain: Mutex<DomainMetrics>,
+    otel: OtelInstruments,
+}
+
+type WorkspaceCounter = fn(&WorkspaceMetrics) -> u64;
+
+const MAX_LABELED_WORKSPACES: usize = 1_000;
+const OTHER_WORKSPACE_LABEL: &str = "other";
+
+#[derive(Debug, Default)]
+struct DomainMetrics {
+    workspaces: BTreeMap<String, WorkspaceMetrics>,
+    auth_failures: BTreeMap<&'static str, u64>,
+    redis_publish_backlog: u64,
+    redis_publish_dropped: BTreeMap<&'static str, u64>,
+    emails: BTreeMap<(&'static str, &'static str), u64>,
+}
+
+#[derive(Debug, Default)]
+struct WorkspaceMetrics {
+    messages_created: u64,
+    reactions_added: u64,
+    ws_events_emitted: u64,
+    attachments_committed: u64,
+    attachment_bytes_committed: u64,
+}
+
+impl DomainMetrics {
+    fn workspace(&mut self, workspace_id: Uuid) -> &mut WorkspaceMetrics {
+        let mut label = workspace_id.to_string();
+        if !self.workspaces.contains_key(&label) && self.workspaces.len() >= MAX_LABELED_WORKSPACES
+        {
+            label = OTHER_WORKSPACE_LABEL.to_string();
+        }
+        self.workspaces.entry(label).or_default()
+    }
+}
+
+#[derive(Debug)]
+struct OtelInstruments {
+    in_flight: UpDownCounter<i64>,
+    requests_total: Counter<u64>,
+    request_duration_ms: Histogram<f64>,
+}
+
+impl OtelInstruments {
+    fn from_global_meter() -> Self {
+        let meter = global::meter("galynx-api");
+        Self {
+            in_flight: meter
+                .i64_up_down_counter("galynx.http.in_flight")
+                .with_description("HTTP requests currently being served")
+                .build(),
+            requests_total: meter
+                .u64_counter("galynx.http.requests")
+                .with_description("HTTP requests served")
+                .build(),
+            request_duration_ms: meter
+                .f64_histogram("galynx.http.request.duration")
+                .with_unit("ms")
+                .with_description("HTTP request latency")
+                .with_boundaries(
+                    ROUTE_LATENCY_BUCKETS_MS
+                        .iter()
+                        .map(|le| *le as f64)
+                        .collect(),
+                )
+                .build(),
+        }
+    }
+}
+
+const ROUTE_LATENCY_BUCKETS_MS: [u64; 7] = [50, 100, 250, 500, 1000, 2500, 5000];
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RouteKey {
+    method: String,
+    route: String,
+}
+
+#[derive(Debug, Default)]
+struct RouteMetrics {
+    requests_2xx: u64,
+    requests_4xx: u64,
+    requests_5xx: u64,
+    requests_other: u64,
+    latency_buckets: [u64; ROUTE_LATENCY_BUCKETS_MS.len() + 1],
+    latency_ms_sum: u64,
 }
 
 impl Default for AppMetrics {
@@ -45,6 +170,9 @@ impl Default for AppMetrics {
             latency_ms_le_2500: AtomicU64::new(0),
             latency_ms_le_5000: AtomicU64::new(0),
             latency_ms_inf: AtomicU64::new(0),
+            routes: Mutex::new(BTreeMap::new()),
+            domain: Mutex::new(DomainMetrics::default()),
+            otel: OtelInstruments::from_global_meter(),
         }
     }
 }
@@ -52,9 +180,10 @@ impl Default for AppMetrics {
 impl AppMetrics {
     pub fn on_request_start(&self) {
         self.in_flight.fetch_add(1, Ordering::Relaxed);
+        self.otel.in_flight.add(1, &[]);
     }
 
-    pub fn on_request_end(&self, status: u16, duration: Duration) {
+    pub fn on_request_end(&self, method: &str, route: &str, status: u16, duration: Duration) {
         self.in_flight.fetch_sub(1, Ordering::Relaxed);
         self.requests_total.fetch_add(1, Ordering::Relaxed);
 
@@ -84,6 +213,187 @@ impl AppMetrics {
         } else {
             self.latency_ms_inf.fetch_add(1, Ordering::Relaxed);
         }
+
+        self.record_route(method, route, status, ms);
+        self.record_otel(method, route, status, duration);
+    }
+
+    fn record_otel(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        let attributes = [
+            KeyValue::new("http.request.method", method.to_string()),
+            KeyValue::new("http.route", route.to_string()),
+            KeyValue::new("http.response.status_code", i64::from(status)),
+        ];
+        self.otel.in_flight.add(-1, &[]);
+        self.otel.requests_total.add(1, &attributes);
+        self.otel
+            .request_duration_ms
+            .record(duration.as_secs_f64() * 1000.0, &attributes);
+    }
+
+    pub fn record_message_created(&self, workspace_id: Uuid) {
+        self.with_domain(|domain| domain.workspace(workspace_id).messages_created += 1);
+    }
+
+    pub fn record_reaction_added(&self, workspace_id: Uuid) {
+        self.with_domain(|domain| domain.workspace(workspace_id).reactions_added += 1);
+    }
+
+    pub fn record_ws_event_emitted(&self, workspace_id: Uuid) {
+        self.with_domain(|domain| domain.workspace(workspace_id).ws_events_emitted += 1);
+    }
+
+    pub fn record_attachment_committed(&self, workspace_id: Uuid, size_bytes: u64) {
+        self.with_domain(|domain| {
+            let workspace = domain.workspace(workspace_id);
+            workspace.attachments_committed += 1;
+            workspace.attachment_bytes_committed += size_bytes;
+        });
+    }
+
+    pub fn record_auth_failure(&self, flow: &'static str) {
+        self.with_domain(|domain| *domain.auth_failures.entry(flow).or_default() += 1);
+    }
+
+    pub fn record_redis_publish_queued(&self) {
+        self.with_domain(|domain| domain.redis_publish_backlog += 1);
+    }
+
+    pub fn record_redis_publish_dequeued(&self) {
+        self.with_domain(|domain| {
+            domain.redis_publish_backlog = domain.redis_publish_backlog.saturating_sub(1)
+        });
+    }
+
+    pub fn record_redis_publish_dropped(&self, reason: &'static str) {
+        self.with_domain(|domain| *domain.redis_publish_dropped.entry(reason).or_default() += 1);
+    }
+
+    pub fn record_email(&self, template: &'static str, outcome: &'static str) {
+        self.with_domain(|domain| *domain.emails.entry((template, outcome)).or_default() += 1);
+    }
+
+    fn with_domain(&self, update: impl FnOnce(&mut DomainMetrics)) {
+        let mut domain = self.domain.lock().unwrap_or_else(|e| e.into_inner());
+        update(&mut domain);
+    }
+
+    fn render_domain_metrics(&self, out: &mut String) {
+        let domain = self.domain.lock().unwrap_or_else(|e| e.into_inner());
+        let counters: [(&str, WorkspaceCounter); 5] = [
+            ("galynx_messages_created_total", |m| m.messages_created),
+            ("galynx_reactions_added_total", |m| m.reactions_added),
+            ("galynx_ws_events_emitted_total", |m| m.ws_events_emitted),
+            ("galynx_attachments_committed_total", |m| {
+                m.attachments_committed
+            }),
+            ("galynx_attachment_bytes_committed_total", |m| {
+                m.attachment_bytes_committed
+            }),
+        ];
+        for (name, value) in counters {
+            let _ = writeln!(out, "# TYPE {name} counter");
+            for (workspace_id, metrics) in domain.workspaces.iter() {
+                let _ = writeln!(
+                    out,
+                    "{name}{{workspace_id=\"{workspace_id}\"}} {}",
+                    value(metrics)
+                );
+            }
+        }
+        out.push_str("# TYPE galynx_auth_failures_total counter\n");
+        for (flow, count) in domain.auth_failures.iter() {
+            let _ = writeln!(out, "galynx_auth_failures_total{{flow=\"{flow}\"}} {count}");
+        }
+        let _ = writeln!(
+            out,
+            "# TYPE galynx_ws_redis_publish_backlog gauge\ngalynx_ws_redis_publish_backlog {}",
+            domain.redis_publish_backlog
+        );
+        out.push_str("# TYPE galynx_ws_redis_publish_dropped_total counter\n");
+        for (reason, count) in domain.redis_publish_dropped.iter() {
+            let _ = writeln!(
+                out,
+                "galynx_ws_redis_publish_dropped_total{{reason=\"{reason}\"}} {count}"
+            );
+        }
+        out.push_str("# TYPE galynx_emails_total counter\n");
+        for ((template, outcome), count) in domain.emails.iter() {
+            let _ = writeln!(
+                out,
+                "galynx_emails_total{{template=\"{template}\",outcome=\"{outcome}\"}} {count}"
+            );
+        }
+    }
+
+    fn record_route(&self, method: &str, route: &str, status: u16, ms: u64) {
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = routes
+            .entry(RouteKey {
+                method: method.to_string(),
+                route: route.to_string(),
+            })
+            .or_default();
+        match status {
+            200..=299 => entry.requests_2xx += 1,
+            400..=499 => entry.requests_4xx += 1,
+            500.. => entry.requests_5xx += 1,
+            _ => entry.requests_other += 1,
+        }
+        let bucket = ROUTE_LATENCY_BUCKETS_MS
+            .iter()
+            .position(|le| ms <= *le)
+            .unwrap_or(ROUTE_LATENCY_BUCKETS_MS.len());
+        entry.latency_buckets[bucket] += 1;
+        entry.latency_ms_sum += ms;
+    }
+
+    fn render_route_metrics(&self, out: &mut String) {
+        let routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        out.push_str("# TYPE galynx_http_route_requests_total counter\n");
+        for (key, metrics) in routes.iter() {
+            for (status_class, count) in [
+                ("2xx", metrics.requests_2xx),
+                ("4xx", metrics.requests_4xx),
+                ("5xx", metrics.requests_5xx),
+                ("other", metrics.requests_other),
+            ] {
+                if count == 0 {
+                    continue;
+                }
+                let _ = writeln!(
+                    out,
+                    "galynx_http_route_requests_total{{method=\"{}\",route=\"{}\",status_class=\"{}\"}} {}",
+                    key.method, key.route, status_class, count
+                );
+            }
+        }
+        out.push_str("# TYPE galynx_http_route_request_duration_ms histogram\n");
+        for (key, metrics) in routes.iter() {
+            let labels = format!("method=\"{}\",route=\"{}\"", key.method, key.route);
+            let mut cumulative = 0;
+            for (index, le) in ROUTE_LATENCY_BUCKETS_MS.iter().enumerate() {
+                cumulative += metrics.latency_buckets[index];
+                let _ = writeln!(
+                    out,
+                    "galynx_http_route_request_duration_ms_bucket{{{labels},le=\"{le}\"}} {cumulative}"
+                );
+            }
+            cumulative += metrics.latency_buckets[ROUTE_LATENCY_BUCKETS_MS.len()];
+            let _ = writeln!(
+                out,
+                "galynx_http_route_request_duration_ms_bucket{{{labels},le=\"+Inf\"}} {cumulative}"
+            );
+            let _ = writeln!(
+                out,
+                "galynx_http_route_request_duration_ms_sum{{{labels}}} {}",
+                metrics.latency_ms_sum
+            );
+            let _ = writeln!(
+                out,
+                "galynx_http_route_request_duration_ms_count{{{labels}}} {cumulative}"
+            );
+        }
     }
 
     pub fn render_prometheus(&self) -> String {
@@ -96,7 +406,7 @@ impl AppMetrics {
         let le_5000 = le_2500 + self.latency_ms_le_5000.load(Ordering::Relaxed);
         let total = le_5000 + self.latency_ms_inf.load(Ordering::Relaxed);
 
-        format!(
+        let mut out = format!(
             concat!(
                 "# TYPE galynx_http_in_flight gauge\n",
                 "galynx_http_in_flight {}\n",
@@ -130,7 +440,10 @@ impl AppMetrics {
             le_5000,
             total,
             total,
-        )
+        );
+        self.render_route_metrics(&mut out);
+        self.render_domain_metrics(&mut out);
+        out
     }
 }
 
@@ -139,22 +452,323 @@ pub async fn metrics_middleware(
     request: Request<axum::body::Body>,
     next: Next,
 ) -> Response {
-    let started_at = std::time::Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let started_at = Instant::now();
     state.metrics.on_request_start();
-    let response = next.run(request).await;
+    let (response, workspace_id) = REQUEST_WORKSPACE
+        .scope(Cell::new(None), async {
+            let response = next.run(request).await;
+            (response, REQUEST_WORKSPACE.with(Cell::get))
+        })
+        .await;
+    let elapsed = started_at.elapsed();
     state
         .metrics
-        .on_request_end(response.status().as_u16(), started_at.elapsed());
+        .on_request_end(&method, &route, response.status().as_u16(), elapsed);
+    if let Some(threshold) = slow_threshold(state.config.slow_operation_threshold_ms)
+        && elapsed >= threshold
+    {
+        warn!(
+            method = %method,
+            route = %route,
+            status = response.status().as_u16(),
+            workspace_id = workspace_id.map(tracing::field::display),
+            duration_ms = elapsed.as_millis() as u64,
+            "slow request"
+        );
+    }
     response
 }
 
+fn slow_threshold(threshold_ms: u64) -> Option<Duration> {
+    (threshold_ms > 0).then(|| Duration::from_millis(threshold_ms))
+}
+
+pub struct SlowStorageLayer {
+    threshold: Option<Duration>,
+}
+
+struct StorageSpanTiming {
+    started_at: Instant,
+    collection: String,
+    operation: String,
+}
+
+#[derive(Default)]
+struct StorageFieldVisitor {
+    collection: Option<String>,
+    operation: Option<String>,
+}
+
+impl Visit for StorageFieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "collection" => self.collection = Some(value.to_string()),
+            "operation" => self.operation = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl SlowStorageLayer {
+    pub fn new(threshold_ms: u64) -> Self {
+        Self {
+            threshold: slow_threshold(threshold_ms),
+        }
+    }
+}
+
+impl<S> Layer<S> for SlowStorageLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if self.threshold.is_none() || !attrs.metadata().target().ends_with("::storage") {
+            return;
+        }
+        let mut visitor = StorageFieldVisitor::default();
+        attrs.record(&mut visitor);
+        let (Some(collection), Some(operation)) = (visitor.collection, visitor.operation) else {
+            return;
+        };
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(StorageSpanTiming {
+                started_at: Instant::now(),
+                collection,
+                operation,
+            });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+        let timing = ctx
+            .span(&id)
+            .and_then(|span| span.extensions_mut().remove::<StorageSpanTiming>());
+        let Some(timing) = timing else {
+            return;
+        };
+        let elapsed = timing.started_at.elapsed();
+        if elapsed >= threshold {
+            warn!(
+                collection = %timing.collection,
+                operation = %timing.operation,
+                duration_ms = elapsed.as_millis() as u64,
+                "slow storage operation"
+            );
+        }
+    }
+}
+
+/// Tags the request being served with the caller's workspace; a no-op
+/// outside `metrics_middleware` (WebSocket commands, background jobs).
+pub(crate) fn record_request_workspace(workspace_id: Uuid) {
+    let _ = REQUEST_WORKSPACE.try_with(|workspace| workspace.set(Some(workspace_id)));
+}
+
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+pub async fn request_id_middleware(mut request: Request<axum::body::Body>, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| is_valid_request_id(value))
+        .map(ToString::to_string)
+        .unwrap_or_else(|| Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)).to_string());
+    let header_value =
+        HeaderValue::from_str(&request_id).expect("request id must be a valid header value");
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header_value.clone());
+
+    let mut response = REQUEST_ID.scope(request_id, next.run(request)).await;
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header_value);
+    response
+}
+
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b':'))
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/metrics",
+    operation_id = "metrics",
+    tag = "system",
+    security((), ("metrics_token" = [])),
     responses(
-        (status = 200, description = "Prometheus metrics", body = String)
+        (status = 200, description = "Prometheus metrics", body = String),
+        (status = 401, description = "Missing or invalid metrics token", body = ErrorResponse)
     )
 )]
-pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
-    state.metrics.render_prometheus()
+pub async fn metrics_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(expected) = state.config.metrics_token.as_deref() {
+        let provided = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .unwrap_or_default();
+        if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            return Err(ApiError::Unauthorized("invalid metrics token".to_string()));
+        }
+    }
+    Ok(state.metrics.render_prometheus())
+}
+
+pub(crate) fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    left.iter()
+        .zip(right)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn render_prometheus_includes_domain_counters() {
+        let metrics = AppMetrics::default();
+        let workspace_id = Uuid::new_v4();
+        metrics.record_message_created(workspace_id);
+        metrics.record_message_created(workspace_id);
+        metrics.record_attachment_committed(workspace_id, 2048);
+        metrics.record_auth_failure("login");
+
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains(&format!(
+            "galynx_messages_created_total{{workspace_id=\"{workspace_id}\"}} 2"
+        )));
+        assert!(rendered.contains(&format!(
+            "galynx_attachment_bytes_committed_total{{workspace_id=\"{workspace_id}\"}} 2048"
+        )));
+        assert!(rendered.contains("galynx_auth_failures_total{flow=\"login\"} 1"));
+    }
+
+    #[derive(Clone, Default)]
+    struct SlowEventCounter(Arc<AtomicU64>);
+
+    impl<S: Subscriber> Layer<S> for SlowEventCounter {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == tracing::Level::WARN {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn slow_storage_layer_warns_on_slow_spans_only() {
+        let counter = SlowEventCounter::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(SlowStorageLayer::new(20))
+            .with(counter.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let fast = tracing::info_span!(
+                target: "galynx_api::storage",
+                "get_channel",
+                collection = "channels",
+                operation = "get_channel"
+            );
+            drop(fast);
+
+            let slow = tracing::info_span!(
+                target: "galynx_api::storage",
+                "list_messages",
+                collection = "messages",
+                operation = "list_messages"
+            );
+            std::thread::sleep(Duration::from_millis(30));
+            drop(slow);
+        });
+
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn constant_time_eq_compares_full_tokens() {
+        assert!(constant_time_eq(b"scrape-token", b"scrape-token"));
+        assert!(!constant_time_eq(b"scrape-token", b"scrape-tokeN"));
+        assert!(!constant_time_eq(b"scrape", b"scrape-token"));
+    }
+
+    #[tokio::test]
+    async fn request_workspace_is_recorded_only_inside_a_request() {
+        let workspace_id = Uuid::new_v4();
+        record_request_workspace(workspace_id);
+        let recorded = REQUEST_WORKSPACE
+            .scope(Cell::new(None), async {
+                record_request_workspace(workspace_id);
+                REQUEST_WORKSPACE.with(Cell::get)
+            })
+            .await;
+        assert_eq!(recorded, Some(workspace_id));
+    }
+
+    #[test]
+    fn request_id_validation_rejects_unsafe_values() {
+        assert!(is_valid_request_id("0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b"));
+        assert!(is_valid_request_id("lb:abc_123.4"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[test]
+    fn render_prometheus_includes_route_labels() {
+        let metrics = AppMetrics::default();
+        metrics.on_request_start();
+        metrics.on_request_end(
+            "GET",
+            "/api/v1/channels/:channel_id/messages",
+            200,
+            Duration::from_millis(120),
+        );
+        metrics.on_request_start();
+        metrics.on_request_end("POST", "/api/v1/channels", 400, Duration::from_millis(10));
+
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains(
+            "galynx_http_route_requests_total{method=\"GET\",route=\"/api/v1/channels/:channel_id/messages\",status_class=\"2xx\"} 1"
+        ));
+        assert!(rendered.contains(
+            "galynx_http_route_request_duration_ms_bucket{method=\"GET\",route=\"/api/v1/channels/:channel_id/messages\",le=\"100\"} 0"
+        ));
+        assert!(rendered.contains(
+            "galynx_http_route_request_duration_ms_bucket{method=\"GET\",route=\"/api/v1/channels/:channel_id/messages\",le=\"250\"} 1"
+        ));
+        assert!(rendered.contains(
+            "galynx_http_route_requests_total{method=\"POST\",route=\"/api/v1/channels\",status_class=\"4xx\"} 1"
+        ));
+    }
 }
@@ -0,0 +1,348 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, header},
+    routing::{get, put},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    admin::{WorkspaceStatsResponse, workspace_stats_for},
+    app::AppState,
+    errors::{ApiError, ApiResult, ErrorResponse},
+    observability::constant_time_eq,
+    realtime,
+    storage::WorkspaceRecordStore,
+};
+
+const MAX_SUSPENSION_REASON_LEN: usize = 500;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OperatorWorkspaceResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub suspended_at: Option<i64>,
+    pub suspended_reason: Option<String>,
+    pub usage: WorkspaceStatsResponse,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct UsageTotalsResponse {
+    pub workspaces: usize,
+    pub suspended_workspaces: usize,
+    pub members: usize,
+    pub channels: usize,
+    pub messages: usize,
+    pub attachments: usize,
+    pub attachment_bytes: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OperatorWorkspaceListResponse {
+    pub items: Vec<OperatorWorkspaceResponse>,
+    pub totals: UsageTotalsResponse,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OperatorMemberResponse {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OperatorChannelResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub is_private: bool,
+    pub created_at: i64,
+}
+
+/// Metadata-only view of a tenant: no message bodies or attachment contents.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OperatorWorkspaceDetailResponse {
+    pub workspace: OperatorWorkspaceResponse,
+    pub members: Vec<OperatorMemberResponse>,
+    pub channels: Vec<OperatorChannelResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSuspensionRequest {
+    pub suspended: bool,
+    pub reason: Option<String>,
+}
+
+/// Cross-workspace routes; only mounted when `OPERATOR_TOKEN` is configured.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/admin/workspaces", get(list_workspaces))
+        .route("/api/v1/admin/workspaces/:id", get(get_workspace))
+        .route(
+            "/api/v1/admin/workspaces/:id/suspension",
+            put(update_suspension),
+        )
+}
+
+/// Operator endpoints take `Authorization: Bearer <OPERATOR_TOKEN>`, never a
+/// user access token, so no workspace role can reach them.
+pub(crate) fn ensure_operator(state: &AppState, headers: &HeaderMap) -> ApiResult<()> {
+    let Some(expected) = state.config.operator_token.as_deref() else {
+        return Err(ApiError::NotFound("operator API is disabled".to_string()));
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+    if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized("invalid operator token".to_string()))
+    }
+}
+
+async fn workspace_response(
+    state: &AppState,
+    workspace: WorkspaceRecordStore,
+) -> OperatorWorkspaceResponse {
+    OperatorWorkspaceResponse {
+        usage: workspace_stats_for(&state.storage, workspace.id).await,
+        id: workspace.id,
+        name: workspace.name,
+        created_by: workspace.created_by,
+        created_at: workspace.created_at,
+        suspended_at: workspace.suspended_at,
+        suspended_reason: workspace.suspended_reason,
+    }
+}
+
+fn usage_totals(items: &[OperatorWorkspaceResponse]) -> UsageTotalsResponse {
+    items
+        .iter()
+        .fold(UsageTotalsResponse::default(), |mut totals, item| {
+            totals.workspaces += 1;
+            totals.suspended_workspaces += usize::from(item.suspended_at.is_some());
+            totals.members += item.usage.members;
+            totals.channels += item.usage.channels;
+            totals.messages += item.usage.messages;
+            totals.attachments += item.usage.attachments;
+            totals.attachment_bytes += item.usage.attachment_bytes;
+            totals
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/workspaces",
+    operation_id = "operator_list_workspaces",
+    tag = "operator",
+    security(("operator_token" = [])),
+    responses(
+        (status = 200, description = "Every workspace with its usage and instance totals", body = OperatorWorkspaceListResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_workspaces(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<OperatorWorkspaceListResponse>> {
+    ensure_operator(&state, &headers)?;
+    let mut workspaces = state.storage.list_workspaces().await;
+    workspaces.sort_by_key(|workspace| (workspace.created_at, workspace.id));
+    let mut items = Vec::with_capacity(workspaces.len());
+    for workspace in workspaces {
+        items.push(workspace_response(&state, workspace).await);
+    }
+    let totals = usage_totals(&items);
+    Ok(Json(OperatorWorkspaceListResponse { items, totals }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/workspaces/{id}",
+    operation_id = "operator_get_workspace",
+    tag = "operator",
+    security(("operator_token" = [])),
+    responses(
+        (status = 200, description = "Workspace metadata, members and channels", body = OperatorWorkspaceDetailResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Workspace not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_workspace(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+) -> ApiResult<Json<OperatorWorkspaceDetailResponse>> {
+    ensure_operator(&state, &headers)?;
+    let workspace = state
+        .storage
+        .get_workspace(workspace_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound("workspace not found".to_string()))?;
+    let mut members = Vec::new();
+    for (user_id, role) in state.storage.list_workspace_memberships(workspace_id).await {
+        let Some(user) = state.storage.get_auth_user_by_id(user_id).await else {
+            continue;
+        };
+        members.push(OperatorMemberResponse {
+            user_id,
+            email: user.email,
+            name: user.name,
+            role,
+        });
+    }
+    members.sort_by(|a, b| a.email.cmp(&b.email));
+    let mut channels = state
+        .storage
+        .list_channels(workspace_id)
+        .await
+        .into_iter()
+        .map(|channel| OperatorChannelResponse {
+            id: channel.id,
+            name: channel.name,
+            is_private: channel.is_private,
+            created_at: channel.created_at,
+        })
+        .collect::<Vec<_>>();
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(OperatorWorkspaceDetailResponse {
+        workspace: workspace_response(&state, workspace).await,
+        members,
+        channels,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/workspaces/{id}/suspension",
+    operation_id = "operator_update_suspension",
+    tag = "operator",
+    security(("operator_token" = [])),
+    request_body = UpdateSuspensionRequest,
+    responses(
+        (status = 200, description = "Suspension updated", body = OperatorWorkspaceResponse),
+        (status = 400, description = "Invalid reason", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid operator token", body = ErrorResponse),
+        (status = 404, description = "Workspace not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_suspension(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<UpdateSuspensionRequest>,
+) -> ApiResult<Json<OperatorWorkspaceResponse>> {
+    ensure_operator(&state, &headers)?;
+    let reason = payload
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_SUSPENSION_REASON_LEN)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "reason must be at most {MAX_SUSPENSION_REASON_LEN} characters"
+        )));
+    }
+    let mut workspace = state
+        .storage
+        .get_workspace(workspace_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound("workspace not found".to_string()))?;
+    if payload.suspended {
+        workspace.suspended_at = workspace
+            .suspended_at
+            .or(Some(Utc::now().timestamp_millis()));
+        workspace.suspended_reason = reason;
+    } else {
+        workspace.suspended_at = None;
+        workspace.suspended_reason = None;
+    }
+    state.storage.put_workspace(workspace.clone()).await;
+
+    let action = if payload.suspended {
+        "WORKSPACE_SUSPENDED"
+    } else {
+        "WORKSPACE_UNSUSPENDED"
+    };
+    tracing::warn!(%workspace_id, "{}", action);
+    state
+        .audit
+        .write(
+            workspace_id,
+            None,
+            action,
+            "workspace",
+            Some(workspace_id.to_string()),
+            json!({ "reason": workspace.suspended_reason, "actor": "operator" }),
+        )
+        .await;
+    state
+        .realtime
+        .emit(
+            workspace_id,
+            realtime::make_event(
+                action,
+                workspace_id,
+                None,
+                None,
+                json!({
+                    "suspended_at": workspace.suspended_at,
+                    "reason": workspace.suspended_reason,
+                }),
+            ),
+        )
+        .await;
+    Ok(Json(workspace_response(&state, workspace).await))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(
+        suspended: bool,
+        messages: usize,
+        attachment_bytes: u64,
+    ) -> OperatorWorkspaceResponse {
+        let id = Uuid::new_v4();
+        OperatorWorkspaceResponse {
+            id,
+            name: "tenant".to_string(),
+            created_by: Uuid::new_v4(),
+            created_at: 0,
+            suspended_at: suspended.then_some(1),
+            suspended_reason: None,
+            usage: WorkspaceStatsResponse {
+                workspace_id: id,
+                members: 2,
+                channels: 1,
+                messages,
+                deleted_messages: 0,
+                attachments: 1,
+                attachment_bytes,
+                pending_uploads: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn usage_totals_sum_every_workspace() {
+        let totals = usage_totals(&[workspace(false, 10, 100), workspace(true, 5, 50)]);
+        assert_eq!(totals.workspaces, 2);
+        assert_eq!(totals.suspended_workspaces, 1);
+        assert_eq!(totals.members, 4);
+        assert_eq!(totals.messages, 15);
+        assert_eq!(totals.attachment_bytes, 150);
+    }
+}
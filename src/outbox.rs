@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::time::MissedTickBehavior;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    realtime::{self, WsEventEnvelope},
+    storage::OutboxEventRecordStore,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+const CLAIM_LEASE_MS: i64 = 30_000;
+const CLAIM_BATCH: usize = 100;
+const DELIVERED_RETENTION_MS: i64 = 60 * 60 * 1000;
+
+/// Outbox entry for a channel event emitted to `workspace_id` (and the
+/// workspaces the channel is shared with) once dispatched.
+pub(crate) fn record(workspace_id: Uuid, event: &WsEventEnvelope) -> OutboxEventRecordStore {
+    OutboxEventRecordStore {
+        id: Uuid::now_v7(),
+        workspace_id,
+        event_json: serde_json::to_string(event).unwrap_or_default(),
+        created_at: Utc::now().timestamp_millis(),
+        claimed_until: 0,
+        delivered_at: None,
+    }
+}
+
+/// Publishes outbox events as soon as they are queued on this instance and
+/// polls for ones left behind by crashed or remote writers.
+pub(crate) fn spawn_dispatcher(state: AppState) {
+    tokio::spawn(async move {
+        let signal = state.storage.outbox_signal();
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut purge = tokio::time::interval(PURGE_INTERVAL);
+        purge.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = signal.notified() => {}
+                _ = poll.tick() => {}
+                _ = purge.tick() => {
+                    let before = Utc::now().timestamp_millis() - DELIVERED_RETENTION_MS;
+                    state.storage.purge_delivered_outbox(before).await;
+                }
+            }
+            dispatch_pending(&state).await;
+        }
+    });
+}
+
+/// Emits every claimable outbox event and marks it delivered. An instance
+/// that dies between both steps leaves the lease to expire, so the event is
+/// published again: delivery is at least once.
+async fn dispatch_pending(state: &AppState) {
+    state
+        .storage
+        .recover_embedded_outbox_events(CLAIM_BATCH as i64)
+        .await;
+    loop {
+        let now = Utc::now().timestamp_millis();
+        let batch = state
+            .storage
+            .claim_outbox_events(now, now + CLAIM_LEASE_MS, CLAIM_BATCH)
+            .await;
+        if batch.is_empty() {
+            return;
+        }
+        for record in batch {
+            match serde_json::from_str::<WsEventEnvelope>(&record.event_json) {
+                Ok(event) => realtime::emit_channel_event(state, record.workspace_id, event).await,
+                Err(error) => warn!("dropping unreadable outbox event {}: {}", record.id, error),
+            }
+            state
+                .storage
+                .mark_outbox_delivered(record.id, Utc::now().timestamp_millis())
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::storage::{MessageRecordStore, PersistenceBackend, Storage};
+
+    #[tokio::test]
+    async fn outbox_events_are_leased_until_delivered() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let (workspace_id, channel_id) = (Uuid::new_v4(), Uuid::new_v4());
+        for body in ["first", "second"] {
+            let message = MessageRecordStore {
+                id: Uuid::new_v4(),
+                workspace_id,
+                channel_id,
+                sender_id: Uuid::new_v4(),
+                body_md: body.to_string(),
+                thread_root_id: None,
+                origin_workspace_id: None,
+                seq: 0,
+                created_at: 0,
+                edited_at: None,
+                deleted_at: None,
+                crosspost_group_id: None,
+            };
+            let event = realtime::make_event(
+                "MESSAGE_CREATED",
+                workspace_id,
+                Some(channel_id),
+                None,
+                json!({ "body_md": body }),
+            );
+            storage
+                .write_message_with_event(message.clone(), record(workspace_id, &event))
+                .await
+                .expect("message stored");
+            assert!(storage.get_message(&message.id).await.is_some());
+        }
+
+        let now = Utc::now().timestamp_millis();
+        let claimed = storage.claim_outbox_events(now, now + 1_000, 10).await;
+        assert_eq!(claimed.len(), 2);
+        let event: WsEventEnvelope =
+            serde_json::from_str(&claimed[0].event_json).expect("event should round-trip");
+        assert_eq!(event.payload["body_md"], "first");
+        assert!(
+            storage
+                .claim_outbox_events(now, now + 1_000, 10)
+                .await
+                .is_empty()
+        );
+
+        storage.mark_outbox_delivered(claimed[0].id, now).await;
+        let reclaimed = storage
+            .claim_outbox_events(now + 1_000, now + 2_000, 10)
+            .await;
+        assert_eq!(reclaimed.len(), 1);
+        assert_eq!(reclaimed[0].id, claimed[1].id);
+
+        storage.purge_delivered_outbox(now + 1).await;
+        storage.mark_outbox_delivered(claimed[1].id, now).await;
+        assert!(
+            storage
+                .claim_outbox_events(now + 5_000, now + 6_000, 10)
+                .await
+                .is_empty()
+        );
+    }
+}
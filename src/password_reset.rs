@@ -0,0 +1,160 @@
+use std::net::SocketAddr;
+
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{
+    app::AppState,
+    auth::{hash_password, token_hash},
+    email::{self, EmailTemplate},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+};
+
+const PURPOSE: &str = "password_reset";
+const TOKEN_TTL_MINUTES: i64 = 60;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    /// Token from the link in the reset email.
+    pub token: String,
+    pub password: String,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/auth/password/forgot", post(forgot_password))
+        .route("/api/v1/auth/password/reset", post(reset_password))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/forgot",
+    operation_id = "forgot_password",
+    tag = "auth",
+    security(()),
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 202, description = "Reset email queued if the address belongs to a user"),
+        (status = 429, description = "Rate limited", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn forgot_password(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> ApiResult<StatusCode> {
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    state
+        .rate_limit
+        .check_auth(&client_ip, Some(&payload.email))
+        .await?;
+
+    // Unknown addresses get the same answer so the endpoint does not reveal
+    // which emails have accounts.
+    let Some(user) = state.storage.get_auth_user_by_email(&payload.email).await else {
+        return Ok(StatusCode::ACCEPTED);
+    };
+    let token = email::issue_token(&state.storage, &user, PURPOSE, TOKEN_TTL_MINUTES).await;
+    state.email.send(
+        &user.email,
+        &user.name,
+        EmailTemplate::PasswordReset {
+            token,
+            expires_minutes: TOKEN_TTL_MINUTES,
+        },
+    );
+    state
+        .audit
+        .write_for_user(
+            user.id,
+            "AUTH_PASSWORD_RESET_REQUESTED",
+            json!({ "ip": client_ip }),
+        )
+        .await;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/reset",
+    operation_id = "reset_password",
+    tag = "auth",
+    security(()),
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 204, description = "Password changed and every session signed out"),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn reset_password(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> ApiResult<StatusCode> {
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    state.rate_limit.check_auth(&client_ip, None).await?;
+
+    let password = payload.password.trim();
+    let mut errors = FieldErrors::default();
+    if password.len() < 8 {
+        errors.add("password", "must have at least 8 characters");
+    }
+    errors.into_result()?;
+
+    let now = Utc::now().timestamp();
+    let invalid = || {
+        ApiError::Coded(
+            ErrorCode::EmailTokenInvalid,
+            "invalid or expired reset token".to_string(),
+        )
+    };
+    let token = state
+        .storage
+        .take_email_token(&token_hash(payload.token.trim()), PURPOSE, now)
+        .await
+        .ok_or_else(invalid)?;
+    let mut user = state
+        .storage
+        .get_auth_user_by_id(token.user_id)
+        .await
+        .filter(|user| user.email.eq_ignore_ascii_case(&token.email))
+        .ok_or_else(invalid)?;
+
+    user.password_hash = hash_password(password)?;
+    state.storage.put_auth_user(user.clone()).await;
+    state.storage.remove_email_tokens(user.id, PURPOSE).await;
+    let revoked = state
+        .storage
+        .revoke_refresh_sessions(user.id, None, now)
+        .await;
+    state
+        .audit
+        .write_for_user(
+            user.id,
+            "AUTH_PASSWORD_RESET",
+            json!({ "ip": client_ip, "sessions_revoked": revoked }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
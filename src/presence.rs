@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    errors::{ApiResult, ErrorResponse},
+    workspaces::ensure_context_workspace,
+};
+
+/// How often away timeouts are checked and local presence is re-announced to
+/// the other instances.
+const PRESENCE_TICK: Duration = Duration::from_secs(15);
+/// Presence reported by another instance is dropped when that instance stops
+/// refreshing it (e.g. it crashed).
+const REMOTE_PRESENCE_TTL_MS: i64 = 4 * 15_000;
+
+/// (workspace_id, user_id)
+type PresenceKey = (Uuid, Uuid);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceStatus {
+    Offline,
+    Away,
+    Online,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PresenceResponse {
+    pub user_id: Uuid,
+    pub status: PresenceStatus,
+    /// Last command received from any of the user's sockets (unix millis).
+    pub last_active_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PresenceListResponse {
+    /// Users with at least one open socket; everyone else is offline.
+    pub items: Vec<PresenceResponse>,
+}
+
+/// Presence of one user on one instance, as relayed over the Redis bridge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PresenceSync {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub status: PresenceStatus,
+    pub last_active_at: i64,
+}
+
+struct LocalPresence {
+    connections: usize,
+    last_active_at: i64,
+}
+
+struct RemotePresence {
+    status: PresenceStatus,
+    last_active_at: i64,
+    seen_at: i64,
+}
+
+/// Who is connected, on this instance and (through the Redis bridge) on the
+/// others. A user is online while any socket sent a command within the away
+/// timeout, away while sockets are open but idle, and offline otherwise.
+pub(crate) struct PresenceTracker {
+    away_after_ms: i64,
+    local: HashMap<PresenceKey, LocalPresence>,
+    remote: HashMap<PresenceKey, HashMap<String, RemotePresence>>,
+    /// Last status announced with `PRESENCE_CHANGED`; offline users are absent.
+    announced: HashMap<PresenceKey, PresenceResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/v1/workspaces/:id/presence", get(get_presence))
+}
+
+pub(crate) fn spawn_ticker(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PRESENCE_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            state.realtime.refresh_presence().await;
+        }
+    });
+}
+
+impl PresenceTracker {
+    pub fn new(away_after: Duration) -> Self {
+        Self {
+            away_after_ms: away_after.as_millis() as i64,
+            local: HashMap::new(),
+            remote: HashMap::new(),
+            announced: HashMap::new(),
+        }
+    }
+
+    pub fn connect(&mut self, workspace_id: Uuid, user_id: Uuid, now: i64) {
+        let local = self
+            .local
+            .entry((workspace_id, user_id))
+            .or_insert(LocalPresence {
+                connections: 0,
+                last_active_at: now,
+            });
+        local.connections += 1;
+        local.last_active_at = now;
+    }
+
+    pub fn disconnect(&mut self, workspace_id: Uuid, user_id: Uuid) {
+        let key = (workspace_id, user_id);
+        if let Some(local) = self.local.get_mut(&key) {
+            local.connections = local.connections.saturating_sub(1);
+            if local.connections == 0 {
+                self.local.remove(&key);
+            }
+        }
+    }
+
+    /// Records activity from one of the user's sockets; returns true when the
+    /// user was away on this instance and is now back online.
+    pub fn touch(&mut self, workspace_id: Uuid, user_id: Uuid, now: i64) -> bool {
+        let away_after_ms = self.away_after_ms;
+        let Some(local) = self.local.get_mut(&(workspace_id, user_id)) else {
+            return false;
+        };
+        let was_away = now - local.last_active_at >= away_after_ms;
+        local.last_active_at = now;
+        was_away
+    }
+
+    /// This instance's view of the user, to share with the other instances.
+    pub fn local_sync(&self, workspace_id: Uuid, user_id: Uuid, now: i64) -> PresenceSync {
+        let (status, last_active_at) = match self.local.get(&(workspace_id, user_id)) {
+            Some(local) => (self.local_status(local, now), local.last_active_at),
+            None => (PresenceStatus::Offline, now),
+        };
+        PresenceSync {
+            workspace_id,
+            user_id,
+            status,
+            last_active_at,
+        }
+    }
+
+    pub fn local_snapshot(&self, now: i64) -> Vec<PresenceSync> {
+        self.local
+            .keys()
+            .map(|(workspace_id, user_id)| self.local_sync(*workspace_id, *user_id, now))
+            .collect()
+    }
+
+    pub fn apply_remote(&mut self, instance_id: &str, sync: &PresenceSync, now: i64) {
+        let key = (sync.workspace_id, sync.user_id);
+        let instances = self.remote.entry(key).or_default();
+        if sync.status == PresenceStatus::Offline {
+            instances.remove(instance_id);
+        } else {
+            instances.insert(
+                instance_id.to_string(),
+                RemotePresence {
+                    status: sync.status,
+                    last_active_at: sync.last_active_at,
+                    seen_at: now,
+                },
+            );
+        }
+        if instances.is_empty() {
+            self.remote.remove(&key);
+        }
+    }
+
+    /// Online and away users of the workspace across all instances.
+    pub fn workspace(&self, workspace_id: Uuid, now: i64) -> Vec<PresenceResponse> {
+        let mut users = self
+            .local
+            .keys()
+            .chain(self.remote.keys())
+            .filter(|(workspace, _)| *workspace == workspace_id)
+            .map(|(_, user_id)| *user_id)
+            .collect::<Vec<_>>();
+        users.sort();
+        users.dedup();
+        users
+            .into_iter()
+            .filter_map(|user_id| self.current(workspace_id, user_id, now))
+            .collect()
+    }
+
+    /// Drops stale remote entries and returns, per workspace, every user whose
+    /// status differs from the last one announced.
+    pub fn changes(&mut self, now: i64) -> Vec<(Uuid, PresenceResponse)> {
+        for instances in self.remote.values_mut() {
+            instances.retain(|_, remote| now - remote.seen_at < REMOTE_PRESENCE_TTL_MS);
+        }
+        self.remote.retain(|_, instances| !instances.is_empty());
+
+        let mut keys = self
+            .local
+            .keys()
+            .chain(self.remote.keys())
+            .chain(self.announced.keys())
+            .copied()
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        let mut changes = Vec::new();
+        for (workspace_id, user_id) in keys {
+            let key = (workspace_id, user_id);
+            let previous = self.announced.get(&key);
+            let current = match self.current(workspace_id, user_id, now) {
+                Some(current) => current,
+                None => PresenceResponse {
+                    user_id,
+                    status: PresenceStatus::Offline,
+                    last_active_at: previous.map_or(now, |previous| previous.last_active_at),
+                },
+            };
+            let previous_status =
+                previous.map_or(PresenceStatus::Offline, |previous| previous.status);
+            if current.status == previous_status {
+                continue;
+            }
+            if current.status == PresenceStatus::Offline {
+                self.announced.remove(&key);
+            } else {
+                self.announced.insert(key, current.clone());
+            }
+            changes.push((workspace_id, current));
+        }
+        changes
+    }
+
+    fn local_status(&self, local: &LocalPresence, now: i64) -> PresenceStatus {
+        if now - local.last_active_at >= self.away_after_ms {
+            PresenceStatus::Away
+        } else {
+            PresenceStatus::Online
+        }
+    }
+
+    fn current(&self, workspace_id: Uuid, user_id: Uuid, now: i64) -> Option<PresenceResponse> {
+        let key = (workspace_id, user_id);
+        let local = self
+            .local
+            .get(&key)
+            .map(|local| (self.local_status(local, now), local.last_active_at));
+        let remote = self
+            .remote
+            .get(&key)
+            .into_iter()
+            .flat_map(HashMap::values)
+            .map(|remote| (remote.status, remote.last_active_at));
+        let (status, last_active_at) = local.into_iter().chain(remote).fold(
+            None,
+            |best: Option<(PresenceStatus, i64)>, (status, last_active_at)| {
+                Some(
+                    best.map_or((status, last_active_at), |(best_status, best_at)| {
+                        (best_status.max(status), best_at.max(last_active_at))
+                    }),
+                )
+            },
+        )?;
+        Some(PresenceResponse {
+            user_id,
+            status,
+            last_active_at,
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/workspaces/{id}/presence",
+    operation_id = "get_workspace_presence",
+    tag = "realtime",
+    responses(
+        (status = 200, description = "Online and away members", body = PresenceListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Token belongs to another workspace", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_presence(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+) -> ApiResult<Json<PresenceListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    Ok(Json(PresenceListResponse {
+        items: state.realtime.workspace_presence(workspace_id).await,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presence_goes_away_when_idle_and_merges_other_instances() {
+        let workspace_id = Uuid::new_v4();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut tracker = PresenceTracker::new(Duration::from_secs(60));
+        let status = |changes: &[(Uuid, PresenceResponse)], user_id| {
+            changes
+                .iter()
+                .find(|(_, presence)| presence.user_id == user_id)
+                .map(|(_, presence)| presence.status)
+        };
+
+        tracker.connect(workspace_id, alice, 0);
+        tracker.connect(workspace_id, alice, 0);
+        let changes = tracker.changes(1_000);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(status(&changes, alice), Some(PresenceStatus::Online));
+        assert!(tracker.changes(2_000).is_empty());
+
+        assert_eq!(
+            status(&tracker.changes(60_000), alice),
+            Some(PresenceStatus::Away)
+        );
+        assert!(tracker.touch(workspace_id, alice, 61_000));
+        assert!(!tracker.touch(workspace_id, alice, 62_000));
+        assert_eq!(
+            status(&tracker.changes(62_000), alice),
+            Some(PresenceStatus::Online)
+        );
+
+        tracker.disconnect(workspace_id, alice);
+        assert!(tracker.changes(63_000).is_empty());
+        tracker.apply_remote(
+            "other",
+            &PresenceSync {
+                workspace_id,
+                user_id: alice,
+                status: PresenceStatus::Online,
+                last_active_at: 63_000,
+            },
+            63_000,
+        );
+        tracker.disconnect(workspace_id, alice);
+        assert!(tracker.changes(63_500).is_empty());
+        assert_eq!(tracker.workspace(workspace_id, 63_500).len(), 1);
+        assert_eq!(
+            tracker.local_sync(workspace_id, alice, 63_500).status,
+            PresenceStatus::Offline
+        );
+
+        tracker.apply_remote(
+            "other",
+            &PresenceSync {
+                workspace_id,
+                user_id: bob,
+                status: PresenceStatus::Away,
+                last_active_at: 10_000,
+            },
+            63_500,
+        );
+        assert_eq!(
+            status(&tracker.changes(64_000), bob),
+            Some(PresenceStatus::Away)
+        );
+        let changes = tracker.changes(63_500 + REMOTE_PRESENCE_TTL_MS);
+        assert_eq!(status(&changes, alice), Some(PresenceStatus::Offline));
+        assert_eq!(status(&changes, bob), Some(PresenceStatus::Offline));
+        assert!(
+            tracker
+                .workspace(workspace_id, 63_500 + REMOTE_PRESENCE_TTL_MS)
+                .is_empty()
+        );
+    }
+}
@@ -1,51 +1,164 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    net::{IpAddr, SocketAddr},
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use axum::http::{HeaderMap, header};
+use chrono::Utc;
+use serde::Serialize;
 use tokio::sync::RwLock;
+use tracing::warn;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::errors::{ApiError, ApiResult};
+use crate::{
+    config::RateLimitSettings,
+    errors::{ApiError, ApiResult},
+};
+
+const MAX_TRACKED_KEYS: usize = 100_000;
+/// How often a full limiter may sweep for refilled buckets before turning a
+/// new key away, so a flood of new keys cannot make every request O(n).
+const FULL_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_RECENT_REJECTIONS: usize = 100;
 
 #[derive(Clone)]
 pub struct RateLimitService {
-    auth_limiter: Arc<RwLock<FixedWindowLimiter>>,
-    ws_connect_limiter: Arc<RwLock<FixedWindowLimiter>>,
-    ws_command_limiter: Arc<RwLock<FixedWindowLimiter>>,
+    auth_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    ws_connect_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    ws_command_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    message_write_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    channel_create_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    attachment_presign_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    invite_limiter: Arc<RwLock<TokenBucketLimiter>>,
+    trusted_proxies: Arc<Vec<IpCidr>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
 }
 
+/// Token bucket limiter: each key holds up to `capacity` tokens that refill
+/// continuously at `capacity / window`, so bursts are bounded by the capacity
+/// instead of doubling at fixed window boundaries.
 #[derive(Debug)]
-struct FixedWindowLimiter {
-    max_requests: u32,
-    window: Duration,
-    buckets: HashMap<String, WindowBucket>,
+struct TokenBucketLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    sweep_interval: Duration,
+    max_keys: usize,
+    last_sweep_at: Instant,
+    buckets: HashMap<String, TokenBucket>,
+    recent_rejections: VecDeque<(String, i64)>,
 }
 
 #[derive(Debug)]
-struct WindowBucket {
-    count: u32,
-    reset_at: Instant,
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+    rejected: u64,
+    last_rejected_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitBucketResponse {
+    pub limiter: String,
+    pub key: String,
+    pub capacity: u32,
+    pub remaining: u32,
+    /// Milliseconds until the next request would be accepted; 0 when not throttled.
+    pub retry_after_ms: u64,
+    /// Rejections since the bucket was created (fully refilled buckets are dropped).
+    pub rejected: u64,
+    pub last_rejected_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitRejectionResponse {
+    pub limiter: String,
+    pub key: String,
+    pub at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitInspectionResponse {
+    pub key: String,
+    /// Only keys with a live bucket appear; a missing limiter means full quota.
+    pub buckets: Vec<RateLimitBucketResponse>,
+    pub recent_rejections: Vec<RateLimitRejectionResponse>,
 }
 
 impl RateLimitService {
-    pub fn new() -> Self {
+    pub fn new(trusted_proxies: &[String], settings: &RateLimitSettings) -> Self {
+        let trusted_proxies = trusted_proxies
+            .iter()
+            .filter_map(|value| {
+                let parsed = IpCidr::parse(value);
+                if parsed.is_none() {
+                    warn!("ignoring invalid trusted proxy entry: {}", value);
+                }
+                parsed
+            })
+            .collect::<Vec<_>>();
         Self {
-            auth_limiter: Arc::new(RwLock::new(FixedWindowLimiter::new(
-                30,
-                Duration::from_secs(60),
-            ))),
-            ws_connect_limiter: Arc::new(RwLock::new(FixedWindowLimiter::new(
-                12,
-                Duration::from_secs(60),
-            ))),
-            ws_command_limiter: Arc::new(RwLock::new(FixedWindowLimiter::new(
-                600,
-                Duration::from_secs(60),
-            ))),
+            auth_limiter: per_minute_limiter(settings.auth_per_minute),
+            ws_connect_limiter: per_minute_limiter(settings.ws_connect_per_minute),
+            ws_command_limiter: per_minute_limiter(settings.ws_command_per_minute),
+            message_write_limiter: per_minute_limiter(settings.message_write_per_minute),
+            channel_create_limiter: per_minute_limiter(settings.channel_create_per_minute),
+            attachment_presign_limiter: per_minute_limiter(settings.attachment_presign_per_minute),
+            invite_limiter: per_minute_limiter(settings.invite_per_minute),
+            trusted_proxies: Arc::new(trusted_proxies),
+        }
+    }
+
+    /// Applies new limits in place; existing buckets keep their tokens
+    /// (clamped to the new capacity) so a reload does not reset clients.
+    pub async fn reconfigure(&self, settings: &RateLimitSettings) {
+        for (limiter, max_requests) in [
+            (&self.auth_limiter, settings.auth_per_minute),
+            (&self.ws_connect_limiter, settings.ws_connect_per_minute),
+            (&self.ws_command_limiter, settings.ws_command_per_minute),
+            (
+                &self.message_write_limiter,
+                settings.message_write_per_minute,
+            ),
+            (
+                &self.channel_create_limiter,
+                settings.channel_create_per_minute,
+            ),
+            (
+                &self.attachment_presign_limiter,
+                settings.attachment_presign_per_minute,
+            ),
+            (&self.invite_limiter, settings.invite_per_minute),
+        ] {
+            limiter
+                .write()
+                .await
+                .set_limit(max_requests, Duration::from_secs(60));
+        }
+    }
+
+    /// Resolves the client IP, honoring forwarding headers only when the
+    /// socket peer is one of the configured trusted proxies.
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<SocketAddr>) -> String {
+        let Some(peer_ip) = peer.map(|addr| addr.ip().to_canonical()) else {
+            return "unknown".to_string();
+        };
+        if !self.is_trusted_proxy(peer_ip) {
+            return peer_ip.to_string();
         }
+        client_ip_from_headers(headers, |ip| self.is_trusted_proxy(ip))
+            .unwrap_or_else(|| peer_ip.to_string())
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|cidr| cidr.contains(ip))
     }
 
     pub async fn check_auth(&self, client_ip: &str, email: Option<&str>) -> ApiResult<()> {
@@ -75,88 +188,499 @@ impl RateLimitService {
             .await
             .check(&key, "too many websocket commands, slow down")
     }
+
+    pub async fn check_message_write(&self, user_id: Uuid) -> ApiResult<()> {
+        let key = format!("user={}", user_id);
+        self.message_write_limiter
+            .write()
+            .await
+            .check(&key, "too many message writes, slow down")
+    }
+
+    pub async fn check_channel_create(&self, user_id: Uuid) -> ApiResult<()> {
+        let key = format!("user={}", user_id);
+        self.channel_create_limiter
+            .write()
+            .await
+            .check(&key, "too many channels created, retry in a minute")
+    }
+
+    pub async fn check_attachment_presign(&self, user_id: Uuid) -> ApiResult<()> {
+        let key = format!("user={}", user_id);
+        self.attachment_presign_limiter
+            .write()
+            .await
+            .check(&key, "too many attachment uploads, retry in a minute")
+    }
+
+    /// Bucket state and recent rejections on this instance for every key that
+    /// contains `filter` as a component, e.g. `user=<id>`, `<id>` or an IP.
+    pub async fn inspect(&self, filter: &str) -> RateLimitInspectionResponse {
+        let filter = normalize_key(filter);
+        let now = Instant::now();
+        let mut buckets = Vec::new();
+        let mut recent_rejections = Vec::new();
+        for (name, limiter) in [
+            ("auth", &self.auth_limiter),
+            ("ws_connect", &self.ws_connect_limiter),
+            ("ws_command", &self.ws_command_limiter),
+            ("message_write", &self.message_write_limiter),
+            ("channel_create", &self.channel_create_limiter),
+            ("attachment_presign", &self.attachment_presign_limiter),
+            ("invite", &self.invite_limiter),
+        ] {
+            let limiter = limiter.read().await;
+            buckets.extend(limiter.inspect(name, &filter, now));
+            recent_rejections.extend(
+                limiter
+                    .recent_rejections
+                    .iter()
+                    .filter(|(key, _)| key_matches(key, &filter))
+                    .map(|(key, at)| RateLimitRejectionResponse {
+                        limiter: name.to_string(),
+                        key: key.clone(),
+                        at: *at,
+                    }),
+            );
+        }
+        recent_rejections.sort_by_key(|rejection| std::cmp::Reverse(rejection.at));
+        RateLimitInspectionResponse {
+            key: filter,
+            buckets,
+            recent_rejections,
+        }
+    }
+
+    pub async fn check_invite(&self, user_id: Uuid) -> ApiResult<()> {
+        let key = format!("user={}", user_id);
+        self.invite_limiter
+            .write()
+            .await
+            .check(&key, "too many invitations issued, retry in a minute")
+    }
 }
 
-impl FixedWindowLimiter {
+impl TokenBucketLimiter {
     fn new(max_requests: u32, window: Duration) -> Self {
+        Self::with_max_keys(max_requests, window, MAX_TRACKED_KEYS)
+    }
+
+    fn with_max_keys(max_requests: u32, window: Duration, max_keys: usize) -> Self {
+        let capacity = f64::from(max_requests.max(1));
         Self {
-            max_requests,
-            window,
+            capacity,
+            refill_per_sec: capacity / window.as_secs_f64().max(f64::EPSILON),
+            sweep_interval: window,
+            max_keys: max_keys.max(1),
+            last_sweep_at: Instant::now(),
             buckets: HashMap::new(),
+            recent_rejections: VecDeque::new(),
+        }
+    }
+
+    fn set_limit(&mut self, max_requests: u32, window: Duration) {
+        let capacity = f64::from(max_requests.max(1));
+        self.capacity = capacity;
+        self.refill_per_sec = capacity / window.as_secs_f64().max(f64::EPSILON);
+        self.sweep_interval = window;
+        for bucket in self.buckets.values_mut() {
+            bucket.tokens = bucket.tokens.min(capacity);
         }
     }
 
     fn check(&mut self, key: &str, message: &str) -> ApiResult<()> {
-        let now = Instant::now();
-        let bucket = self.buckets.entry(key.to_string()).or_insert(WindowBucket {
-            count: 0,
-            reset_at: now + self.window,
-        });
+        self.check_at(key, message, Instant::now())
+    }
 
-        if now >= bucket.reset_at {
-            bucket.count = 0;
-            bucket.reset_at = now + self.window;
+    fn check_at(&mut self, key: &str, message: &str, now: Instant) -> ApiResult<()> {
+        if now.saturating_duration_since(self.last_sweep_at) >= self.sweep_interval {
+            self.evict_refilled(now);
+        }
+        // When every slot holds a live bucket, new keys are rejected rather than
+        // evicting someone else's bucket, which would hand that key a full quota.
+        if !self.buckets.contains_key(key) && self.buckets.len() >= self.max_keys {
+            if now.saturating_duration_since(self.last_sweep_at) >= FULL_SWEEP_INTERVAL {
+                self.evict_refilled(now);
+            }
+            if self.buckets.len() >= self.max_keys {
+                record_rejection(&mut self.recent_rejections, key);
+                return Err(ApiError::TooManyRequests(message.to_string()));
+            }
         }
 
-        if bucket.count >= self.max_requests {
+        let bucket = self.buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: self.capacity,
+            updated_at: now,
+            rejected: 0,
+            last_rejected_at: None,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens < 1.0 {
+            bucket.rejected += 1;
+            bucket.last_rejected_at = Some(record_rejection(&mut self.recent_rejections, key));
             return Err(ApiError::TooManyRequests(message.to_string()));
         }
 
-        bucket.count += 1;
+        bucket.tokens -= 1.0;
         Ok(())
     }
+
+    fn inspect(&self, name: &str, filter: &str, now: Instant) -> Vec<RateLimitBucketResponse> {
+        let mut items = self
+            .buckets
+            .iter()
+            .filter(|(key, _)| key_matches(key, filter))
+            .map(|(key, bucket)| {
+                let elapsed = now.saturating_duration_since(bucket.updated_at);
+                let tokens = (bucket.tokens + elapsed.as_secs_f64() * self.refill_per_sec)
+                    .min(self.capacity);
+                let retry_after_ms = if tokens >= 1.0 {
+                    0
+                } else {
+                    ((1.0 - tokens) / self.refill_per_sec * 1000.0).ceil() as u64
+                };
+                RateLimitBucketResponse {
+                    limiter: name.to_string(),
+                    key: key.clone(),
+                    capacity: self.capacity as u32,
+                    remaining: tokens.floor() as u32,
+                    retry_after_ms,
+                    rejected: bucket.rejected,
+                    last_rejected_at: bucket.last_rejected_at,
+                }
+            })
+            .collect::<Vec<_>>();
+        items.sort_by(|a, b| a.key.cmp(&b.key));
+        items
+    }
+
+    // A bucket that has fully refilled is indistinguishable from a fresh one,
+    // so dropping it loses no limiting state.
+    fn evict_refilled(&mut self, now: Instant) {
+        let capacity = self.capacity;
+        let refill_per_sec = self.refill_per_sec;
+        self.buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated_at);
+            bucket.tokens + elapsed.as_secs_f64() * refill_per_sec < capacity
+        });
+        self.last_sweep_at = now;
+    }
+}
+
+fn record_rejection(recent_rejections: &mut VecDeque<(String, i64)>, key: &str) -> i64 {
+    let at = Utc::now().timestamp_millis();
+    if recent_rejections.len() >= MAX_RECENT_REJECTIONS {
+        recent_rejections.pop_front();
+    }
+    recent_rejections.push_back((key.to_string(), at));
+    at
 }
 
-pub fn client_ip_from_headers(headers: &HeaderMap) -> String {
+fn client_ip_from_headers(
+    headers: &HeaderMap,
+    is_trusted: impl Fn(IpAddr) -> bool,
+) -> Option<String> {
     if let Some(value) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
-        let first = value.split(',').next().unwrap_or_default().trim();
-        if !first.is_empty() {
-            return first.to_string();
+        let hops = value
+            .split(',')
+            .map(str::trim)
+            .filter(|hop| !hop.is_empty())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if let Some(ip) = first_untrusted_hop(hops, &is_trusted) {
+            return Some(ip);
         }
     }
     if let Some(value) = headers.get("x-real-ip").and_then(|v| v.to_str().ok()) {
         let ip = value.trim();
         if !ip.is_empty() {
-            return ip.to_string();
+            return Some(ip.to_string());
         }
     }
-    if let Some(value) = headers
-        .get(header::FORWARDED)
-        .and_then(|v| v.to_str().ok())
-        .and_then(parse_forwarded_for)
-    {
-        return value;
+    let hops = headers
+        .get_all(header::FORWARDED)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(forwarded_for_hops)
+        .collect::<Vec<_>>();
+    first_untrusted_hop(hops, &is_trusted)
+}
+
+/// Walks the chain right-to-left: each trusted hop appended the address it
+/// saw, so the first untrusted entry is the real client. Falls back to the
+/// leftmost entry when every hop is trusted.
+fn first_untrusted_hop(hops: Vec<String>, is_trusted: impl Fn(IpAddr) -> bool) -> Option<String> {
+    for hop in hops.iter().rev() {
+        match hop.parse::<IpAddr>() {
+            Ok(ip) if is_trusted(ip.to_canonical()) => continue,
+            _ => return Some(hop.clone()),
+        }
     }
-    "unknown".to_string()
+    hops.into_iter().next()
+}
+
+/// The `for=` address of every element of an RFC 7239 `Forwarded` header, in
+/// order, without quotes, brackets or ports.
+fn forwarded_for_hops(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.trim().split_once('=')?;
+                if !key.trim().eq_ignore_ascii_case("for") {
+                    return None;
+                }
+                let node = value.trim().trim_matches('"');
+                let ip = match node.strip_prefix('[') {
+                    Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+                    None if node.matches(':').count() == 1 => {
+                        node.split(':').next().unwrap_or_default()
+                    }
+                    None => node,
+                };
+                (!ip.is_empty()).then(|| ip.to_string())
+            })
+        })
+        .collect()
 }
 
-fn parse_forwarded_for(value: &str) -> Option<String> {
-    for segment in value.split(';') {
-        let segment = segment.trim();
-        if let Some(for_value) = segment.strip_prefix("for=") {
-            let ip = for_value.trim_matches('"').trim();
-            if !ip.is_empty() {
-                return Some(ip.to_string());
+impl IpCidr {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (address, prefix) = match value.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (value, None),
+        };
+        let network = address.trim().parse::<IpAddr>().ok()?.to_canonical();
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().ok()?,
+            None => max_prefix,
+        };
+        if prefix_len > max_prefix {
+            return None;
+        }
+        Some(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    pub(crate) fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
             }
+            _ => false,
         }
     }
-    None
+}
+
+fn per_minute_limiter(max_requests: u32) -> Arc<RwLock<TokenBucketLimiter>> {
+    Arc::new(RwLock::new(TokenBucketLimiter::new(
+        max_requests,
+        Duration::from_secs(60),
+    )))
 }
 
 fn normalize_key(value: &str) -> String {
     value.trim().to_ascii_lowercase()
 }
 
+/// Keys look like `ip=<ip>|user=<id>`; a filter matches the whole key, one
+/// `name=value` component or just the value of one.
+fn key_matches(key: &str, filter: &str) -> bool {
+    key == filter
+        || key.split('|').any(|part| {
+            part == filter
+                || part
+                    .split_once('=')
+                    .is_some_and(|(_, value)| value == filter)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
-    async fn fixed_window_limiter_blocks_after_limit() {
-        let mut limiter = FixedWindowLimiter::new(2, Duration::from_secs(60));
+    async fn token_bucket_limiter_blocks_after_limit() {
+        let mut limiter = TokenBucketLimiter::new(2, Duration::from_secs(60));
         assert!(limiter.check("key", "limit").is_ok());
         assert!(limiter.check("key", "limit").is_ok());
         let result = limiter.check("key", "limit");
         assert!(matches!(result, Err(ApiError::TooManyRequests(_))));
     }
+
+    #[test]
+    fn token_bucket_limiter_refills_gradually() {
+        let mut limiter = TokenBucketLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(limiter.check_at("key", "limit", start).is_ok());
+        assert!(limiter.check_at("key", "limit", start).is_ok());
+        assert!(limiter.check_at("key", "limit", start).is_err());
+
+        // one token refills every 30s; half a window is not a full reset
+        let later = start + Duration::from_secs(30);
+        assert!(limiter.check_at("key", "limit", later).is_ok());
+        assert!(limiter.check_at("key", "limit", later).is_err());
+    }
+
+    #[tokio::test]
+    async fn channel_create_limit_is_tracked_per_user() {
+        let service = RateLimitService::new(&[], &RateLimitSettings::default());
+        let noisy_user = Uuid::new_v4();
+        for _ in 0..10 {
+            assert!(service.check_channel_create(noisy_user).await.is_ok());
+        }
+        let result = service.check_channel_create(noisy_user).await;
+        assert!(matches!(result, Err(ApiError::TooManyRequests(_))));
+        assert!(service.check_channel_create(Uuid::new_v4()).await.is_ok());
+    }
+
+    #[test]
+    fn token_bucket_limiter_evicts_refilled_buckets() {
+        let mut limiter = TokenBucketLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(limiter.check_at("a", "limit", start).is_ok());
+        assert!(limiter.check_at("b", "limit", start).is_ok());
+        assert_eq!(limiter.buckets.len(), 2);
+
+        let later = start + Duration::from_secs(120);
+        assert!(limiter.check_at("c", "limit", later).is_ok());
+        assert_eq!(limiter.buckets.len(), 1);
+        assert!(limiter.buckets.contains_key("c"));
+    }
+
+    #[test]
+    fn token_bucket_limiter_caps_tracked_keys() {
+        let mut limiter = TokenBucketLimiter::with_max_keys(5, Duration::from_secs(60), 2);
+        let start = Instant::now();
+        assert!(limiter.check_at("a", "limit", start).is_ok());
+        assert!(
+            limiter
+                .check_at("b", "limit", start + Duration::from_millis(1))
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_at("c", "limit", start + Duration::from_secs(2))
+                .is_err()
+        );
+        assert_eq!(limiter.buckets.len(), 2);
+        assert!(limiter.buckets.contains_key("a"));
+        assert!(
+            limiter
+                .check_at("a", "limit", start + Duration::from_secs(2))
+                .is_ok()
+        );
+
+        assert!(
+            limiter
+                .check_at("c", "limit", start + Duration::from_secs(61))
+                .is_ok()
+        );
+        assert!(limiter.buckets.contains_key("c"));
+    }
+
+    #[test]
+    fn token_bucket_limiter_set_limit_clamps_existing_buckets() {
+        let mut limiter = TokenBucketLimiter::new(10, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check_at("k", "blocked", now).is_ok());
+
+        limiter.set_limit(2, Duration::from_secs(60));
+        assert!(limiter.check_at("k", "blocked", now).is_ok());
+        assert!(limiter.check_at("k", "blocked", now).is_ok());
+        assert!(limiter.check_at("k", "blocked", now).is_err());
+    }
+
+    #[tokio::test]
+    async fn inspect_reports_buckets_and_rejections_for_a_key() {
+        let service = RateLimitService::new(&[], &RateLimitSettings::default());
+        let user_id = Uuid::new_v4();
+        for _ in 0..11 {
+            let _ = service.check_channel_create(user_id).await;
+        }
+        assert!(service.check_message_write(user_id).await.is_ok());
+        assert!(service.check_message_write(Uuid::new_v4()).await.is_ok());
+
+        let report = service.inspect(&user_id.to_string()).await;
+        assert_eq!(report.buckets.len(), 2);
+        let channel_create = report
+            .buckets
+            .iter()
+            .find(|bucket| bucket.limiter == "channel_create")
+            .unwrap();
+        assert_eq!(channel_create.remaining, 0);
+        assert_eq!(channel_create.rejected, 1);
+        assert!(channel_create.retry_after_ms > 0);
+        assert_eq!(report.recent_rejections.len(), 1);
+        assert_eq!(report.recent_rejections[0].key, format!("user={user_id}"));
+
+        assert_eq!(
+            service
+                .inspect(&format!("user={user_id}"))
+                .await
+                .buckets
+                .len(),
+            2
+        );
+        assert!(service.inspect("user=").await.buckets.is_empty());
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarded_headers_from_untrusted_peers() {
+        let service =
+            RateLimitService::new(&["10.0.0.0/8".to_string()], &RateLimitSettings::default());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+
+        let direct = service.client_ip(&headers, Some("198.51.100.1:4000".parse().unwrap()));
+        assert_eq!(direct, "198.51.100.1");
+
+        let proxied = service.client_ip(&headers, Some("10.1.2.3:4000".parse().unwrap()));
+        assert_eq!(proxied, "203.0.113.7");
+    }
+
+    #[test]
+    fn client_ip_skips_trusted_hops_in_forwarded_chain() {
+        let service =
+            RateLimitService::new(&["10.0.0.0/8".to_string()], &RateLimitSettings::default());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "1.1.1.1, 203.0.113.7, 10.0.0.5".parse().unwrap(),
+        );
+        let ip = service.client_ip(&headers, Some("10.0.0.1:4000".parse().unwrap()));
+        assert_eq!(ip, "203.0.113.7");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::FORWARDED,
+            r#"for=1.1.1.1, for="203.0.113.7:4711";proto=https, For="[10::5]""#
+                .parse()
+                .unwrap(),
+        );
+        let service = RateLimitService::new(
+            &["10.0.0.0/8".to_string(), "10::/16".to_string()],
+            &RateLimitSettings::default(),
+        );
+        let ip = service.client_ip(&headers, Some("10.0.0.1:4000".parse().unwrap()));
+        assert_eq!(ip, "203.0.113.7");
+    }
 }
@@ -1,11 +1,22 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{delete, get},
+};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{
+    app::AppState,
     auth::AuthContext,
     channels::ChannelService,
-    errors::{ApiError, ApiResult},
+    errors::{ApiError, ApiResult, ErrorResponse},
+    mutes, realtime,
     storage::Storage,
 };
 
@@ -25,6 +36,30 @@ pub struct ReactionUpdateResponse {
     pub op: String,
 }
 
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReactionSummaryResponse {
+    pub emoji: String,
+    pub count: usize,
+    pub user_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddReactionRequest {
+    pub emoji: String,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/messages/:id/reactions",
+            get(list_reactions).post(add_reaction),
+        )
+        .route(
+            "/api/v1/messages/:id/reactions/:emoji",
+            delete(remove_reaction),
+        )
+}
+
 impl ReactionService {
     pub fn new(storage: std::sync::Arc<Storage>) -> Self {
         Self { storage }
@@ -41,6 +76,7 @@ impl ReactionService {
         let message = channels
             .get_message(context.workspace_id, message_id)
             .await?;
+        mutes::ensure_not_muted(&self.storage, context.workspace_id, context.user_id).await?;
 
         self.storage
             .add_reaction(message_id, &emoji, context.user_id)
@@ -83,6 +119,34 @@ impl ReactionService {
             "removed",
         ))
     }
+
+    pub async fn list_reactions(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        message_id: Uuid,
+    ) -> ApiResult<Vec<ReactionSummaryResponse>> {
+        channels
+            .get_message(context.workspace_id, message_id)
+            .await?;
+
+        let mut grouped = BTreeMap::<String, Vec<Uuid>>::new();
+        for (emoji, user_id) in self.storage.list_message_reactions(message_id).await {
+            grouped.entry(emoji).or_default().push(user_id);
+        }
+        Ok(grouped
+            .into_iter()
+            .map(|(emoji, mut user_ids)| {
+                user_ids.sort_unstable();
+                user_ids.dedup();
+                ReactionSummaryResponse {
+                    emoji,
+                    count: user_ids.len(),
+                    user_ids,
+                }
+            })
+            .collect())
+    }
 }
 
 fn normalize_emoji(emoji: &str) -> ApiResult<String> {
@@ -119,6 +183,125 @@ fn build_update(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/messages/{id}/reactions",
+    operation_id = "list_reactions",
+    tag = "reactions",
+    responses(
+        (status = 200, description = "Reactions grouped by emoji", body = [ReactionSummaryResponse]),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Message not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_reactions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(message_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<ReactionSummaryResponse>>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let items = state
+        .reactions
+        .list_reactions(&state.channels, &context, message_id)
+        .await?;
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/messages/{id}/reactions",
+    operation_id = "add_reaction",
+    tag = "reactions",
+    request_body = AddReactionRequest,
+    responses(
+        (status = 200, description = "Reaction added", body = ReactionUpdateResponse),
+        (status = 400, description = "Invalid emoji", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Message not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn add_reaction(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(message_id): Path<Uuid>,
+    Json(payload): Json<AddReactionRequest>,
+) -> ApiResult<Json<ReactionUpdateResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let update = state
+        .reactions
+        .add_reaction(&state.channels, &context, message_id, &payload.emoji)
+        .await?;
+    state.metrics.record_reaction_added(context.workspace_id);
+    publish_update(&state, &context, &update, "REACTION_ADDED").await;
+    Ok(Json(update))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/messages/{id}/reactions/{emoji}",
+    operation_id = "remove_reaction",
+    tag = "reactions",
+    responses(
+        (status = 200, description = "Reaction removed", body = ReactionUpdateResponse),
+        (status = 400, description = "Invalid emoji", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Message not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn remove_reaction(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((message_id, emoji)): Path<(Uuid, String)>,
+) -> ApiResult<Json<ReactionUpdateResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let update = state
+        .reactions
+        .remove_reaction(&state.channels, &context, message_id, &emoji)
+        .await?;
+    publish_update(&state, &context, &update, "REACTION_REMOVED").await;
+    Ok(Json(update))
+}
+
+async fn publish_update(
+    state: &AppState,
+    context: &AuthContext,
+    update: &ReactionUpdateResponse,
+    action: &str,
+) {
+    realtime::emit_channel_event(
+        state,
+        context.workspace_id,
+        realtime::make_event(
+            "REACTION_UPDATED",
+            context.workspace_id,
+            Some(update.channel_id),
+            None,
+            serde_json::to_value(update).unwrap_or_default(),
+        ),
+    )
+    .await;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            action,
+            "message",
+            Some(update.message_id.to_string()),
+            json!({ "emoji": update.emoji }),
+        )
+        .await;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +336,7 @@ mod tests {
                 crate::channels::CreateMessageRequest {
                     body_md: "hello".to_string(),
                 },
+                None,
             )
             .await
             .expect("message should be created");
@@ -164,6 +348,14 @@ mod tests {
             .expect("reaction add should work");
         assert_eq!(added.count, 1);
 
+        let listed = service
+            .list_reactions(&channels, &context, message.id)
+            .await
+            .expect("reaction list should work");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].emoji, "👍");
+        assert_eq!(listed[0].user_ids, vec![user_id]);
+
         let removed = service
             .remove_reaction(&channels, &context, message.id, "👍")
             .await
@@ -1,10 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
 use axum::{
     Router,
     extract::{
-        State, WebSocketUpgrade,
-        ws::{Message, WebSocket},
+        ConnectInfo, State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket, close_code},
     },
     http::{HeaderMap, StatusCode},
     response::IntoResponse,
@@ -15,7 +15,7 @@ use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tokio::{
-    sync::{RwLock, broadcast, mpsc},
+    sync::{RwLock, broadcast, mpsc, oneshot},
     time::{Duration, sleep},
 };
 use tracing::{info, warn};
@@ -23,20 +23,39 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{
+    analytics,
     app::AppState,
     auth::AuthContext,
     channels::{CreateMessageRequest, MessageQuery, UpdateMessageRequest},
     errors::{ApiError, ApiResult, ErrorResponse},
-    rate_limit::client_ip_from_headers,
+    event_subscriptions, ip_allowlists, moderation, notifications,
+    observability::AppMetrics,
+    presence::{PresenceResponse, PresenceSync, PresenceTracker},
+    session_limits,
 };
 
 const REDIS_WS_CHANNEL: &str = "galynx:ws:events";
+const REDIS_PUBLISH_QUEUE_CAPACITY: usize = 10_000;
+const REDIS_PUBLISH_MAX_ATTEMPTS: u32 = 5;
+const REDIS_PUBLISH_RETRY_DELAY: Duration = Duration::from_millis(400);
+/// Bridge-only event carrying one instance's presence entries; never
+/// delivered to sockets.
+const PRESENCE_SYNC_EVENT: &str = "PRESENCE_SYNC";
+
+/// Open sockets of one user in one workspace, oldest first; each sender tells
+/// its socket to close.
+type UserConnections = Vec<(Uuid, oneshot::Sender<()>)>;
 
 #[derive(Clone)]
 pub struct RealtimeHub {
     workspaces: Arc<RwLock<HashMap<Uuid, broadcast::Sender<WsEventEnvelope>>>>,
+    connections: Arc<RwLock<HashMap<(Uuid, Uuid), UserConnections>>>,
+    presence: Arc<RwLock<PresenceTracker>>,
+    emitted: broadcast::Sender<(Uuid, WsEventEnvelope)>,
     instance_id: String,
-    redis_outbox: Option<mpsc::UnboundedSender<String>>,
+    redis_url: Option<String>,
+    redis_outbox: Option<mpsc::Sender<RedisEventEnvelope>>,
+    metrics: Arc<AppMetrics>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -47,6 +66,9 @@ pub struct WsEventEnvelope {
     pub correlation_id: Option<String>,
     pub server_ts: i64,
     pub payload: Value,
+    /// When set, only sockets authenticated as this user receive the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +106,7 @@ struct FetchMorePayload {
     channel_id: Uuid,
     cursor: Option<String>,
     limit: Option<usize>,
+    after_seq: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -104,19 +127,26 @@ pub fn router() -> Router<AppState> {
 }
 
 impl RealtimeHub {
-    pub fn new(redis_url: Option<&str>) -> Self {
+    pub fn new(redis_url: Option<&str>, presence_away: Duration, metrics: Arc<AppMetrics>) -> Self {
         let workspaces = Arc::new(RwLock::new(HashMap::new()));
+        let presence = Arc::new(RwLock::new(PresenceTracker::new(presence_away)));
         let instance_id = Uuid::new_v4().to_string();
 
-        let redis_outbox = redis_url
+        let redis_url = redis_url
             .map(str::trim)
             .filter(|value| !value.is_empty())
-            .map(|value| {
-                let (tx, rx) = mpsc::unbounded_channel::<String>();
-                spawn_redis_publisher(value.to_string(), rx);
-                spawn_redis_subscriber(value.to_string(), workspaces.clone(), instance_id.clone());
-                tx
-            });
+            .map(ToString::to_string);
+        let redis_outbox = redis_url.as_deref().map(|value| {
+            let (tx, rx) = mpsc::channel(REDIS_PUBLISH_QUEUE_CAPACITY);
+            spawn_redis_publisher(value.to_string(), rx, metrics.clone());
+            spawn_redis_subscriber(
+                value.to_string(),
+                workspaces.clone(),
+                presence.clone(),
+                instance_id.clone(),
+            );
+            tx
+        });
 
         if redis_outbox.is_some() {
             info!("realtime redis bridge enabled");
@@ -126,11 +156,142 @@ impl RealtimeHub {
 
         Self {
             workspaces,
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            presence,
+            emitted: broadcast::channel(1024).0,
             instance_id,
+            redis_url,
             redis_outbox,
+            metrics,
+        }
+    }
+
+    /// Tracks a new socket of `context.user_id` on this instance. When more
+    /// than `limit` are open the oldest ones are signalled to close; returns the
+    /// connection id, its close signal and how many sockets were evicted.
+    pub async fn register_connection(
+        &self,
+        context: &AuthContext,
+        limit: Option<usize>,
+    ) -> (Uuid, oneshot::Receiver<()>, usize) {
+        let connection_id = Uuid::new_v4();
+        let (tx, rx) = oneshot::channel();
+        let mut connections = self.connections.write().await;
+        let open = connections
+            .entry((context.workspace_id, context.user_id))
+            .or_default();
+        open.push((connection_id, tx));
+        let excess = limit.map_or(0, |limit| open.len().saturating_sub(limit.max(1)));
+        for (_, evict) in open.drain(..excess) {
+            let _ = evict.send(());
+        }
+        drop(connections);
+        self.presence.write().await.connect(
+            context.workspace_id,
+            context.user_id,
+            Utc::now().timestamp_millis(),
+        );
+        self.announce_presence(context).await;
+        (connection_id, rx, excess)
+    }
+
+    pub async fn unregister_connection(&self, context: &AuthContext, connection_id: Uuid) {
+        let mut connections = self.connections.write().await;
+        let key = (context.workspace_id, context.user_id);
+        if let Some(open) = connections.get_mut(&key) {
+            open.retain(|(id, _)| *id != connection_id);
+            if open.is_empty() {
+                connections.remove(&key);
+            }
+        }
+        drop(connections);
+        self.presence
+            .write()
+            .await
+            .disconnect(context.workspace_id, context.user_id);
+        self.announce_presence(context).await;
+    }
+
+    /// Marks the user active; an away user comes back online right away
+    /// instead of on the next presence tick.
+    pub async fn touch_presence(&self, context: &AuthContext) {
+        let back = self.presence.write().await.touch(
+            context.workspace_id,
+            context.user_id,
+            Utc::now().timestamp_millis(),
+        );
+        if back {
+            self.announce_presence(context).await;
+        }
+    }
+
+    /// Online and away members of the workspace, across instances.
+    pub async fn workspace_presence(&self, workspace_id: Uuid) -> Vec<PresenceResponse> {
+        self.presence
+            .read()
+            .await
+            .workspace(workspace_id, Utc::now().timestamp_millis())
+    }
+
+    /// Re-announces every local user to the other instances and emits the
+    /// changes caused by away timeouts or instances that went silent.
+    pub async fn refresh_presence(&self) {
+        let snapshot = self
+            .presence
+            .read()
+            .await
+            .local_snapshot(Utc::now().timestamp_millis());
+        self.publish_presence(snapshot);
+        self.emit_presence_changes().await;
+    }
+
+    async fn announce_presence(&self, context: &AuthContext) {
+        let sync = self.presence.read().await.local_sync(
+            context.workspace_id,
+            context.user_id,
+            Utc::now().timestamp_millis(),
+        );
+        self.publish_presence(vec![sync]);
+        self.emit_presence_changes().await;
+    }
+
+    /// `PRESENCE_CHANGED` only goes to this instance's sockets: every instance
+    /// derives the same changes from the synced state, so relaying them too
+    /// would deliver each one twice.
+    async fn emit_presence_changes(&self) {
+        let changes = emit_presence_changes(&self.presence, &self.workspaces).await;
+        for workspace_id in changes {
+            self.metrics.record_ws_event_emitted(workspace_id);
         }
     }
 
+    fn publish_presence(&self, entries: Vec<PresenceSync>) {
+        let Some(redis_outbox) = &self.redis_outbox else {
+            return;
+        };
+        if entries.is_empty() {
+            return;
+        }
+        let payload = RedisEventEnvelope {
+            source_instance_id: self.instance_id.clone(),
+            event: WsEventEnvelope {
+                event_type: PRESENCE_SYNC_EVENT.to_string(),
+                workspace_id: None,
+                channel_id: None,
+                correlation_id: None,
+                server_ts: Utc::now().timestamp_millis(),
+                payload: json!({ "entries": entries }),
+                recipient_id: None,
+            },
+        };
+        enqueue_redis_event(redis_outbox, payload, &self.metrics);
+    }
+
+    pub async fn ping_redis(&self) -> Option<Result<(), String>> {
+        let redis_url = self.redis_url.as_deref()?;
+        Some(ping_redis(redis_url).await)
+    }
+
     pub async fn subscribe(&self, workspace_id: Uuid) -> broadcast::Receiver<WsEventEnvelope> {
         let sender = {
             let mut workspaces = self.workspaces.write().await;
@@ -142,8 +303,26 @@ impl RealtimeHub {
         sender.subscribe()
     }
 
+    /// Every event emitted from this instance with the workspace it was sent
+    /// to; events relayed from other instances over Redis are not included.
+    pub fn subscribe_emitted(&self) -> broadcast::Receiver<(Uuid, WsEventEnvelope)> {
+        self.emitted.subscribe()
+    }
+
+    /// Open subscriptions (WebSockets and gRPC streams) for the workspace on this instance.
+    pub async fn connection_count(&self, workspace_id: Uuid) -> usize {
+        self.workspaces
+            .read()
+            .await
+            .get(&workspace_id)
+            .map(broadcast::Sender::receiver_count)
+            .unwrap_or_default()
+    }
+
     pub async fn emit(&self, workspace_id: Uuid, event: WsEventEnvelope) {
+        self.metrics.record_ws_event_emitted(workspace_id);
         self.emit_local(workspace_id, event.clone()).await;
+        let _ = self.emitted.send((workspace_id, event.clone()));
 
         let Some(redis_outbox) = &self.redis_outbox else {
             return;
@@ -153,14 +332,7 @@ impl RealtimeHub {
             source_instance_id: self.instance_id.clone(),
             event,
         };
-        match serde_json::to_string(&payload) {
-            Ok(serialized) => {
-                let _ = redis_outbox.send(serialized);
-            }
-            Err(error) => {
-                warn!("failed to serialize realtime redis payload: {}", error);
-            }
-        }
+        enqueue_redis_event(redis_outbox, payload, &self.metrics);
     }
 
     async fn emit_local(&self, workspace_id: Uuid, event: WsEventEnvelope) {
@@ -168,22 +340,78 @@ impl RealtimeHub {
     }
 }
 
-fn spawn_redis_publisher(redis_url: String, mut rx: mpsc::UnboundedReceiver<String>) {
+/// Queues an event for the Redis bridge without waiting; when the queue is
+/// full the event is dropped so local delivery is never held up by Redis.
+fn enqueue_redis_event(
+    redis_outbox: &mpsc::Sender<RedisEventEnvelope>,
+    payload: RedisEventEnvelope,
+    metrics: &AppMetrics,
+) {
+    match redis_outbox.try_send(payload) {
+        Ok(()) => metrics.record_redis_publish_queued(),
+        Err(mpsc::error::TrySendError::Full(payload)) => {
+            metrics.record_redis_publish_dropped("queue_full");
+            warn!(
+                event_type = %payload.event.event_type,
+                workspace_id = ?payload.event.workspace_id,
+                "redis publish queue full, dropping event"
+            );
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            metrics.record_redis_publish_dropped("closed");
+        }
+    }
+}
+
+fn spawn_redis_publisher(
+    redis_url: String,
+    mut rx: mpsc::Receiver<RedisEventEnvelope>,
+    metrics: Arc<AppMetrics>,
+) {
     tokio::spawn(async move {
-        while let Some(payload) = rx.recv().await {
-            loop {
-                match publish_redis_event(&redis_url, &payload).await {
-                    Ok(()) => break,
-                    Err(error) => {
-                        warn!("redis publish failed, retrying: {}", error);
-                        sleep(Duration::from_millis(400)).await;
-                    }
+        while let Some(envelope) = rx.recv().await {
+            metrics.record_redis_publish_dequeued();
+            let payload = match serde_json::to_string(&envelope) {
+                Ok(payload) => payload,
+                Err(error) => {
+                    metrics.record_redis_publish_dropped("serialize");
+                    warn!("failed to serialize realtime redis payload: {}", error);
+                    continue;
                 }
+            };
+            if let Err(error) = publish_with_retries(&redis_url, &payload).await {
+                metrics.record_redis_publish_dropped("retries_exhausted");
+                tracing::error!(
+                    target: "galynx_api::realtime::dead_letter",
+                    event_type = %envelope.event.event_type,
+                    workspace_id = ?envelope.event.workspace_id,
+                    channel_id = ?envelope.event.channel_id,
+                    error = %error,
+                    "redis publish gave up after {REDIS_PUBLISH_MAX_ATTEMPTS} attempts"
+                );
             }
         }
     });
 }
 
+async fn publish_with_retries(redis_url: &str, payload: &str) -> Result<(), String> {
+    let mut attempt = 1;
+    loop {
+        match publish_redis_event(redis_url, payload).await {
+            Ok(()) => return Ok(()),
+            Err(error) if attempt >= REDIS_PUBLISH_MAX_ATTEMPTS => return Err(error),
+            Err(error) => {
+                warn!(
+                    "redis publish failed (attempt {attempt}/{REDIS_PUBLISH_MAX_ATTEMPTS}), retrying: {}",
+                    error
+                );
+                sleep(REDIS_PUBLISH_RETRY_DELAY * attempt).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
 async fn publish_redis_event(redis_url: &str, payload: &str) -> Result<(), String> {
     let client =
         redis::Client::open(redis_url).map_err(|error| format!("invalid redis url: {error}"))?;
@@ -202,15 +430,37 @@ async fn publish_redis_event(redis_url: &str, payload: &str) -> Result<(), Strin
     Ok(())
 }
 
+async fn ping_redis(redis_url: &str) -> Result<(), String> {
+    let client =
+        redis::Client::open(redis_url).map_err(|error| format!("invalid redis url: {error}"))?;
+    let mut connection = client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|error| format!("redis connection error: {error}"))?;
+
+    redis::cmd("PING")
+        .query_async::<String>(&mut connection)
+        .await
+        .map_err(|error| format!("redis ping failed: {error}"))?;
+
+    Ok(())
+}
+
 fn spawn_redis_subscriber(
     redis_url: String,
     workspaces: Arc<RwLock<HashMap<Uuid, broadcast::Sender<WsEventEnvelope>>>>,
+    presence: Arc<RwLock<PresenceTracker>>,
     instance_id: String,
 ) {
     tokio::spawn(async move {
         loop {
-            if let Err(error) =
-                run_redis_subscriber(&redis_url, workspaces.clone(), &instance_id).await
+            if let Err(error) = run_redis_subscriber(
+                &redis_url,
+                workspaces.clone(),
+                presence.clone(),
+                &instance_id,
+            )
+            .await
             {
                 warn!("redis subscriber failed, reconnecting: {}", error);
                 sleep(Duration::from_secs(1)).await;
@@ -222,6 +472,7 @@ fn spawn_redis_subscriber(
 async fn run_redis_subscriber(
     redis_url: &str,
     workspaces: Arc<RwLock<HashMap<Uuid, broadcast::Sender<WsEventEnvelope>>>>,
+    presence: Arc<RwLock<PresenceTracker>>,
     instance_id: &str,
 ) -> Result<(), String> {
     let client =
@@ -251,6 +502,25 @@ async fn run_redis_subscriber(
             continue;
         }
 
+        if envelope.event.event_type == PRESENCE_SYNC_EVENT {
+            let entries: Vec<PresenceSync> = envelope
+                .event
+                .payload
+                .get("entries")
+                .cloned()
+                .and_then(|entries| serde_json::from_value(entries).ok())
+                .unwrap_or_default();
+            {
+                let mut presence = presence.write().await;
+                let now = Utc::now().timestamp_millis();
+                for entry in &entries {
+                    presence.apply_remote(&envelope.source_instance_id, entry, now);
+                }
+            }
+            emit_presence_changes(&presence, &workspaces).await;
+            continue;
+        }
+
         let Some(workspace_id) = envelope.event.workspace_id else {
             continue;
         };
@@ -261,6 +531,35 @@ async fn run_redis_subscriber(
     Ok(())
 }
 
+/// Emits `PRESENCE_CHANGED` to local sockets for every pending presence
+/// change and returns the workspace of each event.
+async fn emit_presence_changes(
+    presence: &RwLock<PresenceTracker>,
+    workspaces: &Arc<RwLock<HashMap<Uuid, broadcast::Sender<WsEventEnvelope>>>>,
+) -> Vec<Uuid> {
+    let changes = presence
+        .write()
+        .await
+        .changes(Utc::now().timestamp_millis());
+    let mut emitted = Vec::with_capacity(changes.len());
+    for (workspace_id, change) in changes {
+        emit_workspace_event(
+            workspaces,
+            workspace_id,
+            make_event(
+                "PRESENCE_CHANGED",
+                workspace_id,
+                None,
+                None,
+                serde_json::to_value(&change).unwrap_or_default(),
+            ),
+        )
+        .await;
+        emitted.push(workspace_id);
+    }
+    emitted
+}
+
 async fn emit_workspace_event(
     workspaces: &Arc<RwLock<HashMap<Uuid, broadcast::Sender<WsEventEnvelope>>>>,
     workspace_id: Uuid,
@@ -278,21 +577,33 @@ async fn emit_workspace_event(
 #[utoipa::path(
     get,
     path = "/api/v1/ws",
+    operation_id = "ws_upgrade",
+    tag = "realtime",
     responses(
         (status = 101, description = "WebSocket upgraded"),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Address not in the workspace IP allowlist", body = ErrorResponse)
     )
 )]
 pub(crate) async fn ws_upgrade(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
 ) -> Result<impl IntoResponse, ApiError> {
-    let client_ip = client_ip_from_headers(&headers);
+    let client_ip = state
+        .rate_limit
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
-        .await?;
+        .await
+        .inspect_err(|error| {
+            if error.status_code() == StatusCode::UNAUTHORIZED {
+                state.metrics.record_auth_failure("ws");
+            }
+        })?;
+    ip_allowlists::enforce(&state, &context, &client_ip, "ws").await?;
     state
         .rate_limit
         .check_ws_connect(&client_ip, context.user_id)
@@ -301,8 +612,29 @@ pub(crate) async fn ws_upgrade(
     Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, context)))
 }
 
-async fn handle_socket(mut socket: WebSocket, state: AppState, context: AuthContext) {
+async fn handle_socket(socket: WebSocket, state: AppState, context: AuthContext) {
+    let limit = session_limits::max_ws_connections(&state.storage, context.workspace_id).await;
+    let (connection_id, evicted, evicted_count) =
+        state.realtime.register_connection(&context, limit).await;
+    if evicted_count > 0 {
+        session_limits::audit_eviction(&state, &context, "websocket", evicted_count).await;
+    }
+    serve_socket(socket, &state, &context, evicted).await;
+    state
+        .realtime
+        .unregister_connection(&context, connection_id)
+        .await;
+}
+
+async fn serve_socket(
+    mut socket: WebSocket,
+    state: &AppState,
+    context: &AuthContext,
+    mut evicted: oneshot::Receiver<()>,
+) {
     let mut rx = state.realtime.subscribe(context.workspace_id).await;
+    let filters = event_subscriptions::websocket_filters(&state.storage, context).await;
+    analytics::record_connection(state, context).await;
     state
         .audit
         .write(
@@ -325,6 +657,7 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, context: AuthCont
             "user_id": context.user_id,
             "role": context.role,
         }),
+        recipient_id: None,
     };
     if socket
         .send(Message::Text(
@@ -336,11 +669,42 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, context: AuthCont
         return;
     }
 
+    let mut maintenance = state.maintenance.subscribe();
     loop {
         tokio::select! {
+            _ = &mut evicted => {
+                let _ = socket
+                    .send(Message::Close(Some(CloseFrame {
+                        code: close_code::POLICY,
+                        reason: "connection limit reached".into(),
+                    })))
+                    .await;
+                return;
+            }
+            changed = maintenance.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                if *maintenance.borrow_and_update() {
+                    let _ = socket
+                        .send(Message::Close(Some(maintenance_close_frame(
+                            &state.maintenance.message(),
+                        ))))
+                        .await;
+                    return;
+                }
+            }
             outbound = rx.recv() => {
                 match outbound {
                     Ok(event) => {
+                        if event.recipient_id.is_some_and(|recipient| recipient != context.user_id) {
+                            continue;
+                        }
+                        if !filters.is_empty()
+                            && !filters.iter().any(|filter| event_subscriptions::matches(filter, &event))
+                        {
+                            continue;
+                        }
                         if socket
                             .send(Message::Text(serde_json::to_string(&event).unwrap_or_default()))
                             .await
@@ -359,7 +723,8 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, context: AuthCont
                 let Some(inbound) = inbound else { return; };
                 match inbound {
                     Ok(Message::Text(text)) => {
-                        if let Err(error) = handle_client_text(&state, &context, &mut socket, &text).await {
+                        state.realtime.touch_presence(context).await;
+                        if let Err(error) = handle_client_text(state, context, &mut socket, &text).await {
                             let _ = socket.send(Message::Text(error_event(error))).await;
                         }
                     }
@@ -377,6 +742,18 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, context: AuthCont
     }
 }
 
+/// `1013 Try Again Later`; close reasons are capped at 123 bytes by the protocol.
+fn maintenance_close_frame(message: &str) -> CloseFrame<'static> {
+    let mut end = message.len().min(123);
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    CloseFrame {
+        code: close_code::AGAIN,
+        reason: message[..end].to_string().into(),
+    }
+}
+
 async fn handle_client_text(
     state: &AppState,
     context: &AuthContext,
@@ -419,16 +796,28 @@ async fn handle_client_text(
                 return Ok(());
             }
 
+            let moderated = moderation::moderate_message(
+                state,
+                context,
+                payload.channel_id,
+                None,
+                payload.body_md,
+            )
+            .await?;
             let message = state
                 .channels
                 .create_message(
                     context,
                     payload.channel_id,
                     CreateMessageRequest {
-                        body_md: payload.body_md,
+                        body_md: moderated.body.clone(),
                     },
+                    command.client_msg_id.clone(),
                 )
                 .await?;
+            moderated.open_case(state, &message).await;
+            state.metrics.record_message_created(context.workspace_id);
+            analytics::record_message(state, &message).await;
             if let Some(client_msg_id) = dedup_client_msg_id.as_deref() {
                 state
                     .storage
@@ -442,19 +831,6 @@ async fn handle_client_text(
                     )
                     .await;
             }
-            state
-                .realtime
-                .emit(
-                    context.workspace_id,
-                    event(
-                        "MESSAGE_CREATED",
-                        context.workspace_id,
-                        Some(message.channel_id),
-                        command.client_msg_id.clone(),
-                        serde_json::to_value(&message).unwrap_or_default(),
-                    ),
-                )
-                .await;
             state
                 .audit
                 .write(
@@ -466,6 +842,7 @@ async fn handle_client_text(
                     json!({ "channel_id": message.channel_id, "client_msg_id": command.client_msg_id.clone() }),
                 )
                 .await;
+            notifications::notify_message(state, &message).await;
             send_ack(
                 socket,
                 "SEND_MESSAGE",
@@ -501,29 +878,25 @@ async fn handle_client_text(
                     .put_ws_command_once(&dedup_key, Utc::now().timestamp_millis())
                     .await;
             }
+            let moderated = moderation::moderate_message_edit(
+                state,
+                context,
+                payload.message_id,
+                payload.body_md,
+            )
+            .await?;
             let message = state
                 .channels
                 .update_message(
                     context,
                     payload.message_id,
                     UpdateMessageRequest {
-                        body_md: payload.body_md,
+                        body_md: moderated.body.clone(),
                     },
+                    command.client_msg_id.clone(),
                 )
                 .await?;
-            state
-                .realtime
-                .emit(
-                    context.workspace_id,
-                    event(
-                        "MESSAGE_UPDATED",
-                        context.workspace_id,
-                        Some(message.channel_id),
-                        command.client_msg_id.clone(),
-                        serde_json::to_value(&message).unwrap_or_default(),
-                    ),
-                )
-                .await;
+            moderated.open_case(state, &message).await;
             state
                 .audit
                 .write(
@@ -576,21 +949,8 @@ async fn handle_client_text(
                 .await?;
             state
                 .channels
-                .delete_message(context, payload.message_id)
+                .delete_message(context, payload.message_id, command.client_msg_id.clone())
                 .await?;
-            state
-                .realtime
-                .emit(
-                    context.workspace_id,
-                    event(
-                        "MESSAGE_DELETED",
-                        context.workspace_id,
-                        Some(target.channel_id),
-                        command.client_msg_id.clone(),
-                        json!({"message_id": payload.message_id}),
-                    ),
-                )
-                .await;
             state
                 .audit
                 .write(
@@ -621,6 +981,7 @@ async fn handle_client_text(
                     &MessageQuery {
                         cursor: payload.cursor,
                         limit: payload.limit,
+                        after_seq: payload.after_seq,
                     },
                 )
                 .await?;
@@ -647,6 +1008,7 @@ async fn handle_client_text(
                     &MessageQuery {
                         cursor: payload.cursor,
                         limit: payload.limit,
+                        after_seq: None,
                     },
                 )
                 .await?;
@@ -689,19 +1051,19 @@ async fn handle_client_text(
                 .reactions
                 .add_reaction(&state.channels, context, payload.message_id, &payload.emoji)
                 .await?;
-            state
-                .realtime
-                .emit(
+            state.metrics.record_reaction_added(context.workspace_id);
+            emit_channel_event(
+                state,
+                context.workspace_id,
+                event(
+                    "REACTION_UPDATED",
                     context.workspace_id,
-                    event(
-                        "REACTION_UPDATED",
-                        context.workspace_id,
-                        Some(update.channel_id),
-                        command.client_msg_id.clone(),
-                        serde_json::to_value(&update).unwrap_or_default(),
-                    ),
-                )
-                .await;
+                    Some(update.channel_id),
+                    command.client_msg_id.clone(),
+                    serde_json::to_value(&update).unwrap_or_default(),
+                ),
+            )
+            .await;
             state
                 .audit
                 .write(
@@ -752,19 +1114,18 @@ async fn handle_client_text(
                 .reactions
                 .remove_reaction(&state.channels, context, payload.message_id, &payload.emoji)
                 .await?;
-            state
-                .realtime
-                .emit(
+            emit_channel_event(
+                state,
+                context.workspace_id,
+                event(
+                    "REACTION_UPDATED",
                     context.workspace_id,
-                    event(
-                        "REACTION_UPDATED",
-                        context.workspace_id,
-                        Some(update.channel_id),
-                        command.client_msg_id.clone(),
-                        serde_json::to_value(&update).unwrap_or_default(),
-                    ),
-                )
-                .await;
+                    Some(update.channel_id),
+                    command.client_msg_id.clone(),
+                    serde_json::to_value(&update).unwrap_or_default(),
+                ),
+            )
+            .await;
             state
                 .audit
                 .write(
@@ -784,6 +1145,15 @@ async fn handle_client_text(
             )
             .await?;
         }
+        "HEARTBEAT" => {
+            send_ack(
+                socket,
+                "HEARTBEAT",
+                command.client_msg_id,
+                json!({"ok": true}),
+            )
+            .await?;
+        }
         other => {
             return Err(ApiError::BadRequest(format!(
                 "unsupported websocket command: {other}"
@@ -807,6 +1177,7 @@ fn event(
         correlation_id,
         server_ts: Utc::now().timestamp_millis(),
         payload,
+        recipient_id: None,
     }
 }
 
@@ -826,6 +1197,48 @@ pub fn make_event(
     )
 }
 
+/// Emits a channel-scoped event to the channel's workspace and to every
+/// workspace it is shared with; falls back to `workspace_id` when the channel
+/// no longer exists.
+pub(crate) async fn emit_channel_event(
+    state: &AppState,
+    workspace_id: Uuid,
+    event: WsEventEnvelope,
+) {
+    let mut workspaces = match event.channel_id {
+        Some(channel_id) => state.channels.channel_workspaces(channel_id).await,
+        None => Vec::new(),
+    };
+    if workspaces.is_empty() {
+        workspaces.push(workspace_id);
+    }
+    for target in workspaces {
+        state
+            .realtime
+            .emit(
+                target,
+                WsEventEnvelope {
+                    workspace_id: Some(target),
+                    ..event.clone()
+                },
+            )
+            .await;
+    }
+}
+
+/// Event delivered only to `recipient_id`'s sockets in the workspace.
+pub fn make_user_event(
+    event_type: &str,
+    workspace_id: Uuid,
+    recipient_id: Uuid,
+    payload: Value,
+) -> WsEventEnvelope {
+    WsEventEnvelope {
+        recipient_id: Some(recipient_id),
+        ..event(event_type, workspace_id, None, None, payload)
+    }
+}
+
 async fn send_ack(
     socket: &mut WebSocket,
     command: &str,
@@ -842,6 +1255,7 @@ async fn send_ack(
             "command": command,
             "result": payload,
         }),
+        recipient_id: None,
     };
 
     socket
@@ -858,23 +1272,14 @@ fn error_event(error: ApiError) -> String {
         "event_type": "ERROR",
         "server_ts": Utc::now().timestamp_millis(),
         "payload": {
-            "status": status_from_error(&error),
+            "status": error.status_code().as_u16(),
+            "code": error.code(),
             "error": error.to_string(),
         }
     });
     serde_json::to_string(&body).unwrap_or_else(|_| "{\"event_type\":\"ERROR\"}".to_string())
 }
 
-fn status_from_error(error: &ApiError) -> u16 {
-    match error {
-        ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED.as_u16(),
-        ApiError::BadRequest(_) => StatusCode::BAD_REQUEST.as_u16(),
-        ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS.as_u16(),
-        ApiError::NotFound(_) => StatusCode::NOT_FOUND.as_u16(),
-        ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
-    }
-}
-
 fn normalize_client_msg_id(value: Option<&str>) -> ApiResult<Option<String>> {
     let Some(value) = value else {
         return Ok(None);
@@ -912,7 +1317,8 @@ fn ws_command_once_key(
 
 #[cfg(test)]
 mod tests {
-    use super::normalize_client_msg_id;
+    use super::*;
+    use crate::auth::WorkspaceRole;
 
     #[test]
     fn normalize_client_msg_id_accepts_trimmed_value() {
@@ -925,4 +1331,55 @@ mod tests {
         let error = normalize_client_msg_id(Some("   ")).expect_err("should fail");
         assert_eq!(error.to_string(), "client_msg_id must not be empty");
     }
+
+    #[tokio::test]
+    async fn connections_beyond_the_limit_evict_the_oldest() {
+        let hub = RealtimeHub::new(
+            None,
+            Duration::from_secs(300),
+            Arc::new(AppMetrics::default()),
+        );
+        let context = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            role: WorkspaceRole::Member,
+        };
+        let (first, mut first_rx, evicted) = hub.register_connection(&context, Some(2)).await;
+        assert_eq!(evicted, 0);
+        let (_, mut second_rx, _) = hub.register_connection(&context, Some(2)).await;
+        let (_, mut third_rx, evicted) = hub.register_connection(&context, Some(2)).await;
+        assert_eq!(evicted, 1);
+        assert!(first_rx.try_recv().is_ok());
+        assert!(second_rx.try_recv().is_err());
+        assert!(third_rx.try_recv().is_err());
+
+        hub.unregister_connection(&context, first).await;
+        let (_, _, evicted) = hub.register_connection(&context, None).await;
+        assert_eq!(evicted, 0);
+    }
+
+    #[test]
+    fn full_redis_queue_drops_events_and_counts_them() {
+        let metrics = AppMetrics::default();
+        let (tx, mut rx) = mpsc::channel(1);
+        let envelope = || RedisEventEnvelope {
+            source_instance_id: "test".to_string(),
+            event: make_event("MESSAGE_CREATED", Uuid::new_v4(), None, None, json!({})),
+        };
+        enqueue_redis_event(&tx, envelope(), &metrics);
+        enqueue_redis_event(&tx, envelope(), &metrics);
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains("galynx_ws_redis_publish_backlog 1"));
+        assert!(
+            rendered.contains("galynx_ws_redis_publish_dropped_total{reason=\"queue_full\"} 1")
+        );
+
+        assert!(rx.try_recv().is_ok());
+        metrics.record_redis_publish_dequeued();
+        assert!(
+            metrics
+                .render_prometheus()
+                .contains("galynx_ws_redis_publish_backlog 0")
+        );
+    }
 }
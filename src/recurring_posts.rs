@@ -0,0 +1,803 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, patch, post},
+};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    channels::{self, ChannelService},
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    storage::{RecurringPostRecordStore, Storage},
+};
+
+const MAX_RECURRING_POSTS_PER_WORKSPACE: usize = 50;
+const RUN_INTERVAL: Duration = Duration::from_secs(30);
+/// Long enough to reach the next 29 February.
+const SEARCH_HORIZON_DAYS: i64 = 366 * 4 + 1;
+
+#[derive(Clone)]
+pub struct RecurringPostService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRecurringPostRequest {
+    pub channel_id: Uuid,
+    /// Cron expression `minute hour day-of-month month day-of-week` in UTC,
+    /// e.g. `0 9 * * 1-5` for weekdays at 09:00.
+    pub schedule: String,
+    pub body_md: String,
+}
+
+/// Omitted fields are left unchanged.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRecurringPostRequest {
+    pub channel_id: Option<Uuid>,
+    pub schedule: Option<String>,
+    pub body_md: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RecurringPostResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub body_md: String,
+    pub schedule: String,
+    /// Author of every message the post creates.
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub paused: bool,
+    /// `null` while paused.
+    pub next_run_at: Option<i64>,
+    pub last_run_at: Option<i64>,
+    pub last_message_id: Option<Uuid>,
+    /// Why the last run did not post; `null` when it succeeded.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecurringPostListResponse {
+    pub items: Vec<RecurringPostResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/recurring-posts",
+            get(list_recurring_posts).post(create_recurring_post),
+        )
+        .route(
+            "/api/v1/recurring-posts/:id",
+            patch(update_recurring_post).delete(delete_recurring_post),
+        )
+        .route(
+            "/api/v1/recurring-posts/:id/pause",
+            post(pause_recurring_post),
+        )
+        .route(
+            "/api/v1/recurring-posts/:id/resume",
+            post(resume_recurring_post),
+        )
+}
+
+/// Parsed five-field cron expression. Each field is a bit set of the values
+/// it matches; `*`, lists, ranges and steps are supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub(crate) fn parse(expression: &str) -> Result<Self, String> {
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+        let &[minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return Err(
+                "must have 5 fields: minute hour day-of-month month day-of-week".to_string(),
+            );
+        };
+        // Day-of-week accepts 7 as a second spelling of Sunday.
+        let days_of_week = parse_field(day_of_week, 0, 7, "day-of-week")?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")? as u32,
+            days_of_month: parse_field(day_of_month, 1, 31, "day-of-month")? as u32,
+            months: parse_field(month, 1, 12, "month")? as u16,
+            days_of_week: ((days_of_week | (days_of_week >> 7)) & 0x7f) as u8,
+            any_day_of_month: day_of_month == "*",
+            any_day_of_week: day_of_week == "*",
+        })
+    }
+
+    /// First matching minute strictly after `after`, or `None` when the
+    /// expression never fires (e.g. `0 0 30 2 *`).
+    pub(crate) fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut at = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(ChronoDuration::minutes(1))?;
+        let horizon = at + ChronoDuration::days(SEARCH_HORIZON_DAYS);
+        while at < horizon {
+            if self.months & (1 << at.month()) == 0 {
+                let (year, month) = if at.month() == 12 {
+                    (at.year() + 1, 1)
+                } else {
+                    (at.year(), at.month() + 1)
+                };
+                at = NaiveDate::from_ymd_opt(year, month, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.matches_day(at) {
+                at = (at.date_naive() + ChronoDuration::days(1))
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if self.hours & (1 << at.hour()) == 0 {
+                at = at.with_minute(0)? + ChronoDuration::hours(1);
+            } else if self.minutes & (1 << at.minute()) == 0 {
+                at += ChronoDuration::minutes(1);
+            } else {
+                return Some(at);
+            }
+        }
+        None
+    }
+
+    /// Like cron, a restricted day-of-month and day-of-week match either one.
+    fn matches_day(&self, at: DateTime<Utc>) -> bool {
+        let by_month = self.days_of_month & (1 << at.day()) != 0;
+        let by_week = self.days_of_week & (1 << at.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => by_week,
+            (false, true) => by_month,
+            (false, false) => by_month || by_week,
+        }
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid {name} field `{field}`");
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse().map_err(|_| invalid())?,
+                    end.parse().map_err(|_| invalid())?,
+                ),
+                None => {
+                    let value = range.parse().map_err(|_| invalid())?;
+                    // `5/15` means "from 5 to the end, every 15".
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn next_run_at(schedule: &str, after: i64) -> Option<i64> {
+    let schedule = CronSchedule::parse(schedule).ok()?;
+    schedule
+        .next_after(DateTime::from_timestamp_millis(after)?)
+        .map(|at| at.timestamp_millis())
+}
+
+/// Checks the definition and returns its first run after `now`.
+fn validate(schedule: &str, body_md: &str, now: i64) -> ApiResult<i64> {
+    let mut errors = FieldErrors::default();
+    let next = match CronSchedule::parse(schedule) {
+        Ok(_) => next_run_at(schedule, now),
+        Err(error) => {
+            errors.add("schedule", error);
+            Some(now)
+        }
+    };
+    if next.is_none() {
+        errors.add("schedule", "never matches a date");
+    }
+    if body_md.is_empty() {
+        errors.add("body_md", "must not be empty");
+    }
+    errors.into_result()?;
+    Ok(next.unwrap_or(now))
+}
+
+impl RecurringPostService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn list(&self, context: &AuthContext) -> ApiResult<RecurringPostListResponse> {
+        ensure_workspace_admin(context)?;
+        let mut records = self
+            .storage
+            .list_recurring_posts(context.workspace_id)
+            .await;
+        records.sort_by_key(|record| (record.paused, record.next_run_at, record.id));
+        Ok(RecurringPostListResponse {
+            items: records.iter().map(RecurringPostResponse::from).collect(),
+        })
+    }
+
+    pub async fn create(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        payload: CreateRecurringPostRequest,
+    ) -> ApiResult<RecurringPostResponse> {
+        ensure_workspace_admin(context)?;
+        let body_md = payload.body_md.trim().to_string();
+        let schedule = payload
+            .schedule
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let now = Utc::now().timestamp_millis();
+        let next_run_at = validate(&schedule, &body_md, now)?;
+        self.ensure_target_channel(channels, context, payload.channel_id)
+            .await?;
+        if self
+            .storage
+            .list_recurring_posts(context.workspace_id)
+            .await
+            .len()
+            >= MAX_RECURRING_POSTS_PER_WORKSPACE
+        {
+            return Err(ApiError::Conflict(format!(
+                "at most {MAX_RECURRING_POSTS_PER_WORKSPACE} recurring posts per workspace"
+            )));
+        }
+
+        let record = RecurringPostRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id: context.workspace_id,
+            channel_id: payload.channel_id,
+            body_md,
+            schedule,
+            created_by: context.user_id,
+            created_at: now,
+            paused: false,
+            next_run_at,
+            last_run_at: None,
+            last_message_id: None,
+            last_error: None,
+        };
+        self.storage.put_recurring_post(record.clone()).await?;
+        Ok(RecurringPostResponse::from(&record))
+    }
+
+    pub async fn update(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        post_id: Uuid,
+        payload: UpdateRecurringPostRequest,
+    ) -> ApiResult<RecurringPostResponse> {
+        ensure_workspace_admin(context)?;
+        let mut record = self.find(context, post_id).await?;
+        if let Some(body_md) = payload.body_md {
+            record.body_md = body_md.trim().to_string();
+        }
+        let schedule_changed = payload.schedule.is_some();
+        if let Some(schedule) = payload.schedule {
+            record.schedule = schedule.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        let next_run_at = validate(
+            &record.schedule,
+            &record.body_md,
+            Utc::now().timestamp_millis(),
+        )?;
+        if let Some(channel_id) = payload.channel_id {
+            self.ensure_target_channel(channels, context, channel_id)
+                .await?;
+            record.channel_id = channel_id;
+        }
+        if schedule_changed {
+            record.next_run_at = next_run_at;
+        }
+        self.storage.put_recurring_post(record.clone()).await?;
+        Ok(RecurringPostResponse::from(&record))
+    }
+
+    /// Resuming skips the runs missed while paused and continues from now.
+    pub async fn set_paused(
+        &self,
+        context: &AuthContext,
+        post_id: Uuid,
+        paused: bool,
+    ) -> ApiResult<RecurringPostResponse> {
+        ensure_workspace_admin(context)?;
+        let mut record = self.find(context, post_id).await?;
+        if record.paused == paused {
+            return Err(ApiError::Conflict(if paused {
+                "recurring post is already paused".to_string()
+            } else {
+                "recurring post is not paused".to_string()
+            }));
+        }
+        record.paused = paused;
+        if !paused {
+            record.next_run_at = validate(
+                &record.schedule,
+                &record.body_md,
+                Utc::now().timestamp_millis(),
+            )?;
+        }
+        self.storage.put_recurring_post(record.clone()).await?;
+        Ok(RecurringPostResponse::from(&record))
+    }
+
+    pub async fn delete(&self, context: &AuthContext, post_id: Uuid) -> ApiResult<()> {
+        ensure_workspace_admin(context)?;
+        self.find(context, post_id).await?;
+        self.storage.remove_recurring_post(post_id).await;
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        context: &AuthContext,
+        post_id: Uuid,
+    ) -> ApiResult<RecurringPostRecordStore> {
+        self.storage
+            .get_recurring_post(post_id)
+            .await
+            .filter(|record| record.workspace_id == context.workspace_id)
+            .ok_or_else(|| ApiError::NotFound("recurring post not found".to_string()))
+    }
+
+    /// The creator must be able to post there, and the server cannot write
+    /// into end-to-end encrypted channels.
+    async fn ensure_target_channel(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        channel_id: Uuid,
+    ) -> ApiResult<()> {
+        channels.ensure_channel_access(context, channel_id).await?;
+        if self
+            .storage
+            .get_channel(&channel_id)
+            .await
+            .is_some_and(|channel| channel.e2ee)
+        {
+            return Err(ApiError::BadRequest(
+                "recurring posts cannot target encrypted channels".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<&RecurringPostRecordStore> for RecurringPostResponse {
+    fn from(record: &RecurringPostRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            channel_id: record.channel_id,
+            body_md: record.body_md.clone(),
+            schedule: record.schedule.clone(),
+            created_by: record.created_by,
+            created_at: record.created_at,
+            paused: record.paused,
+            next_run_at: (!record.paused).then_some(record.next_run_at),
+            last_run_at: record.last_run_at,
+            last_message_id: record.last_message_id,
+            last_error: record.last_error.clone(),
+        }
+    }
+}
+
+fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
+    match context.role {
+        WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
+            "you do not have permission to manage recurring posts".to_string(),
+        )),
+    }
+}
+
+/// Posts due recurring messages every few seconds for as long as the server
+/// runs.
+pub(crate) fn spawn_runner(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RUN_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            run_due(&state).await;
+        }
+    });
+}
+
+/// Each due post runs once, even if several slots were missed while the
+/// server was down, and is rescheduled from now. Messages go through the same
+/// pipeline as a message written by the creator, so mutes, moderation and
+/// channel access apply; a failure is kept in `last_error`.
+async fn run_due(state: &AppState) {
+    let now = Utc::now().timestamp_millis();
+    for post in state.storage.list_due_recurring_posts(now).await {
+        let Some(next_run_at) = next_run_at(&post.schedule, now) else {
+            continue;
+        };
+        if !state
+            .storage
+            .claim_recurring_post_run(post.id, post.next_run_at, next_run_at)
+            .await
+        {
+            continue;
+        }
+
+        let posted = match state
+            .auth
+            .member_context(post.workspace_id, post.created_by)
+            .await
+        {
+            Ok(context) => {
+                channels::post_message(state, &context, post.channel_id, post.body_md.clone()).await
+            }
+            Err(error) => Err(error),
+        };
+        let (message_id, error) = match posted {
+            Ok(message) => (Some(message.id), None),
+            Err(error) => {
+                tracing::warn!(recurring_post_id = %post.id, %error, "recurring post failed");
+                (None, Some(error.to_string()))
+            }
+        };
+        state
+            .storage
+            .record_recurring_post_run(post.id, now, message_id, error)
+            .await;
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/recurring-posts",
+    operation_id = "list_recurring_posts",
+    tag = "recurring_posts",
+    responses(
+        (status = 200, description = "Recurring posts, active ones by next run first", body = RecurringPostListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_recurring_posts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<RecurringPostListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(state.recurring_posts.list(&context).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/recurring-posts",
+    operation_id = "create_recurring_post",
+    tag = "recurring_posts",
+    request_body = CreateRecurringPostRequest,
+    responses(
+        (status = 201, description = "Recurring post scheduled", body = RecurringPostResponse),
+        (status = 400, description = "Encrypted channel", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse),
+        (status = 409, description = "Too many recurring posts", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_recurring_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateRecurringPostRequest>,
+) -> ApiResult<(StatusCode, Json<RecurringPostResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let post = state
+        .recurring_posts
+        .create(&state.channels, &context, payload)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "RECURRING_POST_CREATED",
+            "recurring_post",
+            Some(post.id.to_string()),
+            json!({ "channel_id": post.channel_id, "schedule": post.schedule }),
+        )
+        .await;
+    Ok((StatusCode::CREATED, Json(post)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/recurring-posts/{id}",
+    operation_id = "update_recurring_post",
+    tag = "recurring_posts",
+    request_body = UpdateRecurringPostRequest,
+    responses(
+        (status = 200, description = "Recurring post updated", body = RecurringPostResponse),
+        (status = 400, description = "Encrypted channel", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Recurring post or channel not found", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_recurring_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(post_id): Path<Uuid>,
+    Json(payload): Json<UpdateRecurringPostRequest>,
+) -> ApiResult<Json<RecurringPostResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let post = state
+        .recurring_posts
+        .update(&state.channels, &context, post_id, payload)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "RECURRING_POST_UPDATED",
+            "recurring_post",
+            Some(post.id.to_string()),
+            json!({ "channel_id": post.channel_id, "schedule": post.schedule }),
+        )
+        .await;
+    Ok(Json(post))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/recurring-posts/{id}/pause",
+    operation_id = "pause_recurring_post",
+    tag = "recurring_posts",
+    responses(
+        (status = 200, description = "Recurring post paused", body = RecurringPostResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Recurring post not found", body = ErrorResponse),
+        (status = 409, description = "Already paused", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn pause_recurring_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(post_id): Path<Uuid>,
+) -> ApiResult<Json<RecurringPostResponse>> {
+    set_paused(state, headers, post_id, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/recurring-posts/{id}/resume",
+    operation_id = "resume_recurring_post",
+    tag = "recurring_posts",
+    responses(
+        (status = 200, description = "Recurring post resumed", body = RecurringPostResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Recurring post not found", body = ErrorResponse),
+        (status = 409, description = "Not paused", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn resume_recurring_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(post_id): Path<Uuid>,
+) -> ApiResult<Json<RecurringPostResponse>> {
+    set_paused(state, headers, post_id, false).await
+}
+
+async fn set_paused(
+    state: AppState,
+    headers: HeaderMap,
+    post_id: Uuid,
+    paused: bool,
+) -> ApiResult<Json<RecurringPostResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let post = state
+        .recurring_posts
+        .set_paused(&context, post_id, paused)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            if paused {
+                "RECURRING_POST_PAUSED"
+            } else {
+                "RECURRING_POST_RESUMED"
+            },
+            "recurring_post",
+            Some(post.id.to_string()),
+            json!({ "next_run_at": post.next_run_at }),
+        )
+        .await;
+    Ok(Json(post))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/recurring-posts/{id}",
+    operation_id = "delete_recurring_post",
+    tag = "recurring_posts",
+    responses(
+        (status = 204, description = "Recurring post deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Recurring post not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn delete_recurring_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(post_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    state.recurring_posts.delete(&context, post_id).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "RECURRING_POST_DELETED",
+            "recurring_post",
+            Some(post_id.to_string()),
+            json!({}),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PersistenceBackend;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .expect("valid timestamp")
+            .with_timezone(&Utc)
+    }
+
+    #[tokio::test]
+    async fn schedules_find_the_next_slot_and_runs_are_claimed_once() {
+        let next = |expression: &str, after: &str| {
+            CronSchedule::parse(expression)
+                .expect("valid schedule")
+                .next_after(at(after))
+        };
+        // 2026-03-06 is a Friday.
+        assert_eq!(
+            next("0 9 * * 1-5", "2026-03-06T09:00:00Z"),
+            Some(at("2026-03-09T09:00:00Z"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2026-03-06T10:07:30Z"),
+            Some(at("2026-03-06T10:15:00Z"))
+        );
+        assert_eq!(
+            next("30 8 1 * 0", "2026-03-06T10:00:00Z"),
+            Some(at("2026-03-08T08:30:00Z"))
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2026-03-01T00:00:00Z"),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2026-03-01T00:00:00Z"), None);
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "0 9 * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(CronSchedule::parse(invalid).is_err(), "{invalid}");
+        }
+
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let (workspace_id, owner_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let channels = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let posts = RecurringPostService::new(storage.clone());
+        let owner = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let channel_id = channels.list_channels(workspace_id).await[0].id;
+        let request = |schedule: &str| CreateRecurringPostRequest {
+            channel_id,
+            schedule: schedule.to_string(),
+            body_md: "Standup: what are you working on?".to_string(),
+        };
+        assert!(matches!(
+            posts.create(&channels, &owner, request("0 25 * * *")).await,
+            Err(ApiError::Validation(_))
+        ));
+        let post = posts
+            .create(&channels, &owner, request("0  9 * *  1-5"))
+            .await
+            .expect("created");
+        assert_eq!(post.schedule, "0 9 * * 1-5");
+        let scheduled_at = post.next_run_at.expect("active post");
+
+        assert_eq!(
+            storage.list_due_recurring_posts(scheduled_at).await.len(),
+            1
+        );
+        assert!(
+            storage
+                .claim_recurring_post_run(post.id, scheduled_at, scheduled_at + 60_000)
+                .await
+        );
+        assert!(
+            !storage
+                .claim_recurring_post_run(post.id, scheduled_at, scheduled_at + 60_000)
+                .await
+        );
+
+        let paused = posts
+            .set_paused(&owner, post.id, true)
+            .await
+            .expect("paused");
+        assert_eq!(paused.next_run_at, None);
+        assert!(storage.list_due_recurring_posts(i64::MAX).await.is_empty());
+        assert!(matches!(
+            posts.set_paused(&owner, post.id, true).await,
+            Err(ApiError::Conflict(_))
+        ));
+    }
+}
@@ -0,0 +1,344 @@
+use std::time::Duration;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    notifications::{NewNotification, NotificationKind},
+    realtime,
+    storage::ReminderRecordStore,
+};
+
+const MAX_REMINDERS_PER_USER: usize = 100;
+const MAX_REMINDER_DELAY_MINUTES: i64 = 365 * 24 * 60;
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateReminderRequest {
+    /// Minutes from now; mutually exclusive with `remind_at`.
+    pub delay_minutes: Option<i64>,
+    /// Unix millis; mutually exclusive with `delay_minutes`.
+    pub remind_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReminderResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub message_id: Uuid,
+    pub remind_at: i64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReminderListResponse {
+    pub items: Vec<ReminderResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/messages/:id/remind", post(create_reminder))
+        .route("/api/v1/me/reminders", get(list_reminders))
+        .route("/api/v1/me/reminders/:id", delete(cancel_reminder))
+}
+
+impl From<&ReminderRecordStore> for ReminderResponse {
+    fn from(record: &ReminderRecordStore) -> Self {
+        Self {
+            id: record.id,
+            workspace_id: record.workspace_id,
+            channel_id: record.channel_id,
+            message_id: record.message_id,
+            remind_at: record.remind_at,
+            created_at: record.created_at,
+        }
+    }
+}
+
+/// Resolves the requested delivery time in epoch millis; exactly one of the
+/// two fields must be set and the result must fall within the next year.
+fn resolve_remind_at(payload: &CreateReminderRequest, now: i64) -> ApiResult<i64> {
+    let mut errors = FieldErrors::default();
+    let max = now + MAX_REMINDER_DELAY_MINUTES * 60_000;
+    let remind_at = match (payload.delay_minutes, payload.remind_at) {
+        (Some(minutes), None) => {
+            if !(1..=MAX_REMINDER_DELAY_MINUTES).contains(&minutes) {
+                errors.add(
+                    "delay_minutes",
+                    format!("must be between 1 and {MAX_REMINDER_DELAY_MINUTES}"),
+                );
+            }
+            now + minutes.clamp(1, MAX_REMINDER_DELAY_MINUTES) * 60_000
+        }
+        (None, Some(at)) => {
+            if at <= now {
+                errors.add("remind_at", "must be in the future");
+            } else if at > max {
+                errors.add("remind_at", "must be within one year");
+            }
+            at
+        }
+        _ => {
+            errors.add(
+                "delay_minutes",
+                "set exactly one of delay_minutes or remind_at",
+            );
+            now
+        }
+    };
+    errors.into_result()?;
+    Ok(remind_at)
+}
+
+/// Delivers due reminders every few seconds for as long as the server runs.
+pub(crate) fn spawn_delivery(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DELIVERY_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            deliver_due(&state).await;
+        }
+    });
+}
+
+/// Stores a `reminder` notification and pushes a `REMINDER_DUE` event for each
+/// due reminder. Reminders whose message was deleted or is no longer visible
+/// to the user are dropped silently.
+async fn deliver_due(state: &AppState) {
+    for reminder in state
+        .storage
+        .take_due_reminders(Utc::now().timestamp_millis())
+        .await
+    {
+        let Some(message) = state
+            .storage
+            .get_message(&reminder.message_id)
+            .await
+            .filter(|message| message.deleted_at.is_none())
+        else {
+            continue;
+        };
+        if !state
+            .notifications
+            .can_view_channel(reminder.workspace_id, reminder.user_id, reminder.channel_id)
+            .await
+        {
+            continue;
+        }
+        let notification = state
+            .notifications
+            .create(NewNotification {
+                workspace_id: reminder.workspace_id,
+                user_id: reminder.user_id,
+                kind: NotificationKind::Reminder,
+                actor_id: Some(message.sender_id),
+                channel_id: Some(reminder.channel_id),
+                message_id: Some(reminder.message_id),
+                body: message.body_md.clone(),
+            })
+            .await;
+        state
+            .realtime
+            .emit(
+                reminder.workspace_id,
+                realtime::make_user_event(
+                    "REMINDER_DUE",
+                    reminder.workspace_id,
+                    reminder.user_id,
+                    json!({
+                        "reminder": ReminderResponse::from(&reminder),
+                        "notification": notification,
+                    }),
+                ),
+            )
+            .await;
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/messages/{id}/remind",
+    operation_id = "create_reminder",
+    tag = "reminders",
+    request_body = CreateReminderRequest,
+    responses(
+        (status = 201, description = "Reminder scheduled", body = ReminderResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Message not found", body = ErrorResponse),
+        (status = 409, description = "Too many pending reminders", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn create_reminder(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(message_id): Path<Uuid>,
+    Json(payload): Json<CreateReminderRequest>,
+) -> ApiResult<(StatusCode, Json<ReminderResponse>)> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let message = state
+        .channels
+        .get_message(context.workspace_id, message_id)
+        .await?;
+    state
+        .channels
+        .ensure_channel_access(&context, message.channel_id)
+        .await?;
+    let now = Utc::now().timestamp_millis();
+    let remind_at = resolve_remind_at(&payload, now)?;
+    if state
+        .storage
+        .list_reminders(context.workspace_id, context.user_id)
+        .await
+        .len()
+        >= MAX_REMINDERS_PER_USER
+    {
+        return Err(ApiError::Conflict(format!(
+            "at most {MAX_REMINDERS_PER_USER} pending reminders per user"
+        )));
+    }
+
+    let record = ReminderRecordStore {
+        id: Uuid::new_v4(),
+        workspace_id: context.workspace_id,
+        user_id: context.user_id,
+        channel_id: message.channel_id,
+        message_id,
+        remind_at,
+        created_at: now,
+    };
+    state.storage.put_reminder(record.clone()).await;
+    Ok((StatusCode::CREATED, Json(ReminderResponse::from(&record))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/reminders",
+    operation_id = "list_reminders",
+    tag = "reminders",
+    responses(
+        (status = 200, description = "Pending reminders, soonest first", body = ReminderListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_reminders(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<ReminderListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let mut items = state
+        .storage
+        .list_reminders(context.workspace_id, context.user_id)
+        .await
+        .iter()
+        .map(ReminderResponse::from)
+        .collect::<Vec<_>>();
+    items.sort_by_key(|item| (item.remind_at, item.id));
+    Ok(Json(ReminderListResponse { items }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/reminders/{id}",
+    operation_id = "cancel_reminder",
+    tag = "reminders",
+    responses(
+        (status = 204, description = "Reminder cancelled"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Reminder not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn cancel_reminder(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(reminder_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    if !state
+        .storage
+        .remove_reminder(context.workspace_id, context.user_id, reminder_id)
+        .await
+    {
+        return Err(ApiError::NotFound("reminder not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{PersistenceBackend, Storage};
+
+    #[tokio::test]
+    async fn reminders_resolve_and_are_taken_once_due() {
+        let now = 1_000_000;
+        let request = |delay_minutes, remind_at: Option<i64>| CreateReminderRequest {
+            delay_minutes,
+            remind_at,
+        };
+        assert_eq!(
+            resolve_remind_at(&request(Some(5), None), now).unwrap(),
+            now + 300_000
+        );
+        assert_eq!(
+            resolve_remind_at(&request(None, Some(now + 1_000)), now).unwrap(),
+            now + 1_000
+        );
+        for invalid in [
+            request(None, None),
+            request(Some(5), Some(now + 1_000)),
+            request(Some(0), None),
+            request(None, Some(now - 1)),
+        ] {
+            assert!(matches!(
+                resolve_remind_at(&invalid, now),
+                Err(ApiError::Validation(_))
+            ));
+        }
+
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let (workspace_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        for remind_at in [now - 1, now + 60_000] {
+            storage
+                .put_reminder(ReminderRecordStore {
+                    id: Uuid::new_v4(),
+                    workspace_id,
+                    user_id,
+                    channel_id: Uuid::new_v4(),
+                    message_id: Uuid::new_v4(),
+                    remind_at,
+                    created_at: now - 10,
+                })
+                .await;
+        }
+        let due = storage.take_due_reminders(now).await;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].remind_at, now - 1);
+        assert!(storage.take_due_reminders(now).await.is_empty());
+        assert_eq!(storage.list_reminders(workspace_id, user_id).await.len(), 1);
+    }
+}
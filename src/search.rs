@@ -0,0 +1,305 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    routing::get,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::AuthContext,
+    channels::{ChannelService, MessageListResponse, parse_cursor},
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse},
+    storage::{MessageSearch, Storage},
+};
+
+const QUERY_MAX_CHARS: usize = 200;
+const MAX_TERMS: usize = 10;
+
+#[derive(Clone)]
+pub struct SearchService {
+    storage: Arc<Storage>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SearchMessagesQuery {
+    /// Keywords; every one must appear in the body (case-insensitive).
+    pub q: String,
+    pub channel_id: Option<Uuid>,
+    pub sender_id: Option<Uuid>,
+    /// Only messages created at or after this time (unix millis).
+    pub from: Option<i64>,
+    /// Only messages created before this time (unix millis).
+    pub to: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/v1/search/messages", get(search_messages))
+}
+
+impl SearchService {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Searches the messages of every channel the caller can read, or of
+    /// `channel_id` only. Deleted messages and end-to-end encrypted channels
+    /// are never searched.
+    pub async fn search_messages(
+        &self,
+        channels: &ChannelService,
+        context: &AuthContext,
+        query: &SearchMessagesQuery,
+    ) -> ApiResult<MessageListResponse> {
+        let terms = search_terms(&query.q)?;
+        if let (Some(from), Some(to)) = (query.from, query.to)
+            && from >= to
+        {
+            return Err(ApiError::BadRequest("from must be before to".to_string()));
+        }
+        let limit = query.limit.unwrap_or(50).clamp(1, 100);
+        let before = query
+            .cursor
+            .as_deref()
+            .map(parse_cursor)
+            .transpose()
+            .map_err(|error| {
+                ApiError::Coded(ErrorCode::InvalidCursor, format!("invalid cursor: {error}"))
+            })?;
+
+        let mut visible = channels.visible_channels(context).await;
+        if let Some(channel_id) = query.channel_id {
+            channels.ensure_channel_access(context, channel_id).await?;
+            visible.retain(|channel| channel.id == channel_id);
+            if visible.iter().any(|channel| channel.e2ee) {
+                return Err(ApiError::BadRequest(
+                    "encrypted channels cannot be searched".to_string(),
+                ));
+            }
+        }
+        visible.retain(|channel| !channel.e2ee);
+        let mut workspace_ids = visible
+            .iter()
+            .map(|channel| channel.workspace_id)
+            .collect::<Vec<_>>();
+        workspace_ids.sort();
+        workspace_ids.dedup();
+
+        let mut found = self
+            .storage
+            .search_messages(&MessageSearch {
+                workspace_ids,
+                channel_ids: visible.iter().map(|channel| channel.id).collect(),
+                sender_id: query.sender_id,
+                terms,
+                from: query.from,
+                to: query.to,
+                before,
+                limit: limit + 1,
+            })
+            .await;
+        let has_more = found.len() > limit;
+        found.truncate(limit);
+        let items = channels.message_responses(context, found).await;
+        let next_cursor = items
+            .last()
+            .filter(|_| has_more)
+            .map(|message| format!("{}:{}", message.created_at, message.id.as_u128()));
+        Ok(MessageListResponse { items, next_cursor })
+    }
+}
+
+fn search_terms(query: &str) -> ApiResult<Vec<String>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(ApiError::BadRequest("q is required".to_string()));
+    }
+    if query.chars().count() > QUERY_MAX_CHARS {
+        return Err(ApiError::BadRequest(format!(
+            "q must be at most {QUERY_MAX_CHARS} characters"
+        )));
+    }
+    let mut terms = query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    terms.sort();
+    terms.dedup();
+    if terms.len() > MAX_TERMS {
+        return Err(ApiError::BadRequest(format!(
+            "q must have at most {MAX_TERMS} keywords"
+        )));
+    }
+    Ok(terms)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/search/messages",
+    operation_id = "search_messages",
+    tag = "messages",
+    params(SearchMessagesQuery),
+    responses(
+        (status = 200, description = "Matching messages, newest first", body = MessageListResponse),
+        (status = 400, description = "Invalid query, range or cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "No access to the channel", body = ErrorResponse),
+        (status = 404, description = "Channel not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn search_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SearchMessagesQuery>,
+) -> ApiResult<Json<MessageListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    Ok(Json(
+        state
+            .search
+            .search_messages(&state.channels, &context, &query)
+            .await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::WorkspaceRole,
+        channels::{CreateChannelRequest, CreateMessageRequest},
+        storage::PersistenceBackend,
+    };
+
+    #[tokio::test]
+    async fn search_matches_all_keywords_in_visible_channels() {
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let channels = ChannelService::new(storage.clone(), workspace_id, owner_id);
+        let search = SearchService::new(storage.clone());
+        let owner = AuthContext {
+            user_id: owner_id,
+            workspace_id,
+            role: WorkspaceRole::Owner,
+        };
+        let member = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id,
+            role: WorkspaceRole::Member,
+        };
+        storage
+            .put_membership_role(workspace_id, member.user_id, "member")
+            .await;
+        let general = channels.list_channels(workspace_id).await[0].id;
+        let private = channels
+            .create_channel(
+                workspace_id,
+                owner_id,
+                CreateChannelRequest {
+                    name: "ops".to_string(),
+                    is_private: true,
+                    e2ee: false,
+                },
+            )
+            .await
+            .expect("channel")
+            .id;
+        for (channel_id, body) in [
+            (general, "Deploy failed on staging"),
+            (general, "deploy went fine"),
+            (general, "lunch?"),
+            (private, "deploy FAILED in prod"),
+        ] {
+            channels
+                .create_message(
+                    &owner,
+                    channel_id,
+                    CreateMessageRequest {
+                        body_md: body.to_string(),
+                    },
+                    None,
+                )
+                .await
+                .expect("message");
+        }
+        let query = |q: &str, limit| SearchMessagesQuery {
+            q: q.to_string(),
+            channel_id: None,
+            sender_id: None,
+            from: None,
+            to: None,
+            cursor: None,
+            limit,
+        };
+        let bodies = |page: &MessageListResponse| {
+            page.items
+                .iter()
+                .map(|item| item.body_md.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let page = search
+            .search_messages(&channels, &owner, &query("failed  DEPLOY", Some(1)))
+            .await
+            .expect("searched");
+        assert_eq!(page.items.len(), 1);
+        let next = search
+            .search_messages(
+                &channels,
+                &owner,
+                &SearchMessagesQuery {
+                    cursor: page.next_cursor.clone(),
+                    ..query("failed deploy", Some(1))
+                },
+            )
+            .await
+            .expect("second page");
+        let mut found = [bodies(&page), bodies(&next)].concat();
+        found.sort();
+        assert_eq!(
+            found,
+            vec!["Deploy failed on staging", "deploy FAILED in prod"]
+        );
+        assert_eq!(next.next_cursor, None);
+
+        let page = search
+            .search_messages(&channels, &member, &query("deploy", None))
+            .await
+            .expect("searched");
+        assert_eq!(page.items.len(), 2);
+        assert!(page.items.iter().all(|item| item.channel_id == general));
+        assert!(matches!(
+            search
+                .search_messages(
+                    &channels,
+                    &member,
+                    &SearchMessagesQuery {
+                        channel_id: Some(private),
+                        ..query("deploy", None)
+                    },
+                )
+                .await,
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(matches!(
+            search
+                .search_messages(&channels, &member, &query("   ", None))
+                .await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::get,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthContext, WorkspaceRole},
+    errors::{ApiError, ApiResult, ErrorResponse, FieldErrors},
+    storage::{SessionLimitsRecordStore, Storage},
+    workspaces::ensure_context_workspace,
+};
+
+const MAX_SESSION_LIMIT: u32 = 100;
+
+/// Per-user caps on concurrent sessions in the workspace; `null` is unlimited.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SessionLimits {
+    pub max_refresh_sessions: Option<u32>,
+    /// Enforced per API instance.
+    pub max_ws_connections: Option<u32>,
+}
+
+/// Replaces both caps; omitted or `null` fields remove the cap.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSessionLimitsRequest {
+    pub max_refresh_sessions: Option<u32>,
+    pub max_ws_connections: Option<u32>,
+}
+
+impl From<&SessionLimitsRecordStore> for SessionLimits {
+    fn from(record: &SessionLimitsRecordStore) -> Self {
+        let cap = |value: Option<i64>| value.and_then(|value| u32::try_from(value).ok());
+        Self {
+            max_refresh_sessions: cap(record.max_refresh_sessions),
+            max_ws_connections: cap(record.max_ws_connections),
+        }
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/api/v1/workspaces/:id/session-limits",
+        get(get_session_limits).put(update_session_limits),
+    )
+}
+
+async fn session_limits(storage: &Storage, workspace_id: Uuid) -> SessionLimits {
+    storage
+        .get_session_limits(workspace_id)
+        .await
+        .as_ref()
+        .map(SessionLimits::from)
+        .unwrap_or_default()
+}
+
+pub(crate) async fn max_ws_connections(storage: &Storage, workspace_id: Uuid) -> Option<usize> {
+    session_limits(storage, workspace_id)
+        .await
+        .max_ws_connections
+        .map(|limit| limit as usize)
+}
+
+/// Revokes the caller's oldest refresh sessions beyond the workspace cap;
+/// called right after a login created a new one.
+pub(crate) async fn enforce_refresh_sessions(state: &AppState, context: &AuthContext) {
+    let Some(limit) = session_limits(&state.storage, context.workspace_id)
+        .await
+        .max_refresh_sessions
+    else {
+        return;
+    };
+    let now = Utc::now().timestamp();
+    let mut sessions = state
+        .storage
+        .list_active_refresh_sessions(context.user_id, context.workspace_id, now)
+        .await;
+    let excess = sessions.len().saturating_sub(limit as usize);
+    if excess == 0 {
+        return;
+    }
+    sessions.sort_by(|a, b| a.1.created_at.cmp(&b.1.created_at).then(a.0.cmp(&b.0)));
+    for (hash, _) in sessions.iter().take(excess) {
+        state
+            .storage
+            .update_refresh_session(hash, |session| session.revoked_at = Some(now))
+            .await;
+    }
+    audit_eviction(state, context, "refresh", excess).await;
+}
+
+pub(crate) async fn audit_eviction(
+    state: &AppState,
+    context: &AuthContext,
+    transport: &str,
+    count: usize,
+) {
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "SESSIONS_EVICTED",
+            "session",
+            None,
+            json!({ "transport": transport, "count": count }),
+        )
+        .await;
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/workspaces/{id}/session-limits",
+    operation_id = "get_session_limits",
+    tag = "workspaces",
+    responses(
+        (status = 200, description = "Session limits in effect", body = SessionLimits),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_session_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+) -> ApiResult<Json<SessionLimits>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    Ok(Json(session_limits(&state.storage, workspace_id).await))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/workspaces/{id}/session-limits",
+    operation_id = "update_session_limits",
+    tag = "workspaces",
+    request_body = UpdateSessionLimitsRequest,
+    responses(
+        (status = 200, description = "Session limits replaced", body = SessionLimits),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_session_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<UpdateSessionLimitsRequest>,
+) -> ApiResult<Json<SessionLimits>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    if !matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin) {
+        return Err(ApiError::Forbidden(
+            "you do not have permission to change session limits".to_string(),
+        ));
+    }
+    let mut errors = FieldErrors::default();
+    for (field, value) in [
+        ("max_refresh_sessions", payload.max_refresh_sessions),
+        ("max_ws_connections", payload.max_ws_connections),
+    ] {
+        if value.is_some_and(|value| !(1..=MAX_SESSION_LIMIT).contains(&value)) {
+            errors.add(field, format!("must be between 1 and {MAX_SESSION_LIMIT}"));
+        }
+    }
+    errors.into_result()?;
+
+    let record = SessionLimitsRecordStore {
+        workspace_id,
+        max_refresh_sessions: payload.max_refresh_sessions.map(i64::from),
+        max_ws_connections: payload.max_ws_connections.map(i64::from),
+        updated_by: context.user_id,
+        updated_at: Utc::now().timestamp_millis(),
+    };
+    state.storage.put_session_limits(record.clone()).await;
+    let limits = SessionLimits::from(&record);
+    state
+        .audit
+        .write(
+            workspace_id,
+            Some(context.user_id),
+            "SESSION_LIMITS_UPDATED",
+            "workspace",
+            Some(workspace_id.to_string()),
+            json!(limits),
+        )
+        .await;
+    Ok(Json(limits))
+}
@@ -0,0 +1,321 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    routing::{delete, get},
+};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{self, AuthContext},
+    errors::{ApiError, ApiResult, ErrorResponse},
+    notifications,
+    storage::{KnownDeviceRecordStore, Storage},
+};
+
+const USER_AGENT_MAX_CHARS: usize = 256;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    /// The login came from an IP/user-agent pair the account had not used.
+    pub new_device: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionListResponse {
+    pub items: Vec<SessionResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/me/sessions", get(list_sessions))
+        .route("/api/v1/me/sessions/:id", delete(revoke_session))
+}
+
+/// Tags the session a login just created with where it came from and, when
+/// the IP/user-agent pair is new for the account, audits the login and warns
+/// the user in-app and by email. The first login ever seen is not alerted.
+pub(crate) async fn track_login(
+    state: &AppState,
+    context: &AuthContext,
+    refresh_token: &str,
+    client_ip: &str,
+    headers: &HeaderMap,
+) {
+    let user_agent = user_agent(headers);
+    let new_device = remember_device(
+        &state.storage,
+        context.user_id,
+        client_ip,
+        user_agent.as_deref().unwrap_or_default(),
+        Utc::now().timestamp(),
+    )
+    .await;
+    state
+        .storage
+        .update_refresh_session(&auth::token_hash(refresh_token), |session| {
+            session.ip = Some(client_ip.to_string());
+            session.user_agent = user_agent.clone();
+            session.new_device = new_device;
+        })
+        .await;
+    if !new_device {
+        return;
+    }
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "AUTH_NEW_DEVICE",
+            "user",
+            Some(context.user_id.to_string()),
+            json!({ "ip": client_ip, "user_agent": user_agent }),
+        )
+        .await;
+    notifications::notify_new_device_login(
+        state,
+        context,
+        client_ip,
+        user_agent.as_deref().unwrap_or("unknown browser"),
+    )
+    .await;
+}
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.chars().take(USER_AGENT_MAX_CHARS).collect())
+}
+
+/// Records the IP/user-agent pair for the user and reports whether it is new
+/// to an account that had already logged in from somewhere else.
+async fn remember_device(
+    storage: &Storage,
+    user_id: Uuid,
+    ip: &str,
+    user_agent: &str,
+    now: i64,
+) -> bool {
+    let fingerprint = auth::token_hash(&format!("{ip}\n{user_agent}"));
+    let known = storage.list_known_devices(user_id).await;
+    let seen = known
+        .iter()
+        .find(|device| device.fingerprint == fingerprint);
+    let new_device = seen.is_none() && !known.is_empty();
+    storage
+        .put_known_device(KnownDeviceRecordStore {
+            user_id,
+            first_seen_at: seen.map_or(now, |device| device.first_seen_at),
+            fingerprint,
+            ip: ip.to_string(),
+            user_agent: user_agent.to_string(),
+            last_seen_at: now,
+        })
+        .await;
+    new_device
+}
+
+async fn active_sessions(
+    storage: &Storage,
+    context: &AuthContext,
+) -> Vec<(String, SessionResponse)> {
+    let mut sessions = storage
+        .list_active_refresh_sessions(
+            context.user_id,
+            context.workspace_id,
+            Utc::now().timestamp(),
+        )
+        .await
+        .into_iter()
+        .map(|(hash, session)| {
+            (
+                hash,
+                SessionResponse {
+                    id: session.session_id,
+                    ip: session.ip,
+                    user_agent: session.user_agent,
+                    created_at: session.created_at,
+                    expires_at: session.expires_at,
+                    new_device: session.new_device,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    sessions.sort_by(|a, b| {
+        b.1.created_at
+            .cmp(&a.1.created_at)
+            .then(a.1.id.cmp(&b.1.id))
+    });
+    sessions
+}
+
+/// Revokes the refresh token of one of the caller's sessions in the current
+/// workspace; access tokens already issued stay valid until they expire.
+async fn revoke(
+    storage: &Storage,
+    context: &AuthContext,
+    session_id: Uuid,
+) -> ApiResult<SessionResponse> {
+    let now = Utc::now().timestamp();
+    let mut revoked = None;
+    for (hash, session) in active_sessions(storage, context).await {
+        if session.id != session_id {
+            continue;
+        }
+        storage
+            .update_refresh_session(&hash, |session| session.revoked_at = Some(now))
+            .await;
+        revoked = Some(session);
+    }
+    revoked.ok_or_else(|| ApiError::NotFound("session not found".to_string()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/sessions",
+    operation_id = "list_sessions",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Active sessions of the caller in the current workspace, newest first", body = SessionListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn list_sessions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<SessionListResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let items = active_sessions(&state.storage, &context)
+        .await
+        .into_iter()
+        .map(|(_, session)| session)
+        .collect();
+    Ok(Json(SessionListResponse { items }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/sessions/{id}",
+    operation_id = "revoke_session",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn revoke_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let session = revoke(&state.storage, &context, session_id).await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "SESSION_REVOKED",
+            "session",
+            Some(session.id.to_string()),
+            json!({
+                "ip": session.ip,
+                "user_agent": session.user_agent,
+                "new_device": session.new_device,
+            }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::WorkspaceRole,
+        storage::{PersistenceBackend, RefreshSessionRecordStore},
+    };
+
+    #[tokio::test]
+    async fn unseen_devices_are_flagged_and_sessions_revocable() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let context = AuthContext {
+            user_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            role: WorkspaceRole::Member,
+        };
+        let user_id = context.user_id;
+
+        assert!(!remember_device(&storage, user_id, "10.0.0.1", "Firefox", 1).await);
+        assert!(!remember_device(&storage, user_id, "10.0.0.1", "Firefox", 2).await);
+        assert!(remember_device(&storage, user_id, "10.0.0.1", "curl", 3).await);
+        assert!(!remember_device(&storage, Uuid::new_v4(), "10.0.0.1", "curl", 3).await);
+        let known = storage.list_known_devices(user_id).await;
+        assert_eq!(known.len(), 2);
+        let firefox = known
+            .iter()
+            .find(|device| device.user_agent == "Firefox")
+            .expect("firefox device");
+        assert_eq!((firefox.first_seen_at, firefox.last_seen_at), (1, 2));
+
+        let session_id = Uuid::new_v4();
+        for (hash, session_id, created_at) in
+            [("old", session_id, 10), ("kept", Uuid::new_v4(), 20)]
+        {
+            storage
+                .put_refresh_session(
+                    hash.to_string(),
+                    RefreshSessionRecordStore {
+                        session_id,
+                        user_id,
+                        workspace_id: context.workspace_id,
+                        created_at,
+                        expires_at: 4_000_000_000,
+                        revoked_at: None,
+                        replaced_by_hash: None,
+                        ip: Some("10.0.0.1".to_string()),
+                        user_agent: Some("curl".to_string()),
+                        new_device: true,
+                    },
+                )
+                .await;
+        }
+        let sessions = active_sessions(&storage, &context).await;
+        assert_eq!(sessions[0].0, "kept");
+
+        let revoked = revoke(&storage, &context, session_id)
+            .await
+            .expect("revoked");
+        assert!(revoked.new_device);
+        let old = storage.get_refresh_session("old").await.expect("session");
+        assert!(old.revoked_at.is_some());
+        assert_eq!(active_sessions(&storage, &context).await.len(), 1);
+        assert!(matches!(
+            revoke(&storage, &context, session_id).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
@@ -4,20 +4,33 @@ use std::{
 };
 
 use mongodb::{
-    Client, Collection, IndexModel,
+    Client, Collection, Database, IndexModel,
     bson::{Bson, DateTime as BsonDateTime, Document, doc, from_bson, to_bson},
-    options::IndexOptions,
+    options::{IndexOptions, ReturnDocument},
 };
+use serde::Serialize;
 use serde_json::Value;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::encryption::{self, DataKey, MessageEncryption};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PersistenceBackend {
     Memory,
     Mongo,
 }
 
+impl PersistenceBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Memory => "memory",
+            Self::Mongo => "mongo",
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum StorageInitError {
     #[error("mongo backend requires MONGO_URI")]
@@ -26,12 +39,97 @@ pub enum StorageInitError {
     MongoInit(#[from] mongodb::error::Error),
 }
 
+/// Encryption at rest is configured but a body could not be sealed with its
+/// workspace data key; the write is refused rather than stored in plaintext.
+#[derive(Debug, thiserror::Error)]
+#[error("failed to seal data for workspace {workspace_id}")]
+pub struct SealError {
+    pub workspace_id: Uuid,
+}
+
+/// A write that has to land together with its realtime event failed; nothing
+/// was stored.
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    #[error(transparent)]
+    Seal(#[from] SealError),
+    #[error("storage write failed: {0}")]
+    Mongo(#[from] mongodb::error::Error),
+}
+
 type WsDedupKey = (Uuid, Uuid, Uuid, String);
+/// `(channel_id, key_epoch, user_id)`.
+type ChannelKeyId = (Uuid, i64, Uuid);
+/// `(workspace_id, user_id, day)`.
+type ApiUsageKey = (Uuid, Uuid, String);
+
+/// Devices unused this long are forgotten, so the next login alerts again.
+const KNOWN_DEVICE_TTL_SECS: u64 = 180 * 24 * 60 * 60;
+const IDEMPOTENCY_KEY_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Ordered, append-only list of data migrations. Applied ids are recorded in
+/// `schema_migrations`, so each one runs at most once per database.
+const SCHEMA_MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "0001_workspaces_name_lc",
+        "backfill workspaces.name_lc used by the name index",
+    ),
+    (
+        "0002_refresh_sessions_expires_at_dt",
+        "backfill refresh_sessions.expires_at_dt so the TTL index can expire them",
+    ),
+    (
+        "0003_messages_seq",
+        "number existing messages per channel and seed channel_sequences",
+    ),
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub indexes_ensured: bool,
+    pub applied: Vec<AppliedMigration>,
+    pub already_applied: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaPlan {
+    pub missing_indexes: Vec<String>,
+    pub pending_migrations: Vec<String>,
+}
+
+/// Size, index and purge-backlog figures of one collection.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CollectionStats {
+    pub name: String,
+    pub documents: u64,
+    /// Uncompressed data size as reported by Mongo; `None` on the memory
+    /// backend.
+    pub size_bytes: Option<u64>,
+    pub storage_bytes: Option<u64>,
+    pub index_bytes: Option<u64>,
+    pub indexes: Vec<String>,
+    /// Indexes the schema expects that the collection does not have.
+    pub missing_indexes: Vec<String>,
+    /// Documents already past their expiry (TTL, expired uploads, delivered
+    /// outbox events) that are still stored; `None` when nothing in the
+    /// collection expires.
+    pub pending_purge: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedMigration {
+    pub id: String,
+    pub description: String,
+    pub modified: u64,
+}
 
 #[derive(Clone)]
 pub struct Storage {
     backend: PersistenceBackend,
     mongo: Option<MongoState>,
+    encryption: Option<Arc<MessageEncryption>>,
+    data_keys: Arc<RwLock<HashMap<Uuid, Arc<DataKey>>>>,
+    workspace_keys: Arc<RwLock<HashMap<Uuid, String>>>,
     workspaces: Arc<RwLock<HashMap<Uuid, WorkspaceRecordStore>>>,
     audit_entries: Arc<RwLock<Vec<AuditEntryRecord>>>,
     pending_uploads: Arc<RwLock<HashMap<Uuid, PendingUploadRecord>>>,
@@ -39,17 +137,49 @@ pub struct Storage {
     reactions: Arc<RwLock<HashSet<(Uuid, String, Uuid)>>>,
     channels: Arc<RwLock<HashMap<Uuid, ChannelRecordStore>>>,
     channel_members: Arc<RwLock<HashSet<(Uuid, Uuid)>>>,
+    channel_admins: Arc<RwLock<HashSet<(Uuid, Uuid)>>>,
     messages: Arc<RwLock<HashMap<Uuid, MessageRecordStore>>>,
+    dm_conversations: Arc<RwLock<HashMap<Uuid, DmConversationRecordStore>>>,
+    dm_messages: Arc<RwLock<HashMap<Uuid, DmMessageRecordStore>>>,
+    channel_sequences: Arc<RwLock<HashMap<Uuid, i64>>>,
     auth_users: Arc<RwLock<HashMap<Uuid, AuthUserRecordStore>>>,
     auth_users_by_email: Arc<RwLock<HashMap<String, Uuid>>>,
     auth_memberships: Arc<RwLock<HashMap<(Uuid, Uuid), String>>>,
     refresh_sessions: Arc<RwLock<HashMap<String, RefreshSessionRecordStore>>>,
+    known_devices: Arc<RwLock<HashMap<(Uuid, String), KnownDeviceRecordStore>>>,
+    email_tokens: Arc<RwLock<HashMap<String, EmailTokenRecordStore>>>,
     ws_command_dedup: Arc<RwLock<HashMap<WsDedupKey, Uuid>>>,
     ws_command_once: Arc<RwLock<HashSet<String>>>,
+    idempotency_keys: Arc<RwLock<HashMap<String, IdempotencyRecordStore>>>,
+    notifications: Arc<RwLock<HashMap<Uuid, NotificationRecordStore>>>,
+    keyword_subscriptions: Arc<RwLock<HashMap<Uuid, KeywordSubscriptionRecordStore>>>,
+    announcements: Arc<RwLock<HashMap<Uuid, AnnouncementRecordStore>>>,
+    channel_links: Arc<RwLock<HashMap<Uuid, ChannelLinkRecordStore>>>,
+    legal_holds: Arc<RwLock<HashMap<Uuid, LegalHoldRecordStore>>>,
+    user_mutes: Arc<RwLock<HashMap<(Uuid, Uuid), UserMuteRecordStore>>>,
+    moderation_cases: Arc<RwLock<HashMap<Uuid, ModerationCaseRecordStore>>>,
+    channel_access_requests: Arc<RwLock<HashMap<Uuid, ChannelAccessRequestRecordStore>>>,
+    read_markers: Arc<RwLock<HashMap<(Uuid, Uuid), ReadMarkerRecordStore>>>,
+    daily_activity: Arc<RwLock<HashMap<(Uuid, String), DailyActivityRecordStore>>>,
+    api_usage: Arc<RwLock<HashMap<ApiUsageKey, ApiUsageRecordStore>>>,
+    reminders: Arc<RwLock<HashMap<Uuid, ReminderRecordStore>>>,
+    recurring_posts: Arc<RwLock<HashMap<Uuid, RecurringPostRecordStore>>>,
+    user_groups: Arc<RwLock<HashMap<Uuid, UserGroupRecordStore>>>,
+    message_limits: Arc<RwLock<HashMap<Uuid, MessageLimitsRecordStore>>>,
+    calls: Arc<RwLock<HashMap<Uuid, CallRecordStore>>>,
+    channel_keys: Arc<RwLock<HashMap<ChannelKeyId, ChannelKeyRecordStore>>>,
+    user_public_keys: Arc<RwLock<HashMap<Uuid, UserPublicKeyRecordStore>>>,
+    ip_allowlists: Arc<RwLock<HashMap<Uuid, IpAllowlistRecordStore>>>,
+    session_limits: Arc<RwLock<HashMap<Uuid, SessionLimitsRecordStore>>>,
+    event_subscriptions: Arc<RwLock<HashMap<Uuid, EventSubscriptionRecordStore>>>,
+    app_installations: Arc<RwLock<HashMap<Uuid, AppInstallationRecordStore>>>,
+    realtime_outbox: Arc<RwLock<HashMap<Uuid, OutboxEventRecordStore>>>,
+    outbox_signal: Arc<Notify>,
 }
 
 #[derive(Clone)]
 struct MongoState {
+    database: Database,
     workspaces: Collection<Document>,
     audit_entries: Collection<Document>,
     pending_uploads: Collection<Document>,
@@ -57,12 +187,43 @@ struct MongoState {
     reactions: Collection<Document>,
     channels: Collection<Document>,
     channel_members: Collection<Document>,
+    channel_admins: Collection<Document>,
     messages: Collection<Document>,
+    dm_conversations: Collection<Document>,
+    dm_messages: Collection<Document>,
+    channel_sequences: Collection<Document>,
     auth_users: Collection<Document>,
     auth_memberships: Collection<Document>,
     refresh_sessions: Collection<Document>,
+    known_devices: Collection<Document>,
+    email_tokens: Collection<Document>,
     ws_command_dedup: Collection<Document>,
     ws_command_once: Collection<Document>,
+    idempotency_keys: Collection<Document>,
+    notifications: Collection<Document>,
+    keyword_subscriptions: Collection<Document>,
+    announcements: Collection<Document>,
+    channel_links: Collection<Document>,
+    legal_holds: Collection<Document>,
+    user_mutes: Collection<Document>,
+    moderation_cases: Collection<Document>,
+    channel_access_requests: Collection<Document>,
+    read_markers: Collection<Document>,
+    daily_activity: Collection<Document>,
+    api_usage: Collection<Document>,
+    reminders: Collection<Document>,
+    recurring_posts: Collection<Document>,
+    user_groups: Collection<Document>,
+    message_limits: Collection<Document>,
+    calls: Collection<Document>,
+    workspace_keys: Collection<Document>,
+    channel_keys: Collection<Document>,
+    user_public_keys: Collection<Document>,
+    ip_allowlists: Collection<Document>,
+    session_limits: Collection<Document>,
+    event_subscriptions: Collection<Document>,
+    app_installations: Collection<Document>,
+    realtime_outbox: Collection<Document>,
 }
 
 #[derive(Debug, Clone)]
@@ -71,6 +232,8 @@ pub struct WorkspaceRecordStore {
     pub name: String,
     pub created_by: Uuid,
     pub created_at: i64,
+    pub suspended_at: Option<i64>,
+    pub suspended_reason: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -94,6 +257,7 @@ pub struct PendingUploadRecord {
     pub content_type: String,
     pub size_bytes: u64,
     pub storage_key: String,
+    pub thumbnail_key: Option<String>,
     pub expires_at: i64,
     pub created_at: i64,
 }
@@ -110,6 +274,7 @@ pub struct AttachmentRecordStore {
     pub size_bytes: u64,
     pub bucket: String,
     pub key: String,
+    pub thumbnail_key: Option<String>,
     pub region: String,
     pub created_at: i64,
 }
@@ -120,6 +285,13 @@ pub struct ChannelRecordStore {
     pub workspace_id: Uuid,
     pub name: String,
     pub is_private: bool,
+    pub topic: Option<String>,
+    pub icon_emoji: Option<String>,
+    pub color: Option<String>,
+    /// Bodies are client-side ciphertext the server cannot read.
+    pub e2ee: bool,
+    /// Bumped on every membership change of an `e2ee` channel.
+    pub key_epoch: i64,
     pub created_by: Uuid,
     pub created_at: i64,
 }
@@ -132,9 +304,50 @@ pub struct MessageRecordStore {
     pub sender_id: Uuid,
     pub body_md: String,
     pub thread_root_id: Option<Uuid>,
+    /// Sender's workspace when it differs from `workspace_id` (shared channels).
+    pub origin_workspace_id: Option<Uuid>,
+    /// Per-channel position assigned by `next_channel_seq`, starting at 1.
+    pub seq: i64,
     pub created_at: i64,
     pub edited_at: Option<i64>,
     pub deleted_at: Option<i64>,
+    /// Shared by the copies of a message cross-posted to several channels.
+    pub crosspost_group_id: Option<Uuid>,
+}
+
+/// Filters of a message search. `terms` are lowercase and must all appear
+/// in the body; results are newest first, at most `limit`.
+#[derive(Debug, Clone)]
+pub struct MessageSearch {
+    pub workspace_ids: Vec<Uuid>,
+    pub channel_ids: Vec<Uuid>,
+    pub sender_id: Option<Uuid>,
+    pub terms: Vec<String>,
+    /// Inclusive lower bound on `created_at` (unix millis).
+    pub from: Option<i64>,
+    /// Exclusive upper bound on `created_at` (unix millis).
+    pub to: Option<i64>,
+    /// `(created_at, id)` of the last result of the previous page.
+    pub before: Option<(i64, u128)>,
+    pub limit: usize,
+}
+
+impl MessageSearch {
+    fn matches(&self, message: &MessageRecordStore) -> bool {
+        if message.deleted_at.is_some()
+            || !self.channel_ids.contains(&message.channel_id)
+            || self.sender_id.is_some_and(|id| id != message.sender_id)
+            || self.from.is_some_and(|from| message.created_at < from)
+            || self.to.is_some_and(|to| message.created_at >= to)
+            || self
+                .before
+                .is_some_and(|before| (message.created_at, message.id.as_u128()) >= before)
+        {
+            return false;
+        }
+        let body = message.body_md.to_lowercase();
+        self.terms.iter().all(|term| body.contains(term.as_str()))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -143,21 +356,420 @@ pub struct AuthUserRecordStore {
     pub email: String,
     pub name: String,
     pub password_hash: String,
+    /// When the user confirmed `email` through a verification link.
+    pub email_verified_at: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub actor_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+    pub message_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: i64,
+    pub read_at: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeywordSubscriptionRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub keyword: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnouncementRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub author_id: Uuid,
+    pub title: String,
+    pub body_md: String,
+    pub severity: String,
+    pub pinned: bool,
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+/// Connects a channel owned by `host_workspace_id` to `guest_workspace_id`;
+/// active once both workspace owners have approved.
+#[derive(Debug, Clone)]
+pub struct ChannelLinkRecordStore {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub host_workspace_id: Uuid,
+    pub guest_workspace_id: Uuid,
+    pub host_approved_by: Uuid,
+    pub guest_approved_by: Option<Uuid>,
+    pub created_at: i64,
+    pub approved_at: Option<i64>,
+}
+
+impl ChannelLinkRecordStore {
+    pub fn is_active(&self) -> bool {
+        self.guest_approved_by.is_some()
+    }
+}
+
+/// Freezes deletion of the content covered by `scope` (`workspace`, `channel`
+/// or `user`) until the hold is released.
+#[derive(Debug, Clone)]
+pub struct LegalHoldRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub scope: String,
+    pub target_id: Uuid,
+    pub reason: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+}
+
+/// Incremental per-workspace counters for one UTC day (`YYYY-MM-DD`).
+#[derive(Debug, Clone, Default)]
+pub struct DailyActivityRecordStore {
+    pub workspace_id: Uuid,
+    pub day: String,
+    pub messages: i64,
+    pub active_users: HashSet<Uuid>,
+    pub channel_messages: HashMap<Uuid, i64>,
+    pub peak_ws_connections: i64,
+}
+
+/// Workspace-wide write ban of one member until `expires_at` (ms).
+#[derive(Debug, Clone)]
+pub struct UserMuteRecordStore {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub reason: Option<String>,
+    pub muted_by: Uuid,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+/// Reported or flagged message awaiting (or past) an admin decision.
+#[derive(Debug, Clone)]
+pub struct ModerationCaseRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub message_id: Uuid,
+    pub channel_id: Uuid,
+    pub author_id: Uuid,
+    pub source: String,
+    pub category: String,
+    pub details: Option<String>,
+    pub reporter_id: Option<Uuid>,
+    pub status: String,
+    pub created_at: i64,
+    pub resolution: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<i64>,
+}
+
+/// A member's request to join a private channel, decided by a channel manager.
+#[derive(Debug, Clone)]
+pub struct ChannelAccessRequestRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    pub note: Option<String>,
+    pub status: String,
+    pub created_at: i64,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<i64>,
+}
+
+/// Authenticated API calls of one user in one workspace for one UTC day.
+#[derive(Debug, Clone, Default)]
+pub struct ApiUsageRecordStore {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub day: String,
+    pub requests: i64,
+    pub rate_limited: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+}
+
+/// Named set of workspace members mentioned together as `@handle`.
+#[derive(Debug, Clone)]
+pub struct UserGroupRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub handle: String,
+    pub name: String,
+    pub member_ids: Vec<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Per-workspace overrides of the message limits, one document per workspace.
+#[derive(Debug, Clone)]
+pub struct MessageLimitsRecordStore {
+    pub workspace_id: Uuid,
+    pub max_message_chars: i64,
+    pub max_attachments_per_message: i64,
+    pub max_thread_depth: i64,
+    pub updated_by: Uuid,
+    pub updated_at: i64,
+}
+
+/// CIDR ranges allowed to authenticate into a workspace; no document (or an
+/// empty list) means any address.
+#[derive(Debug, Clone)]
+pub struct IpAllowlistRecordStore {
+    pub workspace_id: Uuid,
+    pub cidrs: Vec<String>,
+    pub updated_by: Uuid,
+    pub updated_at: i64,
+}
+
+/// Per-user caps on concurrent sessions in a workspace; `None` is unlimited.
+#[derive(Debug, Clone)]
+pub struct SessionLimitsRecordStore {
+    pub workspace_id: Uuid,
+    pub max_refresh_sessions: Option<i64>,
+    pub max_ws_connections: Option<i64>,
+    pub updated_by: Uuid,
+    pub updated_at: i64,
+}
+
+/// Huddle in a channel; at most one per channel has no `ended_at`.
+#[derive(Debug, Clone)]
+pub struct CallRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub started_by: Uuid,
+    pub join_url: Option<String>,
+    pub participant_ids: Vec<Uuid>,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+}
+
+/// A channel key for one `e2ee` channel epoch, wrapped by a member for `user_id`.
+#[derive(Debug, Clone)]
+pub struct ChannelKeyRecordStore {
+    pub channel_id: Uuid,
+    pub key_epoch: i64,
+    pub user_id: Uuid,
+    pub wrapped_key: String,
+    pub wrapped_by: Uuid,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserPublicKeyRecordStore {
+    pub user_id: Uuid,
+    pub public_key: String,
+    pub updated_at: i64,
+}
+
+/// Events a bot or app asked to have pushed to it; `delivery` is `websocket`
+/// or `webhook`.
+#[derive(Debug, Clone)]
+pub struct EventSubscriptionRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub event_types: Vec<String>,
+    pub channel_ids: Vec<Uuid>,
+    pub delivery: String,
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub created_at: i64,
+}
+
+/// Third-party app installed in a workspace from a manifest, acting through
+/// its provisioned bot user.
+#[derive(Debug, Clone)]
+pub struct AppInstallationRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub scopes: Vec<String>,
+    pub webhook_url: Option<String>,
+    pub event_subscription_id: Option<Uuid>,
+    pub slash_commands: Vec<SlashCommandRecordStore>,
+    pub bot_user_id: Uuid,
+    pub installed_by: Uuid,
+    pub installed_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlashCommandRecordStore {
+    pub command: String,
+    pub description: String,
+}
+
+/// Realtime event persisted together with the write that caused it; pending
+/// until a dispatcher publishes it and sets `delivered_at`.
+#[derive(Debug, Clone)]
+pub struct OutboxEventRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Serialized `WsEventEnvelope`.
+    pub event_json: String,
+    pub created_at: i64,
+    /// Unix millis until which a dispatcher holds the event.
+    pub claimed_until: i64,
+    pub delivered_at: Option<i64>,
+}
+
+/// Message posted to a channel on a cron schedule on behalf of `created_by`.
+#[derive(Debug, Clone)]
+pub struct RecurringPostRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub channel_id: Uuid,
+    pub body_md: String,
+    /// Five-field cron expression, evaluated in UTC.
+    pub schedule: String,
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub paused: bool,
+    pub next_run_at: i64,
+    pub last_run_at: Option<i64>,
+    pub last_message_id: Option<Uuid>,
+    /// Why the last run did not post, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// Pending "remind me later" for one message; removed once delivered.
+#[derive(Debug, Clone)]
+pub struct ReminderRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub channel_id: Uuid,
+    pub message_id: Uuid,
+    pub remind_at: i64,
+    pub created_at: i64,
+}
+
+/// Last message a user has read in a channel, keyed by `(user_id, channel_id)`.
+#[derive(Debug, Clone)]
+pub struct ReadMarkerRecordStore {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub channel_id: Uuid,
+    pub last_read_seq: i64,
+    pub updated_at: i64,
+}
+
+/// Single-use token mailed to a user, keyed by the SHA-256 of its value;
+/// `purpose` is `password_reset` or `email_verification`.
+#[derive(Debug, Clone)]
+pub struct EmailTokenRecordStore {
+    pub purpose: String,
+    pub user_id: Uuid,
+    pub email: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// SHA-256 of the request body the key was first used with.
+    pub request_hash: String,
+    /// `None` while the first request is still being processed.
+    pub status: Option<u16>,
+    pub body: Value,
+    pub created_at: i64,
 }
 
 #[derive(Debug, Clone)]
 pub struct RefreshSessionRecordStore {
+    /// Stable id of the login; every rotated token of the session shares it.
+    pub session_id: Uuid,
     pub user_id: Uuid,
     pub workspace_id: Uuid,
+    /// Login time (unix seconds) of the session; kept across rotations.
+    pub created_at: i64,
     pub expires_at: i64,
     pub revoked_at: Option<i64>,
     pub replaced_by_hash: Option<String>,
+    /// Client address and user agent the session logged in from.
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Set when the login came from an IP/user-agent pair not seen before.
+    pub new_device: bool,
+}
+
+/// IP/user-agent pair a user has logged in from, keyed by its fingerprint.
+#[derive(Debug, Clone)]
+pub struct KnownDeviceRecordStore {
+    pub user_id: Uuid,
+    pub fingerprint: String,
+    pub ip: String,
+    pub user_agent: String,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+}
+
+/// Direct conversation between a fixed set of workspace members.
+#[derive(Debug, Clone)]
+pub struct DmConversationRecordStore {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Participants, sorted; includes the creator.
+    pub member_ids: Vec<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+    pub last_message_at: Option<i64>,
+}
+
+impl DmConversationRecordStore {
+    /// Identifies the member set, so one conversation exists per set.
+    pub fn member_key(&self) -> String {
+        self.member_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DmMessageRecordStore {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub workspace_id: Uuid,
+    pub sender_id: Uuid,
+    pub body_md: String,
+    pub created_at: i64,
 }
 
 impl Storage {
     pub async fn new(
         backend: PersistenceBackend,
         mongo_uri: Option<&str>,
+    ) -> Result<Self, StorageInitError> {
+        Self::connect(backend, mongo_uri, true).await
+    }
+
+    /// Connects without creating indexes, for callers that must not write
+    /// (e.g. `galynx-bootstrap --dry-run`).
+    pub async fn new_without_indexes(
+        backend: PersistenceBackend,
+        mongo_uri: Option<&str>,
+    ) -> Result<Self, StorageInitError> {
+        Self::connect(backend, mongo_uri, false).await
+    }
+
+    async fn connect(
+        backend: PersistenceBackend,
+        mongo_uri: Option<&str>,
+        ensure_indexes: bool,
     ) -> Result<Self, StorageInitError> {
         let mongo = if matches!(backend, PersistenceBackend::Mongo) {
             let uri = mongo_uri
@@ -167,6 +779,7 @@ impl Storage {
             let client = Client::with_uri_str(uri).await?;
             let database = client.database("galynx");
             let state = MongoState {
+                database: database.clone(),
                 workspaces: database.collection::<Document>("workspaces"),
                 audit_entries: database.collection::<Document>("audit_log"),
                 pending_uploads: database.collection::<Document>("pending_uploads"),
@@ -174,14 +787,47 @@ impl Storage {
                 reactions: database.collection::<Document>("reactions"),
                 channels: database.collection::<Document>("channels"),
                 channel_members: database.collection::<Document>("channel_members"),
+                channel_admins: database.collection::<Document>("channel_admins"),
                 messages: database.collection::<Document>("messages"),
+                dm_conversations: database.collection::<Document>("dm_conversations"),
+                dm_messages: database.collection::<Document>("dm_messages"),
+                channel_sequences: database.collection::<Document>("channel_sequences"),
                 auth_users: database.collection::<Document>("auth_users"),
                 auth_memberships: database.collection::<Document>("auth_memberships"),
                 refresh_sessions: database.collection::<Document>("refresh_sessions"),
+                known_devices: database.collection::<Document>("known_devices"),
+                email_tokens: database.collection::<Document>("email_tokens"),
                 ws_command_dedup: database.collection::<Document>("ws_command_dedup"),
                 ws_command_once: database.collection::<Document>("ws_command_once"),
+                idempotency_keys: database.collection::<Document>("idempotency_keys"),
+                notifications: database.collection::<Document>("notifications"),
+                keyword_subscriptions: database.collection::<Document>("keyword_subscriptions"),
+                announcements: database.collection::<Document>("announcements"),
+                channel_links: database.collection::<Document>("channel_links"),
+                legal_holds: database.collection::<Document>("legal_holds"),
+                user_mutes: database.collection::<Document>("user_mutes"),
+                moderation_cases: database.collection::<Document>("moderation_cases"),
+                channel_access_requests: database.collection::<Document>("channel_access_requests"),
+                read_markers: database.collection::<Document>("read_markers"),
+                daily_activity: database.collection::<Document>("daily_activity"),
+                api_usage: database.collection::<Document>("api_usage"),
+                reminders: database.collection::<Document>("reminders"),
+                recurring_posts: database.collection::<Document>("recurring_posts"),
+                user_groups: database.collection::<Document>("user_groups"),
+                message_limits: database.collection::<Document>("message_limits"),
+                calls: database.collection::<Document>("calls"),
+                workspace_keys: database.collection::<Document>("workspace_keys"),
+                channel_keys: database.collection::<Document>("channel_keys"),
+                user_public_keys: database.collection::<Document>("user_public_keys"),
+                ip_allowlists: database.collection::<Document>("ip_allowlists"),
+                session_limits: database.collection::<Document>("session_limits"),
+                event_subscriptions: database.collection::<Document>("event_subscriptions"),
+                app_installations: database.collection::<Document>("app_installations"),
+                realtime_outbox: database.collection::<Document>("realtime_outbox"),
             };
-            ensure_mongo_indexes(&state).await?;
+            if ensure_indexes {
+                ensure_mongo_indexes(&state).await?;
+            }
             Some(state)
         } else {
             None
@@ -190,6 +836,9 @@ impl Storage {
         Ok(Self {
             backend,
             mongo,
+            encryption: None,
+            data_keys: Arc::new(RwLock::new(HashMap::new())),
+            workspace_keys: Arc::new(RwLock::new(HashMap::new())),
             workspaces: Arc::new(RwLock::new(HashMap::new())),
             audit_entries: Arc::new(RwLock::new(Vec::new())),
             pending_uploads: Arc::new(RwLock::new(HashMap::new())),
@@ -197,13 +846,44 @@ impl Storage {
             reactions: Arc::new(RwLock::new(HashSet::new())),
             channels: Arc::new(RwLock::new(HashMap::new())),
             channel_members: Arc::new(RwLock::new(HashSet::new())),
+            channel_admins: Arc::new(RwLock::new(HashSet::new())),
             messages: Arc::new(RwLock::new(HashMap::new())),
+            dm_conversations: Arc::new(RwLock::new(HashMap::new())),
+            dm_messages: Arc::new(RwLock::new(HashMap::new())),
+            channel_sequences: Arc::new(RwLock::new(HashMap::new())),
             auth_users: Arc::new(RwLock::new(HashMap::new())),
             auth_users_by_email: Arc::new(RwLock::new(HashMap::new())),
             auth_memberships: Arc::new(RwLock::new(HashMap::new())),
             refresh_sessions: Arc::new(RwLock::new(HashMap::new())),
+            known_devices: Arc::new(RwLock::new(HashMap::new())),
+            email_tokens: Arc::new(RwLock::new(HashMap::new())),
             ws_command_dedup: Arc::new(RwLock::new(HashMap::new())),
             ws_command_once: Arc::new(RwLock::new(HashSet::new())),
+            idempotency_keys: Arc::new(RwLock::new(HashMap::new())),
+            notifications: Arc::new(RwLock::new(HashMap::new())),
+            keyword_subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            announcements: Arc::new(RwLock::new(HashMap::new())),
+            channel_links: Arc::new(RwLock::new(HashMap::new())),
+            legal_holds: Arc::new(RwLock::new(HashMap::new())),
+            user_mutes: Arc::new(RwLock::new(HashMap::new())),
+            moderation_cases: Arc::new(RwLock::new(HashMap::new())),
+            channel_access_requests: Arc::new(RwLock::new(HashMap::new())),
+            read_markers: Arc::new(RwLock::new(HashMap::new())),
+            daily_activity: Arc::new(RwLock::new(HashMap::new())),
+            api_usage: Arc::new(RwLock::new(HashMap::new())),
+            reminders: Arc::new(RwLock::new(HashMap::new())),
+            recurring_posts: Arc::new(RwLock::new(HashMap::new())),
+            user_groups: Arc::new(RwLock::new(HashMap::new())),
+            message_limits: Arc::new(RwLock::new(HashMap::new())),
+            calls: Arc::new(RwLock::new(HashMap::new())),
+            channel_keys: Arc::new(RwLock::new(HashMap::new())),
+            user_public_keys: Arc::new(RwLock::new(HashMap::new())),
+            ip_allowlists: Arc::new(RwLock::new(HashMap::new())),
+            session_limits: Arc::new(RwLock::new(HashMap::new())),
+            event_subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            app_installations: Arc::new(RwLock::new(HashMap::new())),
+            realtime_outbox: Arc::new(RwLock::new(HashMap::new())),
+            outbox_signal: Arc::new(Notify::new()),
         })
     }
 
@@ -211,46 +891,505 @@ impl Storage {
         self.backend
     }
 
-    pub async fn put_workspace(&self, workspace: WorkspaceRecordStore) {
-        self.workspaces
-            .write()
-            .await
-            .insert(workspace.id, workspace.clone());
-        if let Some(mongo) = &self.mongo {
-            let name_lc = workspace.name.to_ascii_lowercase();
-            let document = doc! {
-                "_id": workspace.id.to_string(),
-                "name": workspace.name,
-                "name_lc": name_lc,
-                "created_by": workspace.created_by.to_string(),
-                "created_at": workspace.created_at,
-            };
-            let _ = mongo
-                .workspaces
-                .delete_one(doc! { "_id": workspace.id.to_string() })
-                .await;
-            let _ = mongo.workspaces.insert_one(document).await;
+    /// Ensures indexes and applies pending data migrations. A no-op for the
+    /// memory backend, which has no persistent schema.
+    pub async fn migrate(&self) -> Result<MigrationReport, mongodb::error::Error> {
+        let mut report = MigrationReport::default();
+        let Some(mongo) = &self.mongo else {
+            return Ok(report);
+        };
+        ensure_mongo_indexes(mongo).await?;
+        report.indexes_ensured = true;
+
+        let applied = mongo.database.collection::<Document>("schema_migrations");
+        for (id, description) in SCHEMA_MIGRATIONS {
+            if applied.find_one(doc! { "_id": *id }).await?.is_some() {
+                report.already_applied.push(id.to_string());
+                continue;
+            }
+            let modified = apply_migration(mongo, id).await?;
+            applied
+                .insert_one(doc! {
+                    "_id": *id,
+                    "description": *description,
+                    "modified": modified as i64,
+                    "applied_at": chrono::Utc::now().timestamp_millis(),
+                })
+                .await?;
+            report.applied.push(AppliedMigration {
+                id: id.to_string(),
+                description: description.to_string(),
+                modified,
+            });
         }
+        Ok(report)
     }
 
-    pub async fn get_workspace(&self, workspace_id: Uuid) -> Option<WorkspaceRecordStore> {
-        if let Some(mongo) = &self.mongo {
+    /// Reports missing indexes and pending migrations without writing.
+    pub async fn schema_plan(&self) -> Result<SchemaPlan, mongodb::error::Error> {
+        let mut plan = SchemaPlan::default();
+        let Some(mongo) = &self.mongo else {
+            return Ok(plan);
+        };
+
+        let collections = mongo.database.list_collection_names().await?;
+        let mut existing: HashMap<String, Vec<String>> = HashMap::new();
+        for (collection, model) in mongo_index_models(mongo) {
+            let name = collection.name().to_string();
+            if !existing.contains_key(&name) {
+                let names = if collections.contains(&name) {
+                    collection.list_index_names().await?
+                } else {
+                    Vec::new()
+                };
+                existing.insert(name.clone(), names);
+            }
+            let index_name = mongo_index_name(&model.keys);
+            if !existing[&name].contains(&index_name) {
+                plan.missing_indexes.push(format!("{name}.{index_name}"));
+            }
+        }
+
+        let applied = mongo.database.collection::<Document>("schema_migrations");
+        for (id, _) in SCHEMA_MIGRATIONS {
+            if applied.find_one(doc! { "_id": *id }).await?.is_none() {
+                plan.pending_migrations.push(id.to_string());
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Per-collection statistics, sorted by name. Mongo reports `collStats`
+    /// sizes and index health; the memory backend only counts entries.
+    pub async fn collection_stats(&self) -> Result<Vec<CollectionStats>, mongodb::error::Error> {
+        let Some(mongo) = &self.mongo else {
+            return Ok(self.memory_collection_stats().await);
+        };
+        let now = chrono::Utc::now();
+        let models = mongo_index_models(mongo);
+        let mut purge_filters = HashMap::from([
+            (
+                "pending_uploads".to_string(),
+                doc! { "expires_at": { "$lt": now.timestamp() } },
+            ),
+            (
+                "realtime_outbox".to_string(),
+                doc! { "delivered_at": { "$ne": Bson::Null } },
+            ),
+        ]);
+        for (collection, model) in &models {
+            let expire_after = model
+                .options
+                .as_ref()
+                .and_then(|options| options.expire_after);
+            if let (Some(expire_after), Some((field, _))) = (expire_after, model.keys.iter().next())
+            {
+                let cutoff = now.timestamp_millis() - expire_after.as_millis() as i64;
+                purge_filters.insert(
+                    collection.name().to_string(),
+                    doc! { field: { "$lt": BsonDateTime::from_millis(cutoff) } },
+                );
+            }
+        }
+
+        let existing = mongo.database.list_collection_names().await?;
+        let mut names = existing
+            .iter()
+            .filter(|name| !name.starts_with("system."))
+            .cloned()
+            .chain(
+                models
+                    .iter()
+                    .map(|(collection, _)| collection.name().to_string()),
+            )
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        let mut stats = Vec::with_capacity(names.len());
+        for name in names {
+            let collection = mongo.database.collection::<Document>(&name);
+            let mut entry = CollectionStats {
+                name: name.clone(),
+                ..CollectionStats::default()
+            };
+            if existing.contains(&name) {
+                let coll_stats = mongo
+                    .database
+                    .run_command(doc! { "collStats": &name })
+                    .await?;
+                entry.documents = u64_stat(&coll_stats, "count").unwrap_or_default();
+                entry.size_bytes = u64_stat(&coll_stats, "size");
+                entry.storage_bytes = u64_stat(&coll_stats, "storageSize");
+                entry.index_bytes = u64_stat(&coll_stats, "totalIndexSize");
+                entry.indexes = collection.list_index_names().await?;
+                entry.indexes.sort();
+            }
+            entry.missing_indexes = models
+                .iter()
+                .filter(|(collection, _)| collection.name() == name)
+                .map(|(_, model)| mongo_index_name(&model.keys))
+                .filter(|index| !entry.indexes.contains(index))
+                .collect();
+            if let Some(filter) = purge_filters.remove(&name) {
+                entry.pending_purge = Some(collection.count_documents(filter).await?);
+            }
+            stats.push(entry);
+        }
+        Ok(stats)
+    }
+
+    async fn memory_collection_stats(&self) -> Vec<CollectionStats> {
+        let now = chrono::Utc::now();
+        let (now_secs, now_ms) = (now.timestamp(), now.timestamp_millis());
+        let count = |documents: usize, pending_purge: Option<usize>| {
+            (documents as u64, pending_purge.map(|count| count as u64))
+        };
+        let pending_uploads = self.pending_uploads.read().await;
+        let refresh_sessions = self.refresh_sessions.read().await;
+        let known_devices = self.known_devices.read().await;
+        let email_tokens = self.email_tokens.read().await;
+        let idempotency_keys = self.idempotency_keys.read().await;
+        let user_mutes = self.user_mutes.read().await;
+        let realtime_outbox = self.realtime_outbox.read().await;
+        let entries = [
+            (
+                "workspaces",
+                count(self.workspaces.read().await.len(), None),
+            ),
+            (
+                "audit_log",
+                count(self.audit_entries.read().await.len(), None),
+            ),
+            (
+                "pending_uploads",
+                count(
+                    pending_uploads.len(),
+                    Some(
+                        pending_uploads
+                            .values()
+                            .filter(|upload| upload.expires_at < now_secs)
+                            .count(),
+                    ),
+                ),
+            ),
+            (
+                "attachments",
+                count(self.attachments.read().await.len(), None),
+            ),
+            ("reactions", count(self.reactions.read().await.len(), None)),
+            ("channels", count(self.channels.read().await.len(), None)),
+            (
+                "channel_members",
+                count(self.channel_members.read().await.len(), None),
+            ),
+            (
+                "channel_admins",
+                count(self.channel_admins.read().await.len(), None),
+            ),
+            ("messages", count(self.messages.read().await.len(), None)),
+            (
+                "dm_conversations",
+                count(self.dm_conversations.read().await.len(), None),
+            ),
+            (
+                "dm_messages",
+                count(self.dm_messages.read().await.len(), None),
+            ),
+            (
+                "channel_sequences",
+                count(self.channel_sequences.read().await.len(), None),
+            ),
+            (
+                "auth_users",
+                count(self.auth_users.read().await.len(), None),
+            ),
+            (
+                "auth_memberships",
+                count(self.auth_memberships.read().await.len(), None),
+            ),
+            (
+                "refresh_sessions",
+                count(
+                    refresh_sessions.len(),
+                    Some(
+                        refresh_sessions
+                            .values()
+                            .filter(|session| session.expires_at < now_secs)
+                            .count(),
+                    ),
+                ),
+            ),
+            (
+                "known_devices",
+                count(
+                    known_devices.len(),
+                    Some(
+                        known_devices
+                            .values()
+                            .filter(|device| {
+                                device.last_seen_at < now_secs - KNOWN_DEVICE_TTL_SECS as i64
+                            })
+                            .count(),
+                    ),
+                ),
+            ),
+            (
+                "email_tokens",
+                count(
+                    email_tokens.len(),
+                    Some(
+                        email_tokens
+                            .values()
+                            .filter(|token| token.expires_at < now_secs)
+                            .count(),
+                    ),
+                ),
+            ),
+            (
+                "ws_command_dedup",
+                count(self.ws_command_dedup.read().await.len(), None),
+            ),
+            (
+                "ws_command_once",
+                count(self.ws_command_once.read().await.len(), None),
+            ),
+            (
+                "idempotency_keys",
+                count(
+                    idempotency_keys.len(),
+                    Some(
+                        idempotency_keys
+                            .values()
+                            .filter(|key| {
+                                key.created_at < now_ms - IDEMPOTENCY_KEY_TTL_SECS as i64 * 1000
+                            })
+                            .count(),
+                    ),
+                ),
+            ),
+            (
+                "notifications",
+                count(self.notifications.read().await.len(), None),
+            ),
+            (
+                "keyword_subscriptions",
+                count(self.keyword_subscriptions.read().await.len(), None),
+            ),
+            (
+                "announcements",
+                count(self.announcements.read().await.len(), None),
+            ),
+            (
+                "channel_links",
+                count(self.channel_links.read().await.len(), None),
+            ),
+            (
+                "legal_holds",
+                count(self.legal_holds.read().await.len(), None),
+            ),
+            (
+                "user_mutes",
+                count(
+                    user_mutes.len(),
+                    Some(
+                        user_mutes
+                            .values()
+                            .filter(|mute| mute.expires_at < now_ms)
+                            .count(),
+                    ),
+                ),
+            ),
+            (
+                "moderation_cases",
+                count(self.moderation_cases.read().await.len(), None),
+            ),
+            (
+                "channel_access_requests",
+                count(self.channel_access_requests.read().await.len(), None),
+            ),
+            (
+                "read_markers",
+                count(self.read_markers.read().await.len(), None),
+            ),
+            (
+                "daily_activity",
+                count(self.daily_activity.read().await.len(), None),
+            ),
+            ("api_usage", count(self.api_usage.read().await.len(), None)),
+            ("reminders", count(self.reminders.read().await.len(), None)),
+            (
+                "recurring_posts",
+                count(self.recurring_posts.read().await.len(), None),
+            ),
+            (
+                "user_groups",
+                count(self.user_groups.read().await.len(), None),
+            ),
+            (
+                "message_limits",
+                count(self.message_limits.read().await.len(), None),
+            ),
+            ("calls", count(self.calls.read().await.len(), None)),
+            (
+                "workspace_keys",
+                count(self.workspace_keys.read().await.len(), None),
+            ),
+            (
+                "channel_keys",
+                count(self.channel_keys.read().await.len(), None),
+            ),
+            (
+                "user_public_keys",
+                count(self.user_public_keys.read().await.len(), None),
+            ),
+            (
+                "ip_allowlists",
+                count(self.ip_allowlists.read().await.len(), None),
+            ),
+            (
+                "session_limits",
+                count(self.session_limits.read().await.len(), None),
+            ),
+            (
+                "event_subscriptions",
+                count(self.event_subscriptions.read().await.len(), None),
+            ),
+            (
+                "app_installations",
+                count(self.app_installations.read().await.len(), None),
+            ),
+            (
+                "realtime_outbox",
+                count(
+                    realtime_outbox.len(),
+                    Some(
+                        realtime_outbox
+                            .values()
+                            .filter(|event| event.delivered_at.is_some())
+                            .count(),
+                    ),
+                ),
+            ),
+        ];
+        let mut stats = entries
+            .into_iter()
+            .map(|(name, (documents, pending_purge))| CollectionStats {
+                name: name.to_string(),
+                documents,
+                pending_purge,
+                ..CollectionStats::default()
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
+    pub async fn ping(&self) -> Option<Result<(), String>> {
+        let mongo = self.mongo.as_ref()?;
+        Some(
+            mongo
+                .database
+                .run_command(doc! { "ping": 1 })
+                .await
+                .map(|_| ())
+                .map_err(|error| format!("mongo ping failed: {error}")),
+        )
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "workspaces",
+            operation = "put_workspace",
+        )
+    )]
+    pub async fn put_workspace(&self, workspace: WorkspaceRecordStore) {
+        self.workspaces
+            .write()
+            .await
+            .insert(workspace.id, workspace.clone());
+        if let Some(mongo) = &self.mongo {
+            let name_lc = workspace.name.to_ascii_lowercase();
+            let document = doc! {
+                "_id": workspace.id.to_string(),
+                "name": workspace.name,
+                "name_lc": name_lc,
+                "created_by": workspace.created_by.to_string(),
+                "created_at": workspace.created_at,
+                "suspended_at": workspace.suspended_at,
+                "suspended_reason": workspace.suspended_reason,
+            };
+            let _ = mongo
+                .workspaces
+                .delete_one(doc! { "_id": workspace.id.to_string() })
+                .await;
+            let _ = mongo.workspaces.insert_one(document).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "workspaces",
+            operation = "get_workspace",
+        )
+    )]
+    pub async fn get_workspace(&self, workspace_id: Uuid) -> Option<WorkspaceRecordStore> {
+        if let Some(mongo) = &self.mongo {
             let found = mongo
                 .workspaces
                 .find_one(doc! { "_id": workspace_id.to_string() })
                 .await;
             if let Ok(Some(document)) = found {
-                return Some(WorkspaceRecordStore {
-                    id: uuid_field(&document, "_id")?,
-                    name: string_field(&document, "name").unwrap_or_default(),
-                    created_by: uuid_field(&document, "created_by")?,
-                    created_at: i64_field(&document, "created_at").unwrap_or_default(),
-                });
+                return workspace_from_document(&document);
             }
         }
         self.workspaces.read().await.get(&workspace_id).cloned()
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "workspaces",
+            operation = "list_workspaces",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_workspaces(&self) -> Vec<WorkspaceRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo.workspaces.find(doc! {}).await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                let Some(workspace) = workspace_from_document(&document) else {
+                    continue;
+                };
+                items.push(workspace);
+            }
+            record_count(items.len());
+            return items;
+        }
+
+        let items: Vec<_> = self.workspaces.read().await.values().cloned().collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "audit_log",
+            operation = "append_audit_entry",
+        )
+    )]
     pub async fn append_audit_entry(&self, entry: AuditEntryRecord) {
         self.audit_entries.write().await.push(entry.clone());
         if let Some(mongo) = &self.mongo {
@@ -270,6 +1409,15 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "audit_log",
+            operation = "list_audit_entries",
+            count = tracing::field::Empty,
+        )
+    )]
     pub async fn list_audit_entries(&self, workspace_id: Uuid) -> Vec<AuditEntryRecord> {
         if let Some(mongo) = &self.mongo {
             let filter = doc! { "workspace_id": workspace_id.to_string() };
@@ -311,21 +1459,62 @@ impl Storage {
                         created_at: document.get_i64("created_at").unwrap_or_default(),
                     });
                 }
+                record_count(items.len());
                 return items;
             } else {
                 tracing::warn!("failed to read audit entries from mongo, using memory fallback");
             }
         }
 
-        self.audit_entries
+        let items: Vec<_> = self
+            .audit_entries
             .read()
             .await
             .iter()
             .filter(|entry| entry.workspace_id == workspace_id)
             .cloned()
-            .collect()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "audit_log",
+            operation = "update_audit_metadata",
+        )
+    )]
+    pub async fn update_audit_metadata(&self, entry_id: Uuid, metadata: Value) {
+        if let Some(entry) = self
+            .audit_entries
+            .write()
+            .await
+            .iter_mut()
+            .find(|entry| entry.id == entry_id)
+        {
+            entry.metadata = metadata.clone();
+        }
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .audit_entries
+                .update_one(
+                    doc! { "_id": entry_id.to_string() },
+                    doc! { "$set": { "metadata": to_bson(&metadata).unwrap_or(Bson::Null) } },
+                )
+                .await;
+        }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "pending_uploads",
+            operation = "put_pending_upload",
+        )
+    )]
     pub async fn put_pending_upload(&self, upload_id: Uuid, pending: PendingUploadRecord) {
         self.pending_uploads
             .write()
@@ -341,6 +1530,7 @@ impl Storage {
                 "content_type": pending.content_type,
                 "size_bytes": pending.size_bytes as i64,
                 "storage_key": pending.storage_key,
+                "thumbnail_key": pending.thumbnail_key,
                 "expires_at": pending.expires_at,
                 "created_at": pending.created_at,
             };
@@ -354,6 +1544,14 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "pending_uploads",
+            operation = "take_pending_upload",
+        )
+    )]
     pub async fn take_pending_upload(&self, upload_id: &Uuid) -> Option<PendingUploadRecord> {
         let in_memory = self.pending_uploads.write().await.remove(upload_id);
         if let Some(mongo) = &self.mongo {
@@ -370,6 +1568,7 @@ impl Storage {
                     content_type: string_field(&document, "content_type").unwrap_or_default(),
                     size_bytes: i64_field(&document, "size_bytes").unwrap_or_default() as u64,
                     storage_key: string_field(&document, "storage_key").unwrap_or_default(),
+                    thumbnail_key: string_field(&document, "thumbnail_key"),
                     expires_at: i64_field(&document, "expires_at").unwrap_or_default(),
                     created_at: i64_field(&document, "created_at").unwrap_or_default(),
                 });
@@ -378,6 +1577,14 @@ impl Storage {
         in_memory
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "attachments",
+            operation = "put_attachment",
+        )
+    )]
     pub async fn put_attachment(&self, attachment: AttachmentRecordStore) {
         self.attachments
             .write()
@@ -395,6 +1602,7 @@ impl Storage {
                 "size_bytes": attachment.size_bytes as i64,
                 "bucket": attachment.bucket,
                 "key": attachment.key,
+                "thumbnail_key": attachment.thumbnail_key,
                 "region": attachment.region,
                 "created_at": attachment.created_at,
             };
@@ -408,6 +1616,14 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "attachments",
+            operation = "get_attachment",
+        )
+    )]
     pub async fn get_attachment(&self, attachment_id: &Uuid) -> Option<AttachmentRecordStore> {
         if let Some(mongo) = &self.mongo {
             let found = mongo
@@ -426,6 +1642,7 @@ impl Storage {
                     size_bytes: i64_field(&document, "size_bytes").unwrap_or_default() as u64,
                     bucket: string_field(&document, "bucket").unwrap_or_default(),
                     key: string_field(&document, "key").unwrap_or_default(),
+                    thumbnail_key: string_field(&document, "thumbnail_key"),
                     region: string_field(&document, "region").unwrap_or_default(),
                     created_at: i64_field(&document, "created_at").unwrap_or_default(),
                 });
@@ -434,6 +1651,15 @@ impl Storage {
         self.attachments.read().await.get(attachment_id).cloned()
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "attachments",
+            operation = "list_attachments_for_messages",
+            count = tracing::field::Empty,
+        )
+    )]
     pub async fn list_attachments_for_messages(
         &self,
         workspace_id: Uuid,
@@ -441,6 +1667,7 @@ impl Storage {
     ) -> HashMap<Uuid, Vec<AttachmentRecordStore>> {
         let mut grouped: HashMap<Uuid, Vec<AttachmentRecordStore>> = HashMap::new();
         if message_ids.is_empty() {
+            record_count(grouped.values().map(Vec::len).sum());
             return grouped;
         }
 
@@ -478,6 +1705,7 @@ impl Storage {
                                 as u64,
                             bucket: string_field(&document, "bucket").unwrap_or_default(),
                             key: string_field(&document, "key").unwrap_or_default(),
+                            thumbnail_key: string_field(&document, "thumbnail_key"),
                             region: string_field(&document, "region").unwrap_or_default(),
                             created_at: i64_field(&document, "created_at").unwrap_or_default(),
                         })
@@ -493,6 +1721,7 @@ impl Storage {
                             .then_with(|| a.id.cmp(&b.id))
                     });
                 }
+                record_count(grouped.values().map(Vec::len).sum());
                 return grouped;
             }
         }
@@ -520,9 +1749,140 @@ impl Storage {
                     .then_with(|| a.id.cmp(&b.id))
             });
         }
+        record_count(grouped.values().map(Vec::len).sum());
         grouped
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "attachments",
+            operation = "list_attachments",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_attachments(&self, workspace_id: Uuid) -> Vec<AttachmentRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .attachments
+                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                if let Some(attachment) = attachment_from_document(&document) {
+                    items.push(attachment);
+                }
+            }
+            record_count(items.len());
+            return items;
+        }
+
+        let items: Vec<_> = self
+            .attachments
+            .read()
+            .await
+            .values()
+            .filter(|attachment| attachment.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "attachments",
+            operation = "remove_attachment",
+        )
+    )]
+    pub async fn remove_attachment(&self, attachment_id: &Uuid) {
+        self.attachments.write().await.remove(attachment_id);
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .attachments
+                .delete_one(doc! { "_id": attachment_id.to_string() })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "pending_uploads",
+            operation = "list_pending_uploads",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_pending_uploads(
+        &self,
+        workspace_id: Uuid,
+    ) -> Vec<(Uuid, PendingUploadRecord)> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .pending_uploads
+                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                let Some(upload) = (|| {
+                    Some((
+                        uuid_field(&document, "_id")?,
+                        PendingUploadRecord {
+                            workspace_id,
+                            channel_id: uuid_field(&document, "channel_id")?,
+                            uploader_id: uuid_field(&document, "uploader_id")?,
+                            filename: string_field(&document, "filename").unwrap_or_default(),
+                            content_type: string_field(&document, "content_type")
+                                .unwrap_or_default(),
+                            size_bytes: i64_field(&document, "size_bytes").unwrap_or_default()
+                                as u64,
+                            storage_key: string_field(&document, "storage_key").unwrap_or_default(),
+                            thumbnail_key: string_field(&document, "thumbnail_key"),
+                            expires_at: i64_field(&document, "expires_at").unwrap_or_default(),
+                            created_at: i64_field(&document, "created_at").unwrap_or_default(),
+                        },
+                    ))
+                })() else {
+                    continue;
+                };
+                items.push(upload);
+            }
+            record_count(items.len());
+            return items;
+        }
+
+        let items: Vec<_> = self
+            .pending_uploads
+            .read()
+            .await
+            .iter()
+            .filter(|(_, pending)| pending.workspace_id == workspace_id)
+            .map(|(upload_id, pending)| (*upload_id, pending.clone()))
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reactions",
+            operation = "add_reaction",
+        )
+    )]
     pub async fn add_reaction(&self, message_id: Uuid, emoji: &str, user_id: Uuid) {
         self.reactions
             .write()
@@ -544,6 +1904,14 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reactions",
+            operation = "remove_reaction",
+        )
+    )]
     pub async fn remove_reaction(&self, message_id: Uuid, emoji: &str, user_id: Uuid) {
         self.reactions
             .write()
@@ -557,6 +1925,122 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reactions",
+            operation = "list_message_reactions",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_message_reactions(&self, message_id: Uuid) -> Vec<(String, Uuid)> {
+        if let Some(mongo) = &self.mongo {
+            let mut items = Vec::new();
+            if let Ok(mut cursor) = mongo
+                .reactions
+                .find(doc! { "message_id": message_id.to_string() })
+                .await
+            {
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    if let (Some(emoji), Some(user_id)) = (
+                        string_field(&document, "emoji"),
+                        uuid_field(&document, "user_id"),
+                    ) {
+                        items.push((emoji, user_id));
+                    }
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .reactions
+            .read()
+            .await
+            .iter()
+            .filter(|(msg_id, _, _)| *msg_id == message_id)
+            .map(|(_, emoji, user_id)| (emoji.clone(), *user_id))
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reactions",
+            operation = "list_reactions_for_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_reactions_for_messages(
+        &self,
+        message_ids: &[Uuid],
+    ) -> HashMap<Uuid, Vec<(String, Uuid)>> {
+        let mut grouped: HashMap<Uuid, Vec<(String, Uuid)>> = HashMap::new();
+        if message_ids.is_empty() {
+            record_count(0);
+            return grouped;
+        }
+
+        if let Some(mongo) = &self.mongo {
+            let message_id_values = message_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            if let Ok(mut cursor) = mongo
+                .reactions
+                .find(doc! { "message_id": { "$in": message_id_values } })
+                .await
+            {
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    if let (Some(message_id), Some(emoji), Some(user_id)) = (
+                        uuid_field(&document, "message_id"),
+                        string_field(&document, "emoji"),
+                        uuid_field(&document, "user_id"),
+                    ) {
+                        grouped
+                            .entry(message_id)
+                            .or_default()
+                            .push((emoji, user_id));
+                    }
+                }
+                record_count(grouped.values().map(Vec::len).sum());
+                return grouped;
+            }
+        }
+
+        let wanted = message_ids.iter().copied().collect::<HashSet<_>>();
+        for (message_id, emoji, user_id) in self.reactions.read().await.iter() {
+            if wanted.contains(message_id) {
+                grouped
+                    .entry(*message_id)
+                    .or_default()
+                    .push((emoji.clone(), *user_id));
+            }
+        }
+        record_count(grouped.values().map(Vec::len).sum());
+        grouped
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reactions",
+            operation = "list_reaction_users",
+            count = tracing::field::Empty,
+        )
+    )]
     pub async fn list_reaction_users(&self, message_id: Uuid, emoji: &str) -> Vec<Uuid> {
         if let Some(mongo) = &self.mongo {
             let mut users = Vec::new();
@@ -573,20 +2057,32 @@ impl Storage {
                         users.push(user_id);
                     }
                 }
+                record_count(users.len());
                 return users;
             }
         }
 
-        self.reactions
+        let items: Vec<_> = self
+            .reactions
             .read()
             .await
             .iter()
             .filter_map(|(msg_id, stored_emoji, user_id)| {
                 (*msg_id == message_id && stored_emoji == emoji).then_some(*user_id)
             })
-            .collect()
+            .collect();
+        record_count(items.len());
+        items
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channels",
+            operation = "insert_channel",
+        )
+    )]
     pub async fn insert_channel(&self, channel: ChannelRecordStore) {
         self.channels
             .write()
@@ -598,6 +2094,11 @@ impl Storage {
                 "workspace_id": channel.workspace_id.to_string(),
                 "name": channel.name,
                 "is_private": channel.is_private,
+                "topic": channel.topic,
+                "icon_emoji": channel.icon_emoji,
+                "color": channel.color,
+                "e2ee": channel.e2ee,
+                "key_epoch": channel.key_epoch,
                 "created_by": channel.created_by.to_string(),
                 "created_at": channel.created_at,
             };
@@ -609,6 +2110,15 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channels",
+            operation = "list_channels",
+            count = tracing::field::Empty,
+        )
+    )]
     pub async fn list_channels(&self, workspace_id: Uuid) -> Vec<ChannelRecordStore> {
         if let Some(mongo) = &self.mongo {
             let mut channels = Vec::new();
@@ -630,24 +2140,41 @@ impl Storage {
                             workspace_id,
                             name: string_field(&document, "name").unwrap_or_default(),
                             is_private: bool_field(&document, "is_private").unwrap_or(false),
+                            topic: string_field(&document, "topic"),
+                            icon_emoji: string_field(&document, "icon_emoji"),
+                            color: string_field(&document, "color"),
+                            e2ee: bool_field(&document, "e2ee").unwrap_or(false),
+                            key_epoch: i64_field(&document, "key_epoch").unwrap_or_default(),
                             created_by,
                             created_at: i64_field(&document, "created_at").unwrap_or_default(),
                         });
                     }
                 }
+                record_count(channels.len());
                 return channels;
             }
         }
 
-        self.channels
+        let items: Vec<_> = self
+            .channels
             .read()
             .await
             .values()
             .filter(|channel| channel.workspace_id == workspace_id)
             .cloned()
-            .collect()
+            .collect();
+        record_count(items.len());
+        items
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channels",
+            operation = "get_channel",
+        )
+    )]
     pub async fn get_channel(&self, channel_id: &Uuid) -> Option<ChannelRecordStore> {
         if let Some(mongo) = &self.mongo {
             let found = mongo
@@ -660,6 +2187,11 @@ impl Storage {
                     workspace_id: uuid_field(&document, "workspace_id")?,
                     name: string_field(&document, "name").unwrap_or_default(),
                     is_private: bool_field(&document, "is_private").unwrap_or(false),
+                    topic: string_field(&document, "topic"),
+                    icon_emoji: string_field(&document, "icon_emoji"),
+                    color: string_field(&document, "color"),
+                    e2ee: bool_field(&document, "e2ee").unwrap_or(false),
+                    key_epoch: i64_field(&document, "key_epoch").unwrap_or_default(),
                     created_by: uuid_field(&document, "created_by")?,
                     created_at: i64_field(&document, "created_at").unwrap_or_default(),
                 });
@@ -668,6 +2200,14 @@ impl Storage {
         self.channels.read().await.get(channel_id).cloned()
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channels",
+            operation = "remove_channel",
+        )
+    )]
     pub async fn remove_channel(&self, channel_id: &Uuid) -> Option<ChannelRecordStore> {
         let deleted = self.channels.write().await.remove(channel_id);
         if let Some(mongo) = &self.mongo {
@@ -681,6 +2221,11 @@ impl Storage {
                     workspace_id: uuid_field(&document, "workspace_id")?,
                     name: string_field(&document, "name").unwrap_or_default(),
                     is_private: bool_field(&document, "is_private").unwrap_or(false),
+                    topic: string_field(&document, "topic"),
+                    icon_emoji: string_field(&document, "icon_emoji"),
+                    color: string_field(&document, "color"),
+                    e2ee: bool_field(&document, "e2ee").unwrap_or(false),
+                    key_epoch: i64_field(&document, "key_epoch").unwrap_or_default(),
                     created_by: uuid_field(&document, "created_by")?,
                     created_at: i64_field(&document, "created_at").unwrap_or_default(),
                 });
@@ -689,11 +2234,23 @@ impl Storage {
         deleted
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_members",
+            operation = "add_channel_member",
+        )
+    )]
     pub async fn add_channel_member(&self, channel_id: Uuid, user_id: Uuid) {
-        self.channel_members
+        let added = self
+            .channel_members
             .write()
             .await
             .insert((channel_id, user_id));
+        if added {
+            self.advance_key_epoch(channel_id).await;
+        }
         if let Some(mongo) = &self.mongo {
             let id = format!("{channel_id}:{user_id}");
             let document = doc! {
@@ -706,6 +2263,15 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_members",
+            operation = "list_channel_members",
+            count = tracing::field::Empty,
+        )
+    )]
     pub async fn list_channel_members(&self, channel_id: Uuid) -> Vec<Uuid> {
         if let Some(mongo) = &self.mongo {
             let mut users = Vec::new();
@@ -722,20 +2288,32 @@ impl Storage {
                         users.push(user_id);
                     }
                 }
+                record_count(users.len());
                 return users;
             }
         }
 
-        self.channel_members
+        let items: Vec<_> = self
+            .channel_members
             .read()
             .await
             .iter()
             .filter_map(|(stored_channel_id, user_id)| {
                 (*stored_channel_id == channel_id).then_some(*user_id)
             })
-            .collect()
+            .collect();
+        record_count(items.len());
+        items
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_members",
+            operation = "is_channel_member",
+        )
+    )]
     pub async fn is_channel_member(&self, channel_id: Uuid, user_id: Uuid) -> bool {
         if let Some(mongo) = &self.mongo
             && let Ok(found) = mongo
@@ -755,11 +2333,23 @@ impl Storage {
             .contains(&(channel_id, user_id))
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_members",
+            operation = "remove_channel_member",
+        )
+    )]
     pub async fn remove_channel_member(&self, channel_id: Uuid, user_id: Uuid) {
-        self.channel_members
+        let removed = self
+            .channel_members
             .write()
             .await
             .remove(&(channel_id, user_id));
+        if removed {
+            self.advance_key_epoch(channel_id).await;
+        }
         if let Some(mongo) = &self.mongo {
             let _ = mongo
                 .channel_members
@@ -771,6 +2361,14 @@ impl Storage {
         }
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_members",
+            operation = "remove_channel_members",
+        )
+    )]
     pub async fn remove_channel_members(&self, channel_id: Uuid) {
         self.channel_members
             .write()
@@ -784,265 +2382,370 @@ impl Storage {
         }
     }
 
-    pub async fn channel_name_exists(&self, workspace_id: Uuid, name: &str) -> bool {
-        if let Some(mongo) = &self.mongo
-            && let Ok(result) = mongo
-                .channels
-                .find_one(doc! { "workspace_id": workspace_id.to_string(), "name": name.to_ascii_lowercase() })
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_admins",
+            operation = "set_channel_admin",
+        )
+    )]
+    pub async fn set_channel_admin(&self, channel_id: Uuid, user_id: Uuid, is_admin: bool) {
+        if is_admin {
+            self.channel_admins
+                .write()
                 .await
-        {
-            return result.is_some();
+                .insert((channel_id, user_id));
+        } else {
+            self.channel_admins
+                .write()
+                .await
+                .remove(&(channel_id, user_id));
         }
-
-        self.channels.read().await.values().any(|channel| {
-            channel.workspace_id == workspace_id && channel.name.eq_ignore_ascii_case(name)
-        })
-    }
-
-    pub async fn insert_message(&self, message: MessageRecordStore) {
-        self.messages
-            .write()
-            .await
-            .insert(message.id, message.clone());
         if let Some(mongo) = &self.mongo {
-            let document = doc! {
-                "_id": message.id.to_string(),
-                "workspace_id": message.workspace_id.to_string(),
-                "channel_id": message.channel_id.to_string(),
-                "sender_id": message.sender_id.to_string(),
-                "body_md": message.body_md,
-                "thread_root_id": message.thread_root_id.map(|value| value.to_string()),
-                "created_at": message.created_at,
-                "edited_at": message.edited_at,
-                "deleted_at": message.deleted_at,
-            };
-            let _ = mongo
-                .messages
-                .delete_one(doc! { "_id": message.id.to_string() })
-                .await;
-            let _ = mongo.messages.insert_one(document).await;
+            let id = format!("{channel_id}:{user_id}");
+            if is_admin {
+                let document = doc! {
+                    "_id": id.clone(),
+                    "channel_id": channel_id.to_string(),
+                    "user_id": user_id.to_string(),
+                };
+                let _ = mongo
+                    .channel_admins
+                    .replace_one(doc! { "_id": id }, document)
+                    .upsert(true)
+                    .await;
+            } else {
+                let _ = mongo.channel_admins.delete_one(doc! { "_id": id }).await;
+            }
         }
     }
 
-    pub async fn get_message(&self, message_id: &Uuid) -> Option<MessageRecordStore> {
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_admins",
+            operation = "list_channel_admins",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_channel_admins(&self, channel_id: Uuid) -> Vec<Uuid> {
         if let Some(mongo) = &self.mongo {
-            let found = mongo
-                .messages
-                .find_one(doc! { "_id": message_id.to_string() })
-                .await;
-            if let Ok(Some(document)) = found {
-                return Some(MessageRecordStore {
-                    id: uuid_field(&document, "_id")?,
-                    workspace_id: uuid_field(&document, "workspace_id")?,
-                    channel_id: uuid_field(&document, "channel_id")?,
-                    sender_id: uuid_field(&document, "sender_id")?,
-                    body_md: string_field(&document, "body_md").unwrap_or_default(),
-                    thread_root_id: optional_uuid_field(&document, "thread_root_id"),
-                    created_at: i64_field(&document, "created_at").unwrap_or_default(),
-                    edited_at: optional_i64_field(&document, "edited_at"),
-                    deleted_at: optional_i64_field(&document, "deleted_at"),
-                });
-            }
-        }
-        self.messages.read().await.get(message_id).cloned()
-    }
-
-    pub async fn list_messages(&self, workspace_id: Uuid) -> Vec<MessageRecordStore> {
-        if let Some(mongo) = &self.mongo {
-            let mut messages = Vec::new();
+            let mut users = Vec::new();
             if let Ok(mut cursor) = mongo
-                .messages
-                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .channel_admins
+                .find(doc! { "channel_id": channel_id.to_string() })
                 .await
             {
                 while let Ok(true) = cursor.advance().await {
                     let Ok(document) = cursor.deserialize_current() else {
                         continue;
                     };
-                    if let (Some(id), Some(channel_id), Some(sender_id)) = (
-                        uuid_field(&document, "_id"),
-                        uuid_field(&document, "channel_id"),
-                        uuid_field(&document, "sender_id"),
-                    ) {
-                        messages.push(MessageRecordStore {
-                            id,
-                            workspace_id,
-                            channel_id,
-                            sender_id,
-                            body_md: string_field(&document, "body_md").unwrap_or_default(),
-                            thread_root_id: optional_uuid_field(&document, "thread_root_id"),
-                            created_at: i64_field(&document, "created_at").unwrap_or_default(),
-                            edited_at: optional_i64_field(&document, "edited_at"),
-                            deleted_at: optional_i64_field(&document, "deleted_at"),
-                        });
+                    if let Some(user_id) = uuid_field(&document, "user_id") {
+                        users.push(user_id);
                     }
                 }
-                return messages;
+                record_count(users.len());
+                return users;
             }
         }
 
-        self.messages
+        let items: Vec<_> = self
+            .channel_admins
             .read()
             .await
-            .values()
-            .filter(|message| message.workspace_id == workspace_id)
-            .cloned()
-            .collect()
+            .iter()
+            .filter_map(|(stored_channel_id, user_id)| {
+                (*stored_channel_id == channel_id).then_some(*user_id)
+            })
+            .collect();
+        record_count(items.len());
+        items
     }
 
-    pub async fn update_message(&self, message: MessageRecordStore) {
-        self.insert_message(message).await;
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_admins",
+            operation = "is_channel_admin",
+        )
+    )]
+    pub async fn is_channel_admin(&self, channel_id: Uuid, user_id: Uuid) -> bool {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .channel_admins
+                .find_one(doc! { "_id": format!("{channel_id}:{user_id}") })
+                .await
+        {
+            return found.is_some();
+        }
+
+        self.channel_admins
+            .read()
+            .await
+            .contains(&(channel_id, user_id))
     }
 
-    pub async fn remove_messages_for_channel(&self, channel_id: Uuid) {
-        self.messages
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_admins",
+            operation = "remove_channel_admins",
+        )
+    )]
+    pub async fn remove_channel_admins(&self, channel_id: Uuid) {
+        self.channel_admins
             .write()
             .await
-            .retain(|_, message| message.channel_id != channel_id);
+            .retain(|(stored_channel_id, _)| *stored_channel_id != channel_id);
         if let Some(mongo) = &self.mongo {
             let _ = mongo
-                .messages
+                .channel_admins
                 .delete_many(doc! { "channel_id": channel_id.to_string() })
                 .await;
         }
     }
 
-    pub async fn put_auth_user(&self, user: AuthUserRecordStore) {
-        self.auth_users_by_email
-            .write()
-            .await
-            .insert(user.email.to_ascii_lowercase(), user.id);
-        self.auth_users.write().await.insert(user.id, user.clone());
-        if let Some(mongo) = &self.mongo {
-            let document = doc! {
-                "_id": user.id.to_string(),
-                "email": user.email.to_ascii_lowercase(),
-                "name": user.name,
-                "password_hash": user.password_hash,
-            };
-            let _ = mongo
-                .auth_users
-                .delete_one(doc! { "_id": user.id.to_string() })
-                .await;
-            let _ = mongo.auth_users.insert_one(document).await;
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channels",
+            operation = "channel_name_exists",
+        )
+    )]
+    pub async fn channel_name_exists(&self, workspace_id: Uuid, name: &str) -> bool {
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .channels
+                .find_one(doc! { "workspace_id": workspace_id.to_string(), "name": name.to_ascii_lowercase() })
+                .await
+        {
+            return result.is_some();
         }
+
+        self.channels.read().await.values().any(|channel| {
+            channel.workspace_id == workspace_id && channel.name.eq_ignore_ascii_case(name)
+        })
     }
 
-    pub async fn get_auth_user_by_email(&self, email: &str) -> Option<AuthUserRecordStore> {
-        if let Some(mongo) = &self.mongo {
-            let normalized = email.trim().to_ascii_lowercase();
-            let found = mongo
-                .auth_users
-                .find_one(doc! { "email": normalized })
-                .await;
-            if let Ok(Some(document)) = found {
-                return Some(AuthUserRecordStore {
-                    id: uuid_field(&document, "_id")?,
-                    email: string_field(&document, "email").unwrap_or_default(),
-                    name: string_field(&document, "name").unwrap_or_default(),
-                    password_hash: string_field(&document, "password_hash").unwrap_or_default(),
-                });
+    /// Seals message bodies written to Mongo from now on; existing plaintext
+    /// bodies stay readable.
+    pub fn with_message_encryption(mut self, encryption: MessageEncryption) -> Self {
+        self.encryption = Some(Arc::new(encryption));
+        self
+    }
+
+    /// Loads, or creates on first use, the data key of `workspace_id`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "workspace_keys",
+            operation = "data_key",
+        )
+    )]
+    async fn data_key(&self, workspace_id: Uuid) -> Option<Arc<DataKey>> {
+        let encryption = self.encryption.as_ref()?;
+        if let Some(key) = self.data_keys.read().await.get(&workspace_id) {
+            return Some(key.clone());
+        }
+        let mut data_keys = self.data_keys.write().await;
+        if let Some(key) = data_keys.get(&workspace_id) {
+            return Some(key.clone());
+        }
+        let key = match self.wrapped_data_key(workspace_id).await {
+            Some(wrapped) => encryption.unwrap_data_key(workspace_id, &wrapped),
+            None => {
+                let (key, wrapped) = encryption.generate_data_key(workspace_id)?;
+                if self.put_wrapped_data_key(workspace_id, &wrapped).await {
+                    Some(key)
+                } else {
+                    let stored = self.wrapped_data_key(workspace_id).await?;
+                    encryption.unwrap_data_key(workspace_id, &stored)
+                }
             }
+        };
+        let Some(key) = key else {
+            tracing::error!(%workspace_id, "failed to unwrap workspace data key");
+            return None;
+        };
+        let key = Arc::new(key);
+        data_keys.insert(workspace_id, key.clone());
+        Some(key)
+    }
+
+    async fn wrapped_data_key(&self, workspace_id: Uuid) -> Option<String> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .workspace_keys
+                .find_one(doc! { "_id": workspace_id.to_string() })
+                .await
+        {
+            return found.and_then(|document| string_field(&document, "wrapped_key"));
         }
+        self.workspace_keys.read().await.get(&workspace_id).cloned()
+    }
 
-        let normalized = email.trim().to_ascii_lowercase();
-        let user_id = self
-            .auth_users_by_email
-            .read()
+    /// Returns false when another writer stored a key first.
+    async fn put_wrapped_data_key(&self, workspace_id: Uuid, wrapped: &str) -> bool {
+        if let Some(mongo) = &self.mongo
+            && mongo
+                .workspace_keys
+                .insert_one(doc! {
+                    "_id": workspace_id.to_string(),
+                    "wrapped_key": wrapped,
+                    "created_at": chrono::Utc::now().timestamp_millis(),
+                })
+                .await
+                .is_err()
+        {
+            return false;
+        }
+        let mut keys = self.workspace_keys.write().await;
+        if keys.contains_key(&workspace_id) {
+            return false;
+        }
+        keys.insert(workspace_id, wrapped.to_string());
+        true
+    }
+
+    async fn seal_body(
+        &self,
+        workspace_id: Uuid,
+        record_id: Uuid,
+        body: &str,
+    ) -> Result<String, SealError> {
+        if self.encryption.is_none() {
+            return Ok(body.to_string());
+        }
+        self.data_key(workspace_id)
             .await
-            .get(&normalized)
-            .copied()?;
-        self.auth_users.read().await.get(&user_id).cloned()
+            .and_then(|key| key.seal(record_id, body))
+            .ok_or_else(|| {
+                tracing::error!(%workspace_id, %record_id, "failed to seal body");
+                SealError { workspace_id }
+            })
     }
 
-    pub async fn get_auth_user_by_id(&self, user_id: Uuid) -> Option<AuthUserRecordStore> {
-        if let Some(mongo) = &self.mongo {
-            let found = mongo
-                .auth_users
-                .find_one(doc! { "_id": user_id.to_string() })
-                .await;
-            if let Ok(Some(document)) = found {
-                return Some(AuthUserRecordStore {
-                    id: uuid_field(&document, "_id")?,
-                    email: string_field(&document, "email").unwrap_or_default(),
-                    name: string_field(&document, "name").unwrap_or_default(),
-                    password_hash: string_field(&document, "password_hash").unwrap_or_default(),
-                });
-            }
+    async fn open_body(&self, workspace_id: Uuid, message_id: Uuid, stored: String) -> String {
+        if !encryption::is_sealed(&stored) {
+            return stored;
+        }
+        match self.data_key(workspace_id).await {
+            Some(key) => key.open(message_id, &stored).unwrap_or_else(|| {
+                tracing::warn!(%message_id, "failed to open sealed message body");
+                String::new()
+            }),
+            None => String::new(),
         }
-        self.auth_users.read().await.get(&user_id).cloned()
     }
 
-    pub async fn put_membership_role(&self, workspace_id: Uuid, user_id: Uuid, role: &str) {
-        self.auth_memberships
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "insert_message",
+        )
+    )]
+    pub async fn insert_message(&self, message: MessageRecordStore) -> Result<(), SealError> {
+        let sealed_body = match &self.mongo {
+            Some(_) => Some(
+                self.seal_body(message.workspace_id, message.id, &message.body_md)
+                    .await?,
+            ),
+            None => None,
+        };
+        self.messages
             .write()
             .await
-            .insert((workspace_id, user_id), role.to_string());
-        if let Some(mongo) = &self.mongo {
-            let id = format!("{workspace_id}:{user_id}");
-            let document = doc! {
-                "_id": id.clone(),
-                "workspace_id": workspace_id.to_string(),
-                "user_id": user_id.to_string(),
-                "role": role,
-            };
-            let _ = mongo.auth_memberships.delete_one(doc! { "_id": id }).await;
-            let _ = mongo.auth_memberships.insert_one(document).await;
+            .insert(message.id, message.clone());
+        if let (Some(mongo), Some(body_md)) = (&self.mongo, sealed_body) {
+            // `$set` rather than a replace keeps any embedded outbox events
+            // that the dispatcher has not moved out yet.
+            let _ = mongo
+                .messages
+                .update_one(
+                    doc! { "_id": message.id.to_string() },
+                    doc! { "$set": message_fields(&message, body_md) },
+                )
+                .upsert(true)
+                .await;
         }
+        Ok(())
     }
 
-    pub async fn get_membership_role(&self, workspace_id: Uuid, user_id: Uuid) -> Option<String> {
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_sequences",
+            operation = "next_channel_seq",
+        )
+    )]
+    pub async fn next_channel_seq(&self, channel_id: Uuid) -> i64 {
+        let mut sequences = self.channel_sequences.write().await;
+        let current = sequences.entry(channel_id).or_default();
         if let Some(mongo) = &self.mongo {
-            let found = mongo
-                .auth_memberships
-                .find_one(doc! {
-                    "workspace_id": workspace_id.to_string(),
-                    "user_id": user_id.to_string()
-                })
+            let result = mongo
+                .channel_sequences
+                .find_one_and_update(
+                    doc! { "_id": channel_id.to_string() },
+                    doc! { "$inc": { "seq": 1_i64 } },
+                )
+                .upsert(true)
+                .return_document(ReturnDocument::After)
                 .await;
-            if let Ok(Some(document)) = found {
-                return string_field(&document, "role");
+            if let Ok(Some(document)) = result
+                && let Some(seq) = i64_field(&document, "seq")
+            {
+                *current = seq;
+                return seq;
             }
         }
-
-        self.auth_memberships
-            .read()
-            .await
-            .get(&(workspace_id, user_id))
-            .cloned()
+        *current += 1;
+        *current
     }
 
-    pub async fn find_primary_membership(&self, user_id: Uuid) -> Option<(Uuid, String)> {
-        if let Some(mongo) = &self.mongo
-            && let Ok(mut cursor) = mongo
-                .auth_memberships
-                .find(doc! { "user_id": user_id.to_string() })
-                .await
-            && let Ok(true) = cursor.advance().await
-        {
-            let Ok(document) = cursor.deserialize_current() else {
-                return None;
-            };
-            return Some((
-                uuid_field(&document, "workspace_id")?,
-                string_field(&document, "role").unwrap_or_default(),
-            ));
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "get_message",
+        )
+    )]
+    pub async fn get_message(&self, message_id: &Uuid) -> Option<MessageRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let found = mongo
+                .messages
+                .find_one(doc! { "_id": message_id.to_string() })
+                .await;
+            if let Ok(Some(document)) = found {
+                return self.message_from_document(&document).await;
+            }
         }
-
-        self.auth_memberships
-            .read()
-            .await
-            .iter()
-            .find_map(|((workspace_id, member_id), role)| {
-                (*member_id == user_id).then(|| (*workspace_id, role.clone()))
-            })
+        self.messages.read().await.get(message_id).cloned()
     }
 
-    pub async fn list_workspace_memberships(&self, workspace_id: Uuid) -> Vec<(Uuid, String)> {
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "list_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_messages(&self, workspace_id: Uuid) -> Vec<MessageRecordStore> {
         if let Some(mongo) = &self.mongo {
-            let mut memberships = Vec::new();
+            let mut messages = Vec::new();
             if let Ok(mut cursor) = mongo
-                .auth_memberships
+                .messages
                 .find(doc! { "workspace_id": workspace_id.to_string() })
                 .await
             {
@@ -1050,363 +2753,4930 @@ impl Storage {
                     let Ok(document) = cursor.deserialize_current() else {
                         continue;
                     };
-                    let Some(user_id) = uuid_field(&document, "user_id") else {
-                        continue;
-                    };
-                    let role = string_field(&document, "role").unwrap_or_default();
-                    memberships.push((user_id, role));
+                    if let Some(message) = self.message_from_document(&document).await {
+                        messages.push(message);
+                    }
                 }
-                return memberships;
+                record_count(messages.len());
+                return messages;
             }
         }
 
-        self.auth_memberships
+        let items: Vec<_> = self
+            .messages
             .read()
             .await
-            .iter()
-            .filter_map(|((stored_workspace_id, user_id), role)| {
-                (*stored_workspace_id == workspace_id).then_some((*user_id, role.clone()))
-            })
-            .collect()
+            .values()
+            .filter(|message| message.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
     }
 
-    pub async fn list_user_memberships(&self, user_id: Uuid) -> Vec<(Uuid, String)> {
+    /// Every copy of a cross-posted message, across workspaces.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "list_crosspost_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_crosspost_messages(&self, group_id: Uuid) -> Vec<MessageRecordStore> {
         if let Some(mongo) = &self.mongo {
-            let mut memberships = Vec::new();
+            let mut messages = Vec::new();
             if let Ok(mut cursor) = mongo
-                .auth_memberships
-                .find(doc! { "user_id": user_id.to_string() })
+                .messages
+                .find(doc! { "crosspost_group_id": group_id.to_string() })
                 .await
             {
                 while let Ok(true) = cursor.advance().await {
                     let Ok(document) = cursor.deserialize_current() else {
                         continue;
                     };
-                    let Some(workspace_id) = uuid_field(&document, "workspace_id") else {
-                        continue;
-                    };
-                    let role = string_field(&document, "role").unwrap_or_default();
-                    memberships.push((workspace_id, role));
+                    if let Some(message) = self.message_from_document(&document).await {
+                        messages.push(message);
+                    }
                 }
-                return memberships;
+                record_count(messages.len());
+                return messages;
             }
         }
 
-        self.auth_memberships
+        let items: Vec<_> = self
+            .messages
             .read()
             .await
-            .iter()
+            .values()
+            .filter(|message| message.crosspost_group_id == Some(group_id))
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    /// Keyword search over message bodies. Mongo answers from the text index
+    /// unless bodies are encrypted at rest, in which case (like the memory
+    /// backend) it scans the workspaces' messages.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "search_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn search_messages(&self, search: &MessageSearch) -> Vec<MessageRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && self.encryption.is_none()
+        {
+            let phrases = search
+                .terms
+                .iter()
+                .map(|term| format!("\"{}\"", term.replace('"', "")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let channel_ids = search
+                .channel_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let mut filter = doc! {
+                "$text": { "$search": phrases },
+                "channel_id": { "$in": channel_ids },
+                "deleted_at": Bson::Null,
+            };
+            if let Some(sender_id) = search.sender_id {
+                filter.insert("sender_id", sender_id.to_string());
+            }
+            let mut created_at = Document::new();
+            if let Some(from) = search.from {
+                created_at.insert("$gte", from);
+            }
+            if let Some(to) = search.to {
+                created_at.insert("$lt", to);
+            }
+            if !created_at.is_empty() {
+                filter.insert("created_at", created_at);
+            }
+            if let Some((created_at, id)) = search.before {
+                filter.insert(
+                    "$or",
+                    vec![
+                        doc! { "created_at": { "$lt": created_at } },
+                        doc! {
+                            "created_at": created_at,
+                            "_id": { "$lt": Uuid::from_u128(id).to_string() },
+                        },
+                    ],
+                );
+            }
+            let mut messages = Vec::new();
+            if let Ok(mut cursor) = mongo
+                .messages
+                .find(filter)
+                .sort(doc! { "created_at": -1, "_id": -1 })
+                .limit(search.limit as i64)
+                .await
+            {
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    if let Some(message) = self.message_from_document(&document).await {
+                        messages.push(message);
+                    }
+                }
+                record_count(messages.len());
+                return messages;
+            }
+        }
+
+        let mut messages = Vec::new();
+        for workspace_id in &search.workspace_ids {
+            messages.extend(
+                self.list_messages(*workspace_id)
+                    .await
+                    .into_iter()
+                    .filter(|message| search.matches(message)),
+            );
+        }
+        messages.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
+        });
+        messages.truncate(search.limit);
+        record_count(messages.len());
+        messages
+    }
+
+    async fn message_from_document(&self, document: &Document) -> Option<MessageRecordStore> {
+        let id = uuid_field(document, "_id")?;
+        let workspace_id = uuid_field(document, "workspace_id")?;
+        Some(MessageRecordStore {
+            id,
+            workspace_id,
+            channel_id: uuid_field(document, "channel_id")?,
+            sender_id: uuid_field(document, "sender_id")?,
+            body_md: self
+                .open_body(
+                    workspace_id,
+                    id,
+                    string_field(document, "body_md").unwrap_or_default(),
+                )
+                .await,
+            thread_root_id: optional_uuid_field(document, "thread_root_id"),
+            origin_workspace_id: optional_uuid_field(document, "origin_workspace_id"),
+            seq: i64_field(document, "seq").unwrap_or_default(),
+            created_at: i64_field(document, "created_at").unwrap_or_default(),
+            edited_at: optional_i64_field(document, "edited_at"),
+            deleted_at: optional_i64_field(document, "deleted_at"),
+            crosspost_group_id: optional_uuid_field(document, "crosspost_group_id"),
+        })
+    }
+
+    /// Stores `conversation` unless its member set already has one in the
+    /// workspace, and returns the stored conversation and whether it is new.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_conversations",
+            operation = "insert_dm_conversation",
+        )
+    )]
+    pub async fn insert_dm_conversation(
+        &self,
+        conversation: DmConversationRecordStore,
+    ) -> (DmConversationRecordStore, bool) {
+        let member_key = conversation.member_key();
+        if let Some(mongo) = &self.mongo {
+            let member_ids = conversation
+                .member_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let inserted = mongo
+                .dm_conversations
+                .insert_one(doc! {
+                    "_id": conversation.id.to_string(),
+                    "workspace_id": conversation.workspace_id.to_string(),
+                    "member_ids": member_ids,
+                    "member_key": &member_key,
+                    "created_by": conversation.created_by.to_string(),
+                    "created_at": conversation.created_at,
+                    "last_message_at": conversation.last_message_at,
+                })
+                .await;
+            if inserted.is_err()
+                && let Ok(Some(document)) = mongo
+                    .dm_conversations
+                    .find_one(doc! {
+                        "workspace_id": conversation.workspace_id.to_string(),
+                        "member_key": &member_key,
+                    })
+                    .await
+                && let Some(existing) = dm_conversation_from_document(&document)
+            {
+                return (existing, false);
+            }
+        }
+        let mut conversations = self.dm_conversations.write().await;
+        if let Some(existing) = conversations.values().find(|existing| {
+            existing.workspace_id == conversation.workspace_id
+                && existing.member_key() == member_key
+        }) {
+            return (existing.clone(), false);
+        }
+        conversations.insert(conversation.id, conversation.clone());
+        (conversation, true)
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_conversations",
+            operation = "get_dm_conversation",
+        )
+    )]
+    pub async fn get_dm_conversation(
+        &self,
+        conversation_id: Uuid,
+    ) -> Option<DmConversationRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(Some(document)) = mongo
+                .dm_conversations
+                .find_one(doc! { "_id": conversation_id.to_string() })
+                .await
+        {
+            return dm_conversation_from_document(&document);
+        }
+        self.dm_conversations
+            .read()
+            .await
+            .get(&conversation_id)
+            .cloned()
+    }
+
+    /// Conversations of `user_id` in `workspace_id`, in no particular order.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_conversations",
+            operation = "list_dm_conversations",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_dm_conversations(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> Vec<DmConversationRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .dm_conversations
+                .find(doc! {
+                    "workspace_id": workspace_id.to_string(),
+                    "member_ids": user_id.to_string(),
+                })
+                .await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Ok(document) = cursor.deserialize_current()
+                    && let Some(conversation) = dm_conversation_from_document(&document)
+                {
+                    items.push(conversation);
+                }
+            }
+            record_count(items.len());
+            return items;
+        }
+        let items: Vec<_> = self
+            .dm_conversations
+            .read()
+            .await
+            .values()
+            .filter(|conversation| {
+                conversation.workspace_id == workspace_id
+                    && conversation.member_ids.contains(&user_id)
+            })
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_messages",
+            operation = "list_dm_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_dm_messages(
+        &self,
+        conversation_id: Uuid,
+        before: Option<(i64, u128)>,
+        limit: usize,
+    ) -> Vec<DmMessageRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! { "conversation_id": conversation_id.to_string() };
+            if let Some((created_at, id)) = before {
+                filter.insert(
+                    "$or",
+                    vec![
+                        doc! { "created_at": { "$lt": created_at } },
+                        doc! {
+                            "created_at": created_at,
+                            "_id": { "$lt": Uuid::from_u128(id).to_string() },
+                        },
+                    ],
+                );
+            }
+            if let Ok(mut cursor) = mongo
+                .dm_messages
+                .find(filter)
+                .sort(doc! { "created_at": -1, "_id": -1 })
+                .limit(limit as i64)
+                .await
+            {
+                let mut messages = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    if let Some(message) = self.dm_message_from_document(&document).await {
+                        messages.push(message);
+                    }
+                }
+                record_count(messages.len());
+                return messages;
+            }
+        }
+
+        let mut messages: Vec<_> = self
+            .dm_messages
+            .read()
+            .await
+            .values()
+            .filter(|message| {
+                message.conversation_id == conversation_id
+                    && before
+                        .is_none_or(|cursor| (message.created_at, message.id.as_u128()) < cursor)
+            })
+            .cloned()
+            .collect();
+        messages.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.as_u128().cmp(&a.id.as_u128()))
+        });
+        messages.truncate(limit);
+        record_count(messages.len());
+        messages
+    }
+
+    /// Every DM message of the workspace, across conversations.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_messages",
+            operation = "list_workspace_dm_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_workspace_dm_messages(
+        &self,
+        workspace_id: Uuid,
+    ) -> Vec<DmMessageRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let mut messages = Vec::new();
+            if let Ok(mut cursor) = mongo
+                .dm_messages
+                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .await
+            {
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    if let Some(message) = self.dm_message_from_document(&document).await {
+                        messages.push(message);
+                    }
+                }
+                record_count(messages.len());
+                return messages;
+            }
+        }
+
+        let items: Vec<_> = self
+            .dm_messages
+            .read()
+            .await
+            .values()
+            .filter(|message| message.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    /// Rewrites the body of a stored DM message; everything else is kept.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_messages",
+            operation = "update_dm_message",
+        )
+    )]
+    pub async fn update_dm_message(&self, message: DmMessageRecordStore) -> Result<(), SealError> {
+        let sealed_body = match &self.mongo {
+            Some(_) => Some(
+                self.seal_body(message.workspace_id, message.id, &message.body_md)
+                    .await?,
+            ),
+            None => None,
+        };
+        if let Some(stored) = self.dm_messages.write().await.get_mut(&message.id) {
+            stored.body_md = message.body_md.clone();
+        }
+        if let (Some(mongo), Some(body_md)) = (&self.mongo, sealed_body) {
+            let _ = mongo
+                .dm_messages
+                .update_one(
+                    doc! { "_id": message.id.to_string() },
+                    doc! { "$set": { "body_md": body_md } },
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_messages",
+            operation = "remove_dm_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn remove_dm_messages(&self, message_ids: &[Uuid]) {
+        record_count(message_ids.len());
+        if message_ids.is_empty() {
+            return;
+        }
+        let message_set = message_ids.iter().copied().collect::<HashSet<_>>();
+        self.dm_messages
+            .write()
+            .await
+            .retain(|message_id, _| !message_set.contains(message_id));
+        if let Some(mongo) = &self.mongo {
+            let message_id_values = message_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let _ = mongo
+                .dm_messages
+                .delete_many(doc! { "_id": { "$in": message_id_values } })
+                .await;
+        }
+    }
+
+    async fn dm_message_from_document(&self, document: &Document) -> Option<DmMessageRecordStore> {
+        let id = uuid_field(document, "_id")?;
+        let workspace_id = uuid_field(document, "workspace_id")?;
+        Some(DmMessageRecordStore {
+            id,
+            conversation_id: uuid_field(document, "conversation_id")?,
+            workspace_id,
+            sender_id: uuid_field(document, "sender_id")?,
+            body_md: self
+                .open_body(
+                    workspace_id,
+                    id,
+                    string_field(document, "body_md").unwrap_or_default(),
+                )
+                .await,
+            created_at: i64_field(document, "created_at").unwrap_or_default(),
+        })
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "update_message",
+        )
+    )]
+    pub async fn update_message(&self, message: MessageRecordStore) -> Result<(), SealError> {
+        self.insert_message(message).await
+    }
+
+    /// Stores (or replaces) `message` and queues `event` in the realtime outbox
+    /// as one write: the event is pushed onto the message document itself, so
+    /// both land or neither does, and is then moved to `realtime_outbox`. An
+    /// event left embedded by a crash is moved by the dispatcher instead.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "write_message_with_event",
+        )
+    )]
+    pub async fn write_message_with_event(
+        &self,
+        message: MessageRecordStore,
+        event: OutboxEventRecordStore,
+    ) -> Result<(), WriteError> {
+        if let Some(mongo) = &self.mongo {
+            let body_md = self
+                .seal_body(message.workspace_id, message.id, &message.body_md)
+                .await?;
+            let event_document = self.outbox_event_document(&event).await?;
+            mongo
+                .messages
+                .update_one(
+                    doc! { "_id": message.id.to_string() },
+                    doc! {
+                        "$set": message_fields(&message, body_md),
+                        "$push": { "outbox_events": event_document.clone() },
+                    },
+                )
+                .upsert(true)
+                .await?;
+            move_embedded_outbox_event(
+                mongo,
+                &mongo.messages,
+                &message.id.to_string(),
+                event_document,
+            )
+            .await;
+        }
+        self.messages
+            .write()
+            .await
+            .insert(message.id, message.clone());
+        self.realtime_outbox
+            .write()
+            .await
+            .insert(event.id, event.clone());
+        self.outbox_signal.notify_one();
+        Ok(())
+    }
+
+    /// Stores `message`, moves its conversation's last activity forward and
+    /// queues `events` (one per participant) in the realtime outbox in the
+    /// same write, the way `write_message_with_event` does for channel messages.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "dm_messages",
+            operation = "write_dm_message_with_events",
+        )
+    )]
+    pub async fn write_dm_message_with_events(
+        &self,
+        message: DmMessageRecordStore,
+        events: Vec<OutboxEventRecordStore>,
+    ) -> Result<(), WriteError> {
+        if let Some(mongo) = &self.mongo {
+            let body_md = self
+                .seal_body(message.workspace_id, message.id, &message.body_md)
+                .await?;
+            let mut event_documents = Vec::with_capacity(events.len());
+            for event in &events {
+                event_documents.push(self.outbox_event_document(event).await?);
+            }
+            mongo
+                .dm_messages
+                .update_one(
+                    doc! { "_id": message.id.to_string() },
+                    doc! {
+                        "$set": {
+                            "conversation_id": message.conversation_id.to_string(),
+                            "workspace_id": message.workspace_id.to_string(),
+                            "sender_id": message.sender_id.to_string(),
+                            "body_md": body_md,
+                            "created_at": message.created_at,
+                        },
+                        "$push": { "outbox_events": { "$each": event_documents.clone() } },
+                    },
+                )
+                .upsert(true)
+                .await?;
+            let _ = mongo
+                .dm_conversations
+                .update_one(
+                    doc! { "_id": message.conversation_id.to_string() },
+                    doc! { "$max": { "last_message_at": message.created_at } },
+                )
+                .await;
+            for event_document in event_documents {
+                move_embedded_outbox_event(
+                    mongo,
+                    &mongo.dm_messages,
+                    &message.id.to_string(),
+                    event_document,
+                )
+                .await;
+            }
+        }
+        if let Some(conversation) = self
+            .dm_conversations
+            .write()
+            .await
+            .get_mut(&message.conversation_id)
+        {
+            conversation.last_message_at =
+                conversation.last_message_at.max(Some(message.created_at));
+        }
+        self.dm_messages.write().await.insert(message.id, message);
+        let mut outbox = self.realtime_outbox.write().await;
+        for event in events {
+            outbox.insert(event.id, event);
+        }
+        self.outbox_signal.notify_one();
+        Ok(())
+    }
+
+    /// Outbox document for `event`. The payload carries the message body, so
+    /// it is sealed like it.
+    async fn outbox_event_document(
+        &self,
+        event: &OutboxEventRecordStore,
+    ) -> Result<Document, SealError> {
+        let event_json = self
+            .seal_body(event.workspace_id, event.id, &event.event_json)
+            .await?;
+        Ok(doc! {
+            "_id": event.id.to_string(),
+            "workspace_id": event.workspace_id.to_string(),
+            "event_json": event_json,
+            "created_at": event.created_at,
+            "claimed_until": event.claimed_until,
+            "delivered_at": event.delivered_at,
+        })
+    }
+
+    /// Moves outbox events still embedded in message or DM documents (the
+    /// writer stopped before moving them) into `realtime_outbox`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "recover_embedded_outbox_events",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn recover_embedded_outbox_events(&self, limit: i64) {
+        let Some(mongo) = &self.mongo else {
+            return;
+        };
+        let mut moved = 0;
+        for collection in [&mongo.messages, &mongo.dm_messages] {
+            let Ok(mut cursor) = collection
+                .find(doc! { "outbox_events._id": { "$exists": true } })
+                .projection(doc! { "outbox_events": 1 })
+                .limit(limit)
+                .await
+            else {
+                continue;
+            };
+            let mut documents = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Ok(document) = cursor.deserialize_current() {
+                    documents.push(document);
+                }
+            }
+            for document in documents {
+                let (Ok(owner_id), Ok(events)) =
+                    (document.get_str("_id"), document.get_array("outbox_events"))
+                else {
+                    continue;
+                };
+                for event in events.iter().filter_map(Bson::as_document) {
+                    move_embedded_outbox_event(mongo, collection, owner_id, event.clone()).await;
+                    moved += 1;
+                }
+            }
+        }
+        record_count(moved);
+    }
+
+    async fn outbox_event_from_document(
+        &self,
+        document: &Document,
+    ) -> Option<OutboxEventRecordStore> {
+        let id = uuid_field(document, "_id")?;
+        let workspace_id = uuid_field(document, "workspace_id")?;
+        Some(OutboxEventRecordStore {
+            id,
+            workspace_id,
+            event_json: self
+                .open_body(workspace_id, id, string_field(document, "event_json")?)
+                .await,
+            created_at: i64_field(document, "created_at").unwrap_or_default(),
+            claimed_until: i64_field(document, "claimed_until").unwrap_or_default(),
+            delivered_at: optional_i64_field(document, "delivered_at"),
+        })
+    }
+
+    /// Signalled whenever an event is queued in the realtime outbox.
+    pub fn outbox_signal(&self) -> Arc<Notify> {
+        self.outbox_signal.clone()
+    }
+
+    /// Leases up to `limit` undelivered outbox events, oldest first, until
+    /// `lease_until`; events whose lease expired are handed out again.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "realtime_outbox",
+            operation = "claim_outbox_events",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn claim_outbox_events(
+        &self,
+        now: i64,
+        lease_until: i64,
+        limit: usize,
+    ) -> Vec<OutboxEventRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let mut claimed = Vec::new();
+            while claimed.len() < limit {
+                let Ok(found) = mongo
+                    .realtime_outbox
+                    .find_one_and_update(
+                        doc! { "delivered_at": Bson::Null, "claimed_until": { "$lte": now } },
+                        doc! { "$set": { "claimed_until": lease_until } },
+                    )
+                    .sort(doc! { "created_at": 1 })
+                    .return_document(ReturnDocument::After)
+                    .await
+                else {
+                    break;
+                };
+                let Some(document) = found else {
+                    break;
+                };
+                let Some(event) = self.outbox_event_from_document(&document).await else {
+                    break;
+                };
+                claimed.push(event);
+            }
+            record_count(claimed.len());
+            return claimed;
+        }
+        let mut outbox = self.realtime_outbox.write().await;
+        let mut pending = outbox
+            .values_mut()
+            .filter(|event| event.delivered_at.is_none() && event.claimed_until <= now)
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|event| (event.created_at, event.id));
+        let claimed = pending
+            .into_iter()
+            .take(limit)
+            .map(|event| {
+                event.claimed_until = lease_until;
+                event.clone()
+            })
+            .collect::<Vec<_>>();
+        record_count(claimed.len());
+        claimed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "realtime_outbox",
+            operation = "mark_outbox_delivered",
+        )
+    )]
+    pub async fn mark_outbox_delivered(&self, event_id: Uuid, delivered_at: i64) {
+        if let Some(event) = self.realtime_outbox.write().await.get_mut(&event_id) {
+            event.delivered_at = Some(delivered_at);
+        }
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .realtime_outbox
+                .update_one(
+                    doc! { "_id": event_id.to_string() },
+                    doc! { "$set": { "delivered_at": delivered_at } },
+                )
+                .await;
+        }
+    }
+
+    /// Drops outbox events delivered before `before`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "realtime_outbox",
+            operation = "purge_delivered_outbox",
+        )
+    )]
+    pub async fn purge_delivered_outbox(&self, before: i64) {
+        self.realtime_outbox
+            .write()
+            .await
+            .retain(|_, event| event.delivered_at.is_none_or(|at| at >= before));
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .realtime_outbox
+                .delete_many(doc! { "delivered_at": { "$lt": before } })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "remove_messages_for_channel",
+        )
+    )]
+    pub async fn remove_messages_for_channel(&self, channel_id: Uuid) {
+        self.messages
+            .write()
+            .await
+            .retain(|_, message| message.channel_id != channel_id);
+        self.channel_sequences.write().await.remove(&channel_id);
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .messages
+                .delete_many(doc! { "channel_id": channel_id.to_string() })
+                .await;
+            let _ = mongo
+                .channel_sequences
+                .delete_one(doc! { "_id": channel_id.to_string() })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "messages",
+            operation = "remove_messages",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn remove_messages(&self, message_ids: &[Uuid]) {
+        record_count(message_ids.len());
+        if message_ids.is_empty() {
+            return;
+        }
+        let message_set = message_ids.iter().copied().collect::<HashSet<_>>();
+        self.messages
+            .write()
+            .await
+            .retain(|message_id, _| !message_set.contains(message_id));
+        self.reactions
+            .write()
+            .await
+            .retain(|(message_id, _, _)| !message_set.contains(message_id));
+        if let Some(mongo) = &self.mongo {
+            let message_id_values = message_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let _ = mongo
+                .messages
+                .delete_many(doc! { "_id": { "$in": message_id_values.clone() } })
+                .await;
+            let _ = mongo
+                .reactions
+                .delete_many(doc! { "message_id": { "$in": message_id_values } })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_users",
+            operation = "put_auth_user",
+        )
+    )]
+    pub async fn put_auth_user(&self, user: AuthUserRecordStore) {
+        let mut by_email = self.auth_users_by_email.write().await;
+        if let Some(previous) = self.auth_users.write().await.insert(user.id, user.clone())
+            && !previous.email.eq_ignore_ascii_case(&user.email)
+        {
+            by_email.remove(&previous.email.to_ascii_lowercase());
+        }
+        by_email.insert(user.email.to_ascii_lowercase(), user.id);
+        drop(by_email);
+        if let Some(mongo) = &self.mongo {
+            let document = doc! {
+                "_id": user.id.to_string(),
+                "email": user.email.to_ascii_lowercase(),
+                "name": user.name,
+                "password_hash": user.password_hash,
+                "email_verified_at": user.email_verified_at,
+            };
+            let _ = mongo
+                .auth_users
+                .delete_one(doc! { "_id": user.id.to_string() })
+                .await;
+            let _ = mongo.auth_users.insert_one(document).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_users",
+            operation = "get_auth_user_by_email",
+        )
+    )]
+    pub async fn get_auth_user_by_email(&self, email: &str) -> Option<AuthUserRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let normalized = email.trim().to_ascii_lowercase();
+            let found = mongo
+                .auth_users
+                .find_one(doc! { "email": normalized })
+                .await;
+            if let Ok(Some(document)) = found {
+                return Some(AuthUserRecordStore {
+                    id: uuid_field(&document, "_id")?,
+                    email: string_field(&document, "email").unwrap_or_default(),
+                    name: string_field(&document, "name").unwrap_or_default(),
+                    password_hash: string_field(&document, "password_hash").unwrap_or_default(),
+                    email_verified_at: optional_i64_field(&document, "email_verified_at"),
+                });
+            }
+        }
+
+        let normalized = email.trim().to_ascii_lowercase();
+        let user_id = self
+            .auth_users_by_email
+            .read()
+            .await
+            .get(&normalized)
+            .copied()?;
+        self.auth_users.read().await.get(&user_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_users",
+            operation = "get_auth_user_by_id",
+        )
+    )]
+    pub async fn get_auth_user_by_id(&self, user_id: Uuid) -> Option<AuthUserRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let found = mongo
+                .auth_users
+                .find_one(doc! { "_id": user_id.to_string() })
+                .await;
+            if let Ok(Some(document)) = found {
+                return Some(AuthUserRecordStore {
+                    id: uuid_field(&document, "_id")?,
+                    email: string_field(&document, "email").unwrap_or_default(),
+                    name: string_field(&document, "name").unwrap_or_default(),
+                    password_hash: string_field(&document, "password_hash").unwrap_or_default(),
+                    email_verified_at: optional_i64_field(&document, "email_verified_at"),
+                });
+            }
+        }
+        self.auth_users.read().await.get(&user_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_memberships",
+            operation = "put_membership_role",
+        )
+    )]
+    pub async fn put_membership_role(&self, workspace_id: Uuid, user_id: Uuid, role: &str) {
+        self.auth_memberships
+            .write()
+            .await
+            .insert((workspace_id, user_id), role.to_string());
+        if let Some(mongo) = &self.mongo {
+            let id = format!("{workspace_id}:{user_id}");
+            let document = doc! {
+                "_id": id.clone(),
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+                "role": role,
+            };
+            let _ = mongo.auth_memberships.delete_one(doc! { "_id": id }).await;
+            let _ = mongo.auth_memberships.insert_one(document).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_memberships",
+            operation = "delete_membership",
+        )
+    )]
+    pub async fn delete_membership(&self, workspace_id: Uuid, user_id: Uuid) {
+        self.auth_memberships
+            .write()
+            .await
+            .remove(&(workspace_id, user_id));
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .auth_memberships
+                .delete_one(doc! { "_id": format!("{workspace_id}:{user_id}") })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_memberships",
+            operation = "get_membership_role",
+        )
+    )]
+    pub async fn get_membership_role(&self, workspace_id: Uuid, user_id: Uuid) -> Option<String> {
+        if let Some(mongo) = &self.mongo {
+            let found = mongo
+                .auth_memberships
+                .find_one(doc! {
+                    "workspace_id": workspace_id.to_string(),
+                    "user_id": user_id.to_string()
+                })
+                .await;
+            if let Ok(Some(document)) = found {
+                return string_field(&document, "role");
+            }
+        }
+
+        self.auth_memberships
+            .read()
+            .await
+            .get(&(workspace_id, user_id))
+            .cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_memberships",
+            operation = "find_primary_membership",
+        )
+    )]
+    pub async fn find_primary_membership(&self, user_id: Uuid) -> Option<(Uuid, String)> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .auth_memberships
+                .find(doc! { "user_id": user_id.to_string() })
+                .await
+            && let Ok(true) = cursor.advance().await
+        {
+            let Ok(document) = cursor.deserialize_current() else {
+                return None;
+            };
+            return Some((
+                uuid_field(&document, "workspace_id")?,
+                string_field(&document, "role").unwrap_or_default(),
+            ));
+        }
+
+        self.auth_memberships
+            .read()
+            .await
+            .iter()
+            .find_map(|((workspace_id, member_id), role)| {
+                (*member_id == user_id).then(|| (*workspace_id, role.clone()))
+            })
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_memberships",
+            operation = "list_workspace_memberships",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_workspace_memberships(&self, workspace_id: Uuid) -> Vec<(Uuid, String)> {
+        if let Some(mongo) = &self.mongo {
+            let mut memberships = Vec::new();
+            if let Ok(mut cursor) = mongo
+                .auth_memberships
+                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .await
+            {
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(user_id) = uuid_field(&document, "user_id") else {
+                        continue;
+                    };
+                    let role = string_field(&document, "role").unwrap_or_default();
+                    memberships.push((user_id, role));
+                }
+                record_count(memberships.len());
+                return memberships;
+            }
+        }
+
+        let items: Vec<_> = self
+            .auth_memberships
+            .read()
+            .await
+            .iter()
+            .filter_map(|((stored_workspace_id, user_id), role)| {
+                (*stored_workspace_id == workspace_id).then_some((*user_id, role.clone()))
+            })
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "auth_memberships",
+            operation = "list_user_memberships",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_user_memberships(&self, user_id: Uuid) -> Vec<(Uuid, String)> {
+        if let Some(mongo) = &self.mongo {
+            let mut memberships = Vec::new();
+            if let Ok(mut cursor) = mongo
+                .auth_memberships
+                .find(doc! { "user_id": user_id.to_string() })
+                .await
+            {
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(workspace_id) = uuid_field(&document, "workspace_id") else {
+                        continue;
+                    };
+                    let role = string_field(&document, "role").unwrap_or_default();
+                    memberships.push((workspace_id, role));
+                }
+                record_count(memberships.len());
+                return memberships;
+            }
+        }
+
+        let items: Vec<_> = self
+            .auth_memberships
+            .read()
+            .await
+            .iter()
             .filter_map(|((workspace_id, member_id), role)| {
                 (*member_id == user_id).then_some((*workspace_id, role.clone()))
             })
-            .collect()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "refresh_sessions",
+            operation = "get_refresh_session",
+        )
+    )]
+    pub async fn get_refresh_session(&self, token_hash: &str) -> Option<RefreshSessionRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let found = mongo
+                .refresh_sessions
+                .find_one(doc! { "_id": token_hash })
+                .await;
+            if let Ok(Some(document)) = found {
+                return refresh_session_from_document(&document);
+            }
+        }
+        self.refresh_sessions.read().await.get(token_hash).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "refresh_sessions",
+            operation = "put_refresh_session",
+        )
+    )]
+    pub async fn put_refresh_session(
+        &self,
+        token_hash: String,
+        session: RefreshSessionRecordStore,
+    ) {
+        self.refresh_sessions
+            .write()
+            .await
+            .insert(token_hash.clone(), session.clone());
+        if let Some(mongo) = &self.mongo {
+            let document = doc! {
+                "_id": token_hash.clone(),
+                "user_id": session.user_id.to_string(),
+                "workspace_id": session.workspace_id.to_string(),
+                "created_at": session.created_at,
+                "expires_at": session.expires_at,
+                "expires_at_dt": BsonDateTime::from_millis(session.expires_at * 1000),
+                "revoked_at": session.revoked_at,
+                "replaced_by_hash": session.replaced_by_hash,
+                "session_id": session.session_id.to_string(),
+                "ip": session.ip,
+                "user_agent": session.user_agent,
+                "new_device": session.new_device,
+            };
+            let _ = mongo
+                .refresh_sessions
+                .delete_one(doc! { "_id": token_hash })
+                .await;
+            let _ = mongo.refresh_sessions.insert_one(document).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "refresh_sessions",
+            operation = "update_refresh_session",
+        )
+    )]
+    pub async fn update_refresh_session(
+        &self,
+        token_hash: &str,
+        update_fn: impl FnOnce(&mut RefreshSessionRecordStore),
+    ) -> Option<RefreshSessionRecordStore> {
+        let mut session = self.get_refresh_session(token_hash).await?;
+        update_fn(&mut session);
+        self.put_refresh_session(token_hash.to_string(), session.clone())
+            .await;
+        Some(session)
+    }
+
+    /// Live (not revoked, not expired) refresh sessions of `user_id` in
+    /// `workspace_id`, keyed by token hash.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "refresh_sessions",
+            operation = "list_active_refresh_sessions",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_active_refresh_sessions(
+        &self,
+        user_id: Uuid,
+        workspace_id: Uuid,
+        now: i64,
+    ) -> Vec<(String, RefreshSessionRecordStore)> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .refresh_sessions
+                .find(doc! {
+                    "user_id": user_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                    "revoked_at": Bson::Null,
+                    "expires_at": { "$gt": now },
+                })
+                .await
+        {
+            let mut sessions = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                if let Some(hash) = string_field(&document, "_id")
+                    && let Some(session) = refresh_session_from_document(&document)
+                {
+                    sessions.push((hash, session));
+                }
+            }
+            record_count(sessions.len());
+            return sessions;
+        }
+        let sessions: Vec<_> = self
+            .refresh_sessions
+            .read()
+            .await
+            .iter()
+            .filter(|(_, session)| {
+                session.user_id == user_id
+                    && session.workspace_id == workspace_id
+                    && session.revoked_at.is_none()
+                    && session.expires_at > now
+            })
+            .map(|(hash, session)| (hash.clone(), session.clone()))
+            .collect();
+        record_count(sessions.len());
+        sessions
+    }
+
+    /// Revokes every live refresh session of `user_id`, optionally limited to
+    /// one workspace. Returns how many sessions were revoked.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "refresh_sessions",
+            operation = "revoke_refresh_sessions",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn revoke_refresh_sessions(
+        &self,
+        user_id: Uuid,
+        workspace_id: Option<Uuid>,
+        revoked_at: i64,
+    ) -> u64 {
+        let mut revoked = 0;
+        for session in self.refresh_sessions.write().await.values_mut() {
+            if session.user_id == user_id
+                && session.revoked_at.is_none()
+                && workspace_id.is_none_or(|id| session.workspace_id == id)
+            {
+                session.revoked_at = Some(revoked_at);
+                revoked += 1;
+            }
+        }
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! { "user_id": user_id.to_string(), "revoked_at": Bson::Null };
+            if let Some(workspace_id) = workspace_id {
+                filter.insert("workspace_id", workspace_id.to_string());
+            }
+            if let Ok(result) = mongo
+                .refresh_sessions
+                .update_many(filter, doc! { "$set": { "revoked_at": revoked_at } })
+                .await
+            {
+                revoked = revoked.max(result.modified_count);
+            }
+        }
+        record_count(revoked as usize);
+        revoked
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "known_devices",
+            operation = "list_known_devices",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_known_devices(&self, user_id: Uuid) -> Vec<KnownDeviceRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .known_devices
+                .find(doc! { "user_id": user_id.to_string() })
+                .await
+        {
+            let mut devices = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Ok(document) = cursor.deserialize_current()
+                    && let Some(device) = known_device_from_document(&document)
+                {
+                    devices.push(device);
+                }
+            }
+            record_count(devices.len());
+            return devices;
+        }
+        let devices: Vec<_> = self
+            .known_devices
+            .read()
+            .await
+            .values()
+            .filter(|device| device.user_id == user_id)
+            .cloned()
+            .collect();
+        record_count(devices.len());
+        devices
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "known_devices",
+            operation = "put_known_device",
+        )
+    )]
+    pub async fn put_known_device(&self, device: KnownDeviceRecordStore) {
+        self.known_devices
+            .write()
+            .await
+            .insert((device.user_id, device.fingerprint.clone()), device.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .known_devices
+                .replace_one(
+                    doc! { "_id": format!("{}:{}", device.user_id, device.fingerprint) },
+                    doc! {
+                        "user_id": device.user_id.to_string(),
+                        "fingerprint": device.fingerprint,
+                        "ip": device.ip,
+                        "user_agent": device.user_agent,
+                        "first_seen_at": device.first_seen_at,
+                        "last_seen_at": device.last_seen_at,
+                        "last_seen_at_dt": BsonDateTime::from_millis(device.last_seen_at * 1000),
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "email_tokens",
+            operation = "put_email_token",
+        )
+    )]
+    pub async fn put_email_token(&self, token_hash: String, token: EmailTokenRecordStore) {
+        self.email_tokens
+            .write()
+            .await
+            .insert(token_hash.clone(), token.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .email_tokens
+                .insert_one(doc! {
+                    "_id": token_hash,
+                    "purpose": token.purpose,
+                    "user_id": token.user_id.to_string(),
+                    "email": token.email,
+                    "created_at": token.created_at,
+                    "expires_at": token.expires_at,
+                    "expires_at_dt": BsonDateTime::from_millis(token.expires_at * 1000),
+                })
+                .await;
+        }
+    }
+
+    /// Removes and returns the unexpired `purpose` token with this hash, so
+    /// each token can be redeemed once.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "email_tokens",
+            operation = "take_email_token",
+        )
+    )]
+    pub async fn take_email_token(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+        now: i64,
+    ) -> Option<EmailTokenRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            self.email_tokens.write().await.remove(token_hash);
+            let found = mongo
+                .email_tokens
+                .find_one_and_delete(doc! {
+                    "_id": token_hash,
+                    "purpose": purpose,
+                    "expires_at": { "$gt": now },
+                })
+                .await;
+            return found
+                .ok()
+                .flatten()
+                .as_ref()
+                .and_then(email_token_from_document);
+        }
+        let mut tokens = self.email_tokens.write().await;
+        let token = tokens
+            .get(token_hash)
+            .filter(|token| token.purpose == purpose && token.expires_at > now)?
+            .clone();
+        tokens.remove(token_hash);
+        Some(token)
+    }
+
+    /// Invalidates every outstanding `purpose` token of `user_id`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "email_tokens",
+            operation = "remove_email_tokens",
+        )
+    )]
+    pub async fn remove_email_tokens(&self, user_id: Uuid, purpose: &str) {
+        self.email_tokens
+            .write()
+            .await
+            .retain(|_, token| token.user_id != user_id || token.purpose != purpose);
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .email_tokens
+                .delete_many(doc! { "user_id": user_id.to_string(), "purpose": purpose })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "ws_command_dedup",
+            operation = "get_ws_command_message_id",
+        )
+    )]
+    pub async fn get_ws_command_message_id(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        channel_id: Uuid,
+        client_msg_id: &str,
+    ) -> Option<Uuid> {
+        let dedup_key = (
+            workspace_id,
+            user_id,
+            channel_id,
+            client_msg_id.trim().to_string(),
+        );
+
+        if let Some(mongo) = &self.mongo {
+            let mongo_id = format!(
+                "{}:{}:{}:{}",
+                dedup_key.0, dedup_key.1, dedup_key.2, dedup_key.3
+            );
+            if let Ok(Some(document)) = mongo
+                .ws_command_dedup
+                .find_one(doc! { "_id": mongo_id })
+                .await
+            {
+                return uuid_field(&document, "message_id");
+            }
+        }
+
+        self.ws_command_dedup.read().await.get(&dedup_key).copied()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "ws_command_dedup",
+            operation = "put_ws_command_message_id",
+        )
+    )]
+    pub async fn put_ws_command_message_id(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        channel_id: Uuid,
+        client_msg_id: &str,
+        message_id: Uuid,
+        created_at: i64,
+    ) {
+        let dedup_key = (
+            workspace_id,
+            user_id,
+            channel_id,
+            client_msg_id.trim().to_string(),
+        );
+        self.ws_command_dedup
+            .write()
+            .await
+            .insert(dedup_key.clone(), message_id);
+
+        if let Some(mongo) = &self.mongo {
+            let mongo_id = format!(
+                "{}:{}:{}:{}",
+                dedup_key.0, dedup_key.1, dedup_key.2, dedup_key.3
+            );
+            let document = doc! {
+                "_id": mongo_id.clone(),
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+                "channel_id": channel_id.to_string(),
+                "client_msg_id": dedup_key.3,
+                "message_id": message_id.to_string(),
+                "created_at": created_at,
+            };
+            let _ = mongo
+                .ws_command_dedup
+                .delete_one(doc! { "_id": mongo_id })
+                .await;
+            let _ = mongo.ws_command_dedup.insert_one(document).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "ws_command_once",
+            operation = "has_ws_command_once",
+        )
+    )]
+    pub async fn has_ws_command_once(&self, key: &str) -> bool {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo.ws_command_once.find_one(doc! { "_id": key }).await
+        {
+            return found.is_some();
+        }
+        self.ws_command_once.read().await.contains(key)
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "ws_command_once",
+            operation = "put_ws_command_once",
+        )
+    )]
+    pub async fn put_ws_command_once(&self, key: &str, created_at: i64) {
+        self.ws_command_once.write().await.insert(key.to_string());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .ws_command_once
+                .delete_one(doc! { "_id": key.to_string() })
+                .await;
+            let _ = mongo
+                .ws_command_once
+                .insert_one(doc! {
+                    "_id": key.to_string(),
+                    "created_at": created_at,
+                    "created_at_dt": BsonDateTime::from_millis(created_at),
+                })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "idempotency_keys",
+            operation = "get_idempotency_record",
+        )
+    )]
+    pub async fn get_idempotency_record(&self, key: &str) -> Option<IdempotencyRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(Some(document)) = mongo.idempotency_keys.find_one(doc! { "_id": key }).await
+        {
+            let id = uuid_field(&document, "id")?;
+            let workspace_id = uuid_field(&document, "workspace_id")?;
+            let body = match document.get("body") {
+                Some(Bson::String(stored)) if encryption::is_sealed(stored) => {
+                    let opened = self.open_body(workspace_id, id, stored.clone()).await;
+                    serde_json::from_str(&opened).unwrap_or(Value::Null)
+                }
+                other => other
+                    .cloned()
+                    .and_then(|value| from_bson::<Value>(value).ok())
+                    .unwrap_or(Value::Null),
+            };
+            return Some(IdempotencyRecordStore {
+                id,
+                workspace_id,
+                request_hash: string_field(&document, "request_hash").unwrap_or_default(),
+                status: document.get_i32("status").ok().map(|status| status as u16),
+                body,
+                created_at: document.get_i64("created_at").unwrap_or_default(),
+            });
+        }
+        self.idempotency_keys.read().await.get(key).cloned()
+    }
+
+    /// Claims `key` with a pending `record` unless another record holds it.
+    /// A holder for which `replaceable` returns true (expired or abandoned) is
+    /// dropped first. Returns the current holder when the claim fails.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "idempotency_keys",
+            operation = "reserve_idempotency_key",
+        )
+    )]
+    pub async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        record: IdempotencyRecordStore,
+        replaceable: impl Fn(&IdempotencyRecordStore) -> bool,
+    ) -> Option<IdempotencyRecordStore> {
+        let Some(mongo) = &self.mongo else {
+            let mut records = self.idempotency_keys.write().await;
+            if let Some(holder) = records.get(key).filter(|holder| !replaceable(holder)) {
+                return Some(holder.clone());
+            }
+            records.insert(key.to_string(), record);
+            return None;
+        };
+
+        let document = doc! {
+            "_id": key.to_string(),
+            "id": record.id.to_string(),
+            "workspace_id": record.workspace_id.to_string(),
+            "request_hash": record.request_hash.clone(),
+            "status": Bson::Null,
+            "body": Bson::Null,
+            "created_at": record.created_at,
+            "created_at_dt": BsonDateTime::from_millis(record.created_at),
+        };
+        for _ in 0..3 {
+            match mongo.idempotency_keys.insert_one(document.clone()).await {
+                Ok(_) => {
+                    self.idempotency_keys
+                        .write()
+                        .await
+                        .insert(key.to_string(), record);
+                    return None;
+                }
+                Err(error) if !is_duplicate_key_error(&error) => {
+                    tracing::warn!("idempotency key not reserved: {}", error);
+                    return None;
+                }
+                Err(_) => {}
+            }
+            let Some(holder) = self.get_idempotency_record(key).await else {
+                continue;
+            };
+            if !replaceable(&holder) {
+                return Some(holder);
+            }
+            let _ = mongo
+                .idempotency_keys
+                .delete_one(doc! { "_id": key.to_string(), "id": holder.id.to_string() })
+                .await;
+        }
+        None
+    }
+
+    /// Drops the pending reservation `id` of `key`, so the request can be
+    /// retried after it failed.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "idempotency_keys",
+            operation = "release_idempotency_key",
+        )
+    )]
+    pub async fn release_idempotency_key(&self, key: &str, id: Uuid) {
+        let mut records = self.idempotency_keys.write().await;
+        if records
+            .get(key)
+            .is_some_and(|record| record.id == id && record.status.is_none())
+        {
+            records.remove(key);
+        }
+        drop(records);
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .idempotency_keys
+                .delete_one(
+                    doc! { "_id": key.to_string(), "id": id.to_string(), "status": Bson::Null },
+                )
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "idempotency_keys",
+            operation = "put_idempotency_record",
+        )
+    )]
+    pub async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: IdempotencyRecordStore,
+    ) -> Result<(), SealError> {
+        let sealed_body = match (&self.mongo, &self.encryption) {
+            (Some(_), Some(_)) => Some(Bson::String(
+                self.seal_body(record.workspace_id, record.id, &record.body.to_string())
+                    .await?,
+            )),
+            (Some(_), None) => Some(to_bson(&record.body).unwrap_or(Bson::Null)),
+            (None, _) => None,
+        };
+        self.idempotency_keys
+            .write()
+            .await
+            .insert(key.to_string(), record.clone());
+        if let (Some(mongo), Some(body)) = (&self.mongo, sealed_body) {
+            let _ = mongo
+                .idempotency_keys
+                .delete_one(doc! { "_id": key.to_string() })
+                .await;
+            let _ = mongo
+                .idempotency_keys
+                .insert_one(doc! {
+                    "_id": key.to_string(),
+                    "id": record.id.to_string(),
+                    "workspace_id": record.workspace_id.to_string(),
+                    "request_hash": record.request_hash,
+                    "status": record.status.map(i32::from),
+                    "body": body,
+                    "created_at": record.created_at,
+                    "created_at_dt": BsonDateTime::from_millis(record.created_at),
+                })
+                .await;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "notifications",
+            operation = "put_notification",
+        )
+    )]
+    pub async fn put_notification(
+        &self,
+        notification: NotificationRecordStore,
+    ) -> Result<(), SealError> {
+        let sealed_body = match &self.mongo {
+            Some(_) => Some(
+                self.seal_body(
+                    notification.workspace_id,
+                    notification.id,
+                    &notification.body,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        self.notifications
+            .write()
+            .await
+            .insert(notification.id, notification.clone());
+        if let (Some(mongo), Some(body)) = (&self.mongo, sealed_body) {
+            let _ = mongo
+                .notifications
+                .delete_one(doc! { "_id": notification.id.to_string() })
+                .await;
+            let _ = mongo
+                .notifications
+                .insert_one(doc! {
+                    "_id": notification.id.to_string(),
+                    "workspace_id": notification.workspace_id.to_string(),
+                    "user_id": notification.user_id.to_string(),
+                    "kind": notification.kind,
+                    "actor_id": notification.actor_id.map(|value| value.to_string()),
+                    "channel_id": notification.channel_id.map(|value| value.to_string()),
+                    "message_id": notification.message_id.map(|value| value.to_string()),
+                    "body": body,
+                    "created_at": notification.created_at,
+                    "read_at": notification.read_at,
+                })
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn notification_from_document(
+        &self,
+        document: &Document,
+    ) -> Option<NotificationRecordStore> {
+        let id = uuid_field(document, "_id")?;
+        let workspace_id = uuid_field(document, "workspace_id")?;
+        Some(NotificationRecordStore {
+            id,
+            workspace_id,
+            user_id: uuid_field(document, "user_id")?,
+            kind: string_field(document, "kind")?,
+            actor_id: optional_uuid_field(document, "actor_id"),
+            channel_id: optional_uuid_field(document, "channel_id"),
+            message_id: optional_uuid_field(document, "message_id"),
+            body: self
+                .open_body(
+                    workspace_id,
+                    id,
+                    string_field(document, "body").unwrap_or_default(),
+                )
+                .await,
+            created_at: i64_field(document, "created_at").unwrap_or_default(),
+            read_at: optional_i64_field(document, "read_at"),
+        })
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "notifications",
+            operation = "list_notifications",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_notifications(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> Vec<NotificationRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+            };
+            if let Ok(mut cursor) = mongo.notifications.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    if let Some(notification) = self.notification_from_document(&document).await {
+                        items.push(notification);
+                    }
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .notifications
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id && item.user_id == user_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    /// Every notification of the workspace, for all recipients.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "notifications",
+            operation = "list_workspace_notifications",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_workspace_notifications(
+        &self,
+        workspace_id: Uuid,
+    ) -> Vec<NotificationRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .notifications
+                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                if let Some(notification) = self.notification_from_document(&document).await {
+                    items.push(notification);
+                }
+            }
+            record_count(items.len());
+            return items;
+        }
+        let items: Vec<_> = self
+            .notifications
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    /// Unread notifications of every user created at or after `since`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "notifications",
+            operation = "list_unread_notifications_since",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_unread_notifications_since(
+        &self,
+        since: i64,
+    ) -> Vec<NotificationRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .notifications
+                .find(doc! { "read_at": Bson::Null, "created_at": { "$gte": since } })
+                .await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                if let Some(notification) = self.notification_from_document(&document).await {
+                    items.push(notification);
+                }
+            }
+            record_count(items.len());
+            return items;
+        }
+        let items: Vec<_> = self
+            .notifications
+            .read()
+            .await
+            .values()
+            .filter(|item| item.read_at.is_none() && item.created_at >= since)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    /// Marks unread notifications as read; `ids = None` marks all of them.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "notifications",
+            operation = "mark_notifications_read",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn mark_notifications_read(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        ids: Option<&[Uuid]>,
+        read_at: i64,
+    ) -> u64 {
+        let mut updated = 0;
+        for item in self.notifications.write().await.values_mut() {
+            if item.workspace_id == workspace_id
+                && item.user_id == user_id
+                && item.read_at.is_none()
+                && ids.is_none_or(|ids| ids.contains(&item.id))
+            {
+                item.read_at = Some(read_at);
+                updated += 1;
+            }
+        }
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+                "read_at": Bson::Null,
+            };
+            if let Some(ids) = ids {
+                let ids = ids.iter().map(ToString::to_string).collect::<Vec<_>>();
+                filter.insert("_id", doc! { "$in": ids });
+            }
+            if let Ok(result) = mongo
+                .notifications
+                .update_many(filter, doc! { "$set": { "read_at": read_at } })
+                .await
+            {
+                updated = updated.max(result.modified_count);
+            }
+        }
+        record_count(updated as usize);
+        updated
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "notifications",
+            operation = "remove_notifications",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn remove_notifications(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        read_only: bool,
+    ) -> u64 {
+        let mut removed = 0;
+        self.notifications.write().await.retain(|_, item| {
+            let matches = item.workspace_id == workspace_id
+                && item.user_id == user_id
+                && (!read_only || item.read_at.is_some());
+            if matches {
+                removed += 1;
+            }
+            !matches
+        });
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+            };
+            if read_only {
+                filter.insert("read_at", doc! { "$ne": Bson::Null });
+            }
+            if let Ok(result) = mongo.notifications.delete_many(filter).await {
+                removed = removed.max(result.deleted_count);
+            }
+        }
+        record_count(removed as usize);
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "keyword_subscriptions",
+            operation = "put_keyword_subscription",
+        )
+    )]
+    pub async fn put_keyword_subscription(&self, subscription: KeywordSubscriptionRecordStore) {
+        self.keyword_subscriptions
+            .write()
+            .await
+            .insert(subscription.id, subscription.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .keyword_subscriptions
+                .insert_one(doc! {
+                    "_id": subscription.id.to_string(),
+                    "workspace_id": subscription.workspace_id.to_string(),
+                    "user_id": subscription.user_id.to_string(),
+                    "keyword": subscription.keyword,
+                    "created_at": subscription.created_at,
+                })
+                .await;
+        }
+    }
+
+    /// Every keyword subscription in the workspace, across all users.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "keyword_subscriptions",
+            operation = "list_keyword_subscriptions",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_keyword_subscriptions(
+        &self,
+        workspace_id: Uuid,
+    ) -> Vec<KeywordSubscriptionRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! { "workspace_id": workspace_id.to_string() };
+            if let Ok(mut cursor) = mongo.keyword_subscriptions.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(subscription) = (|| {
+                        Some(KeywordSubscriptionRecordStore {
+                            id: uuid_field(&document, "_id")?,
+                            workspace_id: uuid_field(&document, "workspace_id")?,
+                            user_id: uuid_field(&document, "user_id")?,
+                            keyword: string_field(&document, "keyword")?,
+                            created_at: i64_field(&document, "created_at").unwrap_or_default(),
+                        })
+                    })() else {
+                        continue;
+                    };
+                    items.push(subscription);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .keyword_subscriptions
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "keyword_subscriptions",
+            operation = "remove_keyword_subscription",
+        )
+    )]
+    pub async fn remove_keyword_subscription(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        subscription_id: Uuid,
+    ) -> bool {
+        let mut removed = false;
+        {
+            let mut subscriptions = self.keyword_subscriptions.write().await;
+            if subscriptions
+                .get(&subscription_id)
+                .is_some_and(|item| item.workspace_id == workspace_id && item.user_id == user_id)
+            {
+                subscriptions.remove(&subscription_id);
+                removed = true;
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .keyword_subscriptions
+                .delete_one(doc! {
+                    "_id": subscription_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                    "user_id": user_id.to_string(),
+                })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reminders",
+            operation = "put_reminder",
+        )
+    )]
+    pub async fn put_reminder(&self, reminder: ReminderRecordStore) {
+        self.reminders
+            .write()
+            .await
+            .insert(reminder.id, reminder.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .reminders
+                .insert_one(doc! {
+                    "_id": reminder.id.to_string(),
+                    "workspace_id": reminder.workspace_id.to_string(),
+                    "user_id": reminder.user_id.to_string(),
+                    "channel_id": reminder.channel_id.to_string(),
+                    "message_id": reminder.message_id.to_string(),
+                    "remind_at": reminder.remind_at,
+                    "created_at": reminder.created_at,
+                })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reminders",
+            operation = "list_reminders",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_reminders(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> Vec<ReminderRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+            };
+            if let Ok(mut cursor) = mongo.reminders.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(reminder) = reminder_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(reminder);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .reminders
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id && item.user_id == user_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reminders",
+            operation = "remove_reminder",
+        )
+    )]
+    pub async fn remove_reminder(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        reminder_id: Uuid,
+    ) -> bool {
+        let mut removed = false;
+        {
+            let mut reminders = self.reminders.write().await;
+            if reminders
+                .get(&reminder_id)
+                .is_some_and(|item| item.workspace_id == workspace_id && item.user_id == user_id)
+            {
+                reminders.remove(&reminder_id);
+                removed = true;
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .reminders
+                .delete_one(doc! {
+                    "_id": reminder_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                    "user_id": user_id.to_string(),
+                })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "put_recurring_post",
+        )
+    )]
+    pub async fn put_recurring_post(
+        &self,
+        post: RecurringPostRecordStore,
+    ) -> Result<(), SealError> {
+        let document = match &self.mongo {
+            Some(_) => Some(self.recurring_post_document(&post).await?),
+            None => None,
+        };
+        self.recurring_posts
+            .write()
+            .await
+            .insert(post.id, post.clone());
+        if let (Some(mongo), Some(document)) = (&self.mongo, document) {
+            let _ = mongo
+                .recurring_posts
+                .replace_one(doc! { "_id": post.id.to_string() }, document)
+                .upsert(true)
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Stored form of `post`; the body is sealed like message bodies, since it
+    /// is posted as one.
+    async fn recurring_post_document(
+        &self,
+        post: &RecurringPostRecordStore,
+    ) -> Result<Document, SealError> {
+        let body_md = self
+            .seal_body(post.workspace_id, post.id, &post.body_md)
+            .await?;
+        Ok(doc! {
+            "workspace_id": post.workspace_id.to_string(),
+            "channel_id": post.channel_id.to_string(),
+            "body_md": body_md,
+            "schedule": &post.schedule,
+            "created_by": post.created_by.to_string(),
+            "created_at": post.created_at,
+            "paused": post.paused,
+            "next_run_at": post.next_run_at,
+            "last_run_at": post.last_run_at,
+            "last_message_id": post.last_message_id.map(|id| id.to_string()),
+            "last_error": &post.last_error,
+        })
+    }
+
+    async fn open_recurring_post(&self, document: &Document) -> Option<RecurringPostRecordStore> {
+        let mut post = recurring_post_from_document(document)?;
+        post.body_md = self
+            .open_body(
+                post.workspace_id,
+                post.id,
+                std::mem::take(&mut post.body_md),
+            )
+            .await;
+        Some(post)
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "get_recurring_post",
+        )
+    )]
+    pub async fn get_recurring_post(&self, post_id: Uuid) -> Option<RecurringPostRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(Some(document)) = mongo
+                .recurring_posts
+                .find_one(doc! { "_id": post_id.to_string() })
+                .await
+        {
+            return self.open_recurring_post(&document).await;
+        }
+        self.recurring_posts.read().await.get(&post_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "list_recurring_posts",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_recurring_posts(&self, workspace_id: Uuid) -> Vec<RecurringPostRecordStore> {
+        self.find_recurring_posts(doc! { "workspace_id": workspace_id.to_string() }, |post| {
+            post.workspace_id == workspace_id
+        })
+        .await
+    }
+
+    /// Active posts whose next run is at or before `now`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "list_due_recurring_posts",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_due_recurring_posts(&self, now: i64) -> Vec<RecurringPostRecordStore> {
+        self.find_recurring_posts(
+            doc! { "paused": false, "next_run_at": { "$lte": now } },
+            |post| !post.paused && post.next_run_at <= now,
+        )
+        .await
+    }
+
+    async fn find_recurring_posts(
+        &self,
+        filter: Document,
+        matches: impl Fn(&RecurringPostRecordStore) -> bool,
+    ) -> Vec<RecurringPostRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo.recurring_posts.find(filter).await
+        {
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Ok(document) = cursor.deserialize_current()
+                    && let Some(post) = self.open_recurring_post(&document).await
+                {
+                    items.push(post);
+                }
+            }
+            record_count(items.len());
+            return items;
+        }
+
+        let items: Vec<_> = self
+            .recurring_posts
+            .read()
+            .await
+            .values()
+            .filter(|post| matches(post))
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    /// Moves an active post from `scheduled_at` to `next_run_at`. Only one
+    /// caller wins for a given slot, so replicas never post the same run twice.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "claim_recurring_post_run",
+        )
+    )]
+    pub async fn claim_recurring_post_run(
+        &self,
+        post_id: Uuid,
+        scheduled_at: i64,
+        next_run_at: i64,
+    ) -> bool {
+        let mut claimed = false;
+        if let Some(post) = self.recurring_posts.write().await.get_mut(&post_id)
+            && !post.paused
+            && post.next_run_at == scheduled_at
+        {
+            post.next_run_at = next_run_at;
+            claimed = true;
+        }
+        if let Some(mongo) = &self.mongo {
+            claimed = mongo
+                .recurring_posts
+                .update_one(
+                    doc! {
+                        "_id": post_id.to_string(),
+                        "paused": false,
+                        "next_run_at": scheduled_at,
+                    },
+                    doc! { "$set": { "next_run_at": next_run_at } },
+                )
+                .await
+                .is_ok_and(|result| result.modified_count > 0);
+        }
+        claimed
+    }
+
+    /// Records the outcome of a run without touching the definition, which an
+    /// admin may have edited meanwhile.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "record_recurring_post_run",
+        )
+    )]
+    pub async fn record_recurring_post_run(
+        &self,
+        post_id: Uuid,
+        ran_at: i64,
+        message_id: Option<Uuid>,
+        error: Option<String>,
+    ) {
+        if let Some(post) = self.recurring_posts.write().await.get_mut(&post_id) {
+            post.last_run_at = Some(ran_at);
+            post.last_message_id = message_id;
+            post.last_error = error.clone();
+        }
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .recurring_posts
+                .update_one(
+                    doc! { "_id": post_id.to_string() },
+                    doc! { "$set": {
+                        "last_run_at": ran_at,
+                        "last_message_id": message_id.map(|id| id.to_string()),
+                        "last_error": error,
+                    } },
+                )
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "recurring_posts",
+            operation = "remove_recurring_post",
+        )
+    )]
+    pub async fn remove_recurring_post(&self, post_id: Uuid) -> bool {
+        let mut removed = self
+            .recurring_posts
+            .write()
+            .await
+            .remove(&post_id)
+            .is_some();
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .recurring_posts
+                .delete_one(doc! { "_id": post_id.to_string() })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    /// Removes and returns reminders due at `now`. With Mongo each reminder is
+    /// claimed by its delete, so replicas never deliver the same one twice.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "reminders",
+            operation = "take_due_reminders",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn take_due_reminders(&self, now: i64) -> Vec<ReminderRecordStore> {
+        let mut due = Vec::new();
+        self.reminders.write().await.retain(|_, item| {
+            if item.remind_at <= now {
+                due.push(item.clone());
+                return false;
+            }
+            true
+        });
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .reminders
+                .find(doc! { "remind_at": { "$lte": now } })
+                .await
+        {
+            let mut candidates = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Ok(document) = cursor.deserialize_current()
+                    && let Some(reminder) = reminder_from_document(&document)
+                {
+                    candidates.push(reminder);
+                }
+            }
+            due.clear();
+            for reminder in candidates {
+                if let Ok(result) = mongo
+                    .reminders
+                    .delete_one(doc! { "_id": reminder.id.to_string() })
+                    .await
+                    && result.deleted_count > 0
+                {
+                    due.push(reminder);
+                }
+            }
+        }
+        record_count(due.len());
+        due
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "calls",
+            operation = "put_call",
+        )
+    )]
+    pub async fn put_call(&self, call: CallRecordStore) {
+        self.calls.write().await.insert(call.id, call.clone());
+        if let Some(mongo) = &self.mongo {
+            let participant_ids = call
+                .participant_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let _ = mongo
+                .calls
+                .replace_one(
+                    doc! { "_id": call.id.to_string() },
+                    doc! {
+                        "_id": call.id.to_string(),
+                        "workspace_id": call.workspace_id.to_string(),
+                        "channel_id": call.channel_id.to_string(),
+                        "started_by": call.started_by.to_string(),
+                        "join_url": call.join_url,
+                        "participant_ids": participant_ids,
+                        "started_at": call.started_at,
+                        "ended_at": call.ended_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "calls",
+            operation = "get_active_call",
+        )
+    )]
+    pub async fn get_active_call(&self, channel_id: Uuid) -> Option<CallRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .calls
+                .find_one(doc! { "channel_id": channel_id.to_string(), "ended_at": null })
+                .await
+        {
+            return found.as_ref().and_then(call_from_document);
+        }
+        self.calls
+            .read()
+            .await
+            .values()
+            .find(|call| call.channel_id == channel_id && call.ended_at.is_none())
+            .cloned()
+    }
+
+    /// Starts a new key epoch so members re-key an `e2ee` channel after its
+    /// membership changed; a no-op for other channels.
+    async fn advance_key_epoch(&self, channel_id: Uuid) {
+        if let Some(mut channel) = self.get_channel(&channel_id).await
+            && channel.e2ee
+        {
+            channel.key_epoch += 1;
+            self.insert_channel(channel).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_keys",
+            operation = "put_channel_key",
+        )
+    )]
+    pub async fn put_channel_key(&self, key: ChannelKeyRecordStore) {
+        self.channel_keys
+            .write()
+            .await
+            .insert((key.channel_id, key.key_epoch, key.user_id), key.clone());
+        if let Some(mongo) = &self.mongo {
+            let id = format!("{}:{}:{}", key.channel_id, key.key_epoch, key.user_id);
+            let _ = mongo
+                .channel_keys
+                .replace_one(
+                    doc! { "_id": id.clone() },
+                    doc! {
+                        "_id": id,
+                        "channel_id": key.channel_id.to_string(),
+                        "key_epoch": key.key_epoch,
+                        "user_id": key.user_id.to_string(),
+                        "wrapped_key": key.wrapped_key,
+                        "wrapped_by": key.wrapped_by.to_string(),
+                        "created_at": key.created_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_keys",
+            operation = "list_channel_keys",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_channel_keys(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+    ) -> Vec<ChannelKeyRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .channel_keys
+                .find(doc! {
+                    "channel_id": channel_id.to_string(),
+                    "user_id": user_id.to_string(),
+                })
+                .await
+        {
+            let mut keys = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Some(key) = cursor
+                    .deserialize_current()
+                    .ok()
+                    .as_ref()
+                    .and_then(channel_key_from_document)
+                {
+                    keys.push(key);
+                }
+            }
+            record_count(keys.len());
+            return keys;
+        }
+        let keys: Vec<_> = self
+            .channel_keys
+            .read()
+            .await
+            .values()
+            .filter(|key| key.channel_id == channel_id && key.user_id == user_id)
+            .cloned()
+            .collect();
+        record_count(keys.len());
+        keys
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_keys",
+            operation = "remove_channel_keys",
+        )
+    )]
+    pub async fn remove_channel_keys(&self, channel_id: Uuid) {
+        self.channel_keys
+            .write()
+            .await
+            .retain(|(stored_channel_id, _, _), _| *stored_channel_id != channel_id);
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .channel_keys
+                .delete_many(doc! { "channel_id": channel_id.to_string() })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_public_keys",
+            operation = "put_user_public_key",
+        )
+    )]
+    pub async fn put_user_public_key(&self, key: UserPublicKeyRecordStore) {
+        self.user_public_keys
+            .write()
+            .await
+            .insert(key.user_id, key.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .user_public_keys
+                .replace_one(
+                    doc! { "_id": key.user_id.to_string() },
+                    doc! {
+                        "_id": key.user_id.to_string(),
+                        "public_key": key.public_key,
+                        "updated_at": key.updated_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_public_keys",
+            operation = "get_user_public_key",
+        )
+    )]
+    pub async fn get_user_public_key(&self, user_id: Uuid) -> Option<UserPublicKeyRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .user_public_keys
+                .find_one(doc! { "_id": user_id.to_string() })
+                .await
+        {
+            return found.and_then(|document| {
+                Some(UserPublicKeyRecordStore {
+                    user_id: uuid_field(&document, "_id")?,
+                    public_key: string_field(&document, "public_key")?,
+                    updated_at: i64_field(&document, "updated_at").unwrap_or_default(),
+                })
+            });
+        }
+        self.user_public_keys.read().await.get(&user_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "ip_allowlists",
+            operation = "put_ip_allowlist",
+        )
+    )]
+    pub async fn put_ip_allowlist(&self, allowlist: IpAllowlistRecordStore) {
+        self.ip_allowlists
+            .write()
+            .await
+            .insert(allowlist.workspace_id, allowlist.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .ip_allowlists
+                .replace_one(
+                    doc! { "_id": allowlist.workspace_id.to_string() },
+                    doc! {
+                        "_id": allowlist.workspace_id.to_string(),
+                        "cidrs": allowlist.cidrs,
+                        "updated_by": allowlist.updated_by.to_string(),
+                        "updated_at": allowlist.updated_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "ip_allowlists",
+            operation = "get_ip_allowlist",
+        )
+    )]
+    pub async fn get_ip_allowlist(&self, workspace_id: Uuid) -> Option<IpAllowlistRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .ip_allowlists
+                .find_one(doc! { "_id": workspace_id.to_string() })
+                .await
+        {
+            return found.as_ref().and_then(ip_allowlist_from_document);
+        }
+        self.ip_allowlists.read().await.get(&workspace_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "app_installations",
+            operation = "put_app_installation",
+        )
+    )]
+    pub async fn put_app_installation(&self, installation: AppInstallationRecordStore) {
+        self.app_installations
+            .write()
+            .await
+            .insert(installation.id, installation.clone());
+        if let Some(mongo) = &self.mongo {
+            let slash_commands = installation
+                .slash_commands
+                .iter()
+                .map(|command| {
+                    doc! { "command": &command.command, "description": &command.description }
+                })
+                .collect::<Vec<_>>();
+            let _ = mongo
+                .app_installations
+                .replace_one(
+                    doc! { "_id": installation.id.to_string() },
+                    doc! {
+                        "_id": installation.id.to_string(),
+                        "workspace_id": installation.workspace_id.to_string(),
+                        "name": installation.name,
+                        "description": installation.description,
+                        "scopes": installation.scopes,
+                        "webhook_url": installation.webhook_url,
+                        "event_subscription_id": installation
+                            .event_subscription_id
+                            .map(|id| id.to_string()),
+                        "slash_commands": slash_commands,
+                        "bot_user_id": installation.bot_user_id.to_string(),
+                        "installed_by": installation.installed_by.to_string(),
+                        "installed_at": installation.installed_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "app_installations",
+            operation = "list_app_installations",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_app_installations(
+        &self,
+        workspace_id: Uuid,
+    ) -> Vec<AppInstallationRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(mut cursor) = mongo
+                .app_installations
+                .find(doc! { "workspace_id": workspace_id.to_string() })
+                .await
+        {
+            let mut installations = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                if let Some(installation) = cursor
+                    .deserialize_current()
+                    .ok()
+                    .as_ref()
+                    .and_then(app_installation_from_document)
+                {
+                    installations.push(installation);
+                }
+            }
+            record_count(installations.len());
+            return installations;
+        }
+        let installations: Vec<_> = self
+            .app_installations
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(installations.len());
+        installations
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "app_installations",
+            operation = "remove_app_installation",
+        )
+    )]
+    pub async fn remove_app_installation(&self, workspace_id: Uuid, installation_id: Uuid) -> bool {
+        let mut removed = false;
+        {
+            let mut installations = self.app_installations.write().await;
+            if installations
+                .get(&installation_id)
+                .is_some_and(|item| item.workspace_id == workspace_id)
+            {
+                installations.remove(&installation_id);
+                removed = true;
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .app_installations
+                .delete_one(doc! {
+                    "_id": installation_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "event_subscriptions",
+            operation = "put_event_subscription",
+        )
+    )]
+    pub async fn put_event_subscription(&self, subscription: EventSubscriptionRecordStore) {
+        self.event_subscriptions
+            .write()
+            .await
+            .insert(subscription.id, subscription.clone());
+        if let Some(mongo) = &self.mongo {
+            let channel_ids = subscription
+                .channel_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let _ = mongo
+                .event_subscriptions
+                .replace_one(
+                    doc! { "_id": subscription.id.to_string() },
+                    doc! {
+                        "_id": subscription.id.to_string(),
+                        "workspace_id": subscription.workspace_id.to_string(),
+                        "user_id": subscription.user_id.to_string(),
+                        "event_types": subscription.event_types,
+                        "channel_ids": channel_ids,
+                        "delivery": subscription.delivery,
+                        "webhook_url": subscription.webhook_url,
+                        "webhook_secret": subscription.webhook_secret,
+                        "created_at": subscription.created_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    /// Every event subscription in the workspace, or only `user_id`'s.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "event_subscriptions",
+            operation = "list_event_subscriptions",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_event_subscriptions(
+        &self,
+        workspace_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Vec<EventSubscriptionRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! { "workspace_id": workspace_id.to_string() };
+            if let Some(user_id) = user_id {
+                filter.insert("user_id", user_id.to_string());
+            }
+            if let Ok(mut cursor) = mongo.event_subscriptions.find(filter).await {
+                let mut subscriptions = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    if let Some(subscription) = cursor
+                        .deserialize_current()
+                        .ok()
+                        .as_ref()
+                        .and_then(event_subscription_from_document)
+                    {
+                        subscriptions.push(subscription);
+                    }
+                }
+                record_count(subscriptions.len());
+                return subscriptions;
+            }
+        }
+        let subscriptions: Vec<_> = self
+            .event_subscriptions
+            .read()
+            .await
+            .values()
+            .filter(|item| {
+                item.workspace_id == workspace_id && user_id.is_none_or(|id| item.user_id == id)
+            })
+            .cloned()
+            .collect();
+        record_count(subscriptions.len());
+        subscriptions
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "event_subscriptions",
+            operation = "remove_event_subscription",
+        )
+    )]
+    pub async fn remove_event_subscription(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        subscription_id: Uuid,
+    ) -> bool {
+        let mut removed = false;
+        {
+            let mut subscriptions = self.event_subscriptions.write().await;
+            if subscriptions
+                .get(&subscription_id)
+                .is_some_and(|item| item.workspace_id == workspace_id && item.user_id == user_id)
+            {
+                subscriptions.remove(&subscription_id);
+                removed = true;
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .event_subscriptions
+                .delete_one(doc! {
+                    "_id": subscription_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                    "user_id": user_id.to_string(),
+                })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "session_limits",
+            operation = "put_session_limits",
+        )
+    )]
+    pub async fn put_session_limits(&self, limits: SessionLimitsRecordStore) {
+        self.session_limits
+            .write()
+            .await
+            .insert(limits.workspace_id, limits.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .session_limits
+                .replace_one(
+                    doc! { "_id": limits.workspace_id.to_string() },
+                    doc! {
+                        "_id": limits.workspace_id.to_string(),
+                        "max_refresh_sessions": limits.max_refresh_sessions,
+                        "max_ws_connections": limits.max_ws_connections,
+                        "updated_by": limits.updated_by.to_string(),
+                        "updated_at": limits.updated_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "session_limits",
+            operation = "get_session_limits",
+        )
+    )]
+    pub async fn get_session_limits(&self, workspace_id: Uuid) -> Option<SessionLimitsRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .session_limits
+                .find_one(doc! { "_id": workspace_id.to_string() })
+                .await
+        {
+            return found.and_then(|document| {
+                Some(SessionLimitsRecordStore {
+                    workspace_id: uuid_field(&document, "_id")?,
+                    max_refresh_sessions: optional_i64_field(&document, "max_refresh_sessions"),
+                    max_ws_connections: optional_i64_field(&document, "max_ws_connections"),
+                    updated_by: uuid_field(&document, "updated_by")?,
+                    updated_at: i64_field(&document, "updated_at").unwrap_or_default(),
+                })
+            });
+        }
+        self.session_limits.read().await.get(&workspace_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "message_limits",
+            operation = "put_message_limits",
+        )
+    )]
+    pub async fn put_message_limits(&self, limits: MessageLimitsRecordStore) {
+        self.message_limits
+            .write()
+            .await
+            .insert(limits.workspace_id, limits.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .message_limits
+                .replace_one(
+                    doc! { "_id": limits.workspace_id.to_string() },
+                    doc! {
+                        "_id": limits.workspace_id.to_string(),
+                        "max_message_chars": limits.max_message_chars,
+                        "max_attachments_per_message": limits.max_attachments_per_message,
+                        "max_thread_depth": limits.max_thread_depth,
+                        "updated_by": limits.updated_by.to_string(),
+                        "updated_at": limits.updated_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "message_limits",
+            operation = "get_message_limits",
+        )
+    )]
+    pub async fn get_message_limits(&self, workspace_id: Uuid) -> Option<MessageLimitsRecordStore> {
+        if let Some(mongo) = &self.mongo
+            && let Ok(found) = mongo
+                .message_limits
+                .find_one(doc! { "_id": workspace_id.to_string() })
+                .await
+        {
+            return found.as_ref().and_then(message_limits_from_document);
+        }
+        self.message_limits.read().await.get(&workspace_id).cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_groups",
+            operation = "put_user_group",
+        )
+    )]
+    pub async fn put_user_group(&self, group: UserGroupRecordStore) {
+        self.user_groups
+            .write()
+            .await
+            .insert(group.id, group.clone());
+        if let Some(mongo) = &self.mongo {
+            let member_ids = group
+                .member_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let _ = mongo
+                .user_groups
+                .replace_one(
+                    doc! { "_id": group.id.to_string() },
+                    doc! {
+                        "_id": group.id.to_string(),
+                        "workspace_id": group.workspace_id.to_string(),
+                        "handle": group.handle,
+                        "name": group.name,
+                        "member_ids": member_ids,
+                        "created_by": group.created_by.to_string(),
+                        "created_at": group.created_at,
+                        "updated_at": group.updated_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_groups",
+            operation = "list_user_groups",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_user_groups(&self, workspace_id: Uuid) -> Vec<UserGroupRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! { "workspace_id": workspace_id.to_string() };
+            if let Ok(mut cursor) = mongo.user_groups.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(group) = user_group_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(group);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .user_groups
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_groups",
+            operation = "remove_user_group",
+        )
+    )]
+    pub async fn remove_user_group(&self, workspace_id: Uuid, group_id: Uuid) -> bool {
+        let mut removed = false;
+        {
+            let mut groups = self.user_groups.write().await;
+            if groups
+                .get(&group_id)
+                .is_some_and(|item| item.workspace_id == workspace_id)
+            {
+                groups.remove(&group_id);
+                removed = true;
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .user_groups
+                .delete_one(doc! {
+                    "_id": group_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "announcements",
+            operation = "put_announcement",
+        )
+    )]
+    pub async fn put_announcement(
+        &self,
+        announcement: AnnouncementRecordStore,
+    ) -> Result<(), SealError> {
+        let document = match &self.mongo {
+            Some(_) => Some(self.announcement_document(&announcement).await?),
+            None => None,
+        };
+        self.announcements
+            .write()
+            .await
+            .insert(announcement.id, announcement.clone());
+        if let (Some(mongo), Some(document)) = (&self.mongo, document) {
+            let _ = mongo.announcements.insert_one(document).await;
+        }
+        Ok(())
+    }
+
+    /// Stored form of `announcement`, with the body sealed like message bodies.
+    async fn announcement_document(
+        &self,
+        announcement: &AnnouncementRecordStore,
+    ) -> Result<Document, SealError> {
+        let body_md = self
+            .seal_body(
+                announcement.workspace_id,
+                announcement.id,
+                &announcement.body_md,
+            )
+            .await?;
+        Ok(doc! {
+            "_id": announcement.id.to_string(),
+            "workspace_id": announcement.workspace_id.to_string(),
+            "author_id": announcement.author_id.to_string(),
+            "title": &announcement.title,
+            "body_md": body_md,
+            "severity": &announcement.severity,
+            "pinned": announcement.pinned,
+            "expires_at": announcement.expires_at,
+            "created_at": announcement.created_at,
+        })
+    }
+
+    async fn open_announcement(&self, document: &Document) -> Option<AnnouncementRecordStore> {
+        let mut announcement = announcement_from_document(document)?;
+        announcement.body_md = self
+            .open_body(
+                announcement.workspace_id,
+                announcement.id,
+                std::mem::take(&mut announcement.body_md),
+            )
+            .await;
+        Some(announcement)
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "announcements",
+            operation = "list_announcements",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_announcements(&self, workspace_id: Uuid) -> Vec<AnnouncementRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! { "workspace_id": workspace_id.to_string() };
+            if let Ok(mut cursor) = mongo.announcements.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(announcement) = self.open_announcement(&document).await else {
+                        continue;
+                    };
+                    items.push(announcement);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .announcements
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "announcements",
+            operation = "remove_announcement",
+        )
+    )]
+    pub async fn remove_announcement(&self, workspace_id: Uuid, announcement_id: Uuid) -> bool {
+        let mut removed = false;
+        {
+            let mut announcements = self.announcements.write().await;
+            if announcements
+                .get(&announcement_id)
+                .is_some_and(|item| item.workspace_id == workspace_id)
+            {
+                announcements.remove(&announcement_id);
+                removed = true;
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(result) = mongo
+                .announcements
+                .delete_one(doc! {
+                    "_id": announcement_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                })
+                .await
+        {
+            removed |= result.deleted_count > 0;
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "legal_holds",
+            operation = "put_legal_hold",
+        )
+    )]
+    pub async fn put_legal_hold(&self, hold: LegalHoldRecordStore) {
+        self.legal_holds.write().await.insert(hold.id, hold.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .legal_holds
+                .insert_one(doc! {
+                    "_id": hold.id.to_string(),
+                    "workspace_id": hold.workspace_id.to_string(),
+                    "scope": hold.scope,
+                    "target_id": hold.target_id.to_string(),
+                    "reason": hold.reason,
+                    "created_by": hold.created_by.to_string(),
+                    "created_at": hold.created_at,
+                })
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "legal_holds",
+            operation = "list_legal_holds",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_legal_holds(&self, workspace_id: Uuid) -> Vec<LegalHoldRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! { "workspace_id": workspace_id.to_string() };
+            if let Ok(mut cursor) = mongo.legal_holds.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(hold) = legal_hold_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(hold);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .legal_holds
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "legal_holds",
+            operation = "remove_legal_hold",
+        )
+    )]
+    pub async fn remove_legal_hold(
+        &self,
+        workspace_id: Uuid,
+        hold_id: Uuid,
+    ) -> Option<LegalHoldRecordStore> {
+        let mut removed = None;
+        {
+            let mut holds = self.legal_holds.write().await;
+            if holds
+                .get(&hold_id)
+                .is_some_and(|item| item.workspace_id == workspace_id)
+            {
+                removed = holds.remove(&hold_id);
+            }
+        }
+        if let Some(mongo) = &self.mongo
+            && let Ok(Some(document)) = mongo
+                .legal_holds
+                .find_one_and_delete(doc! {
+                    "_id": hold_id.to_string(),
+                    "workspace_id": workspace_id.to_string(),
+                })
+                .await
+            && removed.is_none()
+        {
+            removed = legal_hold_from_document(&document);
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_mutes",
+            operation = "put_user_mute",
+        )
+    )]
+    pub async fn put_user_mute(&self, mute: UserMuteRecordStore) {
+        self.user_mutes
+            .write()
+            .await
+            .insert((mute.workspace_id, mute.user_id), mute.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .user_mutes
+                .replace_one(
+                    doc! { "_id": format!("{}:{}", mute.workspace_id, mute.user_id) },
+                    doc! {
+                        "workspace_id": mute.workspace_id.to_string(),
+                        "user_id": mute.user_id.to_string(),
+                        "reason": mute.reason,
+                        "muted_by": mute.muted_by.to_string(),
+                        "created_at": mute.created_at,
+                        "expires_at": mute.expires_at,
+                        "expires_at_dt": BsonDateTime::from_millis(mute.expires_at),
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
     }
 
-    pub async fn get_refresh_session(&self, token_hash: &str) -> Option<RefreshSessionRecordStore> {
+    /// The user's mute in the workspace if it is still in force at `now` (ms).
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_mutes",
+            operation = "get_active_user_mute",
+        )
+    )]
+    pub async fn get_active_user_mute(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+        now: i64,
+    ) -> Option<UserMuteRecordStore> {
         if let Some(mongo) = &self.mongo {
-            let found = mongo
-                .refresh_sessions
-                .find_one(doc! { "_id": token_hash })
+            return mongo
+                .user_mutes
+                .find_one(doc! {
+                    "_id": format!("{workspace_id}:{user_id}"),
+                    "expires_at": { "$gt": now },
+                })
+                .await
+                .ok()
+                .flatten()
+                .as_ref()
+                .and_then(user_mute_from_document);
+        }
+        self.user_mutes
+            .read()
+            .await
+            .get(&(workspace_id, user_id))
+            .filter(|mute| mute.expires_at > now)
+            .cloned()
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_mutes",
+            operation = "list_active_user_mutes",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_active_user_mutes(
+        &self,
+        workspace_id: Uuid,
+        now: i64,
+    ) -> Vec<UserMuteRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "expires_at": { "$gt": now },
+            };
+            if let Ok(mut cursor) = mongo.user_mutes.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(mute) = user_mute_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(mute);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .user_mutes
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id && item.expires_at > now)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "user_mutes",
+            operation = "remove_user_mute",
+        )
+    )]
+    pub async fn remove_user_mute(
+        &self,
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> Option<UserMuteRecordStore> {
+        let mut removed = self
+            .user_mutes
+            .write()
+            .await
+            .remove(&(workspace_id, user_id));
+        if let Some(mongo) = &self.mongo
+            && let Ok(Some(document)) = mongo
+                .user_mutes
+                .find_one_and_delete(doc! { "_id": format!("{workspace_id}:{user_id}") })
+                .await
+            && removed.is_none()
+        {
+            removed = user_mute_from_document(&document);
+        }
+        removed
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "moderation_cases",
+            operation = "put_moderation_case",
+        )
+    )]
+    pub async fn put_moderation_case(&self, case: ModerationCaseRecordStore) {
+        self.moderation_cases
+            .write()
+            .await
+            .insert(case.id, case.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .moderation_cases
+                .replace_one(
+                    doc! { "_id": case.id.to_string() },
+                    doc! {
+                        "workspace_id": case.workspace_id.to_string(),
+                        "message_id": case.message_id.to_string(),
+                        "channel_id": case.channel_id.to_string(),
+                        "author_id": case.author_id.to_string(),
+                        "source": case.source,
+                        "category": case.category,
+                        "details": case.details,
+                        "reporter_id": case.reporter_id.map(|id| id.to_string()),
+                        "status": case.status,
+                        "created_at": case.created_at,
+                        "resolution": case.resolution,
+                        "resolved_by": case.resolved_by.map(|id| id.to_string()),
+                        "resolved_at": case.resolved_at,
+                    },
+                )
+                .upsert(true)
                 .await;
-            if let Ok(Some(document)) = found {
-                return Some(RefreshSessionRecordStore {
-                    user_id: uuid_field(&document, "user_id")?,
-                    workspace_id: uuid_field(&document, "workspace_id")?,
-                    expires_at: i64_field(&document, "expires_at").unwrap_or_default(),
-                    revoked_at: optional_i64_field(&document, "revoked_at"),
-                    replaced_by_hash: string_field(&document, "replaced_by_hash"),
-                });
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "moderation_cases",
+            operation = "get_moderation_case",
+        )
+    )]
+    pub async fn get_moderation_case(&self, case_id: Uuid) -> Option<ModerationCaseRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            return mongo
+                .moderation_cases
+                .find_one(doc! { "_id": case_id.to_string() })
+                .await
+                .ok()
+                .flatten()
+                .as_ref()
+                .and_then(moderation_case_from_document);
+        }
+        self.moderation_cases.read().await.get(&case_id).cloned()
+    }
+
+    /// Cases of the workspace, optionally only those with `status`.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "moderation_cases",
+            operation = "list_moderation_cases",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_moderation_cases(
+        &self,
+        workspace_id: Uuid,
+        status: Option<&str>,
+    ) -> Vec<ModerationCaseRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! { "workspace_id": workspace_id.to_string() };
+            if let Some(status) = status {
+                filter.insert("status", status);
+            }
+            if let Ok(mut cursor) = mongo.moderation_cases.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(case) = moderation_case_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(case);
+                }
+                record_count(items.len());
+                return items;
             }
         }
-        self.refresh_sessions.read().await.get(token_hash).cloned()
+
+        let items: Vec<_> = self
+            .moderation_cases
+            .read()
+            .await
+            .values()
+            .filter(|item| {
+                item.workspace_id == workspace_id
+                    && status.is_none_or(|status| item.status == status)
+            })
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
     }
 
-    pub async fn put_refresh_session(
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_access_requests",
+            operation = "put_channel_access_request",
+        )
+    )]
+    pub async fn put_channel_access_request(&self, request: ChannelAccessRequestRecordStore) {
+        self.channel_access_requests
+            .write()
+            .await
+            .insert(request.id, request.clone());
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .channel_access_requests
+                .replace_one(
+                    doc! { "_id": request.id.to_string() },
+                    doc! {
+                        "workspace_id": request.workspace_id.to_string(),
+                        "channel_id": request.channel_id.to_string(),
+                        "user_id": request.user_id.to_string(),
+                        "note": request.note,
+                        "status": request.status,
+                        "created_at": request.created_at,
+                        "decided_by": request.decided_by.map(|id| id.to_string()),
+                        "decided_at": request.decided_at,
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_access_requests",
+            operation = "list_channel_access_requests",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_channel_access_requests(
         &self,
-        token_hash: String,
-        session: RefreshSessionRecordStore,
-    ) {
-        self.refresh_sessions
+        channel_id: Uuid,
+    ) -> Vec<ChannelAccessRequestRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! { "channel_id": channel_id.to_string() };
+            if let Ok(mut cursor) = mongo.channel_access_requests.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(request) = channel_access_request_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(request);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .channel_access_requests
+            .read()
+            .await
+            .values()
+            .filter(|item| item.channel_id == channel_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "read_markers",
+            operation = "put_read_marker",
+        )
+    )]
+    pub async fn put_read_marker(&self, marker: ReadMarkerRecordStore) {
+        self.read_markers
             .write()
             .await
-            .insert(token_hash.clone(), session.clone());
+            .insert((marker.user_id, marker.channel_id), marker.clone());
         if let Some(mongo) = &self.mongo {
-            let document = doc! {
-                "_id": token_hash.clone(),
-                "user_id": session.user_id.to_string(),
-                "workspace_id": session.workspace_id.to_string(),
-                "expires_at": session.expires_at,
-                "expires_at_dt": BsonDateTime::from_millis(session.expires_at * 1000),
-                "revoked_at": session.revoked_at,
-                "replaced_by_hash": session.replaced_by_hash,
-            };
             let _ = mongo
-                .refresh_sessions
-                .delete_one(doc! { "_id": token_hash })
+                .read_markers
+                .replace_one(
+                    doc! { "_id": format!("{}:{}", marker.user_id, marker.channel_id) },
+                    doc! {
+                        "workspace_id": marker.workspace_id.to_string(),
+                        "user_id": marker.user_id.to_string(),
+                        "channel_id": marker.channel_id.to_string(),
+                        "last_read_seq": marker.last_read_seq,
+                        "updated_at": marker.updated_at,
+                    },
+                )
+                .upsert(true)
                 .await;
-            let _ = mongo.refresh_sessions.insert_one(document).await;
         }
     }
 
-    pub async fn update_refresh_session(
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "read_markers",
+            operation = "list_read_markers",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_read_markers(
         &self,
-        token_hash: &str,
-        update_fn: impl FnOnce(&mut RefreshSessionRecordStore),
-    ) -> Option<RefreshSessionRecordStore> {
-        let mut session = self.get_refresh_session(token_hash).await?;
-        update_fn(&mut session);
-        self.put_refresh_session(token_hash.to_string(), session.clone())
-            .await;
-        Some(session)
+        workspace_id: Uuid,
+        user_id: Uuid,
+    ) -> Vec<ReadMarkerRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "user_id": user_id.to_string(),
+            };
+            if let Ok(mut cursor) = mongo.read_markers.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(marker) = read_marker_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(marker);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .read_markers
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id && item.user_id == user_id)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
     }
 
-    pub async fn get_ws_command_message_id(
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "daily_activity",
+            operation = "record_daily_message",
+        )
+    )]
+    pub async fn record_daily_message(
         &self,
         workspace_id: Uuid,
+        day: &str,
         user_id: Uuid,
         channel_id: Uuid,
-        client_msg_id: &str,
-    ) -> Option<Uuid> {
-        let dedup_key = (
-            workspace_id,
-            user_id,
-            channel_id,
-            client_msg_id.trim().to_string(),
-        );
+    ) {
+        {
+            let mut daily_activity = self.daily_activity.write().await;
+            let entry = daily_activity
+                .entry((workspace_id, day.to_string()))
+                .or_insert_with(|| DailyActivityRecordStore {
+                    workspace_id,
+                    day: day.to_string(),
+                    ..DailyActivityRecordStore::default()
+                });
+            entry.messages += 1;
+            entry.active_users.insert(user_id);
+            *entry.channel_messages.entry(channel_id).or_default() += 1;
+        }
+        if let Some(mongo) = &self.mongo {
+            let mut increments = doc! { "messages": 1_i64 };
+            increments.insert(format!("channels.{channel_id}"), 1_i64);
+            let _ = mongo
+                .daily_activity
+                .update_one(
+                    doc! { "_id": format!("{workspace_id}:{day}") },
+                    doc! {
+                        "$setOnInsert": { "workspace_id": workspace_id.to_string(), "day": day },
+                        "$inc": increments,
+                        "$addToSet": { "active_users": user_id.to_string() },
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "daily_activity",
+            operation = "record_daily_connection",
+        )
+    )]
+    pub async fn record_daily_connection(
+        &self,
+        workspace_id: Uuid,
+        day: &str,
+        user_id: Uuid,
+        connections: i64,
+    ) {
+        {
+            let mut daily_activity = self.daily_activity.write().await;
+            let entry = daily_activity
+                .entry((workspace_id, day.to_string()))
+                .or_insert_with(|| DailyActivityRecordStore {
+                    workspace_id,
+                    day: day.to_string(),
+                    ..DailyActivityRecordStore::default()
+                });
+            entry.active_users.insert(user_id);
+            entry.peak_ws_connections = entry.peak_ws_connections.max(connections);
+        }
         if let Some(mongo) = &self.mongo {
-            let mongo_id = format!(
-                "{}:{}:{}:{}",
-                dedup_key.0, dedup_key.1, dedup_key.2, dedup_key.3
-            );
-            if let Ok(Some(document)) = mongo
-                .ws_command_dedup
-                .find_one(doc! { "_id": mongo_id })
-                .await
-            {
-                return uuid_field(&document, "message_id");
+            let _ = mongo
+                .daily_activity
+                .update_one(
+                    doc! { "_id": format!("{workspace_id}:{day}") },
+                    doc! {
+                        "$setOnInsert": { "workspace_id": workspace_id.to_string(), "day": day },
+                        "$max": { "peak_ws_connections": connections },
+                        "$addToSet": { "active_users": user_id.to_string() },
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "daily_activity",
+            operation = "list_daily_activity",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_daily_activity(
+        &self,
+        workspace_id: Uuid,
+        from_day: &str,
+    ) -> Vec<DailyActivityRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "day": { "$gte": from_day },
+            };
+            if let Ok(mut cursor) = mongo.daily_activity.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(activity) = daily_activity_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(activity);
+                }
+                record_count(items.len());
+                return items;
+            }
+        }
+
+        let items: Vec<_> = self
+            .daily_activity
+            .read()
+            .await
+            .values()
+            .filter(|item| item.workspace_id == workspace_id && item.day.as_str() >= from_day)
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "api_usage",
+            operation = "record_api_usage",
+        )
+    )]
+    pub async fn record_api_usage(&self, usage: ApiUsageRecordStore) {
+        {
+            let mut api_usage = self.api_usage.write().await;
+            let entry = api_usage
+                .entry((usage.workspace_id, usage.user_id, usage.day.clone()))
+                .or_insert_with(|| ApiUsageRecordStore {
+                    workspace_id: usage.workspace_id,
+                    user_id: usage.user_id,
+                    day: usage.day.clone(),
+                    ..ApiUsageRecordStore::default()
+                });
+            entry.requests += usage.requests;
+            entry.rate_limited += usage.rate_limited;
+            entry.request_bytes += usage.request_bytes;
+            entry.response_bytes += usage.response_bytes;
+        }
+        if let Some(mongo) = &self.mongo {
+            let _ = mongo
+                .api_usage
+                .update_one(
+                    doc! { "_id": format!("{}:{}:{}", usage.workspace_id, usage.user_id, usage.day) },
+                    doc! {
+                        "$setOnInsert": {
+                            "workspace_id": usage.workspace_id.to_string(),
+                            "user_id": usage.user_id.to_string(),
+                            "day": &usage.day,
+                        },
+                        "$inc": {
+                            "requests": usage.requests,
+                            "rate_limited": usage.rate_limited,
+                            "request_bytes": usage.request_bytes,
+                            "response_bytes": usage.response_bytes,
+                        },
+                    },
+                )
+                .upsert(true)
+                .await;
+        }
+    }
+
+    /// Usage rows of the workspace from `from_day` on, optionally for a single user.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "api_usage",
+            operation = "list_api_usage",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_api_usage(
+        &self,
+        workspace_id: Uuid,
+        user_id: Option<Uuid>,
+        from_day: &str,
+    ) -> Vec<ApiUsageRecordStore> {
+        if let Some(mongo) = &self.mongo {
+            let mut filter = doc! {
+                "workspace_id": workspace_id.to_string(),
+                "day": { "$gte": from_day },
+            };
+            if let Some(user_id) = user_id {
+                filter.insert("user_id", user_id.to_string());
+            }
+            if let Ok(mut cursor) = mongo.api_usage.find(filter).await {
+                let mut items = Vec::new();
+                while let Ok(true) = cursor.advance().await {
+                    let Ok(document) = cursor.deserialize_current() else {
+                        continue;
+                    };
+                    let Some(usage) = api_usage_from_document(&document) else {
+                        continue;
+                    };
+                    items.push(usage);
+                }
+                record_count(items.len());
+                return items;
             }
         }
 
-        self.ws_command_dedup.read().await.get(&dedup_key).copied()
+        let items: Vec<_> = self
+            .api_usage
+            .read()
+            .await
+            .values()
+            .filter(|item| {
+                item.workspace_id == workspace_id
+                    && user_id.is_none_or(|user_id| item.user_id == user_id)
+                    && item.day.as_str() >= from_day
+            })
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
     }
 
-    pub async fn put_ws_command_message_id(
-        &self,
-        workspace_id: Uuid,
-        user_id: Uuid,
-        channel_id: Uuid,
-        client_msg_id: &str,
-        message_id: Uuid,
-        created_at: i64,
-    ) {
-        let dedup_key = (
-            workspace_id,
-            user_id,
-            channel_id,
-            client_msg_id.trim().to_string(),
-        );
-        self.ws_command_dedup
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_links",
+            operation = "put_channel_link",
+        )
+    )]
+    pub async fn put_channel_link(&self, link: ChannelLinkRecordStore) {
+        self.channel_links
             .write()
             .await
-            .insert(dedup_key.clone(), message_id);
-
+            .insert(link.id, link.clone());
         if let Some(mongo) = &self.mongo {
-            let mongo_id = format!(
-                "{}:{}:{}:{}",
-                dedup_key.0, dedup_key.1, dedup_key.2, dedup_key.3
-            );
-            let document = doc! {
-                "_id": mongo_id.clone(),
-                "workspace_id": workspace_id.to_string(),
-                "user_id": user_id.to_string(),
-                "channel_id": channel_id.to_string(),
-                "client_msg_id": dedup_key.3,
-                "message_id": message_id.to_string(),
-                "created_at": created_at,
-            };
             let _ = mongo
-                .ws_command_dedup
-                .delete_one(doc! { "_id": mongo_id })
+                .channel_links
+                .delete_one(doc! { "_id": link.id.to_string() })
+                .await;
+            let _ = mongo
+                .channel_links
+                .insert_one(doc! {
+                    "_id": link.id.to_string(),
+                    "channel_id": link.channel_id.to_string(),
+                    "host_workspace_id": link.host_workspace_id.to_string(),
+                    "guest_workspace_id": link.guest_workspace_id.to_string(),
+                    "host_approved_by": link.host_approved_by.to_string(),
+                    "guest_approved_by": link.guest_approved_by.map(|value| value.to_string()),
+                    "created_at": link.created_at,
+                    "approved_at": link.approved_at,
+                })
                 .await;
-            let _ = mongo.ws_command_dedup.insert_one(document).await;
         }
     }
 
-    pub async fn has_ws_command_once(&self, key: &str) -> bool {
+    /// Links where the workspace is either host or guest.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_links",
+            operation = "list_channel_links",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_channel_links(&self, workspace_id: Uuid) -> Vec<ChannelLinkRecordStore> {
+        let workspace = workspace_id.to_string();
+        self.find_channel_links(
+            doc! {
+                "$or": [
+                    { "host_workspace_id": &workspace },
+                    { "guest_workspace_id": &workspace },
+                ]
+            },
+            |link| {
+                link.host_workspace_id == workspace_id || link.guest_workspace_id == workspace_id
+            },
+        )
+        .await
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_links",
+            operation = "list_channel_links_for_channel",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn list_channel_links_for_channel(
+        &self,
+        channel_id: Uuid,
+    ) -> Vec<ChannelLinkRecordStore> {
+        self.find_channel_links(doc! { "channel_id": channel_id.to_string() }, |link| {
+            link.channel_id == channel_id
+        })
+        .await
+    }
+
+    async fn find_channel_links(
+        &self,
+        filter: Document,
+        matches: impl Fn(&ChannelLinkRecordStore) -> bool,
+    ) -> Vec<ChannelLinkRecordStore> {
         if let Some(mongo) = &self.mongo
-            && let Ok(found) = mongo.ws_command_once.find_one(doc! { "_id": key }).await
+            && let Ok(mut cursor) = mongo.channel_links.find(filter).await
         {
-            return found.is_some();
+            let mut items = Vec::new();
+            while let Ok(true) = cursor.advance().await {
+                let Ok(document) = cursor.deserialize_current() else {
+                    continue;
+                };
+                let Some(link) = (|| {
+                    Some(ChannelLinkRecordStore {
+                        id: uuid_field(&document, "_id")?,
+                        channel_id: uuid_field(&document, "channel_id")?,
+                        host_workspace_id: uuid_field(&document, "host_workspace_id")?,
+                        guest_workspace_id: uuid_field(&document, "guest_workspace_id")?,
+                        host_approved_by: uuid_field(&document, "host_approved_by")?,
+                        guest_approved_by: optional_uuid_field(&document, "guest_approved_by"),
+                        created_at: i64_field(&document, "created_at").unwrap_or_default(),
+                        approved_at: optional_i64_field(&document, "approved_at"),
+                    })
+                })() else {
+                    continue;
+                };
+                items.push(link);
+            }
+            record_count(items.len());
+            return items;
         }
-        self.ws_command_once.read().await.contains(key)
+
+        let items: Vec<_> = self
+            .channel_links
+            .read()
+            .await
+            .values()
+            .filter(|link| matches(link))
+            .cloned()
+            .collect();
+        record_count(items.len());
+        items
     }
 
-    pub async fn put_ws_command_once(&self, key: &str, created_at: i64) {
-        self.ws_command_once.write().await.insert(key.to_string());
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            backend = self.backend.as_str(),
+            collection = "channel_links",
+            operation = "remove_channel_links",
+            count = tracing::field::Empty,
+        )
+    )]
+    pub async fn remove_channel_links(&self, link_ids: &[Uuid]) {
+        record_count(link_ids.len());
+        if link_ids.is_empty() {
+            return;
+        }
+        self.channel_links
+            .write()
+            .await
+            .retain(|id, _| !link_ids.contains(id));
         if let Some(mongo) = &self.mongo {
+            let ids = link_ids.iter().map(ToString::to_string).collect::<Vec<_>>();
             let _ = mongo
-                .ws_command_once
-                .delete_one(doc! { "_id": key.to_string() })
-                .await;
-            let _ = mongo
-                .ws_command_once
-                .insert_one(doc! {
-                    "_id": key.to_string(),
-                    "created_at": created_at,
-                    "created_at_dt": BsonDateTime::from_millis(created_at),
-                })
+                .channel_links
+                .delete_many(doc! { "_id": { "$in": ids } })
                 .await;
         }
     }
 }
 
-async fn ensure_mongo_indexes(state: &MongoState) -> Result<(), mongodb::error::Error> {
-    state
-        .workspaces
-        .create_index(IndexModel::builder().keys(doc! { "name_lc": 1 }).build())
-        .await?;
-
-    state
-        .auth_users
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "email": 1 })
-                .options(IndexOptions::builder().unique(true).build())
-                .build(),
-        )
-        .await?;
-
-    state
-        .auth_memberships
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "workspace_id": 1, "user_id": 1 })
-                .options(IndexOptions::builder().unique(true).build())
-                .build(),
-        )
-        .await?;
-    state
-        .auth_memberships
-        .create_index(IndexModel::builder().keys(doc! { "user_id": 1 }).build())
-        .await?;
-
-    state
-        .channels
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "workspace_id": 1, "name": 1 })
-                .options(IndexOptions::builder().unique(true).build())
-                .build(),
-        )
-        .await?;
-    state
-        .channel_members
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "channel_id": 1, "user_id": 1 })
-                .options(IndexOptions::builder().unique(true).build())
-                .build(),
-        )
-        .await?;
-
-    state
-        .messages
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "channel_id": 1, "created_at": -1, "_id": -1 })
-                .build(),
-        )
-        .await?;
-    state
-        .messages
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "workspace_id": 1 })
-                .build(),
-        )
-        .await?;
-
-    state
-        .attachments
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "workspace_id": 1, "message_id": 1, "created_at": 1 })
-                .build(),
-        )
-        .await?;
-
-    state
-        .audit_entries
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "workspace_id": 1, "created_at": -1, "_id": -1 })
-                .build(),
-        )
-        .await?;
-
-    state
-        .refresh_sessions
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "user_id": 1, "expires_at": 1 })
-                .build(),
-        )
-        .await?;
-    state
-        .refresh_sessions
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "expires_at_dt": 1 })
-                .options(
-                    IndexOptions::builder()
-                        .expire_after(Some(std::time::Duration::from_secs(0)))
-                        .build(),
-                )
-                .build(),
-        )
-        .await?;
+/// `collStats` reports sizes as int32, int64 or double depending on magnitude.
+fn u64_stat(document: &Document, key: &str) -> Option<u64> {
+    match document.get(key)? {
+        Bson::Int32(value) => u64::try_from(*value).ok(),
+        Bson::Int64(value) => u64::try_from(*value).ok(),
+        Bson::Double(value) => Some(*value as u64),
+        _ => None,
+    }
+}
 
-    state
-        .reactions
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "message_id": 1, "emoji": 1, "user_id": 1 })
-                .options(IndexOptions::builder().unique(true).build())
-                .build(),
-        )
-        .await?;
+fn record_count(count: usize) {
+    tracing::Span::current().record("count", count);
+}
+
+fn mongo_index_models(state: &MongoState) -> Vec<(&Collection<Document>, IndexModel)> {
+    let unique = || IndexOptions::builder().unique(true).build();
+    let ttl = |secs: u64| {
+        IndexOptions::builder()
+            .expire_after(Some(std::time::Duration::from_secs(secs)))
+            .build()
+    };
+    let index = |keys: Document, options: Option<IndexOptions>| {
+        IndexModel::builder().keys(keys).options(options).build()
+    };
 
-    state
-        .ws_command_dedup
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! {
+    vec![
+        (&state.workspaces, index(doc! { "name_lc": 1 }, None)),
+        (
+            &state.auth_users,
+            index(doc! { "email": 1 }, Some(unique())),
+        ),
+        (
+            &state.auth_memberships,
+            index(doc! { "workspace_id": 1, "user_id": 1 }, Some(unique())),
+        ),
+        (&state.auth_memberships, index(doc! { "user_id": 1 }, None)),
+        (
+            &state.channels,
+            index(doc! { "workspace_id": 1, "name": 1 }, Some(unique())),
+        ),
+        (
+            &state.channel_members,
+            index(doc! { "channel_id": 1, "user_id": 1 }, Some(unique())),
+        ),
+        (
+            &state.channel_admins,
+            index(doc! { "channel_id": 1, "user_id": 1 }, Some(unique())),
+        ),
+        (
+            &state.messages,
+            index(doc! { "channel_id": 1, "created_at": -1, "_id": -1 }, None),
+        ),
+        (&state.messages, index(doc! { "workspace_id": 1 }, None)),
+        (
+            &state.messages,
+            index(doc! { "channel_id": 1, "seq": 1 }, None),
+        ),
+        (
+            &state.messages,
+            index(doc! { "crosspost_group_id": 1 }, None),
+        ),
+        (&state.messages, index(doc! { "body_md": "text" }, None)),
+        (
+            &state.messages,
+            index(
+                doc! { "outbox_events._id": 1 },
+                Some(IndexOptions::builder().sparse(true).build()),
+            ),
+        ),
+        (
+            &state.dm_messages,
+            index(
+                doc! { "outbox_events._id": 1 },
+                Some(IndexOptions::builder().sparse(true).build()),
+            ),
+        ),
+        (
+            &state.attachments,
+            index(
+                doc! { "workspace_id": 1, "message_id": 1, "created_at": 1 },
+                None,
+            ),
+        ),
+        (
+            &state.audit_entries,
+            index(
+                doc! { "workspace_id": 1, "created_at": -1, "_id": -1 },
+                None,
+            ),
+        ),
+        (
+            &state.refresh_sessions,
+            index(doc! { "user_id": 1, "expires_at": 1 }, None),
+        ),
+        (
+            &state.refresh_sessions,
+            index(doc! { "expires_at_dt": 1 }, Some(ttl(0))),
+        ),
+        (&state.known_devices, index(doc! { "user_id": 1 }, None)),
+        (
+            &state.known_devices,
+            index(
+                doc! { "last_seen_at_dt": 1 },
+                Some(ttl(KNOWN_DEVICE_TTL_SECS)),
+            ),
+        ),
+        (
+            &state.email_tokens,
+            index(doc! { "user_id": 1, "purpose": 1 }, None),
+        ),
+        (
+            &state.email_tokens,
+            index(doc! { "expires_at_dt": 1 }, Some(ttl(0))),
+        ),
+        (&state.user_mutes, index(doc! { "workspace_id": 1 }, None)),
+        (
+            &state.user_mutes,
+            index(doc! { "expires_at_dt": 1 }, Some(ttl(0))),
+        ),
+        (
+            &state.moderation_cases,
+            index(
+                doc! { "workspace_id": 1, "status": 1, "created_at": -1 },
+                None,
+            ),
+        ),
+        (
+            &state.moderation_cases,
+            index(doc! { "message_id": 1 }, None),
+        ),
+        (
+            &state.channel_access_requests,
+            index(doc! { "channel_id": 1, "status": 1 }, None),
+        ),
+        (
+            &state.reactions,
+            index(
+                doc! { "message_id": 1, "emoji": 1, "user_id": 1 },
+                Some(unique()),
+            ),
+        ),
+        (
+            &state.ws_command_dedup,
+            index(
+                doc! {
                     "workspace_id": 1,
                     "user_id": 1,
                     "channel_id": 1,
                     "client_msg_id": 1
-                })
-                .options(IndexOptions::builder().unique(true).build())
-                .build(),
-        )
-        .await?;
-
-    state
-        .ws_command_once
-        .create_index(
-            IndexModel::builder()
-                .keys(doc! { "created_at_dt": 1 })
-                .options(
-                    IndexOptions::builder()
-                        .expire_after(Some(std::time::Duration::from_secs(7 * 24 * 60 * 60)))
-                        .build(),
+                },
+                Some(unique()),
+            ),
+        ),
+        (
+            &state.ws_command_once,
+            index(doc! { "created_at_dt": 1 }, Some(ttl(7 * 24 * 60 * 60))),
+        ),
+        (
+            &state.idempotency_keys,
+            index(
+                doc! { "created_at_dt": 1 },
+                Some(ttl(IDEMPOTENCY_KEY_TTL_SECS)),
+            ),
+        ),
+        (
+            &state.notifications,
+            index(
+                doc! { "workspace_id": 1, "user_id": 1, "created_at": -1 },
+                None,
+            ),
+        ),
+        (
+            &state.notifications,
+            index(doc! { "read_at": 1, "created_at": 1 }, None),
+        ),
+        (
+            &state.keyword_subscriptions,
+            index(doc! { "workspace_id": 1, "user_id": 1 }, None),
+        ),
+        (
+            &state.announcements,
+            index(doc! { "workspace_id": 1, "created_at": -1 }, None),
+        ),
+        (
+            &state.channel_links,
+            index(
+                doc! { "channel_id": 1, "guest_workspace_id": 1 },
+                Some(unique()),
+            ),
+        ),
+        (
+            &state.channel_links,
+            index(doc! { "guest_workspace_id": 1 }, None),
+        ),
+        (
+            &state.channel_links,
+            index(doc! { "host_workspace_id": 1 }, None),
+        ),
+        (
+            &state.legal_holds,
+            index(doc! { "workspace_id": 1, "created_at": -1 }, None),
+        ),
+        (
+            &state.read_markers,
+            index(doc! { "workspace_id": 1, "user_id": 1 }, None),
+        ),
+        (
+            &state.daily_activity,
+            index(doc! { "workspace_id": 1, "day": 1 }, None),
+        ),
+        (
+            &state.api_usage,
+            index(doc! { "workspace_id": 1, "day": 1 }, None),
+        ),
+        (
+            &state.reminders,
+            index(doc! { "workspace_id": 1, "user_id": 1 }, None),
+        ),
+        (&state.reminders, index(doc! { "remind_at": 1 }, None)),
+        (
+            &state.dm_conversations,
+            index(doc! { "workspace_id": 1, "member_key": 1 }, Some(unique())),
+        ),
+        (
+            &state.dm_conversations,
+            index(doc! { "workspace_id": 1, "member_ids": 1 }, None),
+        ),
+        (
+            &state.dm_messages,
+            index(
+                doc! { "conversation_id": 1, "created_at": -1, "_id": -1 },
+                None,
+            ),
+        ),
+        (
+            &state.recurring_posts,
+            index(doc! { "workspace_id": 1 }, None),
+        ),
+        (
+            &state.recurring_posts,
+            index(doc! { "paused": 1, "next_run_at": 1 }, None),
+        ),
+        (
+            &state.user_groups,
+            index(doc! { "workspace_id": 1, "handle": 1 }, Some(unique())),
+        ),
+        (
+            &state.calls,
+            index(doc! { "channel_id": 1, "ended_at": 1 }, None),
+        ),
+        (
+            &state.channel_keys,
+            index(doc! { "channel_id": 1, "user_id": 1 }, None),
+        ),
+        (
+            &state.event_subscriptions,
+            index(doc! { "workspace_id": 1, "user_id": 1 }, None),
+        ),
+        (
+            &state.app_installations,
+            index(doc! { "workspace_id": 1 }, None),
+        ),
+        (
+            &state.realtime_outbox,
+            index(doc! { "delivered_at": 1, "created_at": 1 }, None),
+        ),
+    ]
+}
+
+/// Default name Mongo assigns to an index, e.g. `workspace_id_1_created_at_-1`.
+fn mongo_index_name(keys: &Document) -> String {
+    keys.iter()
+        .map(|(key, value)| match value {
+            Bson::Int32(direction) => format!("{key}_{direction}"),
+            Bson::Int64(direction) => format!("{key}_{direction}"),
+            Bson::String(kind) => format!("{key}_{kind}"),
+            other => format!("{key}_{other}"),
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+async fn ensure_mongo_indexes(state: &MongoState) -> Result<(), mongodb::error::Error> {
+    for (collection, model) in mongo_index_models(state) {
+        collection.create_index(model).await?;
+    }
+    Ok(())
+}
+
+async fn apply_migration(state: &MongoState, id: &str) -> Result<u64, mongodb::error::Error> {
+    let result = match id {
+        "0001_workspaces_name_lc" => {
+            state
+                .workspaces
+                .update_many(
+                    doc! { "name_lc": { "$exists": false } },
+                    vec![doc! { "$set": { "name_lc": { "$toLower": "$name" } } }],
+                )
+                .await?
+        }
+        "0002_refresh_sessions_expires_at_dt" => {
+            state
+                .refresh_sessions
+                .update_many(
+                    doc! { "expires_at_dt": { "$exists": false } },
+                    vec![doc! { "$set": {
+                        "expires_at_dt": { "$toDate": { "$multiply": ["$expires_at", 1000] } }
+                    } }],
                 )
-                .build(),
+                .await?
+        }
+        "0003_messages_seq" => {
+            let pending = state
+                .messages
+                .count_documents(doc! { "seq": { "$exists": false } })
+                .await?;
+            state
+                .messages
+                .aggregate(vec![
+                    doc! { "$setWindowFields": {
+                        "partitionBy": "$channel_id",
+                        "sortBy": { "created_at": 1, "_id": 1 },
+                        "output": { "seq": { "$documentNumber": {} } },
+                    } },
+                    doc! { "$project": { "channel_id": 1, "seq": { "$toLong": "$seq" } } },
+                    doc! { "$merge": {
+                        "into": "messages",
+                        "on": "_id",
+                        "whenMatched": [{ "$set": { "seq": "$$new.seq" } }],
+                        "whenNotMatched": "discard",
+                    } },
+                ])
+                .await?;
+            state
+                .messages
+                .aggregate(vec![
+                    doc! { "$group": { "_id": "$channel_id", "seq": { "$max": "$seq" } } },
+                    doc! { "$merge": {
+                        "into": "channel_sequences",
+                        "on": "_id",
+                        "whenMatched": "replace",
+                        "whenNotMatched": "insert",
+                    } },
+                ])
+                .await?;
+            return Ok(pending);
+        }
+        _ => return Ok(0),
+    };
+    Ok(result.modified_count)
+}
+
+fn message_fields(message: &MessageRecordStore, body_md: String) -> Document {
+    doc! {
+        "workspace_id": message.workspace_id.to_string(),
+        "channel_id": message.channel_id.to_string(),
+        "sender_id": message.sender_id.to_string(),
+        "body_md": body_md,
+        "thread_root_id": message.thread_root_id.map(|value| value.to_string()),
+        "origin_workspace_id": message.origin_workspace_id.map(|value| value.to_string()),
+        "seq": message.seq,
+        "created_at": message.created_at,
+        "edited_at": message.edited_at,
+        "deleted_at": message.deleted_at,
+        "crosspost_group_id": message.crosspost_group_id.map(|value| value.to_string()),
+    }
+}
+
+/// Copies an event embedded in document `owner_id` of `collection` into
+/// `realtime_outbox` and drops it from the owner. Inserting twice is harmless;
+/// on failure the event stays embedded for the dispatcher's next recovery pass.
+async fn move_embedded_outbox_event(
+    mongo: &MongoState,
+    collection: &Collection<Document>,
+    owner_id: &str,
+    event: Document,
+) {
+    let Ok(event_id) = event.get_str("_id").map(ToString::to_string) else {
+        return;
+    };
+    match mongo.realtime_outbox.insert_one(event).await {
+        Ok(_) => {}
+        Err(error) if is_duplicate_key_error(&error) => {}
+        Err(error) => {
+            tracing::warn!(%event_id, %error, "failed to move embedded outbox event");
+            return;
+        }
+    }
+    let _ = collection
+        .update_one(
+            doc! { "_id": owner_id },
+            doc! { "$pull": { "outbox_events": { "_id": event_id } } },
         )
-        .await?;
+        .await;
+}
 
-    Ok(())
+fn is_duplicate_key_error(error: &mongodb::error::Error) -> bool {
+    matches!(
+        error.kind.as_ref(),
+        mongodb::error::ErrorKind::Write(mongodb::error::WriteFailure::WriteError(write))
+            if write.code == 11000
+    )
 }
 
 fn uuid_field(document: &Document, key: &str) -> Option<Uuid> {
@@ -1439,9 +7709,408 @@ fn bool_field(document: &Document, key: &str) -> Option<bool> {
     document.get_bool(key).ok()
 }
 
+fn workspace_from_document(document: &Document) -> Option<WorkspaceRecordStore> {
+    Some(WorkspaceRecordStore {
+        id: uuid_field(document, "_id")?,
+        name: string_field(document, "name").unwrap_or_default(),
+        created_by: uuid_field(document, "created_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        suspended_at: optional_i64_field(document, "suspended_at"),
+        suspended_reason: string_field(document, "suspended_reason"),
+    })
+}
+
+fn attachment_from_document(document: &Document) -> Option<AttachmentRecordStore> {
+    Some(AttachmentRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        message_id: optional_uuid_field(document, "message_id"),
+        uploader_id: uuid_field(document, "uploader_id")?,
+        filename: string_field(document, "filename").unwrap_or_default(),
+        content_type: string_field(document, "content_type").unwrap_or_default(),
+        size_bytes: i64_field(document, "size_bytes").unwrap_or_default() as u64,
+        bucket: string_field(document, "bucket").unwrap_or_default(),
+        key: string_field(document, "key").unwrap_or_default(),
+        thumbnail_key: string_field(document, "thumbnail_key"),
+        region: string_field(document, "region").unwrap_or_default(),
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+    })
+}
+
+fn legal_hold_from_document(document: &Document) -> Option<LegalHoldRecordStore> {
+    Some(LegalHoldRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        scope: string_field(document, "scope")?,
+        target_id: uuid_field(document, "target_id")?,
+        reason: string_field(document, "reason"),
+        created_by: uuid_field(document, "created_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+    })
+}
+
+fn moderation_case_from_document(document: &Document) -> Option<ModerationCaseRecordStore> {
+    Some(ModerationCaseRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        message_id: uuid_field(document, "message_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        author_id: uuid_field(document, "author_id")?,
+        source: string_field(document, "source")?,
+        category: string_field(document, "category")?,
+        details: string_field(document, "details"),
+        reporter_id: uuid_field(document, "reporter_id"),
+        status: string_field(document, "status")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        resolution: string_field(document, "resolution"),
+        resolved_by: uuid_field(document, "resolved_by"),
+        resolved_at: optional_i64_field(document, "resolved_at"),
+    })
+}
+
+fn channel_access_request_from_document(
+    document: &Document,
+) -> Option<ChannelAccessRequestRecordStore> {
+    Some(ChannelAccessRequestRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        user_id: uuid_field(document, "user_id")?,
+        note: string_field(document, "note"),
+        status: string_field(document, "status")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        decided_by: uuid_field(document, "decided_by"),
+        decided_at: optional_i64_field(document, "decided_at"),
+    })
+}
+
+fn dm_conversation_from_document(document: &Document) -> Option<DmConversationRecordStore> {
+    let member_ids = document
+        .get_array("member_ids")
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|member| Uuid::parse_str(member.as_str()?).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(DmConversationRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        member_ids,
+        created_by: uuid_field(document, "created_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        last_message_at: optional_i64_field(document, "last_message_at"),
+    })
+}
+
+fn announcement_from_document(document: &Document) -> Option<AnnouncementRecordStore> {
+    Some(AnnouncementRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        author_id: uuid_field(document, "author_id")?,
+        title: string_field(document, "title")?,
+        body_md: string_field(document, "body_md").unwrap_or_default(),
+        severity: string_field(document, "severity").unwrap_or_else(|| "info".to_string()),
+        pinned: bool_field(document, "pinned").unwrap_or(false),
+        expires_at: optional_i64_field(document, "expires_at"),
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+    })
+}
+
+fn recurring_post_from_document(document: &Document) -> Option<RecurringPostRecordStore> {
+    Some(RecurringPostRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        body_md: string_field(document, "body_md").unwrap_or_default(),
+        schedule: string_field(document, "schedule")?,
+        created_by: uuid_field(document, "created_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        paused: bool_field(document, "paused").unwrap_or_default(),
+        next_run_at: i64_field(document, "next_run_at").unwrap_or_default(),
+        last_run_at: optional_i64_field(document, "last_run_at"),
+        last_message_id: optional_uuid_field(document, "last_message_id"),
+        last_error: string_field(document, "last_error"),
+    })
+}
+
+/// Sessions stored before `session_id` existed read back with the nil id.
+fn refresh_session_from_document(document: &Document) -> Option<RefreshSessionRecordStore> {
+    Some(RefreshSessionRecordStore {
+        session_id: uuid_field(document, "session_id").unwrap_or_default(),
+        user_id: uuid_field(document, "user_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        expires_at: i64_field(document, "expires_at").unwrap_or_default(),
+        revoked_at: optional_i64_field(document, "revoked_at"),
+        replaced_by_hash: string_field(document, "replaced_by_hash"),
+        ip: string_field(document, "ip"),
+        user_agent: string_field(document, "user_agent"),
+        new_device: bool_field(document, "new_device").unwrap_or_default(),
+    })
+}
+
+fn known_device_from_document(document: &Document) -> Option<KnownDeviceRecordStore> {
+    Some(KnownDeviceRecordStore {
+        user_id: uuid_field(document, "user_id")?,
+        fingerprint: string_field(document, "fingerprint")?,
+        ip: string_field(document, "ip").unwrap_or_default(),
+        user_agent: string_field(document, "user_agent").unwrap_or_default(),
+        first_seen_at: i64_field(document, "first_seen_at").unwrap_or_default(),
+        last_seen_at: i64_field(document, "last_seen_at").unwrap_or_default(),
+    })
+}
+
+fn user_mute_from_document(document: &Document) -> Option<UserMuteRecordStore> {
+    Some(UserMuteRecordStore {
+        workspace_id: uuid_field(document, "workspace_id")?,
+        user_id: uuid_field(document, "user_id")?,
+        reason: string_field(document, "reason"),
+        muted_by: uuid_field(document, "muted_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        expires_at: i64_field(document, "expires_at")?,
+    })
+}
+
+fn daily_activity_from_document(document: &Document) -> Option<DailyActivityRecordStore> {
+    let channel_messages = document
+        .get_document("channels")
+        .map(|channels| {
+            channels
+                .iter()
+                .filter_map(|(channel_id, count)| {
+                    Some((Uuid::parse_str(channel_id).ok()?, count.as_i64()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let active_users = document
+        .get_array("active_users")
+        .map(|users| {
+            users
+                .iter()
+                .filter_map(|user| Uuid::parse_str(user.as_str()?).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(DailyActivityRecordStore {
+        workspace_id: uuid_field(document, "workspace_id")?,
+        day: string_field(document, "day")?,
+        messages: i64_field(document, "messages").unwrap_or_default(),
+        active_users,
+        channel_messages,
+        peak_ws_connections: i64_field(document, "peak_ws_connections").unwrap_or_default(),
+    })
+}
+
+fn api_usage_from_document(document: &Document) -> Option<ApiUsageRecordStore> {
+    Some(ApiUsageRecordStore {
+        workspace_id: uuid_field(document, "workspace_id")?,
+        user_id: uuid_field(document, "user_id")?,
+        day: string_field(document, "day")?,
+        requests: i64_field(document, "requests").unwrap_or_default(),
+        rate_limited: i64_field(document, "rate_limited").unwrap_or_default(),
+        request_bytes: i64_field(document, "request_bytes").unwrap_or_default(),
+        response_bytes: i64_field(document, "response_bytes").unwrap_or_default(),
+    })
+}
+
+fn user_group_from_document(document: &Document) -> Option<UserGroupRecordStore> {
+    let member_ids = document
+        .get_array("member_ids")
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|member| Uuid::parse_str(member.as_str()?).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(UserGroupRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        handle: string_field(document, "handle")?,
+        name: string_field(document, "name").unwrap_or_default(),
+        member_ids,
+        created_by: uuid_field(document, "created_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        updated_at: i64_field(document, "updated_at").unwrap_or_default(),
+    })
+}
+
+fn call_from_document(document: &Document) -> Option<CallRecordStore> {
+    let participant_ids = document
+        .get_array("participant_ids")
+        .map(|participants| {
+            participants
+                .iter()
+                .filter_map(|participant| Uuid::parse_str(participant.as_str()?).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(CallRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        started_by: uuid_field(document, "started_by")?,
+        join_url: string_field(document, "join_url"),
+        participant_ids,
+        started_at: i64_field(document, "started_at").unwrap_or_default(),
+        ended_at: optional_i64_field(document, "ended_at"),
+    })
+}
+
+fn channel_key_from_document(document: &Document) -> Option<ChannelKeyRecordStore> {
+    Some(ChannelKeyRecordStore {
+        channel_id: uuid_field(document, "channel_id")?,
+        key_epoch: i64_field(document, "key_epoch")?,
+        user_id: uuid_field(document, "user_id")?,
+        wrapped_key: string_field(document, "wrapped_key")?,
+        wrapped_by: uuid_field(document, "wrapped_by")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+    })
+}
+
+fn email_token_from_document(document: &Document) -> Option<EmailTokenRecordStore> {
+    Some(EmailTokenRecordStore {
+        purpose: string_field(document, "purpose")?,
+        user_id: uuid_field(document, "user_id")?,
+        email: string_field(document, "email").unwrap_or_default(),
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+        expires_at: i64_field(document, "expires_at").unwrap_or_default(),
+    })
+}
+
+fn app_installation_from_document(document: &Document) -> Option<AppInstallationRecordStore> {
+    let slash_commands = document
+        .get_array("slash_commands")
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_document())
+                .filter_map(|command| {
+                    Some(SlashCommandRecordStore {
+                        command: string_field(command, "command")?,
+                        description: string_field(command, "description").unwrap_or_default(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let scopes = document
+        .get_array("scopes")
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(AppInstallationRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        name: string_field(document, "name")?,
+        description: string_field(document, "description"),
+        scopes,
+        webhook_url: string_field(document, "webhook_url"),
+        event_subscription_id: uuid_field(document, "event_subscription_id"),
+        slash_commands,
+        bot_user_id: uuid_field(document, "bot_user_id")?,
+        installed_by: uuid_field(document, "installed_by")?,
+        installed_at: i64_field(document, "installed_at").unwrap_or_default(),
+    })
+}
+
+fn event_subscription_from_document(document: &Document) -> Option<EventSubscriptionRecordStore> {
+    let strings = |key: &str| -> Vec<String> {
+        document
+            .get_array(key)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Some(EventSubscriptionRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        user_id: uuid_field(document, "user_id")?,
+        event_types: strings("event_types"),
+        channel_ids: strings("channel_ids")
+            .iter()
+            .filter_map(|value| Uuid::parse_str(value).ok())
+            .collect(),
+        delivery: string_field(document, "delivery")?,
+        webhook_url: string_field(document, "webhook_url"),
+        webhook_secret: string_field(document, "webhook_secret"),
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+    })
+}
+
+fn ip_allowlist_from_document(document: &Document) -> Option<IpAllowlistRecordStore> {
+    let cidrs = document
+        .get_array("cidrs")
+        .map(|cidrs| {
+            cidrs
+                .iter()
+                .filter_map(|cidr| cidr.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(IpAllowlistRecordStore {
+        workspace_id: uuid_field(document, "_id")?,
+        cidrs,
+        updated_by: uuid_field(document, "updated_by")?,
+        updated_at: i64_field(document, "updated_at").unwrap_or_default(),
+    })
+}
+
+fn message_limits_from_document(document: &Document) -> Option<MessageLimitsRecordStore> {
+    Some(MessageLimitsRecordStore {
+        workspace_id: uuid_field(document, "_id")?,
+        max_message_chars: i64_field(document, "max_message_chars")?,
+        max_attachments_per_message: i64_field(document, "max_attachments_per_message")?,
+        max_thread_depth: i64_field(document, "max_thread_depth")?,
+        updated_by: uuid_field(document, "updated_by")?,
+        updated_at: i64_field(document, "updated_at").unwrap_or_default(),
+    })
+}
+
+fn reminder_from_document(document: &Document) -> Option<ReminderRecordStore> {
+    Some(ReminderRecordStore {
+        id: uuid_field(document, "_id")?,
+        workspace_id: uuid_field(document, "workspace_id")?,
+        user_id: uuid_field(document, "user_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        message_id: uuid_field(document, "message_id")?,
+        remind_at: i64_field(document, "remind_at")?,
+        created_at: i64_field(document, "created_at").unwrap_or_default(),
+    })
+}
+
+fn read_marker_from_document(document: &Document) -> Option<ReadMarkerRecordStore> {
+    Some(ReadMarkerRecordStore {
+        workspace_id: uuid_field(document, "workspace_id")?,
+        user_id: uuid_field(document, "user_id")?,
+        channel_id: uuid_field(document, "channel_id")?,
+        last_read_seq: i64_field(document, "last_read_seq").unwrap_or_default(),
+        updated_at: i64_field(document, "updated_at").unwrap_or_default(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{PersistenceBackend, Storage};
+    use super::{
+        AnnouncementRecordStore, KnownDeviceRecordStore, PersistenceBackend,
+        RecurringPostRecordStore, RefreshSessionRecordStore, SCHEMA_MIGRATIONS, Storage,
+        mongo_index_name,
+    };
+    use crate::encryption::{KEY_LEN, MessageEncryption};
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use mongodb::bson::doc;
     use uuid::Uuid;
 
     #[tokio::test]
@@ -1482,4 +8151,192 @@ mod tests {
         storage.put_ws_command_once(key, 1_700_000_000_000).await;
         assert!(storage.has_ws_command_once(key).await);
     }
+
+    #[tokio::test]
+    async fn migrate_is_noop_on_memory_backend() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let report = storage.migrate().await.expect("memory migrate");
+        assert!(!report.indexes_ensured);
+        assert!(report.applied.is_empty());
+
+        let ids = SCHEMA_MIGRATIONS
+            .iter()
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(ids, sorted);
+    }
+
+    #[tokio::test]
+    async fn recurring_post_and_announcement_bodies_are_sealed_at_rest() {
+        let encryption =
+            MessageEncryption::from_base64(&STANDARD.encode([7_u8; KEY_LEN])).expect("valid key");
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init")
+            .with_message_encryption(encryption);
+        let post = RecurringPostRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            channel_id: Uuid::new_v4(),
+            body_md: "standup in 5".to_string(),
+            schedule: "0 9 * * 1-5".to_string(),
+            created_by: Uuid::new_v4(),
+            created_at: 0,
+            paused: false,
+            next_run_at: 0,
+            last_run_at: None,
+            last_message_id: None,
+            last_error: None,
+        };
+
+        let mut document = storage
+            .recurring_post_document(&post)
+            .await
+            .expect("body sealed");
+        let stored = document.get_str("body_md").expect("body stored");
+        assert!(stored.starts_with("enc:v1:"));
+        document.insert("_id", post.id.to_string());
+        let opened = storage
+            .open_recurring_post(&document)
+            .await
+            .expect("post read back");
+        assert_eq!(opened.body_md, "standup in 5");
+
+        let announcement = AnnouncementRecordStore {
+            id: Uuid::new_v4(),
+            workspace_id: post.workspace_id,
+            author_id: post.created_by,
+            title: "Maintenance".to_string(),
+            body_md: "down at 22:00".to_string(),
+            severity: "info".to_string(),
+            pinned: false,
+            expires_at: None,
+            created_at: 0,
+        };
+        let document = storage
+            .announcement_document(&announcement)
+            .await
+            .expect("body sealed");
+        let stored = document.get_str("body_md").expect("body stored");
+        assert!(stored.starts_with("enc:v1:"));
+        let opened = storage
+            .open_announcement(&document)
+            .await
+            .expect("announcement read back");
+        assert_eq!(opened.body_md, "down at 22:00");
+    }
+
+    #[test]
+    fn mongo_index_name_matches_server_default() {
+        assert_eq!(mongo_index_name(&doc! { "email": 1 }), "email_1");
+        assert_eq!(
+            mongo_index_name(&doc! { "channel_id": 1, "created_at": -1, "_id": -1 }),
+            "channel_id_1_created_at_-1__id_-1"
+        );
+        assert_eq!(
+            mongo_index_name(&doc! { "body_md": "text" }),
+            "body_md_text"
+        );
+    }
+
+    #[tokio::test]
+    async fn revoke_refresh_sessions_scopes_by_user_and_workspace() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let user_id = Uuid::new_v4();
+        let workspace_a = Uuid::new_v4();
+        let workspace_b = Uuid::new_v4();
+        for (hash, user_id, workspace_id) in [
+            ("a", user_id, workspace_a),
+            ("b", user_id, workspace_b),
+            ("c", Uuid::new_v4(), workspace_a),
+        ] {
+            storage
+                .put_refresh_session(
+                    hash.to_string(),
+                    RefreshSessionRecordStore {
+                        session_id: Uuid::new_v4(),
+                        user_id,
+                        workspace_id,
+                        created_at: 1,
+                        expires_at: 2_000_000_000,
+                        revoked_at: None,
+                        replaced_by_hash: None,
+                        ip: None,
+                        user_agent: None,
+                        new_device: false,
+                    },
+                )
+                .await;
+        }
+
+        assert_eq!(
+            storage
+                .revoke_refresh_sessions(user_id, Some(workspace_a), 1)
+                .await,
+            1
+        );
+        assert_eq!(storage.revoke_refresh_sessions(user_id, None, 2).await, 1);
+        let other = storage.get_refresh_session("c").await.expect("session c");
+        assert_eq!(other.revoked_at, None);
+    }
+
+    #[tokio::test]
+    async fn memory_stats_count_entries_and_expired_rows() {
+        let storage = Storage::new(PersistenceBackend::Memory, None)
+            .await
+            .expect("memory storage should init");
+        let now = chrono::Utc::now().timestamp();
+        let user_id = Uuid::new_v4();
+        for (hash, expires_at) in [("live", now + 60), ("expired", now - 60)] {
+            storage
+                .put_refresh_session(
+                    hash.to_string(),
+                    RefreshSessionRecordStore {
+                        session_id: Uuid::new_v4(),
+                        user_id,
+                        workspace_id: Uuid::new_v4(),
+                        created_at: now - 120,
+                        expires_at,
+                        revoked_at: None,
+                        replaced_by_hash: None,
+                        ip: None,
+                        user_agent: None,
+                        new_device: false,
+                    },
+                )
+                .await;
+        }
+        storage
+            .put_known_device(KnownDeviceRecordStore {
+                user_id,
+                fingerprint: "abc".to_string(),
+                ip: "10.0.0.1".to_string(),
+                user_agent: "curl".to_string(),
+                first_seen_at: now,
+                last_seen_at: now,
+            })
+            .await;
+
+        let stats = storage.collection_stats().await.expect("stats");
+        assert!(stats.windows(2).all(|pair| pair[0].name < pair[1].name));
+        let collection = |name: &str| {
+            stats
+                .iter()
+                .find(|stats| stats.name == name)
+                .unwrap_or_else(|| panic!("{name} stats"))
+        };
+        let sessions = collection("refresh_sessions");
+        assert_eq!((sessions.documents, sessions.pending_purge), (2, Some(1)));
+        assert_eq!(sessions.size_bytes, None);
+        let devices = collection("known_devices");
+        assert_eq!((devices.documents, devices.pending_purge), (1, Some(0)));
+        assert_eq!(collection("messages").pending_purge, None);
+    }
 }
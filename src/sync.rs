@@ -0,0 +1,41 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    routing::get,
+};
+
+use crate::{
+    app::AppState,
+    channels::{SyncQuery, SyncResponse},
+    errors::{ApiResult, ErrorResponse},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/v1/sync", get(sync))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync",
+    operation_id = "sync",
+    tag = "sync",
+    params(SyncQuery),
+    responses(
+        (status = 200, description = "Changes in the caller's channels since the cursor", body = SyncResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn sync(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SyncQuery>,
+) -> ApiResult<Json<SyncResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let changes = state.channels.sync(&context, &query).await?;
+    Ok(Json(changes))
+}
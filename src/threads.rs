@@ -2,19 +2,22 @@ use axum::{
     Json, Router,
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
+    response::Response,
     routing::get,
 };
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
+    analytics,
     app::AppState,
+    auth::AuthContext,
     channels::{
         CreateMessageRequest, MessageListResponse, MessageQuery, MessageResponse,
         ThreadSummaryResponse,
     },
     errors::{ApiResult, ErrorResponse},
-    realtime,
+    idempotency, moderation, notifications, realtime,
 };
 
 pub fn router() -> Router<AppState> {
@@ -29,9 +32,12 @@ pub fn router() -> Router<AppState> {
 #[utoipa::path(
     get,
     path = "/api/v1/threads/{root_id}",
+    operation_id = "get_thread",
+    tag = "threads",
     responses(
         (status = 200, description = "Thread summary", body = ThreadSummaryResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Thread not found", body = ErrorResponse)
     )
 )]
@@ -51,10 +57,13 @@ pub(crate) async fn get_thread(
 #[utoipa::path(
     get,
     path = "/api/v1/threads/{root_id}/replies",
+    operation_id = "list_replies",
+    tag = "threads",
     params(MessageQuery),
     responses(
         (status = 200, description = "Thread replies", body = MessageListResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Thread not found", body = ErrorResponse)
     )
 )]
@@ -78,11 +87,16 @@ pub(crate) async fn list_replies(
 #[utoipa::path(
     post,
     path = "/api/v1/threads/{root_id}/replies",
+    operation_id = "create_reply",
+    tag = "threads",
     request_body = CreateMessageRequest,
     responses(
         (status = 201, description = "Thread reply created", body = MessageResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 404, description = "Thread not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Thread not found", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key in use", body = ErrorResponse),
+        (status = 422, description = "Invalid fields or Idempotency-Key reused", body = ErrorResponse)
     )
 )]
 pub(crate) async fn create_reply(
@@ -90,15 +104,51 @@ pub(crate) async fn create_reply(
     headers: HeaderMap,
     Path(root_id): Path<Uuid>,
     Json(payload): Json<CreateMessageRequest>,
-) -> ApiResult<(StatusCode, Json<MessageResponse>)> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
+    let mut idempotency = idempotency::scope(
+        &headers,
+        &context,
+        &format!("create_reply:{root_id}"),
+        &payload,
+    )?;
+    if let Some(response) = idempotency.begin(&state.storage).await? {
+        return Ok(response);
+    }
+    let reply = post_reply(&state, &context, root_id, payload.body_md).await?;
+    Ok(idempotency
+        .respond(&state.storage, StatusCode::CREATED, &reply)
+        .await)
+}
+
+/// Shared by the REST handler and the gRPC service.
+pub(crate) async fn post_reply(
+    state: &AppState,
+    context: &AuthContext,
+    root_id: Uuid,
+    body_md: String,
+) -> ApiResult<MessageResponse> {
+    state
+        .rate_limit
+        .check_message_write(context.user_id)
+        .await?;
+    let moderated = moderation::moderate_thread_reply(state, context, root_id, body_md).await?;
     let reply = state
         .channels
-        .create_thread_reply(&context, root_id, payload)
+        .create_thread_reply(
+            context,
+            root_id,
+            CreateMessageRequest {
+                body_md: moderated.body.clone(),
+            },
+        )
         .await?;
+    moderated.open_case(state, &reply).await;
+    state.metrics.record_message_created(context.workspace_id);
+    analytics::record_message(state, &reply).await;
     state
         .audit
         .write(
@@ -110,19 +160,19 @@ pub(crate) async fn create_reply(
             json!({ "root_id": root_id, "channel_id": reply.channel_id }),
         )
         .await;
-    let summary = state.channels.thread_summary(&context, root_id).await?;
-    state
-        .realtime
-        .emit(
+    let summary = state.channels.thread_summary(context, root_id).await?;
+    realtime::emit_channel_event(
+        state,
+        context.workspace_id,
+        realtime::make_event(
+            "THREAD_UPDATED",
             context.workspace_id,
-            realtime::make_event(
-                "THREAD_UPDATED",
-                context.workspace_id,
-                Some(reply.channel_id),
-                None,
-                serde_json::to_value(summary).unwrap_or_default(),
-            ),
-        )
-        .await;
-    Ok((StatusCode::CREATED, Json(reply)))
+            Some(reply.channel_id),
+            None,
+            serde_json::to_value(summary).unwrap_or_default(),
+        ),
+    )
+    .await;
+    notifications::notify_message(state, &reply).await;
+    Ok(reply)
 }
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json, Router,
+    body::{Body, HttpBody},
+    extract::{Query, State},
+    http::{HeaderMap, Request, StatusCode, header},
+    middleware::Next,
+    response::Response,
+    routing::get,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    analytics::{AnalyticsQuery, DEFAULT_WINDOW_DAYS, MAX_WINDOW_DAYS},
+    app::AppState,
+    auth,
+    errors::{ApiError, ApiResult, ErrorResponse},
+    storage::ApiUsageRecordStore,
+};
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct DailyUsageResponse {
+    pub day: String,
+    pub requests: i64,
+    /// Requests answered with `429`.
+    pub rate_limited: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MyUsageResponse {
+    pub user_id: Uuid,
+    pub workspace_id: Uuid,
+    pub window_days: u32,
+    pub from_day: String,
+    pub to_day: String,
+    pub requests: i64,
+    pub rate_limited: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    /// One entry per day, oldest first, including days without calls.
+    pub days: Vec<DailyUsageResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserUsageResponse {
+    pub user_id: Uuid,
+    /// `null` when the user no longer exists.
+    pub name: Option<String>,
+    pub requests: i64,
+    pub rate_limited: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkspaceUsageResponse {
+    pub workspace_id: Uuid,
+    pub window_days: u32,
+    pub from_day: String,
+    pub to_day: String,
+    pub requests: i64,
+    pub rate_limited: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    /// Callers in the window, heaviest first.
+    pub users: Vec<UserUsageResponse>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/v1/me/usage", get(my_usage))
+}
+
+/// Meters every request that carries a valid access token against its user and
+/// workspace for the current UTC day. Anonymous calls are not counted.
+pub async fn usage_middleware(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some((user_id, workspace_id)) =
+        auth::access_token_identity(request.headers(), &state.config.jwt_secret)
+    else {
+        return next.run(request).await;
+    };
+    let request_bytes = content_length(request.headers())
+        .or_else(|| request.body().size_hint().exact())
+        .unwrap_or_default();
+    let response = next.run(request).await;
+    // Streamed bodies report no exact size and are counted as zero bytes.
+    let response_bytes = content_length(response.headers())
+        .or_else(|| response.body().size_hint().exact())
+        .unwrap_or_default();
+
+    let usage = ApiUsageRecordStore {
+        workspace_id,
+        user_id,
+        day: day_key(Utc::now().date_naive()),
+        requests: 1,
+        rate_limited: i64::from(response.status() == StatusCode::TOO_MANY_REQUESTS),
+        request_bytes: request_bytes as i64,
+        response_bytes: response_bytes as i64,
+    };
+    let storage = state.storage.clone();
+    tokio::spawn(async move { storage.record_api_usage(usage).await });
+    response
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+fn day_key(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+pub(crate) fn window_days(query: &AnalyticsQuery) -> ApiResult<u32> {
+    let days = query.days.unwrap_or(DEFAULT_WINDOW_DAYS);
+    if !(1..=MAX_WINDOW_DAYS).contains(&days) {
+        return Err(ApiError::BadRequest(format!(
+            "days must be between 1 and {MAX_WINDOW_DAYS}"
+        )));
+    }
+    Ok(days)
+}
+
+fn window(days: u32) -> (NaiveDate, NaiveDate) {
+    let today = Utc::now().date_naive();
+    (today - Duration::days(i64::from(days) - 1), today)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/usage",
+    operation_id = "my_usage",
+    tag = "auth",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Daily API usage of the caller in the current workspace", body = MyUsageResponse),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn my_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AnalyticsQuery>,
+) -> ApiResult<Json<MyUsageResponse>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    let (from, to) = window(window_days(&query)?);
+    let usage = state
+        .storage
+        .list_api_usage(context.workspace_id, Some(context.user_id), &day_key(from))
+        .await;
+    let days = daily_usage(from, to, usage);
+    Ok(Json(MyUsageResponse {
+        user_id: context.user_id,
+        workspace_id: context.workspace_id,
+        window_days: days.len() as u32,
+        from_day: day_key(from),
+        to_day: day_key(to),
+        requests: days.iter().map(|day| day.requests).sum(),
+        rate_limited: days.iter().map(|day| day.rate_limited).sum(),
+        request_bytes: days.iter().map(|day| day.request_bytes).sum(),
+        response_bytes: days.iter().map(|day| day.response_bytes).sum(),
+        days,
+    }))
+}
+
+/// Per-user rollup of the workspace over the last `days` days.
+pub(crate) async fn workspace_usage(
+    state: &AppState,
+    workspace_id: Uuid,
+    days: u32,
+) -> WorkspaceUsageResponse {
+    let (from, to) = window(days);
+    let usage = state
+        .storage
+        .list_api_usage(workspace_id, None, &day_key(from))
+        .await;
+    let mut users = rollup_by_user(usage);
+    for user in &mut users {
+        user.name = state
+            .storage
+            .get_auth_user_by_id(user.user_id)
+            .await
+            .map(|record| record.name);
+    }
+    WorkspaceUsageResponse {
+        workspace_id,
+        window_days: days,
+        from_day: day_key(from),
+        to_day: day_key(to),
+        requests: users.iter().map(|user| user.requests).sum(),
+        rate_limited: users.iter().map(|user| user.rate_limited).sum(),
+        request_bytes: users.iter().map(|user| user.request_bytes).sum(),
+        response_bytes: users.iter().map(|user| user.response_bytes).sum(),
+        users,
+    }
+}
+
+fn daily_usage(
+    from: NaiveDate,
+    to: NaiveDate,
+    usage: Vec<ApiUsageRecordStore>,
+) -> Vec<DailyUsageResponse> {
+    let mut by_day = HashMap::<String, DailyUsageResponse>::new();
+    for item in usage {
+        let entry = by_day.entry(item.day).or_default();
+        entry.requests += item.requests;
+        entry.rate_limited += item.rate_limited;
+        entry.request_bytes += item.request_bytes;
+        entry.response_bytes += item.response_bytes;
+    }
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            let key = day_key(day);
+            let item = by_day.remove(&key).unwrap_or_default();
+            DailyUsageResponse { day: key, ..item }
+        })
+        .collect()
+}
+
+fn rollup_by_user(usage: Vec<ApiUsageRecordStore>) -> Vec<UserUsageResponse> {
+    let mut by_user = HashMap::<Uuid, UserUsageResponse>::new();
+    for item in usage {
+        let entry = by_user
+            .entry(item.user_id)
+            .or_insert_with(|| UserUsageResponse {
+                user_id: item.user_id,
+                name: None,
+                requests: 0,
+                rate_limited: 0,
+                request_bytes: 0,
+                response_bytes: 0,
+            });
+        entry.requests += item.requests;
+        entry.rate_limited += item.rate_limited;
+        entry.request_bytes += item.request_bytes;
+        entry.response_bytes += item.response_bytes;
+    }
+    let mut users = by_user.into_values().collect::<Vec<_>>();
+    users.sort_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then_with(|| a.user_id.cmp(&b.user_id))
+    });
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_fills_gaps_and_ranks_heaviest_callers() {
+        let workspace_id = Uuid::new_v4();
+        let (alice, bot) = (Uuid::new_v4(), Uuid::new_v4());
+        let row = |user_id, day: &str, requests, rate_limited| ApiUsageRecordStore {
+            workspace_id,
+            user_id,
+            day: day.to_string(),
+            requests,
+            rate_limited,
+            request_bytes: requests * 10,
+            response_bytes: requests * 100,
+        };
+        let usage = vec![
+            row(alice, "2026-03-01", 4, 0),
+            row(bot, "2026-03-01", 50, 7),
+            row(bot, "2026-03-03", 25, 0),
+        ];
+
+        let days = daily_usage(
+            NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 3).unwrap(),
+            usage.clone(),
+        );
+        assert_eq!(
+            days.iter()
+                .map(|day| (day.day.as_str(), day.requests, day.rate_limited))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2026-03-01", 54, 7),
+                ("2026-03-02", 0, 0),
+                ("2026-03-03", 25, 0)
+            ]
+        );
+
+        let users = rollup_by_user(usage);
+        assert_eq!(users[0].user_id, bot);
+        assert_eq!(users[0].requests, 75);
+        assert_eq!(users[0].response_bytes, 7500);
+        assert_eq!(users[1].user_id, alice);
+        assert_eq!(users[1].request_bytes, 40);
+    }
+}
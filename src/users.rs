@@ -6,10 +6,11 @@ use argon2::{
 };
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{Path, State},
     http::{HeaderMap, StatusCode},
-    routing::get,
+    routing::{delete, get},
 };
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use utoipa::ToSchema;
@@ -18,7 +19,8 @@ use uuid::Uuid;
 use crate::{
     app::AppState,
     auth::{AuthContext, WorkspaceRole},
-    errors::{ApiError, ApiResult, ErrorResponse},
+    email,
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
     storage::{AuthUserRecordStore, Storage},
 };
 
@@ -45,7 +47,9 @@ pub struct UserResponse {
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/api/v1/users", get(list_users).post(create_user))
+    Router::new()
+        .route("/api/v1/users", get(list_users).post(create_user))
+        .route("/api/v1/users/:id", delete(deactivate_user))
 }
 
 impl UserService {
@@ -84,24 +88,26 @@ impl UserService {
         let name = payload.name.trim().to_string();
         let password = payload.password.trim().to_string();
 
-        if email.is_empty() || name.is_empty() || password.is_empty() {
-            return Err(ApiError::BadRequest(
-                "email, name and password are required".to_string(),
-            ));
+        let mut errors = FieldErrors::default();
+        if email.is_empty() {
+            errors.add("email", "must not be empty");
+        }
+        if name.is_empty() {
+            errors.add("name", "must not be empty");
         }
         if password.len() < 8 {
-            return Err(ApiError::BadRequest(
-                "password must have at least 8 characters".to_string(),
-            ));
+            errors.add("password", "must have at least 8 characters");
         }
         if matches!(payload.role, WorkspaceRole::Owner) {
-            return Err(ApiError::BadRequest(
-                "cannot create owner users via api".to_string(),
-            ));
+            errors.add("role", "cannot create owner users via api");
         }
+        errors.into_result()?;
 
         if self.storage.get_auth_user_by_email(&email).await.is_some() {
-            return Err(ApiError::BadRequest("email already exists".to_string()));
+            return Err(ApiError::Coded(
+                ErrorCode::EmailTaken,
+                "email already exists".to_string(),
+            ));
         }
 
         let user_id = Uuid::new_v4();
@@ -110,6 +116,7 @@ impl UserService {
             email: email.clone(),
             name: name.clone(),
             password_hash: hash_password(&password)?,
+            email_verified_at: None,
         };
         self.storage.put_auth_user(user).await;
         self.storage
@@ -124,6 +131,48 @@ impl UserService {
             role: payload.role,
         })
     }
+
+    pub async fn deactivate_user(
+        &self,
+        workspace_id: Uuid,
+        actor_id: Uuid,
+        user_id: Uuid,
+    ) -> ApiResult<UserResponse> {
+        if actor_id == user_id {
+            return Err(ApiError::BadRequest(
+                "you cannot deactivate your own user".to_string(),
+            ));
+        }
+        let role = self
+            .storage
+            .get_membership_role(workspace_id, user_id)
+            .await
+            .ok_or_else(|| ApiError::NotFound("user not found".to_string()))?;
+        let role = parse_role(&role)?;
+        if matches!(role, WorkspaceRole::Owner) {
+            return Err(ApiError::Forbidden(
+                "owner users cannot be deactivated".to_string(),
+            ));
+        }
+        let user = self
+            .storage
+            .get_auth_user_by_id(user_id)
+            .await
+            .ok_or_else(|| ApiError::NotFound("user not found".to_string()))?;
+
+        self.storage.delete_membership(workspace_id, user_id).await;
+        self.storage
+            .revoke_refresh_sessions(user_id, Some(workspace_id), Utc::now().timestamp())
+            .await;
+
+        Ok(UserResponse {
+            id: user.id,
+            email: user.email,
+            name: user.name,
+            workspace_id,
+            role,
+        })
+    }
 }
 
 fn hash_password(password: &str) -> ApiResult<String> {
@@ -154,7 +203,7 @@ fn parse_role(value: &str) -> ApiResult<WorkspaceRole> {
 fn ensure_user_admin(context: &AuthContext) -> ApiResult<()> {
     match context.role {
         WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
-        WorkspaceRole::Member => Err(ApiError::Unauthorized(
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
             "you do not have permission to manage users".to_string(),
         )),
     }
@@ -163,9 +212,12 @@ fn ensure_user_admin(context: &AuthContext) -> ApiResult<()> {
 #[utoipa::path(
     get,
     path = "/api/v1/users",
+    operation_id = "list_users",
+    tag = "users",
     responses(
         (status = 200, description = "List workspace users", body = [UserResponse]),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
     )
 )]
 pub(crate) async fn list_users(
@@ -184,11 +236,16 @@ pub(crate) async fn list_users(
 #[utoipa::path(
     post,
     path = "/api/v1/users",
+    operation_id = "create_user",
+    tag = "users",
     request_body = CreateUserRequest,
     responses(
         (status = 201, description = "User created", body = UserResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 400, description = "Validation error", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Email already exists", body = ErrorResponse),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
     )
 )]
 pub(crate) async fn create_user(
@@ -201,10 +258,23 @@ pub(crate) async fn create_user(
         .authenticate_headers(&headers, &state.config.jwt_secret)
         .await?;
     ensure_user_admin(&context)?;
+    state.rate_limit.check_invite(context.user_id).await?;
     let user = state
         .users
         .create_user(context.workspace_id, payload)
         .await?;
+    state
+        .channel_templates
+        .join_defaults(&state.storage, context.workspace_id, user.id)
+        .await;
+    email::send_invite(
+        &state,
+        context.user_id,
+        context.workspace_id,
+        &user.email,
+        &user.name,
+    )
+    .await;
     state
         .audit
         .write(
@@ -219,6 +289,47 @@ pub(crate) async fn create_user(
     Ok((StatusCode::CREATED, Json(user)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{id}",
+    operation_id = "deactivate_user",
+    tag = "users",
+    responses(
+        (status = 204, description = "User removed from the workspace"),
+        (status = 400, description = "Cannot deactivate yourself", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn deactivate_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_user_admin(&context)?;
+    let user = state
+        .users
+        .deactivate_user(context.workspace_id, context.user_id, user_id)
+        .await?;
+    state
+        .audit
+        .write(
+            context.workspace_id,
+            Some(context.user_id),
+            "USER_DEACTIVATED",
+            "user",
+            Some(user.id.to_string()),
+            json!({ "email": user.email, "role": user.role }),
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,4 +364,53 @@ mod tests {
             .expect("list users should succeed");
         assert!(listed.iter().any(|item| item.id == created.id));
     }
+
+    #[tokio::test]
+    async fn deactivated_user_loses_workspace_membership() {
+        let storage = Arc::new(
+            Storage::new(PersistenceBackend::Memory, None)
+                .await
+                .expect("memory storage should init"),
+        );
+        let service = UserService::new(storage.clone());
+        let workspace_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        storage
+            .put_membership_role(workspace_id, owner_id, "owner")
+            .await;
+        let created = service
+            .create_user(
+                workspace_id,
+                CreateUserRequest {
+                    email: "leaving@galynx.local".to_string(),
+                    name: "Leaving User".to_string(),
+                    password: "ChangeMe123!".to_string(),
+                    role: WorkspaceRole::Member,
+                },
+            )
+            .await
+            .expect("create user should succeed");
+
+        let self_error = service
+            .deactivate_user(workspace_id, owner_id, owner_id)
+            .await
+            .expect_err("self deactivation must fail");
+        assert!(matches!(self_error, ApiError::BadRequest(_)));
+
+        service
+            .deactivate_user(workspace_id, owner_id, created.id)
+            .await
+            .expect("deactivate should succeed");
+        assert!(
+            storage
+                .get_membership_role(workspace_id, created.id)
+                .await
+                .is_none()
+        );
+        let missing = service
+            .deactivate_user(workspace_id, owner_id, created.id)
+            .await
+            .expect_err("second deactivation must fail");
+        assert!(matches!(missing, ApiError::NotFound(_)));
+    }
 }
@@ -0,0 +1,63 @@
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Version of the public API a request was routed through. Services are shared
+/// across versions; handlers use this to pick the response shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::V1 => "/api/v1",
+            Self::V2 => "/api/v2",
+        }
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| {
+            path.strip_prefix(version.prefix())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    pub fn prefers_problem_json(self) -> bool {
+        matches!(self, Self::V2)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_path(parts.uri.path()).unwrap_or(Self::V1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_is_detected_from_path_prefix() {
+        assert_eq!(
+            ApiVersion::from_path("/api/v1/channels"),
+            Some(ApiVersion::V1)
+        );
+        assert_eq!(
+            ApiVersion::from_path("/api/v2/health"),
+            Some(ApiVersion::V2)
+        );
+        assert_eq!(ApiVersion::from_path("/api/v2"), Some(ApiVersion::V2));
+        assert_eq!(ApiVersion::from_path("/api/v20/health"), None);
+        assert_eq!(ApiVersion::from_path("/health"), None);
+    }
+}
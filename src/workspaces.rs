@@ -8,6 +8,7 @@ use axum::{
     Json, Router,
     extract::{Path, State},
     http::{HeaderMap, StatusCode},
+    response::Response,
     routing::get,
 };
 use chrono::Utc;
@@ -19,13 +20,18 @@ use uuid::Uuid;
 use crate::{
     app::AppState,
     auth::{AuthContext, WorkspaceRole},
-    errors::{ApiError, ApiResult, ErrorResponse},
+    channel_templates::ChannelTemplates,
+    channels::{MessageLimits, UpdateMessageLimitsRequest},
+    conditional, email,
+    errors::{ApiError, ApiResult, ErrorCode, ErrorResponse, FieldErrors},
+    realtime,
     storage::{AuthUserRecordStore, Storage, WorkspaceRecordStore},
 };
 
 #[derive(Clone)]
 pub struct WorkspaceService {
     storage: Arc<Storage>,
+    templates: Arc<ChannelTemplates>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -68,11 +74,15 @@ pub fn router() -> Router<AppState> {
             "/api/v1/workspaces/:id/members",
             get(list_workspace_members).post(onboard_workspace_member),
         )
+        .route(
+            "/api/v1/workspaces/:id/limits",
+            get(get_message_limits).patch(update_message_limits),
+        )
 }
 
 impl WorkspaceService {
-    pub fn new(storage: Arc<Storage>) -> Self {
-        Self { storage }
+    pub fn new(storage: Arc<Storage>, templates: Arc<ChannelTemplates>) -> Self {
+        Self { storage, templates }
     }
 
     pub async fn list_workspaces_for_user(
@@ -80,7 +90,7 @@ impl WorkspaceService {
         user_id: Uuid,
     ) -> ApiResult<Vec<WorkspaceResponse>> {
         let mut memberships = self.storage.list_user_memberships(user_id).await;
-        memberships.sort_by(|a, b| a.0.cmp(&b.0));
+        memberships.sort_by_key(|a| a.0);
         let mut items = Vec::new();
 
         for (workspace_id, role) in memberships {
@@ -105,23 +115,28 @@ impl WorkspaceService {
         payload: CreateWorkspaceRequest,
     ) -> ApiResult<WorkspaceResponse> {
         let name = payload.name.trim().to_string();
+        let mut errors = FieldErrors::default();
         if name.is_empty() {
-            return Err(ApiError::BadRequest(
-                "workspace name is required".to_string(),
-            ));
+            errors.add("name", "must not be empty");
         }
+        errors.into_result()?;
 
         let workspace = WorkspaceRecordStore {
             id: Uuid::new_v4(),
             name: name.clone(),
             created_by: owner_id,
             created_at: Utc::now().timestamp_millis(),
+            suspended_at: None,
+            suspended_reason: None,
         };
 
         self.storage.put_workspace(workspace.clone()).await;
         self.storage
             .put_membership_role(workspace.id, owner_id, "owner")
             .await;
+        self.templates
+            .instantiate(&self.storage, workspace.id, owner_id)
+            .await;
 
         Ok(WorkspaceResponse {
             id: workspace.id,
@@ -164,38 +179,38 @@ impl WorkspaceService {
         workspace_id: Uuid,
         payload: OnboardWorkspaceMemberRequest,
     ) -> ApiResult<WorkspaceMemberResponse> {
+        let mut errors = FieldErrors::default();
         if matches!(payload.role, WorkspaceRole::Owner) {
-            return Err(ApiError::BadRequest(
-                "cannot onboard owner users via api".to_string(),
-            ));
+            errors.add("role", "cannot onboard owner users via api");
         }
-
         let email = payload.email.trim().to_ascii_lowercase();
         if email.is_empty() {
-            return Err(ApiError::BadRequest("email is required".to_string()));
+            errors.add("email", "must not be empty");
         }
+        errors.into_result()?;
 
         let user = if let Some(existing) = self.storage.get_auth_user_by_email(&email).await {
             existing
         } else {
             let name = payload.name.unwrap_or_default().trim().to_string();
             let password = payload.password.unwrap_or_default().trim().to_string();
-            if name.is_empty() || password.is_empty() {
-                return Err(ApiError::BadRequest(
-                    "name and password are required for new users".to_string(),
-                ));
+            let mut errors = FieldErrors::default();
+            if name.is_empty() {
+                errors.add("name", "is required for new users");
             }
-            if password.len() < 8 {
-                return Err(ApiError::BadRequest(
-                    "password must have at least 8 characters".to_string(),
-                ));
+            if password.is_empty() {
+                errors.add("password", "is required for new users");
+            } else if password.len() < 8 {
+                errors.add("password", "must have at least 8 characters");
             }
+            errors.into_result()?;
 
             let user = AuthUserRecordStore {
                 id: Uuid::new_v4(),
                 email: email.clone(),
                 name,
                 password_hash: hash_password(&password)?,
+                email_verified_at: None,
             };
             self.storage.put_auth_user(user.clone()).await;
             user
@@ -204,6 +219,9 @@ impl WorkspaceService {
         self.storage
             .put_membership_role(workspace_id, user.id, role_to_storage(&payload.role))
             .await;
+        self.templates
+            .join_defaults(&self.storage, workspace_id, user.id)
+            .await;
 
         Ok(WorkspaceMemberResponse {
             user_id: user.id,
@@ -234,15 +252,16 @@ fn parse_role(value: &str) -> ApiResult<WorkspaceRole> {
 fn ensure_workspace_admin(context: &AuthContext) -> ApiResult<()> {
     match context.role {
         WorkspaceRole::Owner | WorkspaceRole::Admin => Ok(()),
-        WorkspaceRole::Member => Err(ApiError::Unauthorized(
+        WorkspaceRole::Member => Err(ApiError::Forbidden(
             "you do not have permission to manage workspace members".to_string(),
         )),
     }
 }
 
-fn ensure_context_workspace(context: &AuthContext, workspace_id: Uuid) -> ApiResult<()> {
+pub(crate) fn ensure_context_workspace(context: &AuthContext, workspace_id: Uuid) -> ApiResult<()> {
     if context.workspace_id != workspace_id {
-        return Err(ApiError::Unauthorized(
+        return Err(ApiError::Coded(
+            ErrorCode::WorkspaceMismatch,
             "token workspace does not match requested workspace".to_string(),
         ));
     }
@@ -260,6 +279,8 @@ fn hash_password(password: &str) -> ApiResult<String> {
 #[utoipa::path(
     get,
     path = "/api/v1/workspaces",
+    operation_id = "list_workspaces",
+    tag = "workspaces",
     responses(
         (status = 200, description = "List workspaces for current user", body = [WorkspaceResponse]),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
@@ -283,11 +304,14 @@ pub(crate) async fn list_workspaces(
 #[utoipa::path(
     post,
     path = "/api/v1/workspaces",
+    operation_id = "create_workspace",
+    tag = "workspaces",
     request_body = CreateWorkspaceRequest,
     responses(
         (status = 201, description = "Workspace created", body = WorkspaceResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 400, description = "Validation error", body = ErrorResponse)
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
     )
 )]
 pub(crate) async fn create_workspace(
@@ -319,12 +343,100 @@ pub(crate) async fn create_workspace(
     Ok((StatusCode::CREATED, Json(workspace)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/workspaces/{id}/limits",
+    operation_id = "get_message_limits",
+    tag = "workspaces",
+    responses(
+        (status = 200, description = "Message limits in effect", body = MessageLimits),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn get_message_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+) -> ApiResult<Json<MessageLimits>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    Ok(Json(state.channels.message_limits(workspace_id).await))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/workspaces/{id}/limits",
+    operation_id = "update_message_limits",
+    tag = "workspaces",
+    request_body = UpdateMessageLimitsRequest,
+    responses(
+        (status = 200, description = "Message limits updated", body = MessageLimits),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn update_message_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<UpdateMessageLimitsRequest>,
+) -> ApiResult<Json<MessageLimits>> {
+    let context = state
+        .auth
+        .authenticate_headers(&headers, &state.config.jwt_secret)
+        .await?;
+    ensure_context_workspace(&context, workspace_id)?;
+    if !matches!(context.role, WorkspaceRole::Owner | WorkspaceRole::Admin) {
+        return Err(ApiError::Forbidden(
+            "you do not have permission to change message limits".to_string(),
+        ));
+    }
+    let limits = state
+        .channels
+        .update_message_limits(&context, payload)
+        .await?;
+    state
+        .audit
+        .write(
+            workspace_id,
+            Some(context.user_id),
+            "MESSAGE_LIMITS_UPDATED",
+            "workspace",
+            Some(workspace_id.to_string()),
+            json!(limits),
+        )
+        .await;
+    state
+        .realtime
+        .emit(
+            workspace_id,
+            realtime::make_event(
+                "MESSAGE_LIMITS_UPDATED",
+                workspace_id,
+                None,
+                None,
+                json!(limits),
+            ),
+        )
+        .await;
+    Ok(Json(limits))
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/workspaces/{id}/members",
+    operation_id = "list_workspace_members",
+    tag = "workspaces",
     responses(
         (status = 200, description = "List workspace members", body = [WorkspaceMemberResponse]),
+        (status = 304, description = "Not modified (If-None-Match matched the ETag)"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "Workspace not found", body = ErrorResponse)
     )
 )]
@@ -332,7 +444,7 @@ pub(crate) async fn list_workspace_members(
     State(state): State<AppState>,
     headers: HeaderMap,
     Path(workspace_id): Path<Uuid>,
-) -> ApiResult<Json<Vec<WorkspaceMemberResponse>>> {
+) -> ApiResult<Response> {
     let context = state
         .auth
         .authenticate_headers(&headers, &state.config.jwt_secret)
@@ -345,17 +457,21 @@ pub(crate) async fn list_workspace_members(
     }
 
     let members = state.workspaces.list_members(workspace_id).await?;
-    Ok(Json(members))
+    conditional::json_with_etag(&headers, &members)
 }
 
 #[utoipa::path(
     post,
     path = "/api/v1/workspaces/{id}/members",
+    operation_id = "onboard_workspace_member",
+    tag = "workspaces",
     request_body = OnboardWorkspaceMemberRequest,
     responses(
         (status = 201, description = "Workspace member onboarded", body = WorkspaceMemberResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse),
         (status = 404, description = "Workspace not found", body = ErrorResponse)
     )
 )]
@@ -376,10 +492,19 @@ pub(crate) async fn onboard_workspace_member(
         return Err(ApiError::NotFound("workspace not found".to_string()));
     }
 
+    state.rate_limit.check_invite(context.user_id).await?;
     let user = state
         .workspaces
         .onboard_member(workspace_id, payload)
         .await?;
+    email::send_invite(
+        &state,
+        context.user_id,
+        workspace_id,
+        &user.email,
+        &user.name,
+    )
+    .await;
     state
         .audit
         .write(
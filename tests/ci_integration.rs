@@ -80,7 +80,7 @@ async fn start_server(tag: &str) -> TestServer {
 
     let child = Command::new(binary)
         .env("PORT", port.to_string())
-        .env("JWT_SECRET", "ci-secret")
+        .env("JWT_SECRET", "ci-secret-0123456789abcdef0123456789")
         .env("ACCESS_TTL_MINUTES", "15")
         .env("REFRESH_TTL_DAYS", "30")
         .env("PERSISTENCE_BACKEND", "mongo")